@@ -0,0 +1,389 @@
+pub mod instance;
+pub mod vertex;
+
+use std::sync::Arc;
+
+use wgpu::util::DeviceExt;
+use wgpu_dance::{
+    app::{FrameTime, WindowApp, WindowAppHandler},
+    camera::{Camera, CameraBundle, KeyboardController, Projection},
+    light::{DirectionalLight, DirectionalLightBundle},
+    model::{DrawModel, MeshModel, RenderVertex},
+    post::{tonemap::Tonemapping, PostStack, SceneTextures},
+    shader::ShaderLibrary,
+    texture::{DepthConvention, Texture},
+};
+
+use winit::{
+    dpi::PhysicalSize,
+    event::{ElementState, KeyEvent, MouseScrollDelta, TouchPhase},
+    event_loop::EventLoop,
+    keyboard::{KeyCode, PhysicalKey},
+    window::Window,
+};
+
+const SPACE_BETWEEN: f32 = 3.0;
+const NUM_INSTANCES_PER_ROW: u32 = 10;
+/// 场景颜色使用的 HDR 格式
+const HDR_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba16Float;
+/// 按 L 键依次切换的光照强度，跨度约 10 档 EV
+const LIGHT_LEVELS: [f32; 3] = [1.0, 30.0, 0.03];
+
+struct App {
+    device: wgpu::Device,
+    queue: wgpu::Queue,
+
+    surface: wgpu::Surface<'static>,
+    surface_config: wgpu::SurfaceConfiguration,
+
+    size: winit::dpi::PhysicalSize<u32>,
+    size_changed: bool,
+
+    render_pipeline: wgpu::RenderPipeline,
+
+    obj_model: MeshModel,
+    instances: Vec<instance::Instance>,
+    instance_buffer: wgpu::Buffer,
+
+    scene_color: Texture,
+    depth_texture: Texture,
+
+    camera: CameraBundle,
+    light: DirectionalLightBundle,
+    light_level: usize,
+    post: PostStack,
+}
+
+impl WindowApp for App {
+    async fn new(window: Arc<Window>) -> Self {
+        let instance = wgpu::Instance::new(&wgpu::InstanceDescriptor {
+            backends: wgpu::Backends::all(),
+            ..Default::default()
+        });
+        let surface = instance.create_surface(window.clone()).unwrap();
+
+        let adapter = instance
+            .request_adapter(&wgpu::RequestAdapterOptions {
+                power_preference: wgpu::PowerPreference::default(),
+                compatible_surface: Some(&surface),
+                force_fallback_adapter: false,
+            })
+            .await
+            .unwrap();
+
+        let (device, queue) = adapter
+            .request_device(
+                &wgpu::DeviceDescriptor {
+                    required_features: wgpu::Features::empty(),
+                    required_limits: wgpu::Limits::default(),
+                    label: None,
+                    memory_hints: wgpu::MemoryHints::Performance,
+                },
+                None,
+            )
+            .await
+            .unwrap();
+
+        let size = window.inner_size();
+
+        let caps = surface.get_capabilities(&adapter);
+        let surface_config = wgpu::SurfaceConfiguration {
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+            format: caps.formats[0],
+            width: size.width,
+            height: size.height,
+            present_mode: wgpu::PresentMode::Fifo,
+            alpha_mode: caps.alpha_modes[0],
+            view_formats: vec![],
+            desired_maximum_frame_latency: 2,
+        };
+        surface.configure(&device, &surface_config);
+
+        let camera = Camera {
+            eye: (0.0, 6.0, 20.0).into(),
+            target: (0.0, 4.0, 0.0).into(),
+            up: glam::Vec3::Y,
+            aspect: surface_config.width as f32 / surface_config.height as f32,
+            projection: Projection::Perspective { fovy: 60.0 },
+            znear: 0.1,
+            zfar: 100.0,
+            depth: DepthConvention::Standard,
+        };
+        let camera = CameraBundle::builder(camera)
+            .controller(KeyboardController::new(0.2))
+            .build(&device)
+            .unwrap();
+
+        let light = DirectionalLightBundle::new(DirectionalLight::default(), &device);
+
+        let mut post = PostStack::new(&device, &surface_config, HDR_FORMAT);
+        post.push(Tonemapping::new(&device, post.format()));
+
+        let scene_color =
+            Texture::create_color_target(&device, &surface_config, HDR_FORMAT, "scene_color");
+        let depth_texture =
+            Texture::create_depth_texture(&device, &surface_config, "depth_texture");
+
+        let texture_bind_group_layout = Texture::texture_bind_group_layout(&device);
+
+        let shader = ShaderLibrary::new()
+            .create_shader_module(&device, "Shader", include_str!("shader.wgsl"))
+            .unwrap();
+
+        let render_pipeline_layout =
+            device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some("Render Pipeline Layout"),
+                bind_group_layouts: &[
+                    &camera.bind_group_layout,
+                    &texture_bind_group_layout,
+                    &light.bind_group_layout,
+                ],
+                push_constant_ranges: &[],
+            });
+        let render_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Render Pipeline"),
+            layout: Some(&render_pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                compilation_options: Default::default(),
+                entry_point: Some("vs_main"),
+                buffers: &[
+                    instance::InstanceRaw::buffer_layout_desc(),
+                    vertex::Vertex::buffer_layout_desc(),
+                ],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                compilation_options: Default::default(),
+                entry_point: Some("fs_main"),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: HDR_FORMAT,
+                    blend: Some(wgpu::BlendState::REPLACE),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleList,
+                strip_index_format: None,
+                front_face: wgpu::FrontFace::Ccw,
+                cull_mode: Some(wgpu::Face::Back),
+                polygon_mode: wgpu::PolygonMode::Fill,
+                unclipped_depth: false,
+                conservative: false,
+            },
+            depth_stencil: Some(wgpu::DepthStencilState {
+                format: Texture::DEPTH_FORMAT,
+                depth_write_enabled: true,
+                depth_compare: wgpu::CompareFunction::Less,
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState::default(),
+            }),
+            multisample: wgpu::MultisampleState {
+                count: 1,
+                mask: !0,
+                alpha_to_coverage_enabled: false,
+            },
+            multiview: None,
+            cache: None,
+        });
+
+        let obj_model = MeshModel::load_model::<vertex::Vertex>(
+            "cube.obj",
+            &device,
+            &queue,
+            &texture_bind_group_layout,
+        )
+        .await
+        .unwrap();
+
+        let instances = (0..NUM_INSTANCES_PER_ROW)
+            .flat_map(|z| {
+                (0..NUM_INSTANCES_PER_ROW).map(move |x| {
+                    let x = SPACE_BETWEEN * (x as f32 - NUM_INSTANCES_PER_ROW as f32 / 2.0);
+                    let z = SPACE_BETWEEN * (z as f32 - NUM_INSTANCES_PER_ROW as f32 / 2.0);
+
+                    let position = glam::Vec3 { x, y: 0.0, z };
+                    let rotation = glam::Quat::from_rotation_y((x + z) * 0.1);
+
+                    instance::Instance { position, rotation }
+                })
+            })
+            .collect::<Vec<_>>();
+        let instance_data = instances
+            .iter()
+            .map(instance::Instance::to_raw)
+            .collect::<Vec<_>>();
+        let instance_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Instance Buffer"),
+            contents: bytemuck::cast_slice(&instance_data),
+            usage: wgpu::BufferUsages::VERTEX,
+        });
+
+        Self {
+            device,
+            queue,
+
+            surface,
+            surface_config,
+
+            size,
+            size_changed: false,
+
+            render_pipeline,
+
+            obj_model,
+            instances,
+            instance_buffer,
+
+            scene_color,
+            depth_texture,
+
+            camera,
+            light,
+            light_level: 0,
+            post,
+        }
+    }
+
+    fn render(&mut self) -> Result<(), wgpu::SurfaceError> {
+        self.resize_surface_if_needed();
+
+        let output = self.surface.get_current_texture()?;
+        let view = output
+            .texture
+            .create_view(&wgpu::TextureViewDescriptor::default());
+
+        let mut encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("Render Encoder"),
+            });
+
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Render Pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: &self.scene_color.view,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    // 背景同样随光照强度缩放，使整体亮度一起变化
+                    load: wgpu::LoadOp::Clear(wgpu::Color {
+                        r: 0.1 * self.light.light.intensity as f64,
+                        g: 0.15 * self.light.light.intensity as f64,
+                        b: 0.25 * self.light.light.intensity as f64,
+                        a: 1.0,
+                    }),
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                view: &self.depth_texture.view,
+                depth_ops: Some(wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(1.0),
+                    store: wgpu::StoreOp::Store,
+                }),
+                stencil_ops: None,
+            }),
+            ..Default::default()
+        });
+
+        render_pass.set_pipeline(&self.render_pipeline);
+        render_pass.set_vertex_buffer(0, self.instance_buffer.slice(..));
+        render_pass.set_bind_group(2, &self.light.bind_group, &[]);
+        render_pass.draw_model_instanced(
+            &self.obj_model,
+            0..self.instances.len() as u32,
+            &self.camera.bind_group,
+        );
+
+        drop(render_pass);
+
+        self.post.run(
+            &self.device,
+            &mut encoder,
+            &SceneTextures {
+                color: &self.scene_color,
+                depth: &self.depth_texture,
+                normal_roughness: None,
+                velocity: None,
+            },
+            &view,
+        );
+
+        self.queue.submit(Some(encoder.finish()));
+        output.present();
+
+        Ok(())
+    }
+
+    fn set_window_resized(&mut self, new_size: PhysicalSize<u32>) {
+        if new_size == self.size {
+            return;
+        }
+        self.size = new_size;
+        self.size_changed = true;
+    }
+
+    fn resize_surface_if_needed(&mut self) {
+        if self.size_changed {
+            self.surface_config.width = self.size.width;
+            self.surface_config.height = self.size.height;
+            self.surface.configure(&self.device, &self.surface_config);
+            self.scene_color = Texture::create_color_target(
+                &self.device,
+                &self.surface_config,
+                HDR_FORMAT,
+                "scene_color",
+            );
+            self.depth_texture =
+                Texture::create_depth_texture(&self.device, &self.surface_config, "depth_texture");
+            self.post.resize(&self.device, &self.surface_config);
+            self.size_changed = false;
+        }
+    }
+
+    fn keyboard_input(&mut self, event: &KeyEvent) -> bool {
+        if self.camera.process_key(event) {
+            return true;
+        }
+        if event.state != ElementState::Pressed || event.repeat {
+            return false;
+        }
+        // L 键切换光照强度，E 键切换自动曝光
+        match event.physical_key {
+            PhysicalKey::Code(KeyCode::KeyL) => {
+                self.light_level = (self.light_level + 1) % LIGHT_LEVELS.len();
+                true
+            }
+            PhysicalKey::Code(KeyCode::KeyE) => {
+                if let Some(tonemapping) = self.post.get_mut::<Tonemapping>() {
+                    tonemapping.auto_exposure = !tonemapping.auto_exposure;
+                    tonemapping.meter.reset();
+                }
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn mouse_wheel(&mut self, delta: MouseScrollDelta, _phase: TouchPhase) -> bool {
+        self.camera.process_wheel(delta)
+    }
+
+    fn cameras_mut(&mut self) -> Vec<&mut CameraBundle> {
+        vec![&mut self.camera]
+    }
+
+    fn update(&mut self, time: FrameTime) {
+        self.light.light.intensity = LIGHT_LEVELS[self.light_level];
+        self.light.update(&self.queue);
+
+        self.camera.update(&self.queue, time.delta_secs());
+        self.post.update(&self.queue, &self.camera.state);
+    }
+}
+
+fn main() -> Result<(), impl std::error::Error> {
+    let events_loop = EventLoop::new().unwrap();
+    let mut app = WindowAppHandler::<App>::new("auto exposure example");
+    events_loop.run_app(&mut app)
+}
@@ -0,0 +1,69 @@
+use bytemuck::{Pod, Zeroable};
+use wgpu_dance::model::RenderVertex;
+
+#[derive(Debug, Clone, Copy)]
+pub struct Instance {
+    pub position: glam::Vec3,
+    pub rotation: glam::Quat,
+    /// 自发光辐射度，由材质的自发光乘以该实例的发光系数得到
+    pub emissive: glam::Vec3,
+}
+
+impl Instance {
+    pub fn to_raw(&self) -> InstanceRaw {
+        InstanceRaw {
+            model: (glam::Mat4::from_translation(self.position)
+                * glam::Mat4::from_quat(self.rotation))
+            .to_cols_array_2d(),
+            emissive: self.emissive.extend(0.0).to_array(),
+        }
+    }
+}
+
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub struct InstanceRaw {
+    model: [[f32; 4]; 4],
+    emissive: [f32; 4],
+}
+
+unsafe impl Zeroable for InstanceRaw {}
+unsafe impl Pod for InstanceRaw {}
+
+impl RenderVertex for InstanceRaw {
+    fn buffer_layout_desc<'a>() -> wgpu::VertexBufferLayout<'a> {
+        use core::mem;
+        wgpu::VertexBufferLayout {
+            array_stride: mem::size_of::<InstanceRaw>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Instance,
+            attributes: &[
+                wgpu::VertexAttribute {
+                    offset: 0,
+                    shader_location: 0,
+                    format: wgpu::VertexFormat::Float32x4,
+                },
+                wgpu::VertexAttribute {
+                    offset: mem::size_of::<[f32; 4]>() as wgpu::BufferAddress,
+                    shader_location: 1,
+                    format: wgpu::VertexFormat::Float32x4,
+                },
+                wgpu::VertexAttribute {
+                    offset: mem::size_of::<[f32; 8]>() as wgpu::BufferAddress,
+                    shader_location: 2,
+                    format: wgpu::VertexFormat::Float32x4,
+                },
+                wgpu::VertexAttribute {
+                    offset: mem::size_of::<[f32; 12]>() as wgpu::BufferAddress,
+                    shader_location: 3,
+                    format: wgpu::VertexFormat::Float32x4,
+                },
+                // 顶点属性占用了 4 到 6，自发光放在 7
+                wgpu::VertexAttribute {
+                    offset: mem::size_of::<[f32; 16]>() as wgpu::BufferAddress,
+                    shader_location: 7,
+                    format: wgpu::VertexFormat::Float32x4,
+                },
+            ],
+        }
+    }
+}
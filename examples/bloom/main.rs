@@ -0,0 +1,536 @@
+pub mod instance;
+pub mod vertex;
+
+use std::sync::Arc;
+
+use wgpu::util::DeviceExt;
+use wgpu_dance::{
+    app::{FrameTime, WindowApp, WindowAppHandler},
+    camera::{Camera, CameraBundle, KeyboardController, Projection},
+    context::{GpuContext, GpuContextOptions},
+    light::{DirectionalLight, DirectionalLightBundle},
+    loading::Loading,
+    model::{DrawModel, MeshModel, RenderVertex},
+    overlay::OverlayPass,
+    post::{
+        bloom::Bloom,
+        compare::{CompareMode, ComparePass},
+        tonemap::Tonemapping,
+        PostStack, SceneTextures,
+    },
+    shader::ShaderLibrary,
+    sprite::{pixel_projection, Sprite, SpriteRenderer},
+    texture::{DepthConvention, Texture},
+};
+
+use winit::{
+    dpi::{PhysicalPosition, PhysicalSize},
+    event::{ElementState, KeyEvent, MouseButton, MouseScrollDelta, TouchPhase},
+    event_loop::EventLoop,
+    keyboard::{KeyCode, PhysicalKey},
+    window::Window,
+};
+
+const SPACE_BETWEEN: f32 = 3.0;
+const NUM_INSTANCES_PER_ROW: u32 = 10;
+/// 场景颜色使用的 HDR 格式
+const HDR_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba16Float;
+/// cube.mtl 的 Ke 为 0，这里直接给材质设置自发光，亮度远超 1 才能越过泛光阈值
+const EMISSIVE: glam::Vec3 = glam::vec3(4.0, 1.6, 0.5);
+
+struct App {
+    gpu: GpuContext,
+
+    render_pipeline: wgpu::RenderPipeline,
+
+    obj_model: MeshModel,
+    instances: Vec<instance::Instance>,
+    /// 每个实例的发光系数，0 为不发光
+    glow: Vec<f32>,
+    instance_buffer: wgpu::Buffer,
+
+    scene_color: Texture,
+    depth_texture: Texture,
+
+    camera: CameraBundle,
+    light: DirectionalLightBundle,
+    post: PostStack,
+    /// 色调映射之后叠加的 HUD，颜色不随泛光与曝光变化
+    overlay: OverlayPass,
+    hud: SpriteRenderer,
+    /// 开启时左侧为关闭泛光、右侧为开启泛光的画面
+    compare: ComparePass,
+    comparing: bool,
+    /// 光标横坐标占画面宽度的比例
+    cursor_x: f32,
+}
+
+impl App {
+    /// 左上角的面板：泛光开关，以及泛光阈值与自发光强度的刻度条
+    fn build_hud(&mut self) {
+        const PANEL: glam::Vec2 = glam::vec2(220.0, 88.0);
+        const BAR_WIDTH: f32 = 150.0;
+        let scale = self.gpu.scale_factor() as f32;
+        let origin = glam::Vec2::splat(16.0 * scale) + PANEL * scale * 0.5;
+
+        let bloom_enabled = self.post.is_enabled(Bloom::LABEL);
+        let threshold = self
+            .post
+            .get_mut::<Bloom>()
+            .map_or(0.0, |bloom| bloom.threshold);
+        let emissive = self.obj_model.materials[0].emissive.max_element() / EMISSIVE.max_element();
+
+        self.hud.clear();
+        self.hud.push(&Sprite::rounded_rect(
+            origin,
+            PANEL * scale,
+            12.0 * scale,
+            glam::vec4(0.1, 0.1, 0.12, 0.7),
+        ));
+        let indicator = if bloom_enabled {
+            glam::vec4(1.0, 0.6, 0.2, 1.0)
+        } else {
+            glam::vec4(0.4, 0.4, 0.4, 1.0)
+        };
+        self.hud.push(&Sprite::circle(
+            origin + glam::vec2(-PANEL.x * 0.5 + 24.0, -20.0) * scale,
+            8.0 * scale,
+            indicator,
+        ));
+        let bars = [
+            (threshold / 4.0, glam::vec4(0.35, 0.65, 1.0, 1.0)),
+            (emissive / 4.0, glam::vec4(1.0, 0.6, 0.2, 1.0)),
+        ];
+        for (i, (value, color)) in bars.into_iter().enumerate() {
+            let center = origin + glam::vec2(16.0, i as f32 * 24.0) * scale;
+            let track = glam::vec2(BAR_WIDTH, 10.0) * scale;
+            self.hud.push(&Sprite::rounded_rect(
+                center,
+                track,
+                5.0 * scale,
+                glam::vec4(1.0, 1.0, 1.0, 0.15),
+            ));
+            let fill = track * glam::vec2(value.clamp(0.0, 1.0), 1.0);
+            if fill.x > 0.0 {
+                self.hud.push(&Sprite::rounded_rect(
+                    center - glam::vec2((track.x - fill.x) * 0.5, 0.0),
+                    fill,
+                    5.0 * scale,
+                    color,
+                ));
+            }
+        }
+        self.hud.update(
+            &self.gpu.device,
+            &self.gpu.queue,
+            pixel_projection(self.gpu.size()),
+        );
+    }
+
+    /// 按材质的自发光与各实例的发光系数更新实例数据
+    fn write_instances(&mut self) {
+        let emissive = self.obj_model.materials[0].emissive;
+        for (instance, glow) in self.instances.iter_mut().zip(&self.glow) {
+            instance.emissive = emissive * *glow;
+        }
+        let instance_data = self
+            .instances
+            .iter()
+            .map(instance::Instance::to_raw)
+            .collect::<Vec<_>>();
+        self.gpu.queue.write_buffer(
+            &self.instance_buffer,
+            0,
+            bytemuck::cast_slice(&instance_data),
+        );
+    }
+
+    /// 加载场景中的立方体模型，没有自发光的材质补上默认的自发光颜色
+    async fn load_cube(device: &wgpu::Device, queue: &wgpu::Queue) -> MeshModel {
+        let mut obj_model = MeshModel::load_model::<vertex::Vertex>(
+            "cube.obj",
+            device,
+            queue,
+            &Texture::texture_bind_group_layout(device),
+        )
+        .await
+        .unwrap();
+        if obj_model.materials[0].emissive == glam::Vec3::ZERO {
+            obj_model.materials[0].emissive = EMISSIVE;
+        }
+        obj_model
+    }
+
+    fn with_model(gpu: GpuContext, obj_model: MeshModel) -> Self {
+        let device = &gpu.device;
+
+        let camera = Camera {
+            eye: (0.0, 6.0, 20.0).into(),
+            target: (0.0, 1.0, 0.0).into(),
+            up: glam::Vec3::Y,
+            aspect: gpu.aspect(),
+            projection: Projection::Perspective { fovy: 60.0 },
+            znear: 0.1,
+            zfar: 100.0,
+            depth: DepthConvention::Standard,
+        };
+        let camera = CameraBundle::builder(camera)
+            .controller(KeyboardController::new(0.2))
+            .build(device)
+            .unwrap();
+
+        let light = DirectionalLightBundle::new(
+            DirectionalLight {
+                intensity: 0.4,
+                ..Default::default()
+            },
+            device,
+        );
+
+        // 泛光在 HDR 画面上进行，必须放在色调映射之前
+        let mut post = PostStack::new(device, &gpu.surface_config, HDR_FORMAT);
+        post.push(Bloom::new(device, &gpu.surface_config, post.format()));
+        post.push(Tonemapping::new(device, post.format()));
+
+        // HUD 画在 sRGB 空间的叠加层上，最后与色调映射后的画面合成
+        let overlay = OverlayPass::new(device, &gpu.surface_config, gpu.gamma_format());
+        let compare = ComparePass::new(device, &gpu.surface_config, gpu.format());
+        let white = Texture::from_image(
+            device,
+            &gpu.queue,
+            &image::RgbaImage::from_pixel(1, 1, image::Rgba([255; 4])).into(),
+            Some("hud_texture"),
+        )
+        .unwrap();
+        let hud = SpriteRenderer::new(device, OverlayPass::FORMAT, &white.view);
+
+        let scene_color =
+            Texture::create_color_target(device, &gpu.surface_config, HDR_FORMAT, "scene_color");
+        let depth_texture =
+            Texture::create_depth_texture(device, &gpu.surface_config, "depth_texture");
+
+        let texture_bind_group_layout = Texture::texture_bind_group_layout(device);
+
+        let shader = ShaderLibrary::new()
+            .create_shader_module(device, "Shader", include_str!("shader.wgsl"))
+            .unwrap();
+
+        let render_pipeline_layout =
+            device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some("Render Pipeline Layout"),
+                bind_group_layouts: &[
+                    &camera.bind_group_layout,
+                    &texture_bind_group_layout,
+                    &light.bind_group_layout,
+                ],
+                push_constant_ranges: &[],
+            });
+        let render_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Render Pipeline"),
+            layout: Some(&render_pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                compilation_options: Default::default(),
+                entry_point: Some("vs_main"),
+                buffers: &[
+                    instance::InstanceRaw::buffer_layout_desc(),
+                    vertex::Vertex::buffer_layout_desc(),
+                ],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                compilation_options: Default::default(),
+                entry_point: Some("fs_main"),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: HDR_FORMAT,
+                    blend: Some(wgpu::BlendState::REPLACE),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            primitive: wgpu::PrimitiveState {
+                cull_mode: Some(wgpu::Face::Back),
+                ..Default::default()
+            },
+            depth_stencil: Some(wgpu::DepthStencilState {
+                format: Texture::DEPTH_FORMAT,
+                depth_write_enabled: true,
+                depth_compare: wgpu::CompareFunction::Less,
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState::default(),
+            }),
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+            cache: None,
+        });
+
+        let mut instances = Vec::new();
+        let mut glow = Vec::new();
+        for z in 0..NUM_INSTANCES_PER_ROW {
+            for x in 0..NUM_INSTANCES_PER_ROW {
+                let px = SPACE_BETWEEN * (x as f32 - NUM_INSTANCES_PER_ROW as f32 / 2.0);
+                let pz = SPACE_BETWEEN * (z as f32 - NUM_INSTANCES_PER_ROW as f32 / 2.0);
+                instances.push(instance::Instance {
+                    position: glam::vec3(px, 0.0, pz),
+                    rotation: glam::Quat::from_rotation_y((px + pz) * 0.1),
+                    emissive: glam::Vec3::ZERO,
+                });
+                // 每隔几个立方体点亮一个，亮度各不相同
+                glow.push(if (x * 3 + z * 7) % 5 == 0 {
+                    0.5 + (x + z) as f32 / (2 * NUM_INSTANCES_PER_ROW) as f32
+                } else {
+                    0.0
+                });
+            }
+        }
+        let instance_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Instance Buffer"),
+            contents: bytemuck::cast_slice(
+                &instances
+                    .iter()
+                    .map(instance::Instance::to_raw)
+                    .collect::<Vec<_>>(),
+            ),
+            usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+        });
+
+        let mut app = Self {
+            gpu,
+
+            render_pipeline,
+
+            obj_model,
+            instances,
+            glow,
+            instance_buffer,
+
+            scene_color,
+            depth_texture,
+
+            camera,
+            light,
+            post,
+            overlay,
+            hud,
+            compare,
+            comparing: false,
+            cursor_x: 0.5,
+        };
+        app.write_instances();
+        app
+    }
+}
+
+impl WindowApp for App {
+    async fn new(window: Arc<Window>) -> Self {
+        let gpu = GpuContext::new(window, GpuContextOptions::default())
+            .await
+            .unwrap();
+        let obj_model = Self::load_cube(&gpu.device, &gpu.queue).await;
+        Self::with_model(gpu, obj_model)
+    }
+
+    async fn load(_window: Arc<Window>, loading: Loading) -> Self {
+        loading.step(0.1, "loading cube.obj").await;
+        let obj_model = Self::load_cube(&loading.device, &loading.queue).await;
+        loading.step(0.8, "creating pipelines").await;
+        Self::with_model(loading.finish(), obj_model)
+    }
+
+    fn render(&mut self) -> Result<(), wgpu::SurfaceError> {
+        self.resize_surface_if_needed();
+
+        let frame = self.gpu.current_frame()?;
+
+        let mut encoder = self
+            .gpu
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("Render Encoder"),
+            });
+
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Render Pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: &self.scene_color.view,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(wgpu::Color {
+                        r: 0.01,
+                        g: 0.01,
+                        b: 0.02,
+                        a: 1.0,
+                    }),
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                view: &self.depth_texture.view,
+                depth_ops: Some(wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(1.0),
+                    store: wgpu::StoreOp::Store,
+                }),
+                stencil_ops: None,
+            }),
+            ..Default::default()
+        });
+
+        render_pass.set_pipeline(&self.render_pipeline);
+        render_pass.set_vertex_buffer(0, self.instance_buffer.slice(..));
+        render_pass.set_bind_group(2, &self.light.bind_group, &[]);
+        render_pass.draw_model_instanced(
+            &self.obj_model,
+            0..self.instances.len() as u32,
+            &self.camera.bind_group,
+        );
+
+        drop(render_pass);
+
+        let scene = SceneTextures {
+            color: &self.scene_color,
+            depth: &self.depth_texture,
+            normal_roughness: None,
+            velocity: None,
+        };
+        if self.comparing {
+            let enabled = self.post.is_enabled(Bloom::LABEL);
+            self.post.set_enabled(Bloom::LABEL, false);
+            self.post
+                .run(&self.gpu.device, &mut encoder, &scene, self.compare.left());
+            self.post.set_enabled(Bloom::LABEL, true);
+            self.post
+                .run(&self.gpu.device, &mut encoder, &scene, self.compare.right());
+            self.post.set_enabled(Bloom::LABEL, enabled);
+            self.compare
+                .composite(&self.gpu.queue, &mut encoder, &frame.view);
+        } else {
+            self.post
+                .run(&self.gpu.device, &mut encoder, &scene, &frame.view);
+        }
+
+        let mut overlay_pass = self.overlay.begin(&mut encoder);
+        self.hud.draw(&mut overlay_pass);
+        drop(overlay_pass);
+        self.overlay
+            .composite(&self.gpu.device, &mut encoder, &frame.gamma_view);
+
+        self.gpu.queue.submit(Some(encoder.finish()));
+        self.gpu.present(frame);
+
+        Ok(())
+    }
+
+    fn set_window_resized(&mut self, new_size: PhysicalSize<u32>) {
+        self.gpu.resize(new_size);
+    }
+
+    fn resize_surface_if_needed(&mut self) {
+        if self.gpu.resize_if_needed() {
+            self.scene_color = Texture::create_color_target(
+                &self.gpu.device,
+                &self.gpu.surface_config,
+                HDR_FORMAT,
+                "scene_color",
+            );
+            self.depth_texture = Texture::create_depth_texture(
+                &self.gpu.device,
+                &self.gpu.surface_config,
+                "depth_texture",
+            );
+            self.post.resize(&self.gpu.device, &self.gpu.surface_config);
+            self.overlay
+                .resize(&self.gpu.device, &self.gpu.surface_config);
+            self.compare
+                .resize(&self.gpu.device, &self.gpu.surface_config);
+        }
+    }
+
+    fn gpu_context(&mut self) -> Option<&mut GpuContext> {
+        Some(&mut self.gpu)
+    }
+
+    fn keyboard_input(&mut self, event: &KeyEvent) -> bool {
+        if self.camera.process_key(event) {
+            return true;
+        }
+        if event.state != ElementState::Pressed {
+            return false;
+        }
+        // B 键开关泛光，[ 与 ] 键调整自发光强度，- 与 = 键调整泛光阈值，
+        // C 键依次切换到分割线、并排、差异对照模式再关闭对照
+        match event.physical_key {
+            PhysicalKey::Code(KeyCode::KeyC) if !event.repeat => {
+                if !self.comparing {
+                    self.comparing = true;
+                    self.compare.mode = CompareMode::Split;
+                } else if self.compare.mode == CompareMode::Difference {
+                    self.comparing = false;
+                } else {
+                    self.compare.mode = self.compare.mode.next();
+                }
+                true
+            }
+            PhysicalKey::Code(KeyCode::KeyB) if !event.repeat => {
+                let enabled = !self.post.is_enabled(Bloom::LABEL);
+                self.post.set_enabled(Bloom::LABEL, enabled);
+                true
+            }
+            PhysicalKey::Code(KeyCode::BracketLeft) => {
+                self.obj_model.materials[0].emissive /= 1.25;
+                self.write_instances();
+                true
+            }
+            PhysicalKey::Code(KeyCode::BracketRight) => {
+                self.obj_model.materials[0].emissive *= 1.25;
+                self.write_instances();
+                true
+            }
+            PhysicalKey::Code(KeyCode::Minus) => {
+                if let Some(bloom) = self.post.get_mut::<Bloom>() {
+                    bloom.threshold = (bloom.threshold - 0.25).max(0.0);
+                    println!("bloom threshold: {:.2}", bloom.threshold);
+                }
+                true
+            }
+            PhysicalKey::Code(KeyCode::Equal) => {
+                if let Some(bloom) = self.post.get_mut::<Bloom>() {
+                    bloom.threshold += 0.25;
+                    println!("bloom threshold: {:.2}", bloom.threshold);
+                }
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn mouse_wheel(&mut self, delta: MouseScrollDelta, _phase: TouchPhase) -> bool {
+        self.camera.process_wheel(delta)
+    }
+
+    fn mouse_click(&mut self, state: ElementState, button: MouseButton) -> bool {
+        self.comparing
+            && button == MouseButton::Left
+            && self.compare.mouse_button(state.is_pressed(), self.cursor_x)
+    }
+
+    fn cursor_move(&mut self, position: PhysicalPosition<f64>) -> bool {
+        self.cursor_x = position.x as f32 / self.gpu.size().width.max(1) as f32;
+        self.comparing && self.compare.drag(self.cursor_x)
+    }
+
+    fn cameras_mut(&mut self) -> Vec<&mut CameraBundle> {
+        vec![&mut self.camera]
+    }
+
+    fn update(&mut self, time: FrameTime) {
+        self.light.update(&self.gpu.queue);
+        self.camera.update(&self.gpu.queue, time.delta_secs());
+        self.post.update(&self.gpu.queue, &self.camera.state);
+        self.build_hud();
+    }
+}
+
+fn main() -> Result<(), impl std::error::Error> {
+    let events_loop = EventLoop::new().unwrap();
+    let mut app = WindowAppHandler::<App>::new("bloom example").with_loading_screen();
+    events_loop.run_app(&mut app)
+}
@@ -0,0 +1,63 @@
+#include "wgpu_dance/camera.wgsl"
+#include "wgpu_dance/lighting.wgsl"
+
+struct VertexInput {
+    @location(4) position: vec3f,
+    @location(5) tex_coords: vec2f,
+    @location(6) normal: vec3f,
+}
+
+struct InstanceInput {
+    @location(0) model_matrix_0: vec4f,
+    @location(1) model_matrix_1: vec4f,
+    @location(2) model_matrix_2: vec4f,
+    @location(3) model_matrix_3: vec4f,
+    @location(7) emissive: vec4f,
+};
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4f,
+    @location(0) tex_coords: vec2f,
+    @location(1) world_normal: vec3f,
+    @location(2) emissive: vec3f,
+}
+
+@group(0) @binding(0)
+var<uniform> camera: CameraUniform;
+
+@group(2) @binding(0)
+var<uniform> sun: DirectionalLight;
+
+@vertex
+fn vs_main(
+    model: VertexInput,
+    instance: InstanceInput,
+) -> VertexOutput {
+    var out: VertexOutput;
+    let model_matrix = mat4x4f(
+        instance.model_matrix_0,
+        instance.model_matrix_1,
+        instance.model_matrix_2,
+        instance.model_matrix_3,
+    );
+    out.tex_coords = model.tex_coords;
+    out.emissive = instance.emissive.rgb;
+    out.world_normal = (model_matrix * vec4f(model.normal, 0.0)).xyz;
+    out.clip_position = camera.view_proj * model_matrix * vec4f(model.position, 1.0);
+    return out;
+}
+
+@group(1) @binding(0)
+var t_diffuse: texture_2d<f32>;
+@group(1) @binding(1)
+var s_diffuse: sampler;
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4f {
+    let albedo = textureSample(t_diffuse, s_diffuse, in.tex_coords);
+    let diffuse = lambert(normalize(in.world_normal), sun.direction.xyz) * sun.direction.w;
+    let ambient = 0.08;
+    // 自发光直接加在光照结果上，不受光照影响；大于 1 的部分由泛光扩散开
+    let lit = albedo.rgb * (sun.color.rgb * diffuse + ambient);
+    return vec4f(lit + in.emissive, albedo.a);
+}
@@ -3,13 +3,21 @@ pub mod vertex;
 use std::sync::Arc;
 
 use wgpu_dance::{
-    app::{WindowApp, WindowAppHandler},
-    camera::{Camera, CameraBuddle},
+    app::{FrameTime, WindowApp, WindowAppHandler},
+    camera::{Camera, CameraBundle, KeyboardController, Projection, SmoothedCamera},
     model::{Model, RenderVertex},
-    texture::Texture,
+    texture::{DepthConvention, Texture},
 };
 
-use winit::{dpi::PhysicalSize, event::KeyEvent, event_loop::EventLoop, window::Window};
+use winit::{
+    dpi::PhysicalSize,
+    event::{KeyEvent, MouseScrollDelta, TouchPhase},
+    event_loop::EventLoop,
+    keyboard::{KeyCode, PhysicalKey},
+    window::Window,
+};
+
+const FOVY: f32 = 45.0;
 
 struct App {
     device: wgpu::Device,
@@ -27,7 +35,7 @@ struct App {
 
     diffuse_bind_group: wgpu::BindGroup,
 
-    camera: CameraBuddle,
+    camera: CameraBundle,
 }
 
 impl WindowApp for App {
@@ -128,11 +136,16 @@ impl WindowApp for App {
             // 定义哪个方向朝上
             up: glam::Vec3::Y,
             aspect: surface_config.width as f32 / surface_config.height as f32,
-            fovy: 45.0,
+            projection: Projection::Perspective { fovy: FOVY },
             znear: 0.1,
             zfar: 100.0,
+            depth: DepthConvention::Standard,
         };
-        let camera = CameraBuddle::new(camera, 0.2, &device);
+        // 键盘每帧跳动的移动经过平滑，松开按键后相机会滑行到目标位置
+        let camera = CameraBundle::builder(camera)
+            .controller(SmoothedCamera::new(KeyboardController::new(0.2)))
+            .build(&device)
+            .unwrap();
 
         let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
             label: Some("Shader"),
@@ -285,11 +298,31 @@ impl WindowApp for App {
     }
 
     fn keyboard_input(&mut self, event: &KeyEvent) -> bool {
-        self.camera.controller.process_events(event)
+        // P 在透视与正交投影之间切换，正交投影的范围与目标处透视的可视范围一致
+        if event.state.is_pressed() && event.physical_key == PhysicalKey::Code(KeyCode::KeyP) {
+            let camera = &mut self.camera.state;
+            let distance = (camera.target - camera.eye).length();
+            camera.projection = match camera.projection {
+                Projection::Perspective { .. } => Projection::Orthographic {
+                    half_height: camera.view_height_at(distance) * 0.5,
+                },
+                _ => Projection::Perspective { fovy: FOVY },
+            };
+            return true;
+        }
+        self.camera.process_key(event)
+    }
+
+    fn mouse_wheel(&mut self, delta: MouseScrollDelta, _phase: TouchPhase) -> bool {
+        self.camera.process_wheel(delta)
+    }
+
+    fn cameras_mut(&mut self) -> Vec<&mut CameraBundle> {
+        vec![&mut self.camera]
     }
 
-    fn update(&mut self) {
-        self.camera.update(&self.queue);
+    fn update(&mut self, time: FrameTime) {
+        self.camera.update(&self.queue, time.delta_secs());
     }
 }
 
@@ -11,8 +11,6 @@ use winit::{
     window::{Window, WindowId},
 };
 
-use tokio::runtime::Runtime;
-
 struct WgpuApp {
     window: Arc<Window>,
 
@@ -160,12 +158,10 @@ impl ApplicationHandler for WgpuAppHandler {
             return;
         }
 
-        let rt = Runtime::new().unwrap();
-
         let window_attributes = Window::default_attributes().with_title("create window");
         let window = Arc::new(event_loop.create_window(window_attributes).unwrap());
 
-        let wgpu_app = rt.block_on(WgpuApp::new(window));
+        let wgpu_app = futures::executor::block_on(WgpuApp::new(window));
 
         self.app.lock().unwrap().deref_mut().replace(wgpu_app);
     }
@@ -0,0 +1,166 @@
+//! 由长方体拼成的简单人形角色，以及程序生成的几段动画
+
+use std::f32::consts::TAU;
+
+use wgpu_dance::animation::{AnimationClip, Joint, JointPose, JointTrack, Skeleton};
+
+use crate::vertex::{push_box, SkinnedVertex};
+
+/// 关节名、父关节与相对父关节的位置
+const JOINTS: [(&str, Option<usize>, [f32; 3]); 11] = [
+    ("hips", None, [0.0, 1.0, 0.0]),
+    ("spine", Some(0), [0.0, 0.1, 0.0]),
+    ("head", Some(1), [0.0, 0.65, 0.0]),
+    ("upper_arm_l", Some(1), [0.25, 0.55, 0.0]),
+    ("forearm_l", Some(3), [0.0, -0.32, 0.0]),
+    ("upper_arm_r", Some(1), [-0.25, 0.55, 0.0]),
+    ("forearm_r", Some(5), [0.0, -0.32, 0.0]),
+    ("thigh_l", Some(0), [0.11, -0.05, 0.0]),
+    ("shin_l", Some(7), [0.0, -0.45, 0.0]),
+    ("thigh_r", Some(0), [-0.11, -0.05, 0.0]),
+    ("shin_r", Some(9), [0.0, -0.45, 0.0]),
+];
+
+/// 身体各部分：所属关节、绑定姿态下的中心与半边长
+const PARTS: [(&str, [f32; 3], [f32; 3]); 11] = [
+    ("hips", [0.0, 1.0, 0.0], [0.17, 0.1, 0.09]),
+    ("spine", [0.0, 1.38, 0.0], [0.2, 0.28, 0.1]),
+    ("head", [0.0, 1.86, 0.0], [0.11, 0.12, 0.11]),
+    ("upper_arm_l", [0.25, 1.5, 0.0], [0.05, 0.15, 0.05]),
+    ("forearm_l", [0.25, 1.18, 0.0], [0.045, 0.15, 0.045]),
+    ("upper_arm_r", [-0.25, 1.5, 0.0], [0.05, 0.15, 0.05]),
+    ("forearm_r", [-0.25, 1.18, 0.0], [0.045, 0.15, 0.045]),
+    ("thigh_l", [0.11, 0.73, 0.0], [0.07, 0.21, 0.07]),
+    ("shin_l", [0.11, 0.27, 0.0], [0.06, 0.23, 0.06]),
+    ("thigh_r", [-0.11, 0.73, 0.0], [0.07, 0.21, 0.07]),
+    ("shin_r", [-0.11, 0.27, 0.0], [0.06, 0.23, 0.06]),
+];
+
+/// 每段动画的关键帧数
+const KEYS: usize = 16;
+
+pub fn skeleton() -> Skeleton {
+    let joints = JOINTS
+        .iter()
+        .map(|&(name, parent, translation)| Joint {
+            name: name.to_string(),
+            parent,
+            rest: JointPose {
+                translation: translation.into(),
+                ..Default::default()
+            },
+        })
+        .collect();
+    Skeleton::new(joints).expect("joints are listed parent first")
+}
+
+pub fn mesh(skeleton: &Skeleton) -> (Vec<SkinnedVertex>, Vec<u32>) {
+    let mut vertices = Vec::new();
+    let mut indices = Vec::new();
+    for (joint, center, half_extents) in PARTS {
+        let joint = skeleton.joint_index(joint).unwrap();
+        push_box(
+            &mut vertices,
+            &mut indices,
+            joint,
+            center.into(),
+            half_extents.into(),
+        );
+    }
+    (vertices, indices)
+}
+
+/// 在一个周期内均匀取样的旋转关键帧，`angle` 的参数为 0~1 的相位
+fn rotation_track(
+    skeleton: &Skeleton,
+    joint: &str,
+    duration: f32,
+    axis: glam::Vec3,
+    angle: impl Fn(f32) -> f32,
+) -> JointTrack {
+    JointTrack {
+        joint: skeleton.joint_index(joint).unwrap(),
+        rotations: (0..=KEYS)
+            .map(|i| {
+                let phase = i as f32 / KEYS as f32;
+                (
+                    phase * duration,
+                    glam::Quat::from_axis_angle(axis, angle(phase)),
+                )
+            })
+            .collect(),
+        ..Default::default()
+    }
+}
+
+/// 髋部上下起伏的关键帧
+fn bob_track(skeleton: &Skeleton, duration: f32, height: impl Fn(f32) -> f32) -> JointTrack {
+    JointTrack {
+        joint: skeleton.joint_index("hips").unwrap(),
+        translations: (0..=KEYS)
+            .map(|i| {
+                let phase = i as f32 / KEYS as f32;
+                (phase * duration, glam::vec3(0.0, 1.0 + height(phase), 0.0))
+            })
+            .collect(),
+        ..Default::default()
+    }
+}
+
+/// 行走、挥手与待机三段循环动画
+pub fn clips(skeleton: &Skeleton) -> Vec<AnimationClip> {
+    let swing = |offset: f32, amplitude: f32| move |p: f32| (p * TAU + offset).sin() * amplitude;
+    // 腿向后摆时膝盖弯曲
+    let knee = |offset: f32| move |p: f32| (p * TAU + offset).sin().max(0.0) * 0.8;
+    let walk = AnimationClip {
+        name: "walk".into(),
+        duration: 1.0,
+        tracks: vec![
+            rotation_track(skeleton, "thigh_l", 1.0, glam::Vec3::X, swing(0.0, 0.5)),
+            rotation_track(
+                skeleton,
+                "thigh_r",
+                1.0,
+                glam::Vec3::X,
+                swing(TAU / 2.0, 0.5),
+            ),
+            rotation_track(skeleton, "shin_l", 1.0, glam::Vec3::X, knee(0.0)),
+            rotation_track(skeleton, "shin_r", 1.0, glam::Vec3::X, knee(TAU / 2.0)),
+            rotation_track(
+                skeleton,
+                "upper_arm_l",
+                1.0,
+                glam::Vec3::X,
+                swing(TAU / 2.0, 0.4),
+            ),
+            rotation_track(skeleton, "upper_arm_r", 1.0, glam::Vec3::X, swing(0.0, 0.4)),
+            bob_track(skeleton, 1.0, |p| (p * TAU * 2.0).cos().abs() * 0.03 - 0.03),
+        ],
+    };
+
+    let wave = AnimationClip {
+        name: "wave".into(),
+        duration: 1.2,
+        tracks: vec![
+            rotation_track(skeleton, "upper_arm_r", 1.2, glam::Vec3::Z, |_| -2.6),
+            rotation_track(skeleton, "forearm_r", 1.2, glam::Vec3::Z, |p| {
+                (p * TAU * 2.0).sin() * 0.5 - 0.3
+            }),
+            rotation_track(skeleton, "spine", 1.2, glam::Vec3::Z, swing(0.0, 0.05)),
+        ],
+    };
+
+    let idle = AnimationClip {
+        name: "idle".into(),
+        duration: 2.0,
+        tracks: vec![
+            rotation_track(skeleton, "spine", 2.0, glam::Vec3::X, swing(0.0, 0.05)),
+            rotation_track(skeleton, "head", 2.0, glam::Vec3::Y, swing(0.0, 0.3)),
+            rotation_track(skeleton, "upper_arm_l", 2.0, glam::Vec3::Z, |_| 0.1),
+            rotation_track(skeleton, "upper_arm_r", 2.0, glam::Vec3::Z, |_| -0.1),
+            bob_track(skeleton, 2.0, |p| ((p * TAU).cos() - 1.0) * 0.01),
+        ],
+    };
+
+    vec![walk, wave, idle]
+}
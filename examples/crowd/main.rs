@@ -0,0 +1,441 @@
+pub mod character;
+pub mod vertex;
+
+use std::sync::Arc;
+
+use wgpu::util::DeviceExt;
+use wgpu_dance::{
+    animation::{AnimatedInstance, AnimatedInstanceRaw, BakedAnimations},
+    app::{FrameTime, WindowApp, WindowAppHandler},
+    background::Background,
+    camera::{Camera, CameraBundle, FollowController, KeyboardController, Projection},
+    context::{GpuContext, GpuContextOptions},
+    debug_draw::DebugDraw,
+    instance::Instance,
+    jobs::{self, JobSystem},
+    light::{DirectionalLight, DirectionalLightBundle},
+    model::RenderVertex,
+    pipeline::{PipelineBuilder, ReflectedShader},
+    profiler::{CpuProfiler, CpuSpan},
+    shader::ShaderLibrary,
+    texture::{DepthConvention, Texture},
+};
+
+use winit::{
+    dpi::PhysicalSize,
+    event::{ElementState, KeyEvent, MouseScrollDelta, TouchPhase},
+    event_loop::EventLoop,
+    keyboard::{KeyCode, PhysicalKey},
+    window::Window,
+};
+
+use vertex::SkinnedVertex;
+
+/// 每行的角色数
+const CROWD_SIZE: u32 = 20;
+const SPACING: f32 = 1.5;
+/// 烘焙动画的帧率
+const BAKE_FPS: f32 = 30.0;
+/// 绕人群行走的角色的圆周半径与速度（米/秒）
+const WALK_RADIUS: f32 = CROWD_SIZE as f32 * SPACING * 0.5 + 3.0;
+const WALK_SPEED: f32 = 1.6;
+
+struct App {
+    gpu: GpuContext,
+
+    depth_texture: Texture,
+
+    camera: CameraBundle,
+    light: DirectionalLightBundle,
+    debug: DebugDraw,
+
+    render_pipeline: wgpu::RenderPipeline,
+    wireframe_pipeline: wgpu::RenderPipeline,
+    wireframe: bool,
+    vertex_buffer: wgpu::Buffer,
+    index_buffer: wgpu::Buffer,
+    index_count: u32,
+
+    animations: BakedAnimations,
+    /// 最后一个角色绕着人群行走
+    instances: Vec<AnimatedInstance>,
+    /// 每个角色自己的动画与播放速度
+    own_clips: Vec<(u32, f32)>,
+    instance_buffer: wgpu::Buffer,
+    /// 所有角色统一播放的动画，`None` 时各自播放
+    forced_clip: Option<u32>,
+    /// 上一帧 CPU 端各项并行任务的耗时
+    cpu_spans: Vec<CpuSpan>,
+}
+
+impl App {
+    fn build_grid(&mut self) {
+        self.debug.clear();
+        let grid = glam::vec4(0.5, 0.5, 0.55, 0.4);
+        let extent = CROWD_SIZE as f32 * SPACING * 0.5 + 1.0;
+        for i in -(extent as i32)..=extent as i32 {
+            let i = i as f32;
+            self.debug.line(
+                glam::vec3(i, 0.0, -extent),
+                glam::vec3(i, 0.0, extent),
+                grid,
+            );
+            self.debug.line(
+                glam::vec3(-extent, 0.0, i),
+                glam::vec3(extent, 0.0, i),
+                grid,
+            );
+        }
+    }
+}
+
+impl WindowApp for App {
+    async fn new(window: Arc<Window>) -> Self {
+        let mut gpu = GpuContext::new(window, GpuContextOptions::for_app::<Self>())
+            .await
+            .unwrap();
+        gpu.background = Background::Color(wgpu::Color {
+            r: 0.05,
+            g: 0.06,
+            b: 0.08,
+            a: 1.0,
+        });
+
+        let camera = Camera {
+            eye: (0.0, 12.0, 24.0).into(),
+            target: (0.0, 1.0, 0.0).into(),
+            up: glam::Vec3::Y,
+            aspect: gpu.aspect(),
+            projection: Projection::Perspective { fovy: 45.0 },
+            znear: 0.1,
+            zfar: 200.0,
+            depth: DepthConvention::Standard,
+        };
+        let camera = CameraBundle::builder(camera)
+            .controller(KeyboardController::new(0.2))
+            .build(&gpu.device)
+            .unwrap();
+        let light = DirectionalLightBundle::new(
+            DirectionalLight {
+                direction: glam::vec3(0.4, 1.0, 0.6).normalize(),
+                ..Default::default()
+            },
+            &gpu.device,
+        );
+
+        let depth_texture =
+            Texture::create_depth_texture(&gpu.device, &gpu.surface_config, "depth_texture");
+        let debug = DebugDraw::new(
+            &gpu.device,
+            gpu.format(),
+            Some(Texture::DEPTH_FORMAT),
+            &camera.bind_group_layout,
+        );
+
+        let skeleton = character::skeleton();
+        let animations = BakedAnimations::bake(
+            &gpu.device,
+            &gpu.queue,
+            &skeleton,
+            &character::clips(&skeleton),
+            BAKE_FPS,
+        )
+        .unwrap();
+        for clip in animations.clips() {
+            println!(
+                "baked `{}`: {} frames at row {}",
+                clip.name, clip.frame_count, clip.first_row
+            );
+        }
+
+        let (vertices, indices) = character::mesh(&skeleton);
+        let vertex_buffer = gpu
+            .device
+            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("Character Vertex Buffer"),
+                contents: bytemuck::cast_slice(&vertices),
+                usage: wgpu::BufferUsages::VERTEX,
+            });
+        let index_buffer = gpu
+            .device
+            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("Character Index Buffer"),
+                contents: bytemuck::cast_slice(&indices),
+                usage: wgpu::BufferUsages::INDEX,
+            });
+
+        // 用简单的哈希让相邻角色的动画、朝向与起始时刻各不相同
+        let clip_count = animations.clips().len() as u32;
+        let mut instances = Vec::new();
+        let mut own_clips = Vec::new();
+        for z in 0..CROWD_SIZE {
+            for x in 0..CROWD_SIZE {
+                let hash = (x * 73 + z * 151) % 97;
+                let offset = (CROWD_SIZE as f32 - 1.0) * 0.5;
+                instances.push(AnimatedInstance {
+                    transform: Instance {
+                        position: glam::vec3(x as f32 - offset, 0.0, z as f32 - offset) * SPACING,
+                        rotation: glam::Quat::from_rotation_y(hash as f32 * 0.35),
+                        ..Default::default()
+                    },
+                    clip: hash % clip_count,
+                    time: hash as f32 * 0.13,
+                });
+                own_clips.push((hash % clip_count, 0.8 + (hash % 5) as f32 * 0.1));
+            }
+        }
+        // 行走的角色播放第一段动画（walk），位置在每次更新时计算
+        instances.push(AnimatedInstance::default());
+        own_clips.push((0, 1.0));
+        let instance_buffer = gpu
+            .device
+            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("Animated Instance Buffer"),
+                contents: bytemuck::cast_slice(
+                    &instances
+                        .iter()
+                        .map(AnimatedInstance::to_raw)
+                        .collect::<Vec<_>>(),
+                ),
+                usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+            });
+
+        let shader = ReflectedShader::new(
+            &gpu.device,
+            &ShaderLibrary::new(),
+            "Crowd Shader",
+            include_str!("shader.wgsl"),
+        )
+        .unwrap();
+        let build_pipeline = |label, polygon_mode| {
+            PipelineBuilder::from_reflection(&shader)
+                .label(label)
+                .bind_group_layout(
+                    0,
+                    &camera.bind_group_layout,
+                    &[CameraBundle::layout_entry(wgpu::ShaderStages::VERTEX)],
+                )
+                .bind_group_layout(
+                    1,
+                    &light.bind_group_layout,
+                    &[DirectionalLightBundle::layout_entry()],
+                )
+                .bind_group_layout(
+                    2,
+                    &animations.bind_group_layout,
+                    &BakedAnimations::layout_entries(),
+                )
+                .vertex_buffer(SkinnedVertex::buffer_layout_desc())
+                .vertex_buffer(AnimatedInstanceRaw::buffer_layout_desc())
+                .color_target(wgpu::ColorTargetState {
+                    format: gpu.format(),
+                    blend: Some(wgpu::BlendState::REPLACE),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })
+                .primitive(wgpu::PrimitiveState {
+                    cull_mode: Some(wgpu::Face::Back),
+                    polygon_mode,
+                    ..Default::default()
+                })
+                .depth_stencil(wgpu::DepthStencilState {
+                    format: Texture::DEPTH_FORMAT,
+                    depth_write_enabled: true,
+                    depth_compare: wgpu::CompareFunction::Less,
+                    stencil: wgpu::StencilState::default(),
+                    bias: wgpu::DepthBiasState::default(),
+                })
+                .build(&gpu.device)
+                .unwrap()
+                .pipeline
+        };
+        let render_pipeline = build_pipeline("Crowd Pipeline", wgpu::PolygonMode::Fill);
+        let wireframe_pipeline =
+            build_pipeline("Crowd Wireframe Pipeline", wgpu::PolygonMode::Line);
+
+        let mut app = Self {
+            gpu,
+
+            depth_texture,
+
+            camera,
+            light,
+            debug,
+
+            render_pipeline,
+            wireframe_pipeline,
+            wireframe: false,
+            vertex_buffer,
+            index_buffer,
+            index_count: indices.len() as u32,
+
+            animations,
+            instances,
+            own_clips,
+            instance_buffer,
+            forced_clip: None,
+            cpu_spans: Vec::new(),
+        };
+        app.build_grid();
+        app
+    }
+
+    fn render(&mut self) -> Result<(), wgpu::SurfaceError> {
+        self.resize_surface_if_needed();
+
+        let mut frame = self.gpu.begin_frame()?;
+
+        let mut render_pass = frame
+            .render_pass("Render Pass")
+            .depth(&self.depth_texture.view)
+            .begin();
+
+        // 所有角色在一次实例化绘制中完成
+        render_pass.set_pipeline(if self.wireframe {
+            &self.wireframe_pipeline
+        } else {
+            &self.render_pipeline
+        });
+        render_pass.set_bind_group(0, &self.camera.bind_group, &[]);
+        render_pass.set_bind_group(1, &self.light.bind_group, &[]);
+        render_pass.set_bind_group(2, &self.animations.bind_group, &[]);
+        render_pass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
+        render_pass.set_vertex_buffer(1, self.instance_buffer.slice(..));
+        render_pass.set_index_buffer(self.index_buffer.slice(..), wgpu::IndexFormat::Uint32);
+        render_pass.draw_indexed(0..self.index_count, 0, 0..self.instances.len() as u32);
+
+        self.debug.draw(&mut render_pass, &self.camera.bind_group);
+
+        drop(render_pass);
+
+        self.gpu.end_frame(frame);
+
+        Ok(())
+    }
+
+    fn set_window_resized(&mut self, new_size: PhysicalSize<u32>) {
+        self.gpu.resize(new_size);
+    }
+
+    fn resize_surface_if_needed(&mut self) {
+        if self.gpu.resize_if_needed() {
+            self.depth_texture = Texture::create_depth_texture(
+                &self.gpu.device,
+                &self.gpu.surface_config,
+                "depth_texture",
+            );
+        }
+    }
+
+    fn required_features() -> wgpu::Features {
+        wgpu::Features::POLYGON_MODE_LINE
+    }
+
+    fn gpu_context(&mut self) -> Option<&mut GpuContext> {
+        Some(&mut self.gpu)
+    }
+
+    fn keyboard_input(&mut self, event: &KeyEvent) -> bool {
+        if self.camera.process_key(event) {
+            return true;
+        }
+        if event.state != ElementState::Pressed || event.repeat {
+            return false;
+        }
+        // F 键切换线框模式，P 键打印上一帧 CPU 任务的耗时，C 键切换跟随行走角色的相机，
+        // 1/2/3 键让所有角色播放同一段动画，0 键恢复各自的动画
+        if event.physical_key == PhysicalKey::Code(KeyCode::KeyC) {
+            if self.camera.controller_mut::<FollowController>().is_some() {
+                self.camera.controller = Box::new(KeyboardController::new(0.2));
+            } else {
+                // 角色面朝 +Z，相机放在 -Z 一侧的上方
+                self.camera.controller = Box::new(
+                    FollowController::new(glam::vec3(0.0, 2.5, -6.0))
+                        .with_look_offset(glam::vec3(0.0, 1.2, 0.0)),
+                );
+            }
+            return true;
+        }
+        if event.physical_key == PhysicalKey::Code(KeyCode::KeyF) {
+            self.wireframe = !self.wireframe;
+            return true;
+        }
+        if event.physical_key == PhysicalKey::Code(KeyCode::KeyP) {
+            for span in &self.cpu_spans {
+                println!("{span}");
+            }
+            return true;
+        }
+        self.forced_clip = match event.physical_key {
+            PhysicalKey::Code(KeyCode::Digit0) => None,
+            PhysicalKey::Code(KeyCode::Digit1) => Some(0),
+            PhysicalKey::Code(KeyCode::Digit2) => Some(1),
+            PhysicalKey::Code(KeyCode::Digit3) => Some(2),
+            _ => return false,
+        };
+        if let Some(clip) = self
+            .forced_clip
+            .and_then(|i| self.animations.clips().get(i as usize))
+        {
+            println!("all characters play `{}`", clip.name);
+        }
+        true
+    }
+
+    fn mouse_wheel(&mut self, delta: MouseScrollDelta, _phase: TouchPhase) -> bool {
+        self.camera.process_wheel(delta)
+    }
+
+    fn cameras_mut(&mut self) -> Vec<&mut CameraBundle> {
+        vec![&mut self.camera]
+    }
+
+    fn update(&mut self, time: FrameTime) {
+        // 绕人群转圈，面朝前进方向
+        let angle = time.elapsed_secs() * WALK_SPEED / WALK_RADIUS;
+        let walker = self.instances.last_mut().unwrap();
+        walker.transform.position = glam::vec3(angle.cos(), 0.0, angle.sin()) * WALK_RADIUS;
+        walker.transform.rotation = glam::Quat::from_rotation_y(-angle);
+        let (position, rotation) = (walker.transform.position, walker.transform.rotation);
+        if let Some(follow) = self.camera.controller_mut::<FollowController>() {
+            follow.set_target(position, rotation);
+        }
+        self.camera.update(&self.gpu.queue, time.delta_secs());
+        self.light.update(&self.gpu.queue);
+
+        let dt = time.delta_secs();
+        for (instance, &(clip, speed)) in self.instances.iter_mut().zip(&self.own_clips) {
+            instance.clip = self.forced_clip.unwrap_or(clip);
+            instance.time += dt * speed;
+        }
+        let jobs = jobs::global();
+        let instance_data = jobs.map("crowd instances", &self.instances, AnimatedInstance::to_raw);
+        self.gpu.queue.write_buffer(
+            &self.instance_buffer,
+            0,
+            bytemuck::cast_slice(&instance_data),
+        );
+
+        self.debug.update(
+            &self.gpu.device,
+            &self.gpu.queue,
+            &self.camera.state,
+            self.gpu.size(),
+        );
+
+        if let Some(profiler) = jobs.profiler() {
+            self.cpu_spans = profiler.take();
+        }
+    }
+}
+
+fn main() -> Result<(), impl std::error::Error> {
+    // 角色数量不多，降低并行的门槛，并记录每项任务的耗时
+    let jobs = JobSystem::default()
+        .with_min_parallel_len(64)
+        .with_profiler(Arc::new(CpuProfiler::new()));
+    jobs::set_global(jobs).unwrap();
+
+    let events_loop = EventLoop::new().unwrap();
+    let mut app = WindowAppHandler::<App>::new("crowd example");
+    events_loop.run_app(&mut app)
+}
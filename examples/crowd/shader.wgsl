@@ -0,0 +1,75 @@
+#include "wgpu_dance/baked_animation.wgsl"
+#include "wgpu_dance/camera.wgsl"
+#include "wgpu_dance/lighting.wgsl"
+#include "wgpu_dance/skinning.wgsl"
+
+struct VertexInput {
+    @location(4) position: vec3f,
+    @location(5) normal: vec3f,
+    @location(6) joints: vec4u,
+    @location(7) weights: vec4f,
+}
+
+struct InstanceInput {
+    @location(0) model_matrix_0: vec4f,
+    @location(1) model_matrix_1: vec4f,
+    @location(2) model_matrix_2: vec4f,
+    @location(3) model_matrix_3: vec4f,
+    @location(8) clip: u32,
+    @location(9) time: f32,
+};
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4f,
+    @location(0) world_normal: vec3f,
+    @location(1) @interpolate(flat) clip: u32,
+}
+
+@group(0) @binding(0)
+var<uniform> camera: CameraUniform;
+
+@group(1) @binding(0)
+var<uniform> sun: DirectionalLight;
+
+@group(2) @binding(0)
+var bones: texture_2d<f32>;
+@group(2) @binding(1)
+var<uniform> baked: BakedClips;
+
+@vertex
+fn vs_main(model: VertexInput, instance: InstanceInput) -> VertexOutput {
+    let model_matrix = mat4x4f(
+        instance.model_matrix_0,
+        instance.model_matrix_1,
+        instance.model_matrix_2,
+        instance.model_matrix_3,
+    );
+    let clip = baked.clips[instance.clip];
+    let skin = skin_blend(
+        baked_joint_matrix(bones, clip, model.joints.x, instance.time),
+        baked_joint_matrix(bones, clip, model.joints.y, instance.time),
+        baked_joint_matrix(bones, clip, model.joints.z, instance.time),
+        baked_joint_matrix(bones, clip, model.joints.w, instance.time),
+        model.weights,
+    );
+    let world = model_matrix * skin;
+    var out: VertexOutput;
+    // 实例只有旋转与均匀缩放，可以直接用模型矩阵变换法线
+    out.world_normal = skin_normal(world, model.normal);
+    out.clip_position = camera.view_proj * world * vec4f(model.position, 1.0);
+    out.clip = instance.clip;
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4f {
+    // 按播放的动画着色，便于分辨
+    var palette = array<vec3f, 3>(
+        vec3f(0.8, 0.45, 0.35),
+        vec3f(0.35, 0.55, 0.85),
+        vec3f(0.5, 0.75, 0.4),
+    );
+    let albedo = palette[in.clip % 3u];
+    let diffuse = lambert(normalize(in.world_normal), sun.direction.xyz) * sun.direction.w;
+    return vec4f(albedo * (sun.color.rgb * diffuse + 0.2), 1.0);
+}
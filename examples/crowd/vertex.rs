@@ -0,0 +1,63 @@
+use bytemuck::{Pod, Zeroable};
+
+use wgpu_dance::model::RenderVertex;
+
+/// 蒙皮顶点，使用 4~7 号位置，避开实例数据占用的 0~3 与 8、9 号位置
+#[repr(C)]
+#[derive(Copy, Clone, Debug)]
+pub struct SkinnedVertex {
+    pub position: [f32; 3],
+    pub normal: [f32; 3],
+    pub joints: [u32; 4],
+    pub weights: [f32; 4],
+}
+
+unsafe impl Zeroable for SkinnedVertex {}
+unsafe impl Pod for SkinnedVertex {}
+
+impl RenderVertex for SkinnedVertex {
+    fn buffer_layout_desc<'a>() -> wgpu::VertexBufferLayout<'a> {
+        const ATTRIBUTES: [wgpu::VertexAttribute; 4] = wgpu::vertex_attr_array![
+            4 => Float32x3,
+            5 => Float32x3,
+            6 => Uint32x4,
+            7 => Float32x4,
+        ];
+        wgpu::VertexBufferLayout {
+            array_stride: std::mem::size_of::<SkinnedVertex>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Vertex,
+            attributes: &ATTRIBUTES,
+        }
+    }
+}
+
+/// 追加一个完全绑定到 `joint` 的长方体，`center` 与 `half_extents` 为绑定姿态下的位置与半边长
+pub fn push_box(
+    vertices: &mut Vec<SkinnedVertex>,
+    indices: &mut Vec<u32>,
+    joint: usize,
+    center: glam::Vec3,
+    half_extents: glam::Vec3,
+) {
+    for normal in [
+        glam::Vec3::X,
+        glam::Vec3::NEG_X,
+        glam::Vec3::Y,
+        glam::Vec3::NEG_Y,
+        glam::Vec3::Z,
+        glam::Vec3::NEG_Z,
+    ] {
+        let (u, v) = normal.any_orthonormal_pair();
+        let base = vertices.len() as u32;
+        for (a, b) in [(-1.0, -1.0), (1.0, -1.0), (1.0, 1.0), (-1.0, 1.0)] {
+            vertices.push(SkinnedVertex {
+                position: (center + (normal + u * a + v * b) * half_extents).to_array(),
+                normal: normal.to_array(),
+                joints: [joint as u32, 0, 0, 0],
+                weights: [1.0, 0.0, 0.0, 0.0],
+            });
+        }
+        // u × v 与法线同向，上面的四个角从法线方向看是逆时针
+        indices.extend([base, base + 1, base + 2, base, base + 2, base + 3]);
+    }
+}
@@ -0,0 +1,37 @@
+struct Grid {
+    // x: 宽, y: 高
+    size: vec2u,
+}
+
+@group(0) @binding(0)
+var<uniform> grid: Grid;
+@group(0) @binding(1)
+var<storage, read> current: array<u32>;
+@group(0) @binding(2)
+var<storage, read_write> next: array<u32>;
+
+// 网格首尾相接
+fn cell(x: i32, y: i32) -> u32 {
+    let size = vec2i(grid.size);
+    let wrapped = (vec2i(x, y) + size) % size;
+    return current[u32(wrapped.y) * grid.size.x + u32(wrapped.x)];
+}
+
+@compute @workgroup_size(8, 8)
+fn cs_main(@builtin(global_invocation_id) id: vec3u) {
+    if id.x >= grid.size.x || id.y >= grid.size.y {
+        return;
+    }
+    let x = i32(id.x);
+    let y = i32(id.y);
+    var neighbors = 0u;
+    for (var dy = -1; dy <= 1; dy++) {
+        for (var dx = -1; dx <= 1; dx++) {
+            if dx != 0 || dy != 0 {
+                neighbors += cell(x + dx, y + dy);
+            }
+        }
+    }
+    let alive = cell(x, y) == 1u;
+    next[id.y * grid.size.x + id.x] = select(0u, 1u, neighbors == 3u || (alive && neighbors == 2u));
+}
@@ -0,0 +1,126 @@
+use wgpu::util::DeviceExt;
+use wgpu_dance::{
+    compute::{block_on_compute, ComputeApp, ComputeContext, ComputeStep},
+    scatter::Rng,
+    shader::ShaderLibrary,
+};
+
+const WIDTH: u32 = 64;
+const HEIGHT: u32 = 24;
+const GENERATIONS: u32 = 100;
+
+/// 在 GPU 上演化康威生命游戏，不创建窗口，结束后把最终的网格打印到终端
+struct Life {
+    pipeline: wgpu::ComputePipeline,
+    /// `bind_groups[i]` 从 `cells[i]` 读取、写入 `cells[1 - i]`
+    bind_groups: [wgpu::BindGroup; 2],
+    cells: [wgpu::Buffer; 2],
+}
+
+impl ComputeApp for Life {
+    type Output = Vec<u32>;
+
+    async fn new(gpu: &ComputeContext) -> anyhow::Result<Self> {
+        let device = &gpu.device;
+        let shader = ShaderLibrary::new().create_shader_module(
+            device,
+            "Life Shader",
+            include_str!("life.wgsl"),
+        )?;
+        let pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+            label: Some("Life Pipeline"),
+            layout: None,
+            module: &shader,
+            entry_point: Some("cs_main"),
+            compilation_options: Default::default(),
+            cache: None,
+        });
+
+        // 随机撒下约三分之一的细胞，再放一个滑翔机
+        let mut rng = Rng::new(7);
+        let mut initial = (0..WIDTH * HEIGHT)
+            .map(|_| (rng.next_f32() < 0.3) as u32)
+            .collect::<Vec<_>>();
+        for (x, y) in [(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)] {
+            initial[(y * WIDTH + x) as usize] = 1;
+        }
+        let cells = [
+            gpu.create_storage_buffer("cells_0", &initial),
+            gpu.create_storage_buffer("cells_1", &initial),
+        ];
+        let size = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Grid Uniform Buffer"),
+            contents: bytemuck::cast_slice(&[WIDTH, HEIGHT]),
+            usage: wgpu::BufferUsages::UNIFORM,
+        });
+        let layout = pipeline.get_bind_group_layout(0);
+        let bind_groups = [0, 1].map(|i| {
+            device.create_bind_group(&wgpu::BindGroupDescriptor {
+                label: Some("life_bind_group"),
+                layout: &layout,
+                entries: &[
+                    wgpu::BindGroupEntry {
+                        binding: 0,
+                        resource: size.as_entire_binding(),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 1,
+                        resource: cells[i].as_entire_binding(),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 2,
+                        resource: cells[1 - i].as_entire_binding(),
+                    },
+                ],
+            })
+        });
+
+        Ok(Self {
+            pipeline,
+            bind_groups,
+            cells,
+        })
+    }
+
+    fn step(
+        &mut self,
+        _gpu: &ComputeContext,
+        encoder: &mut wgpu::CommandEncoder,
+        step: u32,
+    ) -> ComputeStep {
+        let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+            label: Some("Life Pass"),
+            timestamp_writes: None,
+        });
+        pass.set_pipeline(&self.pipeline);
+        pass.set_bind_group(0, &self.bind_groups[(step % 2) as usize], &[]);
+        pass.dispatch_workgroups(WIDTH.div_ceil(8), HEIGHT.div_ceil(8), 1);
+        if step + 1 < GENERATIONS {
+            ComputeStep::Continue
+        } else {
+            ComputeStep::Done
+        }
+    }
+
+    async fn finish(self, gpu: &ComputeContext) -> anyhow::Result<Vec<u32>> {
+        // 第 i 代写入 cells[1 - i % 2]
+        let last = GENERATIONS - 1;
+        gpu.read_buffer(&self.cells[(1 - last % 2) as usize]).await
+    }
+}
+
+fn main() -> anyhow::Result<()> {
+    let cells = block_on_compute::<Life>()?;
+    for row in cells.chunks(WIDTH as usize) {
+        let line = row
+            .iter()
+            .map(|&c| if c == 1 { '#' } else { '.' })
+            .collect::<String>();
+        println!("{line}");
+    }
+    println!(
+        "generation {GENERATIONS}: {} cells alive",
+        cells.iter().sum::<u32>()
+    );
+    Ok(())
+}
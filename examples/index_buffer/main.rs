@@ -12,8 +12,6 @@ use winit::{
     window::{Window, WindowId},
 };
 
-use tokio::runtime::Runtime;
-
 pub mod vertex;
 use vertex::Vertex;
 
@@ -274,12 +272,10 @@ impl ApplicationHandler for WgpuAppHandler {
             return;
         }
 
-        let rt = Runtime::new().unwrap();
-
         let window_attributes = Window::default_attributes().with_title("triangle");
         let window = Arc::new(event_loop.create_window(window_attributes).unwrap());
 
-        let wgpu_app = rt.block_on(WgpuApp::new(window));
+        let wgpu_app = futures::executor::block_on(WgpuApp::new(window));
 
         self.app.lock().unwrap().deref_mut().replace(wgpu_app);
     }
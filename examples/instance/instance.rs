@@ -5,14 +5,22 @@ use wgpu_dance::model::RenderVertex;
 pub struct Instance {
     pub position: glam::Vec3,
     pub rotation: glam::Quat,
+    /// 与纹理颜色相乘的颜色，默认为白色
+    pub tint: glam::Vec4,
+    /// 原样传给着色器的自定义数据，含义由着色器决定
+    pub user_data: glam::Vec4,
 }
 
 impl Instance {
+    pub fn model_matrix(&self) -> glam::Mat4 {
+        glam::Mat4::from_translation(self.position) * glam::Mat4::from_quat(self.rotation)
+    }
+
     pub fn to_raw(&self) -> InstanceRaw {
         InstanceRaw {
-            model: (glam::Mat4::from_translation(self.position)
-                * glam::Mat4::from_quat(self.rotation))
-            .to_cols_array_2d(),
+            model: self.model_matrix().to_cols_array_2d(),
+            tint: self.tint.to_array(),
+            user_data: self.user_data.to_array(),
         }
     }
 }
@@ -21,6 +29,8 @@ impl Instance {
 #[derive(Copy, Clone)]
 pub struct InstanceRaw {
     model: [[f32; 4]; 4],
+    tint: [f32; 4],
+    user_data: [f32; 4],
 }
 
 unsafe impl Zeroable for InstanceRaw {}
@@ -58,6 +68,17 @@ impl RenderVertex for InstanceRaw {
                     shader_location: 8,
                     format: wgpu::VertexFormat::Float32x4,
                 },
+                // 颜色与自定义数据紧跟在模型矩阵之后
+                wgpu::VertexAttribute {
+                    offset: mem::size_of::<[f32; 16]>() as wgpu::BufferAddress,
+                    shader_location: 9,
+                    format: wgpu::VertexFormat::Float32x4,
+                },
+                wgpu::VertexAttribute {
+                    offset: mem::size_of::<[f32; 20]>() as wgpu::BufferAddress,
+                    shader_location: 10,
+                    format: wgpu::VertexFormat::Float32x4,
+                },
             ],
         }
     }
@@ -5,13 +5,20 @@ use std::sync::Arc;
 
 use wgpu::util::DeviceExt;
 use wgpu_dance::{
-    app::{WindowApp, WindowAppHandler},
-    camera::{Camera, CameraBuddle},
+    app::{FrameTime, WindowApp, WindowAppHandler},
+    camera::{Camera, CameraBundle, KeyboardController, Projection},
+    frustum::{transform_aabb, Frustum},
     model::{Model, RenderVertex},
-    texture::Texture,
+    texture::{DepthConvention, Texture},
 };
 
-use winit::{dpi::PhysicalSize, event::KeyEvent, event_loop::EventLoop, window::Window};
+use winit::{
+    dpi::PhysicalSize,
+    event::{ElementState, KeyEvent, MouseScrollDelta, TouchPhase},
+    event_loop::EventLoop,
+    keyboard::{KeyCode, PhysicalKey},
+    window::Window,
+};
 
 const NUM_INSTANCES_PER_ROW: u32 = 10;
 const INSTANCE_DISPLACEMENT: glam::Vec3 = glam::Vec3::new(
@@ -19,6 +26,13 @@ const INSTANCE_DISPLACEMENT: glam::Vec3 = glam::Vec3::new(
     0.0,
     NUM_INSTANCES_PER_ROW as f32 * 0.5,
 );
+/// 四个象限各用一种颜色，类似不同队伍的单位
+const TEAM_COLORS: [glam::Vec4; 4] = [
+    glam::vec4(1.0, 0.55, 0.55, 1.0),
+    glam::vec4(0.55, 0.7, 1.0, 1.0),
+    glam::vec4(0.6, 1.0, 0.6, 1.0),
+    glam::vec4(1.0, 1.0, 0.55, 1.0),
+];
 
 struct App {
     device: wgpu::Device,
@@ -35,11 +49,54 @@ struct App {
     model: Model<vertex::Vertex>,
     instances: Vec<instance::Instance>,
     instance_buffer: wgpu::Buffer,
+    /// 高亮的实例
+    highlighted: usize,
+    /// 是否跳过视锥外的实例
+    culling: bool,
+    /// 本帧写入实例缓冲的实例数量
+    visible_count: u32,
 
     diffuse_bind_group: wgpu::BindGroup,
     depth_texture: Texture,
 
-    camera: CameraBuddle,
+    camera: CameraBundle,
+}
+
+impl App {
+    /// 只修改自定义数据中的高亮强度，无需为高亮的实例准备单独的材质
+    fn set_highlighted(&mut self, index: usize) {
+        self.instances[self.highlighted].user_data.x = 0.0;
+        self.instances[index].user_data.x = 1.0;
+        self.highlighted = index;
+    }
+
+    /// 只把与视锥相交的实例写入实例缓冲，绘制时的实例数量随之变化
+    fn upload_visible_instances(&mut self) {
+        let frustum = Frustum::from_camera(&self.camera.state);
+        let (min, max) = self.model.bounds();
+        let instance_data = self
+            .instances
+            .iter()
+            .filter(|instance| {
+                let (min, max) = transform_aabb(instance.model_matrix(), min, max);
+                !self.culling || frustum.intersects_aabb(min, max)
+            })
+            .map(instance::Instance::to_raw)
+            .collect::<Vec<_>>();
+        let visible_count = instance_data.len() as u32;
+        if visible_count != self.visible_count {
+            println!(
+                "drawing {visible_count} of {} instances",
+                self.instances.len()
+            );
+            self.visible_count = visible_count;
+        }
+        self.queue.write_buffer(
+            &self.instance_buffer,
+            0,
+            bytemuck::cast_slice(&instance_data),
+        );
+    }
 }
 
 impl WindowApp for App {
@@ -140,11 +197,15 @@ impl WindowApp for App {
             // 定义哪个方向朝上
             up: glam::Vec3::Y,
             aspect: surface_config.width as f32 / surface_config.height as f32,
-            fovy: 45.0,
+            projection: Projection::Perspective { fovy: 45.0 },
             znear: 0.1,
             zfar: 100.0,
+            depth: DepthConvention::Standard,
         };
-        let camera = CameraBuddle::new(camera, 0.2, &device);
+        let camera = CameraBundle::builder(camera)
+            .controller(KeyboardController::new(0.2))
+            .build(&device)
+            .unwrap();
 
         let depth_texture =
             Texture::create_depth_texture(&device, &surface_config, "depth_texture");
@@ -236,7 +297,16 @@ impl WindowApp for App {
                         )
                     };
 
-                    instance::Instance { position, rotation }
+                    let team = (x >= NUM_INSTANCES_PER_ROW / 2) as usize
+                        + 2 * (z >= NUM_INSTANCES_PER_ROW / 2) as usize;
+                    // 用一个简单的哈希让同一队伍内的明暗也有变化
+                    let variation = ((x * 7 + z * 13) % 10) as f32 / 9.0;
+                    instance::Instance {
+                        position,
+                        rotation,
+                        tint: TEAM_COLORS[team],
+                        user_data: glam::vec4(0.0, variation, 0.0, 0.0),
+                    }
                 })
             })
             .collect::<Vec<_>>();
@@ -247,10 +317,10 @@ impl WindowApp for App {
         let instance_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
             label: Some("Instance Buffer"),
             contents: bytemuck::cast_slice(&instance_data),
-            usage: wgpu::BufferUsages::VERTEX,
+            usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
         });
 
-        Self {
+        let mut app = Self {
             device,
             queue,
 
@@ -265,12 +335,18 @@ impl WindowApp for App {
             model,
             instances,
             instance_buffer,
+            highlighted: 0,
+            culling: true,
+            visible_count: 0,
 
             diffuse_bind_group,
             depth_texture,
 
             camera,
-        }
+        };
+        app.set_highlighted(0);
+        app.upload_visible_instances();
+        app
     }
 
     fn render(&mut self) -> Result<(), wgpu::SurfaceError> {
@@ -321,11 +397,7 @@ impl WindowApp for App {
             self.model.index_buffer.as_ref().unwrap().slice(..),
             wgpu::IndexFormat::Uint32,
         );
-        render_pass.draw_indexed(
-            0..(self.model.indices.len() as _),
-            0,
-            0..(self.instances.len() as _),
-        );
+        render_pass.draw_indexed(0..(self.model.indices.len() as _), 0, 0..self.visible_count);
 
         drop(render_pass);
 
@@ -361,11 +433,34 @@ impl WindowApp for App {
     }
 
     fn keyboard_input(&mut self, event: &KeyEvent) -> bool {
-        self.camera.controller.process_events(event)
+        if self.camera.process_key(event) {
+            return true;
+        }
+        // Tab 键把高亮移到下一个实例，C 键开关视锥剔除
+        match event.physical_key {
+            PhysicalKey::Code(KeyCode::Tab) if event.state == ElementState::Pressed => {
+                self.set_highlighted((self.highlighted + 1) % self.instances.len());
+                true
+            }
+            PhysicalKey::Code(KeyCode::KeyC) if event.state == ElementState::Pressed => {
+                self.culling = !self.culling;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn mouse_wheel(&mut self, delta: MouseScrollDelta, _phase: TouchPhase) -> bool {
+        self.camera.process_wheel(delta)
+    }
+
+    fn cameras_mut(&mut self) -> Vec<&mut CameraBundle> {
+        vec![&mut self.camera]
     }
 
-    fn update(&mut self) {
-        self.camera.update(&self.queue);
+    fn update(&mut self, time: FrameTime) {
+        self.camera.update(&self.queue, time.delta_secs());
+        self.upload_visible_instances();
     }
 }
 
@@ -16,11 +16,16 @@ struct InstanceInput {
     @location(6) model_matrix_1: vec4f,
     @location(7) model_matrix_2: vec4f,
     @location(8) model_matrix_3: vec4f,
+    @location(9) tint: vec4f,
+    // x: 高亮强度，y: 明暗变化，zw 未使用
+    @location(10) user_data: vec4f,
 };
 
 struct VertexOutput {
     @builtin(position) clip_position: vec4f,
     @location(0) tex_coords: vec2f,
+    @location(1) tint: vec4f,
+    @location(2) user_data: vec4f,
 }
 
 @vertex
@@ -36,6 +41,8 @@ fn vs_main(
         instance.model_matrix_3,
     );
     out.tex_coords = model.tex_coords;
+    out.tint = instance.tint;
+    out.user_data = instance.user_data;
     out.clip_position = camera.view_proj * model_matrix * vec4f(model.position, 1.0); // 2.
     return out;
 }
@@ -47,5 +54,7 @@ var s_diffuse: sampler;
 
 @fragment
 fn fs_main(in: VertexOutput) -> @location(0) vec4f {
-    return textureSample(t_diffuse, s_diffuse, in.tex_coords);
+    let color = textureSample(t_diffuse, s_diffuse, in.tex_coords) * in.tint;
+    let shade = color.rgb * (0.75 + 0.25 * in.user_data.y);
+    return vec4f(mix(shade, vec3f(1.0, 0.9, 0.3), 0.6 * in.user_data.x), color.a);
 }
\ No newline at end of file
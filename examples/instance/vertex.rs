@@ -1,5 +1,5 @@
 use bytemuck::{Pod, Zeroable};
-use wgpu_dance::model::RenderVertex;
+use wgpu_dance::model::{RenderVertex, VertexPosition};
 
 #[repr(C)]
 #[derive(Copy, Clone, Debug)]
@@ -11,6 +11,12 @@ pub struct Vertex {
 unsafe impl Zeroable for Vertex {}
 unsafe impl Pod for Vertex {}
 
+impl VertexPosition for Vertex {
+    fn position(&self) -> glam::Vec3 {
+        self.position.into()
+    }
+}
+
 impl RenderVertex for Vertex {
     fn buffer_layout_desc<'a>() -> wgpu::VertexBufferLayout<'a> {
         use core::mem;
@@ -0,0 +1,246 @@
+use std::sync::Arc;
+
+use wgpu_dance::{
+    app::{FrameTime, WindowApp, WindowAppHandler},
+    background::Background,
+    camera::{Camera, CameraBundle, KeyboardController, Projection},
+    context::{GpuContext, GpuContextOptions},
+    debug_draw::DebugDraw,
+    gizmo::LightGizmo,
+    light::{DirectionalLight, PointLight, SpotLight},
+    texture::{DepthConvention, Texture},
+};
+
+use winit::{
+    dpi::PhysicalSize,
+    event::{ElementState, KeyEvent, MouseScrollDelta, TouchPhase},
+    event_loop::EventLoop,
+    keyboard::{KeyCode, PhysicalKey},
+    window::Window,
+};
+
+const GRID_HALF_SIZE: i32 = 10;
+
+struct App {
+    gpu: GpuContext,
+
+    depth_texture: Texture,
+
+    camera: CameraBundle,
+    debug: DebugDraw,
+
+    sun: DirectionalLight,
+    point: PointLight,
+    spot: SpotLight,
+    /// 依次对应平行光、点光源与聚光灯
+    gizmos: [LightGizmo; 3],
+}
+
+impl App {
+    /// 让点光源上下浮动、聚光灯绕竖直轴摆动
+    fn animate_lights(&mut self, time: f32) {
+        self.point.position = glam::vec3(-4.0, 2.5 + time.sin(), 2.0);
+        let yaw = time * 0.5;
+        self.spot.direction = glam::vec3(yaw.cos() * 0.6, -1.0, yaw.sin() * 0.6).normalize();
+    }
+
+    fn build_gizmos(&mut self) {
+        self.debug.clear();
+
+        let grid = glam::vec4(0.5, 0.5, 0.55, 0.4);
+        let extent = GRID_HALF_SIZE as f32;
+        for i in -GRID_HALF_SIZE..=GRID_HALF_SIZE {
+            let i = i as f32;
+            self.debug.line(
+                glam::vec3(i, 0.0, -extent),
+                glam::vec3(i, 0.0, extent),
+                grid,
+            );
+            self.debug.line(
+                glam::vec3(-extent, 0.0, i),
+                glam::vec3(extent, 0.0, i),
+                grid,
+            );
+        }
+
+        self.debug.directional_light(&self.sun, &self.gizmos[0]);
+        self.debug.point_light(&self.point, &self.gizmos[1]);
+        self.debug.spot_light(&self.spot, &self.gizmos[2]);
+    }
+}
+
+impl WindowApp for App {
+    async fn new(window: Arc<Window>) -> Self {
+        let mut gpu = GpuContext::new(window, GpuContextOptions::default())
+            .await
+            .unwrap();
+        gpu.background = Background::Gradient {
+            top: wgpu::Color {
+                r: 0.06,
+                g: 0.07,
+                b: 0.1,
+                a: 1.0,
+            },
+            bottom: wgpu::Color {
+                r: 0.01,
+                g: 0.01,
+                b: 0.015,
+                a: 1.0,
+            },
+        };
+
+        let camera = Camera {
+            eye: (0.0, 10.0, 18.0).into(),
+            target: (0.0, 1.0, 0.0).into(),
+            up: glam::Vec3::Y,
+            aspect: gpu.aspect(),
+            projection: Projection::Perspective { fovy: 45.0 },
+            znear: 0.1,
+            zfar: 100.0,
+            depth: DepthConvention::Standard,
+        };
+        let camera = CameraBundle::builder(camera)
+            .controller(KeyboardController::new(0.2))
+            .build(&gpu.device)
+            .unwrap();
+
+        let depth_texture =
+            Texture::create_depth_texture(&gpu.device, &gpu.surface_config, "depth_texture");
+
+        let debug = DebugDraw::new(
+            &gpu.device,
+            gpu.format(),
+            Some(Texture::DEPTH_FORMAT),
+            &camera.bind_group_layout,
+        );
+
+        let sun = DirectionalLight {
+            color: glam::vec3(1.0, 0.9, 0.7),
+            intensity: 3.0,
+            ..Default::default()
+        };
+        let point = PointLight {
+            color: glam::vec3(0.3, 0.6, 1.0),
+            range: 3.0,
+            ..Default::default()
+        };
+        let spot = SpotLight {
+            position: glam::vec3(4.0, 6.0, 0.0),
+            color: glam::vec3(1.0, 0.3, 0.4),
+            range: 7.0,
+            ..Default::default()
+        };
+        let gizmos = [
+            LightGizmo {
+                anchor: glam::vec3(0.0, 1.0, 0.0),
+                shadow_radius: GRID_HALF_SIZE as f32,
+                ..Default::default()
+            },
+            LightGizmo::default(),
+            LightGizmo::default(),
+        ];
+
+        Self {
+            gpu,
+
+            depth_texture,
+
+            camera,
+            debug,
+
+            sun,
+            point,
+            spot,
+            gizmos,
+        }
+    }
+
+    fn render(&mut self) -> Result<(), wgpu::SurfaceError> {
+        self.resize_surface_if_needed();
+
+        let mut frame = self.gpu.begin_frame()?;
+
+        let mut render_pass = frame
+            .render_pass("Render Pass")
+            .depth(&self.depth_texture.view)
+            .begin();
+
+        self.debug.draw(&mut render_pass, &self.camera.bind_group);
+
+        drop(render_pass);
+
+        self.gpu.end_frame(frame);
+
+        Ok(())
+    }
+
+    fn set_window_resized(&mut self, new_size: PhysicalSize<u32>) {
+        self.gpu.resize(new_size);
+    }
+
+    fn resize_surface_if_needed(&mut self) {
+        if self.gpu.resize_if_needed() {
+            self.depth_texture = Texture::create_depth_texture(
+                &self.gpu.device,
+                &self.gpu.surface_config,
+                "depth_texture",
+            );
+        }
+    }
+
+    fn gpu_context(&mut self) -> Option<&mut GpuContext> {
+        Some(&mut self.gpu)
+    }
+
+    fn keyboard_input(&mut self, event: &KeyEvent) -> bool {
+        if self.camera.process_key(event) {
+            return true;
+        }
+        if event.state != ElementState::Pressed || event.repeat {
+            return false;
+        }
+        // 1/2/3 键分别切换平行光、点光源与聚光灯的显示，F 键切换阴影视锥
+        let index = match event.physical_key {
+            PhysicalKey::Code(KeyCode::Digit1) => 0,
+            PhysicalKey::Code(KeyCode::Digit2) => 1,
+            PhysicalKey::Code(KeyCode::Digit3) => 2,
+            PhysicalKey::Code(KeyCode::KeyF) => {
+                let show = !self.gizmos.iter().any(|gizmo| gizmo.shadow_frustum);
+                for gizmo in &mut self.gizmos {
+                    gizmo.shadow_frustum = show;
+                }
+                return true;
+            }
+            _ => return false,
+        };
+        self.gizmos[index].visible = !self.gizmos[index].visible;
+        true
+    }
+
+    fn mouse_wheel(&mut self, delta: MouseScrollDelta, _phase: TouchPhase) -> bool {
+        self.camera.process_wheel(delta)
+    }
+
+    fn cameras_mut(&mut self) -> Vec<&mut CameraBundle> {
+        vec![&mut self.camera]
+    }
+
+    fn update(&mut self, time: FrameTime) {
+        self.camera.update(&self.gpu.queue, time.delta_secs());
+
+        self.animate_lights(time.elapsed_secs());
+        self.build_gizmos();
+        self.debug.update(
+            &self.gpu.device,
+            &self.gpu.queue,
+            &self.camera.state,
+            self.gpu.size(),
+        );
+    }
+}
+
+fn main() -> Result<(), impl std::error::Error> {
+    let events_loop = EventLoop::new().unwrap();
+    let mut app = WindowAppHandler::<App>::new("light gizmos example");
+    events_loop.run_app(&mut app)
+}
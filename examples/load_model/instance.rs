@@ -5,14 +5,19 @@ use wgpu_dance::model::RenderVertex;
 pub struct Instance {
     pub position: glam::Vec3,
     pub rotation: glam::Quat,
+    /// 被鼠标选中时高亮显示
+    pub selected: bool,
 }
 
 impl Instance {
+    pub fn model_matrix(&self) -> glam::Mat4 {
+        glam::Mat4::from_translation(self.position) * glam::Mat4::from_quat(self.rotation)
+    }
+
     pub fn to_raw(&self) -> InstanceRaw {
         InstanceRaw {
-            model: (glam::Mat4::from_translation(self.position)
-                * glam::Mat4::from_quat(self.rotation))
-            .to_cols_array_2d(),
+            model: self.model_matrix().to_cols_array_2d(),
+            selected: self.selected as u32 as f32,
         }
     }
 }
@@ -21,6 +26,7 @@ impl Instance {
 #[derive(Copy, Clone)]
 pub struct InstanceRaw {
     model: [[f32; 4]; 4],
+    selected: f32,
 }
 
 unsafe impl Zeroable for InstanceRaw {}
@@ -59,6 +65,12 @@ impl RenderVertex for InstanceRaw {
                     shader_location: 3,
                     format: wgpu::VertexFormat::Float32x4,
                 },
+                // 模型顶点占用 4~6 号位置
+                wgpu::VertexAttribute {
+                    offset: mem::size_of::<[f32; 16]>() as wgpu::BufferAddress,
+                    shader_location: 7,
+                    format: wgpu::VertexFormat::Float32,
+                },
             ],
         }
     }
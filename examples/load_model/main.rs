@@ -1,17 +1,25 @@
 pub mod instance;
 pub mod vertex;
 
-use std::sync::Arc;
+use std::{collections::HashMap, path::PathBuf, sync::Arc};
 
 use wgpu::util::DeviceExt;
 use wgpu_dance::{
-    app::{WindowApp, WindowAppHandler},
-    camera::{Camera, CameraBuddle},
-    model::{DrawModel, MeshModel, RenderVertex},
-    texture::Texture,
+    app::{FrameTime, WindowApp, WindowAppHandler},
+    camera::{Camera, CameraBundle, KeyboardController, Projection},
+    model::{DrawModel, Material, MeshModel, RenderVertex},
+    phase::RenderPhase,
+    ray::Ray,
+    shader::{alpha_test_constants, ShaderLibrary},
+    texture::{DepthConvention, Texture},
 };
 
-use winit::{dpi::PhysicalSize, event::KeyEvent, event_loop::EventLoop, window::Window};
+use winit::{
+    dpi::{PhysicalPosition, PhysicalSize},
+    event::{ElementState, KeyEvent, MouseButton, MouseScrollDelta, TouchPhase},
+    event_loop::EventLoop,
+    window::Window,
+};
 
 const SPACE_BETWEEN: f32 = 3.0;
 const NUM_INSTANCES_PER_ROW: u32 = 10;
@@ -29,15 +37,140 @@ struct App {
     size: winit::dpi::PhysicalSize<u32>,
     size_changed: bool,
 
-    render_pipeline: wgpu::RenderPipeline,
+    render_pipeline_layout: wgpu::PipelineLayout,
+    /// 以镂空阈值的位模式为键的管线，阈值 0 为不透明管线
+    render_pipelines: HashMap<u32, wgpu::RenderPipeline>,
 
     obj_model: MeshModel,
     instances: Vec<instance::Instance>,
     instance_buffer: wgpu::Buffer,
+    cursor: glam::Vec2,
 
     depth_texture: Texture,
 
-    camera: CameraBuddle,
+    camera: CameraBundle,
+}
+
+/// 材质使用的镂空阈值，非镂空材质为 0
+fn material_cutoff(material: &Material) -> f32 {
+    match material.phase {
+        RenderPhase::AlphaTest => material.alpha_cutoff,
+        _ => 0.0,
+    }
+}
+
+/// `cutoff` 为 0 时是不透明管线，否则丢弃 alpha 低于该阈值的片元；
+/// GL 后端下变体不能共用着色器模块，见 `alpha_test_constants`
+fn create_render_pipeline(
+    device: &wgpu::Device,
+    layout: &wgpu::PipelineLayout,
+    format: wgpu::TextureFormat,
+    depth: DepthConvention,
+    cutoff: f32,
+) -> wgpu::RenderPipeline {
+    let shader = &ShaderLibrary::new()
+        .create_shader_module(device, "Shader", include_str!("shader.wgsl"))
+        .unwrap();
+    let constants = alpha_test_constants(cutoff);
+    let compilation_options = wgpu::PipelineCompilationOptions {
+        constants: &constants,
+        ..Default::default()
+    };
+    device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+        label: Some("Render Pipeline"),
+        layout: Some(layout),
+        vertex: wgpu::VertexState {
+            module: shader,
+            compilation_options: compilation_options.clone(),
+            entry_point: Some("vs_main"),
+            buffers: &[
+                instance::InstanceRaw::buffer_layout_desc(),
+                vertex::Vertex::buffer_layout_desc(),
+            ],
+        },
+        fragment: Some(wgpu::FragmentState {
+            module: shader,
+            compilation_options,
+            entry_point: Some("fs_main"),
+            targets: &[Some(wgpu::ColorTargetState {
+                format,
+                blend: Some(wgpu::BlendState::REPLACE),
+                write_mask: wgpu::ColorWrites::ALL,
+            })],
+        }),
+        primitive: wgpu::PrimitiveState {
+            topology: wgpu::PrimitiveTopology::TriangleList,
+            strip_index_format: None,
+            front_face: wgpu::FrontFace::Ccw,
+            cull_mode: Some(wgpu::Face::Back),
+            // 将此设置为 Fill 以外的任何值都要需要开启 Feature::NON_FILL_POLYGON_MODE
+            polygon_mode: wgpu::PolygonMode::Fill,
+            // 需要开启 Features::DEPTH_CLIP_CONTROL
+            unclipped_depth: false,
+            // 需要开启 Features::CONSERVATIVE_RASTERIZATION
+            conservative: false,
+        },
+        depth_stencil: Some(depth.depth_stencil_state(true)),
+        multisample: wgpu::MultisampleState {
+            count: 1,
+            mask: !0,
+            alpha_to_coverage_enabled: false,
+        },
+        multiview: None,
+        cache: None,
+    })
+}
+
+impl App {
+    /// 为模型中每种镂空阈值创建管线，已有的变体保留
+    fn create_pipelines(&mut self) {
+        for material in &self.obj_model.materials {
+            let cutoff = material_cutoff(material);
+            self.render_pipelines
+                .entry(cutoff.to_bits())
+                .or_insert_with(|| {
+                    create_render_pipeline(
+                        &self.device,
+                        &self.render_pipeline_layout,
+                        self.surface_config.format,
+                        self.camera.state.depth,
+                        cutoff,
+                    )
+                });
+        }
+    }
+
+    /// 射线最先穿过的实例；射线变换到实例的模型空间后与模型的包围盒求交
+    fn pick_instance(&self, ray: &Ray) -> Option<usize> {
+        let (min, max) = self.obj_model.bounding_box;
+        self.instances
+            .iter()
+            .enumerate()
+            .filter_map(|(i, instance)| {
+                let model = instance.model_matrix();
+                let local = ray.transform(model.inverse());
+                let t = local.intersect_aabb(min, max)?;
+                Some((i, model.transform_point3(local.at(t)).distance(ray.origin)))
+            })
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(i, _)| i)
+    }
+
+    fn select(&mut self, selected: Option<usize>) {
+        for (i, instance) in self.instances.iter_mut().enumerate() {
+            instance.selected = Some(i) == selected;
+        }
+        let instance_data = self
+            .instances
+            .iter()
+            .map(instance::Instance::to_raw)
+            .collect::<Vec<_>>();
+        self.queue.write_buffer(
+            &self.instance_buffer,
+            0,
+            bytemuck::cast_slice(&instance_data),
+        );
+    }
 }
 
 impl WindowApp for App {
@@ -94,19 +227,23 @@ impl WindowApp for App {
             // 定义哪个方向朝上
             up: glam::Vec3::Y,
             aspect: surface_config.width as f32 / surface_config.height as f32,
-            fovy: 45.0,
+            projection: Projection::Perspective { fovy: 45.0 },
             znear: 0.1,
-            zfar: 100.0,
+            // 反向深度：远平面可以放在无限远处，远处的深度精度也更高
+            zfar: f32::INFINITY,
+            depth: DepthConvention::Reversed,
         };
-        let camera = CameraBuddle::new(camera, 0.2, &device);
-
-        let depth_texture =
-            Texture::create_depth_texture(&device, &surface_config, "depth_texture");
+        let camera = CameraBundle::builder(camera)
+            .controller(KeyboardController::new(0.2))
+            .build(&device)
+            .unwrap();
 
-        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
-            label: Some("Shader"),
-            source: wgpu::ShaderSource::Wgsl(include_str!("shader.wgsl").into()),
-        });
+        let depth_texture = Texture::create_depth_texture_with(
+            &device,
+            &surface_config,
+            "depth_texture",
+            camera.state.depth,
+        );
 
         let render_pipeline_layout =
             device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
@@ -117,56 +254,6 @@ impl WindowApp for App {
                 ],
                 push_constant_ranges: &[],
             });
-        let render_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
-            label: Some("Render Pipeline"),
-            layout: Some(&render_pipeline_layout),
-            vertex: wgpu::VertexState {
-                module: &shader,
-                compilation_options: Default::default(),
-                entry_point: Some("vs_main"),
-                buffers: &[
-                    instance::InstanceRaw::buffer_layout_desc(),
-                    vertex::Vertex::buffer_layout_desc(),
-                ],
-            },
-            fragment: Some(wgpu::FragmentState {
-                module: &shader,
-                compilation_options: Default::default(),
-                entry_point: Some("fs_main"),
-                targets: &[Some(wgpu::ColorTargetState {
-                    format: surface_config.format,
-                    blend: Some(wgpu::BlendState::REPLACE),
-                    write_mask: wgpu::ColorWrites::ALL,
-                })],
-            }),
-            primitive: wgpu::PrimitiveState {
-                topology: wgpu::PrimitiveTopology::TriangleList,
-                strip_index_format: None,
-                front_face: wgpu::FrontFace::Ccw,
-                cull_mode: Some(wgpu::Face::Back),
-                // 将此设置为 Fill 以外的任何值都要需要开启 Feature::NON_FILL_POLYGON_MODE
-                polygon_mode: wgpu::PolygonMode::Fill,
-                // 需要开启 Features::DEPTH_CLIP_CONTROL
-                unclipped_depth: false,
-                // 需要开启 Features::CONSERVATIVE_RASTERIZATION
-                conservative: false,
-            },
-            depth_stencil: Some(wgpu::DepthStencilState {
-                format: Texture::DEPTH_FORMAT,
-                depth_write_enabled: true,
-                depth_compare: wgpu::CompareFunction::Less,
-                stencil: wgpu::StencilState::default(),
-                bias: wgpu::DepthBiasState::default(),
-            }),
-            multisample: wgpu::MultisampleState {
-                count: 1,
-                mask: !0,
-                alpha_to_coverage_enabled: false,
-            },
-            multiview: None,
-            cache: None,
-        });
-
         let obj_model = MeshModel::load_model::<vertex::Vertex>(
             "cube.obj",
             &device,
@@ -193,7 +280,11 @@ impl WindowApp for App {
                         )
                     };
 
-                    instance::Instance { position, rotation }
+                    instance::Instance {
+                        position,
+                        rotation,
+                        selected: false,
+                    }
                 })
             })
             .collect::<Vec<_>>();
@@ -204,10 +295,10 @@ impl WindowApp for App {
         let instance_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
             label: Some("Instance Buffer"),
             contents: bytemuck::cast_slice(&instance_data),
-            usage: wgpu::BufferUsages::VERTEX,
+            usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
         });
 
-        Self {
+        let mut app = Self {
             frame_count: 0,
             last_record_time: std::time::Instant::now(),
 
@@ -224,13 +315,17 @@ impl WindowApp for App {
 
             depth_texture,
 
-            render_pipeline,
+            render_pipeline_layout,
+            render_pipelines: HashMap::new(),
 
             obj_model,
 
             instances,
             instance_buffer,
-        }
+            cursor: glam::Vec2::ZERO,
+        };
+        app.create_pipelines();
+        app
     }
 
     fn render(&mut self) -> Result<(), wgpu::SurfaceError> {
@@ -264,21 +359,22 @@ impl WindowApp for App {
             })],
             depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
                 view: &self.depth_texture.view,
-                depth_ops: Some(wgpu::Operations {
-                    load: wgpu::LoadOp::Clear(1.0),
-                    store: wgpu::StoreOp::Store,
-                }),
+                depth_ops: Some(self.camera.state.depth.clear_ops()),
                 stencil_ops: None,
             }),
             ..Default::default()
         });
-        render_pass.set_pipeline(&self.render_pipeline);
         render_pass.set_vertex_buffer(0, self.instance_buffer.slice(..));
-        render_pass.draw_model_instanced(
-            &self.obj_model,
-            0..self.instances.len() as u32,
-            &self.camera.bind_group,
-        );
+        for mesh in &self.obj_model.meshes {
+            let material = &self.obj_model.materials[mesh.material];
+            render_pass.set_pipeline(&self.render_pipelines[&material_cutoff(material).to_bits()]);
+            render_pass.draw_mesh_instanced(
+                mesh,
+                material,
+                0..self.instances.len() as u32,
+                &self.camera.bind_group,
+            );
+        }
 
         drop(render_pass);
 
@@ -307,17 +403,75 @@ impl WindowApp for App {
             self.surface_config.width = self.size.width;
             self.surface_config.height = self.size.height;
             self.surface.configure(&self.device, &self.surface_config);
-            self.depth_texture =
-                Texture::create_depth_texture(&self.device, &self.surface_config, "depth_texture");
+            self.depth_texture = Texture::create_depth_texture_with(
+                &self.device,
+                &self.surface_config,
+                "depth_texture",
+                self.camera.state.depth,
+            );
             self.size_changed = false;
         }
     }
 
     fn keyboard_input(&mut self, event: &KeyEvent) -> bool {
-        self.camera.controller.process_events(event)
+        self.camera.process_key(event)
+    }
+
+    fn mouse_wheel(&mut self, delta: MouseScrollDelta, _phase: TouchPhase) -> bool {
+        self.camera.process_wheel(delta)
+    }
+
+    /// 左键选中光标下的实例，点击空白处取消选中
+    fn mouse_click(&mut self, state: ElementState, button: MouseButton) -> bool {
+        if button != MouseButton::Left || state != ElementState::Pressed {
+            return false;
+        }
+        let ray = self.camera.state.screen_ray(self.cursor, self.size);
+        let selected = self.pick_instance(&ray);
+        if let Some(i) = selected {
+            println!("selected instance {i} at {}", self.instances[i].position);
+        }
+        self.select(selected);
+        true
+    }
+
+    fn cursor_move(&mut self, position: PhysicalPosition<f64>) -> bool {
+        self.cursor = glam::vec2(position.x as f32, position.y as f32);
+        false
+    }
+
+    /// 拖入窗口的 OBJ 文件替换当前模型，加载失败时保留原模型
+    fn file_dropped(&mut self, path: PathBuf) {
+        if !path
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("obj"))
+        {
+            eprintln!("ignoring dropped file {}: not an OBJ model", path.display());
+            return;
+        }
+        let model = futures::executor::block_on(MeshModel::load_model_from_path::<vertex::Vertex>(
+            &path,
+            &self.device,
+            &self.queue,
+            &Texture::texture_bind_group_layout(&self.device),
+        ));
+        match model {
+            Ok(model) if model.materials.is_empty() => {
+                eprintln!("failed to load {}: model has no material", path.display());
+            }
+            Ok(model) => {
+                self.obj_model = model;
+                self.create_pipelines();
+            }
+            Err(e) => eprintln!("failed to load {}: {e:#}", path.display()),
+        }
+    }
+
+    fn cameras_mut(&mut self) -> Vec<&mut CameraBundle> {
+        vec![&mut self.camera]
     }
 
-    fn update(&mut self) {
+    fn update(&mut self, time: FrameTime) {
         self.frame_count += 1;
 
         if self.frame_count == 100 {
@@ -330,7 +484,7 @@ impl WindowApp for App {
             println!("frame rate = {:.2}", frame_rate);
         }
 
-        self.camera.update(&self.queue);
+        self.camera.update(&self.queue, time.delta_secs());
     }
 }
 
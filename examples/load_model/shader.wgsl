@@ -1,3 +1,5 @@
+#include "wgpu_dance/alpha_test.wgsl"
+
 struct CameraUniform {
     view_proj: mat4x4f,
 };
@@ -12,11 +14,14 @@ struct InstanceInput {
     @location(1) model_matrix_1: vec4f,
     @location(2) model_matrix_2: vec4f,
     @location(3) model_matrix_3: vec4f,
+    // 1 表示被选中
+    @location(7) selected: f32,
 };
 
 struct VertexOutput {
     @builtin(position) clip_position: vec4f,
     @location(0) tex_coords: vec2f,
+    @location(1) selected: f32,
 }
 
 @group(0) @binding(0) // 1.
@@ -35,6 +40,7 @@ fn vs_main(
         instance.model_matrix_3,
     );
     out.tex_coords = model.tex_coords;
+    out.selected = instance.selected;
     out.clip_position = camera.view_proj * model_matrix * vec4f(model.position, 1.0); // 2.
     return out;
 }
@@ -46,5 +52,9 @@ var s_diffuse: sampler;
 
 @fragment
 fn fs_main(in: VertexOutput) -> @location(0) vec4f {
-    return textureSample(t_diffuse, s_diffuse, in.tex_coords);
+    let color = textureSample(t_diffuse, s_diffuse, in.tex_coords);
+    // 镂空材质的管线填入了 alpha_cutoff，其余管线不丢弃片元
+    alpha_test(color.a);
+    // 选中的实例向橙色偏移
+    return mix(color, vec4f(1.0, 0.6, 0.1, 1.0), in.selected * 0.5);
 }
\ No newline at end of file
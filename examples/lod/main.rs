@@ -0,0 +1,339 @@
+pub mod vertex;
+
+use std::sync::Arc;
+
+use wgpu::util::DeviceExt;
+use wgpu_dance::{
+    app::{FrameTime, WindowApp, WindowAppHandler},
+    background::Background,
+    camera::{Camera, CameraBundle, KeyboardController, Projection},
+    context::{GpuContext, GpuContextOptions},
+    debug_draw::DebugDraw,
+    light::{DirectionalLight, DirectionalLightBundle},
+    lod::{pack_lod_levels, LodCuller},
+    model::RenderVertex,
+    phase::{RenderPhase, RenderPhases, SortKey},
+    pipeline::{PipelineBuilder, ReflectedShader},
+    scatter::{self, CulledInstanceRaw, MeshSurface, ScatterSettings},
+    shader::ShaderLibrary,
+    texture::{DepthConvention, Texture},
+};
+
+use winit::{
+    dpi::PhysicalSize,
+    event::{ElementState, KeyEvent, MouseScrollDelta, TouchPhase},
+    event_loop::EventLoop,
+    keyboard::{KeyCode, PhysicalKey},
+    window::Window,
+};
+
+use vertex::{uv_sphere, SphereVertex};
+
+const GROUND_HALF_SIZE: f32 = 200.0;
+/// 每个等级球面的 (纬线分段, 经线分段, 最大距离)
+const LEVELS: [(u32, u32, f32); 4] = [
+    (32, 64, 25.0),
+    (16, 32, 60.0),
+    (8, 16, 110.0),
+    (4, 8, 180.0),
+];
+
+struct App {
+    gpu: GpuContext,
+
+    depth_texture: Texture,
+
+    camera: CameraBundle,
+    light: DirectionalLightBundle,
+    debug: DebugDraw,
+
+    render_pipeline: wgpu::RenderPipeline,
+    level_pipeline: wgpu::RenderPipeline,
+    show_levels: bool,
+
+    vertex_buffer: wgpu::Buffer,
+    index_buffer: wgpu::Buffer,
+    culler: LodCuller,
+}
+
+impl WindowApp for App {
+    async fn new(window: Arc<Window>) -> Self {
+        let mut gpu = GpuContext::new(window, GpuContextOptions::default())
+            .await
+            .unwrap();
+        gpu.background = Background::Color(wgpu::Color {
+            r: 0.02,
+            g: 0.02,
+            b: 0.03,
+            a: 1.0,
+        });
+
+        let camera = Camera {
+            eye: (0.0, 15.0, 60.0).into(),
+            target: (0.0, 2.0, 0.0).into(),
+            up: glam::Vec3::Y,
+            aspect: gpu.aspect(),
+            projection: Projection::Perspective { fovy: 60.0 },
+            znear: 0.1,
+            zfar: 400.0,
+            depth: DepthConvention::Standard,
+        };
+        let camera = CameraBundle::builder(camera)
+            .controller(KeyboardController::new(0.6))
+            .build(&gpu.device)
+            .unwrap();
+
+        let depth_texture =
+            Texture::create_depth_texture(&gpu.device, &gpu.surface_config, "depth_texture");
+
+        let light = DirectionalLightBundle::new(DirectionalLight::default(), &gpu.device);
+
+        let mut debug = DebugDraw::new(
+            &gpu.device,
+            gpu.format(),
+            Some(Texture::DEPTH_FORMAT),
+            &camera.bind_group_layout,
+        );
+        let step = 10.0;
+        let lines = (GROUND_HALF_SIZE / step) as i32;
+        for i in -lines..=lines {
+            let i = i as f32 * step;
+            let color = glam::vec4(0.4, 0.45, 0.4, 0.5);
+            debug.line(
+                glam::vec3(i, 0.0, -GROUND_HALF_SIZE),
+                glam::vec3(i, 0.0, GROUND_HALF_SIZE),
+                color,
+            );
+            debug.line(
+                glam::vec3(-GROUND_HALF_SIZE, 0.0, i),
+                glam::vec3(GROUND_HALF_SIZE, 0.0, i),
+                color,
+            );
+        }
+
+        // 平坦地面上散布的球体，球心抬高到与地面相切
+        let ground = [
+            glam::vec3(-GROUND_HALF_SIZE, 0.0, -GROUND_HALF_SIZE),
+            glam::vec3(GROUND_HALF_SIZE, 0.0, -GROUND_HALF_SIZE),
+            glam::vec3(GROUND_HALF_SIZE, 0.0, GROUND_HALF_SIZE),
+            glam::vec3(-GROUND_HALF_SIZE, 0.0, GROUND_HALF_SIZE),
+        ];
+        let mut instances = scatter::scatter(
+            &MeshSurface::new(&ground, &[0, 2, 1, 0, 3, 2]),
+            &ScatterSettings {
+                min_distance: 4.0,
+                scale_range: (0.6, 1.6),
+                align_to_normal: 0.0,
+                ..Default::default()
+            },
+        );
+        for instance in &mut instances {
+            instance.position.y += instance.scale;
+        }
+
+        let meshes = LEVELS.map(|(rings, segments, _)| uv_sphere(rings, segments));
+        let levels = meshes
+            .iter()
+            .zip(LEVELS)
+            .map(|((vertices, indices), (_, _, distance))| {
+                (vertices.as_slice(), indices.as_slice(), distance)
+            })
+            .collect::<Vec<_>>();
+        let (vertices, indices, levels) = pack_lod_levels(&levels);
+        let vertex_buffer = gpu
+            .device
+            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("LOD Sphere Vertex Buffer"),
+                contents: bytemuck::cast_slice(&vertices),
+                usage: wgpu::BufferUsages::VERTEX,
+            });
+        let index_buffer = gpu
+            .device
+            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("LOD Sphere Index Buffer"),
+                contents: bytemuck::cast_slice(&indices),
+                usage: wgpu::BufferUsages::INDEX,
+            });
+        let culler = LodCuller::new(&gpu.device, &instances, 1.0, &levels).unwrap();
+
+        let shader = ReflectedShader::new(
+            &gpu.device,
+            &ShaderLibrary::new(),
+            "LOD Shader",
+            include_str!("shader.wgsl"),
+        )
+        .unwrap();
+        let pipeline = |fragment_entry| {
+            PipelineBuilder::from_reflection(&shader)
+                .label("LOD Pipeline")
+                .fragment_entry(Some(fragment_entry))
+                .bind_group_layout(
+                    0,
+                    &camera.bind_group_layout,
+                    &[CameraBundle::layout_entry(wgpu::ShaderStages::VERTEX)],
+                )
+                .bind_group_layout(
+                    1,
+                    &light.bind_group_layout,
+                    &[DirectionalLightBundle::layout_entry()],
+                )
+                .vertex_buffer(SphereVertex::buffer_layout_desc())
+                .vertex_buffer(CulledInstanceRaw::buffer_layout_desc())
+                .color_target(wgpu::ColorTargetState {
+                    format: gpu.format(),
+                    blend: Some(wgpu::BlendState::REPLACE),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })
+                .primitive(wgpu::PrimitiveState {
+                    cull_mode: Some(wgpu::Face::Back),
+                    ..Default::default()
+                })
+                .depth_stencil(wgpu::DepthStencilState {
+                    format: Texture::DEPTH_FORMAT,
+                    depth_write_enabled: true,
+                    depth_compare: wgpu::CompareFunction::Less,
+                    stencil: wgpu::StencilState::default(),
+                    bias: wgpu::DepthBiasState::default(),
+                })
+                .build(&gpu.device)
+                .unwrap()
+                .pipeline
+        };
+        let render_pipeline = pipeline("fs_main");
+        let level_pipeline = pipeline("fs_levels");
+
+        Self {
+            gpu,
+
+            depth_texture,
+
+            camera,
+            light,
+            debug,
+
+            render_pipeline,
+            level_pipeline,
+            show_levels: false,
+
+            vertex_buffer,
+            index_buffer,
+            culler,
+        }
+    }
+
+    fn render(&mut self) -> Result<(), wgpu::SurfaceError> {
+        self.resize_surface_if_needed();
+
+        let mut frame = self.gpu.begin_frame()?;
+
+        self.culler.cull(&mut frame.encoder);
+
+        let mut render_pass = frame
+            .render_pass("Render Pass")
+            .depth(&self.depth_texture.view)
+            .begin();
+
+        // 提交顺序与绘制顺序无关，网格线属于 Overlay 阶段，总在球体之后绘制
+        let mut phases = RenderPhases::new();
+        phases.push(RenderPhase::Overlay, SortKey::default(), |pass| {
+            self.debug.draw(pass, &self.camera.bind_group)
+        });
+        phases.push(RenderPhase::Opaque, SortKey::default(), |pass| {
+            pass.set_pipeline(if self.show_levels {
+                &self.level_pipeline
+            } else {
+                &self.render_pipeline
+            });
+            pass.set_bind_group(0, &self.camera.bind_group, &[]);
+            pass.set_bind_group(1, &self.light.bind_group, &[]);
+            pass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
+            pass.set_index_buffer(self.index_buffer.slice(..), wgpu::IndexFormat::Uint32);
+            self.culler.draw(pass, 1);
+        });
+        phases.draw(&mut render_pass);
+
+        drop(render_pass);
+
+        self.gpu.end_frame(frame);
+
+        Ok(())
+    }
+
+    fn set_window_resized(&mut self, new_size: PhysicalSize<u32>) {
+        self.gpu.resize(new_size);
+    }
+
+    fn resize_surface_if_needed(&mut self) {
+        if self.gpu.resize_if_needed() {
+            self.depth_texture = Texture::create_depth_texture(
+                &self.gpu.device,
+                &self.gpu.surface_config,
+                "depth_texture",
+            );
+        }
+    }
+
+    fn gpu_context(&mut self) -> Option<&mut GpuContext> {
+        Some(&mut self.gpu)
+    }
+
+    fn keyboard_input(&mut self, event: &KeyEvent) -> bool {
+        if self.camera.process_key(event) {
+            return true;
+        }
+        if event.state != ElementState::Pressed {
+            return false;
+        }
+        // L 键按等级着色，[ 与 ] 键调整 LOD 距离系数，F11 切换全屏，G 键捕获光标
+        match event.physical_key {
+            PhysicalKey::Code(KeyCode::KeyL) if !event.repeat => {
+                self.show_levels = !self.show_levels;
+                true
+            }
+            PhysicalKey::Code(KeyCode::F11) if !event.repeat => {
+                self.gpu.window().toggle_fullscreen();
+                true
+            }
+            PhysicalKey::Code(KeyCode::KeyG) if !event.repeat => {
+                if let Err(e) = self.gpu.window().toggle_cursor_grab() {
+                    eprintln!("{e:#}");
+                }
+                true
+            }
+            PhysicalKey::Code(KeyCode::BracketLeft) => {
+                self.culler.lod_bias = (self.culler.lod_bias / 1.1).max(0.1);
+                true
+            }
+            PhysicalKey::Code(KeyCode::BracketRight) => {
+                self.culler.lod_bias = (self.culler.lod_bias * 1.1).min(4.0);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn mouse_wheel(&mut self, delta: MouseScrollDelta, _phase: TouchPhase) -> bool {
+        self.camera.process_wheel(delta)
+    }
+
+    fn cameras_mut(&mut self) -> Vec<&mut CameraBundle> {
+        vec![&mut self.camera]
+    }
+
+    fn update(&mut self, time: FrameTime) {
+        self.camera.update(&self.gpu.queue, time.delta_secs());
+        self.culler.update(&self.gpu.queue, &self.camera.state);
+        self.debug.update(
+            &self.gpu.device,
+            &self.gpu.queue,
+            &self.camera.state,
+            self.gpu.size(),
+        );
+    }
+}
+
+fn main() -> Result<(), impl std::error::Error> {
+    let events_loop = EventLoop::new().unwrap();
+    let mut app = WindowAppHandler::<App>::new("GPU LOD example");
+    events_loop.run_app(&mut app)
+}
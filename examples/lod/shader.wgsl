@@ -0,0 +1,74 @@
+#include "wgpu_dance/camera.wgsl"
+#include "wgpu_dance/lighting.wgsl"
+
+struct VertexInput {
+    @location(4) position: vec3f,
+    @location(5) normal: vec3f,
+}
+
+struct InstanceInput {
+    @location(0) model_matrix_0: vec4f,
+    @location(1) model_matrix_1: vec4f,
+    @location(2) model_matrix_2: vec4f,
+    @location(3) model_matrix_3: vec4f,
+    // x: 淡出系数, y: LOD 等级
+    @location(7) fade: vec4f,
+};
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4f,
+    @location(0) world_normal: vec3f,
+    @location(1) fade: f32,
+    @location(2) level: f32,
+}
+
+@group(0) @binding(0)
+var<uniform> camera: CameraUniform;
+
+@group(1) @binding(0)
+var<uniform> sun: DirectionalLight;
+
+@vertex
+fn vs_main(model: VertexInput, instance: InstanceInput) -> VertexOutput {
+    let model_matrix = mat4x4f(
+        instance.model_matrix_0,
+        instance.model_matrix_1,
+        instance.model_matrix_2,
+        instance.model_matrix_3,
+    );
+    var out: VertexOutput;
+    out.world_normal = (model_matrix * vec4f(model.normal, 0.0)).xyz;
+    out.clip_position = camera.view_proj * model_matrix * vec4f(model.position, 1.0);
+    out.fade = instance.fade.x;
+    out.level = instance.fade.y;
+    return out;
+}
+
+fn interleaved_gradient_noise(pixel: vec2f) -> f32 {
+    return fract(52.9829189 * fract(dot(pixel, vec2f(0.06711056, 0.00583715))));
+}
+
+fn shade(in: VertexOutput, albedo: vec3f) -> vec4f {
+    if (in.fade < interleaved_gradient_noise(in.clip_position.xy)) {
+        discard;
+    }
+    let diffuse = lambert(normalize(in.world_normal), sun.direction.xyz) * sun.direction.w;
+    return vec4f(albedo * (sun.color.rgb * diffuse + 0.2), 1.0);
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4f {
+    return shade(in, vec3f(0.8, 0.75, 0.7));
+}
+
+// 按 LOD 等级着色：红、黄、绿、蓝
+@fragment
+fn fs_levels(in: VertexOutput) -> @location(0) vec4f {
+    let colors = array<vec3f, 4>(
+        vec3f(0.9, 0.2, 0.2),
+        vec3f(0.9, 0.8, 0.2),
+        vec3f(0.2, 0.8, 0.3),
+        vec3f(0.2, 0.4, 0.9),
+    );
+    return shade(in, colors[min(u32(in.level + 0.5), 3u)]);
+}
@@ -0,0 +1,55 @@
+use bytemuck::{Pod, Zeroable};
+
+use wgpu_dance::model::RenderVertex;
+
+/// 球面顶点，使用 4、5 号位置，避开实例数据占用的 0~3 与 7 号位置
+#[repr(C)]
+#[derive(Copy, Clone, Debug)]
+pub struct SphereVertex {
+    pub position: [f32; 3],
+    pub normal: [f32; 3],
+}
+
+unsafe impl Zeroable for SphereVertex {}
+unsafe impl Pod for SphereVertex {}
+
+impl RenderVertex for SphereVertex {
+    fn buffer_layout_desc<'a>() -> wgpu::VertexBufferLayout<'a> {
+        const ATTRIBUTES: [wgpu::VertexAttribute; 2] = wgpu::vertex_attr_array![
+            4 => Float32x3,
+            5 => Float32x3,
+        ];
+        wgpu::VertexBufferLayout {
+            array_stride: std::mem::size_of::<SphereVertex>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Vertex,
+            attributes: &ATTRIBUTES,
+        }
+    }
+}
+
+/// 经纬度划分的单位球，`rings` 为纬线方向的分段数
+pub fn uv_sphere(rings: u32, segments: u32) -> (Vec<SphereVertex>, Vec<u32>) {
+    let mut vertices = Vec::new();
+    for ring in 0..=rings {
+        let phi = ring as f32 / rings as f32 * std::f32::consts::PI;
+        for segment in 0..=segments {
+            let theta = segment as f32 / segments as f32 * std::f32::consts::TAU;
+            let normal = glam::vec3(phi.sin() * theta.cos(), phi.cos(), phi.sin() * theta.sin());
+            vertices.push(SphereVertex {
+                position: normal.to_array(),
+                normal: normal.to_array(),
+            });
+        }
+    }
+    let stride = segments + 1;
+    let indices = (0..rings)
+        .flat_map(|ring| {
+            (0..segments).flat_map(move |segment| {
+                let a = ring * stride + segment;
+                let b = a + stride;
+                [a, a + 1, b, a + 1, b + 1, b]
+            })
+        })
+        .collect();
+    (vertices, indices)
+}
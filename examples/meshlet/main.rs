@@ -0,0 +1,359 @@
+pub mod vertex;
+
+use std::{sync::Arc, time::Duration};
+
+use wgpu::util::DeviceExt;
+use wgpu_dance::{
+    app::{FrameTime, WindowApp, WindowAppHandler},
+    camera::{Camera, CameraBundle, KeyboardController, Projection},
+    context::{GpuContext, GpuContextOptions},
+    debug_draw::DebugDraw,
+    light::{DirectionalLight, DirectionalLightBundle},
+    meshlet::{MeshletCuller, MeshletMesh},
+    model::RenderVertex,
+    pipeline::{PipelineBuilder, ReflectedShader},
+    profiler::GpuProfiler,
+    resolution::{DynamicResolution, ResolutionController, UpscaleFilter},
+    shader::ShaderLibrary,
+    texture::{DepthConvention, Texture},
+};
+
+use winit::{
+    dpi::PhysicalSize,
+    event::{ElementState, KeyEvent, MouseScrollDelta, TouchPhase},
+    event_loop::EventLoop,
+    keyboard::{KeyCode, PhysicalKey},
+    window::Window,
+};
+
+use vertex::{push_blob, BlobVertex};
+
+/// 每行每列的球体数量，以及每个球体的纬线分段数
+const GRID: i32 = 6;
+const RINGS: u32 = 128;
+const SPACING: f32 = 6.0;
+
+struct App {
+    gpu: GpuContext,
+
+    /// 场景以动态分辨率渲染，再放大到 surface
+    resolution: DynamicResolution,
+
+    camera: CameraBundle,
+    light: DirectionalLightBundle,
+    debug: DebugDraw,
+
+    render_pipeline: wgpu::RenderPipeline,
+
+    vertex_buffer: wgpu::Buffer,
+    culler: MeshletCuller,
+    profiler: GpuProfiler,
+    /// 冻结时剔除使用的相机，便于移开视角观察被剔除的部分
+    frozen_camera: Option<Camera>,
+}
+
+impl WindowApp for App {
+    async fn new(window: Arc<Window>) -> Self {
+        let gpu = GpuContext::new(
+            window,
+            GpuContextOptions {
+                optional_features: wgpu::Features::PIPELINE_STATISTICS_QUERY,
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+
+        let camera = Camera {
+            eye: (0.0, 10.0, 40.0).into(),
+            target: (0.0, 0.0, 0.0).into(),
+            up: glam::Vec3::Y,
+            aspect: gpu.aspect(),
+            projection: Projection::Perspective { fovy: 60.0 },
+            znear: 0.1,
+            zfar: 200.0,
+            depth: DepthConvention::Standard,
+        };
+        let camera = CameraBundle::builder(camera)
+            .controller(KeyboardController::new(0.3))
+            .build(&gpu.device)
+            .unwrap();
+
+        // 垂直同步下帧时间不会低于刷新间隔，目标略低于 60 Hz 的刷新间隔
+        let resolution = DynamicResolution::new(
+            &gpu.device,
+            &gpu.surface_config,
+            ResolutionController::new(Duration::from_secs_f64(1.0 / 62.0)),
+        );
+
+        let light = DirectionalLightBundle::new(DirectionalLight::default(), &gpu.device);
+
+        let debug = DebugDraw::new(
+            &gpu.device,
+            gpu.format(),
+            Some(Texture::DEPTH_FORMAT),
+            &camera.bind_group_layout,
+        );
+
+        let mut vertices = Vec::new();
+        let mut indices = Vec::new();
+        for z in 0..GRID {
+            for x in 0..GRID {
+                let center = glam::vec3(
+                    (x as f32 - (GRID - 1) as f32 * 0.5) * SPACING,
+                    0.0,
+                    (z as f32 - (GRID - 1) as f32 * 0.5) * SPACING,
+                );
+                push_blob(&mut vertices, &mut indices, center, 2.0, RINGS, RINGS * 2);
+            }
+        }
+        let positions = vertices
+            .iter()
+            .map(|v| glam::Vec3::from(v.position))
+            .collect::<Vec<_>>();
+        let mesh = MeshletMesh::build(&positions, &indices);
+        println!(
+            "{} triangles in {} meshlets",
+            mesh.triangle_count(),
+            mesh.meshlets.len()
+        );
+        let vertex_buffer = gpu
+            .device
+            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("Meshlet Vertex Buffer"),
+                contents: bytemuck::cast_slice(&vertices),
+                usage: wgpu::BufferUsages::VERTEX,
+            });
+        let culler = MeshletCuller::new(&gpu.device, &mesh).unwrap();
+        let profiler = GpuProfiler::new(&gpu.device, 1);
+
+        let shader = ReflectedShader::new(
+            &gpu.device,
+            &ShaderLibrary::new(),
+            "Meshlet Shader",
+            include_str!("shader.wgsl"),
+        )
+        .unwrap();
+        let render_pipeline = PipelineBuilder::from_reflection(&shader)
+            .label("Meshlet Pipeline")
+            .bind_group_layout(
+                0,
+                &camera.bind_group_layout,
+                &[CameraBundle::layout_entry(wgpu::ShaderStages::VERTEX)],
+            )
+            .bind_group_layout(
+                1,
+                &light.bind_group_layout,
+                &[DirectionalLightBundle::layout_entry()],
+            )
+            .vertex_buffer(BlobVertex::buffer_layout_desc())
+            .color_target(wgpu::ColorTargetState {
+                format: gpu.format(),
+                blend: Some(wgpu::BlendState::REPLACE),
+                write_mask: wgpu::ColorWrites::ALL,
+            })
+            .primitive(wgpu::PrimitiveState {
+                cull_mode: Some(wgpu::Face::Back),
+                ..Default::default()
+            })
+            .depth_stencil(wgpu::DepthStencilState {
+                format: Texture::DEPTH_FORMAT,
+                depth_write_enabled: true,
+                depth_compare: wgpu::CompareFunction::Less,
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState::default(),
+            })
+            .build(&gpu.device)
+            .unwrap()
+            .pipeline;
+
+        Self {
+            gpu,
+
+            resolution,
+
+            camera,
+            light,
+            debug,
+
+            render_pipeline,
+
+            vertex_buffer,
+            culler,
+            profiler,
+            frozen_camera: None,
+        }
+    }
+
+    fn render(&mut self) -> Result<(), wgpu::SurfaceError> {
+        self.resize_surface_if_needed();
+
+        let frame = self.gpu.current_frame()?;
+
+        let mut encoder = self
+            .gpu
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("Render Encoder"),
+            });
+
+        self.culler.cull(&mut encoder);
+
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Render Pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: self.resolution.color_view(),
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(wgpu::Color {
+                        r: 0.02,
+                        g: 0.02,
+                        b: 0.03,
+                        a: 1.0,
+                    }),
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                view: self.resolution.depth_view(),
+                depth_ops: Some(wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(1.0),
+                    store: wgpu::StoreOp::Store,
+                }),
+                stencil_ops: None,
+            }),
+            occlusion_query_set: self.profiler.occlusion_query_set(),
+            ..Default::default()
+        });
+
+        self.resolution.set_viewport(&mut render_pass);
+        self.profiler.begin(&mut render_pass, "meshlets");
+        render_pass.set_pipeline(&self.render_pipeline);
+        render_pass.set_bind_group(0, &self.camera.bind_group, &[]);
+        render_pass.set_bind_group(1, &self.light.bind_group, &[]);
+        render_pass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
+        self.culler.draw(&mut render_pass);
+        self.profiler.end(&mut render_pass);
+
+        self.debug.draw(&mut render_pass, &self.camera.bind_group);
+
+        drop(render_pass);
+
+        self.resolution
+            .upscale(&self.gpu.queue, &mut encoder, &frame.view);
+
+        self.profiler.resolve(&mut encoder);
+        self.gpu.queue.submit(Some(encoder.finish()));
+        self.profiler.map();
+        self.gpu.present(frame);
+
+        Ok(())
+    }
+
+    fn set_window_resized(&mut self, new_size: PhysicalSize<u32>) {
+        self.gpu.resize(new_size);
+    }
+
+    fn resize_surface_if_needed(&mut self) {
+        if self.gpu.resize_if_needed() {
+            self.resolution
+                .resize(&self.gpu.device, &self.gpu.surface_config);
+        }
+    }
+
+    fn gpu_context(&mut self) -> Option<&mut GpuContext> {
+        Some(&mut self.gpu)
+    }
+
+    fn keyboard_input(&mut self, event: &KeyEvent) -> bool {
+        if self.camera.process_key(event) {
+            return true;
+        }
+        if event.state != ElementState::Pressed {
+            return false;
+        }
+        if event.repeat {
+            return false;
+        }
+        // F 键冻结剔除相机，V 键开关视锥剔除，C 键开关法线锥背面剔除，P 键打印 GPU 查询统计，
+        // R 键开关动态分辨率，T 键切换放大时是否锐化
+        match event.physical_key {
+            PhysicalKey::Code(KeyCode::KeyF) => {
+                self.debug.clear();
+                self.frozen_camera = match self.frozen_camera {
+                    Some(_) => None,
+                    None => {
+                        let camera = self.camera.state;
+                        self.debug.frustum(
+                            camera.build_view_projection_matrix(),
+                            glam::vec4(1.0, 0.8, 0.2, 1.0),
+                        );
+                        Some(camera)
+                    }
+                };
+                true
+            }
+            PhysicalKey::Code(KeyCode::KeyV) => {
+                self.culler.frustum_culling = !self.culler.frustum_culling;
+                true
+            }
+            PhysicalKey::Code(KeyCode::KeyC) => {
+                self.culler.cone_culling = !self.culler.cone_culling;
+                true
+            }
+            PhysicalKey::Code(KeyCode::KeyR) => {
+                self.resolution.enabled = !self.resolution.enabled;
+                true
+            }
+            PhysicalKey::Code(KeyCode::KeyT) => {
+                self.resolution.filter = match self.resolution.filter {
+                    UpscaleFilter::Bilinear => UpscaleFilter::Sharpen { sharpness: 0.5 },
+                    UpscaleFilter::Sharpen { .. } => UpscaleFilter::Bilinear,
+                };
+                true
+            }
+            PhysicalKey::Code(KeyCode::KeyP) => {
+                for stats in self.profiler.results() {
+                    println!("{stats}");
+                }
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn mouse_wheel(&mut self, delta: MouseScrollDelta, _phase: TouchPhase) -> bool {
+        self.camera.process_wheel(delta)
+    }
+
+    fn cameras_mut(&mut self) -> Vec<&mut CameraBundle> {
+        vec![&mut self.camera]
+    }
+
+    fn update(&mut self, time: FrameTime) {
+        if self.resolution.record_frame_time(time.delta) {
+            let (width, height) = self.resolution.internal_size();
+            println!(
+                "render scale {:.2} ({width}x{height})",
+                self.resolution.scale()
+            );
+        }
+        self.profiler.poll(&self.gpu.device);
+        self.camera.update(&self.gpu.queue, time.delta_secs());
+        let cull_camera = self.frozen_camera.as_ref().unwrap_or(&self.camera.state);
+        self.culler
+            .update(&self.gpu.queue, cull_camera, glam::Mat4::IDENTITY);
+        self.debug.update(
+            &self.gpu.device,
+            &self.gpu.queue,
+            &self.camera.state,
+            self.gpu.size(),
+        );
+    }
+}
+
+fn main() -> Result<(), impl std::error::Error> {
+    let events_loop = EventLoop::new().unwrap();
+    let mut app = WindowAppHandler::<App>::new("Meshlet culling example");
+    events_loop.run_app(&mut app)
+}
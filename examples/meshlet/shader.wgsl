@@ -0,0 +1,33 @@
+#include "wgpu_dance/camera.wgsl"
+#include "wgpu_dance/lighting.wgsl"
+
+struct VertexInput {
+    @location(0) position: vec3f,
+    @location(1) normal: vec3f,
+}
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4f,
+    @location(0) world_normal: vec3f,
+}
+
+@group(0) @binding(0)
+var<uniform> camera: CameraUniform;
+
+@group(1) @binding(0)
+var<uniform> sun: DirectionalLight;
+
+@vertex
+fn vs_main(model: VertexInput) -> VertexOutput {
+    var out: VertexOutput;
+    out.world_normal = model.normal;
+    out.clip_position = camera.view_proj * vec4f(model.position, 1.0);
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4f {
+    let diffuse = lambert(normalize(in.world_normal), sun.direction.xyz) * sun.direction.w;
+    let albedo = vec3f(0.75, 0.7, 0.65);
+    return vec4f(albedo * (sun.color.rgb * diffuse + 0.2), 1.0);
+}
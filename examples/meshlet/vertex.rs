@@ -0,0 +1,73 @@
+use bytemuck::{Pod, Zeroable};
+
+use wgpu_dance::model::RenderVertex;
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug)]
+pub struct BlobVertex {
+    pub position: [f32; 3],
+    pub normal: [f32; 3],
+}
+
+unsafe impl Zeroable for BlobVertex {}
+unsafe impl Pod for BlobVertex {}
+
+impl RenderVertex for BlobVertex {
+    fn buffer_layout_desc<'a>() -> wgpu::VertexBufferLayout<'a> {
+        const ATTRIBUTES: [wgpu::VertexAttribute; 2] = wgpu::vertex_attr_array![
+            0 => Float32x3,
+            1 => Float32x3,
+        ];
+        wgpu::VertexBufferLayout {
+            array_stride: std::mem::size_of::<BlobVertex>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Vertex,
+            attributes: &ATTRIBUTES,
+        }
+    }
+}
+
+/// 表面带有起伏的高细分球体，追加到 `vertices` 与 `indices` 中
+pub fn push_blob(
+    vertices: &mut Vec<BlobVertex>,
+    indices: &mut Vec<u32>,
+    center: glam::Vec3,
+    radius: f32,
+    rings: u32,
+    segments: u32,
+) {
+    let displace = |dir: glam::Vec3| {
+        let bumps = (dir.x * 9.0).sin() * (dir.y * 11.0).sin() * (dir.z * 7.0).sin();
+        radius * (1.0 + 0.12 * bumps)
+    };
+    let point = |phi: f32, theta: f32| {
+        let dir = glam::vec3(phi.sin() * theta.cos(), phi.cos(), phi.sin() * theta.sin());
+        center + dir * displace(dir)
+    };
+
+    let base = vertices.len() as u32;
+    let d = 1e-3;
+    for ring in 0..=rings {
+        let phi = ring as f32 / rings as f32 * std::f32::consts::PI;
+        for segment in 0..=segments {
+            let theta = segment as f32 / segments as f32 * std::f32::consts::TAU;
+            let position = point(phi, theta);
+            // 数值差分求法线，两极处切向退化时退回球面法线
+            let normal = (point(phi, theta + d) - position)
+                .cross(point(phi + d, theta) - position)
+                .try_normalize()
+                .unwrap_or((position - center).normalize());
+            vertices.push(BlobVertex {
+                position: position.to_array(),
+                normal: normal.to_array(),
+            });
+        }
+    }
+    let stride = segments + 1;
+    indices.extend((0..rings).flat_map(|ring| {
+        (0..segments).flat_map(move |segment| {
+            let a = base + ring * stride + segment;
+            let b = a + stride;
+            [a, a + 1, b, a + 1, b + 1, b]
+        })
+    }));
+}
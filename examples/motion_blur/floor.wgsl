@@ -0,0 +1,42 @@
+#include "wgpu_dance/camera.wgsl"
+#include "wgpu_dance/lighting.wgsl"
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4f,
+    @location(0) world_position: vec3f,
+    @location(1) clip: vec4f,
+    @location(2) prev_clip: vec4f,
+}
+
+struct FragmentOutput {
+    @location(0) color: vec4f,
+    @location(1) velocity: vec2f,
+}
+
+@group(0) @binding(0)
+var<uniform> camera: CameraUniform;
+
+@group(1) @binding(0)
+var<uniform> sun: DirectionalLight;
+
+@vertex
+fn vs_main(@location(0) position: vec3f) -> VertexOutput {
+    var out: VertexOutput;
+    out.world_position = position;
+    out.clip_position = camera.view_proj * vec4f(position, 1.0);
+    out.clip = out.clip_position;
+    out.prev_clip = camera.prev_view_proj * vec4f(position, 1.0);
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> FragmentOutput {
+    let checker = (i32(floor(in.world_position.x)) + i32(floor(in.world_position.z))) & 1;
+    let base = mix(vec3f(0.2, 0.2, 0.22), vec3f(0.4, 0.4, 0.45), f32(checker));
+    let diffuse = lambert(vec3f(0.0, 1.0, 0.0), sun.direction.xyz) * sun.direction.w;
+
+    var out: FragmentOutput;
+    out.color = vec4f(base * (sun.color.rgb * diffuse + 0.1), 1.0);
+    out.velocity = camera_velocity(camera, in.clip, in.prev_clip);
+    return out;
+}
@@ -0,0 +1,479 @@
+pub mod vertex;
+
+use std::sync::Arc;
+
+use wgpu::util::DeviceExt;
+use wgpu_dance::{
+    app::{FrameTime, WindowApp, WindowAppHandler},
+    camera::{Camera, CameraBundle, KeyboardController, Projection},
+    instance::{Instance, MotionInstanceBuffer, MotionInstanceRaw},
+    light::{DirectionalLight, DirectionalLightBundle},
+    model::{DrawModel, MeshModel, RenderVertex},
+    post::{
+        motion_blur::MotionBlur, taa::TemporalAntiAliasing, PostStack, SceneTextures,
+        VELOCITY_FORMAT,
+    },
+    shader::ShaderLibrary,
+    sky::{Sky, Sun},
+    texture::{DepthConvention, Texture},
+};
+
+use winit::{
+    dpi::PhysicalSize,
+    event::{ElementState, KeyEvent, MouseScrollDelta, TouchPhase},
+    event_loop::EventLoop,
+    keyboard::{KeyCode, PhysicalKey},
+    window::Window,
+};
+
+const NUM_INSTANCES: u32 = 12;
+const ORBIT_RADIUS: f32 = 6.0;
+/// 每秒绕中心旋转的弧度
+const ORBIT_SPEED: f32 = 1.5;
+const FLOOR_HALF_SIZE: f32 = 30.0;
+const FLOOR_HEIGHT: f32 = -1.0;
+
+struct App {
+    device: wgpu::Device,
+    queue: wgpu::Queue,
+
+    surface: wgpu::Surface<'static>,
+    surface_config: wgpu::SurfaceConfiguration,
+
+    size: winit::dpi::PhysicalSize<u32>,
+    size_changed: bool,
+
+    render_pipeline: wgpu::RenderPipeline,
+    floor_pipeline: wgpu::RenderPipeline,
+
+    obj_model: MeshModel,
+    instances: MotionInstanceBuffer,
+    floor_buffer: wgpu::Buffer,
+
+    scene_color: Texture,
+    velocity: Texture,
+    depth_texture: Texture,
+
+    camera: CameraBundle,
+    sun: Sun,
+    sky: Sky,
+    light: DirectionalLightBundle,
+    post: PostStack,
+}
+
+impl WindowApp for App {
+    async fn new(window: Arc<Window>) -> Self {
+        let instance = wgpu::Instance::new(&wgpu::InstanceDescriptor {
+            backends: wgpu::Backends::all(),
+            ..Default::default()
+        });
+        let surface = instance.create_surface(window.clone()).unwrap();
+
+        let adapter = instance
+            .request_adapter(&wgpu::RequestAdapterOptions {
+                power_preference: wgpu::PowerPreference::default(),
+                compatible_surface: Some(&surface),
+                force_fallback_adapter: false,
+            })
+            .await
+            .unwrap();
+
+        let (device, queue) = adapter
+            .request_device(
+                &wgpu::DeviceDescriptor {
+                    required_features: wgpu::Features::empty(),
+                    required_limits: wgpu::Limits::default(),
+                    label: None,
+                    memory_hints: wgpu::MemoryHints::Performance,
+                },
+                None,
+            )
+            .await
+            .unwrap();
+
+        let size = window.inner_size();
+
+        let caps = surface.get_capabilities(&adapter);
+        let surface_config = wgpu::SurfaceConfiguration {
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+            format: caps.formats[0],
+            width: size.width,
+            height: size.height,
+            present_mode: wgpu::PresentMode::Fifo,
+            alpha_mode: caps.alpha_modes[0],
+            view_formats: vec![],
+            desired_maximum_frame_latency: 2,
+        };
+        surface.configure(&device, &surface_config);
+
+        let camera = Camera {
+            eye: (0.0, 5.0, 16.0).into(),
+            target: (0.0, 0.0, 0.0).into(),
+            up: glam::Vec3::Y,
+            aspect: surface_config.width as f32 / surface_config.height as f32,
+            projection: Projection::Perspective { fovy: 45.0 },
+            znear: 0.1,
+            zfar: 100.0,
+            depth: DepthConvention::Standard,
+        };
+        let camera = CameraBundle::builder(camera)
+            .controller(KeyboardController::new(0.2))
+            .build(&device)
+            .unwrap();
+
+        let sun = Sun {
+            time_of_day: 16.5,
+            ..Default::default()
+        };
+        let mut light = DirectionalLight::default();
+        sun.apply_to(&mut light);
+        let light = DirectionalLightBundle::new(light, &device);
+
+        let sky = Sky::new(&device, surface_config.format, None);
+
+        let mut post = PostStack::new(&device, &surface_config, surface_config.format);
+        post.push(TemporalAntiAliasing::new(
+            &device,
+            &surface_config,
+            post.format(),
+        ));
+        post.push(MotionBlur::new(&device, post.format()));
+
+        let scene_color = Texture::create_render_target(&device, &surface_config, "scene_color");
+        let velocity =
+            Texture::create_color_target(&device, &surface_config, VELOCITY_FORMAT, "velocity");
+        let depth_texture =
+            Texture::create_depth_texture(&device, &surface_config, "depth_texture");
+
+        let texture_bind_group_layout = Texture::texture_bind_group_layout(&device);
+        let shader_library = ShaderLibrary::new();
+
+        let color_targets = [
+            Some(wgpu::ColorTargetState {
+                format: surface_config.format,
+                blend: Some(wgpu::BlendState::REPLACE),
+                write_mask: wgpu::ColorWrites::ALL,
+            }),
+            Some(wgpu::ColorTargetState {
+                format: VELOCITY_FORMAT,
+                blend: None,
+                write_mask: wgpu::ColorWrites::ALL,
+            }),
+        ];
+        let depth_stencil = wgpu::DepthStencilState {
+            format: Texture::DEPTH_FORMAT,
+            depth_write_enabled: true,
+            depth_compare: wgpu::CompareFunction::Less,
+            stencil: wgpu::StencilState::default(),
+            bias: wgpu::DepthBiasState::default(),
+        };
+
+        let shader = shader_library
+            .create_shader_module(&device, "Shader", include_str!("shader.wgsl"))
+            .unwrap();
+        let render_pipeline_layout =
+            device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some("Render Pipeline Layout"),
+                bind_group_layouts: &[
+                    &camera.bind_group_layout,
+                    &texture_bind_group_layout,
+                    &light.bind_group_layout,
+                ],
+                push_constant_ranges: &[],
+            });
+        let render_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Render Pipeline"),
+            layout: Some(&render_pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                compilation_options: Default::default(),
+                entry_point: Some("vs_main"),
+                buffers: &[
+                    MotionInstanceRaw::buffer_layout_desc(),
+                    vertex::Vertex::buffer_layout_desc(),
+                ],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                compilation_options: Default::default(),
+                entry_point: Some("fs_main"),
+                targets: &color_targets,
+            }),
+            primitive: wgpu::PrimitiveState {
+                cull_mode: Some(wgpu::Face::Back),
+                ..Default::default()
+            },
+            depth_stencil: Some(depth_stencil.clone()),
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+            cache: None,
+        });
+
+        let floor_shader = shader_library
+            .create_shader_module(&device, "Floor Shader", include_str!("floor.wgsl"))
+            .unwrap();
+        let floor_pipeline_layout =
+            device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some("Floor Pipeline Layout"),
+                bind_group_layouts: &[&camera.bind_group_layout, &light.bind_group_layout],
+                push_constant_ranges: &[],
+            });
+        let floor_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Floor Pipeline"),
+            layout: Some(&floor_pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &floor_shader,
+                compilation_options: Default::default(),
+                entry_point: Some("vs_main"),
+                buffers: &[vertex::FloorVertex::buffer_layout_desc()],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &floor_shader,
+                compilation_options: Default::default(),
+                entry_point: Some("fs_main"),
+                targets: &color_targets,
+            }),
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: Some(depth_stencil),
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+            cache: None,
+        });
+
+        let obj_model = MeshModel::load_model::<vertex::Vertex>(
+            "cube.obj",
+            &device,
+            &queue,
+            &texture_bind_group_layout,
+        )
+        .await
+        .unwrap();
+
+        let instances =
+            MotionInstanceBuffer::new(&device, vec![Instance::default(); NUM_INSTANCES as usize]);
+
+        let floor_vertices = [
+            [-1.0, 1.0],
+            [-1.0, -1.0],
+            [1.0, -1.0],
+            [-1.0, 1.0],
+            [1.0, -1.0],
+            [1.0, 1.0],
+        ]
+        .map(|[x, z]: [f32; 2]| vertex::FloorVertex {
+            position: [x * FLOOR_HALF_SIZE, FLOOR_HEIGHT, -z * FLOOR_HALF_SIZE],
+        });
+        let floor_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Floor Vertex Buffer"),
+            contents: bytemuck::cast_slice(&floor_vertices),
+            usage: wgpu::BufferUsages::VERTEX,
+        });
+
+        Self {
+            device,
+            queue,
+
+            surface,
+            surface_config,
+
+            size,
+            size_changed: false,
+
+            render_pipeline,
+            floor_pipeline,
+
+            obj_model,
+            instances,
+            floor_buffer,
+
+            scene_color,
+            velocity,
+            depth_texture,
+
+            camera,
+            sun,
+            sky,
+            light,
+            post,
+        }
+    }
+
+    fn render(&mut self) -> Result<(), wgpu::SurfaceError> {
+        self.resize_surface_if_needed();
+
+        let output = self.surface.get_current_texture()?;
+        let view = output
+            .texture
+            .create_view(&wgpu::TextureViewDescriptor::default());
+
+        let mut encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("Render Encoder"),
+            });
+
+        // 天空只有一个颜色输出，单独绘制到场景颜色上
+        {
+            let mut sky_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Sky Pass"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: &self.scene_color.view,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                        store: wgpu::StoreOp::Store,
+                    },
+                })],
+                ..Default::default()
+            });
+            self.sky.draw(&mut sky_pass);
+        }
+
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Render Pass"),
+            color_attachments: &[
+                Some(wgpu::RenderPassColorAttachment {
+                    view: &self.scene_color.view,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Load,
+                        store: wgpu::StoreOp::Store,
+                    },
+                }),
+                Some(wgpu::RenderPassColorAttachment {
+                    view: &self.velocity.view,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT),
+                        store: wgpu::StoreOp::Store,
+                    },
+                }),
+            ],
+            depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                view: &self.depth_texture.view,
+                depth_ops: Some(wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(1.0),
+                    store: wgpu::StoreOp::Store,
+                }),
+                stencil_ops: None,
+            }),
+            ..Default::default()
+        });
+
+        render_pass.set_pipeline(&self.floor_pipeline);
+        render_pass.set_bind_group(0, &self.camera.bind_group, &[]);
+        render_pass.set_bind_group(1, &self.light.bind_group, &[]);
+        render_pass.set_vertex_buffer(0, self.floor_buffer.slice(..));
+        render_pass.draw(0..6, 0..1);
+
+        render_pass.set_pipeline(&self.render_pipeline);
+        render_pass.set_vertex_buffer(0, self.instances.buffer().slice(..));
+        render_pass.set_bind_group(2, &self.light.bind_group, &[]);
+        render_pass.draw_model_instanced(
+            &self.obj_model,
+            0..self.instances.len(),
+            &self.camera.bind_group,
+        );
+
+        drop(render_pass);
+
+        self.post.run(
+            &self.device,
+            &mut encoder,
+            &SceneTextures {
+                color: &self.scene_color,
+                depth: &self.depth_texture,
+                normal_roughness: None,
+                velocity: Some(&self.velocity),
+            },
+            &view,
+        );
+
+        self.queue.submit(Some(encoder.finish()));
+        output.present();
+
+        Ok(())
+    }
+
+    fn set_window_resized(&mut self, new_size: PhysicalSize<u32>) {
+        if new_size == self.size {
+            return;
+        }
+        self.size = new_size;
+        self.size_changed = true;
+    }
+
+    fn resize_surface_if_needed(&mut self) {
+        if self.size_changed {
+            self.surface_config.width = self.size.width;
+            self.surface_config.height = self.size.height;
+            self.surface.configure(&self.device, &self.surface_config);
+            self.scene_color =
+                Texture::create_render_target(&self.device, &self.surface_config, "scene_color");
+            self.velocity = Texture::create_color_target(
+                &self.device,
+                &self.surface_config,
+                VELOCITY_FORMAT,
+                "velocity",
+            );
+            self.depth_texture =
+                Texture::create_depth_texture(&self.device, &self.surface_config, "depth_texture");
+            self.post.resize(&self.device, &self.surface_config);
+            self.size_changed = false;
+        }
+    }
+
+    fn keyboard_input(&mut self, event: &KeyEvent) -> bool {
+        // B 键切换运动模糊，T 键切换 TAA，便于对比
+        let label = match event.physical_key {
+            PhysicalKey::Code(KeyCode::KeyB) => MotionBlur::LABEL,
+            PhysicalKey::Code(KeyCode::KeyT) => TemporalAntiAliasing::LABEL,
+            _ => return self.camera.process_key(event),
+        };
+        if event.state == ElementState::Pressed && !event.repeat {
+            let enabled = self.post.is_enabled(label);
+            self.post.set_enabled(label, !enabled);
+        }
+        true
+    }
+
+    fn mouse_wheel(&mut self, delta: MouseScrollDelta, _phase: TouchPhase) -> bool {
+        self.camera.process_wheel(delta)
+    }
+
+    fn cameras_mut(&mut self) -> Vec<&mut CameraBundle> {
+        vec![&mut self.camera]
+    }
+
+    fn update(&mut self, time: FrameTime) {
+        let dt = time.delta_secs();
+        // 立方体绕中心公转并自转，用于产生逐物体速度
+        let time = time.elapsed_secs();
+        for (i, instance) in self.instances.instances.iter_mut().enumerate() {
+            let phase = i as f32 / NUM_INSTANCES as f32 * std::f32::consts::TAU;
+            let angle = phase + time * ORBIT_SPEED;
+            instance.position = glam::vec3(
+                ORBIT_RADIUS * angle.cos(),
+                1.0 + (time * 2.0 + phase).sin(),
+                ORBIT_RADIUS * angle.sin(),
+            );
+            instance.rotation = glam::Quat::from_rotation_y(time * 3.0 + phase);
+        }
+        self.instances.update(&self.queue);
+
+        let taa_enabled = self.post.is_enabled(TemporalAntiAliasing::LABEL);
+        self.camera.jitter = match self.post.get_mut::<TemporalAntiAliasing>() {
+            Some(taa) if taa_enabled => taa.next_jitter(),
+            _ => glam::Vec2::ZERO,
+        };
+        self.camera.update(&self.queue, dt);
+        self.sky.update(&self.queue, &self.camera.state, &self.sun);
+        self.post.update(&self.queue, &self.camera.state);
+    }
+}
+
+fn main() -> Result<(), impl std::error::Error> {
+    let events_loop = EventLoop::new().unwrap();
+    let mut app = WindowAppHandler::<App>::new("motion blur example");
+    events_loop.run_app(&mut app)
+}
@@ -0,0 +1,516 @@
+pub mod instance;
+pub mod vertex;
+
+use std::sync::Arc;
+
+use wgpu::util::DeviceExt;
+use wgpu_dance::{
+    app::{FrameTime, WindowApp, WindowAppHandler},
+    camera::{Camera, CameraBundle, KeyboardController, Projection},
+    environment::{Atmosphere, Environment, EnvironmentBundle},
+    light::{DirectionalLight, DirectionalLightBundle},
+    model::{DrawModel, MeshModel, RenderVertex},
+    particles::{gpu::GpuParticleSystem, EmitterSettings, ParticleSystem},
+    post::{aerial::AerialPerspective, PostStack, SceneTextures},
+    shader::ShaderLibrary,
+    sky::{Sky, Sun},
+    texture::{DepthConvention, Texture},
+};
+
+use winit::{
+    dpi::PhysicalSize,
+    event::{ElementState, KeyEvent, MouseScrollDelta, TouchPhase},
+    event_loop::EventLoop,
+    keyboard::{KeyCode, PhysicalKey},
+    window::Window,
+};
+
+const SPACE_BETWEEN: f32 = 3.0;
+const NUM_INSTANCES_PER_ROW: u32 = 10;
+/// 软粒子的淡出距离
+const SOFTNESS: f32 = 0.8;
+
+struct App {
+    device: wgpu::Device,
+    queue: wgpu::Queue,
+
+    surface: wgpu::Surface<'static>,
+    surface_config: wgpu::SurfaceConfiguration,
+
+    size: winit::dpi::PhysicalSize<u32>,
+    size_changed: bool,
+
+    render_pipeline: wgpu::RenderPipeline,
+
+    obj_model: MeshModel,
+    instances: Vec<instance::Instance>,
+    instance_buffer: wgpu::Buffer,
+
+    scene_color: Texture,
+    depth_texture: Texture,
+
+    camera: CameraBundle,
+    sun: Sun,
+    sky: Sky,
+    light: DirectionalLightBundle,
+    environment: EnvironmentBundle,
+    post: PostStack,
+    emitters: Vec<ParticleSystem>,
+    sparks: GpuParticleSystem,
+}
+
+impl WindowApp for App {
+    async fn new(window: Arc<Window>) -> Self {
+        let instance = wgpu::Instance::new(&wgpu::InstanceDescriptor {
+            backends: wgpu::Backends::all(),
+            ..Default::default()
+        });
+        let surface = instance.create_surface(window.clone()).unwrap();
+
+        let adapter = instance
+            .request_adapter(&wgpu::RequestAdapterOptions {
+                power_preference: wgpu::PowerPreference::default(),
+                compatible_surface: Some(&surface),
+                force_fallback_adapter: false,
+            })
+            .await
+            .unwrap();
+
+        let (device, queue) = adapter
+            .request_device(
+                &wgpu::DeviceDescriptor {
+                    required_features: wgpu::Features::empty(),
+                    required_limits: wgpu::Limits::default(),
+                    label: None,
+                    memory_hints: wgpu::MemoryHints::Performance,
+                },
+                None,
+            )
+            .await
+            .unwrap();
+
+        let size = window.inner_size();
+
+        let caps = surface.get_capabilities(&adapter);
+        let surface_config = wgpu::SurfaceConfiguration {
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+            format: caps.formats[0],
+            width: size.width,
+            height: size.height,
+            present_mode: wgpu::PresentMode::Fifo,
+            alpha_mode: caps.alpha_modes[0],
+            view_formats: vec![],
+            desired_maximum_frame_latency: 2,
+        };
+        surface.configure(&device, &surface_config);
+
+        let camera = Camera {
+            eye: (0.0, 6.0, 20.0).into(),
+            target: (0.0, 4.0, 0.0).into(),
+            up: glam::Vec3::Y,
+            aspect: surface_config.width as f32 / surface_config.height as f32,
+            projection: Projection::Perspective { fovy: 60.0 },
+            znear: 0.1,
+            zfar: 100.0,
+            depth: DepthConvention::Standard,
+        };
+        let camera = CameraBundle::builder(camera)
+            .controller(KeyboardController::new(0.2))
+            .build(&device)
+            .unwrap();
+
+        let sun = Sun {
+            time_of_day: 16.5,
+            ..Default::default()
+        };
+        let mut light = DirectionalLight::default();
+        sun.apply_to(&mut light);
+        let light = DirectionalLightBundle::new(light, &device);
+
+        // 较低的标高与较大的距离换算，使小场景中也能看到贴近地面的高度雾
+        let mut environment = Environment {
+            atmosphere: Atmosphere {
+                mie: 4.0,
+                scale_height: 6.0,
+                distance_scale: 400.0,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        environment.set_sun(&sun);
+        let environment = EnvironmentBundle::new(environment, &device);
+
+        let sky = Sky::new(&device, surface_config.format, Some(Texture::DEPTH_FORMAT));
+
+        let mut post = PostStack::new(&device, &surface_config, surface_config.format);
+        post.push(AerialPerspective::new(&device, post.format(), &environment));
+
+        let scene_color = Texture::create_render_target(&device, &surface_config, "scene_color");
+        let depth_texture =
+            Texture::create_depth_texture(&device, &surface_config, "depth_texture");
+
+        // 一处在立方体之间贴地扩散的烟雾，一处高处上升的蒸汽，用于对比高度雾的影响
+        let emitters = [
+            EmitterSettings {
+                position: glam::vec3(-1.5, 0.0, -1.5),
+                position_jitter: glam::vec3(2.0, 0.2, 2.0),
+                velocity: glam::vec3(0.3, 0.4, 0.0),
+                velocity_jitter: 0.8,
+                acceleration: glam::vec3(0.0, 0.05, 0.0),
+                start_size: 1.5,
+                end_size: 4.0,
+                start_color: glam::vec4(0.7, 0.7, 0.72, 0.5),
+                end_color: glam::vec4(0.6, 0.6, 0.65, 0.0),
+                ..Default::default()
+            },
+            EmitterSettings {
+                position: glam::vec3(6.0, 8.0, -9.0),
+                start_color: glam::vec4(1.0, 0.95, 0.9, 0.7),
+                ..Default::default()
+            },
+        ]
+        .into_iter()
+        .map(|settings| {
+            let mut particles = ParticleSystem::new(
+                &device,
+                settings,
+                surface_config.format,
+                &camera.bind_group_layout,
+                &environment,
+            );
+            particles.softness = SOFTNESS;
+            particles.set_depth_texture(&device, &depth_texture);
+            particles
+        })
+        .collect();
+
+        // 向上喷出后落在立方体上反弹的火花，在 GPU 上模拟并与深度缓冲碰撞
+        let mut sparks = GpuParticleSystem::new(
+            &device,
+            EmitterSettings {
+                position: glam::vec3(1.5, 5.0, 1.5),
+                position_jitter: glam::Vec3::splat(0.1),
+                spawn_rate: 400.0,
+                lifetime: 3.0,
+                velocity: glam::vec3(0.0, 3.0, 0.0),
+                velocity_jitter: 2.5,
+                acceleration: glam::vec3(0.0, -9.8, 0.0),
+                drag: 0.1,
+                start_size: 0.12,
+                end_size: 0.04,
+                start_color: glam::vec4(1.0, 0.75, 0.3, 1.0),
+                end_color: glam::vec4(1.0, 0.3, 0.1, 0.0),
+                max_particles: 2048,
+            },
+            surface_config.format,
+            &camera.bind_group_layout,
+            &environment,
+        );
+        sparks.set_depth_texture(&device, &depth_texture);
+
+        let texture_bind_group_layout = Texture::texture_bind_group_layout(&device);
+
+        let shader = ShaderLibrary::new()
+            .create_shader_module(&device, "Shader", include_str!("shader.wgsl"))
+            .unwrap();
+
+        let render_pipeline_layout =
+            device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some("Render Pipeline Layout"),
+                bind_group_layouts: &[
+                    &camera.bind_group_layout,
+                    &texture_bind_group_layout,
+                    &light.bind_group_layout,
+                ],
+                push_constant_ranges: &[],
+            });
+        let render_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Render Pipeline"),
+            layout: Some(&render_pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                compilation_options: Default::default(),
+                entry_point: Some("vs_main"),
+                buffers: &[
+                    instance::InstanceRaw::buffer_layout_desc(),
+                    vertex::Vertex::buffer_layout_desc(),
+                ],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                compilation_options: Default::default(),
+                entry_point: Some("fs_main"),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: surface_config.format,
+                    blend: Some(wgpu::BlendState::REPLACE),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleList,
+                strip_index_format: None,
+                front_face: wgpu::FrontFace::Ccw,
+                cull_mode: Some(wgpu::Face::Back),
+                polygon_mode: wgpu::PolygonMode::Fill,
+                unclipped_depth: false,
+                conservative: false,
+            },
+            depth_stencil: Some(wgpu::DepthStencilState {
+                format: Texture::DEPTH_FORMAT,
+                depth_write_enabled: true,
+                depth_compare: wgpu::CompareFunction::Less,
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState::default(),
+            }),
+            multisample: wgpu::MultisampleState {
+                count: 1,
+                mask: !0,
+                alpha_to_coverage_enabled: false,
+            },
+            multiview: None,
+            cache: None,
+        });
+
+        let obj_model = MeshModel::load_model::<vertex::Vertex>(
+            "cube.obj",
+            &device,
+            &queue,
+            &texture_bind_group_layout,
+        )
+        .await
+        .unwrap();
+
+        let instances = (0..NUM_INSTANCES_PER_ROW)
+            .flat_map(|z| {
+                (0..NUM_INSTANCES_PER_ROW).map(move |x| {
+                    let x = SPACE_BETWEEN * (x as f32 - NUM_INSTANCES_PER_ROW as f32 / 2.0);
+                    let z = SPACE_BETWEEN * (z as f32 - NUM_INSTANCES_PER_ROW as f32 / 2.0);
+
+                    let position = glam::Vec3 { x, y: 0.0, z };
+                    let rotation = glam::Quat::from_rotation_y((x + z) * 0.1);
+
+                    instance::Instance { position, rotation }
+                })
+            })
+            .collect::<Vec<_>>();
+        let instance_data = instances
+            .iter()
+            .map(instance::Instance::to_raw)
+            .collect::<Vec<_>>();
+        let instance_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Instance Buffer"),
+            contents: bytemuck::cast_slice(&instance_data),
+            usage: wgpu::BufferUsages::VERTEX,
+        });
+
+        Self {
+            device,
+            queue,
+
+            surface,
+            surface_config,
+
+            size,
+            size_changed: false,
+
+            render_pipeline,
+
+            obj_model,
+            instances,
+            instance_buffer,
+
+            scene_color,
+            depth_texture,
+
+            camera,
+            sun,
+            sky,
+            light,
+            environment,
+            post,
+            emitters,
+            sparks,
+        }
+    }
+
+    fn render(&mut self) -> Result<(), wgpu::SurfaceError> {
+        self.resize_surface_if_needed();
+
+        let output = self.surface.get_current_texture()?;
+        let view = output
+            .texture
+            .create_view(&wgpu::TextureViewDescriptor::default());
+
+        let mut encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("Render Encoder"),
+            });
+
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Render Pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: &self.scene_color.view,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                view: &self.depth_texture.view,
+                depth_ops: Some(wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(1.0),
+                    store: wgpu::StoreOp::Store,
+                }),
+                stencil_ops: None,
+            }),
+            ..Default::default()
+        });
+
+        self.sky.draw(&mut render_pass);
+
+        render_pass.set_pipeline(&self.render_pipeline);
+        render_pass.set_vertex_buffer(0, self.instance_buffer.slice(..));
+        render_pass.set_bind_group(2, &self.light.bind_group, &[]);
+        render_pass.draw_model_instanced(
+            &self.obj_model,
+            0..self.instances.len() as u32,
+            &self.camera.bind_group,
+        );
+
+        drop(render_pass);
+
+        // 碰撞需要读取刚写入的深度
+        self.sparks.simulate(&mut encoder);
+
+        self.post.run(
+            &self.device,
+            &mut encoder,
+            &SceneTextures {
+                color: &self.scene_color,
+                depth: &self.depth_texture,
+                normal_roughness: None,
+                velocity: None,
+            },
+            &view,
+        );
+
+        // 粒子不写深度且自行计算高度雾，因此在空气透视之后直接绘制到输出上；
+        // 深度以只读方式绑定，同时供软粒子采样
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Particle Pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: &view,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Load,
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                view: &self.depth_texture.view,
+                depth_ops: None,
+                stencil_ops: None,
+            }),
+            ..Default::default()
+        });
+        for emitter in &self.emitters {
+            emitter.draw(&mut render_pass, &self.camera.bind_group);
+        }
+        self.sparks.draw(&mut render_pass, &self.camera.bind_group);
+        drop(render_pass);
+
+        self.queue.submit(Some(encoder.finish()));
+        output.present();
+
+        Ok(())
+    }
+
+    fn set_window_resized(&mut self, new_size: PhysicalSize<u32>) {
+        if new_size == self.size {
+            return;
+        }
+        self.size = new_size;
+        self.size_changed = true;
+    }
+
+    fn resize_surface_if_needed(&mut self) {
+        if self.size_changed {
+            self.surface_config.width = self.size.width;
+            self.surface_config.height = self.size.height;
+            self.surface.configure(&self.device, &self.surface_config);
+            self.scene_color =
+                Texture::create_render_target(&self.device, &self.surface_config, "scene_color");
+            self.depth_texture =
+                Texture::create_depth_texture(&self.device, &self.surface_config, "depth_texture");
+            self.post.resize(&self.device, &self.surface_config);
+            for emitter in &mut self.emitters {
+                emitter.set_depth_texture(&self.device, &self.depth_texture);
+            }
+            self.sparks
+                .set_depth_texture(&self.device, &self.depth_texture);
+            self.size_changed = false;
+        }
+    }
+
+    fn keyboard_input(&mut self, event: &KeyEvent) -> bool {
+        if self.camera.process_key(event) {
+            return true;
+        }
+        if event.state != ElementState::Pressed || event.repeat {
+            return false;
+        }
+        // P 键切换软粒子，F 键切换粒子上的高度雾，C 键切换火花与场景的碰撞
+        match event.physical_key {
+            PhysicalKey::Code(KeyCode::KeyP) => {
+                for emitter in &mut self.emitters {
+                    emitter.softness = if emitter.softness > 0.0 {
+                        0.0
+                    } else {
+                        SOFTNESS
+                    };
+                }
+                true
+            }
+            PhysicalKey::Code(KeyCode::KeyF) => {
+                for emitter in &mut self.emitters {
+                    emitter.fog = !emitter.fog;
+                }
+                self.sparks.fog = !self.sparks.fog;
+                true
+            }
+            PhysicalKey::Code(KeyCode::KeyC) => {
+                self.sparks.collision.enabled = !self.sparks.collision.enabled;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn mouse_wheel(&mut self, delta: MouseScrollDelta, _phase: TouchPhase) -> bool {
+        self.camera.process_wheel(delta)
+    }
+
+    fn cameras_mut(&mut self) -> Vec<&mut CameraBundle> {
+        vec![&mut self.camera]
+    }
+
+    fn update(&mut self, time: FrameTime) {
+        let dt = time.delta_secs();
+        self.camera.update(&self.queue, time.delta_secs());
+        self.sky.update(&self.queue, &self.camera.state, &self.sun);
+        self.environment.update(&self.queue);
+        self.post.update(&self.queue, &self.camera.state);
+        for emitter in &mut self.emitters {
+            emitter.update(&self.queue, &self.camera.state, dt);
+        }
+        self.sparks.update(&self.queue, &self.camera.state, dt);
+    }
+}
+
+fn main() -> Result<(), impl std::error::Error> {
+    let events_loop = EventLoop::new().unwrap();
+    let mut app = WindowAppHandler::<App>::new("particles example");
+    events_loop.run_app(&mut app)
+}
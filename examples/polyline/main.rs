@@ -0,0 +1,326 @@
+use std::sync::Arc;
+
+use wgpu_dance::{
+    app::{FrameTime, WindowApp, WindowAppHandler},
+    camera::{Camera, CameraBundle, KeyboardController, Projection},
+    polyline::{LineJoin, LineStyle, LineWidth, PolylineRenderer},
+    texture::{DepthConvention, Texture},
+};
+
+use winit::{
+    dpi::PhysicalSize,
+    event::{ElementState, KeyEvent, MouseScrollDelta, TouchPhase},
+    event_loop::EventLoop,
+    keyboard::{KeyCode, PhysicalKey},
+    window::Window,
+};
+
+const GRID_HALF_SIZE: i32 = 10;
+const HELIX_SEGMENTS: usize = 200;
+
+struct App {
+    device: wgpu::Device,
+    queue: wgpu::Queue,
+
+    surface: wgpu::Surface<'static>,
+    surface_config: wgpu::SurfaceConfiguration,
+
+    size: winit::dpi::PhysicalSize<u32>,
+    size_changed: bool,
+
+    depth_texture: Texture,
+
+    camera: CameraBundle,
+    lines: PolylineRenderer,
+    join: LineJoin,
+    world_width: bool,
+}
+
+impl App {
+    /// 重新生成所有折线：地面网格、螺旋线与随时间起伏的锯齿线
+    fn build_lines(&mut self, time: f32) {
+        self.lines.clear();
+
+        let grid = LineStyle {
+            color: glam::vec4(0.5, 0.5, 0.55, 0.6),
+            width: LineWidth::Pixels(1.0),
+            ..Default::default()
+        };
+        let extent = GRID_HALF_SIZE as f32;
+        for i in -GRID_HALF_SIZE..=GRID_HALF_SIZE {
+            let i = i as f32;
+            self.lines.add_strip(
+                &[glam::vec3(i, 0.0, -extent), glam::vec3(i, 0.0, extent)],
+                &grid,
+            );
+            self.lines.add_strip(
+                &[glam::vec3(-extent, 0.0, i), glam::vec3(extent, 0.0, i)],
+                &grid,
+            );
+        }
+
+        let helix = (0..=HELIX_SEGMENTS)
+            .map(|i| {
+                let t = i as f32 / HELIX_SEGMENTS as f32;
+                let angle = t * std::f32::consts::TAU * 4.0 + time;
+                glam::vec3(3.0 * angle.cos(), 0.5 + t * 6.0, 3.0 * angle.sin())
+            })
+            .collect::<Vec<_>>();
+        let width = if self.world_width {
+            LineWidth::World(0.15)
+        } else {
+            LineWidth::Pixels(6.0)
+        };
+        self.lines.add_strip(
+            &helix,
+            &LineStyle {
+                color: glam::vec4(1.0, 0.6, 0.1, 1.0),
+                width,
+                join: self.join,
+                ..Default::default()
+            },
+        );
+
+        let zigzag = (0..12)
+            .map(|i| {
+                let x = i as f32 - 5.5;
+                let y = if i % 2 == 0 { 1.0 } else { 2.5 } + (time * 2.0 + x).sin() * 0.3;
+                glam::vec3(x, y, -6.0)
+            })
+            .collect::<Vec<_>>();
+        self.lines.add_strip(
+            &zigzag,
+            &LineStyle {
+                color: glam::vec4(0.2, 0.8, 1.0, 1.0),
+                width: LineWidth::Pixels(12.0),
+                join: self.join,
+                ..Default::default()
+            },
+        );
+
+        let square = [
+            glam::vec3(-8.0, 0.02, -8.0),
+            glam::vec3(8.0, 0.02, -8.0),
+            glam::vec3(8.0, 0.02, 8.0),
+            glam::vec3(-8.0, 0.02, 8.0),
+        ];
+        self.lines.add_loop(
+            &square,
+            &LineStyle {
+                color: glam::vec4(0.9, 0.2, 0.3, 0.8),
+                width: LineWidth::World(0.3),
+                join: self.join,
+                ..Default::default()
+            },
+        );
+    }
+}
+
+impl WindowApp for App {
+    async fn new(window: Arc<Window>) -> Self {
+        let instance = wgpu::Instance::new(&wgpu::InstanceDescriptor {
+            backends: wgpu::Backends::all(),
+            ..Default::default()
+        });
+        let surface = instance.create_surface(window.clone()).unwrap();
+
+        let adapter = instance
+            .request_adapter(&wgpu::RequestAdapterOptions {
+                power_preference: wgpu::PowerPreference::default(),
+                compatible_surface: Some(&surface),
+                force_fallback_adapter: false,
+            })
+            .await
+            .unwrap();
+
+        let (device, queue) = adapter
+            .request_device(
+                &wgpu::DeviceDescriptor {
+                    required_features: wgpu::Features::empty(),
+                    required_limits: wgpu::Limits::default(),
+                    label: None,
+                    memory_hints: wgpu::MemoryHints::Performance,
+                },
+                None,
+            )
+            .await
+            .unwrap();
+
+        let size = window.inner_size();
+
+        let caps = surface.get_capabilities(&adapter);
+        let surface_config = wgpu::SurfaceConfiguration {
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+            format: caps.formats[0],
+            width: size.width,
+            height: size.height,
+            present_mode: wgpu::PresentMode::Fifo,
+            alpha_mode: caps.alpha_modes[0],
+            view_formats: vec![],
+            desired_maximum_frame_latency: 2,
+        };
+        surface.configure(&device, &surface_config);
+
+        let camera = Camera {
+            eye: (0.0, 8.0, 16.0).into(),
+            target: (0.0, 2.0, 0.0).into(),
+            up: glam::Vec3::Y,
+            aspect: surface_config.width as f32 / surface_config.height as f32,
+            projection: Projection::Perspective { fovy: 45.0 },
+            znear: 0.1,
+            zfar: 100.0,
+            depth: DepthConvention::Standard,
+        };
+        let camera = CameraBundle::builder(camera)
+            .controller(KeyboardController::new(0.2))
+            .build(&device)
+            .unwrap();
+
+        let depth_texture =
+            Texture::create_depth_texture(&device, &surface_config, "depth_texture");
+
+        let lines = PolylineRenderer::new(
+            &device,
+            surface_config.format,
+            Some(Texture::DEPTH_FORMAT),
+            &camera.bind_group_layout,
+        );
+
+        Self {
+            device,
+            queue,
+
+            surface,
+            surface_config,
+
+            size,
+            size_changed: false,
+
+            depth_texture,
+
+            camera,
+            lines,
+            join: LineJoin::Miter,
+            world_width: true,
+        }
+    }
+
+    fn render(&mut self) -> Result<(), wgpu::SurfaceError> {
+        self.resize_surface_if_needed();
+
+        let output = self.surface.get_current_texture()?;
+        let view = output
+            .texture
+            .create_view(&wgpu::TextureViewDescriptor::default());
+
+        let mut encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("Render Encoder"),
+            });
+
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Render Pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: &view,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(wgpu::Color {
+                        r: 0.02,
+                        g: 0.02,
+                        b: 0.03,
+                        a: 1.0,
+                    }),
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                view: &self.depth_texture.view,
+                depth_ops: Some(wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(1.0),
+                    store: wgpu::StoreOp::Store,
+                }),
+                stencil_ops: None,
+            }),
+            ..Default::default()
+        });
+
+        self.lines.draw(&mut render_pass, &self.camera.bind_group);
+
+        drop(render_pass);
+
+        self.queue.submit(Some(encoder.finish()));
+        output.present();
+
+        Ok(())
+    }
+
+    fn set_window_resized(&mut self, new_size: PhysicalSize<u32>) {
+        if new_size == self.size {
+            return;
+        }
+        self.size = new_size;
+        self.size_changed = true;
+    }
+
+    fn resize_surface_if_needed(&mut self) {
+        if self.size_changed {
+            self.surface_config.width = self.size.width;
+            self.surface_config.height = self.size.height;
+            self.surface.configure(&self.device, &self.surface_config);
+            self.depth_texture =
+                Texture::create_depth_texture(&self.device, &self.surface_config, "depth_texture");
+            self.size_changed = false;
+        }
+    }
+
+    fn keyboard_input(&mut self, event: &KeyEvent) -> bool {
+        if self.camera.process_key(event) {
+            return true;
+        }
+        if event.state != ElementState::Pressed || event.repeat {
+            return false;
+        }
+        // J 键切换连接方式，U 键切换螺旋线的宽度单位
+        match event.physical_key {
+            PhysicalKey::Code(KeyCode::KeyJ) => {
+                self.join = match self.join {
+                    LineJoin::Miter => LineJoin::Round,
+                    LineJoin::Round => LineJoin::Miter,
+                };
+                true
+            }
+            PhysicalKey::Code(KeyCode::KeyU) => {
+                self.world_width = !self.world_width;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn mouse_wheel(&mut self, delta: MouseScrollDelta, _phase: TouchPhase) -> bool {
+        self.camera.process_wheel(delta)
+    }
+
+    fn cameras_mut(&mut self) -> Vec<&mut CameraBundle> {
+        vec![&mut self.camera]
+    }
+
+    fn update(&mut self, time: FrameTime) {
+        self.camera.update(&self.queue, time.delta_secs());
+
+        self.build_lines(time.elapsed_secs());
+        self.lines.update(
+            &self.device,
+            &self.queue,
+            &self.camera.state,
+            PhysicalSize::new(self.surface_config.width, self.surface_config.height),
+        );
+    }
+}
+
+fn main() -> Result<(), impl std::error::Error> {
+    let events_loop = EventLoop::new().unwrap();
+    let mut app = WindowAppHandler::<App>::new("polyline example");
+    events_loop.run_app(&mut app)
+}
@@ -0,0 +1,34 @@
+#include "wgpu_dance/camera.wgsl"
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4f,
+    @location(0) world_position: vec3f,
+}
+
+@group(0) @binding(0)
+var<uniform> camera: CameraUniform;
+
+@vertex
+fn vs_main(@location(0) position: vec3f) -> VertexOutput {
+    var out: VertexOutput;
+    out.world_position = position;
+    out.clip_position = camera.view_proj * vec4f(position, 1.0);
+    return out;
+}
+
+@group(1) @binding(0)
+var t_reflection: texture_2d<f32>;
+@group(1) @binding(1)
+var s_reflection: sampler;
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4f {
+    // 反射纹理与屏幕同尺寸，直接用片元的屏幕坐标采样
+    let uv = in.clip_position.xy / vec2f(textureDimensions(t_reflection));
+    let reflection = textureSample(t_reflection, s_reflection, uv);
+
+    let checker = (i32(floor(in.world_position.x)) + i32(floor(in.world_position.z))) & 1;
+    let base = mix(vec3f(0.25, 0.25, 0.28), vec3f(0.4, 0.4, 0.45), f32(checker));
+
+    return vec4f(mix(base, reflection.rgb, 0.6), 1.0);
+}
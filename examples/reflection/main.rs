@@ -0,0 +1,457 @@
+pub mod instance;
+pub mod vertex;
+
+use std::sync::Arc;
+
+use wgpu::util::DeviceExt;
+use wgpu_dance::{
+    app::{FrameTime, WindowApp, WindowAppHandler},
+    camera::{Camera, CameraBundle, KeyboardController, Projection},
+    model::{DrawModel, MeshModel, RenderVertex},
+    reflection::{PlanarReflection, Plane},
+    shader::ShaderLibrary,
+    texture::{DepthConvention, Texture},
+};
+
+use winit::{
+    dpi::PhysicalSize,
+    event::{KeyEvent, MouseScrollDelta, TouchPhase},
+    event_loop::EventLoop,
+    window::Window,
+};
+
+const SPACE_BETWEEN: f32 = 3.0;
+const NUM_INSTANCES_PER_ROW: u32 = 6;
+const FLOOR_HEIGHT: f32 = -1.5;
+const FLOOR_HALF_SIZE: f32 = 12.0;
+/// 使用反向深度，反射 pass 的深度纹理与清除值随相机的约定一起改变
+const DEPTH: DepthConvention = DepthConvention::Reversed;
+
+const CLEAR_COLOR: wgpu::Color = wgpu::Color {
+    r: 0.1,
+    g: 0.2,
+    b: 0.3,
+    a: 1.0,
+};
+
+struct App {
+    device: wgpu::Device,
+    queue: wgpu::Queue,
+
+    surface: wgpu::Surface<'static>,
+    surface_config: wgpu::SurfaceConfiguration,
+
+    size: winit::dpi::PhysicalSize<u32>,
+    size_changed: bool,
+
+    render_pipeline: wgpu::RenderPipeline,
+    mirrored_pipeline: wgpu::RenderPipeline,
+    floor_pipeline: wgpu::RenderPipeline,
+
+    obj_model: MeshModel,
+    instances: Vec<instance::Instance>,
+    instance_buffer: wgpu::Buffer,
+    floor_buffer: wgpu::Buffer,
+
+    texture_bind_group_layout: wgpu::BindGroupLayout,
+    depth_texture: Texture,
+
+    camera: CameraBundle,
+    reflection: PlanarReflection,
+}
+
+fn create_scene_pipeline(
+    device: &wgpu::Device,
+    layout: &wgpu::PipelineLayout,
+    shader: &wgpu::ShaderModule,
+    format: wgpu::TextureFormat,
+    front_face: wgpu::FrontFace,
+) -> wgpu::RenderPipeline {
+    device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+        label: Some("Render Pipeline"),
+        layout: Some(layout),
+        vertex: wgpu::VertexState {
+            module: shader,
+            compilation_options: Default::default(),
+            entry_point: Some("vs_main"),
+            buffers: &[
+                instance::InstanceRaw::buffer_layout_desc(),
+                vertex::Vertex::buffer_layout_desc(),
+            ],
+        },
+        fragment: Some(wgpu::FragmentState {
+            module: shader,
+            compilation_options: Default::default(),
+            entry_point: Some("fs_main"),
+            targets: &[Some(wgpu::ColorTargetState {
+                format,
+                blend: Some(wgpu::BlendState::REPLACE),
+                write_mask: wgpu::ColorWrites::ALL,
+            })],
+        }),
+        primitive: wgpu::PrimitiveState {
+            topology: wgpu::PrimitiveTopology::TriangleList,
+            strip_index_format: None,
+            front_face,
+            cull_mode: Some(wgpu::Face::Back),
+            polygon_mode: wgpu::PolygonMode::Fill,
+            unclipped_depth: false,
+            conservative: false,
+        },
+        depth_stencil: Some(wgpu::DepthStencilState {
+            format: Texture::DEPTH_FORMAT,
+            depth_write_enabled: true,
+            depth_compare: DEPTH.compare(),
+            stencil: wgpu::StencilState::default(),
+            bias: wgpu::DepthBiasState::default(),
+        }),
+        multisample: wgpu::MultisampleState {
+            count: 1,
+            mask: !0,
+            alpha_to_coverage_enabled: false,
+        },
+        multiview: None,
+        cache: None,
+    })
+}
+
+impl WindowApp for App {
+    async fn new(window: Arc<Window>) -> Self {
+        let instance = wgpu::Instance::new(&wgpu::InstanceDescriptor {
+            backends: wgpu::Backends::all(),
+            ..Default::default()
+        });
+        let surface = instance.create_surface(window.clone()).unwrap();
+
+        let adapter = instance
+            .request_adapter(&wgpu::RequestAdapterOptions {
+                power_preference: wgpu::PowerPreference::default(),
+                compatible_surface: Some(&surface),
+                force_fallback_adapter: false,
+            })
+            .await
+            .unwrap();
+
+        let (device, queue) = adapter
+            .request_device(
+                &wgpu::DeviceDescriptor {
+                    required_features: wgpu::Features::empty(),
+                    required_limits: wgpu::Limits::default(),
+                    label: None,
+                    memory_hints: wgpu::MemoryHints::Performance,
+                },
+                None,
+            )
+            .await
+            .unwrap();
+
+        let size = window.inner_size();
+
+        let caps = surface.get_capabilities(&adapter);
+        let surface_config = wgpu::SurfaceConfiguration {
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+            format: caps.formats[0],
+            width: size.width,
+            height: size.height,
+            present_mode: wgpu::PresentMode::Fifo,
+            alpha_mode: caps.alpha_modes[0],
+            view_formats: vec![],
+            desired_maximum_frame_latency: 2,
+        };
+        surface.configure(&device, &surface_config);
+
+        let camera = Camera {
+            eye: (0.0, 5.0, 12.0).into(),
+            target: (0.0, 0.0, 0.0).into(),
+            up: glam::Vec3::Y,
+            aspect: surface_config.width as f32 / surface_config.height as f32,
+            projection: Projection::Perspective { fovy: 45.0 },
+            znear: 0.1,
+            zfar: 100.0,
+            depth: DEPTH,
+        };
+        let camera = CameraBundle::builder(camera)
+            .controller(KeyboardController::new(0.2))
+            .build(&device)
+            .unwrap();
+
+        let texture_bind_group_layout = Texture::texture_bind_group_layout(&device);
+
+        // 地板位于 y = FLOOR_HEIGHT，法线朝上
+        let reflection = PlanarReflection::new(
+            Plane::from_point_normal(glam::vec3(0.0, FLOOR_HEIGHT, 0.0), glam::Vec3::Y).unwrap(),
+            &device,
+            &surface_config,
+            &camera,
+            &texture_bind_group_layout,
+        );
+
+        let depth_texture =
+            Texture::create_depth_texture_with(&device, &surface_config, "depth_texture", DEPTH);
+
+        let shader_library = ShaderLibrary::new();
+        let shader = shader_library
+            .create_shader_module(&device, "Shader", include_str!("shader.wgsl"))
+            .unwrap();
+        let floor_shader = shader_library
+            .create_shader_module(&device, "Floor Shader", include_str!("floor.wgsl"))
+            .unwrap();
+
+        let render_pipeline_layout =
+            device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some("Render Pipeline Layout"),
+                bind_group_layouts: &[&camera.bind_group_layout, &texture_bind_group_layout],
+                push_constant_ranges: &[],
+            });
+        let render_pipeline = create_scene_pipeline(
+            &device,
+            &render_pipeline_layout,
+            &shader,
+            surface_config.format,
+            wgpu::FrontFace::Ccw,
+        );
+        // 镜像后三角形环绕方向相反
+        let mirrored_pipeline = create_scene_pipeline(
+            &device,
+            &render_pipeline_layout,
+            &shader,
+            surface_config.format,
+            wgpu::FrontFace::Cw,
+        );
+
+        let floor_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Floor Pipeline"),
+            layout: Some(&render_pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &floor_shader,
+                compilation_options: Default::default(),
+                entry_point: Some("vs_main"),
+                buffers: &[vertex::FloorVertex::buffer_layout_desc()],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &floor_shader,
+                compilation_options: Default::default(),
+                entry_point: Some("fs_main"),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: surface_config.format,
+                    blend: Some(wgpu::BlendState::REPLACE),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleList,
+                strip_index_format: None,
+                front_face: wgpu::FrontFace::Ccw,
+                cull_mode: Some(wgpu::Face::Back),
+                polygon_mode: wgpu::PolygonMode::Fill,
+                unclipped_depth: false,
+                conservative: false,
+            },
+            depth_stencil: Some(wgpu::DepthStencilState {
+                format: Texture::DEPTH_FORMAT,
+                depth_write_enabled: true,
+                depth_compare: DEPTH.compare(),
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState::default(),
+            }),
+            multisample: wgpu::MultisampleState {
+                count: 1,
+                mask: !0,
+                alpha_to_coverage_enabled: false,
+            },
+            multiview: None,
+            cache: None,
+        });
+
+        let obj_model = MeshModel::load_model::<vertex::Vertex>(
+            "cube.obj",
+            &device,
+            &queue,
+            &texture_bind_group_layout,
+        )
+        .await
+        .unwrap();
+
+        let instances = (0..NUM_INSTANCES_PER_ROW)
+            .flat_map(|z| {
+                (0..NUM_INSTANCES_PER_ROW).map(move |x| {
+                    let x = SPACE_BETWEEN * (x as f32 - NUM_INSTANCES_PER_ROW as f32 / 2.0);
+                    let z = SPACE_BETWEEN * (z as f32 - NUM_INSTANCES_PER_ROW as f32 / 2.0);
+
+                    let position = glam::Vec3 { x, y: 0.5, z };
+                    let rotation = glam::Quat::from_rotation_y(x * 0.3 + z * 0.2);
+
+                    instance::Instance { position, rotation }
+                })
+            })
+            .collect::<Vec<_>>();
+        let instance_data = instances
+            .iter()
+            .map(instance::Instance::to_raw)
+            .collect::<Vec<_>>();
+        let instance_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Instance Buffer"),
+            contents: bytemuck::cast_slice(&instance_data),
+            usage: wgpu::BufferUsages::VERTEX,
+        });
+
+        let floor_vertices = [
+            [-1.0, 1.0],
+            [-1.0, -1.0],
+            [1.0, -1.0],
+            [-1.0, 1.0],
+            [1.0, -1.0],
+            [1.0, 1.0],
+        ]
+        .map(|[x, z]: [f32; 2]| vertex::FloorVertex {
+            position: [x * FLOOR_HALF_SIZE, FLOOR_HEIGHT, -z * FLOOR_HALF_SIZE],
+        });
+        let floor_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Floor Vertex Buffer"),
+            contents: bytemuck::cast_slice(&floor_vertices),
+            usage: wgpu::BufferUsages::VERTEX,
+        });
+
+        Self {
+            device,
+            queue,
+
+            surface,
+            surface_config,
+
+            size,
+            size_changed: false,
+
+            render_pipeline,
+            mirrored_pipeline,
+            floor_pipeline,
+
+            obj_model,
+            instances,
+            instance_buffer,
+            floor_buffer,
+
+            texture_bind_group_layout,
+            depth_texture,
+
+            camera,
+            reflection,
+        }
+    }
+
+    fn render(&mut self) -> Result<(), wgpu::SurfaceError> {
+        self.resize_surface_if_needed();
+
+        let output = self.surface.get_current_texture()?;
+        let view = output
+            .texture
+            .create_view(&wgpu::TextureViewDescriptor::default());
+
+        let mut encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("Render Encoder"),
+            });
+
+        // 第一个 pass：用镜像相机把场景渲染到反射纹理
+        let mut reflection_pass = self.reflection.begin_pass(&mut encoder, CLEAR_COLOR);
+        reflection_pass.set_pipeline(&self.mirrored_pipeline);
+        reflection_pass.set_vertex_buffer(0, self.instance_buffer.slice(..));
+        reflection_pass.draw_model_instanced(
+            &self.obj_model,
+            0..self.instances.len() as u32,
+            &self.reflection.camera_bind_group,
+        );
+        drop(reflection_pass);
+
+        // 第二个 pass：正常渲染场景，地板采样反射纹理
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Render Pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: &view,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(CLEAR_COLOR),
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                view: &self.depth_texture.view,
+                depth_ops: Some(DEPTH.clear_ops()),
+                stencil_ops: None,
+            }),
+            ..Default::default()
+        });
+        render_pass.set_pipeline(&self.render_pipeline);
+        render_pass.set_vertex_buffer(0, self.instance_buffer.slice(..));
+        render_pass.draw_model_instanced(
+            &self.obj_model,
+            0..self.instances.len() as u32,
+            &self.camera.bind_group,
+        );
+
+        render_pass.set_pipeline(&self.floor_pipeline);
+        render_pass.set_vertex_buffer(0, self.floor_buffer.slice(..));
+        render_pass.set_bind_group(0, &self.camera.bind_group, &[]);
+        render_pass.set_bind_group(1, &self.reflection.texture_bind_group, &[]);
+        render_pass.draw(0..6, 0..1);
+
+        drop(render_pass);
+
+        self.queue.submit(Some(encoder.finish()));
+        output.present();
+
+        Ok(())
+    }
+
+    fn set_window_resized(&mut self, new_size: PhysicalSize<u32>) {
+        if new_size == self.size {
+            return;
+        }
+        self.size = new_size;
+        self.size_changed = true;
+    }
+
+    fn resize_surface_if_needed(&mut self) {
+        if self.size_changed {
+            self.surface_config.width = self.size.width;
+            self.surface_config.height = self.size.height;
+            self.surface.configure(&self.device, &self.surface_config);
+            self.depth_texture = Texture::create_depth_texture_with(
+                &self.device,
+                &self.surface_config,
+                "depth_texture",
+                DEPTH,
+            );
+            self.reflection.resize(
+                &self.device,
+                &self.surface_config,
+                &self.texture_bind_group_layout,
+            );
+            self.size_changed = false;
+        }
+    }
+
+    fn keyboard_input(&mut self, event: &KeyEvent) -> bool {
+        self.camera.process_key(event)
+    }
+
+    fn mouse_wheel(&mut self, delta: MouseScrollDelta, _phase: TouchPhase) -> bool {
+        self.camera.process_wheel(delta)
+    }
+
+    fn cameras_mut(&mut self) -> Vec<&mut CameraBundle> {
+        vec![&mut self.camera]
+    }
+
+    fn update(&mut self, time: FrameTime) {
+        self.camera.update(&self.queue, time.delta_secs());
+        self.reflection.update(&self.queue, &self.camera.state);
+    }
+}
+
+fn main() -> Result<(), impl std::error::Error> {
+    let events_loop = EventLoop::new().unwrap();
+    let mut app = WindowAppHandler::<App>::new("reflection example");
+    events_loop.run_app(&mut app)
+}
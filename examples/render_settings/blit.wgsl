@@ -0,0 +1,17 @@
+#include "wgpu_dance/fullscreen.wgsl"
+
+@group(0) @binding(0)
+var t_scene: texture_2d<f32>;
+@group(0) @binding(1)
+var s_scene: sampler;
+
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32) -> FullscreenOutput {
+    return fullscreen_vertex(index);
+}
+
+// 场景以渲染比例对应的大小绘制，这里双线性缩放到输出
+@fragment
+fn fs_main(in: FullscreenOutput) -> @location(0) vec4f {
+    return textureSample(t_scene, s_scene, in.uv);
+}
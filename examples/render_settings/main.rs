@@ -0,0 +1,625 @@
+use std::sync::Arc;
+
+use bytemuck::{Pod, Zeroable};
+use wgpu::util::DeviceExt;
+use wgpu_dance::{
+    app::{FrameTime, WindowApp, WindowAppHandler},
+    background::Background,
+    camera::{Camera, CameraBundle, KeyboardController, Projection},
+    context::{GpuContext, GpuContextOptions},
+    egui_layer::EguiLayer,
+    settings::{RenderSettings, SettingsChanges},
+    shader::ShaderLibrary,
+    texture::{DepthConvention, Texture},
+};
+use winit::{
+    dpi::PhysicalSize,
+    event::{KeyEvent, MouseScrollDelta, TouchPhase, WindowEvent},
+    event_loop::EventLoop,
+    window::Window,
+};
+
+const FLOOR_HALF_SIZE: f32 = 200.0;
+const CHECKER_SIZE: u32 = 256;
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug)]
+struct Vertex {
+    position: [f32; 3],
+    normal: [f32; 3],
+    tex_coords: [f32; 2],
+}
+
+unsafe impl Zeroable for Vertex {}
+unsafe impl Pod for Vertex {}
+
+impl Vertex {
+    const ATTRIBUTES: [wgpu::VertexAttribute; 3] =
+        wgpu::vertex_attr_array![0 => Float32x3, 1 => Float32x3, 2 => Float32x2];
+
+    fn buffer_layout_desc() -> wgpu::VertexBufferLayout<'static> {
+        wgpu::VertexBufferLayout {
+            array_stride: std::mem::size_of::<Vertex>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Vertex,
+            attributes: &Self::ATTRIBUTES,
+        }
+    }
+}
+
+/// 一直延伸到远处的地面与一圈旋转的立方体：地面用于观察各向异性过滤，立方体的边缘用于观察 MSAA
+fn scene_vertices() -> Vec<Vertex> {
+    let h = FLOOR_HALF_SIZE;
+    let mut vertices: Vec<Vertex> = [[-h, -h], [-h, h], [h, h], [-h, -h], [h, h], [h, -h]]
+        .into_iter()
+        .map(|[x, z]| Vertex {
+            position: [x, 0.0, z],
+            normal: [0.0, 1.0, 0.0],
+            tex_coords: [x * 0.5, z * 0.5],
+        })
+        .collect();
+
+    // 立方体的六个面：法线与面内的两个轴
+    let faces = [
+        (glam::Vec3::X, glam::Vec3::Z, glam::Vec3::Y),
+        (glam::Vec3::NEG_X, glam::Vec3::Y, glam::Vec3::Z),
+        (glam::Vec3::Y, glam::Vec3::X, glam::Vec3::Z),
+        (glam::Vec3::NEG_Y, glam::Vec3::Z, glam::Vec3::X),
+        (glam::Vec3::Z, glam::Vec3::Y, glam::Vec3::X),
+        (glam::Vec3::NEG_Z, glam::Vec3::X, glam::Vec3::Y),
+    ];
+    for i in 0..12 {
+        let angle = i as f32 / 12.0 * std::f32::consts::TAU;
+        let transform = glam::Mat4::from_rotation_translation(
+            glam::Quat::from_rotation_y(angle * 2.0) * glam::Quat::from_rotation_x(0.3),
+            glam::vec3(angle.cos() * 5.0, 1.2, angle.sin() * 5.0),
+        );
+        for (normal, u, v) in faces {
+            let corner = |a: f32, b: f32| {
+                let p = (normal + u * a + v * b) * 0.5;
+                Vertex {
+                    position: transform.transform_point3(p).to_array(),
+                    normal: transform.transform_vector3(normal).to_array(),
+                    tex_coords: [(a + 1.0) * 0.5, (b + 1.0) * 0.5],
+                }
+            };
+            vertices.extend([
+                corner(-1.0, -1.0),
+                corner(1.0, -1.0),
+                corner(1.0, 1.0),
+                corner(-1.0, -1.0),
+                corner(1.0, 1.0),
+                corner(-1.0, 1.0),
+            ]);
+        }
+    }
+    vertices
+}
+
+/// 带完整 mipmap 的棋盘格纹理，各向异性过滤只在有 mipmap 时才有意义
+fn create_checker(device: &wgpu::Device, queue: &wgpu::Queue) -> wgpu::TextureView {
+    let image = image::RgbaImage::from_fn(CHECKER_SIZE, CHECKER_SIZE, |x, y| {
+        if (x / 32 + y / 32) % 2 == 0 {
+            image::Rgba([230, 230, 225, 255])
+        } else {
+            image::Rgba([40, 60, 90, 255])
+        }
+    });
+    let mip_level_count = CHECKER_SIZE.ilog2() + 1;
+    let texture = device.create_texture(&wgpu::TextureDescriptor {
+        label: Some("checker"),
+        size: wgpu::Extent3d {
+            width: CHECKER_SIZE,
+            height: CHECKER_SIZE,
+            depth_or_array_layers: 1,
+        },
+        mip_level_count,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format: wgpu::TextureFormat::Rgba8UnormSrgb,
+        usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+        view_formats: &[],
+    });
+    for level in 0..mip_level_count {
+        let size = CHECKER_SIZE >> level;
+        let mip = image::imageops::resize(&image, size, size, image::imageops::Triangle);
+        queue.write_texture(
+            wgpu::TexelCopyTextureInfo {
+                texture: &texture,
+                mip_level: level,
+                origin: wgpu::Origin3d::ZERO,
+                aspect: wgpu::TextureAspect::All,
+            },
+            &mip,
+            wgpu::TexelCopyBufferLayout {
+                offset: 0,
+                bytes_per_row: Some(4 * size),
+                rows_per_image: Some(size),
+            },
+            wgpu::Extent3d {
+                width: size,
+                height: size,
+                depth_or_array_layers: 1,
+            },
+        );
+    }
+    texture.create_view(&wgpu::TextureViewDescriptor::default())
+}
+
+/// 按渲染比例与采样数分配的场景附件
+struct SceneTargets {
+    /// 多重采样的颜色附件，关闭 MSAA 时为 `None`，直接绘制到 `resolve`
+    msaa: Option<wgpu::TextureView>,
+    resolve: wgpu::TextureView,
+    depth: wgpu::TextureView,
+    /// 把 `resolve` 缩放到 surface 的绑定组
+    blit_bind_group: wgpu::BindGroup,
+}
+
+impl SceneTargets {
+    fn new(
+        gpu: &GpuContext,
+        settings: &RenderSettings,
+        blit_layout: &wgpu::BindGroupLayout,
+        blit_sampler: &wgpu::Sampler,
+    ) -> Self {
+        let size = settings.scaled_size(gpu.size());
+        let create = |format, sample_count, usage, label| {
+            gpu.device
+                .create_texture(&wgpu::TextureDescriptor {
+                    label: Some(label),
+                    size: wgpu::Extent3d {
+                        width: size.width,
+                        height: size.height,
+                        depth_or_array_layers: 1,
+                    },
+                    mip_level_count: 1,
+                    sample_count,
+                    dimension: wgpu::TextureDimension::D2,
+                    format,
+                    usage,
+                    view_formats: &[],
+                })
+                .create_view(&wgpu::TextureViewDescriptor::default())
+        };
+        let samples = settings.msaa_samples;
+        let msaa = (samples > 1).then(|| {
+            create(
+                gpu.format(),
+                samples,
+                wgpu::TextureUsages::RENDER_ATTACHMENT,
+                "scene_msaa",
+            )
+        });
+        let resolve = create(
+            gpu.format(),
+            1,
+            wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+            "scene_resolve",
+        );
+        let depth = create(
+            Texture::DEPTH_FORMAT,
+            samples,
+            wgpu::TextureUsages::RENDER_ATTACHMENT,
+            "scene_depth",
+        );
+        let blit_bind_group = gpu.device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: blit_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(&resolve),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(blit_sampler),
+                },
+            ],
+            label: Some("blit_bind_group"),
+        });
+        Self {
+            msaa,
+            resolve,
+            depth,
+            blit_bind_group,
+        }
+    }
+}
+
+fn create_scene_pipeline(
+    gpu: &GpuContext,
+    layout: &wgpu::PipelineLayout,
+    shader: &wgpu::ShaderModule,
+    samples: u32,
+) -> wgpu::RenderPipeline {
+    gpu.device
+        .create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Scene Pipeline"),
+            layout: Some(layout),
+            vertex: wgpu::VertexState {
+                module: shader,
+                compilation_options: Default::default(),
+                entry_point: Some("vs_main"),
+                buffers: &[Vertex::buffer_layout_desc()],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: shader,
+                compilation_options: Default::default(),
+                entry_point: Some("fs_main"),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: gpu.format(),
+                    blend: None,
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            primitive: wgpu::PrimitiveState {
+                cull_mode: Some(wgpu::Face::Back),
+                ..Default::default()
+            },
+            depth_stencil: Some(wgpu::DepthStencilState {
+                format: Texture::DEPTH_FORMAT,
+                depth_write_enabled: true,
+                depth_compare: wgpu::CompareFunction::Less,
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState::default(),
+            }),
+            multisample: wgpu::MultisampleState {
+                count: samples,
+                ..Default::default()
+            },
+            multiview: None,
+            cache: None,
+        })
+}
+
+struct App {
+    gpu: GpuContext,
+    egui: EguiLayer,
+
+    /// 当前生效的设置
+    settings: RenderSettings,
+    /// 面板中编辑的设置，每帧与 `settings` 比较
+    edited: RenderSettings,
+    /// surface 格式支持的采样数
+    samples: Vec<u32>,
+
+    camera: CameraBundle,
+    vertex_buffer: wgpu::Buffer,
+    vertex_count: u32,
+
+    shader: wgpu::ShaderModule,
+    pipeline_layout: wgpu::PipelineLayout,
+    scene_pipeline: wgpu::RenderPipeline,
+    texture_layout: wgpu::BindGroupLayout,
+    checker: wgpu::TextureView,
+    texture_bind_group: wgpu::BindGroup,
+
+    blit_pipeline: wgpu::RenderPipeline,
+    blit_layout: wgpu::BindGroupLayout,
+    blit_sampler: wgpu::Sampler,
+    targets: SceneTargets,
+}
+
+impl App {
+    fn create_texture_bind_group(&self) -> wgpu::BindGroup {
+        let sampler = self
+            .gpu
+            .device
+            .create_sampler(&self.settings.sampler_descriptor(Some("checker_sampler")));
+        self.gpu
+            .device
+            .create_bind_group(&wgpu::BindGroupDescriptor {
+                layout: &self.texture_layout,
+                entries: &[
+                    wgpu::BindGroupEntry {
+                        binding: 0,
+                        resource: wgpu::BindingResource::TextureView(&self.checker),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 1,
+                        resource: wgpu::BindingResource::Sampler(&sampler),
+                    },
+                ],
+                label: Some("checker_bind_group"),
+            })
+    }
+
+    /// 只重建设置变化影响到的资源
+    fn apply_settings(&mut self, changes: SettingsChanges) {
+        if changes.render_targets {
+            self.targets = SceneTargets::new(
+                &self.gpu,
+                &self.settings,
+                &self.blit_layout,
+                &self.blit_sampler,
+            );
+        }
+        if changes.pipelines {
+            self.scene_pipeline = create_scene_pipeline(
+                &self.gpu,
+                &self.pipeline_layout,
+                &self.shader,
+                self.settings.msaa_samples,
+            );
+        }
+        if changes.samplers {
+            self.texture_bind_group = self.create_texture_bind_group();
+        }
+        if changes.present_mode {
+            if let Err(e) = self
+                .gpu
+                .set_present_mode_config(self.settings.present_mode())
+            {
+                eprintln!("{e:#}");
+            }
+        }
+        // 本示例没有阴影，实际的应用在这里按 `shadow_quality.map_size()` 重建阴影贴图
+        if changes.shadows {
+            println!(
+                "shadow map size: {:?}",
+                self.settings.shadow_quality.map_size()
+            );
+        }
+    }
+}
+
+impl WindowApp for App {
+    async fn new(window: Arc<Window>) -> Self {
+        let mut gpu = GpuContext::new(window, GpuContextOptions::default())
+            .await
+            .unwrap();
+        gpu.background = Background::Color(wgpu::Color::BLACK);
+        let egui = EguiLayer::new(&gpu);
+
+        let flags = gpu.adapter.get_texture_format_features(gpu.format()).flags;
+        let samples = RenderSettings::supported_samples(&flags);
+        let settings = RenderSettings::default();
+
+        let camera = Camera {
+            eye: (0.0, 3.0, 12.0).into(),
+            target: (0.0, 0.5, 0.0).into(),
+            up: glam::Vec3::Y,
+            aspect: gpu.aspect(),
+            projection: Projection::Perspective { fovy: 45.0 },
+            znear: 0.1,
+            zfar: 500.0,
+            depth: DepthConvention::Standard,
+        };
+        let camera = CameraBundle::builder(camera)
+            .controller(KeyboardController::new(0.2))
+            .build(&gpu.device)
+            .unwrap();
+
+        let vertices = scene_vertices();
+        let vertex_buffer = gpu
+            .device
+            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("Vertex Buffer"),
+                contents: bytemuck::cast_slice(&vertices),
+                usage: wgpu::BufferUsages::VERTEX,
+            });
+
+        let shader_library = ShaderLibrary::new();
+        let shader = shader_library
+            .create_shader_module(&gpu.device, "Scene Shader", include_str!("shader.wgsl"))
+            .unwrap();
+        let texture_layout = Texture::texture_bind_group_layout(&gpu.device);
+        let pipeline_layout = gpu
+            .device
+            .create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some("Scene Pipeline Layout"),
+                bind_group_layouts: &[&camera.bind_group_layout, &texture_layout],
+                push_constant_ranges: &[],
+            });
+        let scene_pipeline =
+            create_scene_pipeline(&gpu, &pipeline_layout, &shader, settings.msaa_samples);
+
+        let blit_shader = shader_library
+            .create_shader_module(&gpu.device, "Blit Shader", include_str!("blit.wgsl"))
+            .unwrap();
+        let blit_layout = Texture::texture_bind_group_layout(&gpu.device);
+        let blit_pipeline_layout =
+            gpu.device
+                .create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                    label: Some("Blit Pipeline Layout"),
+                    bind_group_layouts: &[&blit_layout],
+                    push_constant_ranges: &[],
+                });
+        let blit_pipeline = gpu
+            .device
+            .create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                label: Some("Blit Pipeline"),
+                layout: Some(&blit_pipeline_layout),
+                vertex: wgpu::VertexState {
+                    module: &blit_shader,
+                    compilation_options: Default::default(),
+                    entry_point: Some("vs_main"),
+                    buffers: &[],
+                },
+                fragment: Some(wgpu::FragmentState {
+                    module: &blit_shader,
+                    compilation_options: Default::default(),
+                    entry_point: Some("fs_main"),
+                    targets: &[Some(wgpu::ColorTargetState {
+                        format: gpu.format(),
+                        blend: None,
+                        write_mask: wgpu::ColorWrites::ALL,
+                    })],
+                }),
+                primitive: wgpu::PrimitiveState::default(),
+                depth_stencil: None,
+                multisample: wgpu::MultisampleState::default(),
+                multiview: None,
+                cache: None,
+            });
+        let blit_sampler = gpu.device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("blit_sampler"),
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+        let targets = SceneTargets::new(&gpu, &settings, &blit_layout, &blit_sampler);
+
+        let checker = create_checker(&gpu.device, &gpu.queue);
+        // 绑定组依赖 `settings`，先放一个占位，创建 App 后立即替换
+        let texture_bind_group = gpu.device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: &texture_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(&checker),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(&blit_sampler),
+                },
+            ],
+            label: Some("checker_bind_group"),
+        });
+
+        let mut app = Self {
+            gpu,
+            egui,
+
+            settings,
+            edited: settings,
+            samples,
+
+            camera,
+            vertex_buffer,
+            vertex_count: vertices.len() as u32,
+
+            shader,
+            pipeline_layout,
+            scene_pipeline,
+            texture_layout,
+            checker,
+            texture_bind_group,
+
+            blit_pipeline,
+            blit_layout,
+            blit_sampler,
+            targets,
+        };
+        app.texture_bind_group = app.create_texture_bind_group();
+        app
+    }
+
+    fn render(&mut self) -> Result<(), wgpu::SurfaceError> {
+        self.resize_surface_if_needed();
+
+        let mut frame = self.gpu.begin_frame()?;
+
+        let (view, resolve_target) = match &self.targets.msaa {
+            Some(msaa) => (msaa, Some(&self.targets.resolve)),
+            None => (&self.targets.resolve, None),
+        };
+        let mut scene_pass = frame
+            .encoder
+            .begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Scene Pass"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view,
+                    resolve_target,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color {
+                            r: 0.35,
+                            g: 0.45,
+                            b: 0.6,
+                            a: 1.0,
+                        }),
+                        // 多重采样的附件解析后即可丢弃
+                        store: match resolve_target {
+                            Some(_) => wgpu::StoreOp::Discard,
+                            None => wgpu::StoreOp::Store,
+                        },
+                    },
+                })],
+                depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                    view: &self.targets.depth,
+                    depth_ops: Some(wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(1.0),
+                        store: wgpu::StoreOp::Discard,
+                    }),
+                    stencil_ops: None,
+                }),
+                ..Default::default()
+            });
+        scene_pass.set_pipeline(&self.scene_pipeline);
+        scene_pass.set_bind_group(0, &self.camera.bind_group, &[]);
+        scene_pass.set_bind_group(1, &self.texture_bind_group, &[]);
+        scene_pass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
+        scene_pass.draw(0..self.vertex_count, 0..1);
+        drop(scene_pass);
+
+        let mut blit_pass = frame.render_pass("Blit Pass").begin();
+        blit_pass.set_pipeline(&self.blit_pipeline);
+        blit_pass.set_bind_group(0, &self.targets.blit_bind_group, &[]);
+        blit_pass.draw(0..3, 0..1);
+        drop(blit_pass);
+
+        self.egui
+            .draw(&self.gpu, &mut frame.encoder, &frame.frame.gamma_view);
+
+        self.gpu.end_frame(frame);
+
+        Ok(())
+    }
+
+    fn set_window_resized(&mut self, new_size: PhysicalSize<u32>) {
+        self.gpu.resize(new_size);
+    }
+
+    fn resize_surface_if_needed(&mut self) {
+        if self.gpu.resize_if_needed() {
+            self.targets = SceneTargets::new(
+                &self.gpu,
+                &self.settings,
+                &self.blit_layout,
+                &self.blit_sampler,
+            );
+        }
+    }
+
+    fn gpu_context(&mut self) -> Option<&mut GpuContext> {
+        Some(&mut self.gpu)
+    }
+
+    fn window_event(&mut self, event: &WindowEvent) -> bool {
+        self.egui.on_window_event(self.gpu.window().window(), event)
+    }
+
+    fn keyboard_input(&mut self, event: &KeyEvent) -> bool {
+        self.camera.process_key(event)
+    }
+
+    fn mouse_wheel(&mut self, delta: MouseScrollDelta, _phase: TouchPhase) -> bool {
+        self.camera.process_wheel(delta)
+    }
+
+    fn cameras_mut(&mut self) -> Vec<&mut CameraBundle> {
+        vec![&mut self.camera]
+    }
+
+    fn update(&mut self, time: FrameTime) {
+        let (edited, samples) = (&mut self.edited, &self.samples);
+        self.egui.run(self.gpu.window().window(), |ctx| {
+            egui::Window::new("Render settings").show(ctx, |ui| {
+                edited.ui(ui, samples);
+            });
+        });
+        let changes = self
+            .settings
+            .apply(&mut self.edited, &self.gpu.adapter, self.gpu.format());
+        if !changes.is_empty() {
+            self.apply_settings(changes);
+        }
+
+        self.camera.update(&self.gpu.queue, time.delta_secs());
+    }
+}
+
+fn main() -> Result<(), impl std::error::Error> {
+    let events_loop = EventLoop::new().unwrap();
+    let mut app = WindowAppHandler::<App>::new("render settings example");
+    events_loop.run_app(&mut app)
+}
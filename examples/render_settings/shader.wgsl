@@ -0,0 +1,38 @@
+#include "wgpu_dance/camera.wgsl"
+
+struct VertexInput {
+    @location(0) position: vec3f,
+    @location(1) normal: vec3f,
+    @location(2) tex_coords: vec2f,
+}
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4f,
+    @location(0) normal: vec3f,
+    @location(1) tex_coords: vec2f,
+}
+
+@group(0) @binding(0)
+var<uniform> camera: CameraUniform;
+
+@group(1) @binding(0)
+var t_diffuse: texture_2d<f32>;
+@group(1) @binding(1)
+var s_diffuse: sampler;
+
+@vertex
+fn vs_main(model: VertexInput) -> VertexOutput {
+    var out: VertexOutput;
+    out.normal = model.normal;
+    out.tex_coords = model.tex_coords;
+    out.clip_position = camera.view_proj * vec4f(model.position, 1.0);
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4f {
+    let light = normalize(vec3f(0.4, 1.0, 0.3));
+    let diffuse = max(dot(normalize(in.normal), light), 0.0);
+    let albedo = textureSample(t_diffuse, s_diffuse, in.tex_coords).rgb;
+    return vec4f(albedo * (diffuse * 0.8 + 0.2), 1.0);
+}
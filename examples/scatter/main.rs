@@ -0,0 +1,530 @@
+pub mod vertex;
+
+use std::sync::Arc;
+
+use wgpu::util::DeviceExt;
+use wgpu_dance::{
+    app::{FrameTime, WindowApp, WindowAppHandler},
+    camera::{Camera, CameraBundle, KeyboardController, Projection},
+    environment::{Environment, EnvironmentBundle},
+    indirect::INSTANCE_COUNT_OFFSET,
+    light::{DirectionalLight, DirectionalLightBundle},
+    model::{DrawModel, MeshModel, RenderVertex},
+    post::{aerial::AerialPerspective, PostStack, SceneTextures},
+    profiler::GpuCounters,
+    scatter::{self, CulledInstanceRaw, DensityMap, MeshSurface, ScatterCuller, ScatterSettings},
+    shader::ShaderLibrary,
+    sky::{Sky, Sun},
+    stats::{FrameStats, StatsReporter},
+    texture::{DepthConvention, Texture},
+};
+
+use winit::{
+    dpi::PhysicalSize,
+    event::{KeyEvent, MouseScrollDelta, TouchPhase},
+    event_loop::EventLoop,
+    window::Window,
+};
+
+const TERRAIN_SIZE: f32 = 160.0;
+const TERRAIN_RESOLUTION: u32 = 128;
+/// 立方体模型在单位缩放下的包围球半径
+const CUBE_RADIUS: f32 = 1.75;
+/// 现实中的一秒对应场景中的小时数
+const HOURS_PER_SECOND: f32 = 0.1;
+
+fn terrain_height(x: f32, z: f32) -> f32 {
+    4.0 * (x * 0.05).sin() * (z * 0.04).cos() + 1.5 * (x * 0.13 + z * 0.07).sin()
+}
+
+fn build_terrain() -> (Vec<vertex::TerrainVertex>, Vec<u32>) {
+    let n = TERRAIN_RESOLUTION;
+    let step = TERRAIN_SIZE / (n - 1) as f32;
+    let vertices = (0..n)
+        .flat_map(|j| {
+            (0..n).map(move |i| {
+                let x = i as f32 * step - TERRAIN_SIZE / 2.0;
+                let z = j as f32 * step - TERRAIN_SIZE / 2.0;
+                let dx = terrain_height(x + 0.01, z) - terrain_height(x - 0.01, z);
+                let dz = terrain_height(x, z + 0.01) - terrain_height(x, z - 0.01);
+                let normal = glam::vec3(-dx, 0.02, -dz).normalize();
+                vertex::TerrainVertex {
+                    position: [x, terrain_height(x, z), z],
+                    normal: normal.to_array(),
+                }
+            })
+        })
+        .collect();
+    let indices = (0..n - 1)
+        .flat_map(|j| {
+            (0..n - 1).flat_map(move |i| {
+                let a = j * n + i;
+                [a, a + n, a + 1, a + 1, a + n, a + n + 1]
+            })
+        })
+        .collect();
+    (vertices, indices)
+}
+
+/// 中间留出一条弯曲的空地，向外逐渐变密
+fn build_density_map() -> DensityMap {
+    let size = 64;
+    let values = (0..size)
+        .flat_map(|y| {
+            (0..size).map(move |x| {
+                let u = x as f32 / (size - 1) as f32;
+                let v = y as f32 / (size - 1) as f32;
+                let path = (u - 0.5 - 0.15 * (v * std::f32::consts::TAU).sin()).abs();
+                ((path - 0.05) / 0.25).clamp(0.0, 1.0)
+            })
+        })
+        .collect();
+    DensityMap {
+        width: size,
+        height: size,
+        values,
+    }
+}
+
+struct App {
+    device: wgpu::Device,
+    queue: wgpu::Queue,
+
+    surface: wgpu::Surface<'static>,
+    surface_config: wgpu::SurfaceConfiguration,
+
+    size: winit::dpi::PhysicalSize<u32>,
+    size_changed: bool,
+
+    terrain_pipeline: wgpu::RenderPipeline,
+    terrain_vertex_buffer: wgpu::Buffer,
+    terrain_index_buffer: wgpu::Buffer,
+    terrain_index_count: u32,
+
+    render_pipeline: wgpu::RenderPipeline,
+    obj_model: MeshModel,
+    culler: ScatterCuller,
+    /// 剔除后的实例数，晚几帧显示在窗口标题中
+    counters: GpuCounters,
+
+    scene_color: Texture,
+    depth_texture: Texture,
+
+    camera: CameraBundle,
+    light: DirectionalLightBundle,
+    sun: Sun,
+    sky: Sky,
+    environment: EnvironmentBundle,
+    post: PostStack,
+}
+
+impl WindowApp for App {
+    async fn new(window: Arc<Window>) -> Self {
+        let instance = wgpu::Instance::new(&wgpu::InstanceDescriptor {
+            backends: wgpu::Backends::all(),
+            ..Default::default()
+        });
+        let surface = instance.create_surface(window.clone()).unwrap();
+
+        let adapter = instance
+            .request_adapter(&wgpu::RequestAdapterOptions {
+                power_preference: wgpu::PowerPreference::default(),
+                compatible_surface: Some(&surface),
+                force_fallback_adapter: false,
+            })
+            .await
+            .unwrap();
+
+        let (device, queue) = adapter
+            .request_device(
+                &wgpu::DeviceDescriptor {
+                    required_features: wgpu::Features::empty(),
+                    required_limits: wgpu::Limits::default(),
+                    label: None,
+                    memory_hints: wgpu::MemoryHints::Performance,
+                },
+                None,
+            )
+            .await
+            .unwrap();
+
+        let size = window.inner_size();
+
+        let caps = surface.get_capabilities(&adapter);
+        let surface_config = wgpu::SurfaceConfiguration {
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+            format: caps.formats[0],
+            width: size.width,
+            height: size.height,
+            present_mode: wgpu::PresentMode::Fifo,
+            alpha_mode: caps.alpha_modes[0],
+            view_formats: vec![],
+            desired_maximum_frame_latency: 2,
+        };
+        surface.configure(&device, &surface_config);
+
+        let camera = Camera {
+            eye: (0.0, 12.0, 45.0).into(),
+            target: (0.0, 2.0, 0.0).into(),
+            up: glam::Vec3::Y,
+            aspect: surface_config.width as f32 / surface_config.height as f32,
+            projection: Projection::Perspective { fovy: 60.0 },
+            znear: 0.1,
+            zfar: 200.0,
+            depth: DepthConvention::Standard,
+        };
+        let camera = CameraBundle::builder(camera)
+            .controller(KeyboardController::new(0.4))
+            .build(&device)
+            .unwrap();
+
+        let sun = Sun {
+            time_of_day: 15.0,
+            ..Default::default()
+        };
+        let mut light = DirectionalLight::default();
+        sun.apply_to(&mut light);
+        let light = DirectionalLightBundle::new(light, &device);
+
+        let mut environment = Environment::default();
+        environment.set_sun(&sun);
+        // 贴近地面的薄雾
+        environment.fog.density = 0.004;
+        environment.fog.height_falloff = 0.2;
+        let environment = EnvironmentBundle::new(environment, &device);
+
+        let sky = Sky::new(&device, surface_config.format, Some(Texture::DEPTH_FORMAT));
+
+        let mut post = PostStack::new(&device, &surface_config, surface_config.format);
+        post.push(AerialPerspective::new(&device, post.format(), &environment));
+
+        let scene_color = Texture::create_render_target(&device, &surface_config, "scene_color");
+
+        let depth_texture =
+            Texture::create_depth_texture(&device, &surface_config, "depth_texture");
+
+        let texture_bind_group_layout = Texture::texture_bind_group_layout(&device);
+        let library = ShaderLibrary::new();
+
+        let primitive = wgpu::PrimitiveState {
+            topology: wgpu::PrimitiveTopology::TriangleList,
+            strip_index_format: None,
+            front_face: wgpu::FrontFace::Ccw,
+            cull_mode: Some(wgpu::Face::Back),
+            polygon_mode: wgpu::PolygonMode::Fill,
+            unclipped_depth: false,
+            conservative: false,
+        };
+        let depth_stencil = wgpu::DepthStencilState {
+            format: Texture::DEPTH_FORMAT,
+            depth_write_enabled: true,
+            depth_compare: wgpu::CompareFunction::Less,
+            stencil: wgpu::StencilState::default(),
+            bias: wgpu::DepthBiasState::default(),
+        };
+        let color_target = wgpu::ColorTargetState {
+            format: surface_config.format,
+            blend: Some(wgpu::BlendState::REPLACE),
+            write_mask: wgpu::ColorWrites::ALL,
+        };
+
+        let terrain_shader = library
+            .create_shader_module(&device, "Terrain Shader", include_str!("terrain.wgsl"))
+            .unwrap();
+        let terrain_pipeline_layout =
+            device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some("Terrain Pipeline Layout"),
+                bind_group_layouts: &[&camera.bind_group_layout, &light.bind_group_layout],
+                push_constant_ranges: &[],
+            });
+        let terrain_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Terrain Pipeline"),
+            layout: Some(&terrain_pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &terrain_shader,
+                compilation_options: Default::default(),
+                entry_point: Some("vs_main"),
+                buffers: &[vertex::TerrainVertex::buffer_layout_desc()],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &terrain_shader,
+                compilation_options: Default::default(),
+                entry_point: Some("fs_main"),
+                targets: &[Some(color_target.clone())],
+            }),
+            primitive,
+            depth_stencil: Some(depth_stencil.clone()),
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+            cache: None,
+        });
+
+        let shader = library
+            .create_shader_module(&device, "Shader", include_str!("shader.wgsl"))
+            .unwrap();
+        let render_pipeline_layout =
+            device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some("Render Pipeline Layout"),
+                bind_group_layouts: &[
+                    &camera.bind_group_layout,
+                    &texture_bind_group_layout,
+                    &light.bind_group_layout,
+                ],
+                push_constant_ranges: &[],
+            });
+        let render_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Render Pipeline"),
+            layout: Some(&render_pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                compilation_options: Default::default(),
+                entry_point: Some("vs_main"),
+                buffers: &[
+                    CulledInstanceRaw::buffer_layout_desc(),
+                    vertex::Vertex::buffer_layout_desc(),
+                ],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                compilation_options: Default::default(),
+                entry_point: Some("fs_main"),
+                targets: &[Some(color_target)],
+            }),
+            primitive,
+            depth_stencil: Some(depth_stencil),
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+            cache: None,
+        });
+
+        let obj_model = MeshModel::load_model::<vertex::Vertex>(
+            "cube.obj",
+            &device,
+            &queue,
+            &texture_bind_group_layout,
+        )
+        .await
+        .unwrap();
+
+        let (terrain_vertices, terrain_indices) = build_terrain();
+        let positions = terrain_vertices
+            .iter()
+            .map(|v| glam::Vec3::from(v.position))
+            .collect::<Vec<_>>();
+        let surface_mesh = MeshSurface::new(&positions, &terrain_indices);
+        let instances = scatter::scatter(
+            &surface_mesh,
+            &ScatterSettings {
+                min_distance: 1.2,
+                density: Some(build_density_map()),
+                seed: 7,
+                scale_range: (0.15, 0.35),
+                ..Default::default()
+            },
+        );
+        println!("scattered {} instances", instances.len());
+
+        let mut culler = ScatterCuller::new(
+            &device,
+            &instances,
+            CUBE_RADIUS,
+            obj_model.meshes[0].num_elements,
+        );
+        culler.fade_start = 50.0;
+        culler.fade_end = 80.0;
+        let counters = GpuCounters::new(&device, 1, 3);
+
+        let terrain_vertex_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Terrain Vertex Buffer"),
+            contents: bytemuck::cast_slice(&terrain_vertices),
+            usage: wgpu::BufferUsages::VERTEX,
+        });
+        let terrain_index_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Terrain Index Buffer"),
+            contents: bytemuck::cast_slice(&terrain_indices),
+            usage: wgpu::BufferUsages::INDEX,
+        });
+
+        Self {
+            device,
+            queue,
+
+            surface,
+            surface_config,
+
+            size,
+            size_changed: false,
+
+            terrain_pipeline,
+            terrain_vertex_buffer,
+            terrain_index_buffer,
+            terrain_index_count: terrain_indices.len() as u32,
+
+            render_pipeline,
+            obj_model,
+            culler,
+            counters,
+
+            scene_color,
+            depth_texture,
+
+            camera,
+            light,
+            sun,
+            sky,
+            environment,
+            post,
+        }
+    }
+
+    fn render(&mut self) -> Result<(), wgpu::SurfaceError> {
+        self.resize_surface_if_needed();
+
+        let output = self.surface.get_current_texture()?;
+        let view = output
+            .texture
+            .create_view(&wgpu::TextureViewDescriptor::default());
+
+        let mut encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("Render Encoder"),
+            });
+
+        self.culler.cull(&mut encoder);
+        self.counters.copy(
+            &mut encoder,
+            "visible",
+            self.culler.indirect_buffer(),
+            INSTANCE_COUNT_OFFSET,
+        );
+
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Render Pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: &self.scene_color.view,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                view: &self.depth_texture.view,
+                depth_ops: Some(wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(1.0),
+                    store: wgpu::StoreOp::Store,
+                }),
+                stencil_ops: None,
+            }),
+            ..Default::default()
+        });
+
+        self.sky.draw(&mut render_pass);
+
+        render_pass.set_pipeline(&self.terrain_pipeline);
+        render_pass.set_bind_group(0, &self.camera.bind_group, &[]);
+        render_pass.set_bind_group(1, &self.light.bind_group, &[]);
+        render_pass.set_vertex_buffer(0, self.terrain_vertex_buffer.slice(..));
+        render_pass.set_index_buffer(
+            self.terrain_index_buffer.slice(..),
+            wgpu::IndexFormat::Uint32,
+        );
+        render_pass.draw_indexed(0..self.terrain_index_count, 0, 0..1);
+
+        let mesh = &self.obj_model.meshes[0];
+        render_pass.set_pipeline(&self.render_pipeline);
+        render_pass.set_vertex_buffer(0, self.culler.instance_buffer().slice(..));
+        render_pass.set_bind_group(2, &self.light.bind_group, &[]);
+        render_pass.draw_mesh_indirect(
+            mesh,
+            &self.obj_model.materials[mesh.material],
+            self.culler.indirect_buffer(),
+            &self.camera.bind_group,
+        );
+
+        drop(render_pass);
+
+        self.post.run(
+            &self.device,
+            &mut encoder,
+            &SceneTextures {
+                color: &self.scene_color,
+                depth: &self.depth_texture,
+                normal_roughness: None,
+                velocity: None,
+            },
+            &view,
+        );
+
+        self.queue.submit(Some(encoder.finish()));
+        self.counters.map();
+        output.present();
+
+        Ok(())
+    }
+
+    fn set_window_resized(&mut self, new_size: PhysicalSize<u32>) {
+        if new_size == self.size {
+            return;
+        }
+        self.size = new_size;
+        self.size_changed = true;
+    }
+
+    fn resize_surface_if_needed(&mut self) {
+        if self.size_changed {
+            self.surface_config.width = self.size.width;
+            self.surface_config.height = self.size.height;
+            self.surface.configure(&self.device, &self.surface_config);
+            self.scene_color =
+                Texture::create_render_target(&self.device, &self.surface_config, "scene_color");
+            self.depth_texture =
+                Texture::create_depth_texture(&self.device, &self.surface_config, "depth_texture");
+            self.post.resize(&self.device, &self.surface_config);
+            self.size_changed = false;
+        }
+    }
+
+    fn keyboard_input(&mut self, event: &KeyEvent) -> bool {
+        self.camera.process_key(event)
+    }
+
+    fn mouse_wheel(&mut self, delta: MouseScrollDelta, _phase: TouchPhase) -> bool {
+        self.camera.process_wheel(delta)
+    }
+
+    fn cameras_mut(&mut self) -> Vec<&mut CameraBundle> {
+        vec![&mut self.camera]
+    }
+
+    fn update(&mut self, time: FrameTime) {
+        let dt = time.delta_secs();
+        self.sun.advance(dt * HOURS_PER_SECOND);
+        self.sun.apply_to(&mut self.light.light);
+        self.light.update(&self.queue);
+        self.environment.environment.set_sun(&self.sun);
+        self.environment.update(&self.queue);
+
+        self.camera.update(&self.queue, time.delta_secs());
+        self.culler.update(&self.queue, &self.camera.state);
+        self.sky.update(&self.queue, &self.camera.state, &self.sun);
+        self.post.update(&self.queue, &self.camera.state);
+        self.counters.poll(&self.device);
+    }
+
+    fn stats_summary(&self) -> Option<String> {
+        Some(format!(
+            "{} instances | {}",
+            self.culler.instance_count(),
+            self.counters.summary()
+        ))
+    }
+}
+
+fn main() -> Result<(), impl std::error::Error> {
+    let events_loop = EventLoop::new().unwrap();
+    let mut app = WindowAppHandler::<App>::new("scatter example")
+        .with_frame_stats(FrameStats::default().with_reporter(StatsReporter::WindowTitle));
+    events_loop.run_app(&mut app)
+}
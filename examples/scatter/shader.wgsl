@@ -0,0 +1,69 @@
+#include "wgpu_dance/camera.wgsl"
+#include "wgpu_dance/lighting.wgsl"
+
+struct VertexInput {
+    @location(4) position: vec3f,
+    @location(5) tex_coords: vec2f,
+    @location(6) normal: vec3f,
+}
+
+struct InstanceInput {
+    @location(0) model_matrix_0: vec4f,
+    @location(1) model_matrix_1: vec4f,
+    @location(2) model_matrix_2: vec4f,
+    @location(3) model_matrix_3: vec4f,
+    @location(7) fade: vec4f,
+};
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4f,
+    @location(0) tex_coords: vec2f,
+    @location(1) world_normal: vec3f,
+    @location(2) fade: f32,
+}
+
+@group(0) @binding(0)
+var<uniform> camera: CameraUniform;
+
+@group(2) @binding(0)
+var<uniform> sun: DirectionalLight;
+
+@vertex
+fn vs_main(
+    model: VertexInput,
+    instance: InstanceInput,
+) -> VertexOutput {
+    var out: VertexOutput;
+    let model_matrix = mat4x4f(
+        instance.model_matrix_0,
+        instance.model_matrix_1,
+        instance.model_matrix_2,
+        instance.model_matrix_3,
+    );
+    out.tex_coords = model.tex_coords;
+    out.world_normal = (model_matrix * vec4f(model.normal, 0.0)).xyz;
+    out.clip_position = camera.view_proj * model_matrix * vec4f(model.position, 1.0);
+    out.fade = instance.fade.x;
+    return out;
+}
+
+@group(1) @binding(0)
+var t_diffuse: texture_2d<f32>;
+@group(1) @binding(1)
+var s_diffuse: sampler;
+
+// 交错梯度噪声，用于按淡出系数做抖动剔除，避免远处实例突然消失
+fn interleaved_gradient_noise(pixel: vec2f) -> f32 {
+    return fract(52.9829189 * fract(dot(pixel, vec2f(0.06711056, 0.00583715))));
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4f {
+    if (in.fade < interleaved_gradient_noise(in.clip_position.xy)) {
+        discard;
+    }
+    let albedo = textureSample(t_diffuse, s_diffuse, in.tex_coords);
+    let diffuse = lambert(normalize(in.world_normal), sun.direction.xyz) * sun.direction.w;
+    let ambient = 0.15;
+    return vec4f(albedo.rgb * (sun.color.rgb * diffuse + ambient), albedo.a);
+}
@@ -0,0 +1,33 @@
+#include "wgpu_dance/camera.wgsl"
+#include "wgpu_dance/lighting.wgsl"
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4f,
+    @location(0) world_normal: vec3f,
+    @location(1) height: f32,
+}
+
+@group(0) @binding(0)
+var<uniform> camera: CameraUniform;
+
+@group(1) @binding(0)
+var<uniform> sun: DirectionalLight;
+
+@vertex
+fn vs_main(@location(4) position: vec3f, @location(6) normal: vec3f) -> VertexOutput {
+    var out: VertexOutput;
+    out.world_normal = normal;
+    out.height = position.y;
+    out.clip_position = camera.view_proj * vec4f(position, 1.0);
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4f {
+    let grass = vec3f(0.22, 0.35, 0.14);
+    let rock = vec3f(0.4, 0.36, 0.32);
+    let normal = normalize(in.world_normal);
+    let albedo = mix(rock, grass, smoothstep(0.6, 0.85, normal.y));
+    let diffuse = lambert(normal, sun.direction.xyz) * sun.direction.w;
+    return vec4f(albedo * (sun.color.rgb * diffuse + 0.15), 1.0);
+}
@@ -0,0 +1,17 @@
+#include "wgpu_dance/fullscreen.wgsl"
+
+@group(0) @binding(0)
+var t_traced: texture_2d<f32>;
+@group(0) @binding(1)
+var s_traced: sampler;
+
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32) -> FullscreenOutput {
+    return fullscreen_vertex(index);
+}
+
+// 光线追踪的结果与输出大小相同，直接复制
+@fragment
+fn fs_main(in: FullscreenOutput) -> @location(0) vec4f {
+    return textureSample(t_traced, s_traced, in.uv);
+}
@@ -0,0 +1,384 @@
+use std::{sync::Arc, time::Instant};
+
+use glam::{vec3, Mat4, Quat, Vec3};
+use wgpu_dance::{
+    ao::AoBaker,
+    app::{FrameTime, WindowApp, WindowAppHandler},
+    camera::{Camera, CameraController, KeyboardController, Projection},
+    context::{GpuContext, GpuContextOptions},
+    light::{DirectionalLight, PointLight},
+    post::compare::ComparePass,
+    replay::InputEvent,
+    scene::{
+        raster::{self, SceneRenderer},
+        raytrace::RayTracer,
+        ImageDifference, Scene, SceneLight, SceneMaterial, SceneMesh,
+    },
+    shader::ShaderLibrary,
+    texture::{DepthConvention, Texture},
+};
+use winit::{
+    dpi::{PhysicalPosition, PhysicalSize},
+    event::{ElementState, KeyEvent, MouseButton},
+    event_loop::EventLoop,
+    keyboard::{KeyCode, PhysicalKey},
+    window::Window,
+};
+
+fn build_scene(aspect: f32) -> Scene {
+    let mut scene = Scene::new(Camera {
+        eye: vec3(0.0, 3.0, 8.0),
+        target: vec3(0.0, 0.8, 0.0),
+        up: Vec3::Y,
+        aspect,
+        projection: Projection::Perspective { fovy: 45.0 },
+        znear: 0.1,
+        zfar: 100.0,
+        depth: DepthConvention::Standard,
+    });
+    let floor = scene.add_material(SceneMaterial {
+        base_color: Vec3::splat(0.6),
+        ..Default::default()
+    });
+    let red = scene.add_material(SceneMaterial {
+        base_color: vec3(0.8, 0.1, 0.08),
+        specular: 0.5,
+        shininess: 64.0,
+    });
+    let blue = scene.add_material(SceneMaterial {
+        base_color: vec3(0.1, 0.2, 0.7),
+        specular: 1.0,
+        shininess: 256.0,
+    });
+    let gold = scene.add_material(SceneMaterial {
+        base_color: vec3(0.9, 0.7, 0.2),
+        specular: 0.3,
+        shininess: 16.0,
+    });
+    scene.meshes = vec![
+        SceneMesh::plane(6.0).with_material(floor),
+        SceneMesh::sphere(1.0, 48, 24)
+            .with_transform(Mat4::from_translation(vec3(-1.5, 1.0, 0.0)))
+            .with_material(red),
+        SceneMesh::sphere(0.7, 48, 24)
+            .with_transform(Mat4::from_translation(vec3(1.2, 0.7, 1.2)))
+            .with_material(blue),
+        SceneMesh::cuboid(Vec3::splat(0.6))
+            .with_transform(Mat4::from_rotation_translation(
+                Quat::from_rotation_y(0.6),
+                vec3(1.5, 0.6, -1.5),
+            ))
+            .with_material(gold),
+    ];
+    scene.lights = vec![
+        SceneLight::Directional(DirectionalLight {
+            direction: vec3(-0.4, 1.0, 0.6).normalize(),
+            intensity: 0.8,
+            ..Default::default()
+        }),
+        SceneLight::Point(PointLight {
+            position: vec3(2.5, 2.5, 2.0),
+            color: vec3(1.0, 0.6, 0.3),
+            intensity: 6.0,
+            range: 12.0,
+            ..Default::default()
+        }),
+        SceneLight::Point(PointLight {
+            position: vec3(-3.0, 2.0, 2.5),
+            color: vec3(0.3, 0.5, 1.0),
+            intensity: 5.0,
+            range: 12.0,
+            ..Default::default()
+        }),
+    ];
+    // 环境光较强时烘焙的环境光遮蔽才明显
+    scene.ambient = Vec3::splat(0.15);
+    scene
+}
+
+struct App {
+    gpu: GpuContext,
+    scene: Scene,
+    controller: KeyboardController,
+    renderer: SceneRenderer,
+    depth_texture: Texture,
+
+    compare: ComparePass,
+    cursor_x: f32,
+    tracer: RayTracer,
+    blit_pipeline: wgpu::RenderPipeline,
+    blit_layout: wgpu::BindGroupLayout,
+    /// 最近一次光线追踪的结果，相机移动或窗口大小改变后需要按 R 重新追踪
+    traced: Option<wgpu::BindGroup>,
+}
+
+impl App {
+    /// 烘焙或清除环境光遮蔽，网格顶点随之改变，需要重新创建光栅化渲染器
+    fn toggle_ao(&mut self) {
+        if self.scene.meshes.iter().any(|mesh| !mesh.ao.is_empty()) {
+            for mesh in &mut self.scene.meshes {
+                mesh.ao.clear();
+            }
+        } else {
+            let start = Instant::now();
+            if let Err(e) = self
+                .scene
+                .bake_ao(&AoBaker::default().with_max_distance(1.5))
+            {
+                eprintln!("{e:#}");
+                return;
+            }
+            println!("baked ambient occlusion in {:.2?}", start.elapsed());
+        }
+        self.renderer =
+            SceneRenderer::new(&self.gpu.device, self.gpu.format(), &self.scene).unwrap();
+        self.trace();
+    }
+
+    /// 以当前相机光线追踪，并与同样大小的离屏光栅化结果比较
+    fn trace(&mut self) {
+        let size = self.gpu.size();
+        let start = Instant::now();
+        let image = match self.tracer.render(&self.scene, size.width, size.height) {
+            Ok(image) => image,
+            Err(e) => {
+                eprintln!("{e:#}");
+                return;
+            }
+        };
+        println!(
+            "ray traced {}x{} ({} samples per pixel, shadows {}) in {:.2?}",
+            size.width,
+            size.height,
+            self.tracer.samples * self.tracer.samples,
+            if self.tracer.shadows { "on" } else { "off" },
+            start.elapsed()
+        );
+        match raster::render_image(
+            &self.gpu.device,
+            &self.gpu.queue,
+            &self.scene,
+            size.width,
+            size.height,
+        )
+        .and_then(|rasterized| ImageDifference::new(&rasterized, &image, 8))
+        {
+            Ok(diff) => println!(
+                "raster vs ray traced: rmse {:.2}, max {}, {:.2}% pixels differ by more than 8",
+                diff.rmse,
+                diff.max,
+                diff.mismatched * 100.0
+            ),
+            Err(e) => eprintln!("{e:#}"),
+        }
+
+        let texture = Texture::from_image(
+            &self.gpu.device,
+            &self.gpu.queue,
+            &image::DynamicImage::ImageRgba8(image),
+            Some("traced"),
+        )
+        .unwrap();
+        self.traced = Some(
+            self.gpu
+                .device
+                .create_bind_group(&wgpu::BindGroupDescriptor {
+                    layout: &self.blit_layout,
+                    entries: &[
+                        wgpu::BindGroupEntry {
+                            binding: 0,
+                            resource: wgpu::BindingResource::TextureView(&texture.view),
+                        },
+                        wgpu::BindGroupEntry {
+                            binding: 1,
+                            resource: wgpu::BindingResource::Sampler(&texture.sampler),
+                        },
+                    ],
+                    label: Some("traced_bind_group"),
+                }),
+        );
+    }
+}
+
+impl WindowApp for App {
+    async fn new(window: Arc<Window>) -> Self {
+        let gpu = GpuContext::new(window, GpuContextOptions::default())
+            .await
+            .unwrap();
+        let scene = build_scene(gpu.aspect());
+        let renderer = SceneRenderer::new(&gpu.device, gpu.format(), &scene).unwrap();
+        let depth_texture =
+            Texture::create_depth_texture(&gpu.device, &gpu.surface_config, "depth_texture");
+        let compare = ComparePass::new(&gpu.device, &gpu.surface_config, gpu.format());
+
+        let blit_shader = ShaderLibrary::new()
+            .create_shader_module(&gpu.device, "Blit Shader", include_str!("blit.wgsl"))
+            .unwrap();
+        let blit_layout = Texture::texture_bind_group_layout(&gpu.device);
+        let blit_pipeline_layout =
+            gpu.device
+                .create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                    label: Some("Blit Pipeline Layout"),
+                    bind_group_layouts: &[&blit_layout],
+                    push_constant_ranges: &[],
+                });
+        let blit_pipeline = gpu
+            .device
+            .create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                label: Some("Blit Pipeline"),
+                layout: Some(&blit_pipeline_layout),
+                vertex: wgpu::VertexState {
+                    module: &blit_shader,
+                    compilation_options: Default::default(),
+                    entry_point: Some("vs_main"),
+                    buffers: &[],
+                },
+                fragment: Some(wgpu::FragmentState {
+                    module: &blit_shader,
+                    compilation_options: Default::default(),
+                    entry_point: Some("fs_main"),
+                    targets: &[Some(wgpu::ColorTargetState {
+                        format: gpu.format(),
+                        blend: None,
+                        write_mask: wgpu::ColorWrites::ALL,
+                    })],
+                }),
+                primitive: wgpu::PrimitiveState::default(),
+                depth_stencil: None,
+                multisample: wgpu::MultisampleState::default(),
+                multiview: None,
+                cache: None,
+            });
+
+        let mut app = Self {
+            gpu,
+            scene,
+            controller: KeyboardController::new(0.1),
+            renderer,
+            depth_texture,
+
+            compare,
+            cursor_x: 0.5,
+            tracer: RayTracer::default(),
+            blit_pipeline,
+            blit_layout,
+            traced: None,
+        };
+        app.trace();
+        app
+    }
+
+    fn render(&mut self) -> Result<(), wgpu::SurfaceError> {
+        self.resize_surface_if_needed();
+
+        let mut frame = self.gpu.begin_frame()?;
+        let background = self.scene.background;
+        let background = wgpu::Color {
+            r: background.x as f64,
+            g: background.y as f64,
+            b: background.z as f64,
+            a: 1.0,
+        };
+
+        let mut raster_pass = frame
+            .render_pass("Raster Pass")
+            .color(self.compare.left())
+            .clear_color(background)
+            .depth(&self.depth_texture.view)
+            .begin();
+        self.renderer.draw(&mut raster_pass);
+        drop(raster_pass);
+
+        let mut traced_pass = frame
+            .render_pass("Traced Pass")
+            .color(self.compare.right())
+            .clear_color(background)
+            .begin();
+        if let Some(traced) = &self.traced {
+            traced_pass.set_pipeline(&self.blit_pipeline);
+            traced_pass.set_bind_group(0, traced, &[]);
+            traced_pass.draw(0..3, 0..1);
+        }
+        drop(traced_pass);
+
+        self.compare
+            .composite(&self.gpu.queue, &mut frame.encoder, &frame.frame.view);
+
+        self.gpu.end_frame(frame);
+
+        Ok(())
+    }
+
+    fn set_window_resized(&mut self, new_size: PhysicalSize<u32>) {
+        self.gpu.resize(new_size);
+    }
+
+    fn resize_surface_if_needed(&mut self) {
+        if self.gpu.resize_if_needed() {
+            self.scene.camera.set_viewport_size(self.gpu.size());
+            self.depth_texture = Texture::create_depth_texture(
+                &self.gpu.device,
+                &self.gpu.surface_config,
+                "depth_texture",
+            );
+            self.compare
+                .resize(&self.gpu.device, &self.gpu.surface_config);
+            // 旧的追踪结果大小不再匹配
+            self.traced = None;
+        }
+    }
+
+    fn gpu_context(&mut self) -> Option<&mut GpuContext> {
+        Some(&mut self.gpu)
+    }
+
+    fn keyboard_input(&mut self, event: &KeyEvent) -> bool {
+        if self
+            .controller
+            .process_event(&InputEvent::Key(event.into()))
+        {
+            return true;
+        }
+        if event.state != ElementState::Pressed || event.repeat {
+            return false;
+        }
+        // R 键以当前相机重新追踪，C 键切换对照模式，S 键开关阴影，A 键切换每像素 1 或 16 条射线，
+        // O 键开关烘焙的环境光遮蔽
+        match event.physical_key {
+            PhysicalKey::Code(KeyCode::KeyR) => self.trace(),
+            PhysicalKey::Code(KeyCode::KeyC) => self.compare.mode = self.compare.mode.next(),
+            PhysicalKey::Code(KeyCode::KeyS) => {
+                self.tracer.shadows = !self.tracer.shadows;
+                self.trace();
+            }
+            PhysicalKey::Code(KeyCode::KeyA) => {
+                self.tracer.samples = if self.tracer.samples == 1 { 4 } else { 1 };
+                self.trace();
+            }
+            PhysicalKey::Code(KeyCode::KeyO) => self.toggle_ao(),
+            _ => return false,
+        }
+        true
+    }
+
+    fn mouse_click(&mut self, state: ElementState, button: MouseButton) -> bool {
+        button == MouseButton::Left && self.compare.mouse_button(state.is_pressed(), self.cursor_x)
+    }
+
+    fn cursor_move(&mut self, position: PhysicalPosition<f64>) -> bool {
+        self.cursor_x = position.x as f32 / self.gpu.size().width.max(1) as f32;
+        self.compare.drag(self.cursor_x)
+    }
+
+    fn update(&mut self, time: FrameTime) {
+        self.controller
+            .update(&mut self.scene.camera, time.delta_secs());
+        self.renderer.update(&self.gpu.queue, &self.scene);
+    }
+}
+
+fn main() -> Result<(), impl std::error::Error> {
+    let events_loop = EventLoop::new().unwrap();
+    let mut app = WindowAppHandler::<App>::new("scene compare example");
+    events_loop.run_app(&mut app)
+}
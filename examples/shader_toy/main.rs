@@ -0,0 +1,21 @@
+use wgpu_dance::{
+    app::WindowAppHandler,
+    shader_toy::{ShaderToy, ShaderToyApp},
+};
+use winit::event_loop::EventLoop;
+
+struct Plasma;
+
+impl ShaderToy for Plasma {
+    const PATH: &'static str = concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/examples/shader_toy/plasma.wgsl"
+    );
+    const SOURCE: &'static str = include_str!("plasma.wgsl");
+}
+
+fn main() -> Result<(), impl std::error::Error> {
+    let events_loop = EventLoop::new().unwrap();
+    let mut app = WindowAppHandler::<ShaderToyApp<Plasma>>::new("shader toy example");
+    events_loop.run_app(&mut app)
+}
@@ -0,0 +1,22 @@
+// 运行时修改并保存这个文件，画面会自动更新
+
+fn palette(t: f32) -> vec3f {
+    return 0.5 + 0.5 * cos(6.28318 * (t + vec3f(0.0, 0.33, 0.67)));
+}
+
+fn main_image(frag_coord: vec2f) -> vec4f {
+    let t = toy.time.x;
+    // 以画面中心为原点、短边为单位长度
+    let p = (frag_coord - 0.5 * toy.resolution.xy) / min(toy.resolution.x, toy.resolution.y);
+
+    var v = sin(p.x * 10.0 + t) + sin((p.y * 10.0 + t) * 0.5);
+    v += sin(length(p * 12.0) - t * 2.0);
+
+    // 按住左键时以光标为中心产生涟漪
+    if (toy.mouse.z >= 0.0) {
+        let m = (toy.mouse.xy - 0.5 * toy.resolution.xy) / min(toy.resolution.x, toy.resolution.y);
+        v += 2.0 * sin(length(p - m) * 40.0 - t * 6.0) * exp(-length(p - m) * 4.0);
+    }
+
+    return vec4f(palette(v * 0.25 + t * 0.05), 1.0);
+}
@@ -1,6 +1,6 @@
 use anyhow::Ok;
 use glam::{vec3, vec4, Vec3, Vec4};
-use std::{f32::consts::FRAC_PI_2, fs, io::Write};
+use std::{fs, io::Write};
 
 #[derive(Clone, Copy, Debug, Default)]
 struct Material {
@@ -80,12 +80,11 @@ impl PointLight {
 fn map_to_image(frame_buffer: &[Vec3]) -> Vec<u8> {
     frame_buffer
         .iter()
-        .map(|v| {
+        .flat_map(|v| {
             v.to_array()
                 .map(|x| (x.clamp(0., 1.) * 255.) as u8)
                 .to_vec()
         })
-        .flatten()
         .collect::<Vec<u8>>()
 }
 
@@ -93,7 +92,7 @@ fn refract(i: &Vec3, n: &Vec3, refract_index: &f32) -> Vec3 {
     let mut cosi = -i.dot(*n).clamp(-1., 1.);
     let mut etai = 1.;
     let mut etat = *refract_index;
-    let mut n = n.clone();
+    let mut n = *n;
     if cosi < 0. {
         cosi = -cosi;
         std::mem::swap(&mut etai, &mut etat);
@@ -116,7 +115,7 @@ fn scene_intersect(ray: &Ray, spheres: &[Sphere]) -> Option<(Vec3, Vec3, Materia
     let mut material = Material::default();
 
     for s in spheres {
-        let intersect_test_res = s.ray_intersect(&ray);
+        let intersect_test_res = s.ray_intersect(ray);
         if intersect_test_res.0 && intersect_test_res.1 < dist {
             dist = intersect_test_res.1;
             hit = ray.origin + ray.direction * dist;
@@ -139,7 +138,7 @@ fn scene_intersect(ray: &Ray, spheres: &[Sphere]) -> Option<(Vec3, Vec3, Materia
                 } else {
                     vec3(1., 0.7, 0.3)
                 };
-            material.color = material.color * 0.3;
+            material.color *= 0.3;
             material.albedo = Vec4::X;
             material.refract_index = 1.0;
             material.specular = 0.0;
@@ -306,7 +305,7 @@ fn main() -> anyhow::Result<()> {
 
     let mut f = fs::File::create("./out.ppm")?;
     write!(f, "P6\n{} {}\n255\n", width, height)?;
-    f.write(&map_to_image(&framebuffer))?;
+    f.write_all(&map_to_image(&framebuffer))?;
 
     Ok(())
 }
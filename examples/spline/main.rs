@@ -0,0 +1,416 @@
+pub mod vertex;
+
+use std::sync::Arc;
+
+use wgpu_dance::{
+    app::{FrameTime, WindowApp, WindowAppHandler},
+    camera::{Camera, CameraBundle, KeyboardController, Projection},
+    camera_path::{CameraKeyframe, CameraPath, Easing},
+    light::{DirectionalLight, DirectionalLightBundle},
+    model::{Model, RenderVertex},
+    pipeline::{PipelineBuilder, ReflectedShader},
+    polyline::{LineStyle, LineWidth, PolylineRenderer},
+    shader::ShaderLibrary,
+    spline::{extrude, tube, BezierSpline, CatmullRom, Curve, ExtrudeSettings},
+    texture::{DepthConvention, Texture},
+};
+
+use winit::{
+    dpi::PhysicalSize,
+    event::{KeyEvent, MouseScrollDelta, TouchPhase},
+    event_loop::EventLoop,
+    keyboard::{KeyCode, PhysicalKey},
+    window::Window,
+};
+
+use vertex::TubeVertex;
+
+/// 五角星形截面，按逆时针排列
+fn star_profile(outer: f32, inner: f32) -> Vec<glam::Vec2> {
+    (0..10)
+        .map(|i| {
+            let angle = i as f32 / 10.0 * std::f32::consts::TAU;
+            let radius = if i % 2 == 0 { outer } else { inner };
+            glam::vec2(angle.cos(), angle.sin()) * radius
+        })
+        .collect()
+}
+
+fn build_model(
+    device: &wgpu::Device,
+    mesh: &wgpu_dance::spline::ExtrudedMesh,
+    color: glam::Vec3,
+    label: &str,
+) -> Model<TubeVertex> {
+    let mut model = mesh.to_model(label, |position, normal, _| TubeVertex {
+        position: position.to_array(),
+        normal: normal.to_array(),
+        color: color.to_array(),
+    });
+    model.alloc_buffer(device);
+    model
+}
+
+struct App {
+    device: wgpu::Device,
+    queue: wgpu::Queue,
+
+    surface: wgpu::Surface<'static>,
+    surface_config: wgpu::SurfaceConfiguration,
+
+    size: winit::dpi::PhysicalSize<u32>,
+    size_changed: bool,
+
+    depth_texture: Texture,
+
+    camera: CameraBundle,
+    light: DirectionalLightBundle,
+    lines: PolylineRenderer,
+
+    render_pipeline: wgpu::RenderPipeline,
+    models: Vec<Model<TubeVertex>>,
+}
+
+impl WindowApp for App {
+    async fn new(window: Arc<Window>) -> Self {
+        let instance = wgpu::Instance::new(&wgpu::InstanceDescriptor {
+            backends: wgpu::Backends::all(),
+            ..Default::default()
+        });
+        let surface = instance.create_surface(window.clone()).unwrap();
+
+        let adapter = instance
+            .request_adapter(&wgpu::RequestAdapterOptions {
+                power_preference: wgpu::PowerPreference::default(),
+                compatible_surface: Some(&surface),
+                force_fallback_adapter: false,
+            })
+            .await
+            .unwrap();
+
+        let (device, queue) = adapter
+            .request_device(
+                &wgpu::DeviceDescriptor {
+                    required_features: wgpu::Features::empty(),
+                    required_limits: wgpu::Limits::default(),
+                    label: None,
+                    memory_hints: wgpu::MemoryHints::Performance,
+                },
+                None,
+            )
+            .await
+            .unwrap();
+
+        let size = window.inner_size();
+
+        let caps = surface.get_capabilities(&adapter);
+        let surface_config = wgpu::SurfaceConfiguration {
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+            format: caps.formats[0],
+            width: size.width,
+            height: size.height,
+            present_mode: wgpu::PresentMode::Fifo,
+            alpha_mode: caps.alpha_modes[0],
+            view_formats: vec![],
+            desired_maximum_frame_latency: 2,
+        };
+        surface.configure(&device, &surface_config);
+
+        let camera = Camera {
+            eye: (0.0, 8.0, 16.0).into(),
+            target: (0.0, 2.0, 0.0).into(),
+            up: glam::Vec3::Y,
+            aspect: surface_config.width as f32 / surface_config.height as f32,
+            projection: Projection::Perspective { fovy: 45.0 },
+            znear: 0.1,
+            zfar: 100.0,
+            depth: DepthConvention::Standard,
+        };
+        let camera = CameraBundle::builder(camera)
+            .controller(KeyboardController::new(0.2))
+            .build(&device)
+            .unwrap();
+
+        let depth_texture =
+            Texture::create_depth_texture(&device, &surface_config, "depth_texture");
+
+        let light = DirectionalLightBundle::new(DirectionalLight::default(), &device);
+
+        // 由着色器反射检查 bind group 与顶点输入，相机和光照使用各自已有的 layout
+        let shader = ReflectedShader::new(
+            &device,
+            &ShaderLibrary::new(),
+            "Shader",
+            include_str!("shader.wgsl"),
+        )
+        .unwrap();
+        let render_pipeline = PipelineBuilder::from_reflection(&shader)
+            .label("Render Pipeline")
+            .bind_group_layout(
+                0,
+                &camera.bind_group_layout,
+                &[CameraBundle::layout_entry(wgpu::ShaderStages::VERTEX)],
+            )
+            .bind_group_layout(
+                1,
+                &light.bind_group_layout,
+                &[DirectionalLightBundle::layout_entry()],
+            )
+            .vertex_buffer(TubeVertex::buffer_layout_desc())
+            .color_target(wgpu::ColorTargetState {
+                format: surface_config.format,
+                blend: Some(wgpu::BlendState::REPLACE),
+                write_mask: wgpu::ColorWrites::ALL,
+            })
+            .primitive(wgpu::PrimitiveState {
+                cull_mode: Some(wgpu::Face::Back),
+                ..Default::default()
+            })
+            .depth_stencil(wgpu::DepthStencilState {
+                format: Texture::DEPTH_FORMAT,
+                depth_write_enabled: true,
+                depth_compare: wgpu::CompareFunction::Less,
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState::default(),
+            })
+            .build(&device)
+            .unwrap()
+            .pipeline;
+
+        // 穿过控制点的闭合 Catmull-Rom 导线
+        let wire = CatmullRom::new(vec![
+            glam::vec3(-6.0, 1.0, -2.0),
+            glam::vec3(-2.0, 4.0, -4.0),
+            glam::vec3(3.0, 2.0, -3.0),
+            glam::vec3(6.0, 5.0, 1.0),
+            glam::vec3(1.0, 1.5, 4.0),
+            glam::vec3(-4.0, 3.0, 3.0),
+        ])
+        .closed(true);
+        // 由两段 Bezier 组成的开放路径，沿其挤出星形截面
+        let path = BezierSpline::from_control_points(&[
+            glam::vec3(-6.0, 0.5, 6.0),
+            glam::vec3(-3.0, 6.0, 8.0),
+            glam::vec3(0.0, -2.0, 8.0),
+            glam::vec3(2.0, 3.0, 6.0),
+            glam::vec3(4.0, 8.0, 4.0),
+            glam::vec3(7.0, 1.0, 7.0),
+            glam::vec3(8.0, 4.0, 2.0),
+        ])
+        .unwrap();
+
+        let settings = ExtrudeSettings {
+            segments: 200,
+            ..Default::default()
+        };
+        let models = vec![
+            build_model(
+                &device,
+                &tube(&wire, 0.2, 16, &settings),
+                glam::vec3(0.9, 0.6, 0.2),
+                "wire",
+            ),
+            build_model(
+                &device,
+                &extrude(&path, &star_profile(0.5, 0.25), &settings),
+                glam::vec3(0.3, 0.6, 0.9),
+                "star path",
+            ),
+        ];
+
+        // 用折线画出控制多边形与曲线本身，便于对照
+        let mut lines = PolylineRenderer::new(
+            &device,
+            surface_config.format,
+            Some(Texture::DEPTH_FORMAT),
+            &camera.bind_group_layout,
+        );
+        let control = LineStyle {
+            color: glam::vec4(1.0, 1.0, 1.0, 0.5),
+            width: LineWidth::Pixels(1.5),
+            ..Default::default()
+        };
+        lines.add_loop(&wire.points, &control);
+        let bezier_points = path
+            .segments
+            .iter()
+            .flat_map(|s| [s.p0, s.p1, s.p2])
+            .chain(path.segments.last().map(|s| s.p3))
+            .collect::<Vec<_>>();
+        lines.add_strip(&bezier_points, &control);
+        lines.add_strip(
+            &path.sample(200),
+            &LineStyle {
+                color: glam::vec4(1.0, 0.3, 0.3, 1.0),
+                width: LineWidth::Pixels(2.0),
+                ..Default::default()
+            },
+        );
+
+        Self {
+            device,
+            queue,
+
+            surface,
+            surface_config,
+
+            size,
+            size_changed: false,
+
+            depth_texture,
+
+            camera,
+            light,
+            lines,
+
+            render_pipeline,
+            models,
+        }
+    }
+
+    fn render(&mut self) -> Result<(), wgpu::SurfaceError> {
+        self.resize_surface_if_needed();
+
+        let output = self.surface.get_current_texture()?;
+        let view = output
+            .texture
+            .create_view(&wgpu::TextureViewDescriptor::default());
+
+        let mut encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("Render Encoder"),
+            });
+
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Render Pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: &view,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(wgpu::Color {
+                        r: 0.02,
+                        g: 0.02,
+                        b: 0.03,
+                        a: 1.0,
+                    }),
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                view: &self.depth_texture.view,
+                depth_ops: Some(wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(1.0),
+                    store: wgpu::StoreOp::Store,
+                }),
+                stencil_ops: None,
+            }),
+            ..Default::default()
+        });
+
+        render_pass.set_pipeline(&self.render_pipeline);
+        render_pass.set_bind_group(0, &self.camera.bind_group, &[]);
+        render_pass.set_bind_group(1, &self.light.bind_group, &[]);
+        for model in &self.models {
+            render_pass.set_vertex_buffer(0, model.vertex_buffer.as_ref().unwrap().slice(..));
+            render_pass.set_index_buffer(
+                model.index_buffer.as_ref().unwrap().slice(..),
+                wgpu::IndexFormat::Uint32,
+            );
+            render_pass.draw_indexed(0..(model.indices.len() as u32), 0, 0..1);
+        }
+
+        self.lines.draw(&mut render_pass, &self.camera.bind_group);
+
+        drop(render_pass);
+
+        self.queue.submit(Some(encoder.finish()));
+        output.present();
+
+        Ok(())
+    }
+
+    fn set_window_resized(&mut self, new_size: PhysicalSize<u32>) {
+        if new_size == self.size {
+            return;
+        }
+        self.size = new_size;
+        self.size_changed = true;
+    }
+
+    fn resize_surface_if_needed(&mut self) {
+        if self.size_changed {
+            self.surface_config.width = self.size.width;
+            self.surface_config.height = self.size.height;
+            self.surface.configure(&self.device, &self.surface_config);
+            self.depth_texture =
+                Texture::create_depth_texture(&self.device, &self.surface_config, "depth_texture");
+            self.size_changed = false;
+        }
+    }
+
+    fn keyboard_input(&mut self, event: &KeyEvent) -> bool {
+        if !event.state.is_pressed() || event.repeat {
+            return self.camera.process_key(event);
+        }
+        // F 键开始环绕场景的飞行，空格暂停或继续
+        match event.physical_key {
+            PhysicalKey::Code(KeyCode::KeyF) => {
+                self.camera.play_path(fly_through());
+                true
+            }
+            PhysicalKey::Code(KeyCode::Space) => match &mut self.camera.path {
+                Some(path) if path.is_playing() => {
+                    path.pause();
+                    true
+                }
+                Some(path) => {
+                    path.play();
+                    true
+                }
+                None => false,
+            },
+            _ => self.camera.process_key(event),
+        }
+    }
+
+    fn mouse_wheel(&mut self, delta: MouseScrollDelta, _phase: TouchPhase) -> bool {
+        self.camera.process_wheel(delta)
+    }
+
+    fn cameras_mut(&mut self) -> Vec<&mut CameraBundle> {
+        vec![&mut self.camera]
+    }
+
+    fn update(&mut self, time: FrameTime) {
+        self.camera.update(&self.queue, time.delta_secs());
+        self.lines.update(
+            &self.device,
+            &self.queue,
+            &self.camera.state,
+            PhysicalSize::new(self.surface_config.width, self.surface_config.height),
+        );
+    }
+}
+
+/// 从初始视角出发，绕场景一周后俯冲到近处的镜头
+fn fly_through() -> CameraPath {
+    let target = glam::vec3(0.0, 2.0, 0.0);
+    let keyframes = [
+        (0.0, glam::vec3(0.0, 8.0, 16.0), target),
+        (3.0, glam::vec3(14.0, 5.0, 6.0), target),
+        (6.0, glam::vec3(6.0, 3.0, -12.0), glam::vec3(0.0, 1.0, 0.0)),
+        (9.0, glam::vec3(-12.0, 6.0, -4.0), target),
+        (12.0, glam::vec3(-4.0, 2.5, 6.0), glam::vec3(1.0, 1.5, 0.0)),
+    ]
+    .map(|(time, eye, target)| CameraKeyframe::new(time, eye, target));
+    CameraPath::new(keyframes.to_vec())
+        .unwrap()
+        .with_easing(Easing::EaseInOut)
+}
+
+fn main() -> Result<(), impl std::error::Error> {
+    let events_loop = EventLoop::new().unwrap();
+    let mut app = WindowAppHandler::<App>::new("spline example");
+    events_loop.run_app(&mut app)
+}
@@ -0,0 +1,28 @@
+use bytemuck::{Pod, Zeroable};
+use wgpu_dance::model::RenderVertex;
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug)]
+pub struct TubeVertex {
+    pub position: [f32; 3],
+    pub normal: [f32; 3],
+    pub color: [f32; 3],
+}
+
+unsafe impl Zeroable for TubeVertex {}
+unsafe impl Pod for TubeVertex {}
+
+impl RenderVertex for TubeVertex {
+    fn buffer_layout_desc<'a>() -> wgpu::VertexBufferLayout<'a> {
+        const ATTRIBUTES: [wgpu::VertexAttribute; 3] = wgpu::vertex_attr_array![
+            0 => Float32x3,
+            1 => Float32x3,
+            2 => Float32x3,
+        ];
+        wgpu::VertexBufferLayout {
+            array_stride: std::mem::size_of::<TubeVertex>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Vertex,
+            attributes: &ATTRIBUTES,
+        }
+    }
+}
@@ -0,0 +1,250 @@
+#[path = "../camera/vertex.rs"]
+pub mod vertex;
+
+use std::sync::Arc;
+
+use wgpu_dance::{
+    app::{FrameTime, WindowApp, WindowAppHandler},
+    camera::{Camera, CameraBundle, KeyboardController, Projection},
+    context::{GpuContext, GpuContextOptions},
+    layout::LayoutCache,
+    model::{Model, RenderVertex},
+    replay::InputEvent,
+    texture::{DepthConvention, Texture},
+    viewport::{SplitLayout, SplitScreen},
+};
+
+use winit::{
+    dpi::{PhysicalPosition, PhysicalSize},
+    event::{KeyEvent, MouseScrollDelta, TouchPhase},
+    event_loop::EventLoop,
+    keyboard::{KeyCode, PhysicalKey},
+    window::Window,
+};
+
+struct App {
+    gpu: GpuContext,
+
+    render_pipeline: wgpu::RenderPipeline,
+    model: Model<vertex::Vertex>,
+    diffuse_bind_group: wgpu::BindGroup,
+
+    split: SplitScreen,
+}
+
+impl WindowApp for App {
+    async fn new(window: Arc<Window>) -> Self {
+        let gpu = GpuContext::new(window, GpuContextOptions::default())
+            .await
+            .unwrap();
+        let device = &gpu.device;
+
+        let diffuse_texture = Texture::from_bytes(
+            device,
+            &gpu.queue,
+            include_bytes!("../camera/happy-tree.png"),
+            "happy-tree.png",
+        )
+        .unwrap();
+        let texture_bind_group_layout =
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                entries: &[
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 0,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Texture {
+                            multisampled: false,
+                            view_dimension: wgpu::TextureViewDimension::D2,
+                            sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                        },
+                        count: None,
+                    },
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 1,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                        count: None,
+                    },
+                ],
+                label: Some("texture_bind_group_layout"),
+            });
+        let diffuse_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: &texture_bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(&diffuse_texture.view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(&diffuse_texture.sampler),
+                },
+            ],
+            label: Some("diffuse_bind_group"),
+        });
+
+        // 两个相机各有自己的 uniform 缓冲，共享同一个绑定组布局
+        let layout_cache = LayoutCache::new();
+        let camera = |eye: glam::Vec3| {
+            let camera = Camera {
+                eye,
+                target: glam::Vec3::ZERO,
+                up: glam::Vec3::Y,
+                aspect: gpu.aspect(),
+                projection: Projection::Perspective { fovy: 45.0 },
+                znear: 0.1,
+                zfar: 100.0,
+                depth: DepthConvention::Standard,
+            };
+            CameraBundle::builder(camera)
+                .controller(KeyboardController::new(0.2))
+                .layout_cache(&layout_cache)
+                .build(device)
+                .unwrap()
+        };
+        let cameras = vec![
+            camera(glam::vec3(0.0, 1.0, 2.0)),
+            camera(glam::vec3(2.0, 0.5, -0.5)),
+        ];
+        let split = SplitScreen::new(cameras, SplitLayout::Horizontal, gpu.size()).unwrap();
+
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("../camera/shader.wgsl").into()),
+        });
+        let render_pipeline_layout =
+            device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some("Render Pipeline Layout"),
+                bind_group_layouts: &[
+                    &texture_bind_group_layout,
+                    &split.viewports[0].camera.bind_group_layout,
+                ],
+                push_constant_ranges: &[],
+            });
+        let render_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Render Pipeline"),
+            layout: Some(&render_pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                compilation_options: Default::default(),
+                entry_point: Some("vs_main"),
+                buffers: &[vertex::Vertex::buffer_layout_desc()],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                compilation_options: Default::default(),
+                entry_point: Some("fs_main"),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: gpu.format(),
+                    blend: Some(wgpu::BlendState::REPLACE),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleList,
+                front_face: wgpu::FrontFace::Ccw,
+                // 侧面的相机也能看到背面
+                cull_mode: None,
+                ..Default::default()
+            },
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+            cache: None,
+        });
+
+        let mut model = Model::new(vertex::VERTICES, vertex::INDICES, "simple model");
+        model.alloc_buffer(device);
+
+        Self {
+            gpu,
+
+            render_pipeline,
+            model,
+            diffuse_bind_group,
+
+            split,
+        }
+    }
+
+    fn render(&mut self) -> Result<(), wgpu::SurfaceError> {
+        self.resize_surface_if_needed();
+
+        let mut frame = self.gpu.begin_frame()?;
+
+        let mut render_pass = frame.render_pass("Render Pass").begin();
+        render_pass.set_pipeline(&self.render_pipeline);
+        render_pass.set_bind_group(0, &self.diffuse_bind_group, &[]);
+        render_pass.set_vertex_buffer(0, self.model.vertex_buffer.as_ref().unwrap().slice(..));
+        render_pass.set_index_buffer(
+            self.model.index_buffer.as_ref().unwrap().slice(..),
+            wgpu::IndexFormat::Uint32,
+        );
+        let index_count = self.model.indices.len() as u32;
+        self.split.draw(&mut render_pass, 1, |render_pass, _| {
+            render_pass.draw_indexed(0..index_count, 0, 0..1);
+        });
+
+        drop(render_pass);
+
+        self.gpu.end_frame(frame);
+
+        Ok(())
+    }
+
+    fn set_window_resized(&mut self, new_size: PhysicalSize<u32>) {
+        self.gpu.resize(new_size);
+    }
+
+    fn resize_surface_if_needed(&mut self) {
+        // 视口须与 surface 的大小一致，所以在 surface 重新配置后再划分
+        if self.gpu.resize_if_needed() {
+            self.split.resize(self.gpu.size());
+        }
+    }
+
+    fn gpu_context(&mut self) -> Option<&mut GpuContext> {
+        Some(&mut self.gpu)
+    }
+
+    fn keyboard_input(&mut self, event: &KeyEvent) -> bool {
+        if event.state.is_pressed() && !event.repeat {
+            // L 键在左右与上下分屏之间切换，Tab 键切换接收输入的相机
+            match event.physical_key {
+                PhysicalKey::Code(KeyCode::KeyL) => {
+                    self.split.set_layout(match self.split.layout() {
+                        SplitLayout::Horizontal => SplitLayout::Vertical,
+                        SplitLayout::Vertical => SplitLayout::Horizontal,
+                    });
+                    return true;
+                }
+                PhysicalKey::Code(KeyCode::Tab) => {
+                    let next = (self.split.active() + 1) % self.split.viewports.len();
+                    self.split.set_active(next);
+                    return true;
+                }
+                _ => {}
+            }
+        }
+        self.split.process_event(&InputEvent::Key(event.into()))
+    }
+
+    fn mouse_wheel(&mut self, delta: MouseScrollDelta, _phase: TouchPhase) -> bool {
+        self.split.process_event(&InputEvent::Wheel(delta))
+    }
+
+    fn cursor_move(&mut self, position: PhysicalPosition<f64>) -> bool {
+        self.split.cursor_moved(position);
+        false
+    }
+
+    fn update(&mut self, time: FrameTime) {
+        self.split.update(&self.gpu.queue, time.delta_secs());
+    }
+}
+
+fn main() -> Result<(), impl std::error::Error> {
+    let events_loop = EventLoop::new().unwrap();
+    let mut app = WindowAppHandler::<App>::new("split screen example");
+    events_loop.run_app(&mut app)
+}
@@ -0,0 +1,295 @@
+use std::{sync::Arc, time::Duration};
+
+use wgpu_dance::{
+    app::{FrameTime, KeyInput, WindowApp, WindowAppHandler},
+    background::Background,
+    camera2d::{Camera2D, Camera2DController},
+    context::{GpuContext, GpuContextOptions},
+    sprite::{
+        animation::{AnimationPlayer, SpriteSheet, SpriteSheetDescriptor},
+        NineSlice, Sprite, SpriteRenderer,
+    },
+    stats::{FrameStats, StatsReporter},
+    texture::Texture,
+};
+use winit::{
+    dpi::{PhysicalPosition, PhysicalSize},
+    event::{ElementState, KeyEvent, MouseButton, MouseScrollDelta, TouchPhase},
+    event_loop::EventLoop,
+    keyboard::{KeyCode, PhysicalKey},
+    window::Window,
+};
+
+/// 图集中每一帧的像素大小
+const CELL: u32 = 16;
+/// 精灵相对图集的放大倍数
+const SCALE: f32 = 8.0;
+/// 相邻精灵中心的间距，单位为逻辑像素
+const SPACING: f32 = 180.0;
+
+/// R 键切换到的虚拟分辨率，单位为虚拟像素
+const VIRTUAL_RESOLUTION: PhysicalSize<u32> = PhysicalSize::new(640, 360);
+
+/// 九宫格面板在图集中的帧与边框宽度（像素）
+const PANEL_FRAME: u32 = 8;
+const PANEL_BORDER: u32 = 4;
+
+/// 生成与 `sheet.ron` 对应的图集：第一行为逐渐变大的圆，第二行为旋转的横条，
+/// 第三行的第一格为带边框的面板
+fn generate_sheet(sheet: SpriteSheet) -> image::DynamicImage {
+    let image = image::RgbaImage::from_fn(sheet.columns * CELL, sheet.rows * CELL, |x, y| {
+        let (column, row) = (x / CELL, y / CELL);
+        let (cx, cy) = (x % CELL, y % CELL);
+        if row * sheet.columns + column == PANEL_FRAME {
+            let edge = cx.min(cy).min(CELL - 1 - cx).min(CELL - 1 - cy);
+            return match edge {
+                // 切掉四个角的像素，面板看起来略带圆角
+                0 if cx == cy || cx + cy == CELL - 1 => image::Rgba([0, 0, 0, 0]),
+                e if e < PANEL_BORDER => image::Rgba([180, 190, 220, 255]),
+                _ => image::Rgba([30, 34, 48, 230]),
+            };
+        }
+        let p = glam::vec2(cx as f32, cy as f32) + 0.5 - CELL as f32 * 0.5;
+        let inside = match row {
+            0 => p.length() < 2.0 + column as f32 * 1.8,
+            1 => {
+                let angle = column as f32 * std::f32::consts::FRAC_PI_4;
+                let local = glam::Vec2::from_angle(-angle).rotate(p);
+                local.x.abs() < 7.0 && local.y.abs() < 2.0
+            }
+            _ => false,
+        };
+        if inside {
+            image::Rgba([255, 220 - row as u8 * 100, 120 + row as u8 * 100, 255])
+        } else {
+            image::Rgba([0, 0, 0, 0])
+        }
+    });
+    image::DynamicImage::ImageRgba8(image)
+}
+
+struct App {
+    gpu: GpuContext,
+
+    camera: Camera2D,
+    controller: Camera2DController,
+
+    descriptor: SpriteSheetDescriptor,
+    renderer: SpriteRenderer,
+    players: Vec<AnimationPlayer>,
+    exit_requested: bool,
+}
+
+impl WindowApp for App {
+    async fn new(window: Arc<Window>) -> Self {
+        let mut gpu = GpuContext::new(window, GpuContextOptions::default())
+            .await
+            .unwrap();
+        gpu.background = Background::Color(wgpu::Color {
+            r: 0.08,
+            g: 0.08,
+            b: 0.1,
+            a: 1.0,
+        });
+
+        let descriptor = SpriteSheetDescriptor::from_ron(include_str!("sheet.ron")).unwrap();
+        let texture = Texture::from_image(
+            &gpu.device,
+            &gpu.queue,
+            &generate_sheet(descriptor.sheet),
+            Some("Sprite Sheet"),
+        )
+        .unwrap();
+        let renderer = SpriteRenderer::new(&gpu.device, gpu.format(), &texture.view);
+
+        let mut camera = Camera2D::new(gpu.size(), gpu.scale_factor());
+        camera.pixel_perfect = true;
+
+        let players = ["pulse", "spin", "pop"]
+            .into_iter()
+            .map(|name| AnimationPlayer::new(descriptor.animation(name).unwrap().clone()))
+            .collect();
+
+        Self {
+            gpu,
+
+            camera,
+            controller: Camera2DController::new(400.0),
+
+            descriptor,
+            renderer,
+            players,
+            exit_requested: false,
+        }
+    }
+
+    fn render(&mut self) -> Result<(), wgpu::SurfaceError> {
+        self.resize_surface_if_needed();
+
+        let mut frame = self.gpu.begin_frame()?;
+
+        let mut render_pass = frame.render_pass("Render Pass").begin();
+
+        // 使用虚拟分辨率时只在中央的画面内绘制
+        let screen = self.camera.screen_rect();
+        if !screen.is_empty() {
+            screen.set_viewport(&mut render_pass);
+            self.renderer.draw(&mut render_pass);
+        }
+
+        drop(render_pass);
+
+        self.gpu.end_frame(frame);
+
+        Ok(())
+    }
+
+    fn set_window_resized(&mut self, new_size: PhysicalSize<u32>) {
+        self.gpu.resize(new_size);
+        self.camera.resize(new_size);
+    }
+
+    fn scale_factor_changed(&mut self, scale_factor: f64) {
+        self.camera.scale_factor = scale_factor;
+    }
+
+    fn resize_surface_if_needed(&mut self) {
+        self.gpu.resize_if_needed();
+    }
+
+    fn gpu_context(&mut self) -> Option<&mut GpuContext> {
+        Some(&mut self.gpu)
+    }
+
+    fn should_exit(&self) -> bool {
+        self.exit_requested
+    }
+
+    fn on_exit(&mut self) {
+        let finished = self.players.iter().filter(|p| p.is_finished()).count();
+        println!("{finished}/{} animations finished", self.players.len());
+    }
+
+    fn keyboard_input(&mut self, _event: &KeyEvent) -> bool {
+        false
+    }
+
+    // 在 key_input 中处理按键，录制的输入可以回放
+    fn key_input(&mut self, input: &KeyInput) -> bool {
+        if self.controller.process_keyboard(input) {
+            return true;
+        }
+        if input.state != ElementState::Pressed || input.repeat {
+            return false;
+        }
+        // WASD 或方向键平移，鼠标右键拖动平移，滚轮缩放；空格键重新播放单次动画，
+        // +/- 调整播放速度，P 键切换像素对齐，R 键切换 640x360 的虚拟分辨率，V 键切换呈现模式，
+        // Esc 键退出
+        match input.physical_key {
+            PhysicalKey::Code(KeyCode::Space) => {
+                for player in &mut self.players {
+                    if player.is_finished() {
+                        player.restart();
+                    }
+                }
+            }
+            PhysicalKey::Code(KeyCode::Equal) => {
+                for player in &mut self.players {
+                    player.speed *= 1.25;
+                }
+            }
+            PhysicalKey::Code(KeyCode::Minus) => {
+                for player in &mut self.players {
+                    player.speed /= 1.25;
+                }
+            }
+            PhysicalKey::Code(KeyCode::KeyP) => {
+                self.camera.pixel_perfect = !self.camera.pixel_perfect;
+            }
+            PhysicalKey::Code(KeyCode::KeyR) => {
+                self.camera.virtual_resolution = match self.camera.virtual_resolution {
+                    Some(_) => None,
+                    None => Some(VIRTUAL_RESOLUTION),
+                };
+            }
+            PhysicalKey::Code(KeyCode::KeyV) => {
+                let mode = self.gpu.cycle_present_mode();
+                println!("present mode: {mode:?}");
+            }
+            PhysicalKey::Code(KeyCode::Escape) => self.exit_requested = true,
+            _ => return false,
+        }
+        true
+    }
+
+    fn mouse_click(&mut self, state: ElementState, button: MouseButton) -> bool {
+        self.controller.process_mouse_button(state, button)
+    }
+
+    fn mouse_wheel(&mut self, delta: MouseScrollDelta, _phase: TouchPhase) -> bool {
+        self.controller.process_wheel(delta, &mut self.camera)
+    }
+
+    fn cursor_move(&mut self, position: PhysicalPosition<f64>) -> bool {
+        self.controller.process_cursor(position, &mut self.camera)
+    }
+
+    fn update(&mut self, time: FrameTime) {
+        self.controller
+            .update_camera(&mut self.camera, time.delta_secs());
+
+        let sheet = self.descriptor.sheet;
+        let sprite_size = CELL as f32 * SCALE;
+        // 精灵排成一行，以世界原点为中心
+        let first_x = -SPACING * (self.players.len() - 1) as f32 * 0.5;
+
+        self.renderer.clear();
+        // 精灵背后的九宫格面板，边框按放大倍数绘制，中间拉伸
+        let uv_border = PANEL_BORDER as f32 / (CELL * sheet.columns) as f32;
+        let uv_border_y = PANEL_BORDER as f32 / (CELL * sheet.rows) as f32;
+        self.renderer.push_nine_slice(&NineSlice {
+            min: glam::vec2(first_x - SPACING * 0.5, -sprite_size),
+            size: glam::vec2(SPACING * self.players.len() as f32, sprite_size * 2.0),
+            uv: sheet.frame_uv(PANEL_FRAME),
+            uv_border: glam::vec4(uv_border, uv_border_y, uv_border, uv_border_y),
+            border: glam::Vec4::splat(PANEL_BORDER as f32 * SCALE * 0.5),
+            color: glam::Vec4::ONE,
+        });
+
+        for (i, player) in self.players.iter_mut().enumerate() {
+            player.update(time);
+            let x = first_x + SPACING * i as f32;
+            let sprite = Sprite::new(glam::vec2(x, 0.0), glam::Vec2::splat(sprite_size))
+                .with_uv(sheet.frame_uv(player.frame()));
+            self.renderer.push(&sprite);
+
+            // 面板下方的按钮，单次动画播放结束后高亮，提示可以按空格重新播放
+            let button = glam::vec2(x, sprite_size * 0.75);
+            let color = if player.is_finished() {
+                glam::vec4(0.95, 0.75, 0.3, 1.0)
+            } else {
+                glam::vec4(0.35, 0.4, 0.55, 1.0)
+            };
+            self.renderer.push(&Sprite::rounded_rect(
+                button,
+                glam::vec2(sprite_size * 0.8, 24.0),
+                8.0,
+                color,
+            ));
+            self.renderer
+                .push(&Sprite::circle(button, 14.0, glam::Vec4::ONE).with_outline(2.0));
+        }
+        self.renderer
+            .update(&self.gpu.device, &self.gpu.queue, self.camera.view_proj());
+    }
+}
+
+fn main() -> Result<(), impl std::error::Error> {
+    let events_loop = EventLoop::new().unwrap();
+    // 动画按固定步长推进，与帧率无关；简单的场景没必要渲染得比 60 帧更快
+    let mut app = WindowAppHandler::<App>::new("sprite sheet example")
+        .with_fixed_timestep(Duration::from_secs_f64(1.0 / 60.0))
+        .with_target_fps(60.0)
+        .with_frame_stats(FrameStats::default().with_reporter(StatsReporter::WindowTitle));
+    events_loop.run_app(&mut app)
+}
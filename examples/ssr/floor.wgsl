@@ -0,0 +1,41 @@
+#include "wgpu_dance/camera.wgsl"
+#include "wgpu_dance/lighting.wgsl"
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4f,
+    @location(0) world_position: vec3f,
+}
+
+struct FragmentOutput {
+    @location(0) color: vec4f,
+    @location(1) normal_roughness: vec4f,
+    @location(2) albedo: vec4f,
+}
+
+@group(0) @binding(0)
+var<uniform> camera: CameraUniform;
+
+@group(1) @binding(0)
+var<uniform> sun: DirectionalLight;
+
+@vertex
+fn vs_main(@location(0) position: vec3f) -> VertexOutput {
+    var out: VertexOutput;
+    out.world_position = position;
+    out.clip_position = camera.view_proj * vec4f(position, 1.0);
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> FragmentOutput {
+    let checker = (i32(floor(in.world_position.x)) + i32(floor(in.world_position.z))) & 1;
+    let base = mix(vec3f(0.08, 0.08, 0.1), vec3f(0.3, 0.3, 0.34), f32(checker));
+    let diffuse = lambert(vec3f(0.0, 1.0, 0.0), sun.direction.xyz) * sun.direction.w;
+
+    var out: FragmentOutput;
+    out.color = vec4f(base * (sun.color.rgb * diffuse + 0.1), 1.0);
+    // 深色格子更光滑，便于对比不同粗糙度下的反射
+    out.normal_roughness = vec4f(0.0, 1.0, 0.0, mix(0.05, 0.35, f32(checker)));
+    out.albedo = vec4f(base, 1.0);
+    return out;
+}
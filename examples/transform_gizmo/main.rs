@@ -0,0 +1,362 @@
+pub mod vertex;
+
+use std::sync::Arc;
+
+use wgpu::util::DeviceExt;
+use wgpu_dance::{
+    app::{FrameTime, WindowApp, WindowAppHandler},
+    background::Background,
+    camera::{Camera, CameraBundle, KeyboardController, Projection},
+    context::{GpuContext, GpuContextOptions},
+    debug_draw::DebugDraw,
+    gizmo::{GizmoMode, TransformGizmo},
+    instance::{Instance, MotionInstanceBuffer, MotionInstanceRaw},
+    light::{DirectionalLight, DirectionalLightBundle},
+    model::RenderVertex,
+    pipeline::{PipelineBuilder, ReflectedShader},
+    ray::Ray,
+    shader::ShaderLibrary,
+    texture::{DepthConvention, Texture},
+};
+
+use winit::{
+    dpi::{PhysicalPosition, PhysicalSize},
+    event::{ElementState, KeyEvent, MouseButton, MouseScrollDelta, TouchPhase},
+    event_loop::EventLoop,
+    keyboard::{KeyCode, PhysicalKey},
+    window::Window,
+};
+
+use vertex::{cube, CubeVertex};
+
+const GRID_HALF_SIZE: i32 = 10;
+/// 单位立方体的包围球半径
+const CUBE_RADIUS: f32 = 0.87;
+
+struct App {
+    gpu: GpuContext,
+
+    depth_texture: Texture,
+
+    camera: CameraBundle,
+    light: DirectionalLightBundle,
+    debug: DebugDraw,
+
+    render_pipeline: wgpu::RenderPipeline,
+    vertex_buffer: wgpu::Buffer,
+    index_buffer: wgpu::Buffer,
+    index_count: u32,
+    instances: MotionInstanceBuffer,
+
+    gizmo: TransformGizmo,
+    selected: Option<usize>,
+    cursor: glam::Vec2,
+}
+
+impl App {
+    fn cursor_ray(&self) -> Ray {
+        self.camera.state.screen_ray(self.cursor, self.gpu.size())
+    }
+
+    /// 射线最先穿过的立方体，用包围球近似
+    fn pick_instance(&self, ray: &Ray) -> Option<usize> {
+        self.instances
+            .instances
+            .iter()
+            .enumerate()
+            .filter_map(|(i, instance)| {
+                ray.intersect_sphere(
+                    instance.position,
+                    CUBE_RADIUS * instance.scale.max_element(),
+                )
+                .map(|t| (i, t))
+            })
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(i, _)| i)
+    }
+
+    fn build_debug_lines(&mut self) {
+        self.debug.clear();
+
+        let grid = glam::vec4(0.5, 0.5, 0.55, 0.4);
+        let extent = GRID_HALF_SIZE as f32;
+        for i in -GRID_HALF_SIZE..=GRID_HALF_SIZE {
+            let i = i as f32;
+            self.debug.line(
+                glam::vec3(i, 0.0, -extent),
+                glam::vec3(i, 0.0, extent),
+                grid,
+            );
+            self.debug.line(
+                glam::vec3(-extent, 0.0, i),
+                glam::vec3(extent, 0.0, i),
+                grid,
+            );
+        }
+
+        if let Some(selected) = self.selected {
+            self.debug.transform_gizmo(
+                &self.gizmo,
+                &self.camera.state,
+                &self.instances.instances[selected],
+            );
+        }
+    }
+}
+
+impl WindowApp for App {
+    async fn new(window: Arc<Window>) -> Self {
+        let mut gpu = GpuContext::new(window, GpuContextOptions::default())
+            .await
+            .unwrap();
+        gpu.background = Background::Color(wgpu::Color {
+            r: 0.02,
+            g: 0.02,
+            b: 0.03,
+            a: 1.0,
+        });
+
+        let camera = Camera {
+            eye: (0.0, 8.0, 14.0).into(),
+            target: (0.0, 1.0, 0.0).into(),
+            up: glam::Vec3::Y,
+            aspect: gpu.aspect(),
+            projection: Projection::Perspective { fovy: 45.0 },
+            znear: 0.1,
+            zfar: 100.0,
+            depth: DepthConvention::Standard,
+        };
+        let camera = CameraBundle::builder(camera)
+            .controller(KeyboardController::new(0.2))
+            .build(&gpu.device)
+            .unwrap();
+
+        let depth_texture =
+            Texture::create_depth_texture(&gpu.device, &gpu.surface_config, "depth_texture");
+
+        let light = DirectionalLightBundle::new(DirectionalLight::default(), &gpu.device);
+
+        let debug = DebugDraw::new(
+            &gpu.device,
+            gpu.format(),
+            Some(Texture::DEPTH_FORMAT),
+            &camera.bind_group_layout,
+        );
+
+        let (vertices, indices) = cube();
+        let vertex_buffer = gpu
+            .device
+            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("Cube Vertex Buffer"),
+                contents: bytemuck::cast_slice(&vertices),
+                usage: wgpu::BufferUsages::VERTEX,
+            });
+        let index_buffer = gpu
+            .device
+            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("Cube Index Buffer"),
+                contents: bytemuck::cast_slice(&indices),
+                usage: wgpu::BufferUsages::INDEX,
+            });
+
+        let instances = (-1..=1)
+            .map(|i| Instance {
+                position: glam::vec3(i as f32 * 3.0, 0.5, 0.0),
+                ..Default::default()
+            })
+            .collect();
+        let instances = MotionInstanceBuffer::new(&gpu.device, instances);
+
+        let shader = ReflectedShader::new(
+            &gpu.device,
+            &ShaderLibrary::new(),
+            "Transform Gizmo Shader",
+            include_str!("shader.wgsl"),
+        )
+        .unwrap();
+        let render_pipeline = PipelineBuilder::from_reflection(&shader)
+            .label("Cube Pipeline")
+            .bind_group_layout(
+                0,
+                &camera.bind_group_layout,
+                &[CameraBundle::layout_entry(wgpu::ShaderStages::VERTEX)],
+            )
+            .bind_group_layout(
+                1,
+                &light.bind_group_layout,
+                &[DirectionalLightBundle::layout_entry()],
+            )
+            .vertex_buffer(CubeVertex::buffer_layout_desc())
+            .vertex_buffer(MotionInstanceRaw::buffer_layout_desc())
+            .color_target(wgpu::ColorTargetState {
+                format: gpu.format(),
+                blend: Some(wgpu::BlendState::REPLACE),
+                write_mask: wgpu::ColorWrites::ALL,
+            })
+            .primitive(wgpu::PrimitiveState {
+                cull_mode: Some(wgpu::Face::Back),
+                ..Default::default()
+            })
+            .depth_stencil(wgpu::DepthStencilState {
+                format: Texture::DEPTH_FORMAT,
+                depth_write_enabled: true,
+                depth_compare: wgpu::CompareFunction::Less,
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState::default(),
+            })
+            .build(&gpu.device)
+            .unwrap()
+            .pipeline;
+
+        Self {
+            gpu,
+
+            depth_texture,
+
+            camera,
+            light,
+            debug,
+
+            render_pipeline,
+            vertex_buffer,
+            index_buffer,
+            index_count: indices.len() as u32,
+            instances,
+
+            gizmo: TransformGizmo::default(),
+            selected: None,
+            cursor: glam::Vec2::ZERO,
+        }
+    }
+
+    fn render(&mut self) -> Result<(), wgpu::SurfaceError> {
+        self.resize_surface_if_needed();
+
+        let mut frame = self.gpu.begin_frame()?;
+
+        let mut render_pass = frame
+            .render_pass("Render Pass")
+            .depth(&self.depth_texture.view)
+            .begin();
+
+        render_pass.set_pipeline(&self.render_pipeline);
+        render_pass.set_bind_group(0, &self.camera.bind_group, &[]);
+        render_pass.set_bind_group(1, &self.light.bind_group, &[]);
+        render_pass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
+        render_pass.set_vertex_buffer(1, self.instances.buffer().slice(..));
+        render_pass.set_index_buffer(self.index_buffer.slice(..), wgpu::IndexFormat::Uint32);
+        render_pass.draw_indexed(0..self.index_count, 0, 0..self.instances.len());
+
+        self.debug.draw(&mut render_pass, &self.camera.bind_group);
+
+        drop(render_pass);
+
+        self.gpu.end_frame(frame);
+
+        Ok(())
+    }
+
+    fn set_window_resized(&mut self, new_size: PhysicalSize<u32>) {
+        self.gpu.resize(new_size);
+    }
+
+    fn resize_surface_if_needed(&mut self) {
+        if self.gpu.resize_if_needed() {
+            self.depth_texture = Texture::create_depth_texture(
+                &self.gpu.device,
+                &self.gpu.surface_config,
+                "depth_texture",
+            );
+        }
+    }
+
+    fn gpu_context(&mut self) -> Option<&mut GpuContext> {
+        Some(&mut self.gpu)
+    }
+
+    fn keyboard_input(&mut self, event: &KeyEvent) -> bool {
+        if self.camera.process_key(event) {
+            return true;
+        }
+        if event.state != ElementState::Pressed || event.repeat {
+            return false;
+        }
+        // 鼠标左键选中立方体并拖动手柄，1/2/3 键切换平移、旋转、缩放，Esc 取消选中
+        self.gizmo.mode = match event.physical_key {
+            PhysicalKey::Code(KeyCode::Digit1) => GizmoMode::Translate,
+            PhysicalKey::Code(KeyCode::Digit2) => GizmoMode::Rotate,
+            PhysicalKey::Code(KeyCode::Digit3) => GizmoMode::Scale,
+            PhysicalKey::Code(KeyCode::Escape) => {
+                self.gizmo.end_drag();
+                self.selected = None;
+                return true;
+            }
+            _ => return false,
+        };
+        self.gizmo.end_drag();
+        true
+    }
+
+    fn mouse_wheel(&mut self, delta: MouseScrollDelta, _phase: TouchPhase) -> bool {
+        self.camera.process_wheel(delta)
+    }
+
+    fn mouse_click(&mut self, state: ElementState, button: MouseButton) -> bool {
+        if button != MouseButton::Left {
+            return false;
+        }
+        if state == ElementState::Released {
+            self.gizmo.end_drag();
+            return true;
+        }
+        let ray = self.cursor_ray();
+        if let Some(selected) = self.selected {
+            let target = &self.instances.instances[selected];
+            if self.gizmo.begin_drag(&ray, &self.camera.state, target) {
+                return true;
+            }
+        }
+        self.selected = self.pick_instance(&ray);
+        true
+    }
+
+    fn cursor_move(&mut self, position: PhysicalPosition<f64>) -> bool {
+        self.cursor = glam::vec2(position.x as f32, position.y as f32);
+        let Some(selected) = self.selected else {
+            return false;
+        };
+        let ray = self.cursor_ray();
+        let target = &mut self.instances.instances[selected];
+        if self.gizmo.is_dragging() {
+            if let Some(delta) = self.gizmo.drag(&ray, target) {
+                delta.apply(target);
+            }
+        } else {
+            self.gizmo.hover(&ray, &self.camera.state, target);
+        }
+        true
+    }
+
+    fn cameras_mut(&mut self) -> Vec<&mut CameraBundle> {
+        vec![&mut self.camera]
+    }
+
+    fn update(&mut self, time: FrameTime) {
+        self.camera.update(&self.gpu.queue, time.delta_secs());
+        self.instances.update(&self.gpu.queue);
+
+        self.build_debug_lines();
+        self.debug.update(
+            &self.gpu.device,
+            &self.gpu.queue,
+            &self.camera.state,
+            self.gpu.size(),
+        );
+    }
+}
+
+fn main() -> Result<(), impl std::error::Error> {
+    let events_loop = EventLoop::new().unwrap();
+    let mut app = WindowAppHandler::<App>::new("transform gizmo example");
+    events_loop.run_app(&mut app)
+}
@@ -0,0 +1,54 @@
+use bytemuck::{Pod, Zeroable};
+
+use wgpu_dance::model::RenderVertex;
+
+/// 立方体顶点，使用 4、5 号位置，避开实例数据占用的 0~3 与 8~11 号位置
+#[repr(C)]
+#[derive(Copy, Clone, Debug)]
+pub struct CubeVertex {
+    pub position: [f32; 3],
+    pub normal: [f32; 3],
+}
+
+unsafe impl Zeroable for CubeVertex {}
+unsafe impl Pod for CubeVertex {}
+
+impl RenderVertex for CubeVertex {
+    fn buffer_layout_desc<'a>() -> wgpu::VertexBufferLayout<'a> {
+        const ATTRIBUTES: [wgpu::VertexAttribute; 2] = wgpu::vertex_attr_array![
+            4 => Float32x3,
+            5 => Float32x3,
+        ];
+        wgpu::VertexBufferLayout {
+            array_stride: std::mem::size_of::<CubeVertex>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Vertex,
+            attributes: &ATTRIBUTES,
+        }
+    }
+}
+
+/// 边长为 1、中心在原点的立方体，每个面使用独立的顶点以得到平直的法线
+pub fn cube() -> (Vec<CubeVertex>, Vec<u32>) {
+    let mut vertices = Vec::new();
+    let mut indices = Vec::new();
+    for normal in [
+        glam::Vec3::X,
+        glam::Vec3::NEG_X,
+        glam::Vec3::Y,
+        glam::Vec3::NEG_Y,
+        glam::Vec3::Z,
+        glam::Vec3::NEG_Z,
+    ] {
+        let (u, v) = normal.any_orthonormal_pair();
+        let base = vertices.len() as u32;
+        for (a, b) in [(-1.0, -1.0), (1.0, -1.0), (1.0, 1.0), (-1.0, 1.0)] {
+            vertices.push(CubeVertex {
+                position: ((normal + u * a + v * b) * 0.5).to_array(),
+                normal: normal.to_array(),
+            });
+        }
+        // u × v 与法线同向，上面的四个角从法线方向看是逆时针
+        indices.extend([base, base + 1, base + 2, base, base + 2, base + 3]);
+    }
+    (vertices, indices)
+}
@@ -0,0 +1,206 @@
+use std::sync::Arc;
+
+use wgpu::util::DeviceExt;
+use wgpu_dance::{
+    app::{FrameTime, WindowApp, WindowAppHandler},
+    background::Background,
+    context::{GpuContext, GpuContextOptions},
+    shader::ShaderLibrary,
+    uniform::GpuUniform,
+};
+use winit::{
+    dpi::{LogicalSize, PhysicalSize},
+    event::{ElementState, KeyEvent, MouseButton},
+    event_loop::EventLoop,
+    keyboard::{KeyCode, PhysicalKey},
+    window::Window,
+};
+
+/// 与 `widget.wgsl` 中的 `WidgetUniform` 保持一致
+#[repr(C)]
+#[derive(Debug, Copy, Clone, GpuUniform)]
+struct WidgetUniform {
+    resolution: [f32; 2],
+    time: f32,
+    premultiplied: f32,
+}
+
+struct App {
+    gpu: GpuContext,
+    pipeline: wgpu::RenderPipeline,
+    uniform: WidgetUniform,
+    uniform_buffer: wgpu::Buffer,
+    bind_group: wgpu::BindGroup,
+    exit_requested: bool,
+}
+
+impl WindowApp for App {
+    async fn new(window: Arc<Window>) -> Self {
+        let mut gpu = GpuContext::new(window, GpuContextOptions::for_app::<Self>())
+            .await
+            .unwrap();
+        gpu.background = Background::Transparent;
+        println!("surface alpha mode: {:?}", gpu.alpha_mode());
+
+        let size = gpu.size();
+        let uniform = WidgetUniform {
+            resolution: [size.width as f32, size.height as f32],
+            time: 0.0,
+            premultiplied: match gpu.alpha_mode() {
+                wgpu::CompositeAlphaMode::PreMultiplied => 1.0,
+                _ => 0.0,
+            },
+        };
+        let uniform_buffer = gpu
+            .device
+            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("Widget Uniform Buffer"),
+                contents: bytemuck::bytes_of(&uniform),
+                usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            });
+        let bind_group_layout =
+            gpu.device
+                .create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                    entries: &[wgpu::BindGroupLayoutEntry {
+                        binding: 0,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Buffer {
+                            ty: wgpu::BufferBindingType::Uniform,
+                            has_dynamic_offset: false,
+                            min_binding_size: None,
+                        },
+                        count: None,
+                    }],
+                    label: Some("widget_bind_group_layout"),
+                });
+        let bind_group = gpu.device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: &bind_group_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: uniform_buffer.as_entire_binding(),
+            }],
+            label: Some("widget_bind_group"),
+        });
+
+        let shader = ShaderLibrary::new()
+            .create_shader_module(&gpu.device, "Widget Shader", include_str!("widget.wgsl"))
+            .unwrap();
+        let layout = gpu
+            .device
+            .create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some("Widget Pipeline Layout"),
+                bind_group_layouts: &[&bind_group_layout],
+                push_constant_ranges: &[],
+            });
+        let pipeline = gpu
+            .device
+            .create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                label: Some("Widget Pipeline"),
+                layout: Some(&layout),
+                vertex: wgpu::VertexState {
+                    module: &shader,
+                    compilation_options: Default::default(),
+                    entry_point: Some("vs_main"),
+                    buffers: &[],
+                },
+                fragment: Some(wgpu::FragmentState {
+                    module: &shader,
+                    compilation_options: Default::default(),
+                    entry_point: Some("fs_main"),
+                    targets: &[Some(wgpu::ColorTargetState {
+                        format: gpu.format(),
+                        blend: None,
+                        write_mask: wgpu::ColorWrites::ALL,
+                    })],
+                }),
+                primitive: wgpu::PrimitiveState::default(),
+                depth_stencil: None,
+                multisample: wgpu::MultisampleState::default(),
+                multiview: None,
+                cache: None,
+            });
+
+        Self {
+            gpu,
+            pipeline,
+            uniform,
+            uniform_buffer,
+            bind_group,
+            exit_requested: false,
+        }
+    }
+
+    fn transparent() -> bool {
+        true
+    }
+
+    fn render(&mut self) -> Result<(), wgpu::SurfaceError> {
+        self.resize_surface_if_needed();
+
+        let mut frame = self.gpu.begin_frame()?;
+
+        let mut render_pass = frame.render_pass("Widget Pass").begin();
+        render_pass.set_pipeline(&self.pipeline);
+        render_pass.set_bind_group(0, &self.bind_group, &[]);
+        render_pass.draw(0..3, 0..1);
+        drop(render_pass);
+
+        self.gpu.end_frame(frame);
+
+        Ok(())
+    }
+
+    fn set_window_resized(&mut self, new_size: PhysicalSize<u32>) {
+        self.gpu.resize(new_size);
+    }
+
+    fn resize_surface_if_needed(&mut self) {
+        if self.gpu.resize_if_needed() {
+            let size = self.gpu.size();
+            self.uniform.resolution = [size.width as f32, size.height as f32];
+        }
+    }
+
+    fn gpu_context(&mut self) -> Option<&mut GpuContext> {
+        Some(&mut self.gpu)
+    }
+
+    fn should_exit(&self) -> bool {
+        self.exit_requested
+    }
+
+    fn keyboard_input(&mut self, event: &KeyEvent) -> bool {
+        // Esc 键退出
+        if event.state == ElementState::Pressed
+            && event.physical_key == PhysicalKey::Code(KeyCode::Escape)
+        {
+            self.exit_requested = true;
+            return true;
+        }
+        false
+    }
+
+    // 窗口没有标题栏，按住左键拖动挂件
+    fn mouse_click(&mut self, state: ElementState, button: MouseButton) -> bool {
+        if state != ElementState::Pressed || button != MouseButton::Left {
+            return false;
+        }
+        if let Err(e) = self.gpu.window().window().drag_window() {
+            eprintln!("failed to drag window: {e}");
+        }
+        true
+    }
+
+    fn update(&mut self, time: FrameTime) {
+        self.uniform.time = time.elapsed_secs();
+        self.uniform.write_to(&self.gpu.queue, &self.uniform_buffer);
+    }
+}
+
+fn main() -> Result<(), impl std::error::Error> {
+    let events_loop = EventLoop::new().unwrap();
+    let mut app = WindowAppHandler::<App>::new("transparent widget example")
+        .with_inner_size(LogicalSize::new(240.0, 240.0))
+        .with_decorations(false);
+    events_loop.run_app(&mut app)
+}
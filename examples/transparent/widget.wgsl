@@ -0,0 +1,52 @@
+#include "wgpu_dance/fullscreen.wgsl"
+
+struct WidgetUniform {
+    resolution: vec2f,
+    time: f32,
+    // 1 表示 surface 为预乘 alpha，输出前颜色乘以 alpha
+    premultiplied: f32,
+}
+
+@group(0) @binding(0)
+var<uniform> widget: WidgetUniform;
+
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32) -> FullscreenOutput {
+    return fullscreen_vertex(index);
+}
+
+// 点 p 到线段 ab 的距离
+fn segment(p: vec2f, a: vec2f, b: vec2f) -> f32 {
+    let pa = p - a;
+    let ba = b - a;
+    let h = clamp(dot(pa, ba) / dot(ba, ba), 0.0, 1.0);
+    return length(pa - ba * h);
+}
+
+@fragment
+fn fs_main(in: FullscreenOutput) -> @location(0) vec4f {
+    // 以窗口中心为原点、较短边的一半为 1 的坐标，y 轴向上
+    let half = min(widget.resolution.x, widget.resolution.y) * 0.5;
+    let p = (in.clip_position.xy - widget.resolution * 0.5) * vec2f(1.0, -1.0) / half;
+    let aa = 1.5 / half;
+    let r = length(p);
+
+    // 半透明的表盘与不透明的外圈
+    var color = vec3f(0.08, 0.1, 0.14);
+    var alpha = 0.55 * (1.0 - smoothstep(0.9 - aa, 0.9, r));
+    let rim = 1.0 - smoothstep(0.0, aa, abs(r - 0.9) - 0.03);
+    color = mix(color, vec3f(0.85, 0.9, 1.0), rim);
+    alpha = max(alpha, rim);
+
+    // 秒针每 60 秒转一圈
+    let angle = widget.time * 6.283185 / 60.0;
+    let tip = vec2f(sin(angle), cos(angle)) * 0.75;
+    let hand = 1.0 - smoothstep(0.0, aa, segment(p, vec2f(0.0), tip) - 0.02);
+    color = mix(color, vec3f(1.0, 0.55, 0.25), hand);
+    alpha = max(alpha, hand);
+
+    if (widget.premultiplied > 0.5) {
+        color *= alpha;
+    }
+    return vec4f(color, alpha);
+}
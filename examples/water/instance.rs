@@ -0,0 +1,65 @@
+use bytemuck::{Pod, Zeroable};
+use wgpu_dance::model::RenderVertex;
+
+#[derive(Debug, Clone, Copy)]
+pub struct Instance {
+    pub position: glam::Vec3,
+    pub rotation: glam::Quat,
+}
+
+impl Instance {
+    pub fn to_raw(&self) -> InstanceRaw {
+        InstanceRaw {
+            model: (glam::Mat4::from_translation(self.position)
+                * glam::Mat4::from_quat(self.rotation))
+            .to_cols_array_2d(),
+        }
+    }
+}
+
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub struct InstanceRaw {
+    model: [[f32; 4]; 4],
+}
+
+unsafe impl Zeroable for InstanceRaw {}
+unsafe impl Pod for InstanceRaw {}
+
+impl RenderVertex for InstanceRaw {
+    fn buffer_layout_desc<'a>() -> wgpu::VertexBufferLayout<'a> {
+        use core::mem;
+        wgpu::VertexBufferLayout {
+            array_stride: mem::size_of::<InstanceRaw>() as wgpu::BufferAddress,
+            // step_mode 的值需要从 Vertex 改为 Instance
+            // 这意味着只有着色器开始处理一次新实例化绘制时，才会使用下一个实例数据
+            step_mode: wgpu::VertexStepMode::Instance,
+            attributes: &[
+                wgpu::VertexAttribute {
+                    offset: 0,
+                    // 虽然顶点着色器现在只使用了插槽 0 和 1，但在后面的教程中将会使用 2、3 和 4
+                    // 此处从插槽 5 开始，确保与后面的教程不会有冲突
+                    shader_location: 0,
+                    format: wgpu::VertexFormat::Float32x4,
+                },
+                // mat4 从技术的角度来看是由 4 个 vec4 构成，占用 4 个插槽。
+                // 我们需要为每个 vec4 定义一个插槽，然后在着色器中重新组装出 mat4。
+                wgpu::VertexAttribute {
+                    offset: mem::size_of::<[f32; 4]>() as wgpu::BufferAddress,
+                    shader_location: 1,
+                    format: wgpu::VertexFormat::Float32x4,
+                },
+                wgpu::VertexAttribute {
+                    offset: mem::size_of::<[f32; 8]>() as wgpu::BufferAddress,
+                    shader_location: 2,
+                    format: wgpu::VertexFormat::Float32x4,
+                },
+                wgpu::VertexAttribute {
+                    offset: mem::size_of::<[f32; 12]>() as wgpu::BufferAddress,
+                    shader_location: 3,
+                    format: wgpu::VertexFormat::Float32x4,
+                },
+            ],
+        }
+    }
+}
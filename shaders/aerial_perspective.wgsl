@@ -0,0 +1,50 @@
+#include "wgpu_dance/fullscreen.wgsl"
+#include "wgpu_dance/environment.wgsl"
+#include "wgpu_dance/sky.wgsl"
+#include "wgpu_dance/tonemapping.wgsl"
+
+struct AerialUniform {
+    inv_view_proj: mat4x4f,
+    // xyz: 相机位置, w: 天空像素使用的距离
+    eye: vec4f,
+}
+
+@group(0) @binding(0)
+var<uniform> aerial: AerialUniform;
+@group(0) @binding(1)
+var t_color: texture_2d<f32>;
+@group(0) @binding(2)
+var t_depth: texture_depth_2d;
+
+@group(1) @binding(0)
+var<uniform> env: EnvironmentUniform;
+
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32) -> FullscreenOutput {
+    return fullscreen_vertex(index);
+}
+
+@fragment
+fn fs_main(in: FullscreenOutput) -> @location(0) vec4f {
+    let pixel = vec2i(in.clip_position.xy);
+    let color = textureLoad(t_color, pixel, 0);
+    let depth = textureLoad(t_depth, pixel, 0);
+
+    let ndc = vec2f(in.uv.x * 2.0 - 1.0, 1.0 - in.uv.y * 2.0);
+    let p = aerial.inv_view_proj * vec4f(ndc, min(depth, 0.999999), 1.0);
+    var position = p.xyz / p.w;
+    let view_dir = normalize(position - aerial.eye.xyz);
+    // 天空像素按远处的固定距离处理，使远景与地平线的雾霭一致
+    if (depth >= 1.0) {
+        position = aerial.eye.xyz + view_dir * aerial.eye.w;
+    }
+
+    // 地平线以下的方向取地平线处的天空颜色作为散射光
+    let sky_dir = normalize(vec3f(view_dir.x, max(view_dir.y, 0.02), view_dir.z));
+    let sun_dir = normalize(env.sun_direction.xyz);
+    let radiance = sky_radiance(sky_dir, sun_dir, env.sun_direction.w);
+    let inscatter = tonemap_aces(radiance * env.params.x);
+
+    let rgb = apply_environment_fog(env, color.rgb, inscatter, aerial.eye.xyz, position);
+    return vec4f(rgb, color.a);
+}
@@ -0,0 +1,11 @@
+// alpha 测试（镂空）：覆盖率低于阈值的片元被丢弃，用于树叶、栅栏等贴图
+//
+// 阈值是管线常量，由 shader::alpha_test_constants 在创建管线时填入；
+// 默认值 0 不会丢弃任何片元，同一个着色器可同时用于不透明与镂空管线。
+override alpha_cutoff: f32 = 0.0;
+
+fn alpha_test(alpha: f32) {
+    if alpha < alpha_cutoff {
+        discard;
+    }
+}
@@ -0,0 +1,20 @@
+#include "wgpu_dance/fullscreen.wgsl"
+
+struct GradientUniform {
+    top: vec4f,
+    bottom: vec4f,
+}
+
+@group(0) @binding(0)
+var<uniform> gradient: GradientUniform;
+
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32) -> FullscreenOutput {
+    return fullscreen_vertex(index);
+}
+
+@fragment
+fn fs_main(in: FullscreenOutput) -> @location(0) vec4f {
+    // 颜色是线性值，与清屏颜色一样由 sRGB 的 surface 负责编码
+    return mix(gradient.top, gradient.bottom, in.uv.y);
+}
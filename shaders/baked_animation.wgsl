@@ -0,0 +1,27 @@
+// 与 `animation::BakedClipsUniform` 的内存布局保持一致
+// 每个动画一项，x: 起始行，y: 帧数，z: 时长（秒）
+struct BakedClips {
+    clips: array<vec4f, 32>,
+};
+
+// 骨骼纹理中第 `row` 帧、第 `joint` 个关节的蒙皮矩阵，每个关节占 3 个 texel，存放矩阵的前三行
+fn baked_joint_frame(bones: texture_2d<f32>, joint: u32, row: u32) -> mat4x4f {
+    let x = joint * 3u;
+    let r0 = textureLoad(bones, vec2u(x, row), 0);
+    let r1 = textureLoad(bones, vec2u(x + 1u, row), 0);
+    let r2 = textureLoad(bones, vec2u(x + 2u, row), 0);
+    return transpose(mat4x4f(r0, r1, r2, vec4f(0.0, 0.0, 0.0, 1.0)));
+}
+
+// 动画 `clip` 在 `time` 秒时关节的蒙皮矩阵，循环播放并在相邻两帧之间线性插值
+fn baked_joint_matrix(bones: texture_2d<f32>, clip: vec4f, joint: u32, time: f32) -> mat4x4f {
+    let last = u32(clip.y) - 1u;
+    let t = fract(time / clip.z) * f32(last);
+    let frame = min(u32(t), last);
+    let next = min(frame + 1u, last);
+    let blend = t - f32(frame);
+    let first = u32(clip.x);
+    let a = baked_joint_frame(bones, joint, first + frame);
+    let b = baked_joint_frame(bones, joint, first + next);
+    return a * (1.0 - blend) + b * blend;
+}
@@ -0,0 +1,16 @@
+#include "wgpu_dance/fullscreen.wgsl"
+
+@group(0) @binding(0)
+var t_source: texture_2d<f32>;
+@group(0) @binding(1)
+var s_source: sampler;
+
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32) -> FullscreenOutput {
+    return fullscreen_vertex(index);
+}
+
+@fragment
+fn fs_main(in: FullscreenOutput) -> @location(0) vec4f {
+    return textureSample(t_source, s_source, in.uv);
+}
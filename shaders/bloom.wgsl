@@ -0,0 +1,88 @@
+#include "wgpu_dance/fullscreen.wgsl"
+
+struct BloomUniform {
+    // x: 阈值, y: 软过渡宽度, z: 强度, w: 上采样滤波半径（以纹素计）
+    params: vec4f,
+}
+
+@group(0) @binding(0)
+var t_source: texture_2d<f32>;
+@group(0) @binding(1)
+var s_source: sampler;
+
+@group(1) @binding(0)
+var<uniform> bloom: BloomUniform;
+
+@group(2) @binding(0)
+var t_bloom: texture_2d<f32>;
+@group(2) @binding(1)
+var s_bloom: sampler;
+
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32) -> FullscreenOutput {
+    return fullscreen_vertex(index);
+}
+
+fn texel_size() -> vec2f {
+    return 1.0 / vec2f(textureDimensions(t_source));
+}
+
+// 13 次采样的降采样滤波（Jimenez 2014），比单次双线性稳定，移动时不易闪烁
+fn downsample13(uv: vec2f) -> vec3f {
+    let t = texel_size();
+    let a = textureSample(t_source, s_source, uv + t * vec2f(-2.0, -2.0)).rgb;
+    let b = textureSample(t_source, s_source, uv + t * vec2f(0.0, -2.0)).rgb;
+    let c = textureSample(t_source, s_source, uv + t * vec2f(2.0, -2.0)).rgb;
+    let d = textureSample(t_source, s_source, uv + t * vec2f(-2.0, 0.0)).rgb;
+    let e = textureSample(t_source, s_source, uv).rgb;
+    let f = textureSample(t_source, s_source, uv + t * vec2f(2.0, 0.0)).rgb;
+    let g = textureSample(t_source, s_source, uv + t * vec2f(-2.0, 2.0)).rgb;
+    let h = textureSample(t_source, s_source, uv + t * vec2f(0.0, 2.0)).rgb;
+    let i = textureSample(t_source, s_source, uv + t * vec2f(2.0, 2.0)).rgb;
+    let j = textureSample(t_source, s_source, uv + t * vec2f(-1.0, -1.0)).rgb;
+    let k = textureSample(t_source, s_source, uv + t * vec2f(1.0, -1.0)).rgb;
+    let l = textureSample(t_source, s_source, uv + t * vec2f(-1.0, 1.0)).rgb;
+    let m = textureSample(t_source, s_source, uv + t * vec2f(1.0, 1.0)).rgb;
+    return e * 0.125 + (a + c + g + i) * 0.03125 + (b + d + f + h) * 0.0625 + (j + k + l + m) * 0.125;
+}
+
+// 提取超过阈值的部分，阈值附近按二次曲线过渡
+@fragment
+fn fs_prefilter(in: FullscreenOutput) -> @location(0) vec4f {
+    // 限制单个像素的亮度，避免极亮的小高光在泛光中闪烁
+    let color = min(downsample13(in.uv), vec3f(64.0));
+    let threshold = bloom.params.x;
+    let knee = max(bloom.params.y, 1e-4);
+    let brightness = max(color.r, max(color.g, color.b));
+    let soft = clamp(brightness - threshold + knee, 0.0, 2.0 * knee);
+    let contribution = max(soft * soft / (4.0 * knee), brightness - threshold) / max(brightness, 1e-4);
+    return vec4f(color * contribution, 1.0);
+}
+
+@fragment
+fn fs_downsample(in: FullscreenOutput) -> @location(0) vec4f {
+    return vec4f(downsample13(in.uv), 1.0);
+}
+
+// 3x3 帐篷滤波上采样，结果以加法混合叠加到上一级
+@fragment
+fn fs_upsample(in: FullscreenOutput) -> @location(0) vec4f {
+    let t = texel_size() * bloom.params.w;
+    var sum = textureSample(t_source, s_source, in.uv).rgb * 4.0;
+    sum += textureSample(t_source, s_source, in.uv + t * vec2f(-1.0, 0.0)).rgb * 2.0;
+    sum += textureSample(t_source, s_source, in.uv + t * vec2f(1.0, 0.0)).rgb * 2.0;
+    sum += textureSample(t_source, s_source, in.uv + t * vec2f(0.0, -1.0)).rgb * 2.0;
+    sum += textureSample(t_source, s_source, in.uv + t * vec2f(0.0, 1.0)).rgb * 2.0;
+    sum += textureSample(t_source, s_source, in.uv + t * vec2f(-1.0, -1.0)).rgb;
+    sum += textureSample(t_source, s_source, in.uv + t * vec2f(1.0, -1.0)).rgb;
+    sum += textureSample(t_source, s_source, in.uv + t * vec2f(-1.0, 1.0)).rgb;
+    sum += textureSample(t_source, s_source, in.uv + t * vec2f(1.0, 1.0)).rgb;
+    return vec4f(sum / 16.0, 1.0);
+}
+
+@fragment
+fn fs_composite(in: FullscreenOutput) -> @location(0) vec4f {
+    let scene = textureSample(t_source, s_source, in.uv);
+    let glow = textureSample(t_bloom, s_bloom, in.uv).rgb;
+    return vec4f(scene.rgb + glow * bloom.params.z, scene.a);
+}
@@ -0,0 +1,28 @@
+// 与 `camera::CameraUniform` 的内存布局保持一致
+struct CameraUniform {
+    view_proj: mat4x4f,
+    // 上一帧未抖动的 view-projection
+    prev_view_proj: mat4x4f,
+    // xy: 本帧投影在 NDC 中的抖动
+    jitter: vec4f,
+    // xyz: 相机在世界空间中的位置
+    view_pos: vec4f,
+    view: mat4x4f,
+    // 与 view_proj 一样包含抖动
+    proj: mat4x4f,
+    inv_view_proj: mat4x4f,
+};
+
+// 由 NDC 的 xy 与深度重建世界空间位置
+fn camera_world_position(camera: CameraUniform, ndc: vec2f, depth: f32) -> vec3f {
+    let world = camera.inv_view_proj * vec4f(ndc, depth, 1.0);
+    return world.xyz / world.w;
+}
+
+// 由本帧与上一帧的裁剪坐标计算屏幕空间速度（uv 单位），
+// 满足 `上一帧 uv = 当前 uv - 速度`
+fn camera_velocity(camera: CameraUniform, clip: vec4f, prev_clip: vec4f) -> vec2f {
+    let ndc = clip.xy / clip.w - camera.jitter.xy;
+    let prev_ndc = prev_clip.xy / prev_clip.w;
+    return (ndc - prev_ndc) * vec2f(0.5, -0.5);
+}
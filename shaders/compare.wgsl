@@ -0,0 +1,52 @@
+#include "wgpu_dance/fullscreen.wgsl"
+
+struct CompareUniform {
+    // x: 模式（0 分割线, 1 并排, 2 差异）, y: 分割线的像素位置, z: 差异的放大倍数
+    params: vec4f,
+}
+
+@group(0) @binding(0)
+var t_left: texture_2d<f32>;
+@group(0) @binding(1)
+var t_right: texture_2d<f32>;
+@group(0) @binding(2)
+var<uniform> compare: CompareUniform;
+
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32) -> FullscreenOutput {
+    return fullscreen_vertex(index);
+}
+
+@fragment
+fn fs_main(in: FullscreenOutput) -> @location(0) vec4f {
+    let pixel = vec2i(in.clip_position.xy);
+    let width = i32(textureDimensions(t_left).x);
+    let mode = u32(compare.params.x);
+
+    if (mode == 2u) {
+        let a = textureLoad(t_left, pixel, 0);
+        let b = textureLoad(t_right, pixel, 0);
+        return vec4f(abs(a.rgb - b.rgb) * compare.params.z, 1.0);
+    }
+
+    var split = i32(compare.params.y);
+    if (mode == 1u) {
+        // 两侧都显示画面中间的一半，同一个像素出现在两侧的相同位置
+        split = width / 2;
+        let offset = width / 4;
+        if (pixel.x < split) {
+            return textureLoad(t_left, pixel + vec2i(offset, 0), 0);
+        }
+        if (pixel.x > split) {
+            return textureLoad(t_right, pixel - vec2i(offset, 0), 0);
+        }
+    }
+
+    if (abs(pixel.x - split) < 1) {
+        return vec4f(1.0);
+    }
+    if (pixel.x < split) {
+        return textureLoad(t_left, pixel, 0);
+    }
+    return textureLoad(t_right, pixel, 0);
+}
@@ -0,0 +1,76 @@
+#include "wgpu_dance/fullscreen.wgsl"
+
+struct DebugViewUniform {
+    // x: 视图（1 反照率, 2 法线, 3 线性深度, 4 阴影贴图, 5 过度绘制, 6 缺少输入）,
+    // y: 近平面, z: 远平面, w: 热力图中显示为红色的层数
+    params: vec4f,
+}
+
+@group(0) @binding(0)
+var<uniform> debug_view: DebugViewUniform;
+@group(0) @binding(1)
+var t_albedo: texture_2d<f32>;
+@group(0) @binding(2)
+var t_normal: texture_2d<f32>;
+@group(0) @binding(3)
+var t_depth: texture_depth_2d;
+@group(0) @binding(4)
+var t_shadow: texture_depth_2d;
+@group(0) @binding(5)
+var t_overdraw: texture_2d<f32>;
+
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32) -> FullscreenOutput {
+    return fullscreen_vertex(index);
+}
+
+// 蓝、青、绿、黄、红依次表示越来越多的层数
+fn heatmap(t: f32) -> vec3f {
+    let x = clamp(t, 0.0, 1.0) * 4.0;
+    return clamp(vec3f(x - 2.0, 2.0 - abs(x - 2.0), 2.0 - x), vec3f(0.0), vec3f(1.0));
+}
+
+@fragment
+fn fs_main(in: FullscreenOutput) -> @location(0) vec4f {
+    let pixel = vec2i(in.clip_position.xy);
+    let mode = u32(debug_view.params.x);
+
+    switch mode {
+        case 1u: {
+            return vec4f(textureLoad(t_albedo, pixel, 0).rgb, 1.0);
+        }
+        case 2u: {
+            // 没有几何体的像素法线为零，显示为黑色
+            let normal = textureLoad(t_normal, pixel, 0).xyz;
+            if (dot(normal, normal) == 0.0) {
+                return vec4f(0.0, 0.0, 0.0, 1.0);
+            }
+            return vec4f(normalize(normal) * 0.5 + 0.5, 1.0);
+        }
+        case 3u: {
+            let near = debug_view.params.y;
+            let far = debug_view.params.z;
+            let depth = textureLoad(t_depth, pixel, 0);
+            let linear = near * far / (far - depth * (far - near));
+            return vec4f(vec3f((linear - near) / (far - near)), 1.0);
+        }
+        case 4u: {
+            // 阴影贴图拉伸到整个画面
+            let size = vec2f(textureDimensions(t_shadow));
+            let depth = textureLoad(t_shadow, vec2i(in.uv * size), 0);
+            return vec4f(vec3f(depth), 1.0);
+        }
+        case 5u: {
+            let layers = textureLoad(t_overdraw, pixel, 0).r;
+            if (layers == 0.0) {
+                return vec4f(0.0, 0.0, 0.0, 1.0);
+            }
+            return vec4f(heatmap(layers / debug_view.params.w), 1.0);
+        }
+        default: {
+            // 缺少所需的纹理时显示品红与黑色的棋盘格
+            let checker = ((pixel.x >> 4u) + (pixel.y >> 4u)) & 1;
+            return vec4f(f32(checker), 0.0, f32(checker), 1.0);
+        }
+    }
+}
@@ -0,0 +1,29 @@
+// 把 GPU 上的计数转换为 `dispatch_workgroups_indirect` 的参数，每个 `IndirectDispatch` 一次调用
+
+// 与 `indirect::DispatchArgsParams` 的内存布局保持一致
+struct DispatchArgsParams {
+    // 计数在计数缓冲中的下标（以 u32 为单位）
+    counter_index: u32,
+    workgroup_size: u32,
+    // 单个维度允许的最大工作组数
+    max_workgroups: u32,
+    _padding: u32,
+}
+
+@group(0) @binding(0)
+var<uniform> params: DispatchArgsParams;
+@group(0) @binding(1)
+var<storage, read> counters: array<u32>;
+@group(0) @binding(2)
+var<storage, read_write> args: array<u32, 3>;
+
+@compute @workgroup_size(1)
+fn cs_main() {
+    let count = counters[params.counter_index];
+    let size = max(params.workgroup_size, 1u);
+    // 不用 count + size - 1，避免计数接近 u32 上限时溢出
+    let groups = count / size + select(0u, 1u, count % size != 0u);
+    args[0] = min(groups, params.max_workgroups);
+    args[1] = 1u;
+    args[2] = 1u;
+}
@@ -0,0 +1,85 @@
+struct EnvironmentUniform {
+    // xyz: 指向太阳的方向, w: 大气浑浊度
+    sun_direction: vec4f,
+    // rgb: 太阳颜色, w: 强度
+    sun_color: vec4f,
+    // x: 瑞利散射倍数, y: 米氏散射倍数, z: 标高, w: 场景单位到米的换算
+    atmosphere: vec4f,
+    // x: 天空曝光
+    params: vec4f,
+    // rgb: 环境光, w: IBL 亮度
+    ambient: vec4f,
+    // rgb: 雾的颜色, w: 浓度，为 0 时关闭
+    fog_color: vec4f,
+    // x: 浓度随高度的衰减, y: 雾的基准高度
+    fog_params: vec4f,
+}
+
+// 海平面处的散射系数，单位 1/m
+const RAYLEIGH_BETA = vec3f(5.8e-6, 13.5e-6, 33.1e-6);
+const MIE_BETA = vec3f(21e-6);
+
+// 从 `origin` 到 `end` 的光学厚度，大气密度随高度按 exp(-h / H) 衰减
+fn atmosphere_optical_depth(env: EnvironmentUniform, origin: vec3f, end: vec3f) -> vec3f {
+    let scale_height = max(env.atmosphere.z, 1e-3);
+    let distance = length(end - origin);
+    let h0 = max(origin.y, 0.0) / scale_height;
+    let h1 = max(end.y, 0.0) / scale_height;
+    var density = exp(-h0);
+    if (abs(h1 - h0) > 1e-4) {
+        density = (exp(-h0) - exp(-h1)) / (h1 - h0);
+    }
+    let beta = RAYLEIGH_BETA * env.atmosphere.x + MIE_BETA * env.atmosphere.y;
+    return beta * distance * density * env.atmosphere.w;
+}
+
+// 空气透视：按透射率衰减原始颜色，并混入 `inscatter`（通常取该方向上的天空颜色）
+fn apply_aerial_perspective(
+    env: EnvironmentUniform,
+    color: vec3f,
+    inscatter: vec3f,
+    origin: vec3f,
+    end: vec3f,
+) -> vec3f {
+    let transmittance = exp(-atmosphere_optical_depth(env, origin, end));
+    return color * transmittance + inscatter * (1.0 - transmittance);
+}
+
+// 环境光加上 IBL 在法线方向的辐射度，`ibl` 为 `EnvironmentBundle` 的 binding 1
+fn environment_ambient(
+    env: EnvironmentUniform,
+    ibl: texture_cube<f32>,
+    ibl_sampler: sampler,
+    normal: vec3f,
+) -> vec3f {
+    let irradiance = textureSampleLevel(ibl, ibl_sampler, normal, 0.0).rgb;
+    return env.ambient.rgb + irradiance * env.ambient.w;
+}
+
+// 从 `origin` 到 `end` 的高度雾不透明度，浓度为 density * exp(-falloff * (h - base))
+fn height_fog_amount(env: EnvironmentUniform, origin: vec3f, end: vec3f) -> f32 {
+    let density = env.fog_color.w;
+    if (density <= 0.0) {
+        return 0.0;
+    }
+    let falloff = env.fog_params.x;
+    let distance = length(end - origin);
+    let dy = end.y - origin.y;
+    var integral = exp(-falloff * (origin.y - env.fog_params.y));
+    if (abs(falloff * dy) > 1e-4) {
+        integral *= (1.0 - exp(-falloff * dy)) / (falloff * dy);
+    }
+    return 1.0 - exp(-density * integral * distance);
+}
+
+// 空气透视与高度雾，内置渲染器统一使用它处理远景
+fn apply_environment_fog(
+    env: EnvironmentUniform,
+    color: vec3f,
+    inscatter: vec3f,
+    origin: vec3f,
+    end: vec3f,
+) -> vec3f {
+    let rgb = apply_aerial_perspective(env, color, inscatter, origin, end);
+    return mix(rgb, env.fog_color.rgb, height_fog_amount(env, origin, end));
+}
@@ -0,0 +1,106 @@
+// 亮度直方图与曝光自适应，结果写入 `state[0]`（适应后的平均亮度）
+
+struct ExposureUniform {
+    // x: 最小 EV, y: EV 范围, z: 距上一帧的秒数, w: 为 1 时直接跳到目标亮度
+    params: vec4f,
+    // x: 变亮的适应速度, y: 变暗的适应速度, z/w: 参与平均的亮度百分位区间
+    adaptation: vec4f,
+}
+
+const BIN_COUNT: u32 = 256u;
+
+@group(0) @binding(0)
+var<uniform> exposure: ExposureUniform;
+@group(0) @binding(1)
+var t_color: texture_2d<f32>;
+@group(0) @binding(2)
+var<storage, read_write> histogram: array<atomic<u32>, BIN_COUNT>;
+@group(0) @binding(3)
+var<storage, read_write> state: array<f32, 4>;
+
+var<workgroup> local_bins: array<atomic<u32>, BIN_COUNT>;
+var<workgroup> counts: array<u32, BIN_COUNT>;
+
+fn luminance(color: vec3f) -> f32 {
+    return dot(color, vec3f(0.2126, 0.7152, 0.0722));
+}
+
+// 0 号桶存放接近全黑的像素，不参与平均
+fn bin_of(lum: f32) -> u32 {
+    if (lum < 1e-5) {
+        return 0u;
+    }
+    let t = clamp((log2(lum) - exposure.params.x) / exposure.params.y, 0.0, 1.0);
+    return u32(t * f32(BIN_COUNT - 2u)) + 1u;
+}
+
+fn ev_of(bin: u32) -> f32 {
+    let t = (f32(bin) - 0.5) / f32(BIN_COUNT - 2u);
+    return exposure.params.x + t * exposure.params.y;
+}
+
+@compute @workgroup_size(16, 16)
+fn cs_histogram(
+    @builtin(global_invocation_id) id: vec3u,
+    @builtin(local_invocation_index) local: u32,
+) {
+    atomicStore(&local_bins[local], 0u);
+    workgroupBarrier();
+
+    let dims = textureDimensions(t_color);
+    if (id.x < dims.x && id.y < dims.y) {
+        let color = textureLoad(t_color, vec2i(id.xy), 0).rgb;
+        atomicAdd(&local_bins[bin_of(luminance(color))], 1u);
+    }
+    workgroupBarrier();
+
+    atomicAdd(&histogram[local], atomicLoad(&local_bins[local]));
+}
+
+@compute @workgroup_size(256)
+fn cs_adapt(@builtin(local_invocation_index) local: u32) {
+    // 读取后清空，为下一帧的统计做准备
+    counts[local] = atomicLoad(&histogram[local]);
+    atomicStore(&histogram[local], 0u);
+    workgroupBarrier();
+
+    if (local != 0u) {
+        return;
+    }
+
+    var total = 0u;
+    for (var i = 1u; i < BIN_COUNT; i++) {
+        total += counts[i];
+    }
+    // 去掉最暗与最亮的一部分像素，避免少量高光或阴影拉动曝光
+    let low = f32(total) * exposure.adaptation.z;
+    let high = f32(total) * exposure.adaptation.w;
+    var cumulative = 0.0;
+    var ev_sum = 0.0;
+    var weight = 0.0;
+    for (var i = 1u; i < BIN_COUNT; i++) {
+        let count = f32(counts[i]);
+        let inside = clamp(cumulative + count, low, high) - clamp(cumulative, low, high);
+        ev_sum += inside * ev_of(i);
+        weight += inside;
+        cumulative += count;
+    }
+
+    var current = state[0];
+    if (weight <= 0.0) {
+        if (current <= 0.0) {
+            state[0] = 0.18;
+        }
+        return;
+    }
+    let target_lum = exp2(ev_sum / weight);
+    if (exposure.params.w > 0.5 || current <= 0.0) {
+        current = target_lum;
+    } else {
+        let speed = select(exposure.adaptation.y, exposure.adaptation.x, target_lum > current);
+        // 在对数空间中按指数衰减逼近目标，亮暗变化的感受更均匀
+        let t = 1.0 - exp(-exposure.params.z * speed);
+        current = exp2(mix(log2(current), log2(target_lum), t));
+    }
+    state[0] = current;
+}
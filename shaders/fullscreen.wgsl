@@ -0,0 +1,14 @@
+struct FullscreenOutput {
+    @builtin(position) clip_position: vec4f,
+    // 左上角为 (0, 0)，与纹理坐标一致
+    @location(0) uv: vec2f,
+}
+
+// 覆盖全屏的单个三角形，以 `draw(0..3, 0..1)` 绘制
+fn fullscreen_vertex(index: u32) -> FullscreenOutput {
+    let uv = vec2f(f32((index << 1u) & 2u), f32(index & 2u));
+    var out: FullscreenOutput;
+    out.clip_position = vec4f(uv * 2.0 - 1.0, 0.0, 1.0);
+    out.uv = vec2f(uv.x, 1.0 - uv.y);
+    return out;
+}
@@ -0,0 +1,256 @@
+// 在 GPU 上构建线性 BVH（Karras 2012）：Morton 码排序后的三角形作为叶子，由相邻键的公共前缀生成层次，
+// 再由叶子向上原子地合并包围盒。排序由 `radix_sort.wgsl` 完成，各阶段由 `lbvh::GpuBvh::build` 调度
+
+// 与 `lbvh::LbvhParams` 的内存布局保持一致
+struct LbvhParams {
+    triangle_count: u32,
+    // 以 u32 为单位的顶点步长与位置偏移
+    vertex_stride: u32,
+    position_offset: u32,
+    _padding: u32,
+}
+
+// 与 `lbvh::GpuBvhNode` 的内存布局保持一致；0 号为根节点，
+// 前 n - 1 个为内部节点，之后的 n 个为按 Morton 码排列的叶子
+struct LbvhNode {
+    min: vec3f,
+    // 内部节点为左子节点下标，叶子为三角形下标
+    left: u32,
+    max: vec3f,
+    // 内部节点为右子节点下标，叶子为 LEAF
+    right: u32,
+}
+
+const LEAF: u32 = 0xffffffffu;
+const NO_PARENT: u32 = 0xffffffffu;
+
+@group(0) @binding(0)
+var<uniform> params: LbvhParams;
+@group(0) @binding(1)
+var<storage, read> vertices: array<f32>;
+@group(0) @binding(2)
+var<storage, read> indices: array<u32>;
+// 整个场景的包围盒，6 个可排序编码的分量：最小点 xyz，最大点 xyz
+@group(0) @binding(3)
+var<storage, read_write> scene_bounds: array<atomic<u32>, 6>;
+@group(0) @binding(4)
+var<storage, read_write> keys: array<u32>;
+@group(0) @binding(5)
+var<storage, read_write> values: array<u32>;
+@group(0) @binding(6)
+var<storage, read_write> nodes: array<LbvhNode>;
+@group(0) @binding(7)
+var<storage, read_write> parents: array<u32>;
+// 每个节点 6 个可排序编码的包围盒分量，与 `scene_bounds` 相同
+@group(0) @binding(8)
+var<storage, read_write> node_bounds: array<atomic<u32>>;
+
+var<workgroup> group_bounds: array<atomic<u32>, 6>;
+
+// 浮点数映射为保持大小顺序的 u32，使包围盒可以用整数原子操作合并
+fn to_ordered(value: f32) -> u32 {
+    let bits = bitcast<u32>(value);
+    return select(bits | 0x80000000u, ~bits, (bits & 0x80000000u) != 0u);
+}
+
+fn from_ordered(value: u32) -> f32 {
+    return bitcast<f32>(select(~value, value & 0x7fffffffu, (value & 0x80000000u) != 0u));
+}
+
+fn vertex_position(index: u32) -> vec3f {
+    let base = index * params.vertex_stride + params.position_offset;
+    return vec3f(vertices[base], vertices[base + 1u], vertices[base + 2u]);
+}
+
+fn triangle(index: u32) -> array<vec3f, 3> {
+    return array<vec3f, 3>(
+        vertex_position(indices[index * 3u]),
+        vertex_position(indices[index * 3u + 1u]),
+        vertex_position(indices[index * 3u + 2u]),
+    );
+}
+
+@compute @workgroup_size(1)
+fn cs_reset() {
+    for (var i = 0u; i < 3u; i++) {
+        atomicStore(&scene_bounds[i], 0xffffffffu);
+        atomicStore(&scene_bounds[i + 3u], 0u);
+    }
+    // 只有一个三角形时根节点就是叶子
+    parents[0] = NO_PARENT;
+}
+
+// 先在工作组内合并质心的包围盒，每个工作组只做一次全局原子操作
+@compute @workgroup_size(256)
+fn cs_bounds(
+    @builtin(global_invocation_id) id: vec3u,
+    @builtin(local_invocation_index) local: u32,
+) {
+    if (local < 3u) {
+        atomicStore(&group_bounds[local], 0xffffffffu);
+        atomicStore(&group_bounds[local + 3u], 0u);
+    }
+    workgroupBarrier();
+    if (id.x < params.triangle_count) {
+        let t = triangle(id.x);
+        let centroid = (t[0] + t[1] + t[2]) / 3.0;
+        for (var i = 0u; i < 3u; i++) {
+            atomicMin(&group_bounds[i], to_ordered(centroid[i]));
+            atomicMax(&group_bounds[i + 3u], to_ordered(centroid[i]));
+        }
+    }
+    workgroupBarrier();
+    if (local < 3u) {
+        atomicMin(&scene_bounds[local], atomicLoad(&group_bounds[local]));
+        atomicMax(&scene_bounds[local + 3u], atomicLoad(&group_bounds[local + 3u]));
+    }
+}
+
+// 把 10 位整数的每一位之间插入两个零
+fn expand_bits(value: u32) -> u32 {
+    var v = value & 0x3ffu;
+    v = (v | (v << 16u)) & 0x030000ffu;
+    v = (v | (v << 8u)) & 0x0300f00fu;
+    v = (v | (v << 4u)) & 0x030c30c3u;
+    v = (v | (v << 2u)) & 0x09249249u;
+    return v;
+}
+
+// 质心在场景包围盒中的 30 位 Morton 码，同时初始化该线程负责的节点包围盒
+@compute @workgroup_size(256)
+fn cs_morton(@builtin(global_invocation_id) id: vec3u) {
+    let index = id.x;
+    let n = params.triangle_count;
+    if (index >= n) {
+        return;
+    }
+    let t = triangle(index);
+    let centroid = (t[0] + t[1] + t[2]) / 3.0;
+    var low = vec3f(0.0);
+    var high = vec3f(0.0);
+    for (var i = 0u; i < 3u; i++) {
+        low[i] = from_ordered(atomicLoad(&scene_bounds[i]));
+        high[i] = from_ordered(atomicLoad(&scene_bounds[i + 3u]));
+    }
+    let extent = high - low;
+    let normalized = select(vec3f(0.0), (centroid - low) / extent, extent > vec3f(0.0));
+    let cell = vec3u(clamp(normalized * 1024.0, vec3f(0.0), vec3f(1023.0)));
+    keys[index] = (expand_bits(cell.x) << 2u) | (expand_bits(cell.y) << 1u) | expand_bits(cell.z);
+    values[index] = index;
+
+    // 叶子 n - 1 + index，以及内部节点 index
+    for (var node = index; node < 2u * n - 1u; node += n) {
+        for (var i = 0u; i < 3u; i++) {
+            atomicStore(&node_bounds[node * 6u + i], 0xffffffffu);
+            atomicStore(&node_bounds[node * 6u + i + 3u], 0u);
+        }
+    }
+}
+
+// 排序后第 i 与第 j 个键的公共前缀长度，键相同时用下标继续区分；j 越界时为 -1
+fn common_prefix(i: i32, j: i32) -> i32 {
+    if (j < 0 || j >= i32(params.triangle_count)) {
+        return -1;
+    }
+    let a = keys[i];
+    let b = keys[j];
+    if (a == b) {
+        return 32 + i32(countLeadingZeros(u32(i) ^ u32(j)));
+    }
+    return i32(countLeadingZeros(a ^ b));
+}
+
+@compute @workgroup_size(256)
+fn cs_hierarchy(@builtin(global_invocation_id) id: vec3u) {
+    let n = params.triangle_count;
+    if (id.x + 1u >= n) {
+        return;
+    }
+    let i = i32(id.x);
+    // 节点覆盖的范围向公共前缀更长的一侧延伸
+    let d = select(-1, 1, common_prefix(i, i + 1) > common_prefix(i, i - 1));
+    let min_prefix = common_prefix(i, i - d);
+    var max_length = 2;
+    while (common_prefix(i, i + max_length * d) > min_prefix) {
+        max_length *= 2;
+    }
+    var length = 0;
+    for (var step = max_length / 2; step >= 1; step /= 2) {
+        if (common_prefix(i, i + (length + step) * d) > min_prefix) {
+            length += step;
+        }
+    }
+    let j = i + length * d;
+
+    // 二分查找范围内公共前缀变化的位置
+    let node_prefix = common_prefix(i, j);
+    var split = 0;
+    var remaining = length;
+    loop {
+        remaining = (remaining + 1) / 2;
+        if (common_prefix(i, i + (split + remaining) * d) > node_prefix) {
+            split += remaining;
+        }
+        if (remaining <= 1) {
+            break;
+        }
+    }
+    let gamma = i + split * d + min(d, 0);
+
+    let first = u32(min(i, j));
+    let last = u32(max(i, j));
+    let leaf_base = n - 1u;
+    let left = select(u32(gamma), leaf_base + u32(gamma), first == u32(gamma));
+    let right = select(u32(gamma) + 1u, leaf_base + u32(gamma) + 1u, last == u32(gamma) + 1u);
+    nodes[id.x].left = left;
+    nodes[id.x].right = right;
+    parents[left] = id.x;
+    parents[right] = id.x;
+}
+
+// 每个叶子把自己的包围盒向上合并到所有祖先，某个祖先已经包含它时停止
+@compute @workgroup_size(256)
+fn cs_refit(@builtin(global_invocation_id) id: vec3u) {
+    let n = params.triangle_count;
+    if (id.x >= n) {
+        return;
+    }
+    let triangle_index = values[id.x];
+    let t = triangle(triangle_index);
+    let low = min(min(t[0], t[1]), t[2]);
+    let high = max(max(t[0], t[1]), t[2]);
+    let leaf = n - 1u + id.x;
+    nodes[leaf].left = triangle_index;
+    nodes[leaf].right = LEAF;
+
+    var node = leaf;
+    loop {
+        var grown = false;
+        for (var i = 0u; i < 3u; i++) {
+            let ordered_low = to_ordered(low[i]);
+            let ordered_high = to_ordered(high[i]);
+            let old_low = atomicMin(&node_bounds[node * 6u + i], ordered_low);
+            let old_high = atomicMax(&node_bounds[node * 6u + i + 3u], ordered_high);
+            grown = grown || old_low > ordered_low || old_high < ordered_high;
+        }
+        if (!grown) {
+            break;
+        }
+        node = parents[node];
+        if (node == NO_PARENT) {
+            break;
+        }
+    }
+}
+
+@compute @workgroup_size(256)
+fn cs_finalize(@builtin(global_invocation_id) id: vec3u) {
+    let node = id.x;
+    if (node >= 2u * params.triangle_count - 1u) {
+        return;
+    }
+    for (var i = 0u; i < 3u; i++) {
+        nodes[node].min[i] = from_ordered(atomicLoad(&node_bounds[node * 6u + i]));
+        nodes[node].max[i] = from_ordered(atomicLoad(&node_bounds[node * 6u + i + 3u]));
+    }
+}
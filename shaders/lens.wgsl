@@ -0,0 +1,51 @@
+#include "wgpu_dance/fullscreen.wgsl"
+
+struct LensUniform {
+    // x: 暗角强度, y: 暗角过渡宽度, z: 色差强度, w: 颗粒强度
+    params: vec4f,
+    // x: 帧序号, y: 宽高比
+    frame: vec4f,
+}
+
+@group(0) @binding(0)
+var t_color: texture_2d<f32>;
+@group(0) @binding(1)
+var s_color: sampler;
+
+@group(1) @binding(0)
+var<uniform> lens: LensUniform;
+
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32) -> FullscreenOutput {
+    return fullscreen_vertex(index);
+}
+
+fn hash(p: vec3f) -> f32 {
+    var q = fract(p * 0.1031);
+    q += dot(q, q.zyx + 31.32);
+    return fract((q.x + q.y) * q.z);
+}
+
+@fragment
+fn fs_main(in: FullscreenOutput) -> @location(0) vec4f {
+    let centered = in.uv - 0.5;
+
+    // 色差：红蓝通道沿径向向相反方向偏移，越靠近边缘偏移越大
+    let offset = centered * lens.params.z * 0.02;
+    let base = textureSample(t_color, s_color, in.uv);
+    let r = textureSample(t_color, s_color, in.uv + offset).r;
+    let b = textureSample(t_color, s_color, in.uv - offset).b;
+    var color = vec3f(r, base.g, b);
+
+    // 暗角：按宽高比修正后的到中心距离，0 为中心，1 为角落
+    let aspect = vec2f(lens.frame.y, 1.0);
+    let d = length(centered * aspect) / length(0.5 * aspect);
+    color *= 1.0 - lens.params.x * smoothstep(1.0 - lens.params.y, 1.0, d);
+
+    // 颗粒：每帧变化的噪声，暗部稍强
+    let noise = hash(vec3f(in.clip_position.xy, lens.frame.x)) - 0.5;
+    let luma = dot(color, vec3f(0.2126, 0.7152, 0.0722));
+    color += noise * lens.params.w * mix(1.0, 0.5, luma);
+
+    return vec4f(max(color, vec3f(0.0)), base.a);
+}
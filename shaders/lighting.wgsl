@@ -0,0 +1,75 @@
+// xyz: 光照方向（指向光源），w: 强度
+struct DirectionalLight {
+    direction: vec4f,
+    color: vec4f,
+    // 见 `light::ShadowSettings`，x: 固定偏移，y: 斜率偏移，z: 法线外推距离，w: PCF 半径（纹素）
+    shadow_bias: vec4f,
+    // x: 级联混合宽度
+    shadow_cascade: vec4f,
+};
+
+// 与 `light::PointLightUniform` 的内存布局保持一致
+struct PointLight {
+    // xyz: 位置，w: 作用范围
+    position: vec4f,
+    // rgb: 颜色，a: 强度
+    color: vec4f,
+    // x: 衰减方式，0 为平滑窗口，1 为平方反比
+    attenuation: vec4f,
+};
+
+fn lambert(normal: vec3f, light_dir: vec3f) -> f32 {
+    return max(dot(normal, light_dir), 0.0);
+}
+
+fn blinn_phong(normal: vec3f, light_dir: vec3f, view_dir: vec3f, shininess: f32) -> f32 {
+    let half_dir = normalize(light_dir + view_dir);
+    return pow(max(dot(normal, half_dir), 0.0), shininess);
+}
+
+// 在作用范围边缘平滑衰减到 0 的距离衰减
+fn range_attenuation(distance: f32, range: f32) -> f32 {
+    let ratio = clamp(1.0 - pow(distance / range, 4.0), 0.0, 1.0);
+    return ratio * ratio / max(distance * distance, 0.0001);
+}
+
+// 超过作用范围直接截断的平方反比衰减
+fn inverse_square_attenuation(distance: f32, range: f32) -> f32 {
+    return select(0.0, 1.0 / max(distance * distance, 0.0001), distance < range);
+}
+
+// 按 `mode` 选择的衰减方式，与 `light::Attenuation` 一致
+fn light_attenuation(mode: f32, distance: f32, range: f32) -> f32 {
+    if (mode > 0.5) {
+        return inverse_square_attenuation(distance, range);
+    }
+    return range_attenuation(distance, range);
+}
+
+// 点光源在 `world_position` 处的辐照度（未乘颜色），`normal` 需已归一化
+fn point_light_irradiance(light: PointLight, world_position: vec3f, normal: vec3f) -> f32 {
+    let to_light = light.position.xyz - world_position;
+    let distance = length(to_light);
+    let attenuation = light_attenuation(light.attenuation.x, distance, light.position.w);
+    return lambert(normal, to_light / max(distance, 0.0001)) * attenuation * light.color.a;
+}
+
+// Cook-Torrance 直接光照（GGX 法线分布、Smith 几何项、Schlick 菲涅耳），已乘以 n·l；
+// 向量都需已归一化，`n`、`v`、`l` 分别为法线、指向相机与指向光源的方向
+fn pbr_direct(albedo: vec3f, metallic: f32, roughness: f32, n: vec3f, v: vec3f, l: vec3f) -> vec3f {
+    let h = normalize(v + l);
+    let n_dot_l = max(dot(n, l), 0.0);
+    let n_dot_v = max(dot(n, v), 1e-4);
+    let n_dot_h = max(dot(n, h), 0.0);
+    let a = max(roughness * roughness, 1e-3);
+    let a2 = a * a;
+    let denom = n_dot_h * n_dot_h * (a2 - 1.0) + 1.0;
+    let d = a2 / (3.14159265 * denom * denom);
+    let k = (roughness + 1.0) * (roughness + 1.0) / 8.0;
+    let g = n_dot_v / (n_dot_v * (1.0 - k) + k) * n_dot_l / (n_dot_l * (1.0 - k) + k);
+    let f0 = mix(vec3f(0.04), albedo, metallic);
+    let f = f0 + (1.0 - f0) * pow(1.0 - max(dot(v, h), 0.0), 5.0);
+    let specular = d * g * f / max(4.0 * n_dot_v * n_dot_l, 1e-4);
+    let diffuse = (1.0 - f) * (1.0 - metallic) * albedo / 3.14159265;
+    return (diffuse + specular) * n_dot_l;
+}
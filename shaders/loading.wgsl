@@ -0,0 +1,41 @@
+#include "wgpu_dance/fullscreen.wgsl"
+
+struct LoadingUniform {
+    // xy: 画面像素大小
+    resolution: vec4f,
+    // x: 进度，小于 0 表示未知, y: 秒
+    params: vec4f,
+}
+
+@group(0) @binding(0)
+var<uniform> loading: LoadingUniform;
+
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32) -> FullscreenOutput {
+    return fullscreen_vertex(index);
+}
+
+@fragment
+fn fs_main(in: FullscreenOutput) -> @location(0) vec4f {
+    let background = vec3f(0.06, 0.06, 0.08);
+    let size = loading.resolution.xy;
+    // 居中的进度条，宽度为画面的 40%，高度为 6 像素
+    let half_extent = vec2f(size.x * 0.2, 3.0);
+    let local = in.clip_position.xy - size * 0.5;
+    if (any(abs(local) > half_extent)) {
+        return vec4f(background, 1.0);
+    }
+
+    let x = (local.x + half_extent.x) / (2.0 * half_extent.x);
+    let progress = loading.params.x;
+    var filled: bool;
+    if (progress < 0.0) {
+        // 进度未知时一小段来回移动
+        let center = 0.5 + 0.4 * sin(loading.params.y * 3.0);
+        filled = abs(x - center) < 0.1;
+    } else {
+        filled = x <= progress;
+    }
+    let color = select(vec3f(0.2, 0.2, 0.24), vec3f(0.85, 0.87, 0.95), filled);
+    return vec4f(color, 1.0);
+}
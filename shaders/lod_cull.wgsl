@@ -0,0 +1,75 @@
+// 视锥剔除并按距离选择 LOD，每个等级的实例写入各自的区域并累加对应的间接绘制参数
+
+const MAX_LEVELS: u32 = 4u;
+
+struct LodUniform {
+    planes: array<vec4f, 6>,
+    // xyz: 相机位置, w: 最后一级结束前开始淡出的距离
+    eye: vec4f,
+    // 各等级的最大距离
+    distances: vec4f,
+    // x: 实例数量, y: 等级数量, z: 每个等级区域可容纳的实例数
+    params: vec4f,
+}
+
+struct SourceInstance {
+    model: mat4x4f,
+    bounds: vec4f,
+}
+
+struct CulledInstance {
+    model: mat4x4f,
+    fade: vec4f,
+}
+
+struct DrawIndexedIndirect {
+    index_count: u32,
+    instance_count: atomic<u32>,
+    first_index: u32,
+    base_vertex: i32,
+    first_instance: u32,
+}
+
+@group(0) @binding(0)
+var<uniform> lod: LodUniform;
+@group(0) @binding(1)
+var<storage, read> sources: array<SourceInstance>;
+@group(0) @binding(2)
+var<storage, read_write> culled: array<CulledInstance>;
+@group(0) @binding(3)
+var<storage, read_write> draw_args: array<DrawIndexedIndirect, MAX_LEVELS>;
+
+@compute @workgroup_size(64)
+fn cs_main(@builtin(global_invocation_id) id: vec3u) {
+    if (id.x >= u32(lod.params.x)) {
+        return;
+    }
+    let source = sources[id.x];
+    let center = source.bounds.xyz;
+    let radius = source.bounds.w;
+
+    for (var i = 0u; i < 6u; i++) {
+        let plane = lod.planes[i];
+        if (dot(plane.xyz, center) + plane.w < -radius) {
+            return;
+        }
+    }
+
+    let dist = length(center - lod.eye.xyz);
+    let level_count = u32(lod.params.y);
+    var level = level_count;
+    for (var i = 0u; i < level_count; i++) {
+        if (dist <= lod.distances[i]) {
+            level = i;
+            break;
+        }
+    }
+    if (level >= level_count) {
+        return;
+    }
+
+    let end = lod.distances[level_count - 1u];
+    let fade = 1.0 - smoothstep(end - lod.eye.w, end, dist);
+    let slot = atomicAdd(&draw_args[level].instance_count, 1u);
+    culled[level * u32(lod.params.z) + slot] = CulledInstance(source.model, vec4f(fade, f32(level), 0.0, 0.0));
+}
@@ -0,0 +1,95 @@
+// 每个工作组剔除一个 meshlet，可见时把它的索引复制到压缩后的索引缓冲并累加间接绘制的索引数量
+
+const MAX_TRIANGLES: u32 = 64u;
+const CULLED: u32 = 0xffffffffu;
+
+struct MeshletUniform {
+    planes: array<vec4f, 6>,
+    // xyz: 相机位置, w: 模型矩阵各轴的最大缩放
+    eye: vec4f,
+    model: mat4x4f,
+    // x: meshlet 数量, y: 是否视锥剔除, z: 是否背面剔除
+    params: vec4u,
+}
+
+struct Meshlet {
+    // xyz: 包围球中心, w: 半径
+    bounds: vec4f,
+    // xyz: 法线锥轴, w: 剔除阈值
+    cone: vec4f,
+    // x: 首个索引, y: 索引数量
+    range: vec4u,
+}
+
+struct DrawIndexedIndirect {
+    index_count: atomic<u32>,
+    instance_count: u32,
+    first_index: u32,
+    base_vertex: i32,
+    first_instance: u32,
+}
+
+@group(0) @binding(0)
+var<uniform> cull: MeshletUniform;
+@group(0) @binding(1)
+var<storage, read> meshlets: array<Meshlet>;
+@group(0) @binding(2)
+var<storage, read> source_indices: array<u32>;
+@group(0) @binding(3)
+var<storage, read_write> visible_indices: array<u32>;
+@group(0) @binding(4)
+var<storage, read_write> draw_args: DrawIndexedIndirect;
+
+var<workgroup> write_offset: u32;
+
+fn is_visible(meshlet: Meshlet) -> bool {
+    let center = (cull.model * vec4f(meshlet.bounds.xyz, 1.0)).xyz;
+    let radius = meshlet.bounds.w * cull.eye.w;
+
+    if (cull.params.y != 0u) {
+        for (var i = 0u; i < 6u; i++) {
+            let plane = cull.planes[i];
+            if (dot(plane.xyz, center) + plane.w < -radius) {
+                return false;
+            }
+        }
+    }
+
+    if (cull.params.z != 0u) {
+        let axis = normalize((cull.model * vec4f(meshlet.cone.xyz, 0.0)).xyz);
+        let view = center - cull.eye.xyz;
+        if (dot(view, axis) >= meshlet.cone.w * length(view) + radius) {
+            return false;
+        }
+    }
+    return true;
+}
+
+@compute @workgroup_size(MAX_TRIANGLES)
+fn cs_main(
+    @builtin(workgroup_id) group: vec3u,
+    @builtin(num_workgroups) groups: vec3u,
+    @builtin(local_invocation_index) local: u32,
+) {
+    let index = group.y * groups.x + group.x;
+    if (index >= cull.params.x) {
+        return;
+    }
+    let meshlet = meshlets[index];
+
+    if (local == 0u) {
+        if (is_visible(meshlet)) {
+            write_offset = atomicAdd(&draw_args.index_count, meshlet.range.y);
+        } else {
+            write_offset = CULLED;
+        }
+    }
+    let offset = workgroupUniformLoad(&write_offset);
+    if (offset == CULLED || local * 3u >= meshlet.range.y) {
+        return;
+    }
+
+    for (var k = 0u; k < 3u; k++) {
+        visible_indices[offset + local * 3u + k] = source_indices[meshlet.range.x + local * 3u + k];
+    }
+}
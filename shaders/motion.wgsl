@@ -0,0 +1,15 @@
+// 由深度重投影得到像素在上一帧中的 uv，只包含相机运动；
+// `jitter` 为本帧投影在 NDC 中的抖动
+fn reproject_uv(
+    uv: vec2f,
+    depth: f32,
+    jitter: vec2f,
+    inv_view_proj: mat4x4f,
+    prev_view_proj: mat4x4f,
+) -> vec2f {
+    let ndc = vec2f(uv.x * 2.0 - 1.0, 1.0 - uv.y * 2.0) - jitter;
+    let world = inv_view_proj * vec4f(ndc, depth, 1.0);
+    let prev_clip = prev_view_proj * vec4f(world.xyz / world.w, 1.0);
+    let prev_ndc = prev_clip.xy / prev_clip.w;
+    return vec2f(prev_ndc.x * 0.5 + 0.5, 0.5 - prev_ndc.y * 0.5);
+}
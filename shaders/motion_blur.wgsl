@@ -0,0 +1,53 @@
+#include "wgpu_dance/fullscreen.wgsl"
+#include "wgpu_dance/motion.wgsl"
+
+// 场景提供速度纹理时，几何体像素使用逐物体速度
+override HAS_VELOCITY: bool = false;
+
+struct MotionBlurUniform {
+    inv_view_proj: mat4x4f,
+    prev_view_proj: mat4x4f,
+    // x: 强度（快门时间占帧间隔的比例）, y: 采样数, z: 最大模糊长度（uv 单位）
+    params: vec4f,
+}
+
+@group(0) @binding(0)
+var<uniform> blur: MotionBlurUniform;
+@group(0) @binding(1)
+var t_color: texture_2d<f32>;
+@group(0) @binding(2)
+var t_depth: texture_depth_2d;
+@group(0) @binding(3)
+var t_velocity: texture_2d<f32>;
+@group(0) @binding(4)
+var s_linear: sampler;
+
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32) -> FullscreenOutput {
+    return fullscreen_vertex(index);
+}
+
+@fragment
+fn fs_main(in: FullscreenOutput) -> @location(0) vec4f {
+    let pixel = vec2i(in.clip_position.xy);
+    let depth = textureLoad(t_depth, pixel, 0);
+
+    var velocity = in.uv - reproject_uv(in.uv, depth, vec2f(0.0), blur.inv_view_proj, blur.prev_view_proj);
+    if (HAS_VELOCITY && depth < 1.0) {
+        velocity = textureLoad(t_velocity, pixel, 0).xy;
+    }
+    velocity *= blur.params.x;
+    let len = length(velocity);
+    if (len > blur.params.z) {
+        velocity *= blur.params.z / len;
+    }
+
+    // 沿速度方向在当前位置前后对称采样
+    let samples = max(u32(blur.params.y), 2u);
+    var sum = vec4f(0.0);
+    for (var i = 0u; i < samples; i++) {
+        let t = f32(i) / f32(samples - 1u) - 0.5;
+        sum += textureSampleLevel(t_color, s_linear, in.uv + velocity * t, 0.0);
+    }
+    return sum / f32(samples);
+}
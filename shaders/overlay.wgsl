@@ -0,0 +1,30 @@
+#include "wgpu_dance/fullscreen.wgsl"
+#include "wgpu_dance/tonemapping.wgsl"
+
+// 预乘 alpha 的叠加层，颜色已经是 sRGB 编码
+@group(0) @binding(0)
+var t_overlay: texture_2d<f32>;
+@group(0) @binding(1)
+var s_overlay: sampler;
+
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32) -> FullscreenOutput {
+    return fullscreen_vertex(index);
+}
+
+// 输出视图不是 sRGB 格式，混合直接发生在 sRGB 编码的颜色上
+@fragment
+fn fs_main(in: FullscreenOutput) -> @location(0) vec4f {
+    return textureSample(t_overlay, s_overlay, in.uv);
+}
+
+// 输出视图是 sRGB 格式时先解码；不透明像素与 `fs_main` 一致，半透明边缘的混合在线性空间进行
+@fragment
+fn fs_linear(in: FullscreenOutput) -> @location(0) vec4f {
+    let overlay = textureSample(t_overlay, s_overlay, in.uv);
+    if overlay.a <= 0.0 {
+        return vec4f(0.0);
+    }
+    let color = srgb_to_linear(overlay.rgb / overlay.a);
+    return vec4f(color * overlay.a, overlay.a);
+}
@@ -0,0 +1,94 @@
+#include "wgpu_dance/camera.wgsl"
+#include "wgpu_dance/environment.wgsl"
+#include "wgpu_dance/sky.wgsl"
+#include "wgpu_dance/tonemapping.wgsl"
+
+struct ParticleUniform {
+    camera_right: vec4f,
+    camera_up: vec4f,
+    // xyz: 相机位置, w: 软粒子淡出距离
+    eye: vec4f,
+    // x: znear, y: zfar, z: 是否应用高度雾
+    params: vec4f,
+}
+
+@group(0) @binding(0)
+var<uniform> camera: CameraUniform;
+
+@group(1) @binding(0)
+var<uniform> particles: ParticleUniform;
+@group(1) @binding(1)
+var t_depth: texture_depth_2d;
+
+@group(2) @binding(0)
+var<uniform> env: EnvironmentUniform;
+
+struct ParticleInput {
+    // xyz: 中心位置, w: 尺寸
+    @location(0) position_size: vec4f,
+    @location(1) color: vec4f,
+}
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4f,
+    @location(0) color: vec4f,
+    @location(1) uv: vec2f,
+    @location(2) world_position: vec3f,
+    @location(3) view_depth: f32,
+}
+
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32, particle: ParticleInput) -> VertexOutput {
+    let corners = array<vec2f, 6>(
+        vec2f(-1.0, -1.0), vec2f(1.0, -1.0), vec2f(1.0, 1.0),
+        vec2f(-1.0, -1.0), vec2f(1.0, 1.0), vec2f(-1.0, 1.0),
+    );
+    let corner = corners[index];
+    let half_size = particle.position_size.w * 0.5;
+    let world = particle.position_size.xyz
+        + (particles.camera_right.xyz * corner.x + particles.camera_up.xyz * corner.y) * half_size;
+
+    var out: VertexOutput;
+    out.clip_position = camera.view_proj * vec4f(world, 1.0);
+    out.color = particle.color;
+    out.uv = corner;
+    out.world_position = world;
+    out.view_depth = out.clip_position.w;
+    return out;
+}
+
+fn linear_depth(depth: f32) -> f32 {
+    let znear = particles.params.x;
+    let zfar = particles.params.y;
+    return znear * zfar / (zfar - depth * (zfar - znear));
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4f {
+    // 圆形的柔和粒子
+    let shape = exp(-dot(in.uv, in.uv) * 3.0) * (1.0 - smoothstep(0.8, 1.0, length(in.uv)));
+
+    // 软粒子：越接近场景表面越透明，避免与几何体相交处出现硬边
+    var soft = 1.0;
+    let softness = particles.eye.w;
+    if (softness > 0.0) {
+        let scene_depth = linear_depth(textureLoad(t_depth, vec2i(in.clip_position.xy), 0));
+        soft = clamp((scene_depth - in.view_depth) / softness, 0.0, 1.0);
+    }
+
+    var rgb = in.color.rgb;
+    if (particles.params.z > 0.5) {
+        // 与不透明几何体使用同一套高度雾，远处与高处的粒子同样被雾霭覆盖
+        let view_dir = normalize(in.world_position - particles.eye.xyz);
+        let sky_dir = normalize(vec3f(view_dir.x, max(view_dir.y, 0.02), view_dir.z));
+        let radiance = sky_radiance(sky_dir, normalize(env.sun_direction.xyz), env.sun_direction.w);
+        let inscatter = tonemap_aces(radiance * env.params.x);
+        rgb = apply_environment_fog(env, rgb, inscatter, particles.eye.xyz, in.world_position);
+    }
+
+    let alpha = in.color.a * shape * soft;
+    if (alpha <= 0.001) {
+        discard;
+    }
+    return vec4f(rgb, alpha);
+}
@@ -0,0 +1,171 @@
+struct Particle {
+    // xyz: 位置, w: 年龄
+    position_age: vec4f,
+    // xyz: 速度, w: 是否存活
+    velocity_alive: vec4f,
+}
+
+struct ParticleRaw {
+    // xyz: 中心位置, w: 尺寸
+    position_size: vec4f,
+    color: vec4f,
+}
+
+struct SimulationUniform {
+    view_proj: mat4x4f,
+    inv_view_proj: mat4x4f,
+    // xyz: 发射位置, w: 本帧时长
+    emitter: vec4f,
+    // xyz: 发射位置的随机偏移, w: 生命周期
+    jitter_lifetime: vec4f,
+    // xyz: 初速度, w: 初速度的随机扰动
+    velocity: vec4f,
+    // xyz: 加速度, w: 阻力
+    acceleration: vec4f,
+    start_color: vec4f,
+    end_color: vec4f,
+    // x: 起始尺寸, y: 结束尺寸, z: 本帧发射数, w: 随机种子
+    spawn: vec4f,
+    // x: 是否碰撞, y: 弹性, z: 摩擦, w: 碰撞厚度
+    collision: vec4f,
+    // x: znear, y: zfar, z: 存活粒子上限
+    params: vec4f,
+}
+
+@group(0) @binding(0)
+var<uniform> sim: SimulationUniform;
+@group(0) @binding(1)
+var<storage, read_write> particles: array<Particle>;
+@group(0) @binding(2)
+var<storage, read_write> instances: array<ParticleRaw>;
+@group(0) @binding(3)
+var<storage, read_write> spawned: atomic<u32>;
+@group(0) @binding(4)
+var t_depth: texture_depth_2d;
+
+fn hash(value: u32) -> u32 {
+    // PCG 哈希
+    let state = value * 747796405u + 2891336453u;
+    let word = ((state >> ((state >> 28u) + 4u)) ^ state) * 277803737u;
+    return (word >> 22u) ^ word;
+}
+
+/// 各分量在 [-1, 1) 内的随机向量
+fn random3(seed: u32) -> vec3f {
+    let x = hash(seed);
+    let y = hash(x);
+    let z = hash(y);
+    return vec3f(vec3u(x, y, z) >> vec3u(8u)) / 8388608.0 - 1.0;
+}
+
+fn spawn_particle(index: u32) -> Particle {
+    let seed = hash(index ^ hash(u32(sim.spawn.w)));
+    let position = sim.emitter.xyz + random3(seed) * sim.jitter_lifetime.xyz;
+    let velocity = sim.velocity.xyz + random3(seed ^ 0x9e3779b9u) * sim.velocity.w;
+    return Particle(vec4f(position, 0.0), vec4f(velocity, 1.0));
+}
+
+fn linear_depth(depth: f32) -> f32 {
+    let znear = sim.params.x;
+    let zfar = sim.params.y;
+    return znear * zfar / (zfar - depth * (zfar - znear));
+}
+
+/// 由深度重建像素中心的世界坐标
+fn world_at(pixel: vec2i, size: vec2f) -> vec3f {
+    let depth = textureLoad(t_depth, pixel, 0);
+    let uv = (vec2f(pixel) + 0.5) / size;
+    let world = sim.inv_view_proj * vec4f(uv.x * 2.0 - 1.0, 1.0 - uv.y * 2.0, depth, 1.0);
+    return world.xyz / world.w;
+}
+
+/// 粒子落到深度缓冲中的表面之后时，把它放回表面前方并反弹
+///
+/// 深度缓冲只记录了离相机最近的表面，因此只在表面之后 `collision.w` 的厚度内判定碰撞，
+/// 更深处的粒子视为在物体背后穿过。屏幕外或被遮挡的表面不参与碰撞。
+fn collide(particle: ptr<function, Particle>, previous: vec3f) {
+    let clip = sim.view_proj * vec4f((*particle).position_age.xyz, 1.0);
+    if (clip.w <= 0.0) {
+        return;
+    }
+    let ndc = clip.xy / clip.w;
+    if (any(abs(ndc) >= vec2f(1.0))) {
+        return;
+    }
+    let size = vec2f(textureDimensions(t_depth));
+    let uv = vec2f(ndc.x * 0.5 + 0.5, 0.5 - ndc.y * 0.5);
+    let pixel = clamp(vec2i(uv * size), vec2i(0), vec2i(size) - 2);
+    let depth = textureLoad(t_depth, pixel, 0);
+    if (depth >= 1.0) {
+        return;
+    }
+    let penetration = clip.w - linear_depth(depth);
+    if (penetration < 0.0 || penetration > sim.collision.w) {
+        return;
+    }
+
+    // 相邻像素重建的位置给出表面法线，朝向粒子来的一侧
+    let center = world_at(pixel, size);
+    let dx = world_at(pixel + vec2i(1, 0), size) - center;
+    let dy = world_at(pixel + vec2i(0, 1), size) - center;
+    var normal = normalize(cross(dx, dy));
+    if (dot(normal, previous - center) < 0.0) {
+        normal = -normal;
+    }
+
+    let velocity = (*particle).velocity_alive.xyz;
+    let normal_speed = dot(velocity, normal);
+    if (normal_speed < 0.0) {
+        let tangent = velocity - normal * normal_speed;
+        let bounced = tangent * (1.0 - sim.collision.z) - normal * normal_speed * sim.collision.y;
+        (*particle).velocity_alive = vec4f(bounced, 1.0);
+    }
+    // 沿法线投影到重建的表面上，保留切线方向的位置
+    let position = (*particle).position_age.xyz;
+    let surface = position - normal * (dot(position - center, normal) - 0.01);
+    (*particle).position_age = vec4f(surface, (*particle).position_age.w);
+}
+
+@compute @workgroup_size(64)
+fn cs_main(@builtin(global_invocation_id) id: vec3u) {
+    let index = id.x;
+    if (index >= arrayLength(&particles)) {
+        return;
+    }
+    var particle = particles[index];
+    let dt = sim.emitter.w;
+    let lifetime = sim.jitter_lifetime.w;
+
+    if (particle.velocity_alive.w > 0.5 && particle.position_age.w + dt >= lifetime) {
+        particle.velocity_alive.w = 0.0;
+    }
+    // 死亡的粒子争抢本帧的发射名额
+    if (particle.velocity_alive.w < 0.5 && f32(index) < sim.params.z) {
+        if (atomicAdd(&spawned, 1u) < u32(sim.spawn.z)) {
+            particle = spawn_particle(index);
+        }
+    }
+
+    if (particle.velocity_alive.w < 0.5) {
+        particles[index] = particle;
+        instances[index] = ParticleRaw(vec4f(0.0), vec4f(0.0));
+        return;
+    }
+
+    let previous = particle.position_age.xyz;
+    let damping = max(1.0 - sim.acceleration.w * dt, 0.0);
+    let velocity = (particle.velocity_alive.xyz + sim.acceleration.xyz * dt) * damping;
+    particle.velocity_alive = vec4f(velocity, 1.0);
+    particle.position_age = vec4f(previous + velocity * dt, particle.position_age.w + dt);
+    if (sim.collision.x > 0.5) {
+        collide(&particle, previous);
+    }
+    particles[index] = particle;
+
+    let t = clamp(particle.position_age.w / lifetime, 0.0, 1.0);
+    let size = mix(sim.spawn.x, sim.spawn.y, t);
+    instances[index] = ParticleRaw(
+        vec4f(particle.position_age.xyz, size),
+        mix(sim.start_color, sim.end_color, t),
+    );
+}
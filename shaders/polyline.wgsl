@@ -0,0 +1,144 @@
+#include "wgpu_dance/camera.wgsl"
+
+struct PolylineUniform {
+    // xy: 视口大小（像素）, z: 投影矩阵的垂直缩放，透视投影时为 1 / tan(fovy / 2)
+    viewport: vec4f,
+}
+
+@group(0) @binding(0)
+var<uniform> camera: CameraUniform;
+@group(1) @binding(0)
+var<uniform> polyline: PolylineUniform;
+
+struct SegmentInput {
+    // w 为 1 时表示存在前一个点
+    @location(0) prev: vec4f,
+    @location(1) start: vec4f,
+    @location(2) end: vec4f,
+    // w 为 1 时表示存在后一个点
+    @location(3) next: vec4f,
+    @location(4) color: vec4f,
+    // x: 宽度, y: 是否为世界单位, z: 连接方式（0 斜接, 1 圆角）, w: 斜接长度上限
+    @location(5) params: vec4f,
+}
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4f,
+    @location(0) color: vec4f,
+    // x: 沿线段方向到起点的像素距离, y: 垂直方向的像素距离
+    @location(1) @interpolate(linear) local: vec2f,
+    // x: 线段的像素长度, y: 半宽
+    @location(2) @interpolate(linear) extent: vec2f,
+    @location(3) @interpolate(flat) round_join: u32,
+}
+
+// 边缘留出的抗锯齿像素
+const AA_MARGIN: f32 = 1.0;
+
+fn to_screen(clip: vec4f) -> vec2f {
+    return clip.xy / clip.w * 0.5 * polyline.viewport.xy;
+}
+
+fn half_width(params: vec4f, clip: vec4f) -> f32 {
+    if (params.y > 0.5) {
+        return params.x * 0.5 * polyline.viewport.z * 0.5 * polyline.viewport.y / clip.w;
+    }
+    return params.x * 0.5;
+}
+
+fn perpendicular(dir: vec2f) -> vec2f {
+    return vec2f(-dir.y, dir.x);
+}
+
+fn safe_direction(v: vec2f) -> vec2f {
+    let len = length(v);
+    if (len < 1e-4) {
+        return vec2f(1.0, 0.0);
+    }
+    return v / len;
+}
+
+// 在端点处沿两段法线的平分方向偏移，使相邻线段的外边缘相交于一点；
+// 转角过尖超过上限时退回到本段的法线
+fn miter_offset(normal: vec2f, neighbor_normal: vec2f, width: f32, limit: f32) -> vec2f {
+    let miter = safe_direction(normal + neighbor_normal);
+    let cos_half = dot(miter, normal);
+    if (cos_half < 1.0 / limit) {
+        return normal * width;
+    }
+    return miter * width / cos_half;
+}
+
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32, segment: SegmentInput) -> VertexOutput {
+    var out: VertexOutput;
+    out.color = segment.color;
+    out.round_join = u32(segment.params.z > 0.5);
+
+    // 两个三角形组成的四边形：x 选择端点，y 选择一侧
+    let corners = array<vec2f, 6>(
+        vec2f(0.0, -1.0), vec2f(1.0, -1.0), vec2f(1.0, 1.0),
+        vec2f(0.0, -1.0), vec2f(1.0, 1.0), vec2f(0.0, 1.0),
+    );
+    let corner = corners[index];
+
+    var c0 = camera.view_proj * vec4f(segment.start.xyz, 1.0);
+    var c1 = camera.view_proj * vec4f(segment.end.xyz, 1.0);
+    // 裁剪到近平面，避免位于相机后方的端点投影后翻转
+    if (c0.z < 0.0 && c1.z < 0.0) {
+        out.clip_position = vec4f(0.0, 0.0, -1.0, 1.0);
+        return out;
+    }
+    if (c0.z < 0.0) {
+        c0 = mix(c0, c1, c0.z / (c0.z - c1.z));
+    } else if (c1.z < 0.0) {
+        c1 = mix(c1, c0, c1.z / (c1.z - c0.z));
+    }
+
+    let s0 = to_screen(c0);
+    let s1 = to_screen(c1);
+    let dir = safe_direction(s1 - s0);
+    let normal = perpendicular(dir);
+
+    let clip = select(c0, c1, corner.x > 0.5);
+    let hw = half_width(segment.params, clip);
+    let width = hw + AA_MARGIN;
+
+    var offset = normal * corner.y * width;
+    if (out.round_join == 1u) {
+        // 圆角：两端各延长半宽，由片元着色器裁成胶囊形状
+        offset += dir * (corner.x * 2.0 - 1.0) * width;
+    } else if (corner.x < 0.5 && segment.prev.w > 0.5) {
+        let prev = camera.view_proj * vec4f(segment.prev.xyz, 1.0);
+        if (prev.z >= 0.0) {
+            let prev_normal = perpendicular(safe_direction(s0 - to_screen(prev)));
+            offset = miter_offset(normal, prev_normal, width, segment.params.w) * corner.y;
+        }
+    } else if (corner.x > 0.5 && segment.next.w > 0.5) {
+        let next = camera.view_proj * vec4f(segment.next.xyz, 1.0);
+        if (next.z >= 0.0) {
+            let next_normal = perpendicular(safe_direction(to_screen(next) - s1));
+            offset = miter_offset(normal, next_normal, width, segment.params.w) * corner.y;
+        }
+    }
+
+    let screen = select(s0, s1, corner.x > 0.5) + offset;
+    out.clip_position = vec4f(screen / (0.5 * polyline.viewport.xy) * clip.w, clip.z, clip.w);
+    out.local = vec2f(dot(screen - s0, dir), corner.y * width);
+    out.extent = vec2f(length(s1 - s0), hw);
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4f {
+    var dist = abs(in.local.y);
+    if (in.round_join == 1u) {
+        let along = in.local.x - clamp(in.local.x, 0.0, in.extent.x);
+        dist = length(vec2f(along, in.local.y));
+    }
+    let coverage = clamp(in.extent.y - dist + 0.5, 0.0, 1.0);
+    if (coverage <= 0.0) {
+        discard;
+    }
+    return vec4f(in.color.rgb, in.color.a * coverage);
+}
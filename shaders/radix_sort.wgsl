@@ -0,0 +1,114 @@
+// 按 u32 键稳定排序键值对的一轮基数排序，每轮处理 4 位，由 `lbvh::GpuBvh::build` 调度
+
+// 与 `lbvh::RadixSortPass` 的内存布局保持一致
+struct RadixSortPass {
+    // 本轮处理的最低位
+    shift: u32,
+    count: u32,
+    // 每个工作组处理 WORKGROUP_SIZE 个元素
+    group_count: u32,
+    _padding: u32,
+}
+
+const WORKGROUP_SIZE: u32 = 256u;
+const RADIX: u32 = 16u;
+// 超出元素数量的线程使用的位值，不与任何有效位值相同
+const INVALID_DIGIT: u32 = 0xffffffffu;
+
+@group(0) @binding(0)
+var<uniform> pass_params: RadixSortPass;
+@group(0) @binding(1)
+var<storage, read> src_keys: array<u32>;
+@group(0) @binding(2)
+var<storage, read> src_values: array<u32>;
+@group(0) @binding(3)
+var<storage, read_write> dst_keys: array<u32>;
+@group(0) @binding(4)
+var<storage, read_write> dst_values: array<u32>;
+// 按位值优先排列：`histograms[digit * group_count + group]`，扫描后为每组每个位值的起始位置
+@group(0) @binding(5)
+var<storage, read_write> histograms: array<u32>;
+
+var<workgroup> local_counts: array<atomic<u32>, RADIX>;
+var<workgroup> local_digits: array<u32, WORKGROUP_SIZE>;
+var<workgroup> scan: array<u32, WORKGROUP_SIZE>;
+
+fn digit_of(index: u32) -> u32 {
+    if (index >= pass_params.count) {
+        return INVALID_DIGIT;
+    }
+    return (src_keys[index] >> pass_params.shift) & (RADIX - 1u);
+}
+
+@compute @workgroup_size(256)
+fn cs_histogram(
+    @builtin(global_invocation_id) id: vec3u,
+    @builtin(local_invocation_index) local: u32,
+    @builtin(workgroup_id) group: vec3u,
+) {
+    if (local < RADIX) {
+        atomicStore(&local_counts[local], 0u);
+    }
+    workgroupBarrier();
+    let digit = digit_of(id.x);
+    if (digit != INVALID_DIGIT) {
+        atomicAdd(&local_counts[digit], 1u);
+    }
+    workgroupBarrier();
+    if (local < RADIX) {
+        histograms[local * pass_params.group_count + group.x] = atomicLoad(&local_counts[local]);
+    }
+}
+
+// 单个工作组对整个直方图做排他前缀和，每次处理 WORKGROUP_SIZE 个并累加到下一段
+@compute @workgroup_size(256)
+fn cs_scan(@builtin(local_invocation_index) local: u32) {
+    let total = RADIX * pass_params.group_count;
+    var carry = 0u;
+    for (var base = 0u; base < total; base += WORKGROUP_SIZE) {
+        let index = base + local;
+        var value = 0u;
+        if (index < total) {
+            value = histograms[index];
+        }
+        scan[local] = value;
+        workgroupBarrier();
+        // Hillis-Steele 包含扫描
+        for (var offset = 1u; offset < WORKGROUP_SIZE; offset <<= 1u) {
+            var sum = scan[local];
+            if (local >= offset) {
+                sum += scan[local - offset];
+            }
+            workgroupBarrier();
+            scan[local] = sum;
+            workgroupBarrier();
+        }
+        if (index < total) {
+            histograms[index] = carry + scan[local] - value;
+        }
+        carry += scan[WORKGROUP_SIZE - 1u];
+        workgroupBarrier();
+    }
+}
+
+@compute @workgroup_size(256)
+fn cs_scatter(
+    @builtin(global_invocation_id) id: vec3u,
+    @builtin(local_invocation_index) local: u32,
+    @builtin(workgroup_id) group: vec3u,
+) {
+    let digit = digit_of(id.x);
+    local_digits[local] = digit;
+    workgroupBarrier();
+    if (digit == INVALID_DIGIT) {
+        return;
+    }
+    // 组内排在前面的相同位值的个数，保证排序稳定
+    var rank = 0u;
+    for (var i = 0u; i < local; i++) {
+        rank += select(0u, 1u, local_digits[i] == digit);
+    }
+    let destination = histograms[digit * pass_params.group_count + group.x] + rank;
+    dst_keys[destination] = src_keys[id.x];
+    dst_values[destination] = src_values[id.x];
+}
@@ -0,0 +1,59 @@
+struct CullUniform {
+    planes: array<vec4f, 6>,
+    // xyz: 相机位置, w: 开始淡出的距离
+    eye: vec4f,
+    // x: 完全消失的距离, y: 实例数量
+    params: vec4f,
+}
+
+struct SourceInstance {
+    model: mat4x4f,
+    bounds: vec4f,
+}
+
+struct CulledInstance {
+    model: mat4x4f,
+    fade: vec4f,
+}
+
+struct DrawIndexedIndirect {
+    index_count: u32,
+    instance_count: atomic<u32>,
+    first_index: u32,
+    base_vertex: i32,
+    first_instance: u32,
+}
+
+@group(0) @binding(0)
+var<uniform> cull: CullUniform;
+@group(0) @binding(1)
+var<storage, read> sources: array<SourceInstance>;
+@group(0) @binding(2)
+var<storage, read_write> culled: array<CulledInstance>;
+@group(0) @binding(3)
+var<storage, read_write> draw_args: DrawIndexedIndirect;
+
+@compute @workgroup_size(64)
+fn cs_main(@builtin(global_invocation_id) id: vec3u) {
+    if (id.x >= u32(cull.params.y)) {
+        return;
+    }
+    let source = sources[id.x];
+    let center = source.bounds.xyz;
+    let radius = source.bounds.w;
+
+    let distance = length(center - cull.eye.xyz);
+    if (distance - radius > cull.params.x) {
+        return;
+    }
+    for (var i = 0u; i < 6u; i++) {
+        let plane = cull.planes[i];
+        if (dot(plane.xyz, center) + plane.w < -radius) {
+            return;
+        }
+    }
+
+    let fade = 1.0 - smoothstep(cull.eye.w, cull.params.x, distance);
+    let slot = atomicAdd(&draw_args.instance_count, 1u);
+    culled[slot] = CulledInstance(source.model, vec4f(fade, 0.0, 0.0, 0.0));
+}
@@ -0,0 +1,89 @@
+#include "wgpu_dance/camera.wgsl"
+#include "wgpu_dance/lighting.wgsl"
+
+// 与 `scene::MAX_DIRECTIONAL_LIGHTS`、`scene::MAX_POINT_LIGHTS` 一致
+const MAX_DIRECTIONAL_LIGHTS: u32 = 4u;
+const MAX_POINT_LIGHTS: u32 = 8u;
+
+// 与 `scene::raster::SceneUniform` 的内存布局保持一致
+struct SceneUniform {
+    // 透视投影时 xyz 为相机位置、w 为 1；正交投影时 xyz 为指向相机的方向、w 为 0
+    view: vec4f,
+    // rgb: 环境光
+    ambient: vec4f,
+    // x: 平行光数量，y: 点光源数量
+    counts: vec4u,
+    directional: array<DirectionalLight, MAX_DIRECTIONAL_LIGHTS>,
+    point: array<PointLight, MAX_POINT_LIGHTS>,
+}
+
+// 与 `scene::raster::SceneMaterialUniform` 的内存布局保持一致
+struct SceneMaterial {
+    // rgb: 漫反射颜色，a: 高光强度
+    base_color: vec4f,
+    // x: 高光指数
+    params: vec4f,
+}
+
+@group(0) @binding(0)
+var<uniform> camera: CameraUniform;
+
+@group(1) @binding(0)
+var<uniform> scene: SceneUniform;
+
+@group(2) @binding(0)
+var<uniform> material: SceneMaterial;
+
+struct VertexInput {
+    @location(0) position: vec3f,
+    @location(1) normal: vec3f,
+    // 环境光遮蔽，只作用于环境光
+    @location(2) ao: f32,
+}
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4f,
+    @location(0) world_position: vec3f,
+    @location(1) normal: vec3f,
+    @location(2) ao: f32,
+}
+
+// 顶点已在 CPU 端变换到世界空间，与光线追踪使用的几何完全相同
+@vertex
+fn vs_main(in: VertexInput) -> VertexOutput {
+    var out: VertexOutput;
+    out.clip_position = camera.view_proj * vec4f(in.position, 1.0);
+    out.world_position = in.position;
+    out.normal = in.normal;
+    out.ao = in.ao;
+    return out;
+}
+
+// 单个光源的贡献，与 `Scene::shade` 一致
+fn shade_light(n: vec3f, v: vec3f, l: vec3f, radiance: vec3f) -> vec3f {
+    if (dot(n, l) <= 0.0) {
+        return vec3f(0.0);
+    }
+    let specular = material.base_color.a * blinn_phong(n, l, v, material.params.x);
+    return radiance * (material.base_color.rgb * lambert(n, l) + vec3f(specular));
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4f {
+    let n = normalize(in.normal);
+    let v = select(scene.view.xyz, normalize(scene.view.xyz - in.world_position), scene.view.w > 0.5);
+    var color = material.base_color.rgb * scene.ambient.rgb * in.ao;
+    for (var i = 0u; i < scene.counts.x; i++) {
+        let light = scene.directional[i];
+        color += shade_light(n, v, light.direction.xyz, light.color.rgb * light.direction.w);
+    }
+    for (var i = 0u; i < scene.counts.y; i++) {
+        let light = scene.point[i];
+        let to_light = light.position.xyz - in.world_position;
+        let distance = length(to_light);
+        let attenuation = light_attenuation(light.attenuation.x, distance, light.position.w);
+        let radiance = light.color.rgb * light.color.a * attenuation;
+        color += shade_light(n, v, to_light / max(distance, 0.0001), radiance);
+    }
+    return vec4f(color, 1.0);
+}
@@ -0,0 +1,25 @@
+// 追加在 ShaderToyApp 加载的 WGSL 之后，用户只需定义
+// `fn main_image(frag_coord: vec2f) -> vec4f`
+#include "wgpu_dance/fullscreen.wgsl"
+
+struct ShaderToyUniform {
+    // xy: 画面像素大小, zw: 其倒数
+    resolution: vec4f,
+    // xy: 光标位置（像素，左上角为原点）, zw: 最近一次左键按下的位置，未按住时为负
+    mouse: vec4f,
+    // x: 秒, y: 本帧时长, z: 帧序号
+    time: vec4f,
+}
+
+@group(0) @binding(0)
+var<uniform> toy: ShaderToyUniform;
+
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32) -> FullscreenOutput {
+    return fullscreen_vertex(index);
+}
+
+@fragment
+fn fs_main(in: FullscreenOutput) -> @location(0) vec4f {
+    return main_image(in.clip_position.xy);
+}
@@ -0,0 +1,121 @@
+// PCF 的半径（纹素），是管线常量，由 shader::shadow_pcf_constants 按画质在创建管线时填入
+override shadow_filter_radius: f32 = 1.0;
+
+// 把光源裁剪空间坐标转换为阴影贴图的 uv 与深度
+fn shadow_coords(light_clip: vec4f) -> vec3f {
+    let ndc = light_clip.xyz / light_clip.w;
+    return vec3f(ndc.x * 0.5 + 0.5, -ndc.y * 0.5 + 0.5, ndc.z);
+}
+
+// 比较深度时的偏移，`params` 为 `DirectionalLight.shadow_bias`，表面越倾斜偏移越大
+fn shadow_bias(params: vec4f, normal: vec3f, light_dir: vec3f) -> f32 {
+    let cos_theta = clamp(dot(normal, light_dir), 0.01, 1.0);
+    let tan_theta = sqrt(1.0 - cos_theta * cos_theta) / cos_theta;
+    return params.x + params.y * min(tan_theta, 10.0);
+}
+
+// 在变换到光源空间之前沿法线外推接收点，掠射角时外推得更多
+fn shadow_normal_offset(params: vec4f, world_position: vec3f, normal: vec3f, light_dir: vec3f) -> vec3f {
+    let n_dot_l = clamp(dot(normal, light_dir), 0.0, 1.0);
+    return world_position + normal * params.z * (1.0 - n_dot_l);
+}
+
+// 半径为 `radius` 纹素的方形 PCF，返回 0（完全处于阴影）到 1（完全受光），半径为 0 时只比较一次
+fn shadow_pcf_radius(
+    shadow_map: texture_depth_2d,
+    shadow_sampler: sampler_comparison,
+    light_clip: vec4f,
+    bias: f32,
+    radius: f32,
+) -> f32 {
+    let coords = shadow_coords(light_clip);
+    if (coords.z > 1.0) {
+        return 1.0;
+    }
+    let texel = 1.0 / vec2f(textureDimensions(shadow_map));
+    let taps = i32(ceil(radius));
+    let spacing = select(radius / f32(taps), 0.0, taps == 0);
+    var visibility = 0.0;
+    var count = 0.0;
+    for (var y = -taps; y <= taps; y++) {
+        for (var x = -taps; x <= taps; x++) {
+            let offset = vec2f(f32(x), f32(y)) * spacing * texel;
+            visibility += textureSampleCompareLevel(
+                shadow_map,
+                shadow_sampler,
+                coords.xy + offset,
+                coords.z - bias,
+            );
+            count += 1.0;
+        }
+    }
+    return visibility / count;
+}
+
+// 半径为 `shadow_filter_radius` 的 PCF，默认 3x3，返回 0（完全处于阴影）到 1（完全受光）
+fn shadow_pcf(
+    shadow_map: texture_depth_2d,
+    shadow_sampler: sampler_comparison,
+    light_clip: vec4f,
+    bias: f32,
+) -> f32 {
+    return shadow_pcf_radius(shadow_map, shadow_sampler, light_clip, bias, shadow_filter_radius);
+}
+
+// 与下一级联的混合权重，`params` 为 `DirectionalLight.shadow_cascade`：
+// 视图深度在级联范围 [near, far) 的最后 `params.x` 比例内从 0 过渡到 1
+fn shadow_cascade_blend(params: vec4f, depth: f32, near: f32, far: f32) -> f32 {
+    let width = (far - near) * params.x;
+    if (width <= 0.0) {
+        return select(0.0, 1.0, depth >= far);
+    }
+    return smoothstep(far - width, far, depth);
+}
+
+// 把单张阴影贴图的 uv 映射到阴影图集中的分块，`uv_rect` 为 `ShadowTile::uv_rect`
+fn shadow_atlas_uv(uv_rect: vec4f, uv: vec2f) -> vec2f {
+    return uv_rect.xy + uv * uv_rect.zw;
+}
+
+// 阴影图集中某个分块的 PCF，采样点限制在分块内，避免读到相邻光源的深度
+fn shadow_atlas_pcf(
+    shadow_atlas: texture_depth_2d,
+    shadow_sampler: sampler_comparison,
+    light_clip: vec4f,
+    uv_rect: vec4f,
+    bias: f32,
+    radius: f32,
+) -> f32 {
+    let coords = shadow_coords(light_clip);
+    if (coords.z > 1.0) {
+        return 1.0;
+    }
+    let texel = 1.0 / vec2f(textureDimensions(shadow_atlas));
+    let lo = uv_rect.xy + texel * 0.5;
+    let hi = uv_rect.xy + uv_rect.zw - texel * 0.5;
+    let center = shadow_atlas_uv(uv_rect, coords.xy);
+    let taps = i32(ceil(radius));
+    let spacing = select(radius / f32(taps), 0.0, taps == 0);
+    var visibility = 0.0;
+    var count = 0.0;
+    for (var y = -taps; y <= taps; y++) {
+        for (var x = -taps; x <= taps; x++) {
+            let uv = clamp(center + vec2f(f32(x), f32(y)) * spacing * texel, lo, hi);
+            visibility += textureSampleCompareLevel(shadow_atlas, shadow_sampler, uv, coords.z - bias);
+            count += 1.0;
+        }
+    }
+    return visibility / count;
+}
+
+// 点光源立方体阴影中 `light_to_point` 方向所在的面，顺序与 `PointLight::shadow_view_projs` 一致
+fn point_shadow_face(light_to_point: vec3f) -> u32 {
+    let a = abs(light_to_point);
+    if (a.x >= a.y && a.x >= a.z) {
+        return select(1u, 0u, light_to_point.x > 0.0);
+    }
+    if (a.y >= a.z) {
+        return select(3u, 2u, light_to_point.y > 0.0);
+    }
+    return select(5u, 4u, light_to_point.z > 0.0);
+}
@@ -0,0 +1,14 @@
+// 线性混合蒙皮：按权重混合 4 个关节矩阵
+fn skin_blend(
+    joint_0: mat4x4f,
+    joint_1: mat4x4f,
+    joint_2: mat4x4f,
+    joint_3: mat4x4f,
+    weights: vec4f,
+) -> mat4x4f {
+    return joint_0 * weights.x + joint_1 * weights.y + joint_2 * weights.z + joint_3 * weights.w;
+}
+
+fn skin_normal(skin: mat4x4f, normal: vec3f) -> vec3f {
+    return normalize((skin * vec4f(normal, 0.0)).xyz);
+}
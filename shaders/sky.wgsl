@@ -0,0 +1,70 @@
+// Preetham 解析天空模型
+// sun_direction.w: 大气浑浊度 turbidity
+struct SkyUniform {
+    inv_view_proj: mat4x4f,
+    sun_direction: vec4f,
+    // x: 曝光, y: 太阳圆盘强度
+    params: vec4f,
+};
+
+fn perez(theta: f32, gamma: f32, a: f32, b: f32, c: f32, d: f32, e: f32) -> f32 {
+    let cos_gamma = cos(gamma);
+    return (1.0 + a * exp(b / max(cos(theta), 0.01))) *
+        (1.0 + c * exp(d * gamma) + e * cos_gamma * cos_gamma);
+}
+
+fn perez_yxy(theta: f32, gamma: f32, t: f32) -> vec3f {
+    let lum = perez(theta, gamma,
+        0.1787 * t - 1.4630, -0.3554 * t + 0.4275, -0.0227 * t + 5.3251,
+        0.1206 * t - 2.5771, -0.0670 * t + 0.3703);
+    let x = perez(theta, gamma,
+        -0.0193 * t - 0.2592, -0.0665 * t + 0.0008, -0.0004 * t + 0.2125,
+        -0.0641 * t - 0.8989, -0.0033 * t + 0.0452);
+    let y = perez(theta, gamma,
+        -0.0167 * t - 0.2608, -0.0950 * t + 0.0092, -0.0079 * t + 0.2102,
+        -0.0441 * t - 1.6537, -0.0109 * t + 0.0529);
+    return vec3f(lum, x, y);
+}
+
+fn zenith_yxy(theta_s: f32, t: f32) -> vec3f {
+    let chi = (4.0 / 9.0 - t / 120.0) * (3.14159265 - 2.0 * theta_s);
+    let lum = (4.0453 * t - 4.9710) * tan(chi) - 0.2155 * t + 2.4192;
+
+    let th = vec4f(theta_s * theta_s * theta_s, theta_s * theta_s, theta_s, 1.0);
+    let t2 = t * t;
+    let x = t2 * dot(vec4f(0.00166, -0.00375, 0.00209, 0.0), th) +
+        t * dot(vec4f(-0.02903, 0.06377, -0.03202, 0.00394), th) +
+        dot(vec4f(0.11693, -0.21196, 0.06052, 0.25886), th);
+    let y = t2 * dot(vec4f(0.00275, -0.00610, 0.00317, 0.0), th) +
+        t * dot(vec4f(-0.04214, 0.08970, -0.04153, 0.00516), th) +
+        dot(vec4f(0.15346, -0.26756, 0.06670, 0.26688), th);
+    return vec3f(lum, x, y);
+}
+
+fn yxy_to_linear_srgb(yxy: vec3f) -> vec3f {
+    let big_y = yxy.x;
+    let x = yxy.y;
+    let y = max(yxy.z, 0.0001);
+    let big_x = x * big_y / y;
+    let big_z = (1.0 - x - y) * big_y / y;
+    return vec3f(
+        3.2406 * big_x - 1.5372 * big_y - 0.4986 * big_z,
+        -0.9689 * big_x + 1.8758 * big_y + 0.0415 * big_z,
+        0.0557 * big_x - 0.2040 * big_y + 1.0570 * big_z,
+    );
+}
+
+// 某个观察方向上的天空辐亮度（线性 sRGB，未做色调映射）
+fn sky_radiance(view_dir: vec3f, sun_dir: vec3f, turbidity: f32) -> vec3f {
+    let up = vec3f(0.0, 1.0, 0.0);
+    let theta = acos(clamp(dot(view_dir, up), 0.0, 1.0));
+    let theta_s = acos(clamp(dot(sun_dir, up), 0.0, 1.0));
+    let gamma = acos(clamp(dot(view_dir, sun_dir), -1.0, 1.0));
+
+    let zenith = zenith_yxy(theta_s, turbidity);
+    let ratio = perez_yxy(theta, gamma, turbidity) / perez_yxy(0.0, theta_s, turbidity);
+    // 太阳落到地平线以下后逐渐变暗
+    let night = smoothstep(-0.2, 0.05, sun_dir.y);
+    let yxy = vec3f(zenith.x * ratio.x * night, zenith.y * ratio.y, zenith.z * ratio.z);
+    return max(yxy_to_linear_srgb(yxy), vec3f(0.0));
+}
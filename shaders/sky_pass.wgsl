@@ -0,0 +1,41 @@
+#include "wgpu_dance/sky.wgsl"
+#include "wgpu_dance/tonemapping.wgsl"
+
+@group(0) @binding(0)
+var<uniform> sky: SkyUniform;
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4f,
+    @location(0) ndc: vec2f,
+}
+
+// 覆盖全屏的单个三角形
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32) -> VertexOutput {
+    let uv = vec2f(f32((index << 1u) & 2u), f32(index & 2u));
+    let ndc = uv * 2.0 - 1.0;
+    var out: VertexOutput;
+    out.clip_position = vec4f(ndc, 0.0, 1.0);
+    out.ndc = ndc;
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4f {
+    let near = sky.inv_view_proj * vec4f(in.ndc, 0.0, 1.0);
+    let far = sky.inv_view_proj * vec4f(in.ndc, 1.0, 1.0);
+    let view_dir = normalize(far.xyz / far.w - near.xyz / near.w);
+    let sun_dir = normalize(sky.sun_direction.xyz);
+
+    var radiance = sky_radiance(view_dir, sun_dir, sky.sun_direction.w);
+
+    // 太阳圆盘
+    let sun_disk = smoothstep(0.9995, 0.9999, dot(view_dir, sun_dir));
+    radiance += vec3f(sun_disk * sky.params.y * smoothstep(-0.02, 0.02, sun_dir.y));
+
+    // 地平线以下使用较暗的地面颜色
+    let ground = vec3f(0.05, 0.05, 0.06) * radiance.g;
+    radiance = mix(ground, radiance, smoothstep(-0.02, 0.0, view_dir.y));
+
+    return vec4f(tonemap_aces(radiance * sky.params.x), 1.0);
+}
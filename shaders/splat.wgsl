@@ -0,0 +1,133 @@
+struct Splat {
+    // xyz: 中心位置, w: 不透明度
+    position_opacity: vec4f,
+    color: vec4f,
+    // 协方差上三角: xx, xy, xz, yy
+    covariance_a: vec4f,
+    // yz, zz
+    covariance_b: vec4f,
+}
+
+// 与 `splat::SplatUniform` 的内存布局保持一致
+struct SplatUniform {
+    view: mat4x4f,
+    proj: mat4x4f,
+    // xy: 视口像素大小, zw: 以像素为单位的焦距
+    viewport: vec4f,
+    // x: znear, y: 高斯个数, z: 缩放系数
+    params: vec4f,
+}
+
+@group(0) @binding(0)
+var<uniform> uniforms: SplatUniform;
+@group(0) @binding(1)
+var<storage, read> splats: array<Splat>;
+@group(0) @binding(2)
+var<storage, read> sorted: array<u32>;
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4f,
+    @location(0) color: vec4f,
+    // 以标准差为单位的面片坐标
+    @location(1) offset: vec2f,
+}
+
+// 面片覆盖到 3 倍标准差
+const EXTENT: f32 = 3.0;
+
+fn culled() -> VertexOutput {
+    var out: VertexOutput;
+    out.clip_position = vec4f(0.0, 0.0, 2.0, 1.0);
+    return out;
+}
+
+@vertex
+fn vs_main(
+    @builtin(vertex_index) vertex: u32,
+    @builtin(instance_index) instance: u32,
+) -> VertexOutput {
+    let index = sorted[instance];
+    if (index >= u32(uniforms.params.y)) {
+        return culled();
+    }
+    let splat = splats[index];
+    let view_position = (uniforms.view * vec4f(splat.position_opacity.xyz, 1.0)).xyz;
+    let depth = -view_position.z;
+    if (depth <= uniforms.params.x) {
+        return culled();
+    }
+    let clip = uniforms.proj * vec4f(view_position, 1.0);
+    // 略大于视锥的范围之外的高斯不会覆盖屏幕
+    if (any(abs(clip.xy) > vec2f(1.3 * clip.w))) {
+        return culled();
+    }
+
+    let s = uniforms.params.z * uniforms.params.z;
+    let a = splat.covariance_a;
+    let b = splat.covariance_b;
+    let covariance = mat3x3f(
+        vec3f(a.x, a.y, a.z),
+        vec3f(a.y, a.w, b.x),
+        vec3f(a.z, b.x, b.y),
+    ) * s;
+
+    // EWA：视图变换后在高斯中心处对透视投影做一阶近似，深度取正方向
+    let rotation = mat3x3f(
+        uniforms.view[0].xyz * vec3f(1.0, 1.0, -1.0),
+        uniforms.view[1].xyz * vec3f(1.0, 1.0, -1.0),
+        uniforms.view[2].xyz * vec3f(1.0, 1.0, -1.0),
+    );
+    let focal = uniforms.viewport.zw;
+    let jacobian = mat3x3f(
+        vec3f(focal.x / depth, 0.0, 0.0),
+        vec3f(0.0, focal.y / depth, 0.0),
+        vec3f(
+            -focal.x * view_position.x / (depth * depth),
+            -focal.y * view_position.y / (depth * depth),
+            0.0,
+        ),
+    );
+    let t = jacobian * rotation;
+    let projected = t * covariance * transpose(t);
+    // 低通滤波，保证每个高斯至少覆盖约一个像素
+    let xx = projected[0][0] + 0.3;
+    let xy = projected[0][1];
+    let yy = projected[1][1] + 0.3;
+
+    // 二维协方差的特征分解给出椭圆的两个主轴
+    let mid = 0.5 * (xx + yy);
+    let radius = length(vec2f(0.5 * (xx - yy), xy));
+    let lambda1 = mid + radius;
+    let lambda2 = max(mid - radius, 0.1);
+    var major = vec2f(xy, lambda1 - xx);
+    if (length(major) < 1e-6) {
+        major = vec2f(1.0, 0.0);
+    }
+    major = normalize(major);
+    let minor = vec2f(-major.y, major.x);
+    let axis1 = major * min(sqrt(lambda1), 1024.0);
+    let axis2 = minor * min(sqrt(lambda2), 1024.0);
+
+    let corners = array<vec2f, 6>(
+        vec2f(-1.0, -1.0), vec2f(1.0, -1.0), vec2f(1.0, 1.0),
+        vec2f(-1.0, -1.0), vec2f(1.0, 1.0), vec2f(-1.0, 1.0),
+    );
+    let corner = corners[vertex] * EXTENT;
+    let pixels = corner.x * axis1 + corner.y * axis2;
+
+    var out: VertexOutput;
+    out.clip_position = clip + vec4f(pixels * 2.0 / uniforms.viewport.xy * clip.w, 0.0, 0.0);
+    out.color = vec4f(splat.color.rgb, splat.position_opacity.w);
+    out.offset = corner;
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4f {
+    let alpha = min(in.color.a * exp(-0.5 * dot(in.offset, in.offset)), 0.99);
+    if (alpha < 1.0 / 255.0) {
+        discard;
+    }
+    // 预乘 alpha，配合管线的 under 混合从前往后累积
+    return vec4f(in.color.rgb * alpha, alpha);
+}
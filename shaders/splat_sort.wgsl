@@ -0,0 +1,82 @@
+// 与 `splat.wgsl` 中的定义保持一致
+struct Splat {
+    // xyz: 中心位置, w: 不透明度
+    position_opacity: vec4f,
+    color: vec4f,
+    // 协方差上三角: xx, xy, xz, yy
+    covariance_a: vec4f,
+    // yz, zz
+    covariance_b: vec4f,
+}
+
+struct SplatUniform {
+    view: mat4x4f,
+    proj: mat4x4f,
+    // xy: 视口像素大小, zw: 以像素为单位的焦距
+    viewport: vec4f,
+    // x: znear, y: 高斯个数, z: 缩放系数
+    params: vec4f,
+}
+
+struct SortStage {
+    // 当前双调序列的长度
+    block: u32,
+    // 比较交换的两个元素之间的距离
+    distance: u32,
+    // 补齐到 2 的幂的排序长度
+    count: u32,
+}
+
+@group(0) @binding(0)
+var<uniform> uniforms: SplatUniform;
+@group(0) @binding(1)
+var<storage, read> splats: array<Splat>;
+@group(0) @binding(2)
+var<storage, read_write> keys: array<u32>;
+@group(0) @binding(3)
+var<storage, read_write> indices: array<u32>;
+
+@group(1) @binding(0)
+var<uniform> stage: SortStage;
+
+// 近平面之后的高斯与补齐的元素排在最后，绘制时跳过
+const INVALID_KEY: u32 = 0xffffffffu;
+
+@compute @workgroup_size(256)
+fn cs_keys(@builtin(global_invocation_id) id: vec3u) {
+    let index = id.x;
+    if (index >= arrayLength(&keys)) {
+        return;
+    }
+    var key = INVALID_KEY;
+    if (index < u32(uniforms.params.y)) {
+        let position = splats[index].position_opacity.xyz;
+        let depth = -(uniforms.view * vec4f(position, 1.0)).z;
+        // 正浮点数的位模式与数值同序，可以直接作为无符号键比较
+        if (depth > uniforms.params.x) {
+            key = bitcast<u32>(depth);
+        }
+    }
+    keys[index] = key;
+    indices[index] = index;
+}
+
+// 双调排序的一轮比较交换，结果按键升序，即从近到远
+@compute @workgroup_size(256)
+fn cs_sort(@builtin(global_invocation_id) id: vec3u) {
+    let index = id.x;
+    let partner = index ^ stage.distance;
+    if (index >= stage.count || partner <= index) {
+        return;
+    }
+    let ascending = (index & stage.block) == 0u;
+    let a = keys[index];
+    let b = keys[partner];
+    if ((a > b) == ascending) {
+        keys[index] = b;
+        keys[partner] = a;
+        let i = indices[index];
+        indices[index] = indices[partner];
+        indices[partner] = i;
+    }
+}
@@ -0,0 +1,83 @@
+struct SpriteUniform {
+    view_proj: mat4x4f,
+}
+
+struct SpriteInput {
+    // xy: 中心, zw: 大小
+    @location(0) rect: vec4f,
+    // xy: uv 左上角, zw: uv 右下角
+    @location(1) uv: vec4f,
+    @location(2) color: vec4f,
+    // 旋转角的余弦与正弦
+    @location(3) rotation: vec2f,
+    // x: 形状（0 纹理，1 圆角矩形）, y: 圆角半径, z: 描边宽度
+    @location(4) shape: vec4f,
+}
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4f,
+    @location(0) uv: vec2f,
+    @location(1) color: vec4f,
+    // 相对中心、未旋转的坐标
+    @location(2) local: vec2f,
+    @location(3) @interpolate(flat) half_size: vec2f,
+    @location(4) @interpolate(flat) shape: vec4f,
+}
+
+@group(0) @binding(0)
+var<uniform> sprite: SpriteUniform;
+
+@group(1) @binding(0)
+var t_sprite: texture_2d<f32>;
+@group(1) @binding(1)
+var s_sprite: sampler;
+
+// 两个三角形组成的四边形，(0, 0) 为左上角
+const CORNERS = array<vec2f, 6>(
+    vec2f(0.0, 0.0),
+    vec2f(1.0, 0.0),
+    vec2f(1.0, 1.0),
+    vec2f(0.0, 0.0),
+    vec2f(1.0, 1.0),
+    vec2f(0.0, 1.0),
+);
+
+@vertex
+fn vs_main(@builtin(vertex_index) vertex_index: u32, in: SpriteInput) -> VertexOutput {
+    let corner = CORNERS[vertex_index];
+    let local = (corner - 0.5) * in.rect.zw;
+    let c = in.rotation.x;
+    let s = in.rotation.y;
+    let world = in.rect.xy + vec2f(local.x * c - local.y * s, local.x * s + local.y * c);
+
+    var out: VertexOutput;
+    out.clip_position = sprite.view_proj * vec4f(world, 0.0, 1.0);
+    out.uv = mix(in.uv.xy, in.uv.zw, corner);
+    out.color = in.color;
+    out.local = local;
+    out.half_size = in.rect.zw * 0.5;
+    out.shape = in.shape;
+    return out;
+}
+
+// 圆角矩形的有向距离，内部为负
+fn rounded_rect_sdf(p: vec2f, half_size: vec2f, radius: f32) -> f32 {
+    let r = min(radius, min(half_size.x, half_size.y));
+    let q = abs(p) - half_size + r;
+    return length(max(q, vec2f(0.0))) + min(max(q.x, q.y), 0.0) - r;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4f {
+    // textureSample 与 fwidth 需要在一致控制流中调用，两种结果都先算出再按形状选择
+    let texel = textureSample(t_sprite, s_sprite, in.uv) * in.color;
+
+    let fill = rounded_rect_sdf(in.local, in.half_size, in.shape.y);
+    let outline = in.shape.z;
+    let d = select(fill, abs(fill + outline * 0.5) - outline * 0.5, outline > 0.0);
+    // 以一个像素宽度做边缘抗锯齿
+    let coverage = clamp(0.5 - d / max(fwidth(d), 1e-4), 0.0, 1.0);
+    let shape = vec4f(in.color.rgb, in.color.a * coverage);
+
+    return select(shape, texel, in.shape.x < 0.5);
+}
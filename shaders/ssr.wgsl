@@ -0,0 +1,95 @@
+#include "wgpu_dance/fullscreen.wgsl"
+
+struct SsrUniform {
+    view_proj: mat4x4f,
+    inv_view_proj: mat4x4f,
+    // xyz: 相机位置, w: 最大追踪距离
+    eye: vec4f,
+    // x: 步数, y: 厚度, z: 粗糙度上限, w: 强度
+    params: vec4f,
+    // x: znear, y: zfar
+    depth_params: vec4f,
+}
+
+@group(0) @binding(0)
+var<uniform> ssr: SsrUniform;
+@group(0) @binding(1)
+var t_color: texture_2d<f32>;
+@group(0) @binding(2)
+var t_depth: texture_depth_2d;
+@group(0) @binding(3)
+var t_normal_roughness: texture_2d<f32>;
+@group(0) @binding(4)
+var s_linear: sampler;
+@group(0) @binding(5)
+var t_probe: texture_cube<f32>;
+
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32) -> FullscreenOutput {
+    return fullscreen_vertex(index);
+}
+
+fn view_depth(depth: f32) -> f32 {
+    let znear = ssr.depth_params.x;
+    let zfar = ssr.depth_params.y;
+    return znear * zfar / (zfar - depth * (zfar - znear));
+}
+
+fn world_position(uv: vec2f, depth: f32) -> vec3f {
+    let ndc = vec2f(uv.x * 2.0 - 1.0, 1.0 - uv.y * 2.0);
+    let p = ssr.inv_view_proj * vec4f(ndc, depth, 1.0);
+    return p.xyz / p.w;
+}
+
+@fragment
+fn fs_main(in: FullscreenOutput) -> @location(0) vec4f {
+    let pixel = vec2i(in.clip_position.xy);
+    let color = textureLoad(t_color, pixel, 0);
+    let depth = textureLoad(t_depth, pixel, 0);
+    let normal_roughness = textureLoad(t_normal_roughness, pixel, 0);
+    if (depth >= 1.0 || length(normal_roughness.xyz) < 0.5) {
+        return color;
+    }
+
+    let normal = normalize(normal_roughness.xyz);
+    let roughness = clamp(normal_roughness.w, 0.0, 1.0);
+    let position = world_position(in.uv, depth);
+    let view_dir = normalize(position - ssr.eye.xyz);
+    let ray = reflect(view_dir, normal);
+
+    // 粗糙表面只使用环境探针，越接近上限屏幕空间反射的权重越低
+    var reflection = textureSampleLevel(t_probe, s_linear, ray, 0.0).rgb;
+    let cutoff = ssr.params.z;
+    let ssr_weight = 1.0 - smoothstep(cutoff * 0.5, cutoff, roughness);
+    if (ssr_weight > 0.0) {
+        let steps = u32(ssr.params.x);
+        let step_length = ssr.eye.w / f32(steps);
+        let dims = vec2f(textureDimensions(t_depth));
+        for (var i = 1u; i <= steps; i++) {
+            let p = position + ray * step_length * f32(i);
+            let clip = ssr.view_proj * vec4f(p, 1.0);
+            if (clip.w <= 0.0) {
+                break;
+            }
+            let ndc = clip.xyz / clip.w;
+            let uv = vec2f(ndc.x * 0.5 + 0.5, 0.5 - ndc.y * 0.5);
+            if (any(uv < vec2f(0.0)) || any(uv > vec2f(1.0))) {
+                break;
+            }
+            let scene_depth = textureLoad(t_depth, vec2i(uv * dims), 0);
+            let diff = clip.w - view_depth(scene_depth);
+            if (diff > 0.0 && diff < ssr.params.y) {
+                // 靠近屏幕边缘时淡出到探针，避免反射被生硬截断
+                let edge = min(min(uv.x, 1.0 - uv.x), min(uv.y, 1.0 - uv.y));
+                let fade = smoothstep(0.0, 0.1, edge) * ssr_weight;
+                let hit = textureSampleLevel(t_color, s_linear, uv, 0.0).rgb;
+                reflection = mix(reflection, hit, fade);
+                break;
+            }
+        }
+    }
+
+    let fresnel = 0.04 + 0.96 * pow(1.0 - max(dot(normal, -view_dir), 0.0), 5.0);
+    let weight = (1.0 - roughness) * (1.0 - roughness) * mix(0.25, 1.0, fresnel) * ssr.params.w;
+    return vec4f(mix(color.rgb, reflection, clamp(weight, 0.0, 1.0)), color.a);
+}
@@ -0,0 +1,73 @@
+#include "wgpu_dance/fullscreen.wgsl"
+#include "wgpu_dance/motion.wgsl"
+
+// 场景提供速度纹理时，几何体像素使用逐物体速度
+override HAS_VELOCITY: bool = false;
+
+struct TaaUniform {
+    // 当前帧未抖动的 view-projection 的逆矩阵
+    inv_view_proj: mat4x4f,
+    prev_view_proj: mat4x4f,
+    // xy: 当前帧 NDC 抖动, z: 历史权重, w: 历史是否有效
+    params: vec4f,
+}
+
+struct TaaOutput {
+    @location(0) color: vec4f,
+    @location(1) history: vec4f,
+}
+
+@group(0) @binding(0)
+var<uniform> taa: TaaUniform;
+@group(0) @binding(1)
+var t_color: texture_2d<f32>;
+@group(0) @binding(2)
+var t_depth: texture_depth_2d;
+@group(0) @binding(3)
+var t_history: texture_2d<f32>;
+@group(0) @binding(4)
+var s_linear: sampler;
+@group(0) @binding(5)
+var t_velocity: texture_2d<f32>;
+
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32) -> FullscreenOutput {
+    return fullscreen_vertex(index);
+}
+
+@fragment
+fn fs_main(in: FullscreenOutput) -> TaaOutput {
+    let pixel = vec2i(in.clip_position.xy);
+    let current = textureLoad(t_color, pixel, 0);
+
+    // 3x3 邻域的颜色范围，用于约束历史颜色，减少拖影
+    var lo = current.rgb;
+    var hi = current.rgb;
+    let dims = vec2i(textureDimensions(t_color));
+    for (var y = -1; y <= 1; y++) {
+        for (var x = -1; x <= 1; x++) {
+            let p = clamp(pixel + vec2i(x, y), vec2i(0), dims - 1);
+            let c = textureLoad(t_color, p, 0).rgb;
+            lo = min(lo, c);
+            hi = max(hi, c);
+        }
+    }
+
+    let depth = textureLoad(t_depth, pixel, 0);
+    var prev_uv = reproject_uv(in.uv, depth, taa.params.xy, taa.inv_view_proj, taa.prev_view_proj);
+    if (HAS_VELOCITY && depth < 1.0) {
+        prev_uv = in.uv - textureLoad(t_velocity, pixel, 0).xy;
+    }
+
+    var result = current.rgb;
+    let in_bounds = all(prev_uv >= vec2f(0.0)) && all(prev_uv <= vec2f(1.0));
+    if (taa.params.w > 0.5 && in_bounds) {
+        let history = textureSampleLevel(t_history, s_linear, prev_uv, 0.0).rgb;
+        result = mix(current.rgb, clamp(history, lo, hi), taa.params.z);
+    }
+
+    var out: TaaOutput;
+    out.color = vec4f(result, current.a);
+    out.history = out.color;
+    return out;
+}
@@ -0,0 +1,11 @@
+// 材质贴图坐标变换（KHR_texture_transform），由 model::TextureTransformUniform 填充
+struct TextureTransform {
+    // 3x3 变换矩阵的前两行
+    row_u: vec4f,
+    row_v: vec4f,
+}
+
+fn apply_texture_transform(transform: TextureTransform, uv: vec2f) -> vec2f {
+    let p = vec3f(uv, 1.0);
+    return vec2f(dot(transform.row_u.xyz, p), dot(transform.row_v.xyz, p));
+}
@@ -0,0 +1,53 @@
+#include "wgpu_dance/alpha_test.wgsl"
+#include "wgpu_dance/texture_transform.wgsl"
+#include "wgpu_dance/two_sided.wgsl"
+
+struct ThumbnailUniform {
+    view_proj: mat4x4f,
+    // xyz: 指向光源的方向（已归一化）
+    light_dir: vec4f,
+}
+
+@group(0) @binding(0)
+var<uniform> thumbnail: ThumbnailUniform;
+
+@group(1) @binding(0)
+var t_diffuse: texture_2d<f32>;
+@group(1) @binding(1)
+var s_diffuse: sampler;
+
+@group(2) @binding(0)
+var<uniform> texture_transform: TextureTransform;
+
+struct VertexInput {
+    @location(4) position: vec3f,
+    @location(5) tex_coords: vec2f,
+    @location(6) normal: vec3f,
+}
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4f,
+    @location(0) tex_coords: vec2f,
+    @location(1) normal: vec3f,
+}
+
+@vertex
+fn vs_main(in: VertexInput) -> VertexOutput {
+    var out: VertexOutput;
+    out.clip_position = thumbnail.view_proj * vec4f(in.position, 1.0);
+    out.tex_coords = in.tex_coords;
+    out.normal = in.normal;
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput, @builtin(front_facing) front: bool) -> @location(0) vec4f {
+    let uv = apply_texture_transform(texture_transform, in.tex_coords);
+    let albedo = textureSample(t_diffuse, s_diffuse, uv);
+    alpha_test(albedo.a);
+    // 加上微小偏移避免零法线归一化得到 NaN；单面材质剔除了背面，front 总为 true
+    let n = two_sided_normal(normalize(in.normal + vec3f(0.0, 0.0, 1e-6)), front);
+    let diffuse = max(dot(n, thumbnail.light_dir.xyz), 0.0);
+    let color = albedo.rgb * (0.25 + 0.75 * diffuse);
+    return vec4f(color, 1.0);
+}
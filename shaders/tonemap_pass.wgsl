@@ -0,0 +1,48 @@
+#include "wgpu_dance/fullscreen.wgsl"
+#include "wgpu_dance/tonemapping.wgsl"
+
+struct TonemapUniform {
+    // x: 曝光（EV），自动曝光时作为补偿, y: 是否自动曝光, z: 色调映射曲线（0 ACES, 1 Reinhard, 2 不映射）,
+    // w: 输出的 alpha（0 保留输入, 1 预乘, 2 非预乘）
+    params: vec4f,
+}
+
+@group(0) @binding(0)
+var<uniform> tonemap: TonemapUniform;
+@group(0) @binding(1)
+var t_color: texture_2d<f32>;
+// x: 自动曝光适应后的平均亮度
+@group(0) @binding(2)
+var<storage, read> exposure_state: array<f32, 4>;
+
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32) -> FullscreenOutput {
+    return fullscreen_vertex(index);
+}
+
+@fragment
+fn fs_main(in: FullscreenOutput) -> @location(0) vec4f {
+    let color = textureLoad(t_color, vec2i(in.clip_position.xy), 0);
+    var scale = exp2(tonemap.params.x);
+    if (tonemap.params.y > 0.5) {
+        // 把平均亮度映射到 18% 中灰
+        scale *= 0.18 / max(exposure_state[0], 1e-4);
+    }
+    // 透明输出时输入颜色已乘过 alpha，色调映射是非线性的，需要先还原
+    let transparent = tonemap.params.w > 0.5;
+    var straight = color.rgb;
+    if (transparent && color.a > 0.0) {
+        straight = color.rgb / color.a;
+    }
+    let hdr = straight * scale;
+    var rgb = hdr;
+    if (tonemap.params.z < 0.5) {
+        rgb = tonemap_aces(hdr);
+    } else if (tonemap.params.z < 1.5) {
+        rgb = tonemap_reinhard(hdr);
+    }
+    if (transparent && tonemap.params.w < 1.5) {
+        rgb *= color.a;
+    }
+    return vec4f(rgb, color.a);
+}
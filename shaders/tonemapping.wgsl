@@ -0,0 +1,25 @@
+fn tonemap_reinhard(color: vec3f) -> vec3f {
+    return color / (color + vec3f(1.0));
+}
+
+// Narkowicz 的 ACES 拟合曲线
+fn tonemap_aces(color: vec3f) -> vec3f {
+    let a = 2.51;
+    let b = 0.03;
+    let c = 2.43;
+    let d = 0.59;
+    let e = 0.14;
+    return clamp((color * (a * color + b)) / (color * (c * color + d) + e), vec3f(0.0), vec3f(1.0));
+}
+
+fn linear_to_srgb(color: vec3f) -> vec3f {
+    let low = color * 12.92;
+    let high = 1.055 * pow(color, vec3f(1.0 / 2.4)) - 0.055;
+    return select(high, low, color <= vec3f(0.0031308));
+}
+
+fn srgb_to_linear(color: vec3f) -> vec3f {
+    let low = color / 12.92;
+    let high = pow((color + 0.055) / 1.055, vec3f(2.4));
+    return select(high, low, color <= vec3f(0.04045));
+}
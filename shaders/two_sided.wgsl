@@ -0,0 +1,4 @@
+// 双面材质的背面使用翻转后的法线，从背后看薄片几何（树叶、布料）也有正确的光照
+fn two_sided_normal(normal: vec3f, front_facing: bool) -> vec3f {
+    return select(-normal, normal, front_facing);
+}
@@ -0,0 +1,48 @@
+#include "wgpu_dance/fullscreen.wgsl"
+
+struct UpscaleUniform {
+    // xy: 有效区域在纹理中的 uv 比例, zw: 双线性采样不越过有效区域的 uv 上限
+    region: vec4f,
+    // xy: 源纹理的纹素大小（uv）, z: 锐化强度（0 为纯双线性）
+    params: vec4f,
+}
+
+@group(0) @binding(0)
+var t_source: texture_2d<f32>;
+@group(0) @binding(1)
+var s_source: sampler;
+@group(1) @binding(0)
+var<uniform> upscale: UpscaleUniform;
+
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32) -> FullscreenOutput {
+    return fullscreen_vertex(index);
+}
+
+fn sample_source(uv: vec2f) -> vec3f {
+    return textureSampleLevel(t_source, s_source, min(uv, upscale.region.zw), 0.0).rgb;
+}
+
+@fragment
+fn fs_main(in: FullscreenOutput) -> @location(0) vec4f {
+    let uv = in.uv * upscale.region.xy;
+    let c = sample_source(uv);
+    let sharpness = upscale.params.z;
+    if sharpness <= 0.0 {
+        return vec4f(c, 1.0);
+    }
+
+    // 对比度自适应锐化（与 FSR1 的 RCAS 思路相同）：取源分辨率下的十字邻域，
+    // 局部对比度越高锐化越弱，避免在边缘产生光晕
+    let texel = upscale.params.xy;
+    let n = sample_source(uv - vec2f(0.0, texel.y));
+    let s = sample_source(uv + vec2f(0.0, texel.y));
+    let w = sample_source(uv - vec2f(texel.x, 0.0));
+    let e = sample_source(uv + vec2f(texel.x, 0.0));
+    let lo = min(c, min(min(n, s), min(w, e)));
+    let hi = max(c, max(max(n, s), max(w, e)));
+    let amount = sqrt(clamp(min(lo, 1.0 - hi) / max(hi, vec3f(1e-4)), vec3f(0.0), vec3f(1.0)));
+    let weight = -amount * mix(0.125, 0.2, sharpness);
+    let color = (c + (n + s + w + e) * weight) / (1.0 + 4.0 * weight);
+    return vec4f(clamp(color, vec3f(0.0), vec3f(1.0)), 1.0);
+}
@@ -0,0 +1,58 @@
+struct Wave {
+    // xy: 传播方向, z: 陡峭度, w: 振幅
+    direction_steepness: vec4f,
+    // x: 波长, y: 速度
+    params: vec4f,
+};
+
+struct WaterUniform {
+    // xyz: 相机位置, w: 时间（秒）
+    eye: vec4f,
+    // x: 水面边长, y: 水面高度, z: 网格分辨率, w: 岸边淡出距离
+    grid: vec4f,
+    // x: znear, y: zfar, z: 波的数量
+    depth_params: vec4f,
+    shallow_color: vec4f,
+    deep_color: vec4f,
+    waves: array<Wave, 4>,
+};
+
+struct WaterVertex {
+    position: vec4f,
+    normal: vec4f,
+};
+
+const PI: f32 = 3.14159265;
+
+// 按 GPU Gems 第一章的 Gerstner 波叠加计算顶点位置与法线
+fn gerstner(base: vec2f, water: WaterUniform) -> WaterVertex {
+    var position = vec3f(base.x, water.grid.y, base.y);
+    var normal = vec3f(0.0, 1.0, 0.0);
+    let time = water.eye.w;
+    let count = u32(water.depth_params.z);
+
+    for (var i = 0u; i < count; i++) {
+        let wave = water.waves[i];
+        let d = normalize(wave.direction_steepness.xy);
+        let amplitude = wave.direction_steepness.w;
+        let k = 2.0 * PI / wave.params.x;
+        let q = wave.direction_steepness.z / max(k * amplitude * f32(count), 0.0001);
+        let f = k * (dot(d, base) - wave.params.y * time);
+        let c = cos(f);
+        let s = sin(f);
+
+        position.x += q * amplitude * d.x * c;
+        position.z += q * amplitude * d.y * c;
+        position.y += amplitude * s;
+
+        normal.x -= d.x * k * amplitude * c;
+        normal.z -= d.y * k * amplitude * c;
+        normal.y -= q * k * amplitude * s;
+    }
+
+    return WaterVertex(vec4f(position, 1.0), vec4f(normalize(normal), 0.0));
+}
+
+fn linearize_depth(depth: f32, znear: f32, zfar: f32) -> f32 {
+    return znear * zfar / (zfar - depth * (zfar - znear));
+}
@@ -0,0 +1,17 @@
+#include "wgpu_dance/water.wgsl"
+
+@group(0) @binding(0)
+var<uniform> water: WaterUniform;
+@group(0) @binding(1)
+var<storage, read_write> vertices: array<WaterVertex>;
+
+@compute @workgroup_size(8, 8)
+fn cs_main(@builtin(global_invocation_id) id: vec3u) {
+    let resolution = u32(water.grid.z);
+    if (id.x >= resolution || id.y >= resolution) {
+        return;
+    }
+    let uv = vec2f(id.xy) / f32(resolution - 1u);
+    let base = (uv - 0.5) * water.grid.x;
+    vertices[id.y * resolution + id.x] = gerstner(base, water);
+}
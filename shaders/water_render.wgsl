@@ -0,0 +1,56 @@
+#include "wgpu_dance/camera.wgsl"
+#include "wgpu_dance/water.wgsl"
+
+@group(0) @binding(0)
+var<uniform> camera: CameraUniform;
+
+@group(1) @binding(0)
+var<uniform> water: WaterUniform;
+
+@group(2) @binding(0)
+var t_reflection: texture_2d<f32>;
+@group(2) @binding(1)
+var s_reflection: sampler;
+@group(2) @binding(2)
+var t_scene_depth: texture_depth_2d;
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4f,
+    @location(0) world_position: vec3f,
+    @location(1) normal: vec3f,
+}
+
+@vertex
+fn vs_main(@location(0) position: vec4f, @location(1) normal: vec4f) -> VertexOutput {
+    var out: VertexOutput;
+    out.clip_position = camera.view_proj * position;
+    out.world_position = position.xyz;
+    out.normal = normal.xyz;
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4f {
+    let normal = normalize(in.normal);
+    let view_dir = normalize(water.eye.xyz - in.world_position);
+
+    // 用法线扰动反射纹理的采样坐标
+    let dims = vec2f(textureDimensions(t_reflection));
+    let uv = in.clip_position.xy / dims + normal.xz * 0.03;
+    let reflection = textureSample(t_reflection, s_reflection, clamp(uv, vec2f(0.0), vec2f(1.0))).rgb;
+
+    // 水面与其后方场景之间的距离，决定水的颜色深浅以及岸边的透明度
+    let znear = water.depth_params.x;
+    let zfar = water.depth_params.y;
+    let scene_depth = textureLoad(t_scene_depth, vec2i(in.clip_position.xy), 0);
+    let thickness = linearize_depth(scene_depth, znear, zfar)
+        - linearize_depth(in.clip_position.z, znear, zfar);
+
+    let water_color = mix(water.shallow_color.rgb, water.deep_color.rgb, clamp(thickness / 4.0, 0.0, 1.0));
+    let fresnel = 0.02 + 0.98 * pow(1.0 - max(dot(normal, view_dir), 0.0), 5.0);
+    let color = mix(water_color, reflection, fresnel);
+
+    let shoreline = clamp(thickness / water.grid.w, 0.0, 1.0);
+    let alpha = shoreline * mix(0.75, 1.0, fresnel);
+    return vec4f(color, alpha);
+}
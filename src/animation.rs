@@ -0,0 +1,400 @@
+use anyhow::ensure;
+use bytemuck::{Pod, Zeroable};
+use wgpu::{BindGroupLayoutEntry, Device, Queue};
+
+use crate::{instance::Instance, model::RenderVertex, uniform::GpuUniform};
+
+/// 关节相对父关节的变换
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct JointPose {
+    pub translation: glam::Vec3,
+    pub rotation: glam::Quat,
+    pub scale: glam::Vec3,
+}
+
+impl Default for JointPose {
+    fn default() -> Self {
+        Self {
+            translation: glam::Vec3::ZERO,
+            rotation: glam::Quat::IDENTITY,
+            scale: glam::Vec3::ONE,
+        }
+    }
+}
+
+impl JointPose {
+    pub fn matrix(&self) -> glam::Mat4 {
+        glam::Mat4::from_scale_rotation_translation(self.scale, self.rotation, self.translation)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Joint {
+    pub name: String,
+    /// 父关节的下标，必须小于本关节的下标
+    pub parent: Option<usize>,
+    /// 绑定姿态下的局部变换
+    pub rest: JointPose,
+}
+
+/// 关节按父关节在前的顺序排列的骨架
+#[derive(Debug, Clone)]
+pub struct Skeleton {
+    joints: Vec<Joint>,
+    /// 绑定姿态下各关节世界矩阵的逆
+    inverse_binds: Vec<glam::Mat4>,
+}
+
+impl Skeleton {
+    pub fn new(joints: Vec<Joint>) -> anyhow::Result<Self> {
+        for (i, joint) in joints.iter().enumerate() {
+            ensure!(
+                joint.parent.is_none_or(|parent| parent < i),
+                "joint `{}` must come after its parent",
+                joint.name
+            );
+        }
+        let rest = joints.iter().map(|joint| joint.rest).collect::<Vec<_>>();
+        let mut skeleton = Self {
+            joints,
+            inverse_binds: Vec::new(),
+        };
+        skeleton.inverse_binds = skeleton
+            .world_matrices(&rest)
+            .iter()
+            .map(glam::Mat4::inverse)
+            .collect();
+        Ok(skeleton)
+    }
+
+    pub fn joints(&self) -> &[Joint] {
+        &self.joints
+    }
+
+    pub fn joint_index(&self, name: &str) -> Option<usize> {
+        self.joints.iter().position(|joint| joint.name == name)
+    }
+
+    fn world_matrices(&self, poses: &[JointPose]) -> Vec<glam::Mat4> {
+        let mut world: Vec<glam::Mat4> = Vec::with_capacity(self.joints.len());
+        for (joint, pose) in self.joints.iter().zip(poses) {
+            let parent = joint.parent.map_or(glam::Mat4::IDENTITY, |i| world[i]);
+            world.push(parent * pose.matrix());
+        }
+        world
+    }
+
+    /// 由各关节的局部姿态得到蒙皮矩阵，把绑定姿态下的顶点变换到当前姿态
+    pub fn skin_matrices(&self, poses: &[JointPose]) -> Vec<glam::Mat4> {
+        self.world_matrices(poses)
+            .iter()
+            .zip(&self.inverse_binds)
+            .map(|(world, inverse_bind)| *world * *inverse_bind)
+            .collect()
+    }
+}
+
+/// 一个关节的关键帧，时间单位为秒且递增；为空的轨道保持绑定姿态
+#[derive(Debug, Clone, Default)]
+pub struct JointTrack {
+    pub joint: usize,
+    pub translations: Vec<(f32, glam::Vec3)>,
+    pub rotations: Vec<(f32, glam::Quat)>,
+    pub scales: Vec<(f32, glam::Vec3)>,
+}
+
+/// 在关键帧之间插值，超出范围时取两端的值
+fn sample_keys<T: Copy>(keys: &[(f32, T)], time: f32, lerp: impl Fn(T, T, f32) -> T) -> Option<T> {
+    let next = keys.partition_point(|(t, _)| *t <= time);
+    match (keys.get(next.wrapping_sub(1)), keys.get(next)) {
+        (Some(&(t0, a)), Some(&(t1, b))) => Some(lerp(a, b, (time - t0) / (t1 - t0))),
+        (Some(&(_, value)), None) | (None, Some(&(_, value))) => Some(value),
+        (None, None) => None,
+    }
+}
+
+/// 循环播放的骨骼动画
+#[derive(Debug, Clone)]
+pub struct AnimationClip {
+    pub name: String,
+    /// 时长（秒）
+    pub duration: f32,
+    pub tracks: Vec<JointTrack>,
+}
+
+impl AnimationClip {
+    /// `time` 时刻各关节的局部姿态，超出时长时循环
+    pub fn sample(&self, skeleton: &Skeleton, time: f32) -> Vec<JointPose> {
+        self.pose_at(skeleton, time.rem_euclid(self.duration.max(f32::EPSILON)))
+    }
+
+    fn pose_at(&self, skeleton: &Skeleton, time: f32) -> Vec<JointPose> {
+        let mut poses = skeleton
+            .joints
+            .iter()
+            .map(|joint| joint.rest)
+            .collect::<Vec<_>>();
+        for track in &self.tracks {
+            let Some(pose) = poses.get_mut(track.joint) else {
+                continue;
+            };
+            if let Some(t) = sample_keys(&track.translations, time, glam::Vec3::lerp) {
+                pose.translation = t;
+            }
+            if let Some(r) = sample_keys(&track.rotations, time, glam::Quat::slerp) {
+                pose.rotation = r;
+            }
+            if let Some(s) = sample_keys(&track.scales, time, glam::Vec3::lerp) {
+                pose.scale = s;
+            }
+        }
+        poses
+    }
+}
+
+/// 与 `baked_animation.wgsl` 中的 `BakedClips` 保持一致，每个动画一项：
+/// x: 起始行，y: 帧数，z: 时长（秒）
+#[repr(C)]
+#[derive(Debug, Copy, Clone, GpuUniform)]
+pub struct BakedClipsUniform {
+    pub clips: [[f32; 4]; BakedAnimations::MAX_CLIPS],
+}
+
+/// 烘焙后的动画在骨骼纹理中的位置
+#[derive(Debug, Clone)]
+pub struct BakedClip {
+    pub name: String,
+    pub first_row: u32,
+    pub frame_count: u32,
+    pub duration: f32,
+}
+
+/// 把骨骼动画逐帧烘焙成蒙皮矩阵纹理，供大量实例各自播放不同的动画与时刻
+///
+/// 纹理每行是一帧，每个关节占 3 个 texel，存放仿射蒙皮矩阵的前三行；
+/// 着色器通过 `wgpu_dance/baked_animation.wgsl` 采样，相邻两帧之间线性插值。
+/// 实例数据见 [`AnimatedInstanceRaw`]，所有实例可以在一次实例化绘制中完成。
+pub struct BakedAnimations {
+    clips: Vec<BakedClip>,
+    joint_count: u32,
+    pub bind_group_layout: wgpu::BindGroupLayout,
+    pub bind_group: wgpu::BindGroup,
+}
+
+impl BakedAnimations {
+    /// 着色器中动画表的长度
+    pub const MAX_CLIPS: usize = 32;
+    const FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba32Float;
+
+    /// 绑定 0 为骨骼纹理，绑定 1 为动画表，都只在顶点着色器中使用
+    pub fn layout_entries() -> [BindGroupLayoutEntry; 2] {
+        [
+            BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::VERTEX,
+                ty: wgpu::BindingType::Texture {
+                    sample_type: wgpu::TextureSampleType::Float { filterable: false },
+                    view_dimension: wgpu::TextureViewDimension::D2,
+                    multisampled: false,
+                },
+                count: None,
+            },
+            BindGroupLayoutEntry {
+                binding: 1,
+                visibility: wgpu::ShaderStages::VERTEX,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            },
+        ]
+    }
+
+    /// 以每秒 `fps` 帧烘焙 `clips`，每个动画至少两帧
+    pub fn bake(
+        device: &Device,
+        queue: &Queue,
+        skeleton: &Skeleton,
+        clips: &[AnimationClip],
+        fps: f32,
+    ) -> anyhow::Result<Self> {
+        ensure!(!clips.is_empty(), "no animation clips to bake");
+        ensure!(
+            clips.len() <= Self::MAX_CLIPS,
+            "at most {} clips can be baked",
+            Self::MAX_CLIPS
+        );
+        ensure!(fps > 0.0, "fps must be positive");
+        let joint_count = skeleton.joints.len() as u32;
+        let max_dimension = device.limits().max_texture_dimension_2d;
+        ensure!(
+            joint_count > 0 && joint_count * 3 <= max_dimension,
+            "skeleton has {joint_count} joints, which does not fit in a texture row"
+        );
+
+        let mut baked = Vec::with_capacity(clips.len());
+        let mut texels: Vec<[f32; 4]> = Vec::new();
+        for clip in clips {
+            let frame_count = ((clip.duration * fps).ceil() as u32).max(1) + 1;
+            baked.push(BakedClip {
+                name: clip.name.clone(),
+                first_row: (texels.len() / (joint_count as usize * 3)) as u32,
+                frame_count,
+                duration: clip.duration,
+            });
+            for frame in 0..frame_count {
+                // 最后一帧取时长末尾的姿态，循环时与第一帧衔接
+                let time = clip.duration * frame as f32 / (frame_count - 1) as f32;
+                for skin in skeleton.skin_matrices(&clip.pose_at(skeleton, time)) {
+                    let rows = skin.transpose();
+                    texels.extend([rows.x_axis, rows.y_axis, rows.z_axis].map(|r| r.to_array()));
+                }
+            }
+        }
+        let height = (texels.len() / (joint_count as usize * 3)) as u32;
+        ensure!(
+            height <= max_dimension,
+            "{height} baked frames exceed the texture size limit of {max_dimension}"
+        );
+
+        let size = wgpu::Extent3d {
+            width: joint_count * 3,
+            height,
+            depth_or_array_layers: 1,
+        };
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Baked Animation Texture"),
+            size,
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: Self::FORMAT,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+            view_formats: &[],
+        });
+        queue.write_texture(
+            texture.as_image_copy(),
+            bytemuck::cast_slice(&texels),
+            wgpu::TexelCopyBufferLayout {
+                offset: 0,
+                bytes_per_row: Some(size.width * 16),
+                rows_per_image: Some(height),
+            },
+            size,
+        );
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+        let mut uniform = BakedClipsUniform {
+            clips: [[0.0; 4]; Self::MAX_CLIPS],
+        };
+        for (entry, clip) in uniform.clips.iter_mut().zip(&baked) {
+            *entry = [
+                clip.first_row as f32,
+                clip.frame_count as f32,
+                clip.duration,
+                0.0,
+            ];
+        }
+        let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Baked Clips Buffer"),
+            size: std::mem::size_of::<BakedClipsUniform>() as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        uniform.write_to(queue, &buffer);
+
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &Self::layout_entries(),
+            label: Some("baked_animation_bind_group_layout"),
+        });
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: &bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(&view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: buffer.as_entire_binding(),
+                },
+            ],
+            label: Some("baked_animation_bind_group"),
+        });
+
+        Ok(Self {
+            clips: baked,
+            joint_count,
+            bind_group_layout,
+            bind_group,
+        })
+    }
+
+    pub fn clips(&self) -> &[BakedClip] {
+        &self.clips
+    }
+
+    pub fn clip_index(&self, name: &str) -> Option<u32> {
+        self.clips
+            .iter()
+            .position(|clip| clip.name == name)
+            .map(|i| i as u32)
+    }
+
+    pub fn joint_count(&self) -> u32 {
+        self.joint_count
+    }
+}
+
+/// 播放烘焙动画的实例
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AnimatedInstance {
+    pub transform: Instance,
+    /// [`BakedAnimations::clips`] 中的下标
+    pub clip: u32,
+    /// 播放时刻（秒），超出动画时长时循环
+    pub time: f32,
+}
+
+impl AnimatedInstance {
+    pub fn to_raw(&self) -> AnimatedInstanceRaw {
+        AnimatedInstanceRaw {
+            model: self.transform.model_matrix().to_cols_array_2d(),
+            clip: self.clip,
+            time: self.time,
+        }
+    }
+}
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct AnimatedInstanceRaw {
+    model: [[f32; 4]; 4],
+    clip: u32,
+    time: f32,
+}
+
+unsafe impl Zeroable for AnimatedInstanceRaw {}
+unsafe impl Pod for AnimatedInstanceRaw {}
+
+impl RenderVertex for AnimatedInstanceRaw {
+    /// 模型矩阵占用 0~3 号位置，动画下标与播放时刻占用 8、9 号位置，
+    /// 与使用 4~7 号位置的蒙皮顶点不冲突
+    fn buffer_layout_desc<'a>() -> wgpu::VertexBufferLayout<'a> {
+        const ATTRIBUTES: [wgpu::VertexAttribute; 6] = wgpu::vertex_attr_array![
+            0 => Float32x4,
+            1 => Float32x4,
+            2 => Float32x4,
+            3 => Float32x4,
+            8 => Uint32,
+            9 => Float32,
+        ];
+        wgpu::VertexBufferLayout {
+            array_stride: std::mem::size_of::<AnimatedInstanceRaw>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Instance,
+            attributes: &ATTRIBUTES,
+        }
+    }
+}
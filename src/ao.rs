@@ -0,0 +1,186 @@
+use anyhow::ensure;
+use glam::{Vec2, Vec3};
+
+use crate::{bvh::Bvh, jobs, ray::Ray, scatter::Rng};
+
+/// 烘焙纹理时向未覆盖的纹素扩展的圈数，避免过滤与 mipmap 在 UV 接缝处采样到空白
+const TEXTURE_PADDING: u32 = 2;
+
+/// 光线追踪的环境光遮蔽烘焙
+///
+/// 从表面点沿法线半球按余弦分布发出 `samples` 条射线，结果为 `max_distance` 内没有被
+/// [`Bvh`] 中任何三角形挡住的比例：1 表示完全不遮蔽，0 表示完全遮蔽。
+/// 采样使用按点旋转的 Hammersley 序列，相同的输入总是得到相同的结果。
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct AoBaker {
+    pub samples: u32,
+    /// 超过该距离的遮挡物不计入
+    pub max_distance: f32,
+    /// 射线起点沿法线的偏移，避免与自身相交
+    pub bias: f32,
+}
+
+impl Default for AoBaker {
+    fn default() -> Self {
+        Self {
+            samples: 64,
+            max_distance: 1.0,
+            bias: 1e-3,
+        }
+    }
+}
+
+impl AoBaker {
+    pub fn with_samples(mut self, samples: u32) -> Self {
+        self.samples = samples;
+        self
+    }
+
+    pub fn with_max_distance(mut self, max_distance: f32) -> Self {
+        self.max_distance = max_distance;
+        self
+    }
+
+    /// `position` 处未被遮蔽的比例，`normal` 需已归一化；`seed` 决定采样序列的旋转
+    pub fn occlusion(&self, bvh: &Bvh, position: Vec3, normal: Vec3, seed: u64) -> f32 {
+        let samples = self.samples.max(1);
+        let mut rng = Rng::new(seed);
+        let rotation = glam::vec2(rng.next_f32(), rng.next_f32());
+        let (tangent, bitangent) = normal.any_orthonormal_pair();
+        let origin = position + normal * self.bias;
+        let open = (0..samples)
+            .filter(|&i| {
+                // Cranley-Patterson 旋转，消除相邻点之间相同的条纹
+                let u = (hammersley(i, samples) + rotation).fract();
+                let phi = u.x * std::f32::consts::TAU;
+                let r = u.y.sqrt();
+                let local = glam::vec3(r * phi.cos(), r * phi.sin(), (1.0 - u.y).max(0.0).sqrt());
+                let direction = tangent * local.x + bitangent * local.y + normal * local.z;
+                !bvh.occluded(&Ray::new(origin, direction), self.max_distance)
+            })
+            .count();
+        open as f32 / samples as f32
+    }
+
+    /// 逐顶点烘焙，`positions` 与 `normals` 与构建 `bvh` 时处于同一空间
+    pub fn bake_vertices(&self, bvh: &Bvh, positions: &[Vec3], normals: &[Vec3]) -> Vec<f32> {
+        let vertices = positions.iter().zip(normals).collect::<Vec<_>>();
+        let indices = (0..vertices.len()).collect::<Vec<_>>();
+        jobs::global().map("bake ao", &indices, |&i| {
+            let (&position, &normal) = vertices[i];
+            self.occlusion(bvh, position, normal.normalize_or(Vec3::Y), i as u64)
+        })
+    }
+
+    /// 烘焙到 `(width, height)` 的单通道纹理，纹素位置由 `tex_coords` 在 UV 空间中光栅化三角形得到
+    ///
+    /// UV 原点在左上角，与 wgpu 的纹理坐标一致；没有被任何三角形覆盖的纹素先由相邻纹素填充，
+    /// 仍然空白的为 255。结果是线性数据，上传时应使用非 sRGB 格式，在着色器中与环境光相乘。
+    pub fn bake_texture(
+        &self,
+        bvh: &Bvh,
+        positions: &[Vec3],
+        normals: &[Vec3],
+        tex_coords: &[Vec2],
+        indices: &[u32],
+        (width, height): (u32, u32),
+    ) -> anyhow::Result<image::GrayImage> {
+        ensure!(width > 0 && height > 0, "texture size must be positive");
+        ensure!(
+            positions.len() == normals.len() && positions.len() == tex_coords.len(),
+            "vertex attribute counts differ: {} positions, {} normals, {} tex coords",
+            positions.len(),
+            normals.len(),
+            tex_coords.len()
+        );
+        ensure!(
+            indices.iter().all(|&i| (i as usize) < positions.len()),
+            "index out of range"
+        );
+
+        let size = glam::vec2(width as f32, height as f32);
+        let mut texels = vec![None; (width * height) as usize];
+        for triangle in indices.chunks_exact(3) {
+            let [a, b, c] = [0, 1, 2].map(|i| triangle[i] as usize);
+            let [ta, tb, tc] = [a, b, c].map(|i| tex_coords[i] * size);
+            let area = edge(ta, tb, tc);
+            if area.abs() < f32::EPSILON {
+                continue;
+            }
+            let min = ta.min(tb).min(tc).floor().max(Vec2::ZERO);
+            let max = ta.max(tb).max(tc).ceil().min(size);
+            for y in min.y as u32..max.y as u32 {
+                for x in min.x as u32..max.x as u32 {
+                    let p = glam::vec2(x as f32, y as f32) + 0.5;
+                    let weights =
+                        glam::vec3(edge(tb, tc, p), edge(tc, ta, p), edge(ta, tb, p)) / area;
+                    if weights.min_element() < 0.0 {
+                        continue;
+                    }
+                    let position = positions[a] * weights.x
+                        + positions[b] * weights.y
+                        + positions[c] * weights.z;
+                    let normal =
+                        normals[a] * weights.x + normals[b] * weights.y + normals[c] * weights.z;
+                    texels[(y * width + x) as usize] =
+                        Some((position, normal.normalize_or(Vec3::Y)));
+                }
+            }
+        }
+
+        let rows = (0..height).collect::<Vec<_>>();
+        let mut values = jobs::global()
+            .map("bake ao texture", &rows, |&y| {
+                (0..width)
+                    .map(|x| {
+                        let index = y * width + x;
+                        texels[index as usize].map(|(position, normal)| {
+                            self.occlusion(bvh, position, normal, index as u64)
+                        })
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .into_iter()
+            .flatten()
+            .collect::<Vec<_>>();
+        for _ in 0..TEXTURE_PADDING {
+            values = dilate(&values, width, height);
+        }
+        Ok(image::GrayImage::from_fn(width, height, |x, y| {
+            let value = values[(y * width + x) as usize].unwrap_or(1.0);
+            image::Luma([(value.clamp(0.0, 1.0) * 255.0).round() as u8])
+        }))
+    }
+}
+
+/// `p` 相对于有向边 `ab` 的二维叉积，三角形内部的点对三条边同号
+fn edge(a: Vec2, b: Vec2, p: Vec2) -> f32 {
+    (b - a).perp_dot(p - a)
+}
+
+/// Hammersley 点集的第 `i` 个点，共 `n` 个
+fn hammersley(i: u32, n: u32) -> Vec2 {
+    glam::vec2(i as f32 / n as f32, i.reverse_bits() as f32 / 2f32.powi(32))
+}
+
+/// 空白纹素取相邻四个纹素中已有值的平均
+fn dilate(values: &[Option<f32>], width: u32, height: u32) -> Vec<Option<f32>> {
+    let get = |x: i64, y: i64| {
+        (x >= 0 && y >= 0 && x < width as i64 && y < height as i64)
+            .then(|| values[(y * width as i64 + x) as usize])
+            .flatten()
+    };
+    (0..height as i64)
+        .flat_map(|y| (0..width as i64).map(move |x| (x, y)))
+        .map(|(x, y)| {
+            get(x, y).or_else(|| {
+                let neighbors = [(x - 1, y), (x + 1, y), (x, y - 1), (x, y + 1)]
+                    .into_iter()
+                    .filter_map(|(x, y)| get(x, y))
+                    .collect::<Vec<_>>();
+                (!neighbors.is_empty())
+                    .then(|| neighbors.iter().sum::<f32>() / neighbors.len() as f32)
+            })
+        })
+        .collect()
+}
@@ -1,43 +1,531 @@
 use std::{
     future::Future,
-    ops::{Deref, DerefMut},
+    path::PathBuf,
     sync::{Arc, Mutex},
+    time::Duration,
 };
 
-use tokio::runtime::Runtime;
+// wasm32 上 std::time::Instant 不可用
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::Instant;
+#[cfg(target_arch = "wasm32")]
+use web_time::Instant;
+
+use crate::{
+    camera::CameraBundle,
+    capture::{recording_dir, screenshot_path, RecordOutput},
+    context::{GpuContext, GpuContextOptions},
+    executor::{default_executor, Executor},
+    input::InputState,
+    loading::{Loading, LoadingProgress},
+    pacing::FramePacer,
+    replay::{InputEvent, InputRecorder, InputReplay},
+    stats::{FrameStats, StatsReporter},
+};
 use winit::{
     application::ApplicationHandler,
-    dpi::PhysicalSize,
-    event::{KeyEvent, WindowEvent},
-    event_loop::ActiveEventLoop,
-    window::{Window, WindowId},
+    dpi::{PhysicalPosition, PhysicalSize, Size},
+    event::{
+        DeviceEvent, DeviceId, ElementState, KeyEvent, MouseButton, MouseScrollDelta, TouchPhase,
+        WindowEvent,
+    },
+    event_loop::{ActiveEventLoop, ControlFlow},
+    keyboard::{KeyCode, PhysicalKey},
+    window::{Fullscreen, Icon, Window, WindowAttributes, WindowId},
 };
 
+/// 按键事件中与平台无关的部分
+///
+/// winit 的 [`KeyEvent`] 无法在库外构造，回放录制的输入时按键以这个类型派发给
+/// [`WindowApp::key_input`]。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyInput {
+    pub physical_key: PhysicalKey,
+    pub state: ElementState,
+    pub repeat: bool,
+}
+
+impl From<&KeyEvent> for KeyInput {
+    fn from(event: &KeyEvent) -> Self {
+        Self {
+            physical_key: event.physical_key,
+            state: event.state,
+            repeat: event.repeat,
+        }
+    }
+}
+
+/// 一帧的时间信息，由 [`WindowAppHandler`] 在每次调用 [`WindowApp::update`] 前计算
+#[derive(Debug, Copy, Clone, Default)]
+pub struct FrameTime {
+    /// 距上一次 update 的时间，固定步长模式下恒为步长
+    pub delta: Duration,
+    /// 第一次 update 以来累计的时间，固定步长模式下为已模拟的时间
+    pub elapsed: Duration,
+    /// 此前已经执行的 update 次数
+    pub frame: u64,
+}
+
+impl FrameTime {
+    pub fn delta_secs(&self) -> f32 {
+        self.delta.as_secs_f32()
+    }
+
+    pub fn elapsed_secs(&self) -> f32 {
+        self.elapsed.as_secs_f32()
+    }
+}
+
+/// winit 与 [`WindowApp`] 之间的事件录制与回放层
+///
+/// 录制时把输入与窗口事件连同时间戳、每帧时长写入文件，可以附在问题报告中；回放时忽略真实的输入，
+/// 把文件中的事件按帧重新派发给应用，窗口大小的变化通过调整真实窗口重现。配合
+/// [`InputReplay::screenshot_when_finished`] 可以对交互式示例做基准图像的回归测试。
+/// 文件格式见 [`crate::replay`]。
+#[derive(Default)]
+pub struct EventTape {
+    recorder: Option<InputRecorder>,
+    replay: Option<InputReplay>,
+    /// 已经录制了至少一帧
+    recording_started: bool,
+}
+
+impl EventTape {
+    /// 设置了环境变量 `WGPU_DANCE_RECORD` 时录制到该文件，设置了 `WGPU_DANCE_REPLAY` 时回放该文件；
+    /// 回放时 `WGPU_DANCE_REPLAY_EXIT` 表示结束后退出，`WGPU_DANCE_REPLAY_SCREENSHOT` 给出结束时的截图路径
+    pub fn from_env() -> Self {
+        let mut tape = Self::default();
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            if let Some(path) = std::env::var_os("WGPU_DANCE_RECORD") {
+                match InputRecorder::create(path) {
+                    Ok(recorder) => tape.recorder = Some(recorder),
+                    Err(e) => eprintln!("{e:#}"),
+                }
+            }
+            if let Some(path) = std::env::var_os("WGPU_DANCE_REPLAY") {
+                match InputReplay::load(path) {
+                    Ok(replay) => {
+                        let exit = std::env::var_os("WGPU_DANCE_REPLAY_EXIT").is_some();
+                        let screenshot =
+                            std::env::var_os("WGPU_DANCE_REPLAY_SCREENSHOT").map(PathBuf::from);
+                        tape.replay = Some(
+                            replay
+                                .exit_when_finished(exit)
+                                .screenshot_when_finished(screenshot),
+                        );
+                    }
+                    Err(e) => eprintln!("{e:#}"),
+                }
+            }
+        }
+        tape
+    }
+
+    pub fn with_recorder(mut self, recorder: InputRecorder) -> Self {
+        self.recorder = Some(recorder);
+        self
+    }
+
+    pub fn with_replay(mut self, replay: InputReplay) -> Self {
+        self.replay = Some(replay);
+        self
+    }
+
+    pub fn is_recording(&self) -> bool {
+        self.recorder.is_some()
+    }
+
+    pub fn is_replaying(&self) -> bool {
+        self.replay.is_some()
+    }
+
+    /// 录制真实的事件，并计入输入状态
+    fn observe(&mut self, input: &mut InputState, event: &InputEvent) {
+        if let Some(recorder) = self.recorder.as_mut() {
+            recorder.record_event(event);
+        }
+        input.handle(event);
+    }
+
+    /// 记录一帧的时长；第一帧之前先记下窗口大小，使回放从相同的大小开始
+    fn record_frame(&mut self, delta: Duration, window: Option<&Arc<Window>>) {
+        let Some(recorder) = self.recorder.as_mut() else {
+            return;
+        };
+        if !std::mem::replace(&mut self.recording_started, true) {
+            if let Some(window) = window {
+                recorder.record_event(&InputEvent::Resized(window.inner_size()));
+            }
+        }
+        recorder.record_frame(delta);
+    }
+
+    fn flush(&mut self) {
+        if let Some(recorder) = self.recorder.as_mut() {
+            recorder.flush();
+        }
+    }
+}
+
+/// 计算每帧的 [`FrameTime`]，固定步长模式下把实际经过的时间累积起来按步长切分
+#[derive(Debug, Default)]
+struct FrameClock {
+    fixed_step: Option<Duration>,
+    last: Option<Instant>,
+    accumulator: Duration,
+    elapsed: Duration,
+    frame: u64,
+}
+
+impl FrameClock {
+    /// 单帧最多计入的时间，避免断点或窗口拖动后一次补上过多的 update
+    const MAX_DELTA: Duration = Duration::from_millis(250);
+
+    /// 距上一次测量经过的时间
+    fn measure(&mut self) -> Duration {
+        let now = Instant::now();
+        self.last
+            .replace(now)
+            .map_or(Duration::ZERO, |last| (now - last).min(Self::MAX_DELTA))
+    }
+
+    /// 返回经过 `delta` 后需要依次执行的 update，可变步长模式下恰好一次，固定步长模式下可能为零次或多次
+    fn step(&mut self, delta: Duration) -> Vec<FrameTime> {
+        let Some(step) = self.fixed_step else {
+            self.elapsed += delta;
+            return vec![self.advance(delta)];
+        };
+        self.accumulator += delta;
+        let mut steps = Vec::new();
+        while self.accumulator >= step {
+            self.accumulator -= step;
+            self.elapsed += step;
+            steps.push(self.advance(step));
+        }
+        steps
+    }
+
+    /// 暂停后重新开始计时，恢复后的第一帧不计入暂停的时间
+    fn pause(&mut self) {
+        self.last = None;
+    }
+
+    fn advance(&mut self, delta: Duration) -> FrameTime {
+        let time = FrameTime {
+            delta,
+            elapsed: self.elapsed,
+            frame: self.frame,
+        };
+        self.frame += 1;
+        time
+    }
+}
+
 pub trait WindowApp {
     fn new(window: Arc<Window>) -> impl Future<Output = Self>;
+
+    /// 开启 [`WindowAppHandler::with_loading_screen`] 时代替 `new` 调用
+    ///
+    /// `loading` 中的 GPU 上下文按 [`GpuContextOptions::for_app`] 创建，加载期间窗口显示进度条，
+    /// 用 [`Loading::step`] 更新进度，最后以 [`Loading::finish`] 取得上下文。
+    /// 默认结束加载画面后调用 `new`。
+    fn load(window: Arc<Window>, loading: Loading) -> impl Future<Output = Self>
+    where
+        Self: Sized,
+    {
+        async move {
+            drop(loading.finish());
+            Self::new(window).await
+        }
+    }
     fn set_window_resized(&mut self, new_size: PhysicalSize<u32>);
     fn resize_surface_if_needed(&mut self);
     fn keyboard_input(&mut self, event: &KeyEvent) -> bool;
+
+    /// 创建设备时必须启用的功能，例如线框渲染需要 [`wgpu::Features::POLYGON_MODE_LINE`]；
+    /// 由 [`crate::context::GpuContextOptions::for_app`] 读取
+    fn required_features() -> wgpu::Features
+    where
+        Self: Sized,
+    {
+        wgpu::Features::empty()
+    }
+
+    /// 创建设备时要求的限制，由 [`crate::context::GpuContextOptions::for_app`] 读取
+    fn required_limits() -> wgpu::Limits
+    where
+        Self: Sized,
+    {
+        wgpu::Limits::default()
+    }
+
+    /// 窗口是否透明，用于悬浮挂件一类的应用；为 `true` 时以透明属性创建窗口，
+    /// 并由 [`crate::context::GpuContextOptions::for_app`] 选用能与桌面合成的 alpha 模式。
+    /// 背景应设为 [`crate::background::Background::Transparent`]
+    fn transparent() -> bool
+    where
+        Self: Sized,
+    {
+        false
+    }
+
+    /// `keyboard_input` 没有处理的按键，以及回放录制时的所有按键；
+    /// 希望按键能被回放的应用在这里处理
+    fn key_input(&mut self, _input: &KeyInput) -> bool {
+        false
+    }
+
+    /// 鼠标按键按下或松开，返回是否处理了该事件
+    fn mouse_click(&mut self, _state: ElementState, _button: MouseButton) -> bool {
+        false
+    }
+
+    fn mouse_wheel(&mut self, _delta: MouseScrollDelta, _phase: TouchPhase) -> bool {
+        false
+    }
+
+    /// 光标在窗口内的位置，单位为物理像素
+    fn cursor_move(&mut self, _position: PhysicalPosition<f64>) -> bool {
+        false
+    }
+
+    /// 未经加速与窗口裁剪的原始设备输入，例如用于视角旋转的鼠标相对移动
+    fn device_input(&mut self, _event: &DeviceEvent) -> bool {
+        false
+    }
+
+    /// 在其他回调之前收到的原始窗口事件，用于 egui 等需要完整事件的界面；
+    /// 对键盘与鼠标输入返回 `true` 时不再派发给其他回调，也不会被录制。回放期间不会调用
+    fn window_event(&mut self, _event: &WindowEvent) -> bool {
+        false
+    }
+
     fn render(&mut self) -> Result<(), wgpu::SurfaceError>;
-    fn update(&mut self);
+    fn update(&mut self, time: FrameTime);
+
+    /// [`WindowAppHandler`] 实际调用的 update，额外提供可轮询的输入状态；默认转发给 `update`
+    fn update_with_input(&mut self, time: FrameTime, _input: &InputState) {
+        self.update(time);
+    }
+
+    /// 返回应用使用的 [`GpuContext`]，[`WindowAppHandler`] 在 surface 丢失或过期时用它重新配置
+    fn gpu_context(&mut self) -> Option<&mut GpuContext> {
+        None
+    }
+
+    /// surface 丢失或过期并已重新配置之后调用，用于重建与 surface 相关的资源；
+    /// 没有提供 [`WindowApp::gpu_context`] 的应用需要在这里自己重新配置 surface
+    fn surface_lost(&mut self) {}
+
+    /// 需要跟随 surface 大小自动更新宽高比的相机
+    fn cameras_mut(&mut self) -> Vec<&mut CameraBundle> {
+        Vec::new()
+    }
+
+    /// 附加在帧统计输出之后的内容，例如 [`GpuCounters::summary`](crate::profiler::GpuCounters::summary)；
+    /// 只在通过 [`WindowAppHandler::with_frame_stats`] 开启统计时使用
+    fn stats_summary(&self) -> Option<String> {
+        None
+    }
+
+    /// 每帧 update 之后查询，返回 `true` 时结束事件循环
+    fn should_exit(&self) -> bool {
+        false
+    }
+
+    /// 事件循环结束前调用一次，无论是关闭窗口、[`WindowApp::should_exit`] 还是出错退出，
+    /// 用于保存截图、写出缓冲等清理工作
+    fn on_exit(&mut self) {}
+
+    /// 文件被拖放到窗口上，多个文件时每个文件调用一次
+    fn file_dropped(&mut self, _path: PathBuf) {}
+
+    /// 文件被拖到窗口上方时以其路径调用，拖离窗口或放下后以 `None` 调用，可用于显示放置提示
+    fn file_hovered(&mut self, _path: Option<PathBuf>) {}
+
+    /// 窗口的 DPI 缩放变化，例如拖到另一块显示器上；此时 [`WindowApp::gpu_context`]
+    /// 已经更新，新的物理尺寸随后通过 `set_window_resized` 到达
+    fn scale_factor_changed(&mut self, _scale_factor: f64) {}
+
+    /// 窗口被最小化、完全遮挡或应用被挂起时以 `false` 调用，此后暂停 update 与绘制，直到以 `true` 调用
+    fn on_visibility_changed(&mut self, _visible: bool) {}
+
+    /// 应用被挂起时调用，例如 Android 切到后台；此时 [`WindowApp::gpu_context`] 的 surface
+    /// 已经丢弃，自己持有 surface 的应用需要在这里丢弃它，其他 GPU 资源可以保留
+    fn on_suspended(&mut self) {}
+
+    /// 应用从挂起中恢复、[`WindowApp::gpu_context`] 的 surface 已经重新创建并配置之后调用，
+    /// 用于重建与 surface 相关的资源；没有提供 `gpu_context` 的应用需要在这里自己创建 surface。
+    /// 随后会以当前的窗口大小调用 `set_window_resized`
+    fn on_surface_recreated(&mut self) {}
 }
 
 #[derive(Default)]
 pub struct WindowAppHandler<A: WindowApp> {
     app: Arc<Mutex<Option<A>>>,
     window: Option<Arc<Window>>,
-    title: String,
+    window_attributes: WindowAttributes,
+    clock: FrameClock,
+    tape: EventTape,
+    /// 回放结束后还要绘制一帧保存截图，绘制完成后退出
+    exit_after_frame: bool,
+    stats: Option<FrameStats>,
+    input: InputState,
+    /// 已经请求退出，避免重复调用 `on_exit`
+    exiting: bool,
+    /// 窗口被完全遮挡
+    occluded: bool,
+    minimized: bool,
+    /// 应用被挂起，surface 已经丢弃
+    suspended: bool,
+    screenshot_key: Option<KeyCode>,
+    record_key: Option<KeyCode>,
+    /// 运行 `WindowApp::new`，为 `None` 时在创建窗口时取 [`default_executor`]
+    executor: Option<Box<dyn Executor>>,
+    pacer: FramePacer,
+    /// 帧率限制器正在等待下一帧的时刻，到时在 `about_to_wait` 中请求重绘
+    pacing: bool,
+    /// 开启了加载画面时与 [`WindowApp::load`] 共享的进度
+    loading: Option<LoadingProgress>,
+    /// 窗口创建的时刻，驱动加载画面的动画
+    loading_started: Option<Instant>,
 }
 
 impl<A: WindowApp> WindowAppHandler<A> {
+    /// 按 [`EventTape::from_env`] 的环境变量录制或回放事件，任何示例都无需修改代码即可录制与回放
     pub fn new(title: &str) -> Self {
         Self {
             app: Arc::new(Mutex::new(None)),
             window: None,
-            title: title.to_string(),
+            window_attributes: Window::default_attributes().with_title(title),
+            clock: FrameClock::default(),
+            tape: EventTape::from_env(),
+            exit_after_frame: false,
+            stats: None,
+            input: InputState::default(),
+            exiting: false,
+            occluded: false,
+            minimized: false,
+            suspended: false,
+            screenshot_key: Some(KeyCode::F12),
+            record_key: Some(KeyCode::F9),
+            executor: None,
+            pacer: FramePacer::default(),
+            pacing: false,
+            loading: None,
+            loading_started: None,
         }
     }
 
+    /// 把事件与每帧时长录制到文件
+    pub fn with_input_recording(mut self, recorder: InputRecorder) -> Self {
+        self.tape.recorder = Some(recorder);
+        self
+    }
+
+    /// 替换由环境变量决定的录制与回放
+    pub fn with_event_tape(mut self, tape: EventTape) -> Self {
+        self.tape = tape;
+        self
+    }
+
+    /// 每帧收集帧时间统计，并按 `stats` 的设置输出，例如
+    /// `.with_frame_stats(FrameStats::default().with_reporter(StatsReporter::WindowTitle))`
+    pub fn with_frame_stats(mut self, stats: FrameStats) -> Self {
+        self.stats = Some(stats);
+        self
+    }
+
+    /// 按下该键时通过 [`WindowApp::gpu_context`] 保存截图，默认为 F12，`None` 关闭
+    pub fn with_screenshot_key(mut self, key: Option<KeyCode>) -> Self {
+        self.screenshot_key = key;
+        self
+    }
+
+    /// 按下该键时开始或结束把画面录制为 PNG 序列，默认为 F9，`None` 关闭
+    pub fn with_record_key(mut self, key: Option<KeyCode>) -> Self {
+        self.record_key = key;
+        self
+    }
+
+    /// 使用指定的执行器运行 [`WindowApp::new`]，替换按 cargo 特性选择的默认执行器
+    pub fn with_executor(mut self, executor: impl Executor + 'static) -> Self {
+        self.executor = Some(Box::new(executor));
+        self
+    }
+
+    /// 把渲染限制在 `fps` 帧每秒以下，与呈现模式无关；默认不限制
+    pub fn with_target_fps(mut self, fps: f64) -> Self {
+        self.pacer.set_target_fps(Some(fps));
+        self
+    }
+
+    pub fn with_frame_pacer(mut self, pacer: FramePacer) -> Self {
+        self.pacer = pacer;
+        self
+    }
+
+    /// 以 [`WindowApp::load`] 代替 `new` 初始化应用，加载期间绘制一个进度条，窗口不会像卡住一样；
+    /// 需要执行器在加载时不阻塞事件循环，[`BlockingExecutor`](crate::executor::BlockingExecutor)
+    /// 下看不到加载画面
+    pub fn with_loading_screen(mut self) -> Self {
+        self.loading = Some(LoadingProgress::default());
+        self
+    }
+
+    /// 窗口已经创建，而 [`WindowApp::new`] 尚未完成；此期间到达的事件会被忽略，
+    /// 开启了加载画面时只处理关闭窗口与大小变化
+    pub fn is_loading(&self) -> bool {
+        self.window.is_some() && self.app.lock().unwrap().is_none()
+    }
+
+    /// 回放录制的输入，回放期间忽略真实的键盘与鼠标输入，帧时长也使用录制的值
+    pub fn with_input_replay(mut self, replay: InputReplay) -> Self {
+        self.tape.replay = Some(replay);
+        self
+    }
+
+    /// 窗口的初始内部大小，不含标题栏与边框
+    pub fn with_inner_size(mut self, size: impl Into<Size>) -> Self {
+        self.window_attributes = self.window_attributes.with_inner_size(size);
+        self
+    }
+
+    pub fn with_min_size(mut self, size: impl Into<Size>) -> Self {
+        self.window_attributes = self.window_attributes.with_min_inner_size(size);
+        self
+    }
+
+    pub fn with_resizable(mut self, resizable: bool) -> Self {
+        self.window_attributes = self.window_attributes.with_resizable(resizable);
+        self
+    }
+
+    /// 是否显示标题栏与边框
+    pub fn with_decorations(mut self, decorations: bool) -> Self {
+        self.window_attributes = self.window_attributes.with_decorations(decorations);
+        self
+    }
+
+    /// 无边框全屏可以用 `Some(Fullscreen::Borderless(None))`，表示当前显示器
+    pub fn with_fullscreen(mut self, fullscreen: Option<Fullscreen>) -> Self {
+        self.window_attributes = self.window_attributes.with_fullscreen(fullscreen);
+        self
+    }
+
+    pub fn with_window_icon(mut self, icon: Option<Icon>) -> Self {
+        self.window_attributes = self.window_attributes.with_window_icon(icon);
+        self
+    }
+
+    /// 以固定步长调用 `update`，每帧按实际经过的时间执行零次或多次，适合物理模拟
+    pub fn with_fixed_timestep(mut self, step: Duration) -> Self {
+        assert!(!step.is_zero(), "fixed timestep must be positive");
+        self.clock.fixed_step = Some(step);
+        self
+    }
+
     pub fn pre_present_notify(&self) {
         if let Some(window) = self.window.as_ref() {
             window.pre_present_notify();
@@ -49,26 +537,286 @@ impl<A: WindowApp> WindowAppHandler<A> {
             window.request_redraw();
         }
     }
+
+    /// 加载期间只处理关闭窗口、大小变化与加载画面的重绘
+    fn loading_event(&mut self, event_loop: &ActiveEventLoop, event: WindowEvent) {
+        let Some(progress) = self.loading.as_ref().filter(|p| p.is_showing()) else {
+            return;
+        };
+        match event {
+            WindowEvent::CloseRequested => event_loop.exit(),
+            WindowEvent::Resized(size) => progress.resize(size),
+            WindowEvent::RedrawRequested => {
+                let seconds = self
+                    .loading_started
+                    .map_or(0.0, |started| started.elapsed().as_secs_f32());
+                if let Some(message) = progress.render(seconds) {
+                    if let Some(window) = self.window.as_ref() {
+                        window.set_title(&format!("{} | {message}", self.window_attributes.title));
+                    }
+                }
+                self.request_redraw();
+            }
+            _ => {}
+        }
+    }
+
+    /// 从挂起中恢复：重新创建 surface 并通知应用，然后恢复重绘
+    fn resume_app(&mut self, event_loop: &ActiveEventLoop, window: &Arc<Window>) {
+        self.suspended = false;
+        let mut guard = self.app.lock().unwrap();
+        let Some(app) = guard.as_mut() else {
+            return;
+        };
+        let recreated = match app.gpu_context().map(GpuContext::resume) {
+            Some(Ok(recreated)) => recreated,
+            Some(Err(e)) => {
+                eprintln!("failed to recreate the surface: {e:#}");
+                exit_app(app, &mut self.tape, &mut self.exiting, event_loop);
+                return;
+            }
+            None => true,
+        };
+        if recreated {
+            app.on_surface_recreated();
+        }
+        // 挂起期间窗口大小可能已经变化，例如屏幕旋转
+        let size = window.inner_size();
+        if size.width > 0 && size.height > 0 {
+            resize_app(app, size);
+        }
+        let visible = self.is_visible();
+        visibility_changed(app, &mut self.clock, Some(window), false, visible);
+    }
+
+    fn is_visible(&self) -> bool {
+        !self.occluded && !self.minimized && !self.suspended
+    }
+
+    /// 截图键或录制键被按下
+    fn is_capture_key(&self, event: &KeyEvent) -> bool {
+        event.state == ElementState::Pressed
+            && !event.repeat
+            && matches!(
+                event.physical_key,
+                PhysicalKey::Code(code)
+                    if Some(code) == self.screenshot_key || Some(code) == self.record_key
+            )
+    }
+}
+
+/// 窗口可见性变化时通知应用；重新可见时恢复计时并重新开始重绘循环
+fn visibility_changed<A: WindowApp>(
+    app: &mut A,
+    clock: &mut FrameClock,
+    window: Option<&Arc<Window>>,
+    was_visible: bool,
+    visible: bool,
+) {
+    if visible == was_visible {
+        return;
+    }
+    app.on_visibility_changed(visible);
+    if visible {
+        clock.pause();
+        if let Some(window) = window {
+            window.request_redraw();
+        }
+    }
+}
+
+fn resize_app<A: WindowApp>(app: &mut A, size: PhysicalSize<u32>) {
+    app.set_window_resized(size);
+    for camera in app.cameras_mut() {
+        camera.resize(size);
+    }
+}
+
+/// 录制中时结束录制，否则开始把画面录制到新的 PNG 序列目录
+fn toggle_recording(gpu: &mut GpuContext) {
+    if gpu.is_recording() {
+        match gpu.stop_recording() {
+            Ok(count) => println!("recorded {count} frames"),
+            Err(e) => eprintln!("{e:#}"),
+        }
+    } else {
+        let dir = recording_dir();
+        match gpu.start_recording(&RecordOutput::PngSequence(dir.clone())) {
+            Ok(()) => println!("recording to {}", dir.display()),
+            Err(e) => eprintln!("recording failed: {e:#}"),
+        }
+    }
+}
+
+/// 刷新事件录制并调用 [`WindowApp::on_exit`]，然后结束事件循环
+fn exit_app<A: WindowApp>(
+    app: &mut A,
+    tape: &mut EventTape,
+    exiting: &mut bool,
+    event_loop: &ActiveEventLoop,
+) {
+    if std::mem::replace(exiting, true) {
+        return;
+    }
+    tape.flush();
+    // 写完正在录制的画面
+    if let Some(gpu) = app.gpu_context().filter(|gpu| gpu.is_recording()) {
+        toggle_recording(gpu);
+    }
+    app.on_exit();
+    event_loop.exit();
 }
 
-impl<A: WindowApp> ApplicationHandler for WindowAppHandler<A> {
+/// 先创建 GPU 上下文显示加载画面，再调用 [`WindowApp::load`]；加载画面创建失败时退回 `new`
+async fn load_app<A: WindowApp>(window: Arc<Window>, progress: LoadingProgress, title: &str) -> A {
+    let attached = GpuContext::new(window.clone(), GpuContextOptions::for_app::<A>())
+        .await
+        .and_then(|gpu| progress.attach(gpu));
+    let loading = match attached {
+        Ok(()) => Loading::new(progress),
+        Err(e) => {
+            eprintln!("failed to show loading screen: {e:#}");
+            None
+        }
+    };
+    let Some(loading) = loading else {
+        return A::new(window).await;
+    };
+    window.request_redraw();
+    let app = A::load(window.clone(), loading).await;
+    window.set_title(title);
+    app
+}
+
+/// 把回放的事件派发给应用，焦点与光标离开只影响输入状态
+fn dispatch_input<A: WindowApp>(app: &mut A, window: Option<&Arc<Window>>, event: &InputEvent) {
+    let _ = match event {
+        InputEvent::Key(input) => app.key_input(input),
+        InputEvent::MouseButton(state, button) => app.mouse_click(*state, *button),
+        InputEvent::Wheel(delta) => app.mouse_wheel(*delta, TouchPhase::Moved),
+        InputEvent::Cursor(position) => app.cursor_move(*position),
+        InputEvent::MouseMotion(dx, dy) => {
+            app.device_input(&DeviceEvent::MouseMotion { delta: (*dx, *dy) })
+        }
+        InputEvent::Resized(size) => {
+            // 立即生效时不一定会再收到 Resized 事件，其余情况由随后真实的 Resized 事件处理
+            if let Some(size) = window.and_then(|window| window.request_inner_size(*size)) {
+                if size.width > 0 && size.height > 0 {
+                    resize_app(app, size);
+                }
+            }
+            true
+        }
+        InputEvent::ScaleFactor(scale_factor) => {
+            if let Some(gpu) = app.gpu_context() {
+                gpu.set_scale_factor(*scale_factor);
+            }
+            app.scale_factor_changed(*scale_factor);
+            true
+        }
+        InputEvent::HoveredFile(path) => {
+            app.file_hovered(path.clone());
+            true
+        }
+        InputEvent::DroppedFile(path) => {
+            app.file_hovered(None);
+            app.file_dropped(path.clone());
+            true
+        }
+        InputEvent::Focused(_) | InputEvent::CursorLeft => false,
+    };
+}
+
+impl<A: WindowApp + 'static> ApplicationHandler for WindowAppHandler<A> {
     fn resumed(&mut self, event_loop: &ActiveEventLoop) {
-        if self.app.lock().unwrap().deref().is_some() {
+        if let Some(window) = self.window.clone() {
+            if self.suspended {
+                self.resume_app(event_loop, &window);
+            }
             return;
         }
 
-        let rt = Runtime::new().unwrap();
-
-        let window_attributes = Window::default_attributes().with_title(&self.title);
+        let window_attributes = self
+            .window_attributes
+            .clone()
+            .with_transparent(A::transparent());
+        // 在浏览器中由 winit 创建 canvas 并添加到页面的 body 中
+        #[cfg(target_arch = "wasm32")]
+        let window_attributes = {
+            use winit::platform::web::WindowAttributesExtWebSys;
+            window_attributes.with_append(true)
+        };
         let window = Arc::new(event_loop.create_window(window_attributes).unwrap());
 
-        let wgpu_app = rt.block_on(A::new(window.clone()));
+        // 不阻塞事件循环等待适配器与设备，初始化完成前到达的事件会被忽略
+        self.window.replace(window.clone());
+        let app = self.app.clone();
+        let loading = self.loading.clone();
+        let title = self.window_attributes.title.clone();
+        self.loading_started = Some(Instant::now());
+        self.executor
+            .get_or_insert_with(default_executor)
+            .spawn(Box::pin(async move {
+                let wgpu_app = match loading {
+                    Some(progress) => load_app(window.clone(), progress, &title).await,
+                    None => A::new(window.clone()).await,
+                };
+                app.lock().unwrap().replace(wgpu_app);
+                window.request_redraw();
+            }));
+    }
 
-        self.app.lock().unwrap().deref_mut().replace(wgpu_app);
-        self.window.replace(window);
+    /// Android 在切到后台时销毁原生窗口，surface 必须在这个事件返回前丢弃
+    fn suspended(&mut self, _event_loop: &ActiveEventLoop) {
+        if self.window.is_none() || self.suspended {
+            return;
+        }
+        let was_visible = self.is_visible();
+        self.suspended = true;
+        self.pacing = false;
+        let mut guard = self.app.lock().unwrap();
+        let Some(app) = guard.as_mut() else {
+            return;
+        };
+        if let Some(gpu) = app.gpu_context() {
+            gpu.suspend();
+        }
+        app.on_suspended();
+        visibility_changed(
+            app,
+            &mut self.clock,
+            self.window.as_ref(),
+            was_visible,
+            false,
+        );
     }
 
-    fn suspended(&mut self, _event_loop: &ActiveEventLoop) {}
+    fn about_to_wait(&mut self, event_loop: &ActiveEventLoop) {
+        if self.pacing {
+            // 其他事件也会唤醒事件循环，没到时间时继续休眠，最后一小段忙等
+            match self.pacer.wake_time(Instant::now()) {
+                Some(wake) => event_loop.set_control_flow(ControlFlow::WaitUntil(wake)),
+                None => {
+                    self.pacer.spin_until_due();
+                    self.pacing = false;
+                    event_loop.set_control_flow(ControlFlow::Wait);
+                    self.request_redraw();
+                }
+            }
+        }
+        if !self.is_loading() {
+            return;
+        }
+        if let Some(executor) = self.executor.as_mut() {
+            executor.poll();
+        }
+        // 加载期间定期唤醒事件循环推进初始化，加载完成后恢复为等待事件
+        event_loop.set_control_flow(if self.is_loading() {
+            ControlFlow::WaitUntil(Instant::now() + Duration::from_millis(10))
+        } else {
+            ControlFlow::Wait
+        });
+    }
 
     fn window_event(
         &mut self,
@@ -77,39 +825,270 @@ impl<A: WindowApp> ApplicationHandler for WindowAppHandler<A> {
         event: WindowEvent,
     ) {
         let mut guard = self.app.lock().unwrap();
-        let app = guard.as_mut().unwrap();
+        let Some(app) = guard.as_mut() else {
+            drop(guard);
+            self.loading_event(event_loop, event);
+            return;
+        };
+        // 退出请求之后仍可能收到事件，不再交给应用
+        if self.exiting {
+            return;
+        }
+        // 界面消耗的输入不再交给应用
+        let is_input = matches!(
+            event,
+            WindowEvent::KeyboardInput { .. }
+                | WindowEvent::MouseInput { .. }
+                | WindowEvent::MouseWheel { .. }
+                | WindowEvent::CursorMoved { .. }
+        );
+        if !self.tape.is_replaying() && app.window_event(&event) && is_input {
+            return;
+        }
 
         match event {
             WindowEvent::CloseRequested => {
-                event_loop.exit();
+                exit_app(app, &mut self.tape, &mut self.exiting, event_loop);
             }
+            // 截图键与录制键在回放期间同样有效，且不会被录制
+            WindowEvent::KeyboardInput { event, .. } if self.is_capture_key(&event) => {
+                let screenshot = matches!(
+                    event.physical_key,
+                    PhysicalKey::Code(code) if Some(code) == self.screenshot_key
+                );
+                match app.gpu_context() {
+                    None => eprintln!("screenshots and recording need WindowApp::gpu_context"),
+                    Some(gpu) if screenshot => gpu.request_screenshot(screenshot_path()),
+                    Some(gpu) => toggle_recording(gpu),
+                }
+            }
+            // 回放期间忽略真实的输入与文件拖放，以免干扰录制的结果；
+            // 窗口大小与缩放仍需处理，回放的大小变化正是通过真实的 Resized 事件生效
+            WindowEvent::KeyboardInput { .. }
+            | WindowEvent::MouseInput { .. }
+            | WindowEvent::MouseWheel { .. }
+            | WindowEvent::CursorMoved { .. }
+            | WindowEvent::CursorLeft { .. }
+            | WindowEvent::Focused(_)
+            | WindowEvent::HoveredFile(_)
+            | WindowEvent::HoveredFileCancelled
+            | WindowEvent::DroppedFile(_)
+                if self.tape.is_replaying() => {}
             WindowEvent::Resized(physical_size) => {
-                if physical_size.width == 0 || physical_size.height == 0 {
-                    // 处理最小化窗口的事件
-                } else {
-                    app.set_window_resized(physical_size);
+                if !self.tape.is_replaying() {
+                    self.tape
+                        .observe(&mut self.input, &InputEvent::Resized(physical_size));
+                }
+                let was_visible = self.is_visible();
+                // 部分平台最小化时报告零尺寸而不是 Occluded
+                self.minimized = physical_size.width == 0 || physical_size.height == 0;
+                if !self.minimized {
+                    resize_app(app, physical_size);
                 }
+                let visible = self.is_visible();
+                visibility_changed(
+                    app,
+                    &mut self.clock,
+                    self.window.as_ref(),
+                    was_visible,
+                    visible,
+                );
+            }
+            WindowEvent::HoveredFile(path) => {
+                self.tape.observe(
+                    &mut self.input,
+                    &InputEvent::HoveredFile(Some(path.clone())),
+                );
+                app.file_hovered(Some(path));
+            }
+            WindowEvent::HoveredFileCancelled => {
+                self.tape
+                    .observe(&mut self.input, &InputEvent::HoveredFile(None));
+                app.file_hovered(None);
+            }
+            WindowEvent::DroppedFile(path) => {
+                self.tape
+                    .observe(&mut self.input, &InputEvent::DroppedFile(path.clone()));
+                app.file_hovered(None);
+                app.file_dropped(path);
+            }
+            WindowEvent::ScaleFactorChanged { scale_factor, .. } => {
+                if !self.tape.is_replaying() {
+                    self.tape
+                        .observe(&mut self.input, &InputEvent::ScaleFactor(scale_factor));
+                }
+                if let Some(gpu) = app.gpu_context() {
+                    gpu.set_scale_factor(scale_factor);
+                }
+                app.scale_factor_changed(scale_factor);
+            }
+            WindowEvent::Occluded(occluded) => {
+                let was_visible = self.is_visible();
+                self.occluded = occluded;
+                let visible = self.is_visible();
+                visibility_changed(
+                    app,
+                    &mut self.clock,
+                    self.window.as_ref(),
+                    was_visible,
+                    visible,
+                );
             }
             WindowEvent::KeyboardInput { event, .. } => {
-                let _ = app.keyboard_input(&event);
+                let input = KeyInput::from(&event);
+                self.tape
+                    .observe(&mut self.input, &InputEvent::Key(input.clone()));
+                if !app.keyboard_input(&event) {
+                    let _ = app.key_input(&input);
+                }
             }
+            WindowEvent::MouseInput { state, button, .. } => {
+                self.tape
+                    .observe(&mut self.input, &InputEvent::MouseButton(state, button));
+                let _ = app.mouse_click(state, button);
+            }
+            WindowEvent::MouseWheel { delta, phase, .. } => {
+                self.tape
+                    .observe(&mut self.input, &InputEvent::Wheel(delta));
+                let _ = app.mouse_wheel(delta, phase);
+            }
+            WindowEvent::CursorMoved { position, .. } => {
+                self.tape
+                    .observe(&mut self.input, &InputEvent::Cursor(position));
+                let _ = app.cursor_move(position);
+            }
+            WindowEvent::CursorLeft { .. } => {
+                self.tape.observe(&mut self.input, &InputEvent::CursorLeft);
+            }
+            WindowEvent::Focused(focused) => {
+                self.tape
+                    .observe(&mut self.input, &InputEvent::Focused(focused));
+            }
+            // 不可见时不再请求重绘，重绘循环在重新可见时恢复
+            WindowEvent::RedrawRequested if !self.is_visible() => {}
             WindowEvent::RedrawRequested => {
-                app.update();
+                self.pacer.begin_frame(Instant::now());
+                let mut delta = self.clock.measure();
+                // 统计使用实际经过的时间，回放时也是如此
+                if let Some(stats) = self.stats.as_mut() {
+                    if stats.record(delta) {
+                        // 开启了帧率限制时附上这段时间的节拍统计
+                        let mut summary = stats.summary();
+                        if self.pacer.target_fps().is_some() {
+                            summary = format!("{summary} | {}", self.pacer.stats());
+                            self.pacer.reset_stats();
+                        }
+                        if let Some(extra) = app.stats_summary().filter(|s| !s.is_empty()) {
+                            summary = format!("{summary} | {extra}");
+                        }
+                        match stats.reporter {
+                            StatsReporter::None => {}
+                            StatsReporter::WindowTitle => {
+                                if let Some(window) = self.window.as_ref() {
+                                    window.set_title(&format!(
+                                        "{} | {summary}",
+                                        self.window_attributes.title,
+                                    ));
+                                }
+                            }
+                            StatsReporter::Log => println!("{summary}"),
+                        }
+                    }
+                }
+                match self.tape.replay.as_mut().map(InputReplay::next_frame) {
+                    Some(Some(frame)) => {
+                        for event in &frame.events {
+                            self.input.handle(event);
+                            dispatch_input(app, self.window.as_ref(), event);
+                        }
+                        delta = frame.delta;
+                    }
+                    Some(None) => {
+                        eprintln!("input replay finished");
+                        let replay = self.tape.replay.take().unwrap();
+                        if let Some(path) = replay.screenshot_when_finished {
+                            // 以零时长再绘制一帧，截图与最后一帧回放后的状态一致
+                            delta = Duration::ZERO;
+                            match app.gpu_context() {
+                                Some(gpu) => gpu.request_screenshot(path),
+                                None => eprintln!("replay screenshots need WindowApp::gpu_context"),
+                            }
+                            self.exit_after_frame = replay.exit_when_finished;
+                        } else if replay.exit_when_finished {
+                            exit_app(app, &mut self.tape, &mut self.exiting, event_loop);
+                            return;
+                        }
+                    }
+                    None => {}
+                }
+                self.tape.record_frame(delta, self.window.as_ref());
+                for (i, time) in self.clock.step(delta).into_iter().enumerate() {
+                    app.update_with_input(time, &self.input);
+                    if i == 0 {
+                        self.input.end_frame();
+                    }
+                }
+                if app.should_exit() {
+                    exit_app(app, &mut self.tape, &mut self.exiting, event_loop);
+                    return;
+                }
 
                 self.pre_present_notify();
 
                 match app.render() {
                     Ok(_) => {}
-                    // 当展示平面的上下文丢失，就需重新配置
-                    Err(wgpu::SurfaceError::Lost) => eprintln!("Surface is lost"),
-                    // 所有其他错误（过期、超时等）应在下一帧解决
+                    // GPU 重置或窗口移到其他显示器后 surface 需要重新配置，下一帧再绘制
+                    Err(e @ (wgpu::SurfaceError::Lost | wgpu::SurfaceError::Outdated)) => {
+                        eprintln!("{e}, reconfiguring surface");
+                        if let Some(gpu) = app.gpu_context() {
+                            gpu.reconfigure();
+                        }
+                        app.surface_lost();
+                    }
+                    Err(wgpu::SurfaceError::OutOfMemory) => {
+                        eprintln!("out of memory while acquiring the surface texture, exiting");
+                        exit_app(app, &mut self.tape, &mut self.exiting, event_loop);
+                        return;
+                    }
+                    // 超时等错误跳过这一帧即可
                     Err(e) => eprintln!("{e:?}"),
                 }
+                if self.exit_after_frame {
+                    exit_app(app, &mut self.tape, &mut self.exiting, event_loop);
+                    return;
+                }
 
                 // 除非我们手动请求，RedrawRequested 将只会触发一次。
-                self.request_redraw();
+                // 在浏览器中 winit 通过 requestAnimationFrame 调度这次重绘；
+                // 开启了帧率限制时由 about_to_wait 等到下一帧的时刻再请求
+                if self.pacer.wait_until(Instant::now()).is_some() {
+                    self.pacing = true;
+                } else {
+                    self.request_redraw();
+                }
             }
             _ => (),
         }
     }
+
+    fn device_event(
+        &mut self,
+        _event_loop: &ActiveEventLoop,
+        _device_id: DeviceId,
+        event: DeviceEvent,
+    ) {
+        // 窗口创建之前也可能收到设备事件
+        let mut guard = self.app.lock().unwrap();
+        let Some(app) = guard.as_mut() else {
+            return;
+        };
+        if let DeviceEvent::MouseMotion { delta: (dx, dy) } = event {
+            if self.tape.is_replaying() {
+                return;
+            }
+            self.tape
+                .observe(&mut self.input, &InputEvent::MouseMotion(dx, dy));
+        }
+        let _ = app.device_input(&event);
+    }
 }
@@ -0,0 +1,178 @@
+use wgpu::util::DeviceExt;
+
+use crate::{post::begin_fullscreen_pass, shader::ShaderLibrary, uniform::GpuUniform};
+
+/// 每帧在场景之前铺满 surface 的背景，见 [`GpuContext::background`](crate::context::GpuContext::background)
+#[derive(Debug, Clone)]
+pub enum Background {
+    Color(wgpu::Color),
+    /// 从上到下的竖直渐变，颜色与清屏颜色一样是线性值
+    Gradient {
+        top: wgpu::Color,
+        bottom: wgpu::Color,
+    },
+    /// 全屏绘制的天空，由 [`Sky::background`](crate::sky::Sky::background) 创建；
+    /// 每帧仍需调用 `Sky::update` 更新视角
+    Skybox(BackgroundDraw),
+    /// 清为全透明，用于 [`WindowApp::transparent`](crate::app::WindowApp::transparent) 的透明窗口
+    Transparent,
+}
+
+impl Default for Background {
+    fn default() -> Self {
+        Background::Color(wgpu::Color::BLACK)
+    }
+}
+
+impl Background {
+    /// 绘制到 surface 以外的目标时使用的清屏颜色，渐变与天空取黑色
+    pub fn clear_color(&self) -> wgpu::Color {
+        match self {
+            Background::Color(color) => *color,
+            Background::Transparent => wgpu::Color::TRANSPARENT,
+            Background::Gradient { .. } | Background::Skybox(_) => wgpu::Color::BLACK,
+        }
+    }
+
+    /// 绘制到 surface 的渲染通道对颜色附件的加载方式；渐变与天空已经由单独的通道绘制，保留其内容
+    pub fn load_op(&self) -> wgpu::LoadOp<wgpu::Color> {
+        match self {
+            Background::Gradient { .. } | Background::Skybox(_) => wgpu::LoadOp::Load,
+            _ => wgpu::LoadOp::Clear(self.clear_color()),
+        }
+    }
+}
+
+/// 作为背景的全屏绘制：一条不使用顶点缓冲的管线与其 group 0 的绑定
+///
+/// 管线的颜色目标须为 surface 的格式，且不能带深度，背景在单独的渲染通道中绘制。
+#[derive(Debug, Clone)]
+pub struct BackgroundDraw {
+    pub pipeline: wgpu::RenderPipeline,
+    pub bind_group: wgpu::BindGroup,
+}
+
+impl BackgroundDraw {
+    pub(crate) fn draw(&self, encoder: &mut wgpu::CommandEncoder, view: &wgpu::TextureView) {
+        let mut pass = begin_fullscreen_pass(encoder, "Background Pass", view);
+        pass.set_pipeline(&self.pipeline);
+        pass.set_bind_group(0, &self.bind_group, &[]);
+        pass.draw(0..3, 0..1);
+    }
+}
+
+/// 与 `background.wgsl` 中的 `GradientUniform` 保持一致
+#[repr(C)]
+#[derive(Debug, Copy, Clone, GpuUniform)]
+pub struct GradientUniform {
+    top: [f32; 4],
+    bottom: [f32; 4],
+}
+
+impl GradientUniform {
+    fn new(top: wgpu::Color, bottom: wgpu::Color) -> Self {
+        let rgba = |c: wgpu::Color| [c.r as f32, c.g as f32, c.b as f32, c.a as f32];
+        Self {
+            top: rgba(top),
+            bottom: rgba(bottom),
+        }
+    }
+}
+
+/// [`Background::Gradient`] 的管线，由 [`GpuContext`](crate::context::GpuContext) 在第一次用到时创建
+pub(crate) struct GradientBackground {
+    buffer: wgpu::Buffer,
+    draw: BackgroundDraw,
+}
+
+impl GradientBackground {
+    pub(crate) fn new(device: &wgpu::Device, format: wgpu::TextureFormat) -> Self {
+        let buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Gradient Uniform Buffer"),
+            contents: bytemuck::bytes_of(&GradientUniform::new(
+                wgpu::Color::BLACK,
+                wgpu::Color::BLACK,
+            )),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            }],
+            label: Some("gradient_bind_group_layout"),
+        });
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: &bind_group_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: buffer.as_entire_binding(),
+            }],
+            label: Some("gradient_bind_group"),
+        });
+
+        let shader = ShaderLibrary::new()
+            .create_shader_module(
+                device,
+                "Background Shader",
+                include_str!("../shaders/background.wgsl"),
+            )
+            .expect("built-in background shader");
+        let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Gradient Pipeline Layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Gradient Pipeline"),
+            layout: Some(&layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                compilation_options: Default::default(),
+                entry_point: Some("vs_main"),
+                buffers: &[],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                compilation_options: Default::default(),
+                entry_point: Some("fs_main"),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format,
+                    blend: None,
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+            cache: None,
+        });
+
+        Self {
+            buffer,
+            draw: BackgroundDraw {
+                pipeline,
+                bind_group,
+            },
+        }
+    }
+
+    pub(crate) fn draw(
+        &self,
+        queue: &wgpu::Queue,
+        encoder: &mut wgpu::CommandEncoder,
+        view: &wgpu::TextureView,
+        top: wgpu::Color,
+        bottom: wgpu::Color,
+    ) {
+        GradientUniform::new(top, bottom).write_to(queue, &self.buffer);
+        self.draw.draw(encoder, view);
+    }
+}
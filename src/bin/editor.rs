@@ -0,0 +1,860 @@
+//! 简单的场景编辑器：`cargo run --bin editor --features egui -- [scene.txt]`
+//!
+//! 场景文件不存在时使用默认场景，F2 保存回同一路径，F3 重新加载。
+//! 场景文件存在但无法读取时同样从默认场景开始，但在 F3 重新加载成功之前拒绝保存，以免覆盖原文件。
+//! 检查面板中可以选择对象，编辑立方体的变换与材质颜色，以及太阳的方向、颜色、强度与阴影参数。
+//! `--list-adapters` 列出可用的 GPU，再用环境变量 `WGPU_DANCE_ADAPTER` 按名称选择。
+
+use std::{fmt::Write as _, path::PathBuf, sync::Arc};
+
+use anyhow::{bail, Context};
+use bytemuck::{Pod, Zeroable};
+use wgpu::util::DeviceExt;
+use wgpu_dance::{
+    app::{FrameTime, WindowApp, WindowAppHandler},
+    camera::{Camera, CameraBundle, KeyboardController, Projection},
+    context::{enumerate_adapters, GpuContext, GpuContextOptions},
+    debug_draw::DebugDraw,
+    egui_layer::EguiLayer,
+    gizmo::{GizmoMode, TransformGizmo},
+    instance::{Instance, MotionInstanceBuffer, MotionInstanceRaw},
+    light::{DirectionalLight, DirectionalLightBundle, ShadowSettings},
+    model::RenderVertex,
+    pipeline::{PipelineBuilder, ReflectedShader},
+    ray::Ray,
+    shader::ShaderLibrary,
+    texture::{DepthConvention, Texture},
+};
+use winit::{
+    dpi::{LogicalSize, PhysicalPosition, PhysicalSize},
+    event::{ElementState, KeyEvent, MouseButton, MouseScrollDelta, TouchPhase, WindowEvent},
+    event_loop::EventLoop,
+    keyboard::{KeyCode, PhysicalKey},
+    window::Window,
+};
+
+const DEFAULT_SCENE_PATH: &str = "scene.txt";
+const GRID_HALF_SIZE: i32 = 10;
+/// 单位立方体的包围球半径
+const CUBE_RADIUS: f32 = 0.87;
+/// 太阳手柄到原点的距离，拖动手柄即改变光照方向
+const SUN_HANDLE_DISTANCE: f32 = 6.0;
+const SUN_HANDLE_RADIUS: f32 = 0.4;
+/// 按 C 键依次切换的材质颜色
+const PALETTE: [glam::Vec3; 6] = [
+    glam::vec3(0.7, 0.65, 0.6),
+    glam::vec3(0.8, 0.25, 0.2),
+    glam::vec3(0.25, 0.6, 0.3),
+    glam::vec3(0.2, 0.4, 0.8),
+    glam::vec3(0.85, 0.7, 0.2),
+    glam::vec3(0.15, 0.15, 0.15),
+];
+
+#[derive(Debug, Copy, Clone)]
+struct SceneObject {
+    transform: Instance,
+    albedo: glam::Vec3,
+}
+
+/// 编辑器的场景：若干立方体与一个平行光
+///
+/// 文件为纯文本，每行一个条目，`#` 开头的行为注释：
+///
+/// ```text
+/// sun <方向 x y z> <颜色 r g b> <强度>
+/// shadow <固定偏移> <斜率偏移> <法线外推> <PCF 半径> <级联混合宽度>
+/// cube <位置 x y z> <旋转 x y z w> <缩放 x y z> <颜色 r g b>
+/// ```
+#[derive(Debug, Clone)]
+struct Scene {
+    objects: Vec<SceneObject>,
+    sun: DirectionalLight,
+}
+
+impl Default for Scene {
+    fn default() -> Self {
+        let objects = (-1..=1)
+            .map(|i| SceneObject {
+                transform: Instance {
+                    position: glam::vec3(i as f32 * 3.0, 0.5, 0.0),
+                    ..Default::default()
+                },
+                albedo: PALETTE[(i + 1) as usize],
+            })
+            .collect();
+        Self {
+            objects,
+            sun: DirectionalLight::default(),
+        }
+    }
+}
+
+impl Scene {
+    fn load(path: &std::path::Path) -> anyhow::Result<Self> {
+        let text = std::fs::read_to_string(path)
+            .with_context(|| format!("failed to read scene {}", path.display()))?;
+        Self::parse(&text).with_context(|| format!("failed to parse scene {}", path.display()))
+    }
+
+    fn parse(text: &str) -> anyhow::Result<Self> {
+        let mut objects = Vec::new();
+        let mut sun = None;
+        let mut shadow = None;
+        for (line_no, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let mut words = line.split_whitespace();
+            let kind = words.next().unwrap_or_default();
+            let values = words
+                .map(str::parse::<f32>)
+                .collect::<Result<Vec<_>, _>>()
+                .with_context(|| format!("line {}: invalid number", line_no + 1))?;
+            match (kind, values.as_slice()) {
+                ("sun", &[dx, dy, dz, r, g, b, intensity]) => {
+                    sun = Some(DirectionalLight {
+                        direction: glam::vec3(dx, dy, dz).normalize_or(glam::Vec3::Y),
+                        color: glam::vec3(r, g, b),
+                        intensity,
+                        ..Default::default()
+                    });
+                }
+                (
+                    "shadow",
+                    &[constant_bias, slope_bias, normal_offset, pcf_radius, cascade_blend],
+                ) => {
+                    shadow = Some(ShadowSettings {
+                        constant_bias,
+                        slope_bias,
+                        normal_offset,
+                        pcf_radius,
+                        cascade_blend,
+                    });
+                }
+                ("cube", &[px, py, pz, qx, qy, qz, qw, sx, sy, sz, r, g, b]) => {
+                    objects.push(SceneObject {
+                        transform: Instance {
+                            position: glam::vec3(px, py, pz),
+                            rotation: glam::quat(qx, qy, qz, qw).normalize(),
+                            scale: glam::vec3(sx, sy, sz),
+                        },
+                        albedo: glam::vec3(r, g, b),
+                    });
+                }
+                _ => bail!("line {}: unrecognized entry `{line}`", line_no + 1),
+            }
+        }
+        if objects.is_empty() {
+            bail!("scene contains no objects");
+        }
+        let mut sun = sun.unwrap_or_default();
+        if let Some(shadow) = shadow {
+            sun.shadow = shadow;
+        }
+        Ok(Self { objects, sun })
+    }
+
+    fn albedo_data(&self) -> Vec<[f32; 4]> {
+        self.objects
+            .iter()
+            .map(|o| o.albedo.extend(1.0).to_array())
+            .collect()
+    }
+
+    fn save(&self, path: &std::path::Path) -> anyhow::Result<()> {
+        let mut text = String::from("# wgpu_dance editor scene\n");
+        let sun = &self.sun;
+        let [dx, dy, dz] = sun.direction.to_array();
+        let [r, g, b] = sun.color.to_array();
+        writeln!(text, "sun {dx} {dy} {dz} {r} {g} {b} {}", sun.intensity)?;
+        let shadow = &sun.shadow;
+        writeln!(
+            text,
+            "shadow {} {} {} {} {}",
+            shadow.constant_bias,
+            shadow.slope_bias,
+            shadow.normal_offset,
+            shadow.pcf_radius,
+            shadow.cascade_blend
+        )?;
+        for object in &self.objects {
+            let t = &object.transform;
+            let [px, py, pz] = t.position.to_array();
+            let [qx, qy, qz, qw] = t.rotation.to_array();
+            let [sx, sy, sz] = t.scale.to_array();
+            let [r, g, b] = object.albedo.to_array();
+            writeln!(
+                text,
+                "cube {px} {py} {pz} {qx} {qy} {qz} {qw} {sx} {sy} {sz} {r} {g} {b}"
+            )?;
+        }
+        std::fs::write(path, text)
+            .with_context(|| format!("failed to write scene {}", path.display()))
+    }
+}
+
+/// 立方体顶点，使用 4、5 号位置，避开实例数据占用的位置
+#[repr(C)]
+#[derive(Copy, Clone, Debug)]
+struct CubeVertex {
+    position: [f32; 3],
+    normal: [f32; 3],
+}
+
+unsafe impl Zeroable for CubeVertex {}
+unsafe impl Pod for CubeVertex {}
+
+impl RenderVertex for CubeVertex {
+    fn buffer_layout_desc<'a>() -> wgpu::VertexBufferLayout<'a> {
+        const ATTRIBUTES: [wgpu::VertexAttribute; 2] = wgpu::vertex_attr_array![
+            4 => Float32x3,
+            5 => Float32x3,
+        ];
+        wgpu::VertexBufferLayout {
+            array_stride: std::mem::size_of::<CubeVertex>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Vertex,
+            attributes: &ATTRIBUTES,
+        }
+    }
+}
+
+/// 每个实例的颜色，使用 6 号位置
+fn albedo_buffer_layout_desc<'a>() -> wgpu::VertexBufferLayout<'a> {
+    const ATTRIBUTES: [wgpu::VertexAttribute; 1] = wgpu::vertex_attr_array![6 => Float32x4];
+    wgpu::VertexBufferLayout {
+        array_stride: std::mem::size_of::<[f32; 4]>() as wgpu::BufferAddress,
+        step_mode: wgpu::VertexStepMode::Instance,
+        attributes: &ATTRIBUTES,
+    }
+}
+
+/// 边长为 1、中心在原点的立方体
+fn cube() -> (Vec<CubeVertex>, Vec<u32>) {
+    let mut vertices = Vec::new();
+    let mut indices = Vec::new();
+    for normal in [
+        glam::Vec3::X,
+        glam::Vec3::NEG_X,
+        glam::Vec3::Y,
+        glam::Vec3::NEG_Y,
+        glam::Vec3::Z,
+        glam::Vec3::NEG_Z,
+    ] {
+        let (u, v) = normal.any_orthonormal_pair();
+        let base = vertices.len() as u32;
+        for (a, b) in [(-1.0, -1.0), (1.0, -1.0), (1.0, 1.0), (-1.0, 1.0)] {
+            vertices.push(CubeVertex {
+                position: ((normal + u * a + v * b) * 0.5).to_array(),
+                normal: normal.to_array(),
+            });
+        }
+        indices.extend([base, base + 1, base + 2, base, base + 2, base + 3]);
+    }
+    (vertices, indices)
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+enum Selection {
+    Object(usize),
+    Sun,
+}
+
+/// 一行标签加三个拖动框，返回是否有修改
+fn drag_vec3(ui: &mut egui::Ui, label: &str, value: &mut glam::Vec3, speed: f32) -> bool {
+    ui.horizontal(|ui| {
+        ui.label(label);
+        let mut changed = false;
+        for component in value.as_mut() {
+            changed |= ui
+                .add(egui::DragValue::new(component).speed(speed))
+                .changed();
+        }
+        changed
+    })
+    .inner
+}
+
+/// 检查面板：选择对象，编辑立方体的变换与材质颜色，或太阳的方向、颜色、强度与阴影参数
+fn inspector(ui: &mut egui::Ui, scene: &mut Scene, selected: &mut Option<Selection>) {
+    ui.horizontal_wrapped(|ui| {
+        for i in 0..scene.objects.len() {
+            ui.selectable_value(selected, Some(Selection::Object(i)), format!("cube {i}"));
+        }
+        ui.selectable_value(selected, Some(Selection::Sun), "sun");
+    });
+    ui.separator();
+    match *selected {
+        Some(Selection::Object(i)) => {
+            let object = &mut scene.objects[i];
+            let transform = &mut object.transform;
+            drag_vec3(ui, "position", &mut transform.position, 0.05);
+            // 以 YXZ 顺序的欧拉角（度）编辑旋转
+            let (y, x, z) = transform.rotation.to_euler(glam::EulerRot::YXZ);
+            let mut euler = glam::vec3(x, y, z) * 180.0 / std::f32::consts::PI;
+            if drag_vec3(ui, "rotation", &mut euler, 0.5) {
+                let euler = euler * std::f32::consts::PI / 180.0;
+                transform.rotation =
+                    glam::Quat::from_euler(glam::EulerRot::YXZ, euler.y, euler.x, euler.z);
+            }
+            if drag_vec3(ui, "scale", &mut transform.scale, 0.01) {
+                transform.scale = transform.scale.max(glam::Vec3::splat(0.01));
+            }
+            // 颜色在线性空间编辑，与着色器中的 albedo 一致
+            let mut albedo = object.albedo.to_array();
+            ui.horizontal(|ui| {
+                ui.label("albedo");
+                if ui.color_edit_button_rgb(&mut albedo).changed() {
+                    object.albedo = albedo.into();
+                }
+            });
+        }
+        Some(Selection::Sun) => {
+            let sun = &mut scene.sun;
+            let mut direction = sun.direction;
+            if drag_vec3(ui, "direction", &mut direction, 0.01) {
+                sun.direction = direction.normalize_or(sun.direction);
+            }
+            let mut color = sun.color.to_array();
+            ui.horizontal(|ui| {
+                ui.label("color");
+                if ui.color_edit_button_rgb(&mut color).changed() {
+                    sun.color = color.into();
+                }
+            });
+            ui.add(egui::Slider::new(&mut sun.intensity, 0.0..=10.0).text("intensity"));
+            ui.collapsing("shadow", |ui| {
+                let shadow = &mut sun.shadow;
+                ui.add(
+                    egui::Slider::new(&mut shadow.constant_bias, 0.0..=0.01)
+                        .logarithmic(true)
+                        .text("constant bias"),
+                );
+                ui.add(
+                    egui::Slider::new(&mut shadow.slope_bias, 0.0..=0.05)
+                        .logarithmic(true)
+                        .text("slope bias"),
+                );
+                ui.add(
+                    egui::Slider::new(&mut shadow.normal_offset, 0.0..=0.2).text("normal offset"),
+                );
+                ui.add(egui::Slider::new(&mut shadow.pcf_radius, 0.0..=4.0).text("PCF radius"));
+                ui.add(
+                    egui::Slider::new(&mut shadow.cascade_blend, 0.0..=0.5).text("cascade blend"),
+                );
+            });
+        }
+        None => {
+            ui.label("click a cube or the sun handle to edit it");
+        }
+    }
+}
+
+struct Editor {
+    gpu: GpuContext,
+
+    depth_texture: Texture,
+
+    camera: CameraBundle,
+    light: DirectionalLightBundle,
+    debug: DebugDraw,
+    egui: EguiLayer,
+
+    render_pipeline: wgpu::RenderPipeline,
+    vertex_buffer: wgpu::Buffer,
+    index_buffer: wgpu::Buffer,
+    index_count: u32,
+    instances: MotionInstanceBuffer,
+    albedo_buffer: wgpu::Buffer,
+
+    path: PathBuf,
+    scene: Scene,
+    /// `path` 处的场景文件读取失败后为 `false`，此时保存会用当前场景覆盖用户的文件
+    can_save: bool,
+    gizmo: TransformGizmo,
+    selected: Option<Selection>,
+    cursor: glam::Vec2,
+    /// 最近一次点击的拾取射线与命中点、法线，用于检查反投影是否正确
+    last_pick: Option<(Ray, Option<(glam::Vec3, glam::Vec3)>)>,
+    show_pick_ray: bool,
+}
+
+impl Editor {
+    fn cursor_ray(&self) -> Ray {
+        self.camera.state.screen_ray(self.cursor, self.gpu.size())
+    }
+
+    fn sun_handle(&self) -> Instance {
+        Instance {
+            position: self.scene.sun.direction * SUN_HANDLE_DISTANCE,
+            ..Default::default()
+        }
+    }
+
+    /// 选中对象对应的变换，太阳用手柄的位置表示
+    fn selected_transform(&self) -> Option<Instance> {
+        match self.selected? {
+            Selection::Object(i) => Some(self.scene.objects[i].transform),
+            Selection::Sun => Some(self.sun_handle()),
+        }
+    }
+
+    /// 射线最先穿过的对象及命中点与法线，立方体用包围球近似
+    fn pick(&self, ray: &Ray) -> Option<(Selection, glam::Vec3, glam::Vec3)> {
+        let objects = self.scene.objects.iter().enumerate().filter_map(|(i, o)| {
+            let t = &o.transform;
+            ray.intersect_sphere(t.position, CUBE_RADIUS * t.scale.max_element())
+                .map(|d| (Selection::Object(i), d, t.position))
+        });
+        let sun_position = self.sun_handle().position;
+        let sun = ray
+            .intersect_sphere(sun_position, SUN_HANDLE_RADIUS)
+            .map(|d| (Selection::Sun, d, sun_position));
+        let (selection, d, center) = objects.chain(sun).min_by(|a, b| a.1.total_cmp(&b.1))?;
+        let point = ray.at(d);
+        Some((
+            selection,
+            point,
+            (point - center).normalize_or(-ray.direction),
+        ))
+    }
+
+    /// 场景替换后同步 GPU 上的实例与颜色
+    fn upload_scene(&mut self) {
+        self.instances = MotionInstanceBuffer::new(
+            &self.gpu.device,
+            self.scene.objects.iter().map(|o| o.transform).collect(),
+        );
+        self.albedo_buffer = create_albedo_buffer(&self.gpu.device, &self.scene);
+        self.light.light = self.scene.sun;
+        self.selected = None;
+        self.gizmo.end_drag();
+    }
+
+    fn save(&self) {
+        if !self.can_save {
+            eprintln!(
+                "refusing to overwrite {} before it loads successfully, fix the file and press F3",
+                self.path.display()
+            );
+            return;
+        }
+        match self.scene.save(&self.path) {
+            Ok(()) => println!("saved scene to {}", self.path.display()),
+            Err(e) => eprintln!("{e:?}"),
+        }
+    }
+
+    fn reload(&mut self) {
+        match Scene::load(&self.path) {
+            Ok(scene) => {
+                self.scene = scene;
+                self.can_save = true;
+                self.upload_scene();
+                println!("reloaded scene from {}", self.path.display());
+            }
+            Err(e) => eprintln!("{e:?}"),
+        }
+    }
+
+    fn build_debug_lines(&mut self) {
+        self.debug.clear();
+
+        let grid = glam::vec4(0.5, 0.5, 0.55, 0.4);
+        let extent = GRID_HALF_SIZE as f32;
+        for i in -GRID_HALF_SIZE..=GRID_HALF_SIZE {
+            let i = i as f32;
+            self.debug.line(
+                glam::vec3(i, 0.0, -extent),
+                glam::vec3(i, 0.0, extent),
+                grid,
+            );
+            self.debug.line(
+                glam::vec3(-extent, 0.0, i),
+                glam::vec3(extent, 0.0, i),
+                grid,
+            );
+        }
+
+        let sun = self.sun_handle().position;
+        let sun_color =
+            (self.scene.sun.color / self.scene.sun.color.max_element().max(1.0)).extend(1.0);
+        self.debug.wire_sphere(sun, SUN_HANDLE_RADIUS, sun_color);
+        self.debug.arrow(sun, -sun * 0.3, sun_color);
+
+        if let Some(target) = self.selected_transform() {
+            self.debug
+                .transform_gizmo(&self.gizmo, &self.camera.state, &target);
+        }
+
+        if let Some((ray, hit)) = self.last_pick.filter(|_| self.show_pick_ray) {
+            self.debug.pick_ray(&ray, hit, GRID_HALF_SIZE as f32 * 4.0);
+        }
+    }
+}
+
+fn create_albedo_buffer(device: &wgpu::Device, scene: &Scene) -> wgpu::Buffer {
+    device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+        label: Some("Albedo Buffer"),
+        contents: bytemuck::cast_slice(&scene.albedo_data()),
+        usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+    })
+}
+
+impl WindowApp for Editor {
+    async fn new(window: Arc<Window>) -> Self {
+        let path = std::env::args()
+            .nth(1)
+            .map_or_else(|| PathBuf::from(DEFAULT_SCENE_PATH), PathBuf::from);
+        // 场景文件无法读取时打印错误并从默认场景开始，在重新加载成功之前不允许保存
+        let (scene, can_save) = if path.exists() {
+            match Scene::load(&path) {
+                Ok(scene) => (scene, true),
+                Err(e) => {
+                    eprintln!("{e:?}");
+                    (Scene::default(), false)
+                }
+            }
+        } else {
+            (Scene::default(), true)
+        };
+
+        let gpu = GpuContext::new(window, GpuContextOptions::default())
+            .await
+            .expect("failed to create a GPU context for the editor");
+
+        let camera = Camera {
+            eye: (0.0, 8.0, 14.0).into(),
+            target: (0.0, 1.0, 0.0).into(),
+            up: glam::Vec3::Y,
+            aspect: gpu.aspect(),
+            projection: Projection::Perspective { fovy: 45.0 },
+            znear: 0.1,
+            zfar: 100.0,
+            depth: DepthConvention::Standard,
+        };
+        let camera = CameraBundle::builder(camera)
+            .controller(KeyboardController::new(0.2))
+            .build(&gpu.device)
+            .unwrap();
+
+        let depth_texture =
+            Texture::create_depth_texture(&gpu.device, &gpu.surface_config, "depth_texture");
+
+        let light = DirectionalLightBundle::new(scene.sun, &gpu.device);
+
+        let debug = DebugDraw::new(
+            &gpu.device,
+            gpu.format(),
+            Some(Texture::DEPTH_FORMAT),
+            &camera.bind_group_layout,
+        );
+
+        let egui = EguiLayer::new(&gpu);
+
+        let (vertices, indices) = cube();
+        let vertex_buffer = gpu
+            .device
+            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("Cube Vertex Buffer"),
+                contents: bytemuck::cast_slice(&vertices),
+                usage: wgpu::BufferUsages::VERTEX,
+            });
+        let index_buffer = gpu
+            .device
+            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("Cube Index Buffer"),
+                contents: bytemuck::cast_slice(&indices),
+                usage: wgpu::BufferUsages::INDEX,
+            });
+
+        let instances = MotionInstanceBuffer::new(
+            &gpu.device,
+            scene.objects.iter().map(|o| o.transform).collect(),
+        );
+        let albedo_buffer = create_albedo_buffer(&gpu.device, &scene);
+
+        let shader = ReflectedShader::new(
+            &gpu.device,
+            &ShaderLibrary::new(),
+            "Editor Shader",
+            include_str!("editor.wgsl"),
+        )
+        .unwrap();
+        let render_pipeline = PipelineBuilder::from_reflection(&shader)
+            .label("Editor Pipeline")
+            .bind_group_layout(
+                0,
+                &camera.bind_group_layout,
+                &[CameraBundle::layout_entry(wgpu::ShaderStages::VERTEX)],
+            )
+            .bind_group_layout(
+                1,
+                &light.bind_group_layout,
+                &[DirectionalLightBundle::layout_entry()],
+            )
+            .vertex_buffer(CubeVertex::buffer_layout_desc())
+            .vertex_buffer(MotionInstanceRaw::buffer_layout_desc())
+            .vertex_buffer(albedo_buffer_layout_desc())
+            .color_target(wgpu::ColorTargetState {
+                format: gpu.format(),
+                blend: Some(wgpu::BlendState::REPLACE),
+                write_mask: wgpu::ColorWrites::ALL,
+            })
+            .primitive(wgpu::PrimitiveState {
+                cull_mode: Some(wgpu::Face::Back),
+                ..Default::default()
+            })
+            .depth_stencil(wgpu::DepthStencilState {
+                format: Texture::DEPTH_FORMAT,
+                depth_write_enabled: true,
+                depth_compare: wgpu::CompareFunction::Less,
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState::default(),
+            })
+            .build(&gpu.device)
+            .unwrap()
+            .pipeline;
+
+        Self {
+            gpu,
+
+            depth_texture,
+
+            camera,
+            light,
+            debug,
+            egui,
+
+            render_pipeline,
+            vertex_buffer,
+            index_buffer,
+            index_count: indices.len() as u32,
+            instances,
+            albedo_buffer,
+
+            path,
+            scene,
+            can_save,
+            gizmo: TransformGizmo::default(),
+            selected: None,
+            cursor: glam::Vec2::ZERO,
+            last_pick: None,
+            show_pick_ray: false,
+        }
+    }
+
+    fn render(&mut self) -> Result<(), wgpu::SurfaceError> {
+        self.resize_surface_if_needed();
+
+        let frame = self.gpu.current_frame()?;
+
+        let mut encoder = self
+            .gpu
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("Render Encoder"),
+            });
+
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Render Pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: &frame.view,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(wgpu::Color {
+                        r: 0.02,
+                        g: 0.02,
+                        b: 0.03,
+                        a: 1.0,
+                    }),
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                view: &self.depth_texture.view,
+                depth_ops: Some(wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(1.0),
+                    store: wgpu::StoreOp::Store,
+                }),
+                stencil_ops: None,
+            }),
+            ..Default::default()
+        });
+
+        render_pass.set_pipeline(&self.render_pipeline);
+        render_pass.set_bind_group(0, &self.camera.bind_group, &[]);
+        render_pass.set_bind_group(1, &self.light.bind_group, &[]);
+        render_pass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
+        render_pass.set_vertex_buffer(1, self.instances.buffer().slice(..));
+        render_pass.set_vertex_buffer(2, self.albedo_buffer.slice(..));
+        render_pass.set_index_buffer(self.index_buffer.slice(..), wgpu::IndexFormat::Uint32);
+        render_pass.draw_indexed(0..self.index_count, 0, 0..self.instances.len());
+
+        self.debug.draw(&mut render_pass, &self.camera.bind_group);
+
+        drop(render_pass);
+
+        self.egui.draw(&self.gpu, &mut encoder, &frame.gamma_view);
+
+        self.gpu.queue.submit(Some(encoder.finish()));
+        self.gpu.present(frame);
+
+        Ok(())
+    }
+
+    fn set_window_resized(&mut self, new_size: PhysicalSize<u32>) {
+        self.gpu.resize(new_size);
+    }
+
+    fn resize_surface_if_needed(&mut self) {
+        if self.gpu.resize_if_needed() {
+            self.depth_texture = Texture::create_depth_texture(
+                &self.gpu.device,
+                &self.gpu.surface_config,
+                "depth_texture",
+            );
+        }
+    }
+
+    fn gpu_context(&mut self) -> Option<&mut GpuContext> {
+        Some(&mut self.gpu)
+    }
+
+    fn window_event(&mut self, event: &WindowEvent) -> bool {
+        self.egui.on_window_event(self.gpu.window().window(), event)
+    }
+
+    fn keyboard_input(&mut self, event: &KeyEvent) -> bool {
+        if self.camera.process_key(event) {
+            return true;
+        }
+        if event.state != ElementState::Pressed || event.repeat {
+            return false;
+        }
+        // 鼠标左键选中立方体或太阳手柄并拖动，1/2/3 键切换平移、旋转、缩放，Esc 取消选中，
+        // C 键切换选中立方体的颜色，+/- 调整光照强度，F2 保存，F3 重新加载，
+        // R 键显示最近一次点击的拾取射线、命中点与法线
+        let PhysicalKey::Code(code) = event.physical_key else {
+            return false;
+        };
+        match code {
+            KeyCode::Digit1 => self.gizmo.mode = GizmoMode::Translate,
+            KeyCode::Digit2 => self.gizmo.mode = GizmoMode::Rotate,
+            KeyCode::Digit3 => self.gizmo.mode = GizmoMode::Scale,
+            KeyCode::Escape => self.selected = None,
+            KeyCode::KeyC => {
+                let Some(Selection::Object(i)) = self.selected else {
+                    return false;
+                };
+                let albedo = &mut self.scene.objects[i].albedo;
+                let next = PALETTE
+                    .iter()
+                    .position(|c| c == albedo)
+                    .map_or(0, |p| (p + 1) % PALETTE.len());
+                *albedo = PALETTE[next];
+            }
+            KeyCode::Equal => self.scene.sun.intensity += 0.1,
+            KeyCode::Minus => self.scene.sun.intensity = (self.scene.sun.intensity - 0.1).max(0.0),
+            KeyCode::F2 => self.save(),
+            KeyCode::F3 => self.reload(),
+            KeyCode::KeyR => self.show_pick_ray = !self.show_pick_ray,
+            _ => return false,
+        }
+        self.gizmo.end_drag();
+        true
+    }
+
+    fn mouse_wheel(&mut self, delta: MouseScrollDelta, _phase: TouchPhase) -> bool {
+        self.camera.process_wheel(delta)
+    }
+
+    fn mouse_click(&mut self, state: ElementState, button: MouseButton) -> bool {
+        if button != MouseButton::Left {
+            return false;
+        }
+        if state == ElementState::Released {
+            self.gizmo.end_drag();
+            return true;
+        }
+        let ray = self.cursor_ray();
+        if let Some(target) = self.selected_transform() {
+            if self.gizmo.begin_drag(&ray, &self.camera.state, &target) {
+                return true;
+            }
+        }
+        let hit = self.pick(&ray);
+        self.selected = hit.map(|(selection, _, _)| selection);
+        self.last_pick = Some((ray, hit.map(|(_, point, normal)| (point, normal))));
+        true
+    }
+
+    fn cursor_move(&mut self, position: PhysicalPosition<f64>) -> bool {
+        self.cursor = glam::vec2(position.x as f32, position.y as f32);
+        let (Some(selected), Some(mut target)) = (self.selected, self.selected_transform()) else {
+            return false;
+        };
+        let ray = self.cursor_ray();
+        if !self.gizmo.is_dragging() {
+            self.gizmo.hover(&ray, &self.camera.state, &target);
+            return true;
+        }
+        let Some(delta) = self.gizmo.drag(&ray, &target) else {
+            return true;
+        };
+        delta.apply(&mut target);
+        match selected {
+            Selection::Object(i) => self.scene.objects[i].transform = target,
+            Selection::Sun => {
+                self.scene.sun.direction = target.position.normalize_or(self.scene.sun.direction)
+            }
+        }
+        true
+    }
+
+    fn cameras_mut(&mut self) -> Vec<&mut CameraBundle> {
+        vec![&mut self.camera]
+    }
+
+    fn update(&mut self, time: FrameTime) {
+        let (scene, selected) = (&mut self.scene, &mut self.selected);
+        self.egui.run(self.gpu.window().window(), |ctx| {
+            egui::Window::new("Inspector").show(ctx, |ui| inspector(ui, scene, selected));
+        });
+
+        self.camera.update(&self.gpu.queue, time.delta_secs());
+
+        for (instance, object) in self.instances.instances.iter_mut().zip(&self.scene.objects) {
+            *instance = object.transform;
+        }
+        self.instances.update(&self.gpu.queue);
+        self.gpu.queue.write_buffer(
+            &self.albedo_buffer,
+            0,
+            bytemuck::cast_slice(&self.scene.albedo_data()),
+        );
+
+        self.light.light = self.scene.sun;
+        self.light.update(&self.gpu.queue);
+
+        self.build_debug_lines();
+        self.debug.update(
+            &self.gpu.device,
+            &self.gpu.queue,
+            &self.camera.state,
+            self.gpu.size(),
+        );
+    }
+}
+
+fn main() -> Result<(), impl std::error::Error> {
+    if std::env::args().nth(1).as_deref() == Some("--list-adapters") {
+        enumerate_adapters(wgpu::Backends::all());
+        return Ok(());
+    }
+    let events_loop = EventLoop::new().unwrap();
+    let mut app = WindowAppHandler::<Editor>::new("wgpu_dance editor")
+        .with_inner_size(LogicalSize::new(1280.0, 800.0))
+        .with_min_size(LogicalSize::new(640.0, 400.0));
+    events_loop.run_app(&mut app)
+}
@@ -0,0 +1,657 @@
+//! 模型查看器：`cargo run --bin viewer -- [model.obj]`
+//!
+//! 把 OBJ 文件拖入窗口即可查看，按材质的漫反射颜色与可调的金属度、粗糙度做 PBR 着色。
+//! 鼠标左键拖动环绕模型，滚轮缩放，帧率显示在窗口标题中。
+
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use anyhow::{ensure, Context};
+use bytemuck::{Pod, Zeroable};
+use wgpu::util::DeviceExt;
+use wgpu_dance::{
+    app::{FrameTime, WindowApp, WindowAppHandler},
+    camera::{Camera, CameraBundle, Projection},
+    context::{GpuContext, GpuContextOptions},
+    debug_draw::DebugDraw,
+    light::{DirectionalLight, DirectionalLightBundle},
+    model::RenderVertex,
+    pipeline::{PipelineBuilder, ReflectedShader},
+    shader::ShaderLibrary,
+    sky::{Sky, Sun},
+    stats::{FrameStats, StatsReporter},
+    texture::{DepthConvention, Texture},
+    uniform::GpuUniform,
+};
+use winit::{
+    dpi::{LogicalSize, PhysicalPosition, PhysicalSize},
+    event::{ElementState, KeyEvent, MouseButton, MouseScrollDelta, TouchPhase},
+    event_loop::EventLoop,
+    keyboard::{KeyCode, PhysicalKey},
+    window::Window,
+};
+
+/// 没有材质的网格使用的颜色
+const DEFAULT_ALBEDO: [f32; 3] = [0.8, 0.8, 0.8];
+/// 每像素拖动对应的环绕角度，单位弧度
+const ORBIT_SPEED: f32 = 0.008;
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug)]
+struct ViewerVertex {
+    position: [f32; 3],
+    normal: [f32; 3],
+    albedo: [f32; 3],
+}
+
+unsafe impl Zeroable for ViewerVertex {}
+unsafe impl Pod for ViewerVertex {}
+
+impl RenderVertex for ViewerVertex {
+    fn buffer_layout_desc<'a>() -> wgpu::VertexBufferLayout<'a> {
+        const ATTRIBUTES: [wgpu::VertexAttribute; 3] = wgpu::vertex_attr_array![
+            4 => Float32x3,
+            5 => Float32x3,
+            6 => Float32x3,
+        ];
+        wgpu::VertexBufferLayout {
+            array_stride: std::mem::size_of::<ViewerVertex>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Vertex,
+            attributes: &ATTRIBUTES,
+        }
+    }
+}
+
+/// 与 `viewer.wgsl` 中的 `ViewerUniform` 保持一致
+#[repr(C)]
+#[derive(Debug, Copy, Clone, GpuUniform)]
+struct ViewerUniform {
+    eye: [f32; 4],
+    material: [f32; 4],
+}
+
+/// 合并为单个顶点/索引缓冲的模型
+struct LoadedModel {
+    vertices: Vec<ViewerVertex>,
+    indices: Vec<u32>,
+    center: glam::Vec3,
+    radius: f32,
+}
+
+/// 读取 OBJ，缺少法线时按面法线平均生成；MTL 缺失时所有网格使用默认颜色
+fn load_obj(path: &Path) -> anyhow::Result<LoadedModel> {
+    let (models, materials) = tobj::load_obj(
+        path,
+        &tobj::LoadOptions {
+            triangulate: true,
+            single_index: true,
+            ..Default::default()
+        },
+    )
+    .with_context(|| format!("failed to load {}", path.display()))?;
+    let materials = materials.unwrap_or_else(|e| {
+        eprintln!("{}: {e}, using default material", path.display());
+        Vec::new()
+    });
+
+    let mut vertices = Vec::new();
+    let mut indices = Vec::new();
+    for model in &models {
+        let mesh = &model.mesh;
+        let base = vertices.len();
+        let albedo = mesh
+            .material_id
+            .and_then(|id| materials.get(id))
+            .map_or(DEFAULT_ALBEDO, |m| m.diffuse);
+        vertices.extend(mesh.positions.chunks_exact(3).enumerate().map(|(i, p)| {
+            ViewerVertex {
+                position: [p[0], p[1], p[2]],
+                normal: mesh
+                    .normals
+                    .get(i * 3..i * 3 + 3)
+                    .map_or([0.0; 3], |n| [n[0], n[1], n[2]]),
+                albedo,
+            }
+        }));
+        if mesh.normals.is_empty() {
+            for tri in mesh.indices.chunks_exact(3) {
+                let [a, b, c] =
+                    [0, 1, 2].map(|k| glam::Vec3::from(vertices[base + tri[k] as usize].position));
+                let normal = (b - a).cross(c - a);
+                for &i in tri {
+                    let n = &mut vertices[base + i as usize].normal;
+                    *n = (glam::Vec3::from(*n) + normal).to_array();
+                }
+            }
+            for v in &mut vertices[base..] {
+                v.normal = glam::Vec3::from(v.normal).normalize_or_zero().to_array();
+            }
+        }
+        indices.extend(mesh.indices.iter().map(|&i| i + base as u32));
+    }
+    ensure!(
+        !indices.is_empty(),
+        "{} contains no triangles",
+        path.display()
+    );
+
+    let (min, max) = vertices.iter().fold(
+        (glam::Vec3::splat(f32::MAX), glam::Vec3::splat(f32::MIN)),
+        |(min, max), v| {
+            let p = glam::Vec3::from(v.position);
+            (min.min(p), max.max(p))
+        },
+    );
+    let center = (min + max) * 0.5;
+    let radius = vertices
+        .iter()
+        .map(|v| glam::Vec3::from(v.position).distance(center))
+        .fold(0.0, f32::max)
+        .max(1e-3);
+    Ok(LoadedModel {
+        vertices,
+        indices,
+        center,
+        radius,
+    })
+}
+
+/// 环绕 `target` 的相机参数
+#[derive(Debug, Copy, Clone)]
+struct Orbit {
+    target: glam::Vec3,
+    yaw: f32,
+    pitch: f32,
+    distance: f32,
+}
+
+impl Orbit {
+    fn framing(center: glam::Vec3, radius: f32) -> Self {
+        Self {
+            target: center,
+            yaw: 0.6,
+            pitch: 0.4,
+            distance: radius * 2.5,
+        }
+    }
+
+    fn eye(&self) -> glam::Vec3 {
+        let dir = glam::vec3(
+            self.pitch.cos() * self.yaw.sin(),
+            self.pitch.sin(),
+            self.pitch.cos() * self.yaw.cos(),
+        );
+        self.target + dir * self.distance
+    }
+}
+
+struct Viewer {
+    gpu: GpuContext,
+
+    depth_texture: Texture,
+
+    camera: CameraBundle,
+    light: DirectionalLightBundle,
+    sky: Sky,
+    sun: Sun,
+    debug: DebugDraw,
+
+    render_pipeline: wgpu::RenderPipeline,
+    /// 适配器不支持 `POLYGON_MODE_LINE` 时为 `None`
+    wireframe_pipeline: Option<wgpu::RenderPipeline>,
+    uniform: ViewerUniform,
+    uniform_buffer: wgpu::Buffer,
+    uniform_bind_group: wgpu::BindGroup,
+
+    vertex_buffer: wgpu::Buffer,
+    index_buffer: wgpu::Buffer,
+    index_count: u32,
+    radius: f32,
+
+    orbit: Orbit,
+    home: Orbit,
+    dragging: bool,
+    cursor: Option<glam::Vec2>,
+
+    metallic: f32,
+    roughness: f32,
+    wireframe: bool,
+    show_grid: bool,
+    /// 有文件拖到窗口上方
+    hovering: bool,
+}
+
+impl Viewer {
+    fn set_model(&mut self, model: LoadedModel) {
+        self.vertex_buffer =
+            self.gpu
+                .device
+                .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                    label: Some("Viewer Vertex Buffer"),
+                    contents: bytemuck::cast_slice(&model.vertices),
+                    usage: wgpu::BufferUsages::VERTEX,
+                });
+        self.index_buffer = self
+            .gpu
+            .device
+            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("Viewer Index Buffer"),
+                contents: bytemuck::cast_slice(&model.indices),
+                usage: wgpu::BufferUsages::INDEX,
+            });
+        self.index_count = model.indices.len() as u32;
+        self.radius = model.radius;
+        self.home = Orbit::framing(model.center, model.radius);
+        self.orbit = self.home;
+        self.camera.state.znear = model.radius * 0.01;
+        self.camera.state.zfar = model.radius * 100.0;
+    }
+
+    fn load(&mut self, path: &Path) {
+        match load_obj(path) {
+            Ok(model) => {
+                println!(
+                    "loaded {}: {} vertices, {} triangles, radius {:.3}",
+                    path.display(),
+                    model.vertices.len(),
+                    model.indices.len() / 3,
+                    model.radius
+                );
+                self.set_model(model);
+            }
+            Err(e) => eprintln!("{e:#}"),
+        }
+    }
+
+    /// 以模型底部为高度、随模型大小缩放的地面网格
+    fn build_grid(&mut self) {
+        self.debug.clear();
+        if !self.show_grid {
+            return;
+        }
+        let step = 10f32.powf((self.radius * 0.2).log10().floor());
+        let half = (self.radius * 2.0 / step).ceil() as i32;
+        let y = self.home.target.y - self.radius;
+        let color = glam::vec4(0.6, 0.6, 0.65, 0.5);
+        let extent = half as f32 * step;
+        let origin = self.home.target;
+        for i in -half..=half {
+            let offset = i as f32 * step;
+            self.debug.line(
+                glam::vec3(origin.x + offset, y, origin.z - extent),
+                glam::vec3(origin.x + offset, y, origin.z + extent),
+                color,
+            );
+            self.debug.line(
+                glam::vec3(origin.x - extent, y, origin.z + offset),
+                glam::vec3(origin.x + extent, y, origin.z + offset),
+                color,
+            );
+        }
+    }
+}
+
+impl WindowApp for Viewer {
+    async fn new(window: Arc<Window>) -> Self {
+        let gpu = GpuContext::new(
+            window,
+            GpuContextOptions {
+                optional_features: wgpu::Features::POLYGON_MODE_LINE,
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+
+        let camera = Camera {
+            eye: (0.0, 1.0, 3.0).into(),
+            target: glam::Vec3::ZERO,
+            up: glam::Vec3::Y,
+            aspect: gpu.aspect(),
+            projection: Projection::Perspective { fovy: 45.0 },
+            znear: 0.01,
+            zfar: 100.0,
+            depth: DepthConvention::Standard,
+        };
+        let camera = CameraBundle::builder(camera).build(&gpu.device).unwrap();
+
+        let sun = Sun {
+            time_of_day: 15.0,
+            ..Default::default()
+        };
+        let mut light = DirectionalLight {
+            intensity: 3.0,
+            ..Default::default()
+        };
+        sun.apply_to(&mut light);
+        let light = DirectionalLightBundle::new(light, &gpu.device);
+        let sky = Sky::new(&gpu.device, gpu.format(), Some(Texture::DEPTH_FORMAT));
+
+        let depth_texture =
+            Texture::create_depth_texture(&gpu.device, &gpu.surface_config, "depth_texture");
+        let debug = DebugDraw::new(
+            &gpu.device,
+            gpu.format(),
+            Some(Texture::DEPTH_FORMAT),
+            &camera.bind_group_layout,
+        );
+
+        let uniform = ViewerUniform {
+            eye: [0.0; 4],
+            material: [0.0, 0.5, 0.15, 0.0],
+        };
+        let uniform_buffer = gpu
+            .device
+            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("Viewer Uniform Buffer"),
+                contents: bytemuck::cast_slice(&[uniform]),
+                usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            });
+        let uniform_entry = wgpu::BindGroupLayoutEntry {
+            binding: 0,
+            visibility: wgpu::ShaderStages::FRAGMENT,
+            ty: wgpu::BindingType::Buffer {
+                ty: wgpu::BufferBindingType::Uniform,
+                has_dynamic_offset: false,
+                min_binding_size: None,
+            },
+            count: None,
+        };
+        let uniform_layout =
+            gpu.device
+                .create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                    label: Some("viewer_bind_group_layout"),
+                    entries: &[uniform_entry],
+                });
+        let uniform_bind_group = gpu.device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("viewer_bind_group"),
+            layout: &uniform_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: uniform_buffer.as_entire_binding(),
+            }],
+        });
+
+        let shader = ReflectedShader::new(
+            &gpu.device,
+            &ShaderLibrary::new(),
+            "Viewer Shader",
+            include_str!("viewer.wgsl"),
+        )
+        .unwrap();
+        let build_pipeline = |label, polygon_mode| {
+            PipelineBuilder::from_reflection(&shader)
+                .label(label)
+                .bind_group_layout(
+                    0,
+                    &camera.bind_group_layout,
+                    &[CameraBundle::layout_entry(wgpu::ShaderStages::VERTEX)],
+                )
+                .bind_group_layout(
+                    1,
+                    &light.bind_group_layout,
+                    &[DirectionalLightBundle::layout_entry()],
+                )
+                .bind_group_layout(2, &uniform_layout, &[uniform_entry])
+                .vertex_buffer(ViewerVertex::buffer_layout_desc())
+                .color_target(wgpu::ColorTargetState {
+                    format: gpu.format(),
+                    blend: Some(wgpu::BlendState::REPLACE),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })
+                .primitive(wgpu::PrimitiveState {
+                    polygon_mode,
+                    ..Default::default()
+                })
+                .depth_stencil(wgpu::DepthStencilState {
+                    format: Texture::DEPTH_FORMAT,
+                    depth_write_enabled: true,
+                    depth_compare: wgpu::CompareFunction::Less,
+                    stencil: wgpu::StencilState::default(),
+                    bias: wgpu::DepthBiasState::default(),
+                })
+                .build(&gpu.device)
+                .unwrap()
+                .pipeline
+        };
+        let render_pipeline = build_pipeline("Viewer Pipeline", wgpu::PolygonMode::Fill);
+        let wireframe_pipeline = gpu
+            .device
+            .features()
+            .contains(wgpu::Features::POLYGON_MODE_LINE)
+            .then(|| build_pipeline("Viewer Wireframe Pipeline", wgpu::PolygonMode::Line));
+
+        // 先放一个占位的空缓冲，随后加载命令行指定的模型或内置的立方体
+        let empty_buffer = |usage| {
+            gpu.device.create_buffer(&wgpu::BufferDescriptor {
+                label: None,
+                size: 16,
+                usage,
+                mapped_at_creation: false,
+            })
+        };
+        let home = Orbit::framing(glam::Vec3::ZERO, 1.0);
+        let mut viewer = Self {
+            vertex_buffer: empty_buffer(wgpu::BufferUsages::VERTEX),
+            index_buffer: empty_buffer(wgpu::BufferUsages::INDEX),
+
+            gpu,
+
+            depth_texture,
+
+            camera,
+            light,
+            sky,
+            sun,
+            debug,
+
+            render_pipeline,
+            wireframe_pipeline,
+            uniform,
+            uniform_buffer,
+            uniform_bind_group,
+
+            index_count: 0,
+            radius: 1.0,
+
+            orbit: home,
+            home,
+            dragging: false,
+            cursor: None,
+
+            metallic: 0.0,
+            roughness: 0.5,
+            wireframe: false,
+            show_grid: true,
+            hovering: false,
+        };
+        let path = std::env::args().nth(1).map_or_else(
+            || Path::new("res/cube/cube.obj").to_path_buf(),
+            PathBuf::from,
+        );
+        viewer.load(&path);
+        viewer
+    }
+
+    fn render(&mut self) -> Result<(), wgpu::SurfaceError> {
+        self.resize_surface_if_needed();
+
+        let frame = self.gpu.current_frame()?;
+
+        let mut encoder = self
+            .gpu
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("Render Encoder"),
+            });
+
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Render Pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: &frame.view,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                view: &self.depth_texture.view,
+                depth_ops: Some(wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(1.0),
+                    store: wgpu::StoreOp::Store,
+                }),
+                stencil_ops: None,
+            }),
+            ..Default::default()
+        });
+
+        self.sky.draw(&mut render_pass);
+
+        let pipeline = match &self.wireframe_pipeline {
+            Some(wireframe) if self.wireframe => wireframe,
+            _ => &self.render_pipeline,
+        };
+        render_pass.set_pipeline(pipeline);
+        render_pass.set_bind_group(0, &self.camera.bind_group, &[]);
+        render_pass.set_bind_group(1, &self.light.bind_group, &[]);
+        render_pass.set_bind_group(2, &self.uniform_bind_group, &[]);
+        render_pass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
+        render_pass.set_index_buffer(self.index_buffer.slice(..), wgpu::IndexFormat::Uint32);
+        render_pass.draw_indexed(0..self.index_count, 0, 0..1);
+
+        self.debug.draw(&mut render_pass, &self.camera.bind_group);
+
+        drop(render_pass);
+
+        self.gpu.queue.submit(Some(encoder.finish()));
+        self.gpu.present(frame);
+
+        Ok(())
+    }
+
+    fn set_window_resized(&mut self, new_size: PhysicalSize<u32>) {
+        self.gpu.resize(new_size);
+    }
+
+    fn resize_surface_if_needed(&mut self) {
+        if self.gpu.resize_if_needed() {
+            self.depth_texture = Texture::create_depth_texture(
+                &self.gpu.device,
+                &self.gpu.surface_config,
+                "depth_texture",
+            );
+        }
+    }
+
+    fn gpu_context(&mut self) -> Option<&mut GpuContext> {
+        Some(&mut self.gpu)
+    }
+
+    fn keyboard_input(&mut self, event: &KeyEvent) -> bool {
+        if event.state != ElementState::Pressed {
+            return false;
+        }
+        // F 键切换线框，G 键切换网格，R 键复位相机，[ 与 ] 调整粗糙度，M 键切换金属，
+        // T 键推进太阳的时刻
+        let PhysicalKey::Code(code) = event.physical_key else {
+            return false;
+        };
+        match code {
+            KeyCode::KeyF if !event.repeat => {
+                if self.wireframe_pipeline.is_none() {
+                    eprintln!("wireframe is not supported by this adapter");
+                }
+                self.wireframe = !self.wireframe;
+            }
+            KeyCode::KeyG if !event.repeat => self.show_grid = !self.show_grid,
+            KeyCode::KeyR if !event.repeat => self.orbit = self.home,
+            KeyCode::BracketLeft => self.roughness = (self.roughness - 0.05).max(0.05),
+            KeyCode::BracketRight => self.roughness = (self.roughness + 0.05).min(1.0),
+            KeyCode::KeyM if !event.repeat => {
+                self.metallic = if self.metallic > 0.5 { 0.0 } else { 1.0 }
+            }
+            KeyCode::KeyT => self.sun.advance(0.25),
+            _ => return false,
+        }
+        true
+    }
+
+    fn mouse_click(&mut self, state: ElementState, button: MouseButton) -> bool {
+        if button != MouseButton::Left {
+            return false;
+        }
+        self.dragging = state == ElementState::Pressed;
+        true
+    }
+
+    fn mouse_wheel(&mut self, delta: MouseScrollDelta, _phase: TouchPhase) -> bool {
+        let lines = match delta {
+            MouseScrollDelta::LineDelta(_, y) => y,
+            MouseScrollDelta::PixelDelta(p) => p.y as f32 / 40.0,
+        };
+        self.orbit.distance =
+            (self.orbit.distance * 0.9f32.powf(lines)).clamp(self.radius * 0.1, self.radius * 50.0);
+        true
+    }
+
+    fn cursor_move(&mut self, position: PhysicalPosition<f64>) -> bool {
+        let cursor = glam::vec2(position.x as f32, position.y as f32);
+        let last = self.cursor.replace(cursor);
+        let Some(last) = last.filter(|_| self.dragging) else {
+            return false;
+        };
+        let delta = cursor - last;
+        self.orbit.yaw -= delta.x * ORBIT_SPEED;
+        self.orbit.pitch = (self.orbit.pitch + delta.y * ORBIT_SPEED).clamp(-1.5, 1.5);
+        true
+    }
+
+    fn file_dropped(&mut self, path: PathBuf) {
+        self.load(&path);
+    }
+
+    fn file_hovered(&mut self, path: Option<PathBuf>) {
+        self.hovering = path.is_some();
+    }
+
+    fn cameras_mut(&mut self) -> Vec<&mut CameraBundle> {
+        vec![&mut self.camera]
+    }
+
+    fn update(&mut self, time: FrameTime) {
+        self.camera.state.eye = self.orbit.eye();
+        self.camera.state.target = self.orbit.target;
+        self.camera.update(&self.gpu.queue, time.delta_secs());
+
+        self.sun.apply_to(&mut self.light.light);
+        self.light.light.intensity = 3.0;
+        self.light.update(&self.gpu.queue);
+        self.sky
+            .update(&self.gpu.queue, &self.camera.state, &self.sun);
+
+        // 有文件悬停时提高环境光，提示松开即可加载
+        let ambient = if self.hovering { 0.6 } else { 0.15 };
+        self.uniform.eye = self.camera.state.eye.extend(0.0).to_array();
+        self.uniform.material = [self.metallic, self.roughness, ambient, 0.0];
+        self.uniform.write_to(&self.gpu.queue, &self.uniform_buffer);
+
+        self.build_grid();
+        self.debug.update(
+            &self.gpu.device,
+            &self.gpu.queue,
+            &self.camera.state,
+            self.gpu.size(),
+        );
+    }
+}
+
+fn main() -> Result<(), impl std::error::Error> {
+    let events_loop = EventLoop::new().unwrap();
+    let mut app = WindowAppHandler::<Viewer>::new("wgpu_dance viewer")
+        .with_inner_size(LogicalSize::new(1280.0, 800.0))
+        .with_frame_stats(FrameStats::default().with_reporter(StatsReporter::WindowTitle));
+    events_loop.run_app(&mut app)
+}
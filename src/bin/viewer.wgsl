@@ -0,0 +1,52 @@
+#include "wgpu_dance/camera.wgsl"
+#include "wgpu_dance/lighting.wgsl"
+
+struct VertexInput {
+    @location(4) position: vec3f,
+    @location(5) normal: vec3f,
+    @location(6) albedo: vec3f,
+}
+
+struct ViewerUniform {
+    // xyz: 相机位置
+    eye: vec4f,
+    // x: 金属度, y: 粗糙度, z: 环境光
+    material: vec4f,
+}
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4f,
+    @location(0) world_position: vec3f,
+    @location(1) world_normal: vec3f,
+    @location(2) albedo: vec3f,
+}
+
+@group(0) @binding(0)
+var<uniform> camera: CameraUniform;
+
+@group(1) @binding(0)
+var<uniform> sun: DirectionalLight;
+
+@group(2) @binding(0)
+var<uniform> viewer: ViewerUniform;
+
+@vertex
+fn vs_main(model: VertexInput) -> VertexOutput {
+    var out: VertexOutput;
+    out.world_position = model.position;
+    out.world_normal = model.normal;
+    out.albedo = model.albedo;
+    out.clip_position = camera.view_proj * vec4f(model.position, 1.0);
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput, @builtin(front_facing) front_facing: bool) -> @location(0) vec4f {
+    // 模型不一定封闭，背面按翻转的法线着色
+    let n = normalize(select(-in.world_normal, in.world_normal, front_facing));
+    let v = normalize(viewer.eye.xyz - in.world_position);
+    let l = normalize(sun.direction.xyz);
+    let direct = pbr_direct(in.albedo, viewer.material.x, viewer.material.y, n, v, l);
+    let ambient = in.albedo * viewer.material.z;
+    return vec4f(direct * sun.color.rgb * sun.direction.w + ambient, 1.0);
+}
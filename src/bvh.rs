@@ -0,0 +1,200 @@
+use glam::{Vec2, Vec3};
+
+use crate::ray::Ray;
+
+/// 叶子节点最多包含的三角形数量
+const LEAF_SIZE: usize = 4;
+
+#[derive(Debug, Copy, Clone)]
+struct BvhNode {
+    min: Vec3,
+    max: Vec3,
+    /// 叶子节点为第一个三角形在 `triangles` 中的位置，内部节点为右子节点的下标；
+    /// 左子节点总是紧跟在父节点之后
+    index: u32,
+    /// 叶子节点的三角形数量，内部节点为 0
+    count: u32,
+}
+
+/// 射线与 [`Bvh`] 中三角形的交点
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct BvhHit {
+    pub t: f32,
+    /// 构建时传入的三角形下标
+    pub triangle: usize,
+    /// 三角形第二、三个顶点的重心坐标
+    pub barycentric: Vec2,
+}
+
+/// CPU 上的三角形包围盒层次结构，用于光线追踪与烘焙
+///
+/// 按质心包围盒最长的轴在中位数处二分，构建快、质量对静态网格足够。
+#[derive(Debug, Clone)]
+pub struct Bvh {
+    nodes: Vec<BvhNode>,
+    /// 按叶子顺序重排后的三角形
+    triangles: Vec<[Vec3; 3]>,
+    /// `triangles` 中每个三角形的原始下标
+    order: Vec<u32>,
+}
+
+impl Bvh {
+    /// 由索引三角形构建，三角形下标即 `indices` 中每三个一组的序号
+    pub fn new(positions: &[Vec3], indices: &[u32]) -> Self {
+        Self::from_triangles(
+            indices
+                .chunks_exact(3)
+                .map(|tri| [0, 1, 2].map(|i| positions[tri[i] as usize]))
+                .collect(),
+        )
+    }
+
+    pub fn from_triangles(triangles: Vec<[Vec3; 3]>) -> Self {
+        let centroids = triangles
+            .iter()
+            .map(|[a, b, c]| (*a + *b + *c) / 3.0)
+            .collect::<Vec<_>>();
+        let mut order = (0..triangles.len() as u32).collect::<Vec<_>>();
+        let mut nodes = Vec::with_capacity(triangles.len().div_ceil(LEAF_SIZE) * 2);
+        if !triangles.is_empty() {
+            Self::build(&mut nodes, &mut order, 0, &triangles, &centroids);
+        }
+        let triangles = order.iter().map(|&i| triangles[i as usize]).collect();
+        Self {
+            nodes,
+            triangles,
+            order,
+        }
+    }
+
+    fn build(
+        nodes: &mut Vec<BvhNode>,
+        order: &mut [u32],
+        first: usize,
+        triangles: &[[Vec3; 3]],
+        centroids: &[Vec3],
+    ) {
+        let (min, max) = order.iter().flat_map(|&i| triangles[i as usize]).fold(
+            (Vec3::splat(f32::MAX), Vec3::splat(f32::MIN)),
+            |(min, max), p| (min.min(p), max.max(p)),
+        );
+        let node = nodes.len();
+        nodes.push(BvhNode {
+            min,
+            max,
+            index: first as u32,
+            count: order.len() as u32,
+        });
+        if order.len() <= LEAF_SIZE {
+            return;
+        }
+
+        let (centroid_min, centroid_max) = order.iter().map(|&i| centroids[i as usize]).fold(
+            (Vec3::splat(f32::MAX), Vec3::splat(f32::MIN)),
+            |(min, max), p| (min.min(p), max.max(p)),
+        );
+        let extent = centroid_max - centroid_min;
+        // 质心重合时无法再分，保留为较大的叶子
+        if extent.max_element() <= 0.0 {
+            return;
+        }
+        let axis = if extent.x >= extent.y && extent.x >= extent.z {
+            0
+        } else if extent.y >= extent.z {
+            1
+        } else {
+            2
+        };
+        let mid = order.len() / 2;
+        order.select_nth_unstable_by(mid, |&a, &b| {
+            centroids[a as usize][axis].total_cmp(&centroids[b as usize][axis])
+        });
+
+        let (left, right) = order.split_at_mut(mid);
+        Self::build(nodes, left, first, triangles, centroids);
+        let right_index = nodes.len() as u32;
+        Self::build(nodes, right, first + mid, triangles, centroids);
+        nodes[node].index = right_index;
+        nodes[node].count = 0;
+    }
+
+    pub fn triangle_count(&self) -> usize {
+        self.triangles.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.triangles.is_empty()
+    }
+
+    /// 包围所有三角形的轴对齐包围盒，`(最小点, 最大点)`；为空时返回 `None`
+    pub fn bounds(&self) -> Option<(Vec3, Vec3)> {
+        self.nodes.first().map(|root| (root.min, root.max))
+    }
+
+    /// 最近的交点，只考虑 `max_t` 之前的三角形；`cull_back` 的含义见 [`Ray::intersect_triangle`]
+    pub fn intersect(&self, ray: &Ray, cull_back: bool, max_t: f32) -> Option<BvhHit> {
+        let mut closest: Option<(f32, usize, Vec2)> = None;
+        self.traverse(ray, max_t, |position, limit| {
+            let (t, barycentric) = ray
+                .intersect_triangle(self.triangles[position], cull_back)
+                .filter(|&(t, _)| t < limit)?;
+            closest = Some((t, position, barycentric));
+            Some(t)
+        });
+        closest.map(|(t, position, barycentric)| BvhHit {
+            t,
+            triangle: self.order[position] as usize,
+            barycentric,
+        })
+    }
+
+    /// `max_t` 之前是否有任何三角形挡住射线，不剔除背面；找到一个交点即返回
+    pub fn occluded(&self, ray: &Ray, max_t: f32) -> bool {
+        let mut hit = false;
+        self.traverse(ray, max_t, |position, limit| {
+            let blocked = ray
+                .intersect_triangle(self.triangles[position], false)
+                .is_some_and(|(t, _)| t < limit);
+            hit |= blocked;
+            // 以 0 作为新的上限，结束遍历
+            blocked.then_some(0.0)
+        });
+        hit
+    }
+
+    /// 按由近到远的顺序访问与射线相交的叶子中的三角形；`visit` 接收三角形位置与当前上限，
+    /// 返回新的上限时缩小搜索范围
+    fn traverse(&self, ray: &Ray, max_t: f32, mut visit: impl FnMut(usize, f32) -> Option<f32>) {
+        let mut limit = max_t;
+        let mut stack = Vec::with_capacity(64);
+        if !self.nodes.is_empty() {
+            stack.push(0u32);
+        }
+        let enter = |node: &BvhNode| ray.intersect_aabb(node.min, node.max);
+        while let Some(index) = stack.pop() {
+            let node = &self.nodes[index as usize];
+            if !enter(node).is_some_and(|t| t < limit) {
+                continue;
+            }
+            if node.count > 0 {
+                let first = node.index as usize;
+                for position in first..first + node.count as usize {
+                    if let Some(t) = visit(position, limit) {
+                        limit = t;
+                    }
+                }
+                continue;
+            }
+            let left = index + 1;
+            let right = node.index;
+            let t_left = enter(&self.nodes[left as usize]).unwrap_or(f32::INFINITY);
+            let t_right = enter(&self.nodes[right as usize]).unwrap_or(f32::INFINITY);
+            // 先压入较远的子节点，使较近的先出栈
+            if t_left <= t_right {
+                stack.extend([right, left]);
+            } else {
+                stack.extend([left, right]);
+            }
+        }
+    }
+}
@@ -1,48 +1,201 @@
-use bytemuck::{Pod, Zeroable};
-use wgpu::{util::DeviceExt, BindGroup, BindGroupLayout, Buffer, Device, Queue};
+use std::any::Any;
+
+use anyhow::ensure;
+use wgpu::{util::DeviceExt, BindGroup, BindGroupLayout, Buffer, Device, Queue, ShaderStages};
 use winit::{
-    event::{ElementState, KeyEvent},
+    dpi::PhysicalSize,
+    event::{ElementState, KeyEvent, MouseScrollDelta},
     keyboard::{KeyCode, PhysicalKey},
 };
 
+use crate::{
+    app::KeyInput, camera_path::CameraPath, layout::LayoutCache, ray::Ray, replay::InputEvent,
+    texture::DepthConvention, uniform::GpuUniform,
+};
+
+/// 相机的投影方式
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Projection {
+    /// 透视投影，`fovy` 为垂直视角（度）
+    Perspective { fovy: f32 },
+    /// 正交投影，可视范围高 `2 * half_height`，宽度由 `aspect` 推出，窗口缩放时保持比例
+    Orthographic { half_height: f32 },
+    /// 正交投影，直接给出观察空间中的可视范围，不随 `aspect` 变化
+    OrthographicBounds {
+        left: f32,
+        right: f32,
+        bottom: f32,
+        top: f32,
+    },
+}
+
+impl Projection {
+    pub fn matrix(&self, aspect: f32, znear: f32, zfar: f32) -> glam::Mat4 {
+        match *self {
+            Projection::Perspective { fovy } => {
+                glam::Mat4::perspective_rh(fovy.to_radians(), aspect, znear, zfar)
+            }
+            Projection::Orthographic { half_height } => {
+                let half_width = half_height * aspect;
+                glam::Mat4::orthographic_rh(
+                    -half_width,
+                    half_width,
+                    -half_height,
+                    half_height,
+                    znear,
+                    zfar,
+                )
+            }
+            Projection::OrthographicBounds {
+                left,
+                right,
+                bottom,
+                top,
+            } => glam::Mat4::orthographic_rh(left, right, bottom, top, znear, zfar),
+        }
+    }
+
+    pub fn is_orthographic(&self) -> bool {
+        !matches!(self, Projection::Perspective { .. })
+    }
+}
+
 #[derive(Debug, Copy, Clone)]
 pub struct Camera {
     pub eye: glam::Vec3,
     pub target: glam::Vec3,
     pub up: glam::Vec3,
     pub aspect: f32,
-    pub fovy: f32,
+    pub projection: Projection,
     pub znear: f32,
+    /// 反向深度的透视投影可以为 `f32::INFINITY`
     pub zfar: f32,
+    pub depth: DepthConvention,
 }
 
 impl Camera {
+    pub fn set_viewport_size(&mut self, size: PhysicalSize<u32>) {
+        if size.width > 0 && size.height > 0 {
+            self.aspect = size.width as f32 / size.height as f32;
+        }
+    }
+
+    pub fn view_matrix(&self) -> glam::Mat4 {
+        glam::Mat4::look_at_rh(self.eye, self.target, self.up)
+    }
+
+    /// 按 `depth` 的约定把近、远平面映射到对应的深度
+    pub fn projection_matrix(&self) -> glam::Mat4 {
+        match (self.depth, self.projection) {
+            (DepthConvention::Standard, projection) => {
+                projection.matrix(self.aspect, self.znear, self.zfar)
+            }
+            (DepthConvention::Reversed, Projection::Perspective { fovy })
+                if self.zfar.is_infinite() =>
+            {
+                glam::Mat4::perspective_infinite_reverse_rh(
+                    fovy.to_radians(),
+                    self.aspect,
+                    self.znear,
+                )
+            }
+            (DepthConvention::Reversed, projection) => {
+                projection.matrix(self.aspect, self.zfar, self.znear)
+            }
+        }
+    }
+
     pub fn build_view_projection_matrix(&self) -> glam::Mat4 {
-        let view = glam::Mat4::look_at_rh(self.eye, self.target, self.up);
-        let proj =
-            glam::Mat4::perspective_rh(self.fovy.to_radians(), self.aspect, self.znear, self.zfar);
-        proj * view
+        self.projection_matrix() * self.view_matrix()
+    }
+
+    /// 视线方向上距相机 `depth` 处可视范围的世界空间高度，正交投影时与 `depth` 无关
+    pub fn view_height_at(&self, depth: f32) -> f32 {
+        let scale = 2.0 / self.projection_matrix().y_axis.y;
+        if self.projection.is_orthographic() {
+            scale
+        } else {
+            depth * scale
+        }
+    }
+
+    /// NDC 坐标（深度范围 [0, 1]）对应的世界坐标
+    pub fn unproject(&self, ndc: glam::Vec3) -> glam::Vec3 {
+        self.build_view_projection_matrix()
+            .inverse()
+            .project_point3(ndc)
+    }
+
+    /// 穿过视口中 `cursor` 像素的射线，起点在近平面上
+    pub fn screen_ray(&self, cursor: glam::Vec2, viewport: PhysicalSize<u32>) -> Ray {
+        let ndc = glam::vec2(
+            cursor.x / viewport.width.max(1) as f32 * 2.0 - 1.0,
+            1.0 - cursor.y / viewport.height.max(1) as f32 * 2.0,
+        );
+        let near = self.unproject(ndc.extend(self.depth.near_depth()));
+        // 远平面可能在无限远处，取中间的深度确定方向
+        let far = self.unproject(ndc.extend(0.5));
+        Ray::new(near, far - near)
     }
 }
 
 #[repr(C)]
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, GpuUniform)]
 pub struct CameraUniform {
     view_proj: [[f32; 4]; 4],
+    /// 上一帧未抖动的 view-projection，用于计算速度
+    prev_view_proj: [[f32; 4]; 4],
+    /// xy: 本帧投影在 NDC 中的抖动
+    jitter: [f32; 4],
+    /// xyz: 相机在世界空间中的位置，w 为 1
+    view_pos: [f32; 4],
+    view: [[f32; 4]; 4],
+    /// 与 `view_proj` 一样包含抖动
+    proj: [[f32; 4]; 4],
+    /// `view_proj` 的逆，用于由 NDC 与深度重建世界空间位置
+    inv_view_proj: [[f32; 4]; 4],
 }
 
-unsafe impl Zeroable for CameraUniform {}
-unsafe impl Pod for CameraUniform {}
-
 impl CameraUniform {
     pub fn new() -> Self {
         Self {
             view_proj: glam::Mat4::IDENTITY.to_cols_array_2d(),
+            prev_view_proj: glam::Mat4::IDENTITY.to_cols_array_2d(),
+            jitter: [0.0; 4],
+            view_pos: [0.0, 0.0, 0.0, 1.0],
+            view: glam::Mat4::IDENTITY.to_cols_array_2d(),
+            proj: glam::Mat4::IDENTITY.to_cols_array_2d(),
+            inv_view_proj: glam::Mat4::IDENTITY.to_cols_array_2d(),
         }
     }
 
     pub fn update_view_proj(&mut self, camera: &Camera) {
-        self.view_proj = camera.build_view_projection_matrix().to_cols_array_2d();
+        self.set_view_and_projection(camera.view_matrix(), camera.projection_matrix(), camera.eye);
+    }
+
+    /// 只设置 `view_proj` 及其逆，`view`、`proj` 与 `view_pos` 保持不变
+    pub fn set_view_proj(&mut self, view_proj: glam::Mat4) {
+        self.view_proj = view_proj.to_cols_array_2d();
+        self.inv_view_proj = view_proj.inverse().to_cols_array_2d();
+    }
+
+    /// 由观察矩阵、投影矩阵与相机的世界空间位置设置所有矩阵
+    pub fn set_view_and_projection(
+        &mut self,
+        view: glam::Mat4,
+        proj: glam::Mat4,
+        view_pos: glam::Vec3,
+    ) {
+        self.view = view.to_cols_array_2d();
+        self.proj = proj.to_cols_array_2d();
+        self.view_pos = view_pos.extend(1.0).to_array();
+        self.set_view_proj(proj * view);
+    }
+
+    /// 设置计算速度所需的上一帧矩阵与本帧抖动
+    pub fn set_motion(&mut self, prev_view_proj: glam::Mat4, jitter: glam::Vec2) {
+        self.prev_view_proj = prev_view_proj.to_cols_array_2d();
+        self.jitter = [jitter.x, jitter.y, 0.0, 0.0];
     }
 }
 
@@ -52,32 +205,81 @@ impl Default for CameraUniform {
     }
 }
 
+/// 根据输入移动相机的控制器，由 [`CameraBundle`] 持有并在 [`CameraBundle::update`] 中驱动
+///
+/// 实现这个 trait 即可替换默认的 [`KeyboardController`]，不需要修改相机模块。
+pub trait CameraController: Any + std::fmt::Debug {
+    /// 处理一个输入事件，返回是否处理了该事件
+    fn process_event(&mut self, event: &InputEvent) -> bool;
+
+    /// 移动相机，`dt` 为距上一次更新的秒数
+    fn update(&mut self, camera: &mut Camera, dt: f32);
+}
+
+/// 默认的控制器：W/S 或上下方向键前后移动，A/D 或左右方向键绕目标水平旋转，鼠标滚轮拉近或拉远
+///
+/// 每次更新按键移动 `speed`，与 `dt` 无关；滚轮每滚动一格把到目标的距离乘以 `zoom_step`
+/// 或除以它，结果限制在 `[min_distance, max_distance]` 内。
 #[derive(Debug, Copy, Clone)]
-pub struct CameraController {
+pub struct KeyboardController {
     speed: f32,
+    /// 向前滚动一格时距离缩放的比例，小于 1
+    pub zoom_step: f32,
+    pub min_distance: f32,
+    pub max_distance: f32,
     is_forward_pressed: bool,
     is_backward_pressed: bool,
     is_left_pressed: bool,
     is_right_pressed: bool,
+    /// 上一次更新以来累计的滚动格数，向前滚动为正
+    scroll: f32,
 }
 
-impl CameraController {
+impl KeyboardController {
     pub fn new(speed: f32) -> Self {
         Self {
             speed,
+            zoom_step: 0.9,
+            min_distance: 0.1,
+            max_distance: f32::INFINITY,
             is_forward_pressed: false,
             is_backward_pressed: false,
             is_left_pressed: false,
             is_right_pressed: false,
+            scroll: 0.0,
         }
     }
 
-    pub fn process_events(&mut self, event: &KeyEvent) -> bool {
-        let KeyEvent {
-            state,
-            physical_key,
-            ..
-        } = event;
+    pub fn with_zoom_step(mut self, zoom_step: f32) -> Self {
+        self.zoom_step = zoom_step;
+        self
+    }
+
+    pub fn with_distance_range(mut self, min_distance: f32, max_distance: f32) -> Self {
+        self.min_distance = min_distance;
+        self.max_distance = max_distance;
+        self
+    }
+}
+
+impl CameraController for KeyboardController {
+    fn process_event(&mut self, event: &InputEvent) -> bool {
+        let (state, physical_key) = match event {
+            InputEvent::Key(KeyInput {
+                state,
+                physical_key,
+                ..
+            }) => (state, physical_key),
+            InputEvent::Wheel(delta) => {
+                self.scroll += match *delta {
+                    MouseScrollDelta::LineDelta(_, y) => y,
+                    // 触控板按像素滚动，约 40 像素算一格
+                    MouseScrollDelta::PixelDelta(p) => p.y as f32 / 40.0,
+                };
+                return true;
+            }
+            _ => return false,
+        };
 
         let is_pressed = *state == ElementState::Pressed;
 
@@ -102,7 +304,7 @@ impl CameraController {
         }
     }
 
-    pub fn update_camera(&self, camera: &mut Camera) {
+    fn update(&mut self, camera: &mut Camera, _dt: f32) {
         let forward = camera.target - camera.eye;
         let forward_norm = forward.normalize();
         let forward_mag = forward.length();
@@ -129,41 +331,344 @@ impl CameraController {
         if self.is_left_pressed {
             camera.eye = camera.target - (forward - right * self.speed).normalize() * forward_mag;
         }
+
+        if self.scroll != 0.0 {
+            let offset = camera.eye - camera.target;
+            let distance = (offset.length() * self.zoom_step.powf(self.scroll))
+                .clamp(self.min_distance, self.max_distance.max(self.min_distance));
+            camera.eye = camera.target + offset.normalize_or(glam::Vec3::Z) * distance;
+            self.scroll = 0.0;
+        }
     }
 }
 
+/// 平滑相机：内部控制器只移动目标相机，实际相机每次更新以指数衰减向目标靠近，
+/// 让逐帧跳变的移动（如 [`KeyboardController`]）变得连续，松开按键后仍会滑行到目标位置
+///
+/// 观察点线性插值，视线方向球面插值，视线长度线性插值，只修改 `eye` 与 `target`。
+/// 在外部直接修改相机位置后需要调用 [`SmoothedCamera::snap`]，否则会被拉回原来的目标。
 #[derive(Debug, Clone)]
-pub struct CameraBuddle {
+pub struct SmoothedCamera<C> {
+    pub inner: C,
+    /// 衰减速率（1/秒），越大越快到达目标；每秒剩余的距离比例为 `exp(-damping)`
+    pub damping: f32,
+    goal: Option<Camera>,
+}
+
+impl<C: CameraController> SmoothedCamera<C> {
+    pub fn new(inner: C) -> Self {
+        Self {
+            inner,
+            damping: 10.0,
+            goal: None,
+        }
+    }
+
+    pub fn with_damping(mut self, damping: f32) -> Self {
+        self.damping = damping;
+        self
+    }
+
+    /// 丢弃当前目标，下一次更新时以相机的实际位置作为新目标
+    pub fn snap(&mut self) {
+        self.goal = None;
+    }
+}
+
+impl<C: CameraController> CameraController for SmoothedCamera<C> {
+    fn process_event(&mut self, event: &InputEvent) -> bool {
+        self.inner.process_event(event)
+    }
+
+    fn update(&mut self, camera: &mut Camera, dt: f32) {
+        let goal = self.goal.get_or_insert(*camera);
+        self.inner.update(goal, dt);
+
+        // 与帧率无关的插值比例
+        let t = 1.0 - (-self.damping.max(0.0) * dt.max(0.0)).exp();
+        let offset = camera.eye - camera.target;
+        let goal_offset = goal.eye - goal.target;
+        let rotation = glam::Quat::IDENTITY.slerp(
+            glam::Quat::from_rotation_arc(
+                offset.normalize_or(glam::Vec3::Z),
+                goal_offset.normalize_or(glam::Vec3::Z),
+            ),
+            t,
+        );
+        let distance = offset.length() + (goal_offset.length() - offset.length()) * t;
+        camera.target = camera.target.lerp(goal.target, t);
+        camera.eye = camera.target + rotation * offset.normalize_or(glam::Vec3::Z) * distance;
+    }
+}
+
+/// 跟随相机：追踪一个移动的目标，相机与观察点分别由临界阻尼附近的弹簧拉向期望位置
+///
+/// 期望的相机位置为目标位置加上 `offset`，观察点为目标位置加上 `look_offset`，再沿目标的速度
+/// 方向前移 `look_ahead` 秒的距离，让相机提前看向目标要去的地方。`follow_rotation` 为 true 时
+/// 两个偏移都在目标的局部坐标系中，相机会绕到目标身后。每帧用 [`FollowController::set_target`]
+/// 写入目标的变换，可以通过 [`CameraBundle::controller_mut`] 取得控制器；设置目标之前不移动相机。
+#[derive(Debug, Copy, Clone)]
+pub struct FollowController {
+    /// 相机相对目标的偏移，默认在 +Z 方向（身后）的上方
+    pub offset: glam::Vec3,
+    /// 观察点相对目标的偏移
+    pub look_offset: glam::Vec3,
+    /// 观察点沿目标速度方向前移的时间（秒），为零时始终看向目标
+    pub look_ahead: f32,
+    /// 弹簧的固有角频率（弧度/秒），越大跟得越紧
+    pub frequency: f32,
+    /// 阻尼比，1 为临界阻尼，小于 1 时会有回弹
+    pub damping_ratio: f32,
+    pub follow_rotation: bool,
+    target: Option<(glam::Vec3, glam::Quat)>,
+    /// 上一次更新时的目标位置，用于估计目标速度
+    previous: Option<glam::Vec3>,
+    eye_velocity: glam::Vec3,
+    look_velocity: glam::Vec3,
+    snapped: bool,
+}
+
+impl Default for FollowController {
+    fn default() -> Self {
+        Self {
+            offset: glam::vec3(0.0, 2.0, 5.0),
+            look_offset: glam::vec3(0.0, 1.0, 0.0),
+            look_ahead: 0.3,
+            frequency: 6.0,
+            damping_ratio: 1.0,
+            follow_rotation: true,
+            target: None,
+            previous: None,
+            eye_velocity: glam::Vec3::ZERO,
+            look_velocity: glam::Vec3::ZERO,
+            snapped: false,
+        }
+    }
+}
+
+impl FollowController {
+    pub fn new(offset: glam::Vec3) -> Self {
+        Self {
+            offset,
+            ..Default::default()
+        }
+    }
+
+    pub fn with_look_offset(mut self, look_offset: glam::Vec3) -> Self {
+        self.look_offset = look_offset;
+        self
+    }
+
+    pub fn with_look_ahead(mut self, look_ahead: f32) -> Self {
+        self.look_ahead = look_ahead;
+        self
+    }
+
+    pub fn with_spring(mut self, frequency: f32, damping_ratio: f32) -> Self {
+        self.frequency = frequency;
+        self.damping_ratio = damping_ratio;
+        self
+    }
+
+    pub fn with_follow_rotation(mut self, follow_rotation: bool) -> Self {
+        self.follow_rotation = follow_rotation;
+        self
+    }
+
+    /// 目标在世界空间中的位置与朝向
+    pub fn set_target(&mut self, position: glam::Vec3, rotation: glam::Quat) {
+        self.target = Some((position, rotation));
+    }
+
+    /// 下一次更新时直接把相机放到期望位置，例如切换目标或传送之后
+    pub fn snap(&mut self) {
+        self.snapped = false;
+        self.previous = None;
+    }
+
+    /// 期望的 `(eye, target)`，`velocity` 为目标的速度
+    fn goal(
+        &self,
+        position: glam::Vec3,
+        rotation: glam::Quat,
+        velocity: glam::Vec3,
+    ) -> (glam::Vec3, glam::Vec3) {
+        let rotation = if self.follow_rotation {
+            rotation
+        } else {
+            glam::Quat::IDENTITY
+        };
+        (
+            position + rotation * self.offset,
+            position + rotation * self.look_offset + velocity * self.look_ahead,
+        )
+    }
+
+    /// 阻尼弹簧的一步隐式欧拉积分，任意步长下都稳定；返回新的 `(位置, 速度)`
+    fn spring(
+        &self,
+        position: glam::Vec3,
+        velocity: glam::Vec3,
+        goal: glam::Vec3,
+        dt: f32,
+    ) -> (glam::Vec3, glam::Vec3) {
+        let omega = self.frequency.max(0.0);
+        let zeta = self.damping_ratio.max(0.0);
+        let velocity = (velocity + (goal - position) * (omega * omega * dt))
+            / (1.0 + 2.0 * zeta * omega * dt + omega * omega * dt * dt);
+        (position + velocity * dt, velocity)
+    }
+}
+
+impl CameraController for FollowController {
+    fn process_event(&mut self, _event: &InputEvent) -> bool {
+        false
+    }
+
+    fn update(&mut self, camera: &mut Camera, dt: f32) {
+        let Some((position, rotation)) = self.target else {
+            return;
+        };
+        let dt = dt.max(0.0);
+        let velocity = match self.previous {
+            Some(previous) if dt > 0.0 => (position - previous) / dt,
+            _ => glam::Vec3::ZERO,
+        };
+        self.previous = Some(position);
+        let (eye, target) = self.goal(position, rotation, velocity);
+        if !self.snapped {
+            (camera.eye, camera.target) = (eye, target);
+            self.eye_velocity = glam::Vec3::ZERO;
+            self.look_velocity = glam::Vec3::ZERO;
+            self.snapped = true;
+            return;
+        }
+        (camera.eye, self.eye_velocity) = self.spring(camera.eye, self.eye_velocity, eye, dt);
+        (camera.target, self.look_velocity) =
+            self.spring(camera.target, self.look_velocity, target, dt);
+    }
+}
+
+#[derive(Debug)]
+pub struct CameraBundle {
     pub state: Camera,
     pub mat: CameraUniform,
-    pub controller: CameraController,
+    pub controller: Box<dyn CameraController>,
+    /// 播放中的路径代替控制器移动相机，暂停或播放结束后交还给控制器
+    pub path: Option<CameraPath>,
+    /// 投影在 NDC 中的亚像素偏移，用于 TAA；为零时不抖动
+    pub jitter: glam::Vec2,
     pub buffer: Buffer,
     pub bind_group_layout: BindGroupLayout,
     pub bind_group: BindGroup,
+    prev_view_proj: Option<glam::Mat4>,
 }
 
-impl CameraBuddle {
-    pub fn new(camera: Camera, speed: f32, device: &Device) -> Self {
+#[deprecated(note = "renamed to `CameraBundle`")]
+pub type CameraBuddle = CameraBundle;
+
+pub struct CameraBundleBuilder<'a> {
+    camera: Camera,
+    controller: Box<dyn CameraController>,
+    visibility: ShaderStages,
+    layout_cache: Option<&'a LayoutCache>,
+}
+
+impl<'a> CameraBundleBuilder<'a> {
+    /// 默认为速度 0.2 的 [`KeyboardController`]
+    pub fn controller(mut self, controller: impl CameraController + 'static) -> Self {
+        self.controller = Box::new(controller);
+        self
+    }
+
+    /// 相机 uniform 在哪些着色器阶段可见，默认仅顶点着色器
+    pub fn visibility(mut self, visibility: ShaderStages) -> Self {
+        self.visibility = visibility;
+        self
+    }
+
+    pub fn layout_cache(mut self, layout_cache: &'a LayoutCache) -> Self {
+        self.layout_cache = Some(layout_cache);
+        self
+    }
+
+    fn validate(&self) -> anyhow::Result<()> {
+        let Camera {
+            aspect,
+            projection,
+            znear,
+            zfar,
+            ..
+        } = self.camera;
+        match projection {
+            Projection::Perspective { fovy } => ensure!(
+                fovy.is_finite() && fovy > 0.0 && fovy < 180.0,
+                "camera fovy must be in (0, 180) degrees, got {fovy}"
+            ),
+            Projection::Orthographic { half_height } => ensure!(
+                half_height.is_finite() && half_height > 0.0,
+                "camera half_height must be positive, got {half_height}"
+            ),
+            Projection::OrthographicBounds {
+                left,
+                right,
+                bottom,
+                top,
+            } => ensure!(
+                [left, right, bottom, top].iter().all(|v| v.is_finite())
+                    && left < right
+                    && bottom < top,
+                "camera orthographic bounds must satisfy left < right and bottom < top, \
+                 got left {left}, right {right}, bottom {bottom}, top {top}"
+            ),
+        }
+        ensure!(
+            znear.is_finite() && znear > 0.0,
+            "camera znear must be positive, got {znear}"
+        );
+        ensure!(
+            zfar > znear,
+            "camera zfar ({zfar}) must be greater than znear ({znear})"
+        );
+        ensure!(
+            zfar.is_finite()
+                || (self.camera.depth == DepthConvention::Reversed
+                    && !projection.is_orthographic()),
+            "an infinite camera zfar needs a reversed-depth perspective projection"
+        );
+        ensure!(
+            aspect.is_finite() && aspect > 0.0,
+            "camera aspect must be positive, got {aspect}"
+        );
+        ensure!(
+            (self.camera.target - self.camera.eye).length_squared() > 0.0,
+            "camera eye and target must not coincide"
+        );
+        Ok(())
+    }
+
+    pub fn build(self, device: &Device) -> anyhow::Result<CameraBundle> {
+        self.validate()?;
+
+        let camera = self.camera;
         let mut mat = CameraUniform::new();
         mat.update_view_proj(&camera);
+        mat.set_motion(camera.build_view_projection_matrix(), glam::Vec2::ZERO);
         let buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
             label: Some("Camera Buffer"),
             contents: bytemuck::cast_slice(&[mat]),
             usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
         });
-        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
-            entries: &[wgpu::BindGroupLayoutEntry {
-                binding: 0,
-                visibility: wgpu::ShaderStages::VERTEX,
-                ty: wgpu::BindingType::Buffer {
-                    ty: wgpu::BufferBindingType::Uniform,
-                    has_dynamic_offset: false,
-                    min_binding_size: None,
-                },
-                count: None,
-            }],
-            label: Some("camera_bind_group_layout"),
-        });
+
+        let entries = [CameraBundle::layout_entry(self.visibility)];
+        let bind_group_layout = match self.layout_cache {
+            Some(cache) => cache.get_or_create(device, "camera_bind_group_layout", &entries),
+            None => device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                entries: &entries,
+                label: Some("camera_bind_group_layout"),
+            }),
+        };
+
         let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
             layout: &bind_group_layout,
             entries: &[wgpu::BindGroupEntry {
@@ -172,19 +677,110 @@ impl CameraBuddle {
             }],
             label: Some("camera_bind_group"),
         });
-        Self {
+
+        Ok(CameraBundle {
             state: camera,
             mat,
-            controller: CameraController::new(speed),
+            controller: self.controller,
+            path: None,
+            jitter: glam::Vec2::ZERO,
             buffer,
             bind_group_layout,
             bind_group,
+            prev_view_proj: None,
+        })
+    }
+}
+
+impl CameraBundle {
+    /// `bind_group_layout` 唯一的条目，`visibility` 与 builder 中设置的一致
+    pub fn layout_entry(visibility: ShaderStages) -> wgpu::BindGroupLayoutEntry {
+        wgpu::BindGroupLayoutEntry {
+            binding: 0,
+            visibility,
+            ty: wgpu::BindingType::Buffer {
+                ty: wgpu::BufferBindingType::Uniform,
+                has_dynamic_offset: false,
+                min_binding_size: wgpu::BufferSize::new(
+                    std::mem::size_of::<CameraUniform>() as wgpu::BufferAddress
+                ),
+            },
+            count: None,
         }
     }
 
-    pub fn update(&mut self, queue: &Queue) {
-        self.controller.update_camera(&mut self.state);
-        self.mat.update_view_proj(&self.state);
-        queue.write_buffer(&self.buffer, 0, bytemuck::cast_slice(&[self.mat]));
+    pub fn builder<'a>(camera: Camera) -> CameraBundleBuilder<'a> {
+        CameraBundleBuilder {
+            camera,
+            controller: Box::new(KeyboardController::new(0.2)),
+            visibility: ShaderStages::VERTEX,
+            layout_cache: None,
+        }
+    }
+
+    /// # Panics
+    /// 相机参数不合法时 panic，需要处理错误时请使用 [`CameraBundle::builder`]
+    pub fn new(camera: Camera, speed: f32, device: &Device) -> Self {
+        Self::builder(camera)
+            .controller(KeyboardController::new(speed))
+            .build(device)
+            .expect("invalid camera parameters")
+    }
+
+    /// surface 大小变化时更新投影的宽高比
+    pub fn resize(&mut self, size: PhysicalSize<u32>) {
+        self.state.set_viewport_size(size);
+    }
+
+    /// 把输入交给控制器，返回是否处理了该事件
+    pub fn process_event(&mut self, event: &InputEvent) -> bool {
+        self.controller.process_event(event)
+    }
+
+    /// [`CameraBundle::process_event`] 的按键版本，用于 [`WindowApp::keyboard_input`](crate::app::WindowApp::keyboard_input)
+    pub fn process_key(&mut self, event: &KeyEvent) -> bool {
+        self.process_event(&InputEvent::Key(event.into()))
+    }
+
+    /// [`CameraBundle::process_event`] 的滚轮版本，用于 [`WindowApp::mouse_wheel`](crate::app::WindowApp::mouse_wheel)
+    pub fn process_wheel(&mut self, delta: MouseScrollDelta) -> bool {
+        self.process_event(&InputEvent::Wheel(delta))
+    }
+
+    /// 当前控制器的类型为 `T` 时返回它，以便调整参数或写入状态
+    pub fn controller_mut<T: CameraController>(&mut self) -> Option<&mut T> {
+        let controller: &mut dyn Any = self.controller.as_mut();
+        controller.downcast_mut::<T>()
+    }
+
+    /// 替换当前路径并从头播放
+    pub fn play_path(&mut self, mut path: CameraPath) {
+        path.seek(path.start_time());
+        path.play();
+        self.path = Some(path);
+    }
+
+    /// 由路径或控制器移动相机后写入 uniform，`dt` 为距上一次更新的秒数
+    pub fn update(&mut self, queue: &Queue, dt: f32) {
+        match self.path.as_mut().filter(|path| path.is_playing()) {
+            Some(path) => {
+                path.advance(dt);
+                path.apply(&mut self.state);
+            }
+            None => self.controller.update(&mut self.state, dt),
+        }
+        let view = self.state.view_matrix();
+        let proj = self.state.projection_matrix();
+        let view_proj = proj * view;
+        // 平移裁剪空间的 xy 分量（乘以 w），等价于在 NDC 中偏移 `jitter`
+        self.mat.set_view_and_projection(
+            view,
+            glam::Mat4::from_translation(self.jitter.extend(0.0)) * proj,
+            self.state.eye,
+        );
+        self.mat
+            .set_motion(self.prev_view_proj.unwrap_or(view_proj), self.jitter);
+        self.prev_view_proj = Some(view_proj);
+        self.mat.write_to(queue, &self.buffer);
     }
 }
@@ -0,0 +1,252 @@
+use crate::{app::KeyInput, viewport::ViewportRect};
+use winit::{
+    dpi::{PhysicalPosition, PhysicalSize},
+    event::{ElementState, MouseButton, MouseScrollDelta},
+    keyboard::{KeyCode, PhysicalKey},
+};
+
+/// 2D 正交相机，世界单位在缩放为 1 时等于一个逻辑像素，y 轴向下
+///
+/// 视口以物理像素表示，`scale_factor` 为窗口的 DPI 缩放，
+/// 因此同一场景在高分屏上保持相同的逻辑大小。
+///
+/// 设置 [`Camera2D::virtual_resolution`] 后世界单位在缩放为 1 时等于一个虚拟像素，
+/// 虚拟画面保持宽高比放大到窗口中央，其余部分留黑边；绘制前须用
+/// [`Camera2D::screen_rect`] 设置视口与裁剪矩形。
+#[derive(Debug, Copy, Clone)]
+pub struct Camera2D {
+    /// 视口中心对应的世界坐标
+    pub position: glam::Vec2,
+    pub zoom: f32,
+    pub viewport: PhysicalSize<u32>,
+    pub scale_factor: f64,
+    /// 把每个世界单位对应的物理像素数取整，并把相机位置对齐到物理像素，像素画不会闪烁或变形
+    pub pixel_perfect: bool,
+    /// 以虚拟像素表示的画面大小，为 `None` 时画面覆盖整个视口，此时忽略 `scale_factor`
+    pub virtual_resolution: Option<PhysicalSize<u32>>,
+}
+
+impl Camera2D {
+    pub const MIN_ZOOM: f32 = 0.05;
+    pub const MAX_ZOOM: f32 = 64.0;
+
+    pub fn new(viewport: PhysicalSize<u32>, scale_factor: f64) -> Self {
+        Self {
+            position: glam::Vec2::ZERO,
+            zoom: 1.0,
+            viewport,
+            scale_factor,
+            pixel_perfect: false,
+            virtual_resolution: None,
+        }
+    }
+
+    pub fn with_virtual_resolution(mut self, resolution: PhysicalSize<u32>) -> Self {
+        self.virtual_resolution = Some(resolution);
+        self
+    }
+
+    pub fn resize(&mut self, viewport: PhysicalSize<u32>) {
+        self.viewport = viewport;
+    }
+
+    /// 缩放为 1 时每个世界单位对应的物理像素数，像素对齐模式下虚拟画面只按整数倍放大
+    fn base_scale(&self) -> f32 {
+        let Some(resolution) = self
+            .virtual_resolution
+            .filter(|r| r.width > 0 && r.height > 0)
+        else {
+            return self.scale_factor as f32;
+        };
+        let scale = (self.viewport.width as f32 / resolution.width as f32)
+            .min(self.viewport.height as f32 / resolution.height as f32);
+        if self.pixel_perfect {
+            scale.floor().max(1.0)
+        } else {
+            scale
+        }
+    }
+
+    /// 画面在视口中的区域（物理像素），没有虚拟分辨率时为整个视口
+    pub fn screen_rect(&self) -> ViewportRect {
+        let Some(resolution) = self
+            .virtual_resolution
+            .filter(|r| r.width > 0 && r.height > 0)
+        else {
+            return ViewportRect::full(self.viewport);
+        };
+        let scale = self.base_scale();
+        let width = ((resolution.width as f32 * scale).round() as u32).min(self.viewport.width);
+        let height = ((resolution.height as f32 * scale).round() as u32).min(self.viewport.height);
+        ViewportRect::new(
+            (self.viewport.width - width) / 2,
+            (self.viewport.height - height) / 2,
+            width,
+            height,
+        )
+    }
+
+    /// 每个世界单位对应的物理像素数
+    pub fn pixels_per_unit(&self) -> f32 {
+        let ppu = self.zoom * self.base_scale();
+        if self.pixel_perfect {
+            ppu.round().max(1.0)
+        } else {
+            ppu
+        }
+    }
+
+    /// 实际用于投影的相机位置，像素对齐模式下使视口左上角落在物理像素的边界上
+    pub fn snapped_position(&self) -> glam::Vec2 {
+        if self.pixel_perfect {
+            let ppu = self.pixels_per_unit();
+            // 视口宽高为奇数时中心在半个像素处，对齐的是左上角而不是中心
+            let half = self.viewport_size() * 0.5;
+            ((self.position * ppu - half).round() + half) / ppu
+        } else {
+            self.position
+        }
+    }
+
+    /// 画面的物理像素大小
+    fn viewport_size(&self) -> glam::Vec2 {
+        let rect = self.screen_rect();
+        glam::vec2(rect.width as f32, rect.height as f32)
+    }
+
+    /// 画面左上角在视口中的物理像素坐标
+    fn screen_origin(&self) -> glam::Vec2 {
+        let rect = self.screen_rect();
+        glam::vec2(rect.x as f32, rect.y as f32)
+    }
+
+    /// 画面覆盖的世界范围的一半
+    pub fn half_extent(&self) -> glam::Vec2 {
+        self.viewport_size() * 0.5 / self.pixels_per_unit()
+    }
+
+    pub fn view_proj(&self) -> glam::Mat4 {
+        let center = self.snapped_position();
+        let half = self.half_extent();
+        glam::Mat4::orthographic_rh(
+            center.x - half.x,
+            center.x + half.x,
+            center.y + half.y,
+            center.y - half.y,
+            -1.0,
+            1.0,
+        )
+    }
+
+    /// 窗口中的物理像素坐标（例如光标位置）对应的世界坐标
+    pub fn screen_to_world(&self, screen: PhysicalPosition<f64>) -> glam::Vec2 {
+        let screen = glam::vec2(screen.x as f32, screen.y as f32) - self.screen_origin();
+        self.snapped_position() + (screen - self.viewport_size() * 0.5) / self.pixels_per_unit()
+    }
+
+    pub fn world_to_screen(&self, world: glam::Vec2) -> PhysicalPosition<f64> {
+        let screen = (world - self.snapped_position()) * self.pixels_per_unit()
+            + self.viewport_size() * 0.5
+            + self.screen_origin();
+        PhysicalPosition::new(screen.x as f64, screen.y as f64)
+    }
+
+    /// 逻辑像素到物理像素，UI 布局通常以逻辑像素为单位
+    pub fn logical_to_physical(&self, logical: glam::Vec2) -> glam::Vec2 {
+        logical * self.scale_factor as f32
+    }
+
+    pub fn physical_to_logical(&self, physical: glam::Vec2) -> glam::Vec2 {
+        physical / self.scale_factor as f32
+    }
+
+    /// 按 `factor` 缩放，保持 `anchor` 处（物理像素）的世界坐标不动
+    pub fn zoom_at(&mut self, anchor: PhysicalPosition<f64>, factor: f32) {
+        let before = self.screen_to_world(anchor);
+        self.zoom = (self.zoom * factor).clamp(Self::MIN_ZOOM, Self::MAX_ZOOM);
+        let after = self.screen_to_world(anchor);
+        self.position += before - after;
+    }
+}
+
+/// [`Camera2D`] 的平移与缩放控制：WASD 或方向键平移，鼠标中键或右键拖动平移，滚轮以光标为中心缩放
+#[derive(Debug, Copy, Clone)]
+pub struct Camera2DController {
+    /// 键盘平移速度，单位为逻辑像素每秒，与缩放无关
+    pub speed: f32,
+    /// 滚轮每滚动一格的缩放倍数
+    pub zoom_step: f32,
+    direction: [bool; 4],
+    dragging: bool,
+    cursor: Option<PhysicalPosition<f64>>,
+}
+
+impl Camera2DController {
+    pub fn new(speed: f32) -> Self {
+        Self {
+            speed,
+            zoom_step: 1.1,
+            direction: [false; 4],
+            dragging: false,
+            cursor: None,
+        }
+    }
+
+    /// 使用 [`KeyEvent`](winit::event::KeyEvent) 时传入 `&event.into()`
+    pub fn process_keyboard(&mut self, input: &KeyInput) -> bool {
+        let pressed = input.state == ElementState::Pressed;
+        let index = match input.physical_key {
+            PhysicalKey::Code(KeyCode::KeyW | KeyCode::ArrowUp) => 0,
+            PhysicalKey::Code(KeyCode::KeyA | KeyCode::ArrowLeft) => 1,
+            PhysicalKey::Code(KeyCode::KeyS | KeyCode::ArrowDown) => 2,
+            PhysicalKey::Code(KeyCode::KeyD | KeyCode::ArrowRight) => 3,
+            _ => return false,
+        };
+        self.direction[index] = pressed;
+        true
+    }
+
+    pub fn process_mouse_button(&mut self, state: ElementState, button: MouseButton) -> bool {
+        if !matches!(button, MouseButton::Middle | MouseButton::Right) {
+            return false;
+        }
+        self.dragging = state == ElementState::Pressed;
+        true
+    }
+
+    /// 拖动时按光标移动的距离平移相机，使光标下的世界坐标保持不动
+    pub fn process_cursor(
+        &mut self,
+        position: PhysicalPosition<f64>,
+        camera: &mut Camera2D,
+    ) -> bool {
+        let last = self.cursor.replace(position);
+        let Some(last) = last.filter(|_| self.dragging) else {
+            return false;
+        };
+        camera.position += camera.screen_to_world(last) - camera.screen_to_world(position);
+        true
+    }
+
+    pub fn process_wheel(&mut self, delta: MouseScrollDelta, camera: &mut Camera2D) -> bool {
+        let lines = match delta {
+            MouseScrollDelta::LineDelta(_, y) => y,
+            // 触控板按像素滚动，约 40 像素算一格
+            MouseScrollDelta::PixelDelta(p) => p.y as f32 / 40.0,
+        };
+        let anchor = self.cursor.unwrap_or_else(|| {
+            PhysicalPosition::new(
+                camera.viewport.width as f64 * 0.5,
+                camera.viewport.height as f64 * 0.5,
+            )
+        });
+        camera.zoom_at(anchor, self.zoom_step.powf(lines));
+        true
+    }
+
+    pub fn update_camera(&self, camera: &mut Camera2D, dt: f32) {
+        let [up, left, down, right] = self.direction.map(|d| d as i32 as f32);
+        let direction = glam::vec2(right - left, down - up).normalize_or_zero();
+        camera.position += direction * self.speed * dt / camera.zoom;
+    }
+}
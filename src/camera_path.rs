@@ -0,0 +1,175 @@
+use anyhow::ensure;
+
+use crate::{
+    camera::Camera,
+    spline::{CatmullRom, Curve},
+};
+
+/// 相机路径上的关键帧，`time` 以秒为单位
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct CameraKeyframe {
+    pub time: f32,
+    pub eye: glam::Vec3,
+    pub target: glam::Vec3,
+}
+
+impl CameraKeyframe {
+    pub fn new(time: f32, eye: glam::Vec3, target: glam::Vec3) -> Self {
+        Self { time, eye, target }
+    }
+}
+
+/// 作用于整条路径的时间曲线，输入与输出都在 [0, 1]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum Easing {
+    #[default]
+    Linear,
+    /// 三次缓入
+    EaseIn,
+    /// 三次缓出
+    EaseOut,
+    /// smoothstep，起止速度为零
+    EaseInOut,
+}
+
+impl Easing {
+    pub fn apply(self, t: f32) -> f32 {
+        let t = t.clamp(0.0, 1.0);
+        match self {
+            Easing::Linear => t,
+            Easing::EaseIn => t * t * t,
+            Easing::EaseOut => 1.0 - (1.0 - t).powi(3),
+            Easing::EaseInOut => t * t * (3.0 - 2.0 * t),
+        }
+    }
+}
+
+/// 关键帧相机路径：位置与观察点分别用经过关键帧的 Catmull-Rom 样条插值
+///
+/// 相邻关键帧之间按时间线性映射到样条的一段，再对整条路径的时间施加 `easing`。
+/// 交给 [`CameraBundle::play_path`](crate::camera::CameraBundle::play_path) 后，
+/// 播放期间由路径而不是控制器驱动相机。
+#[derive(Debug, Clone)]
+pub struct CameraPath {
+    keyframes: Vec<CameraKeyframe>,
+    eye: CatmullRom,
+    target: CatmullRom,
+    pub easing: Easing,
+    /// 为 true 时播放到结尾后从头开始，否则停在最后一帧
+    pub looping: bool,
+    time: f32,
+    playing: bool,
+}
+
+impl CameraPath {
+    /// 关键帧至少两个，时间须严格递增；创建后处于暂停状态，时间为第一个关键帧
+    pub fn new(keyframes: Vec<CameraKeyframe>) -> anyhow::Result<Self> {
+        ensure!(
+            keyframes.len() >= 2,
+            "camera path needs at least 2 keyframes, got {}",
+            keyframes.len()
+        );
+        ensure!(
+            keyframes.iter().all(|k| k.time.is_finite())
+                && keyframes.windows(2).all(|k| k[0].time < k[1].time),
+            "camera path keyframe times must be finite and strictly increasing"
+        );
+        let time = keyframes[0].time;
+        Ok(Self {
+            eye: CatmullRom::new(keyframes.iter().map(|k| k.eye).collect()),
+            target: CatmullRom::new(keyframes.iter().map(|k| k.target).collect()),
+            keyframes,
+            easing: Easing::default(),
+            looping: false,
+            time,
+            playing: false,
+        })
+    }
+
+    pub fn with_easing(mut self, easing: Easing) -> Self {
+        self.easing = easing;
+        self
+    }
+
+    pub fn with_looping(mut self, looping: bool) -> Self {
+        self.looping = looping;
+        self
+    }
+
+    pub fn keyframes(&self) -> &[CameraKeyframe] {
+        &self.keyframes
+    }
+
+    pub fn start_time(&self) -> f32 {
+        self.keyframes[0].time
+    }
+
+    pub fn end_time(&self) -> f32 {
+        self.keyframes[self.keyframes.len() - 1].time
+    }
+
+    pub fn duration(&self) -> f32 {
+        self.end_time() - self.start_time()
+    }
+
+    /// 时间 `time` 处的 `(eye, target)`，超出关键帧范围时取两端
+    pub fn sample(&self, time: f32) -> (glam::Vec3, glam::Vec3) {
+        let progress = (time - self.start_time()) / self.duration();
+        let time = self.start_time() + self.easing.apply(progress) * self.duration();
+        let i = self
+            .keyframes
+            .partition_point(|k| k.time <= time)
+            .clamp(1, self.keyframes.len() - 1);
+        let (a, b) = (self.keyframes[i - 1].time, self.keyframes[i].time);
+        let local = ((time - a) / (b - a)).clamp(0.0, 1.0);
+        (
+            self.eye.segment(i - 1).point(local),
+            self.target.segment(i - 1).point(local),
+        )
+    }
+
+    pub fn time(&self) -> f32 {
+        self.time
+    }
+
+    pub fn is_playing(&self) -> bool {
+        self.playing
+    }
+
+    /// 从当前时间开始播放，已经停在结尾时从头播放
+    pub fn play(&mut self) {
+        if self.time >= self.end_time() {
+            self.time = self.start_time();
+        }
+        self.playing = true;
+    }
+
+    pub fn pause(&mut self) {
+        self.playing = false;
+    }
+
+    pub fn seek(&mut self, time: f32) {
+        self.time = time.clamp(self.start_time(), self.end_time());
+    }
+
+    /// 播放中时前进 `dt` 秒；不循环的路径到达结尾后自动暂停
+    pub fn advance(&mut self, dt: f32) {
+        if !self.playing {
+            return;
+        }
+        self.time += dt.max(0.0);
+        if self.time >= self.end_time() {
+            if self.looping {
+                self.time = self.start_time() + (self.time - self.start_time()) % self.duration();
+            } else {
+                self.time = self.end_time();
+                self.playing = false;
+            }
+        }
+    }
+
+    /// 把当前时间的位置与观察点写入相机
+    pub fn apply(&self, camera: &mut Camera) {
+        (camera.eye, camera.target) = self.sample(self.time);
+    }
+}
@@ -0,0 +1,288 @@
+use std::{
+    collections::VecDeque,
+    io::Write,
+    path::{Path, PathBuf},
+    process::{Child, Command, Stdio},
+    sync::mpsc::{channel, sync_channel, Receiver, SyncSender},
+    thread::JoinHandle,
+};
+
+// wasm32 上 std::time::SystemTime 不可用
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::{SystemTime, UNIX_EPOCH};
+#[cfg(target_arch = "wasm32")]
+use web_time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::{anyhow, ensure, Context};
+
+use crate::texture::{
+    copy_texture_to_readback, padded_bytes_per_row, read_texture_rgba8, rgba8_swizzle, unpad_rgba8,
+};
+
+/// 把 surface 纹理或任意渲染目标读回为 RGBA 图像
+///
+/// 纹理需带 `COPY_SRC` 用途，格式为 RGBA8 或 BGRA8；会阻塞等待 GPU 完成之前提交的工作，
+/// 因此应在提交本帧的绘制命令之后、present 之前调用。
+pub fn screenshot(
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    texture: &wgpu::Texture,
+) -> anyhow::Result<image::RgbaImage> {
+    read_texture_rgba8(device, queue, texture)
+}
+
+/// 截图并保存为 PNG
+pub fn save_screenshot(
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    texture: &wgpu::Texture,
+    path: impl AsRef<Path>,
+) -> anyhow::Result<()> {
+    let path = path.as_ref();
+    screenshot(device, queue, texture)?
+        .save_with_format(path, image::ImageFormat::Png)
+        .with_context(|| format!("failed to save screenshot to {}", path.display()))
+}
+
+/// 当前目录下以毫秒时间戳命名的截图路径，例如 `screenshot-1700000000123.png`
+pub fn screenshot_path() -> PathBuf {
+    PathBuf::from(format!("screenshot-{}.png", timestamp_millis()))
+}
+
+/// 当前目录下以毫秒时间戳命名的录制目录，例如 `recording-1700000000123`
+pub fn recording_dir() -> PathBuf {
+    PathBuf::from(format!("recording-{}", timestamp_millis()))
+}
+
+fn timestamp_millis() -> u128 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis())
+        .unwrap_or_default()
+}
+
+/// 录制的输出方式
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RecordOutput {
+    /// 在目录中写入 `frame-000000.png` 形式的编号图片序列
+    PngSequence(PathBuf),
+    /// 把原始 RGBA 帧通过管道交给 `ffmpeg` 编码为视频，需要 ffmpeg 在 PATH 中
+    Ffmpeg { path: PathBuf, fps: u32 },
+}
+
+/// 写入线程的输出目标
+enum Sink {
+    Png(PathBuf),
+    Ffmpeg(Child),
+}
+
+impl Sink {
+    fn open(output: &RecordOutput, width: u32, height: u32) -> anyhow::Result<Self> {
+        match output {
+            RecordOutput::PngSequence(dir) => {
+                std::fs::create_dir_all(dir)
+                    .with_context(|| format!("failed to create {}", dir.display()))?;
+                Ok(Self::Png(dir.clone()))
+            }
+            RecordOutput::Ffmpeg { path, fps } => {
+                let child = Command::new("ffmpeg")
+                    .args(["-y", "-loglevel", "error"])
+                    .args(["-f", "rawvideo", "-pix_fmt", "rgba"])
+                    .args(["-s", &format!("{width}x{height}")])
+                    .args(["-r", &fps.to_string(), "-i", "-"])
+                    // yuv420p 要求宽高为偶数
+                    .args(["-vf", "crop=trunc(iw/2)*2:trunc(ih/2)*2"])
+                    .args(["-pix_fmt", "yuv420p"])
+                    .arg(path)
+                    .stdin(Stdio::piped())
+                    .spawn()
+                    .context("failed to start ffmpeg")?;
+                Ok(Self::Ffmpeg(child))
+            }
+        }
+    }
+
+    /// 写入所有收到的帧，返回写入的帧数
+    fn run(self, frames: Receiver<Vec<u8>>, width: u32, height: u32) -> anyhow::Result<u64> {
+        let mut count = 0;
+        match self {
+            Self::Png(dir) => {
+                for pixels in frames {
+                    let path = dir.join(format!("frame-{count:06}.png"));
+                    image::save_buffer(&path, &pixels, width, height, image::ColorType::Rgba8)
+                        .with_context(|| format!("failed to save {}", path.display()))?;
+                    count += 1;
+                }
+            }
+            Self::Ffmpeg(mut child) => {
+                let mut stdin = child.stdin.take().expect("ffmpeg stdin is piped");
+                for pixels in frames {
+                    stdin
+                        .write_all(&pixels)
+                        .context("failed to write frame to ffmpeg")?;
+                    count += 1;
+                }
+                // 关闭管道后 ffmpeg 才会结束编码
+                drop(stdin);
+                let status = child.wait()?;
+                ensure!(status.success(), "ffmpeg exited with {status}");
+            }
+        }
+        Ok(count)
+    }
+}
+
+/// 逐帧录制 surface 或渲染目标，输出编号的 PNG 序列或交给 ffmpeg 编码成视频
+///
+/// 每帧的画面复制到一组轮流使用的暂存缓冲中异步读回，PNG 编码与写入在后台线程进行，
+/// 不会让渲染等待 GPU。只有所有暂存缓冲都在读回中时才会等待最早的一帧，保证不丢帧。
+pub struct Recorder {
+    device: wgpu::Device,
+    queue: wgpu::Queue,
+    width: u32,
+    height: u32,
+    swizzle: bool,
+    /// 空闲的暂存缓冲
+    free: Vec<wgpu::Buffer>,
+    /// 按提交顺序排列的读回中的缓冲
+    in_flight: VecDeque<(wgpu::Buffer, Receiver<Result<(), wgpu::BufferAsyncError>>)>,
+    frames: Option<SyncSender<Vec<u8>>>,
+    writer: Option<JoinHandle<anyhow::Result<u64>>>,
+}
+
+impl Recorder {
+    /// 暂存缓冲的数量
+    pub const RING_SIZE: usize = 3;
+    /// 等待写入的帧数上限，写入跟不上时 [`Recorder::capture`] 会阻塞
+    const QUEUE_SIZE: usize = 8;
+
+    /// 录制 `width`×`height`、格式为 `format` 的画面，格式须为 RGBA8 或 BGRA8
+    pub fn new(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        width: u32,
+        height: u32,
+        format: wgpu::TextureFormat,
+        output: &RecordOutput,
+    ) -> anyhow::Result<Self> {
+        let swizzle = rgba8_swizzle(format)?;
+        let sink = Sink::open(output, width, height)?;
+        let (frames, receiver) = sync_channel(Self::QUEUE_SIZE);
+        let writer = std::thread::Builder::new()
+            .name("frame recorder".into())
+            .spawn(move || sink.run(receiver, width, height))?;
+        let free = (0..Self::RING_SIZE)
+            .map(|_| {
+                device.create_buffer(&wgpu::BufferDescriptor {
+                    label: Some("Recorder Staging Buffer"),
+                    size: (padded_bytes_per_row(width) * height) as wgpu::BufferAddress,
+                    usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+                    mapped_at_creation: false,
+                })
+            })
+            .collect();
+        Ok(Self {
+            device: device.clone(),
+            queue: queue.clone(),
+            width,
+            height,
+            swizzle,
+            free,
+            in_flight: VecDeque::new(),
+            frames: Some(frames),
+            writer: Some(writer),
+        })
+    }
+
+    /// 录制一帧；纹理需带 `COPY_SRC` 用途，本帧的绘制命令需已提交
+    pub fn capture(&mut self, texture: &wgpu::Texture) -> anyhow::Result<()> {
+        ensure!(
+            (texture.width(), texture.height()) == (self.width, self.height),
+            "frame size changed during recording"
+        );
+        self.receive(false)?;
+        if self.free.is_empty() {
+            self.receive(true)?;
+        }
+        let buffer = self.free.pop().expect("a staging buffer was just freed");
+
+        let mut encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("Recorder Encoder"),
+            });
+        copy_texture_to_readback(&mut encoder, texture, &buffer);
+        self.queue.submit(Some(encoder.finish()));
+
+        let (sender, receiver) = channel();
+        buffer
+            .slice(..)
+            .map_async(wgpu::MapMode::Read, move |result| {
+                let _ = sender.send(result);
+            });
+        self.in_flight.push_back((buffer, receiver));
+        Ok(())
+    }
+
+    /// 按顺序取出已读回的帧交给写入线程；`wait` 为 `true` 时至少等到最早的一帧
+    fn receive(&mut self, wait: bool) -> anyhow::Result<()> {
+        let mut maintain = if wait {
+            wgpu::Maintain::Wait
+        } else {
+            wgpu::Maintain::Poll
+        };
+        while let Some((_, receiver)) = self.in_flight.front() {
+            self.device.poll(maintain);
+            maintain = wgpu::Maintain::Poll;
+            let Ok(result) = receiver.try_recv() else {
+                break;
+            };
+            let (buffer, _) = self.in_flight.pop_front().unwrap();
+            result?;
+            let pixels = unpad_rgba8(
+                &buffer.slice(..).get_mapped_range(),
+                self.width,
+                self.swizzle,
+            );
+            buffer.unmap();
+            self.free.push(buffer);
+            let frames = self.frames.as_ref().expect("recorder is not finished");
+            if frames.send(pixels).is_err() {
+                // 写入线程已出错退出，取回它的错误
+                return Err(self
+                    .join()
+                    .err()
+                    .unwrap_or_else(|| anyhow!("frame recorder stopped")));
+            }
+        }
+        Ok(())
+    }
+
+    /// 等待写入线程结束，返回写入的帧数
+    fn join(&mut self) -> anyhow::Result<u64> {
+        self.frames = None;
+        let writer = self
+            .writer
+            .take()
+            .ok_or_else(|| anyhow!("recorder is finished"))?;
+        writer
+            .join()
+            .map_err(|_| anyhow!("frame recorder thread panicked"))?
+    }
+
+    /// 写完所有已录制的帧并结束录制，返回写入的帧数
+    pub fn finish(mut self) -> anyhow::Result<u64> {
+        while !self.in_flight.is_empty() {
+            self.receive(true)?;
+        }
+        self.join()
+    }
+}
+
+impl Drop for Recorder {
+    fn drop(&mut self) {
+        if self.writer.is_some() {
+            let _ = self.join();
+        }
+    }
+}
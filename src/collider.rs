@@ -0,0 +1,339 @@
+use std::collections::{HashMap, HashSet};
+
+use anyhow::ensure;
+use glam::Vec3;
+
+use crate::model::{bounding_box, Model, RenderVertex, VertexPosition};
+
+/// 顶点聚类简化时每个轴上最多的格子数
+const MAX_CLUSTER_RESOLUTION: u32 = 1024;
+
+/// 给物理模拟使用的碰撞网格，顶点与索引都在模型空间
+///
+/// 精细的渲染网格直接用于碰撞检测代价很高：动态物体通常用 [`CollisionMesh::convex_hull`]
+/// 得到的凸包，静态场景用 [`CollisionMesh::simplified`] 得到的低面数三角网格。
+/// 三角形按逆时针方向为正面，与渲染管线一致。
+#[derive(Debug, Clone, PartialEq)]
+pub struct CollisionMesh {
+    pub vertices: Vec<Vec3>,
+    pub indices: Vec<u32>,
+    /// 为 true 时网格是凸包，物理引擎可以按凸多面体处理
+    pub convex: bool,
+}
+
+impl CollisionMesh {
+    /// 包围 `points` 的凸包（quickhull），结果只包含凸包上的顶点，法线朝外
+    ///
+    /// 点的数量少于 4 个或全部共面时返回错误，这种情况应改用盒子等基本形状。
+    pub fn convex_hull(points: &[Vec3]) -> anyhow::Result<Self> {
+        ensure!(
+            points.iter().all(|p| p.is_finite()),
+            "convex hull points must be finite"
+        );
+        ensure!(
+            points.len() >= 4,
+            "convex hull needs at least 4 points, got {}",
+            points.len()
+        );
+        let (min, max) = bounding_box(points.iter().copied());
+        let epsilon = (max - min).max_element() * 1e-5;
+        ensure!(epsilon > 0.0, "convex hull points are all the same");
+
+        let Some(initial) = initial_tetrahedron(points, epsilon) else {
+            anyhow::bail!("convex hull points are coplanar");
+        };
+        let mut faces = Vec::new();
+        let center = initial.iter().map(|&i| points[i]).sum::<Vec3>() / 4.0;
+        let [a, b, c, d] = initial;
+        for [i, j, k] in [[a, b, c], [a, b, d], [a, c, d], [b, c, d]] {
+            let mut face = HullFace::new(points, [i, j, k]);
+            // 让四面体的每个面都背对其中心
+            if face.distance(center) > 0.0 {
+                face = HullFace::new(points, [i, k, j]);
+            }
+            faces.push(face);
+        }
+        let remaining = (0..points.len())
+            .filter(|i| !initial.contains(i))
+            .collect::<Vec<_>>();
+        assign_outside(points, &mut faces, remaining, epsilon);
+
+        while let Some((face, eye)) = faces
+            .iter()
+            .enumerate()
+            .filter(|(_, f)| f.alive)
+            .find_map(|(i, f)| f.farthest(points).map(|p| (i, p)))
+        {
+            let eye_point = points[eye];
+            // 当前凸包上从 eye 可见的面相互连通，直接检查所有面即可
+            let visible = faces
+                .iter()
+                .enumerate()
+                .filter(|(i, f)| f.alive && (*i == face || f.distance(eye_point) > epsilon))
+                .map(|(i, _)| i)
+                .collect::<Vec<_>>();
+            let edges = visible
+                .iter()
+                .flat_map(|&i| faces[i].edges())
+                .collect::<HashSet<_>>();
+            // 可见区域的边界：反向边不属于任何可见面
+            let horizon = edges
+                .iter()
+                .filter(|&&(i, j)| !edges.contains(&(j, i)))
+                .copied()
+                .collect::<Vec<_>>();
+
+            let mut orphans = Vec::new();
+            for &i in &visible {
+                faces[i].alive = false;
+                orphans.append(&mut faces[i].outside);
+            }
+            orphans.retain(|&i| i != eye);
+            let first_new = faces.len();
+            faces.extend(
+                horizon
+                    .into_iter()
+                    .map(|(i, j)| HullFace::new(points, [i, j, eye])),
+            );
+            assign_outside(points, &mut faces[first_new..], orphans, epsilon);
+        }
+
+        let mut remap = HashMap::new();
+        let mut vertices = Vec::new();
+        let mut indices = Vec::new();
+        for face in faces.iter().filter(|f| f.alive) {
+            for i in face.vertices {
+                indices.push(*remap.entry(i).or_insert_with(|| {
+                    vertices.push(points[i]);
+                    vertices.len() as u32 - 1
+                }));
+            }
+        }
+        Ok(Self {
+            vertices,
+            indices,
+            convex: true,
+        })
+    }
+
+    /// 用顶点聚类把三角网格简化到不超过 `max_triangles` 个三角形
+    ///
+    /// 包围盒被划分为等大的格子，同一格子中的顶点合并为它们的平均位置，退化与重复的三角形被丢弃；
+    /// 格子数在满足预算的前提下尽量多。网格本来就满足预算时原样返回。
+    pub fn simplified(
+        positions: &[Vec3],
+        indices: &[u32],
+        max_triangles: usize,
+    ) -> anyhow::Result<Self> {
+        ensure!(
+            indices.len().is_multiple_of(3),
+            "index count {} is not a multiple of 3",
+            indices.len()
+        );
+        ensure!(
+            indices.iter().all(|&i| (i as usize) < positions.len()),
+            "index out of range"
+        );
+        ensure!(
+            positions.iter().all(|p| p.is_finite()),
+            "collision mesh positions must be finite"
+        );
+        if indices.len() / 3 <= max_triangles {
+            return Ok(Self {
+                vertices: positions.to_vec(),
+                indices: indices.to_vec(),
+                convex: false,
+            });
+        }
+
+        // 三角形数量随格子数大致单调增加，二分查找满足预算的最大格子数
+        let (mut low, mut high) = (1, MAX_CLUSTER_RESOLUTION);
+        let mut best = cluster(positions, indices, low);
+        while low < high {
+            let resolution = (low + high).div_ceil(2);
+            let candidate = cluster(positions, indices, resolution);
+            if candidate.indices.len() / 3 <= max_triangles {
+                low = resolution;
+                best = candidate;
+            } else {
+                high = resolution - 1;
+            }
+        }
+        ensure!(
+            !best.indices.is_empty(),
+            "cannot simplify mesh to {max_triangles} triangles, use a convex hull instead"
+        );
+        Ok(best)
+    }
+
+    /// `model` 所有顶点的凸包
+    pub fn model_convex_hull<V: RenderVertex + VertexPosition>(
+        model: &Model<V>,
+    ) -> anyhow::Result<Self> {
+        let points = model.vertices.iter().map(V::position).collect::<Vec<_>>();
+        Self::convex_hull(&points)
+    }
+
+    /// 把 `model` 简化到不超过 `max_triangles` 个三角形，见 [`CollisionMesh::simplified`]
+    pub fn model_simplified<V: RenderVertex + VertexPosition>(
+        model: &Model<V>,
+        max_triangles: usize,
+    ) -> anyhow::Result<Self> {
+        let positions = model.vertices.iter().map(V::position).collect::<Vec<_>>();
+        Self::simplified(&positions, &model.indices, max_triangles)
+    }
+
+    pub fn triangle_count(&self) -> usize {
+        self.indices.len() / 3
+    }
+
+    /// 模型空间中的轴对齐包围盒，`(最小点, 最大点)`
+    pub fn bounds(&self) -> (Vec3, Vec3) {
+        bounding_box(self.vertices.iter().copied())
+    }
+
+    /// 物理引擎常用的顶点数组与三角形数组，例如 rapier 的 `ColliderBuilder::convex_hull`
+    /// 与 `ColliderBuilder::trimesh`
+    pub fn to_arrays(&self) -> (Vec<[f32; 3]>, Vec<[u32; 3]>) {
+        (
+            self.vertices.iter().map(|v| v.to_array()).collect(),
+            self.indices
+                .chunks_exact(3)
+                .map(|t| [t[0], t[1], t[2]])
+                .collect(),
+        )
+    }
+}
+
+/// 凸包构建过程中的一个面，`outside` 为在它外侧、尚未处理的点
+struct HullFace {
+    vertices: [usize; 3],
+    normal: Vec3,
+    offset: f32,
+    outside: Vec<usize>,
+    alive: bool,
+}
+
+impl HullFace {
+    fn new(points: &[Vec3], vertices: [usize; 3]) -> Self {
+        let [a, b, c] = vertices.map(|i| points[i]);
+        let normal = (b - a).cross(c - a).normalize_or_zero();
+        Self {
+            vertices,
+            normal,
+            offset: normal.dot(a),
+            outside: Vec::new(),
+            alive: true,
+        }
+    }
+
+    /// 点到面所在平面的有向距离，外侧为正
+    fn distance(&self, p: Vec3) -> f32 {
+        self.normal.dot(p) - self.offset
+    }
+
+    fn edges(&self) -> [(usize, usize); 3] {
+        let [a, b, c] = self.vertices;
+        [(a, b), (b, c), (c, a)]
+    }
+
+    fn farthest(&self, points: &[Vec3]) -> Option<usize> {
+        self.outside.iter().copied().max_by(|&a, &b| {
+            self.distance(points[a])
+                .total_cmp(&self.distance(points[b]))
+        })
+    }
+}
+
+/// 把每个点交给第一个在外侧距离超过 `epsilon` 的面，不在任何面外侧的点已在凸包内
+fn assign_outside(points: &[Vec3], faces: &mut [HullFace], candidates: Vec<usize>, epsilon: f32) {
+    for i in candidates {
+        if let Some(face) = faces
+            .iter_mut()
+            .find(|f| f.alive && f.distance(points[i]) > epsilon)
+        {
+            face.outside.push(i);
+        }
+    }
+}
+
+/// 由极值点构成、体积不为零的初始四面体
+fn initial_tetrahedron(points: &[Vec3], epsilon: f32) -> Option<[usize; 4]> {
+    let farthest_by = |f: &dyn Fn(Vec3) -> f32| {
+        (0..points.len()).max_by(|&a, &b| f(points[a]).total_cmp(&f(points[b])))
+    };
+    let a = (0..points.len()).min_by(|&a, &b| points[a].x.total_cmp(&points[b].x))?;
+    let b = farthest_by(&|p| p.distance_squared(points[a]))?;
+    let ab = (points[b] - points[a]).normalize_or_zero();
+    let c = farthest_by(&|p| (p - points[a]).cross(ab).length_squared())?;
+    let normal = (points[b] - points[a])
+        .cross(points[c] - points[a])
+        .normalize_or_zero();
+    let d = farthest_by(&|p| normal.dot(p - points[a]).abs())?;
+    (normal != Vec3::ZERO && normal.dot(points[d] - points[a]).abs() > epsilon)
+        .then_some([a, b, c, d])
+}
+
+/// 以每个轴 `resolution` 个格子聚类顶点
+fn cluster(positions: &[Vec3], indices: &[u32], resolution: u32) -> CollisionMesh {
+    let (min, max) = bounding_box(positions.iter().copied());
+    let cell = ((max - min).max_element() / resolution as f32).max(f32::MIN_POSITIVE);
+    let mut cells = HashMap::new();
+    let mut sums: Vec<(Vec3, u32)> = Vec::new();
+    let mut remap = HashMap::new();
+    let mut cluster_of = |i: u32| {
+        *remap.entry(i).or_insert_with(|| {
+            let p = positions[i as usize];
+            let key = ((p - min) / cell)
+                .floor()
+                .as_uvec3()
+                .min(glam::UVec3::splat(resolution - 1));
+            let index = *cells.entry(key.to_array()).or_insert_with(|| {
+                sums.push((Vec3::ZERO, 0));
+                sums.len() as u32 - 1
+            });
+            sums[index as usize].0 += p;
+            sums[index as usize].1 += 1;
+            index
+        })
+    };
+
+    let mut seen = HashSet::new();
+    let mut clustered = Vec::new();
+    for triangle in indices.chunks_exact(3) {
+        let [a, b, c] = [0, 1, 2].map(|i| cluster_of(triangle[i]));
+        if a == b || b == c || c == a {
+            continue;
+        }
+        // 旋转到最小下标在前，保持环绕方向不变
+        let key = if a < b && a < c {
+            [a, b, c]
+        } else if b < c {
+            [b, c, a]
+        } else {
+            [c, a, b]
+        };
+        if seen.insert(key) {
+            clustered.extend(key);
+        }
+    }
+
+    // 只保留仍被三角形引用的格子
+    let mut compact = HashMap::new();
+    let mut vertices = Vec::new();
+    let indices = clustered
+        .into_iter()
+        .map(|i| {
+            *compact.entry(i).or_insert_with(|| {
+                let (sum, count) = sums[i as usize];
+                vertices.push(sum / count as f32);
+                vertices.len() as u32 - 1
+            })
+        })
+        .collect();
+    CollisionMesh {
+        vertices,
+        indices,
+        convex: false,
+    }
+}
@@ -0,0 +1,212 @@
+use std::future::Future;
+
+use anyhow::{anyhow, Context};
+use wgpu::util::DeviceExt;
+
+use crate::context::{check_adapter, find_adapter};
+
+/// 创建 [`ComputeContext`] 时的选项，含义与 [`GpuContextOptions`](crate::context::GpuContextOptions) 中的同名字段相同
+#[derive(Debug, Clone)]
+pub struct ComputeContextOptions {
+    pub backends: wgpu::Backends,
+    pub power_preference: wgpu::PowerPreference,
+    pub adapter_name_filter: Option<String>,
+    pub force_fallback: bool,
+    pub required_features: wgpu::Features,
+    pub optional_features: wgpu::Features,
+    pub required_limits: wgpu::Limits,
+}
+
+impl Default for ComputeContextOptions {
+    fn default() -> Self {
+        Self {
+            backends: wgpu::Backends::all(),
+            power_preference: wgpu::PowerPreference::default(),
+            adapter_name_filter: std::env::var("WGPU_DANCE_ADAPTER").ok(),
+            force_fallback: std::env::var_os("WGPU_DANCE_FALLBACK_ADAPTER").is_some(),
+            required_features: wgpu::Features::empty(),
+            optional_features: wgpu::Features::empty(),
+            required_limits: wgpu::Limits::default(),
+        }
+    }
+}
+
+impl ComputeContextOptions {
+    /// 默认选项，功能与限制取自 [`ComputeApp::required_features`] 与 [`ComputeApp::required_limits`]
+    pub fn for_app<A: ComputeApp>() -> Self {
+        Self {
+            required_features: A::required_features(),
+            required_limits: A::required_limits(),
+            ..Default::default()
+        }
+    }
+}
+
+/// 不需要窗口与 surface 的 device 与 queue
+pub struct ComputeContext {
+    pub adapter: wgpu::Adapter,
+    pub device: wgpu::Device,
+    pub queue: wgpu::Queue,
+}
+
+impl ComputeContext {
+    pub async fn new(options: ComputeContextOptions) -> anyhow::Result<Self> {
+        let instance = wgpu::Instance::new(&wgpu::InstanceDescriptor {
+            backends: options.backends,
+            ..Default::default()
+        });
+        let adapter = match &options.adapter_name_filter {
+            Some(filter) => find_adapter(
+                &instance,
+                options.backends,
+                options.force_fallback,
+                None,
+                filter,
+            )?,
+            None => instance
+                .request_adapter(&wgpu::RequestAdapterOptions {
+                    power_preference: options.power_preference,
+                    compatible_surface: None,
+                    force_fallback_adapter: options.force_fallback,
+                })
+                .await
+                .ok_or_else(|| anyhow!("no adapter available"))?,
+        };
+        let info = adapter.get_info();
+        println!("using adapter {} ({:?})", info.name, info.backend);
+        check_adapter(
+            &adapter,
+            options.required_features,
+            &options.required_limits,
+        )?;
+
+        let (device, queue) = adapter
+            .request_device(
+                &wgpu::DeviceDescriptor {
+                    required_features: options.required_features
+                        | (options.optional_features & adapter.features()),
+                    required_limits: options.required_limits,
+                    label: None,
+                    memory_hints: wgpu::MemoryHints::Performance,
+                },
+                None,
+            )
+            .await
+            .context("failed to request device")?;
+
+        Ok(Self {
+            adapter,
+            device,
+            queue,
+        })
+    }
+
+    /// 以 `data` 为初始内容的存储缓冲，可以作为复制的源与目标，便于读回与重置
+    pub fn create_storage_buffer<T: bytemuck::Pod>(&self, label: &str, data: &[T]) -> wgpu::Buffer {
+        self.device
+            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some(label),
+                contents: bytemuck::cast_slice(data),
+                usage: wgpu::BufferUsages::STORAGE
+                    | wgpu::BufferUsages::COPY_SRC
+                    | wgpu::BufferUsages::COPY_DST,
+            })
+    }
+
+    /// 把带 `COPY_SRC` 用途的缓冲整个读回 CPU，等待之前提交的所有工作完成
+    pub async fn read_buffer<T: bytemuck::Pod>(
+        &self,
+        buffer: &wgpu::Buffer,
+    ) -> anyhow::Result<Vec<T>> {
+        let staging = self.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Compute Readback Buffer"),
+            size: buffer.size(),
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+        let mut encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("Compute Readback Encoder"),
+            });
+        encoder.copy_buffer_to_buffer(buffer, 0, &staging, 0, buffer.size());
+        self.queue.submit(Some(encoder.finish()));
+
+        let (sender, receiver) = futures::channel::oneshot::channel();
+        let slice = staging.slice(..);
+        slice.map_async(wgpu::MapMode::Read, move |result| {
+            let _ = sender.send(result);
+        });
+        // 浏览器中由事件循环推进映射，原生平台需要主动等待
+        #[cfg(not(target_arch = "wasm32"))]
+        self.device.poll(wgpu::Maintain::Wait);
+        receiver.await??;
+
+        let data = bytemuck::cast_slice(&slice.get_mapped_range()).to_vec();
+        staging.unmap();
+        Ok(data)
+    }
+}
+
+/// [`ComputeApp::step`] 的返回值
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ComputeStep {
+    Continue,
+    Done,
+}
+
+/// 不需要窗口的 GPU 计算程序，例如图像滤波与模拟，用 [`run_compute`] 运行
+///
+/// 运行顺序：`new` 创建管线与缓冲；反复调用 `step` 记录计算命令，每一步单独提交，
+/// 直到返回 [`ComputeStep::Done`]；最后由 `finish` 读回结果。
+pub trait ComputeApp: Sized {
+    type Output;
+
+    fn required_features() -> wgpu::Features {
+        wgpu::Features::empty()
+    }
+
+    fn required_limits() -> wgpu::Limits {
+        wgpu::Limits::default()
+    }
+
+    fn new(gpu: &ComputeContext) -> impl Future<Output = anyhow::Result<Self>>;
+
+    /// 记录第 `step` 步（从 0 开始）的计算命令
+    fn step(
+        &mut self,
+        gpu: &ComputeContext,
+        encoder: &mut wgpu::CommandEncoder,
+        step: u32,
+    ) -> ComputeStep;
+
+    /// 所有步骤提交之后调用，通常用 [`ComputeContext::read_buffer`] 读回结果
+    fn finish(self, gpu: &ComputeContext) -> impl Future<Output = anyhow::Result<Self::Output>>;
+}
+
+/// 创建设备并运行 `A` 的所有步骤
+pub async fn run_compute<A: ComputeApp>(
+    options: ComputeContextOptions,
+) -> anyhow::Result<A::Output> {
+    let gpu = ComputeContext::new(options).await?;
+    let mut app = A::new(&gpu).await?;
+    for step in 0.. {
+        let mut encoder = gpu
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("Compute Encoder"),
+            });
+        let flow = app.step(&gpu, &mut encoder, step);
+        gpu.queue.submit(Some(encoder.finish()));
+        if flow == ComputeStep::Done {
+            break;
+        }
+    }
+    app.finish(&gpu).await
+}
+
+/// 以默认选项运行 `A` 并阻塞到结果读回
+#[cfg(not(target_arch = "wasm32"))]
+pub fn block_on_compute<A: ComputeApp>() -> anyhow::Result<A::Output> {
+    futures::executor::block_on(run_compute::<A>(ComputeContextOptions::for_app::<A>()))
+}
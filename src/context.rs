@@ -0,0 +1,780 @@
+use std::{path::PathBuf, sync::Arc};
+
+use anyhow::{anyhow, ensure, Context};
+use winit::{
+    dpi::{LogicalSize, PhysicalSize},
+    window::Window,
+};
+
+use crate::{
+    app::WindowApp,
+    background::{Background, GradientBackground},
+    capture::{save_screenshot, RecordOutput, Recorder},
+    texture::DepthConvention,
+    window::WindowControl,
+};
+
+/// 呈现模式的选择，除 [`PresentModeConfig::Exact`] 外都会在 surface 不支持时回退
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum PresentModeConfig {
+    /// 垂直同步（Fifo），所有平台都支持
+    #[default]
+    VSync,
+    /// 不撕裂且延迟较低：优先 Mailbox，其次 Fifo
+    LowLatency,
+    /// 不等待垂直同步，用于性能测试：优先 Immediate，其次 Mailbox、Fifo
+    Uncapped,
+    /// 指定的模式，不支持时报错
+    Exact(wgpu::PresentMode),
+}
+
+impl PresentModeConfig {
+    /// 在 `supported` 中选出实际使用的模式
+    pub fn resolve(self, supported: &[wgpu::PresentMode]) -> Option<wgpu::PresentMode> {
+        use wgpu::PresentMode as P;
+        let candidates: &[P] = match self {
+            Self::VSync => &[P::Fifo],
+            Self::LowLatency => &[P::Mailbox, P::Fifo],
+            Self::Uncapped => &[P::Immediate, P::Mailbox, P::Fifo],
+            Self::Exact(mode) => return supported.contains(&mode).then_some(mode),
+        };
+        candidates.iter().copied().find(|m| supported.contains(m))
+    }
+}
+
+/// 创建 [`GpuContext`] 时的选项
+#[derive(Debug, Clone)]
+pub struct GpuContextOptions {
+    pub backends: wgpu::Backends,
+    pub power_preference: wgpu::PowerPreference,
+    /// 只使用名称包含该字符串（不区分大小写）的适配器，优先于 `power_preference`；
+    /// 默认取环境变量 `WGPU_DANCE_ADAPTER`，浏览器中不支持
+    pub adapter_name_filter: Option<String>,
+    /// 使用软件光栅化等后备适配器；设置了环境变量 `WGPU_DANCE_FALLBACK_ADAPTER` 时默认开启
+    pub force_fallback: bool,
+    pub required_features: wgpu::Features,
+    /// 适配器支持时才启用的功能，例如 [`wgpu::Features::PIPELINE_STATISTICS_QUERY`]
+    pub optional_features: wgpu::Features,
+    pub required_limits: wgpu::Limits,
+    pub present_mode: PresentModeConfig,
+    /// 为 `None` 时使用 surface 支持的第一个格式
+    pub surface_format: Option<wgpu::TextureFormat>,
+    /// surface 支持时总会额外加上 `COPY_SRC`，以便截图
+    pub surface_usage: wgpu::TextureUsages,
+    /// 窗口透明时选用能与桌面合成的 alpha 模式，见 [`transparent_alpha_mode`]；
+    /// 窗口本身还需要以透明属性创建
+    pub transparent: bool,
+}
+
+impl Default for GpuContextOptions {
+    fn default() -> Self {
+        Self {
+            backends: wgpu::Backends::all(),
+            power_preference: wgpu::PowerPreference::default(),
+            adapter_name_filter: std::env::var("WGPU_DANCE_ADAPTER").ok(),
+            force_fallback: std::env::var_os("WGPU_DANCE_FALLBACK_ADAPTER").is_some(),
+            required_features: wgpu::Features::empty(),
+            optional_features: wgpu::Features::empty(),
+            required_limits: wgpu::Limits::default(),
+            present_mode: PresentModeConfig::VSync,
+            surface_format: None,
+            surface_usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+            transparent: false,
+        }
+    }
+}
+
+impl GpuContextOptions {
+    /// 默认选项，功能与限制取自 [`WindowApp::required_features`] 与 [`WindowApp::required_limits`]，
+    /// 透明取自 [`WindowApp::transparent`]
+    pub fn for_app<A: WindowApp>() -> Self {
+        Self {
+            required_features: A::required_features(),
+            required_limits: A::required_limits(),
+            transparent: A::transparent(),
+            ..Default::default()
+        }
+    }
+}
+
+/// 透明窗口使用的 alpha 模式：优先预乘，其次非预乘，最后交给窗口系统决定（`Inherit`）；
+/// 都不支持时返回 `None`，窗口只能不透明
+pub fn transparent_alpha_mode(
+    supported: &[wgpu::CompositeAlphaMode],
+) -> Option<wgpu::CompositeAlphaMode> {
+    use wgpu::CompositeAlphaMode as M;
+    [M::PreMultiplied, M::PostMultiplied, M::Inherit]
+        .into_iter()
+        .find(|m| supported.contains(m))
+}
+
+/// 列出 `backends` 下的所有适配器，并打印名称、类型、后端与驱动
+#[cfg(not(target_arch = "wasm32"))]
+pub fn enumerate_adapters(backends: wgpu::Backends) -> Vec<wgpu::AdapterInfo> {
+    let instance = wgpu::Instance::new(&wgpu::InstanceDescriptor {
+        backends,
+        ..Default::default()
+    });
+    instance
+        .enumerate_adapters(backends)
+        .iter()
+        .map(|adapter| {
+            let info = adapter.get_info();
+            println!(
+                "{} ({:?}, {:?}), driver: {} {}",
+                info.name, info.device_type, info.backend, info.driver, info.driver_info
+            );
+            info
+        })
+        .collect()
+}
+
+/// 在支持 surface（为 `None` 时不限）的适配器中找出名称包含 `filter` 的一个，
+/// 开启 `force_fallback` 时只考虑 CPU 适配器
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn find_adapter(
+    instance: &wgpu::Instance,
+    backends: wgpu::Backends,
+    force_fallback: bool,
+    surface: Option<&wgpu::Surface>,
+    filter: &str,
+) -> anyhow::Result<wgpu::Adapter> {
+    let filter = filter.to_lowercase();
+    let adapters = instance.enumerate_adapters(backends);
+    let names = adapters
+        .iter()
+        .map(|adapter| adapter.get_info().name)
+        .collect::<Vec<_>>();
+    adapters
+        .into_iter()
+        .find(|adapter| {
+            let info = adapter.get_info();
+            info.name.to_lowercase().contains(&filter)
+                && (!force_fallback || info.device_type == wgpu::DeviceType::Cpu)
+                && surface.is_none_or(|surface| adapter.is_surface_supported(surface))
+        })
+        .ok_or_else(|| anyhow!("no adapter matching `{filter}`, available: {names:?}"))
+}
+
+#[cfg(target_arch = "wasm32")]
+pub(crate) fn find_adapter(
+    _instance: &wgpu::Instance,
+    _backends: wgpu::Backends,
+    _force_fallback: bool,
+    _surface: Option<&wgpu::Surface>,
+    _filter: &str,
+) -> anyhow::Result<wgpu::Adapter> {
+    anyhow::bail!("adapter_name_filter is not supported in the browser")
+}
+
+/// 确认适配器支持所需的功能与限制，不满足时列出缺少的项
+pub(crate) fn check_adapter(
+    adapter: &wgpu::Adapter,
+    features: wgpu::Features,
+    limits: &wgpu::Limits,
+) -> anyhow::Result<()> {
+    let name = adapter.get_info().name;
+    let missing = features - adapter.features();
+    ensure!(
+        missing.is_empty(),
+        "adapter {name} does not support required features {missing:?}"
+    );
+    let mut exceeded = Vec::new();
+    limits.check_limits_with_fail_fn(&adapter.limits(), false, |name, required, allowed| {
+        exceeded.push(format!("{name} {required} > {allowed}"))
+    });
+    ensure!(
+        exceeded.is_empty(),
+        "adapter {name} does not meet required limits: {}",
+        exceeded.join(", ")
+    );
+    Ok(())
+}
+
+/// sRGB 的 surface 额外允许以去掉 sRGB 后缀的格式创建视图，供 UI 叠加层在 sRGB 空间混合
+fn gamma_view_formats(
+    adapter: &wgpu::Adapter,
+    format: wgpu::TextureFormat,
+) -> Vec<wgpu::TextureFormat> {
+    let supported = adapter
+        .get_downlevel_capabilities()
+        .flags
+        .contains(wgpu::DownlevelFlags::SURFACE_VIEW_FORMATS);
+    if supported && format.is_srgb() {
+        vec![format.remove_srgb_suffix()]
+    } else {
+        vec![]
+    }
+}
+
+/// 当前帧的 surface 纹理及其默认视图，绘制完成后调用 [`GpuContext::present`]
+pub struct Frame {
+    pub output: wgpu::SurfaceTexture,
+    pub view: wgpu::TextureView,
+    /// 以非 sRGB 格式查看同一张纹理，写入的数值不再编码，混合也在 sRGB 空间进行；
+    /// 平台不支持 surface 视图格式时与 `view` 相同，格式见 [`GpuContext::gamma_format`]
+    pub gamma_view: wgpu::TextureView,
+}
+
+impl Frame {
+    pub fn present(self) {
+        self.output.present();
+    }
+}
+
+/// 一帧的 surface 纹理与命令编码器，由 [`GpuContext::begin_frame`] 创建，
+/// 绘制完成后交给 [`GpuContext::end_frame`] 提交并呈现
+pub struct FrameContext {
+    pub frame: Frame,
+    pub encoder: wgpu::CommandEncoder,
+    /// 取自 [`GpuContext::background`]，渐变与天空已经在 [`GpuContext::begin_frame`] 中绘制
+    pub background: Background,
+}
+
+impl FrameContext {
+    /// 配置一个渲染通道，默认绘制到 surface、保留或清为 `background` 且没有深度附件
+    pub fn render_pass<'a>(&'a mut self, label: &'a str) -> RenderPassBuilder<'a> {
+        RenderPassBuilder {
+            encoder: &mut self.encoder,
+            label,
+            frame: &self.frame,
+            color: &self.frame.view,
+            resolve_target: None,
+            background: &self.background,
+            surface: true,
+            load: None,
+            depth: None,
+        }
+    }
+}
+
+/// 由 [`FrameContext::render_pass`] 创建，[`RenderPassBuilder::begin`] 返回可以直接设置管线的渲染通道
+pub struct RenderPassBuilder<'a> {
+    encoder: &'a mut wgpu::CommandEncoder,
+    label: &'a str,
+    frame: &'a Frame,
+    color: &'a wgpu::TextureView,
+    resolve_target: Option<&'a wgpu::TextureView>,
+    background: &'a Background,
+    /// 颜色目标是 surface，此时背景已经绘制或以背景颜色清屏
+    surface: bool,
+    /// 为 `None` 时按背景决定
+    load: Option<wgpu::LoadOp<wgpu::Color>>,
+    depth: Option<(&'a wgpu::TextureView, Option<wgpu::Operations<f32>>)>,
+}
+
+impl<'a> RenderPassBuilder<'a> {
+    /// 绘制到 surface 以外的颜色目标，例如 HDR 场景纹理或多重采样纹理；
+    /// 默认以 [`Background::clear_color`] 清屏
+    pub fn color(mut self, view: &'a wgpu::TextureView) -> Self {
+        self.color = view;
+        self.surface = false;
+        self
+    }
+
+    /// 绘制到 [`Frame::gamma_view`]，着色器输出的颜色不再经过 sRGB 编码
+    pub fn gamma(mut self) -> Self {
+        self.color = &self.frame.gamma_view;
+        self.surface = true;
+        self
+    }
+
+    /// 多重采样的颜色目标解析到的纹理，通常是 [`Frame::view`]
+    pub fn resolve_target(mut self, view: &'a wgpu::TextureView) -> Self {
+        self.resolve_target = Some(view);
+        self
+    }
+
+    pub fn clear_color(mut self, color: wgpu::Color) -> Self {
+        self.load = Some(wgpu::LoadOp::Clear(color));
+        self
+    }
+
+    /// 保留颜色目标中已有的内容
+    pub fn load(mut self) -> Self {
+        self.load = Some(wgpu::LoadOp::Load);
+        self
+    }
+
+    /// 清为 1.0 并写回的深度附件
+    pub fn depth(self, view: &'a wgpu::TextureView) -> Self {
+        self.depth_with(view, DepthConvention::Standard)
+    }
+
+    /// 按 `convention` 清为远平面深度并写回的深度附件
+    pub fn depth_with(mut self, view: &'a wgpu::TextureView, convention: DepthConvention) -> Self {
+        self.depth = Some((view, Some(convention.clear_ops())));
+        self
+    }
+
+    /// 保留已有内容并写回的深度附件，例如不透明通道之后继续绘制
+    pub fn load_depth(mut self, view: &'a wgpu::TextureView) -> Self {
+        self.depth = Some((
+            view,
+            Some(wgpu::Operations {
+                load: wgpu::LoadOp::Load,
+                store: wgpu::StoreOp::Store,
+            }),
+        ));
+        self
+    }
+
+    /// 只读的深度附件，绘制时可以同时在着色器中采样这张深度，例如软粒子
+    pub fn read_only_depth(mut self, view: &'a wgpu::TextureView) -> Self {
+        self.depth = Some((view, None));
+        self
+    }
+
+    pub fn begin(self) -> wgpu::RenderPass<'a> {
+        let load = self.load.unwrap_or_else(|| {
+            if self.surface {
+                self.background.load_op()
+            } else {
+                wgpu::LoadOp::Clear(self.background.clear_color())
+            }
+        });
+        self.encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some(self.label),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: self.color,
+                resolve_target: self.resolve_target,
+                ops: wgpu::Operations {
+                    load,
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: self.depth.map(|(view, depth_ops)| {
+                wgpu::RenderPassDepthStencilAttachment {
+                    view,
+                    depth_ops,
+                    stencil_ops: None,
+                }
+            }),
+            ..Default::default()
+        })
+    }
+}
+
+/// 窗口对应的 device、queue 与 surface
+///
+/// 直接修改 `surface_config` 后需要调用 [`GpuContext::reconfigure`]。
+pub struct GpuContext {
+    pub adapter: wgpu::Adapter,
+    pub device: wgpu::Device,
+    pub queue: wgpu::Queue,
+    /// 应用挂起期间（见 [`GpuContext::suspend`]）为 `None`
+    pub surface: Option<wgpu::Surface<'static>>,
+    pub surface_config: wgpu::SurfaceConfiguration,
+    /// [`GpuContext::begin_frame`] 在场景之前绘制的背景
+    pub background: Background,
+    /// [`Background::Gradient`] 的管线，第一次用到时创建
+    gradient: Option<GradientBackground>,
+    pending_size: Option<PhysicalSize<u32>>,
+    /// 窗口的 DPI 缩放，逻辑像素乘以它得到物理像素
+    scale_factor: f64,
+    /// surface 支持的呈现模式
+    present_modes: Vec<wgpu::PresentMode>,
+    /// 恢复时用来重新创建 surface
+    instance: wgpu::Instance,
+    window: WindowControl,
+    /// 下一次 present 前保存截图的路径
+    screenshot: Option<PathBuf>,
+    recorder: Option<Recorder>,
+}
+
+impl GpuContext {
+    pub async fn new(window: Arc<Window>, options: GpuContextOptions) -> anyhow::Result<Self> {
+        let instance = wgpu::Instance::new(&wgpu::InstanceDescriptor {
+            backends: options.backends,
+            ..Default::default()
+        });
+        let size = window.inner_size();
+        let scale_factor = window.scale_factor();
+        let control = WindowControl::new(window.clone());
+        let surface = instance.create_surface(window)?;
+
+        let adapter = match &options.adapter_name_filter {
+            Some(filter) => find_adapter(
+                &instance,
+                options.backends,
+                options.force_fallback,
+                Some(&surface),
+                filter,
+            )?,
+            None => instance
+                .request_adapter(&wgpu::RequestAdapterOptions {
+                    power_preference: options.power_preference,
+                    compatible_surface: Some(&surface),
+                    force_fallback_adapter: options.force_fallback,
+                })
+                .await
+                .ok_or_else(|| anyhow!("no adapter compatible with the window surface"))?,
+        };
+        let info = adapter.get_info();
+        println!("using adapter {} ({:?})", info.name, info.backend);
+
+        check_adapter(
+            &adapter,
+            options.required_features,
+            &options.required_limits,
+        )?;
+
+        let (device, queue) = adapter
+            .request_device(
+                &wgpu::DeviceDescriptor {
+                    required_features: options.required_features
+                        | (options.optional_features & adapter.features()),
+                    required_limits: options.required_limits,
+                    label: None,
+                    memory_hints: wgpu::MemoryHints::Performance,
+                },
+                None,
+            )
+            .await
+            .context("failed to request device")?;
+
+        let caps = surface.get_capabilities(&adapter);
+        let format = match options.surface_format {
+            Some(format) => {
+                ensure!(
+                    caps.formats.contains(&format),
+                    "surface does not support {format:?}, supported: {:?}",
+                    caps.formats
+                );
+                format
+            }
+            None => caps.formats[0],
+        };
+        let present_mode = options
+            .present_mode
+            .resolve(&caps.present_modes)
+            .ok_or_else(|| {
+                anyhow!(
+                    "surface does not support {:?}, supported: {:?}",
+                    options.present_mode,
+                    caps.present_modes
+                )
+            })?;
+        let alpha_mode = if options.transparent {
+            transparent_alpha_mode(&caps.alpha_modes).unwrap_or_else(|| {
+                eprintln!(
+                    "surface supports no transparent alpha mode, supported: {:?}",
+                    caps.alpha_modes
+                );
+                caps.alpha_modes[0]
+            })
+        } else {
+            caps.alpha_modes[0]
+        };
+        let surface_config = wgpu::SurfaceConfiguration {
+            usage: options.surface_usage | (wgpu::TextureUsages::COPY_SRC & caps.usages),
+            format,
+            width: size.width.max(1),
+            height: size.height.max(1),
+            present_mode,
+            alpha_mode,
+            view_formats: gamma_view_formats(&adapter, format),
+            desired_maximum_frame_latency: 2,
+        };
+        surface.configure(&device, &surface_config);
+
+        Ok(Self {
+            adapter,
+            device,
+            queue,
+            surface: Some(surface),
+            surface_config,
+            background: Background::default(),
+            gradient: None,
+            pending_size: None,
+            scale_factor,
+            present_modes: caps.present_modes,
+            instance,
+            window: control,
+            screenshot: None,
+            recorder: None,
+        })
+    }
+
+    /// 全屏、光标捕获等窗口控制
+    pub fn window(&self) -> &WindowControl {
+        &self.window
+    }
+
+    pub fn size(&self) -> PhysicalSize<u32> {
+        PhysicalSize::new(self.surface_config.width, self.surface_config.height)
+    }
+
+    /// surface 大小换算为逻辑像素，适合布局 UI 与文字
+    pub fn logical_size(&self) -> LogicalSize<f64> {
+        self.size().to_logical(self.scale_factor)
+    }
+
+    pub fn scale_factor(&self) -> f64 {
+        self.scale_factor
+    }
+
+    /// 由 [`crate::app::WindowAppHandler`] 在收到 `ScaleFactorChanged` 时调用，
+    /// 新的物理尺寸随后通过 `Resized` 到达
+    pub fn set_scale_factor(&mut self, scale_factor: f64) {
+        self.scale_factor = scale_factor;
+    }
+
+    pub fn format(&self) -> wgpu::TextureFormat {
+        self.surface_config.format
+    }
+
+    /// surface 与桌面合成的方式；为 `PreMultiplied` 时写入 surface 的颜色须已乘以 alpha，
+    /// 可交给 [`Tonemapping::alpha_mode`](crate::post::tonemap::Tonemapping::alpha_mode) 处理
+    pub fn alpha_mode(&self) -> wgpu::CompositeAlphaMode {
+        self.surface_config.alpha_mode
+    }
+
+    /// [`Frame::gamma_view`] 的格式
+    pub fn gamma_format(&self) -> wgpu::TextureFormat {
+        let gamma = self.surface_config.format.remove_srgb_suffix();
+        if self.surface_config.view_formats.contains(&gamma) {
+            gamma
+        } else {
+            self.surface_config.format
+        }
+    }
+
+    pub fn aspect(&self) -> f32 {
+        self.surface_config.width as f32 / self.surface_config.height as f32
+    }
+
+    /// 记录新的窗口大小，到 [`GpuContext::resize_if_needed`] 时才重新配置 surface；
+    /// 最小化时的零尺寸会被忽略
+    pub fn resize(&mut self, new_size: PhysicalSize<u32>) {
+        if new_size.width == 0 || new_size.height == 0 {
+            return;
+        }
+        self.pending_size = (new_size != self.size()).then_some(new_size);
+    }
+
+    /// 应用等待中的尺寸变化，返回 `true` 时调用方需要重建与 surface 同尺寸的纹理
+    pub fn resize_if_needed(&mut self) -> bool {
+        let Some(size) = self.pending_size.take() else {
+            return false;
+        };
+        self.surface_config.width = size.width;
+        self.surface_config.height = size.height;
+        self.reconfigure();
+        true
+    }
+
+    /// 挂起期间不做任何事
+    pub fn reconfigure(&mut self) {
+        if let Some(surface) = &self.surface {
+            surface.configure(&self.device, &self.surface_config);
+        }
+    }
+
+    /// 丢弃 surface，由 [`WindowAppHandler`](crate::app::WindowAppHandler) 在应用挂起时调用
+    ///
+    /// Android 在应用切到后台时销毁原生窗口，必须在 `suspended` 事件返回前丢弃 surface；
+    /// device、queue 与其他 GPU 资源保持不变。
+    pub fn suspend(&mut self) {
+        self.surface = None;
+    }
+
+    pub fn is_suspended(&self) -> bool {
+        self.surface.is_none()
+    }
+
+    /// 以原有的配置为同一个窗口重新创建 surface，返回 `false` 表示没有挂起、无需重建
+    pub fn resume(&mut self) -> anyhow::Result<bool> {
+        if self.surface.is_some() {
+            return Ok(false);
+        }
+        let surface = self.instance.create_surface(self.window.shared())?;
+        ensure!(
+            self.adapter.is_surface_supported(&surface),
+            "the adapter cannot present to the recreated surface"
+        );
+        let caps = surface.get_capabilities(&self.adapter);
+        ensure!(
+            caps.formats.contains(&self.surface_config.format),
+            "recreated surface does not support {:?}, supported: {:?}",
+            self.surface_config.format,
+            caps.formats
+        );
+        surface.configure(&self.device, &self.surface_config);
+        self.present_modes = caps.present_modes;
+        self.surface = Some(surface);
+        Ok(true)
+    }
+
+    pub fn present_mode(&self) -> wgpu::PresentMode {
+        self.surface_config.present_mode
+    }
+
+    pub fn supported_present_modes(&self) -> &[wgpu::PresentMode] {
+        &self.present_modes
+    }
+
+    /// 切换呈现模式并立即重新配置 surface，surface 不支持时返回错误且不做修改
+    pub fn set_present_mode(&mut self, mode: wgpu::PresentMode) -> anyhow::Result<()> {
+        self.set_present_mode_config(PresentModeConfig::Exact(mode))
+            .map(|_| ())
+    }
+
+    /// 与 [`GpuContext::set_present_mode`] 相同，但允许回退，返回实际使用的模式
+    pub fn set_present_mode_config(
+        &mut self,
+        config: PresentModeConfig,
+    ) -> anyhow::Result<wgpu::PresentMode> {
+        let mode = config.resolve(&self.present_modes).ok_or_else(|| {
+            anyhow!(
+                "surface does not support {config:?}, supported: {:?}",
+                self.present_modes
+            )
+        })?;
+        if mode != self.surface_config.present_mode {
+            self.surface_config.present_mode = mode;
+            self.reconfigure();
+        }
+        Ok(mode)
+    }
+
+    /// 在支持的 Fifo、Mailbox、Immediate 之间轮换，返回新的模式，适合绑定到按键上
+    pub fn cycle_present_mode(&mut self) -> wgpu::PresentMode {
+        use wgpu::PresentMode as P;
+        let order = [P::Fifo, P::Mailbox, P::Immediate];
+        let current = order
+            .iter()
+            .position(|&m| m == self.present_mode())
+            .unwrap_or(0);
+        let next = (1..=order.len())
+            .map(|i| order[(current + i) % order.len()])
+            .find(|m| self.present_modes.contains(m))
+            .unwrap_or(P::Fifo);
+        if next != self.surface_config.present_mode {
+            self.surface_config.present_mode = next;
+            self.reconfigure();
+        }
+        next
+    }
+
+    /// 获取当前帧的 surface 纹理；surface 丢失或过期时重新配置后再尝试一次，挂起期间返回
+    /// [`wgpu::SurfaceError::Lost`]
+    pub fn current_frame(&mut self) -> Result<Frame, wgpu::SurfaceError> {
+        let surface = self.surface.as_ref().ok_or(wgpu::SurfaceError::Lost)?;
+        let output = match surface.get_current_texture() {
+            Ok(output) => output,
+            Err(wgpu::SurfaceError::Lost | wgpu::SurfaceError::Outdated) => {
+                surface.configure(&self.device, &self.surface_config);
+                surface.get_current_texture()?
+            }
+            Err(e) => return Err(e),
+        };
+        let view = output
+            .texture
+            .create_view(&wgpu::TextureViewDescriptor::default());
+        let gamma_view = output.texture.create_view(&wgpu::TextureViewDescriptor {
+            format: Some(self.gamma_format()),
+            ..Default::default()
+        });
+        Ok(Frame {
+            output,
+            view,
+            gamma_view,
+        })
+    }
+
+    /// 获取当前帧并创建本帧的命令编码器，渐变与天空背景在这里先绘制到 surface 上；
+    /// 错误与 [`GpuContext::current_frame`] 相同
+    pub fn begin_frame(&mut self) -> Result<FrameContext, wgpu::SurfaceError> {
+        let frame = self.current_frame()?;
+        let mut encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("Frame Encoder"),
+            });
+        match &self.background {
+            Background::Gradient { top, bottom } => self
+                .gradient
+                .get_or_insert_with(|| {
+                    GradientBackground::new(&self.device, self.surface_config.format)
+                })
+                .draw(&self.queue, &mut encoder, &frame.view, *top, *bottom),
+            Background::Skybox(sky) => sky.draw(&mut encoder, &frame.view),
+            Background::Color(_) | Background::Transparent => {}
+        }
+        Ok(FrameContext {
+            frame,
+            encoder,
+            background: self.background.clone(),
+        })
+    }
+
+    /// 提交本帧的命令并呈现，截图与录制同 [`GpuContext::present`]
+    pub fn end_frame(&mut self, frame: FrameContext) {
+        self.queue.submit(Some(frame.encoder.finish()));
+        self.present(frame.frame);
+    }
+
+    /// 在下一次 [`GpuContext::present`] 前把画面保存为 PNG
+    pub fn request_screenshot(&mut self, path: impl Into<PathBuf>) {
+        self.screenshot = Some(path.into());
+    }
+
+    /// 开始把之后每次 [`GpuContext::present`] 的画面录制到 `output`，窗口尺寸变化时录制会停止
+    pub fn start_recording(&mut self, output: &RecordOutput) -> anyhow::Result<()> {
+        ensure!(self.recorder.is_none(), "already recording");
+        ensure!(
+            self.surface_config
+                .usage
+                .contains(wgpu::TextureUsages::COPY_SRC),
+            "surface does not support COPY_SRC"
+        );
+        self.recorder = Some(Recorder::new(
+            &self.device,
+            &self.queue,
+            self.surface_config.width,
+            self.surface_config.height,
+            self.surface_config.format,
+            output,
+        )?);
+        Ok(())
+    }
+
+    /// 结束录制并写完剩余的帧，返回写入的帧数
+    pub fn stop_recording(&mut self) -> anyhow::Result<u64> {
+        self.recorder
+            .take()
+            .ok_or_else(|| anyhow!("not recording"))?
+            .finish()
+    }
+
+    pub fn is_recording(&self) -> bool {
+        self.recorder.is_some()
+    }
+
+    /// 呈现当前帧，有等待中的截图请求时先保存截图，录制中时录下这一帧；本帧的绘制命令需已提交
+    pub fn present(&mut self, frame: Frame) {
+        let copy_src = self
+            .surface_config
+            .usage
+            .contains(wgpu::TextureUsages::COPY_SRC);
+        if let Some(path) = self.screenshot.take() {
+            let result = if copy_src {
+                save_screenshot(&self.device, &self.queue, &frame.output.texture, &path)
+            } else {
+                Err(anyhow!("surface does not support COPY_SRC"))
+            };
+            match result {
+                Ok(()) => println!("saved screenshot to {}", path.display()),
+                Err(e) => eprintln!("screenshot failed: {e:#}"),
+            }
+        }
+        if let Some(recorder) = &mut self.recorder {
+            if let Err(e) = recorder.capture(&frame.output.texture) {
+                eprintln!("recording stopped: {e:#}");
+                self.recorder = None;
+            }
+        }
+        frame.present();
+    }
+}
@@ -0,0 +1,202 @@
+use std::f32::consts::TAU;
+
+use wgpu::{BindGroup, BindGroupLayout, Device, Queue};
+use winit::dpi::PhysicalSize;
+
+use crate::{
+    camera::Camera,
+    polyline::{LineJoin, LineStyle, LineWidth, PolylineRenderer},
+    ray::Ray,
+};
+
+/// 立即模式的调试线框绘制：每帧 `clear` 后重新添加图形，再 `update` 并 `draw`
+///
+/// 线条由 [`PolylineRenderer`] 绘制，宽度以像素为单位。
+pub struct DebugDraw {
+    /// 线宽，单位像素
+    pub width: f32,
+    /// 圆与球等曲线图形的分段数
+    pub segments: usize,
+    lines: PolylineRenderer,
+}
+
+impl DebugDraw {
+    /// `depth_format` 为 `None` 时线框不做深度测试，总是绘制在最前面
+    pub fn new(
+        device: &Device,
+        color_format: wgpu::TextureFormat,
+        depth_format: Option<wgpu::TextureFormat>,
+        camera_layout: &BindGroupLayout,
+    ) -> Self {
+        Self {
+            width: 1.5,
+            segments: 32,
+            lines: PolylineRenderer::new(device, color_format, depth_format, camera_layout),
+        }
+    }
+
+    fn style(&self, color: glam::Vec4) -> LineStyle {
+        LineStyle {
+            color,
+            width: LineWidth::Pixels(self.width),
+            join: LineJoin::Miter,
+            ..Default::default()
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.lines.clear();
+    }
+
+    pub fn line(&mut self, a: glam::Vec3, b: glam::Vec3, color: glam::Vec4) {
+        self.lines.add_strip(&[a, b], &self.style(color));
+    }
+
+    pub fn strip(&mut self, points: &[glam::Vec3], color: glam::Vec4) {
+        self.lines.add_strip(points, &self.style(color));
+    }
+
+    pub fn closed_strip(&mut self, points: &[glam::Vec3], color: glam::Vec4) {
+        self.lines.add_loop(points, &self.style(color));
+    }
+
+    /// 从 `origin` 指向 `origin + vector` 的箭头
+    pub fn arrow(&mut self, origin: glam::Vec3, vector: glam::Vec3, color: glam::Vec4) {
+        let end = origin + vector;
+        self.line(origin, end, color);
+        let length = vector.length();
+        if length <= f32::EPSILON {
+            return;
+        }
+        let dir = vector / length;
+        let (u, v) = dir.any_orthonormal_pair();
+        let head = length * 0.2;
+        for side in [u, -u, v, -v] {
+            self.line(end, end - dir * head + side * head * 0.4, color);
+        }
+    }
+
+    /// 位于 `normal` 所垂直平面上的圆
+    pub fn circle(
+        &mut self,
+        center: glam::Vec3,
+        normal: glam::Vec3,
+        radius: f32,
+        color: glam::Vec4,
+    ) {
+        let (u, v) = normal.normalize_or(glam::Vec3::Y).any_orthonormal_pair();
+        let points = (0..self.segments.max(3))
+            .map(|i| {
+                let angle = i as f32 / self.segments.max(3) as f32 * TAU;
+                center + (u * angle.cos() + v * angle.sin()) * radius
+            })
+            .collect::<Vec<_>>();
+        self.closed_strip(&points, color);
+    }
+
+    /// 用三个互相垂直的大圆表示球
+    pub fn wire_sphere(&mut self, center: glam::Vec3, radius: f32, color: glam::Vec4) {
+        for axis in [glam::Vec3::X, glam::Vec3::Y, glam::Vec3::Z] {
+            self.circle(center, axis, radius, color);
+        }
+    }
+
+    /// 顶点位于 `apex`、沿 `direction` 张开 `angle` 弧度（半角）、长度为 `length` 的圆锥
+    pub fn cone(
+        &mut self,
+        apex: glam::Vec3,
+        direction: glam::Vec3,
+        angle: f32,
+        length: f32,
+        color: glam::Vec4,
+    ) {
+        let dir = direction.normalize_or(glam::Vec3::NEG_Y);
+        let (u, v) = dir.any_orthonormal_pair();
+        let center = apex + dir * length;
+        let radius = length * angle.tan();
+        self.circle(center, dir, radius, color);
+        for side in [u, -u, v, -v] {
+            self.line(apex, center + side * radius, color);
+        }
+    }
+
+    /// 三个坐标轴方向的短线组成的十字，用于标记没有体积的点
+    pub fn cross(&mut self, center: glam::Vec3, size: f32, color: glam::Vec4) {
+        for axis in [glam::Vec3::X, glam::Vec3::Y, glam::Vec3::Z] {
+            self.line(center - axis * size, center + axis * size, color);
+        }
+    }
+
+    /// 拾取射线：命中时画到命中点 `hit.0` 为止，并标出命中点与法线 `hit.1`；
+    /// 未命中时画出长度为 `length` 的一段
+    pub fn pick_ray(&mut self, ray: &Ray, hit: Option<(glam::Vec3, glam::Vec3)>, length: f32) {
+        let ray_color = glam::vec4(1.0, 0.9, 0.2, 1.0);
+        match hit {
+            Some((point, normal)) => {
+                self.line(ray.origin, point, ray_color);
+                let size = (point - ray.origin).length() * 0.02;
+                self.cross(point, size, glam::vec4(1.0, 0.2, 0.2, 1.0));
+                self.arrow(
+                    point,
+                    normal.normalize_or_zero() * size * 8.0,
+                    glam::vec4(0.2, 0.9, 1.0, 1.0),
+                );
+            }
+            None => self.line(
+                ray.origin,
+                ray.at(length),
+                ray_color * glam::vec4(1.0, 1.0, 1.0, 0.5),
+            ),
+        }
+    }
+
+    pub fn aabb(&mut self, min: glam::Vec3, max: glam::Vec3, color: glam::Vec4) {
+        let corner = |i: usize| {
+            glam::vec3(
+                if i & 1 == 0 { min.x } else { max.x },
+                if i & 2 == 0 { min.y } else { max.y },
+                if i & 4 == 0 { min.z } else { max.z },
+            )
+        };
+        self.box_edges(corner, color);
+    }
+
+    /// 画出 `view_proj` 对应的视锥（深度范围为 wgpu 的 [0, 1]），可用于相机或阴影投影
+    pub fn frustum(&mut self, view_proj: glam::Mat4, color: glam::Vec4) {
+        let inv = view_proj.inverse();
+        let corner = |i: usize| {
+            let ndc = glam::vec3(
+                if i & 1 == 0 { -1.0 } else { 1.0 },
+                if i & 2 == 0 { -1.0 } else { 1.0 },
+                if i & 4 == 0 { 0.0 } else { 1.0 },
+            );
+            inv.project_point3(ndc)
+        };
+        self.box_edges(corner, color);
+    }
+
+    /// 按下标的三个比特选择各轴端点，连接 8 个角点组成的 12 条棱
+    fn box_edges(&mut self, corner: impl Fn(usize) -> glam::Vec3, color: glam::Vec4) {
+        for i in 0..8 {
+            for bit in [1, 2, 4] {
+                if i & bit == 0 {
+                    self.line(corner(i), corner(i | bit), color);
+                }
+            }
+        }
+    }
+
+    pub fn update(
+        &mut self,
+        device: &Device,
+        queue: &Queue,
+        camera: &Camera,
+        size: PhysicalSize<u32>,
+    ) {
+        self.lines.update(device, queue, camera, size);
+    }
+
+    pub fn draw(&self, render_pass: &mut wgpu::RenderPass, camera_bind_group: &BindGroup) {
+        self.lines.draw(render_pass, camera_bind_group);
+    }
+}
@@ -0,0 +1,102 @@
+use winit::{event::WindowEvent, window::Window};
+
+use crate::context::GpuContext;
+
+/// 在 surface 上绘制 egui 界面
+///
+/// 把 [`WindowApp::window_event`](crate::app::WindowApp::window_event) 收到的事件交给
+/// [`EguiLayer::on_window_event`]，每帧用 [`EguiLayer::run`] 构建界面，
+/// 最后在场景之后以 [`EguiLayer::draw`] 画到 [`Frame::gamma_view`](crate::context::Frame::gamma_view) 上。
+pub struct EguiLayer {
+    pub ctx: egui::Context,
+    state: egui_winit::State,
+    renderer: egui_wgpu::Renderer,
+    output: Option<egui::FullOutput>,
+}
+
+impl EguiLayer {
+    pub fn new(gpu: &GpuContext) -> Self {
+        let ctx = egui::Context::default();
+        let window = gpu.window().window();
+        let state = egui_winit::State::new(
+            ctx.clone(),
+            egui::ViewportId::ROOT,
+            window,
+            Some(window.scale_factor() as f32),
+            window.theme(),
+            Some(gpu.device.limits().max_texture_dimension_2d as usize),
+        );
+        // egui 输出 sRGB 编码的颜色，绘制到非 sRGB 的 gamma 视图上
+        let renderer = egui_wgpu::Renderer::new(&gpu.device, gpu.gamma_format(), None, 1, false);
+        Self {
+            ctx,
+            state,
+            renderer,
+            output: None,
+        }
+    }
+
+    /// 处理窗口事件，返回 egui 是否需要独占该事件（例如光标位于面板上）
+    pub fn on_window_event(&mut self, window: &Window, event: &WindowEvent) -> bool {
+        self.state.on_window_event(window, event).consumed
+    }
+
+    /// egui 是否正在使用键盘或鼠标，此时应用不应响应相应的输入
+    pub fn wants_input(&self) -> bool {
+        self.ctx.wants_pointer_input() || self.ctx.wants_keyboard_input()
+    }
+
+    /// 构建这一帧的界面，结果保存到下一次 [`EguiLayer::draw`]
+    pub fn run(&mut self, window: &Window, build: impl FnMut(&egui::Context)) {
+        let input = self.state.take_egui_input(window);
+        let output = self.ctx.run(input, build);
+        self.state
+            .handle_platform_output(window, output.platform_output.clone());
+        self.output = Some(output);
+    }
+
+    /// 把最近一次构建的界面画到 `view` 上，保留其原有内容
+    pub fn draw(
+        &mut self,
+        gpu: &GpuContext,
+        encoder: &mut wgpu::CommandEncoder,
+        view: &wgpu::TextureView,
+    ) {
+        let Some(output) = self.output.take() else {
+            return;
+        };
+        let size = gpu.size();
+        let screen = egui_wgpu::ScreenDescriptor {
+            size_in_pixels: [size.width, size.height],
+            pixels_per_point: output.pixels_per_point,
+        };
+        let jobs = self.ctx.tessellate(output.shapes, output.pixels_per_point);
+        for (id, delta) in &output.textures_delta.set {
+            self.renderer
+                .update_texture(&gpu.device, &gpu.queue, *id, delta);
+        }
+        let commands =
+            self.renderer
+                .update_buffers(&gpu.device, &gpu.queue, encoder, &jobs, &screen);
+        gpu.queue.submit(commands);
+
+        let pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Egui Pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Load,
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            ..Default::default()
+        });
+        self.renderer
+            .render(&mut pass.forget_lifetime(), &jobs, &screen);
+
+        for id in &output.textures_delta.free {
+            self.renderer.free_texture(id);
+        }
+    }
+}
@@ -0,0 +1,297 @@
+use wgpu::{util::DeviceExt, BindGroup, BindGroupLayout, Buffer, Device, Queue};
+
+use crate::{light::DirectionalLight, probe::EnvironmentProbe, sky::Sun, uniform::GpuUniform};
+
+/// 大气散射的近似参数，用于远处几何体的空气透视
+#[derive(Debug, Copy, Clone)]
+pub struct Atmosphere {
+    /// 瑞利散射（蓝色调、随波长变化）的强度倍数
+    pub rayleigh: f32,
+    /// 米氏散射（灰白色雾霾）的强度倍数
+    pub mie: f32,
+    /// 大气密度随高度衰减的标高，单位与场景相同
+    pub scale_height: f32,
+    /// 场景单位到米的换算，场景尺度较小时调大可增强效果
+    pub distance_scale: f32,
+}
+
+impl Default for Atmosphere {
+    fn default() -> Self {
+        Self {
+            rayleigh: 1.0,
+            mie: 1.0,
+            scale_height: 40.0,
+            distance_scale: 100.0,
+        }
+    }
+}
+
+/// 随高度指数衰减的雾，`density` 为 0 时关闭
+#[derive(Debug, Copy, Clone)]
+pub struct Fog {
+    pub color: glam::Vec3,
+    /// `base_height` 处每单位距离的雾浓度
+    pub density: f32,
+    /// 浓度随高度衰减的速度，为 0 时是均匀的距离雾
+    pub height_falloff: f32,
+    pub base_height: f32,
+}
+
+impl Default for Fog {
+    fn default() -> Self {
+        Self {
+            color: glam::vec3(0.6, 0.65, 0.7),
+            density: 0.0,
+            height_falloff: 0.1,
+            base_height: 0.0,
+        }
+    }
+}
+
+/// 场景环境参数，以单个 uniform 提供给着色器
+///
+/// 环境光、雾与 IBL 都在这里设置，内置的渲染器通过 [`EnvironmentBundle`] 的同一个
+/// bind group 读取，每帧调用一次 [`EnvironmentBundle::update`] 即可。
+#[derive(Debug, Copy, Clone)]
+pub struct Environment {
+    /// 指向太阳的方向
+    pub sun_direction: glam::Vec3,
+    pub sun_color: glam::Vec3,
+    pub sun_intensity: f32,
+    pub turbidity: f32,
+    /// 天空辐射度的曝光，与 [`crate::sky::Sky::exposure`] 一致时远景能与天空衔接
+    pub exposure: f32,
+    pub atmosphere: Atmosphere,
+    /// 均匀的环境光
+    pub ambient: glam::Vec3,
+    pub fog: Fog,
+    /// IBL 环境贴图的亮度倍数，见 [`EnvironmentBundle::set_ibl`]
+    pub ibl_intensity: f32,
+}
+
+impl Default for Environment {
+    fn default() -> Self {
+        let light = DirectionalLight::default();
+        Self {
+            sun_direction: light.direction,
+            sun_color: light.color,
+            sun_intensity: light.intensity,
+            turbidity: 2.5,
+            exposure: 0.05,
+            atmosphere: Atmosphere::default(),
+            ambient: glam::Vec3::splat(0.08),
+            fog: Fog::default(),
+            ibl_intensity: 1.0,
+        }
+    }
+}
+
+impl Environment {
+    pub fn set_sun(&mut self, sun: &Sun) {
+        let mut light = DirectionalLight::default();
+        sun.apply_to(&mut light);
+        self.sun_direction = light.direction;
+        self.sun_color = light.color;
+        self.sun_intensity = light.intensity;
+        self.turbidity = sun.turbidity;
+    }
+}
+
+/// 与 `shaders/environment.wgsl` 中的 `EnvironmentUniform` 保持一致
+#[repr(C)]
+#[derive(Debug, Copy, Clone, GpuUniform)]
+pub struct EnvironmentUniform {
+    sun_direction: [f32; 4],
+    sun_color: [f32; 4],
+    atmosphere: [f32; 4],
+    params: [f32; 4],
+    ambient: [f32; 4],
+    fog_color: [f32; 4],
+    fog_params: [f32; 4],
+}
+
+impl EnvironmentUniform {
+    pub fn new(environment: &Environment) -> Self {
+        let mut uniform = Self {
+            sun_direction: [0.0; 4],
+            sun_color: [0.0; 4],
+            atmosphere: [0.0; 4],
+            params: [0.0; 4],
+            ambient: [0.0; 4],
+            fog_color: [0.0; 4],
+            fog_params: [0.0; 4],
+        };
+        uniform.update(environment);
+        uniform
+    }
+
+    pub fn update(&mut self, environment: &Environment) {
+        let atmosphere = &environment.atmosphere;
+        self.sun_direction = environment
+            .sun_direction
+            .normalize()
+            .extend(environment.turbidity)
+            .to_array();
+        self.sun_color = environment
+            .sun_color
+            .extend(environment.sun_intensity)
+            .to_array();
+        self.atmosphere = [
+            atmosphere.rayleigh,
+            atmosphere.mie,
+            atmosphere.scale_height,
+            atmosphere.distance_scale,
+        ];
+        self.params = [environment.exposure, 0.0, 0.0, 0.0];
+        self.ambient = environment
+            .ambient
+            .extend(environment.ibl_intensity)
+            .to_array();
+        let fog = &environment.fog;
+        self.fog_color = fog.color.extend(fog.density.max(0.0)).to_array();
+        self.fog_params = [fog.height_falloff.max(0.0), fog.base_height, 0.0, 0.0];
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct EnvironmentBundle {
+    pub environment: Environment,
+    pub uniform: EnvironmentUniform,
+    pub buffer: Buffer,
+    pub bind_group_layout: BindGroupLayout,
+    pub bind_group: BindGroup,
+    /// 没有设置 IBL 时绑定的全黑立方体贴图
+    empty_ibl: (wgpu::TextureView, wgpu::Sampler),
+}
+
+impl EnvironmentBundle {
+    /// binding 0 为 `EnvironmentUniform`，1 与 2 为 IBL 的立方体贴图与采样器
+    pub fn layout_entries() -> [wgpu::BindGroupLayoutEntry; 3] {
+        [
+            wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            },
+            wgpu::BindGroupLayoutEntry {
+                binding: 1,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Texture {
+                    sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                    view_dimension: wgpu::TextureViewDimension::Cube,
+                    multisampled: false,
+                },
+                count: None,
+            },
+            wgpu::BindGroupLayoutEntry {
+                binding: 2,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                count: None,
+            },
+        ]
+    }
+
+    pub fn new(environment: Environment, device: &Device) -> Self {
+        let uniform = EnvironmentUniform::new(&environment);
+        let buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Environment Buffer"),
+            contents: bytemuck::cast_slice(&[uniform]),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &Self::layout_entries(),
+            label: Some("environment_bind_group_layout"),
+        });
+        let empty_ibl = device
+            .create_texture(&wgpu::TextureDescriptor {
+                label: Some("empty_ibl"),
+                size: wgpu::Extent3d {
+                    width: 1,
+                    height: 1,
+                    depth_or_array_layers: 6,
+                },
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format: EnvironmentProbe::FORMAT,
+                usage: wgpu::TextureUsages::TEXTURE_BINDING,
+                view_formats: &[],
+            })
+            .create_view(&wgpu::TextureViewDescriptor {
+                dimension: Some(wgpu::TextureViewDimension::Cube),
+                ..Default::default()
+            });
+        let empty_ibl = (
+            empty_ibl,
+            device.create_sampler(&wgpu::SamplerDescriptor {
+                mag_filter: wgpu::FilterMode::Linear,
+                min_filter: wgpu::FilterMode::Linear,
+                ..Default::default()
+            }),
+        );
+        let bind_group = Self::create_bind_group(
+            device,
+            &bind_group_layout,
+            &buffer,
+            &empty_ibl.0,
+            &empty_ibl.1,
+        );
+        Self {
+            environment,
+            uniform,
+            buffer,
+            bind_group_layout,
+            bind_group,
+            empty_ibl,
+        }
+    }
+
+    fn create_bind_group(
+        device: &Device,
+        layout: &BindGroupLayout,
+        buffer: &Buffer,
+        ibl_view: &wgpu::TextureView,
+        ibl_sampler: &wgpu::Sampler,
+    ) -> BindGroup {
+        device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::TextureView(ibl_view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: wgpu::BindingResource::Sampler(ibl_sampler),
+                },
+            ],
+            label: Some("environment_bind_group"),
+        })
+    }
+
+    /// 使用环境探针作为 IBL，`None` 时恢复为全黑；会重建 `bind_group`，
+    /// 因此需要在创建保存了该 bind group 的渲染器之前调用
+    pub fn set_ibl(&mut self, device: &Device, probe: Option<&EnvironmentProbe>) {
+        let (view, sampler) = match probe {
+            Some(probe) => (&probe.view, &probe.sampler),
+            None => (&self.empty_ibl.0, &self.empty_ibl.1),
+        };
+        self.bind_group =
+            Self::create_bind_group(device, &self.bind_group_layout, &self.buffer, view, sampler);
+    }
+
+    pub fn update(&mut self, queue: &Queue) {
+        self.uniform.update(&self.environment);
+        self.uniform.write_to(queue, &self.buffer);
+    }
+}
@@ -0,0 +1,112 @@
+use futures::future::LocalBoxFuture;
+
+/// 驱动应用异步初始化的执行器，[`WindowAppHandler`](crate::app::WindowAppHandler)
+/// 在事件循环线程上用它运行 [`WindowApp::new`](crate::app::WindowApp::new)
+///
+/// 默认的执行器由 cargo 特性决定，见 [`default_executor`]；也可以用
+/// [`WindowAppHandler::with_executor`](crate::app::WindowAppHandler::with_executor) 替换。
+pub trait Executor {
+    /// 启动 `future`，可以在返回前就执行完毕
+    fn spawn(&mut self, future: LocalBoxFuture<'static, ()>);
+
+    /// 事件循环每轮进入等待前调用，推进尚未完成的任务
+    fn poll(&mut self) {}
+}
+
+/// 在 `spawn` 中阻塞直到任务完成
+#[derive(Debug, Default)]
+pub struct BlockingExecutor;
+
+impl Executor for BlockingExecutor {
+    fn spawn(&mut self, future: LocalBoxFuture<'static, ()>) {
+        futures::executor::block_on(future);
+    }
+}
+
+/// 在事件循环线程上逐步推进任务，任务未完成时不会阻塞事件循环
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Debug, Default)]
+pub struct LocalExecutor {
+    pool: futures::executor::LocalPool,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Executor for LocalExecutor {
+    fn spawn(&mut self, future: LocalBoxFuture<'static, ()>) {
+        use futures::task::LocalSpawnExt;
+        self.pool
+            .spawner()
+            .spawn_local(future)
+            .expect("local executor has shut down");
+        self.poll();
+    }
+
+    fn poll(&mut self) {
+        self.pool.run_until_stalled();
+    }
+}
+
+/// 在 tokio 运行时的 `LocalSet` 中推进任务，任务可以使用 tokio 的 IO 与定时器
+#[cfg(all(feature = "tokio", not(target_arch = "wasm32")))]
+pub struct TokioExecutor {
+    runtime: tokio::runtime::Runtime,
+    local: tokio::task::LocalSet,
+}
+
+#[cfg(all(feature = "tokio", not(target_arch = "wasm32")))]
+impl TokioExecutor {
+    pub fn new() -> anyhow::Result<Self> {
+        Ok(Self {
+            runtime: tokio::runtime::Runtime::new()?,
+            local: tokio::task::LocalSet::new(),
+        })
+    }
+}
+
+#[cfg(all(feature = "tokio", not(target_arch = "wasm32")))]
+impl Executor for TokioExecutor {
+    fn spawn(&mut self, future: LocalBoxFuture<'static, ()>) {
+        self.local.spawn_local(future);
+        self.poll();
+    }
+
+    fn poll(&mut self) {
+        self.runtime
+            .block_on(self.local.run_until(tokio::task::yield_now()));
+    }
+}
+
+/// 浏览器中交给 `wasm_bindgen_futures` 调度
+#[cfg(target_arch = "wasm32")]
+#[derive(Debug, Default)]
+pub struct WasmExecutor;
+
+#[cfg(target_arch = "wasm32")]
+impl Executor for WasmExecutor {
+    fn spawn(&mut self, future: LocalBoxFuture<'static, ()>) {
+        wasm_bindgen_futures::spawn_local(future);
+    }
+}
+
+/// 浏览器中使用 [`WasmExecutor`]；开启 `tokio` 特性（默认开启）时使用 [`TokioExecutor`]，
+/// 否则使用 [`LocalExecutor`]
+pub fn default_executor() -> Box<dyn Executor> {
+    #[cfg(target_arch = "wasm32")]
+    {
+        Box::new(WasmExecutor)
+    }
+    #[cfg(all(feature = "tokio", not(target_arch = "wasm32")))]
+    {
+        match TokioExecutor::new() {
+            Ok(executor) => Box::new(executor),
+            Err(e) => {
+                eprintln!("failed to start tokio runtime, falling back to local executor: {e:#}");
+                Box::new(LocalExecutor::default())
+            }
+        }
+    }
+    #[cfg(all(not(feature = "tokio"), not(target_arch = "wasm32")))]
+    {
+        Box::new(LocalExecutor::default())
+    }
+}
@@ -0,0 +1,81 @@
+use crate::camera::Camera;
+
+/// 由 view-projection 矩阵提取的视锥，6 个平面的法线朝内，深度范围为 wgpu 的 [0, 1]
+///
+/// 平面存为 `(法线, 距离)`，法线已归一化，点 `p` 在平面内侧当且仅当 `dot(normal, p) + d >= 0`。
+/// 反向深度时近、远平面的顺序互换；无限远的远平面退化为不剔除任何点的 `(0, 0, 0, 1)`。
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Frustum {
+    /// 依次为左、右、下、上、近、远
+    pub planes: [glam::Vec4; 6],
+}
+
+impl Frustum {
+    /// Gribb-Hartmann 方法：平面由矩阵的行组合得到
+    pub fn from_view_proj(view_proj: glam::Mat4) -> Self {
+        let rows = [
+            view_proj.row(0),
+            view_proj.row(1),
+            view_proj.row(2),
+            view_proj.row(3),
+        ];
+        let planes = [
+            rows[3] + rows[0],
+            rows[3] - rows[0],
+            rows[3] + rows[1],
+            rows[3] - rows[1],
+            rows[2],
+            rows[3] - rows[2],
+        ]
+        .map(|p| {
+            let length = p.truncate().length();
+            if length > 0.0 {
+                p / length
+            } else {
+                glam::Vec4::W
+            }
+        });
+        Self { planes }
+    }
+
+    pub fn from_camera(camera: &Camera) -> Self {
+        Self::from_view_proj(camera.build_view_projection_matrix())
+    }
+
+    pub fn contains_point(&self, point: glam::Vec3) -> bool {
+        let point = point.extend(1.0);
+        self.planes.iter().all(|plane| plane.dot(point) >= 0.0)
+    }
+
+    /// 球与视锥相交或在其内部时返回 `true`；保守测试，视锥角落附近可能误判为可见
+    pub fn intersects_sphere(&self, center: glam::Vec3, radius: f32) -> bool {
+        let center = center.extend(1.0);
+        self.planes.iter().all(|plane| plane.dot(center) >= -radius)
+    }
+
+    /// 轴对齐包围盒与视锥相交或在其内部时返回 `true`；
+    /// 对每个平面只检查最靠内侧的角点，与 [`Frustum::intersects_sphere`] 一样是保守测试
+    pub fn intersects_aabb(&self, min: glam::Vec3, max: glam::Vec3) -> bool {
+        self.planes.iter().all(|plane| {
+            let normal = plane.truncate();
+            let corner = glam::Vec3::select(normal.cmpge(glam::Vec3::ZERO), max, min);
+            normal.dot(corner) + plane.w >= 0.0
+        })
+    }
+}
+
+/// 经 `transform` 变换后仍包住原包围盒的轴对齐包围盒，返回 `(最小点, 最大点)`
+pub fn transform_aabb(
+    transform: glam::Mat4,
+    min: glam::Vec3,
+    max: glam::Vec3,
+) -> (glam::Vec3, glam::Vec3) {
+    let center = transform.transform_point3((min + max) * 0.5);
+    let half_extents = (max - min) * 0.5;
+    let linear = glam::Mat3::from_mat4(transform);
+    // 每个轴上的半长是各列在该轴上投影长度之和
+    let extents = linear.x_axis.abs() * half_extents.x
+        + linear.y_axis.abs() * half_extents.y
+        + linear.z_axis.abs() * half_extents.z;
+    (center - extents, center + extents)
+}
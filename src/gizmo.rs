@@ -0,0 +1,319 @@
+use crate::{
+    camera::Camera,
+    debug_draw::DebugDraw,
+    instance::Instance,
+    light::{DirectionalLight, PointLight, SpotLight},
+    ray::Ray,
+};
+
+/// 单个光源的调试图形开关
+#[derive(Debug, Copy, Clone)]
+pub struct LightGizmo {
+    pub visible: bool,
+    /// 同时画出阴影投影的视锥
+    pub shadow_frustum: bool,
+    /// 平行光没有位置，箭头画在这个点上，阴影视锥也以它为中心
+    pub anchor: glam::Vec3,
+    /// 平行光阴影覆盖的半径
+    pub shadow_radius: f32,
+}
+
+impl Default for LightGizmo {
+    fn default() -> Self {
+        Self {
+            visible: true,
+            shadow_frustum: false,
+            anchor: glam::Vec3::ZERO,
+            shadow_radius: 10.0,
+        }
+    }
+}
+
+const SHADOW_FRUSTUM_COLOR: glam::Vec4 = glam::vec4(1.0, 0.9, 0.2, 0.8);
+const SPOT_SHADOW_ZNEAR: f32 = 0.1;
+
+fn light_color(color: glam::Vec3) -> glam::Vec4 {
+    // 颜色可能超过 1，只保留色相以免线框发白
+    (color / color.max_element().max(1.0)).extend(1.0)
+}
+
+impl DebugDraw {
+    /// 平行光：指向光照方向的箭头和表示光源一侧的圆盘
+    pub fn directional_light(&mut self, light: &DirectionalLight, gizmo: &LightGizmo) {
+        if !gizmo.visible {
+            return;
+        }
+        let color = light_color(light.color);
+        let dir = light.direction.normalize_or(glam::Vec3::Y);
+        let length = 1.0 + light.intensity.max(0.0).sqrt();
+        let origin = gizmo.anchor + dir * length;
+        self.circle(origin, dir, 0.25 * length, color);
+        self.arrow(origin, -dir * length, color);
+        if gizmo.shadow_frustum {
+            self.frustum(
+                light.shadow_view_proj(gizmo.anchor, gizmo.shadow_radius),
+                SHADOW_FRUSTUM_COLOR,
+            );
+        }
+    }
+
+    /// 点光源：光源处的小十字与表示影响范围的球
+    ///
+    /// 点光源的阴影需要六个方向的投影，这里不画阴影视锥。
+    pub fn point_light(&mut self, light: &PointLight, gizmo: &LightGizmo) {
+        if !gizmo.visible {
+            return;
+        }
+        let color = light_color(light.color);
+        self.cross(light.position, 0.15, color);
+        self.wire_sphere(
+            light.position,
+            light.range,
+            color * glam::vec4(1.0, 1.0, 1.0, 0.5),
+        );
+    }
+
+    /// 聚光灯：内外两层圆锥，锥的母线长度为光照范围
+    pub fn spot_light(&mut self, light: &SpotLight, gizmo: &LightGizmo) {
+        if !gizmo.visible {
+            return;
+        }
+        let color = light_color(light.color);
+        let dir = light.direction.normalize_or(glam::Vec3::NEG_Y);
+        self.cross(light.position, 0.15, color);
+        self.cone(
+            light.position,
+            dir,
+            light.outer_angle,
+            light.range * light.outer_angle.cos(),
+            color,
+        );
+        self.cone(
+            light.position,
+            dir,
+            light.inner_angle,
+            light.range * light.inner_angle.cos(),
+            color * glam::vec4(1.0, 1.0, 1.0, 0.5),
+        );
+        if gizmo.shadow_frustum {
+            self.frustum(
+                light.shadow_view_proj(SPOT_SHADOW_ZNEAR),
+                SHADOW_FRUSTUM_COLOR,
+            );
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub enum GizmoMode {
+    #[default]
+    Translate,
+    Rotate,
+    Scale,
+}
+
+/// 一次拖动产生的变换增量；平移与旋转在世界空间中，缩放沿实例的局部轴
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum TransformDelta {
+    Translate(glam::Vec3),
+    /// 绕实例中心的旋转
+    Rotate(glam::Quat),
+    /// 各局部轴上的缩放倍数
+    Scale(glam::Vec3),
+}
+
+impl TransformDelta {
+    pub fn apply(&self, instance: &mut Instance) {
+        match *self {
+            TransformDelta::Translate(offset) => instance.position += offset,
+            TransformDelta::Rotate(rotation) => {
+                instance.rotation = (rotation * instance.rotation).normalize()
+            }
+            TransformDelta::Scale(factor) => instance.scale *= factor,
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone)]
+struct GizmoDrag {
+    axis: usize,
+    /// 平移与缩放：上一次光标在轴上的位置；旋转不使用
+    along: f32,
+    /// 旋转：上一次光标在旋转平面上相对中心的方向
+    direction: glam::Vec3,
+}
+
+/// 平移、旋转、缩放操纵器：用光标射线拾取坐标轴，拖动时输出 [`TransformDelta`]
+///
+/// 手柄长度随距离缩放，在屏幕上保持大致相同的大小。
+#[derive(Debug, Clone)]
+pub struct TransformGizmo {
+    pub mode: GizmoMode,
+    /// 手柄长度占视口高度的比例
+    pub screen_size: f32,
+    hovered: Option<usize>,
+    drag: Option<GizmoDrag>,
+}
+
+impl Default for TransformGizmo {
+    fn default() -> Self {
+        Self {
+            mode: GizmoMode::default(),
+            screen_size: 0.15,
+            hovered: None,
+            drag: None,
+        }
+    }
+}
+
+const AXIS_COLORS: [glam::Vec4; 3] = [
+    glam::vec4(0.9, 0.2, 0.2, 1.0),
+    glam::vec4(0.2, 0.85, 0.3, 1.0),
+    glam::vec4(0.25, 0.45, 1.0, 1.0),
+];
+const ACTIVE_AXIS_COLOR: glam::Vec4 = glam::vec4(1.0, 0.9, 0.2, 1.0);
+/// 拾取容差，相对手柄长度
+const PICK_TOLERANCE: f32 = 0.1;
+
+impl TransformGizmo {
+    pub fn is_dragging(&self) -> bool {
+        self.drag.is_some()
+    }
+
+    fn handle_length(&self, camera: &Camera, center: glam::Vec3) -> f32 {
+        let forward = (camera.target - camera.eye).normalize_or_zero();
+        let depth = (center - camera.eye).dot(forward).max(camera.znear);
+        camera.view_height_at(depth) * self.screen_size
+    }
+
+    /// 缩放沿实例的局部轴，其余模式使用世界坐标轴
+    fn axes(&self, target: &Instance) -> [glam::Vec3; 3] {
+        let axes = [glam::Vec3::X, glam::Vec3::Y, glam::Vec3::Z];
+        match self.mode {
+            GizmoMode::Scale => axes.map(|axis| target.rotation * axis),
+            _ => axes,
+        }
+    }
+
+    /// 光标射线下最近的坐标轴
+    fn pick(&self, ray: &Ray, camera: &Camera, target: &Instance) -> Option<usize> {
+        let length = self.handle_length(camera, target.position);
+        let tolerance = length * PICK_TOLERANCE;
+        let mut best: Option<(usize, f32)> = None;
+        for (axis, dir) in self.axes(target).into_iter().enumerate() {
+            let hit = match self.mode {
+                GizmoMode::Translate | GizmoMode::Scale => ray
+                    .closest_to_line(target.position, dir)
+                    .filter(|&(t, s)| t >= 0.0 && (0.0..=length * 1.1).contains(&s))
+                    .filter(|&(t, s)| ray.at(t).distance(target.position + dir * s) < tolerance)
+                    .map(|(t, _)| t),
+                GizmoMode::Rotate => ray
+                    .intersect_plane(target.position, dir)
+                    .filter(|&t| (ray.at(t).distance(target.position) - length).abs() < tolerance),
+            };
+            if let Some(t) = hit {
+                if best.is_none_or(|(_, best_t)| t < best_t) {
+                    best = Some((axis, t));
+                }
+            }
+        }
+        best.map(|(axis, _)| axis)
+    }
+
+    /// 光标射线在坐标轴上的位置（平移、缩放）或旋转平面上相对中心的方向（旋转）
+    fn measure(&self, ray: &Ray, target: &Instance, axis: usize) -> Option<(f32, glam::Vec3)> {
+        let dir = self.axes(target)[axis];
+        match self.mode {
+            GizmoMode::Translate | GizmoMode::Scale => ray
+                .closest_to_line(target.position, dir)
+                .map(|(_, s)| (s, glam::Vec3::ZERO)),
+            GizmoMode::Rotate => {
+                let t = ray.intersect_plane(target.position, dir)?;
+                let offset = ray.at(t) - target.position;
+                Some((0.0, offset.reject_from_normalized(dir).try_normalize()?))
+            }
+        }
+    }
+
+    /// 没有拖动时更新高亮的坐标轴
+    pub fn hover(&mut self, ray: &Ray, camera: &Camera, target: &Instance) {
+        if self.drag.is_none() {
+            self.hovered = self.pick(ray, camera, target);
+        }
+    }
+
+    /// 按下鼠标时调用，射线命中某个坐标轴时开始拖动并返回 `true`
+    pub fn begin_drag(&mut self, ray: &Ray, camera: &Camera, target: &Instance) -> bool {
+        let Some(axis) = self.pick(ray, camera, target) else {
+            return false;
+        };
+        let Some((along, direction)) = self.measure(ray, target, axis) else {
+            return false;
+        };
+        self.hovered = Some(axis);
+        self.drag = Some(GizmoDrag {
+            axis,
+            along,
+            direction,
+        });
+        true
+    }
+
+    /// 光标移动时调用，返回自上一次调用以来的增量，调用方需要把它应用到 `target` 上
+    pub fn drag(&mut self, ray: &Ray, target: &Instance) -> Option<TransformDelta> {
+        let drag = self.drag?;
+        let (along, direction) = self.measure(ray, target, drag.axis)?;
+        let dir = self.axes(target)[drag.axis];
+        let delta = match self.mode {
+            // 平移后中心随之移动，相对中心的抓取位置保持不变，因此不更新 `along`
+            GizmoMode::Translate => TransformDelta::Translate(dir * (along - drag.along)),
+            GizmoMode::Rotate => {
+                let angle = dir
+                    .dot(drag.direction.cross(direction))
+                    .atan2(drag.direction.dot(direction));
+                self.drag = Some(GizmoDrag { direction, ..drag });
+                TransformDelta::Rotate(glam::Quat::from_axis_angle(dir, angle))
+            }
+            GizmoMode::Scale => {
+                if drag.along.abs() < f32::EPSILON {
+                    return None;
+                }
+                let factor = (along / drag.along).max(0.01);
+                self.drag = Some(GizmoDrag { along, ..drag });
+                let mut scale = glam::Vec3::ONE;
+                scale[drag.axis] = factor;
+                TransformDelta::Scale(scale)
+            }
+        };
+        Some(delta)
+    }
+
+    pub fn end_drag(&mut self) {
+        self.drag = None;
+    }
+}
+
+impl DebugDraw {
+    /// 变换操纵器：平移为箭头，旋转为圆环，缩放为末端带方块的线段
+    pub fn transform_gizmo(&mut self, gizmo: &TransformGizmo, camera: &Camera, target: &Instance) {
+        let length = gizmo.handle_length(camera, target.position);
+        let center = target.position;
+        for (axis, dir) in gizmo.axes(target).into_iter().enumerate() {
+            let color = if gizmo.hovered == Some(axis) {
+                ACTIVE_AXIS_COLOR
+            } else {
+                AXIS_COLORS[axis]
+            };
+            match gizmo.mode {
+                GizmoMode::Translate => self.arrow(center, dir * length, color),
+                GizmoMode::Rotate => self.circle(center, dir, length, color),
+                GizmoMode::Scale => {
+                    let end = center + dir * length;
+                    let half = glam::Vec3::splat(length * 0.05);
+                    self.line(center, end, color);
+                    self.aabb(end - half, end + half, color);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,175 @@
+use bytemuck::{Pod, Zeroable};
+use wgpu::{util::DeviceExt, BindGroup, BindGroupLayout, Buffer, Device};
+
+use crate::shader::ShaderLibrary;
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+struct DispatchArgsParams {
+    counter_index: u32,
+    workgroup_size: u32,
+    max_workgroups: u32,
+    _padding: u32,
+}
+
+unsafe impl Zeroable for DispatchArgsParams {}
+unsafe impl Pod for DispatchArgsParams {}
+
+/// `DrawIndirectArgs` 与 `DrawIndexedIndirectArgs` 中 `instance_count` 的字节偏移，
+/// 例如用 [`GpuCounters`](crate::profiler::GpuCounters) 读回剔除后的实例数
+pub const INSTANCE_COUNT_OFFSET: wgpu::BufferAddress = 4;
+
+/// `count` 个线程、每个工作组 `workgroup_size` 个线程时的间接调度参数，除设备上限外与 GPU 上的转换结果相同
+pub fn dispatch_args(count: u32, workgroup_size: u32) -> wgpu::util::DispatchIndirectArgs {
+    wgpu::util::DispatchIndirectArgs {
+        x: count.div_ceil(workgroup_size.max(1)),
+        y: 1,
+        z: 1,
+    }
+}
+
+/// 在 GPU 上把计数转换为 `dispatch_workgroups_indirect` 参数的计算通道
+///
+/// 多级计算中前一级用原子计数写出结果数量（例如存活的粒子），后一级按这个数量调度时，
+/// 不需要读回 CPU：每个计数对应一个 [`IndirectDispatch`]，在两级之间调用
+/// [`DispatchArgsGenerator::generate`]，后一级再用 [`IndirectDispatch::dispatch`] 调度。
+/// 工作组数按整数计算向上取整，并限制在设备允许的范围内；最后一个工作组中多出的线程
+/// 需要由后一级的着色器自己读取计数并跳过。
+pub struct DispatchArgsGenerator {
+    bind_group_layout: BindGroupLayout,
+    pipeline: wgpu::ComputePipeline,
+    max_workgroups: u32,
+}
+
+impl DispatchArgsGenerator {
+    pub fn new(device: &Device) -> Self {
+        let buffer = |binding, ty| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility: wgpu::ShaderStages::COMPUTE,
+            ty: wgpu::BindingType::Buffer {
+                ty,
+                has_dynamic_offset: false,
+                min_binding_size: None,
+            },
+            count: None,
+        };
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &[
+                buffer(0, wgpu::BufferBindingType::Uniform),
+                buffer(1, wgpu::BufferBindingType::Storage { read_only: true }),
+                buffer(2, wgpu::BufferBindingType::Storage { read_only: false }),
+            ],
+            label: Some("dispatch_args_bind_group_layout"),
+        });
+
+        let shader = ShaderLibrary::new()
+            .create_shader_module(
+                device,
+                "Dispatch Args Shader",
+                include_str!("../shaders/dispatch_args.wgsl"),
+            )
+            .expect("built-in dispatch args shader");
+        let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Dispatch Args Pipeline Layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+        let pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+            label: Some("Dispatch Args Pipeline"),
+            layout: Some(&layout),
+            module: &shader,
+            entry_point: Some("cs_main"),
+            compilation_options: Default::default(),
+            cache: None,
+        });
+
+        Self {
+            bind_group_layout,
+            pipeline,
+            max_workgroups: device.limits().max_compute_workgroups_per_dimension,
+        }
+    }
+
+    /// 读取 `counter` 中第 `counter_index` 个 u32 作为线程数，`counter` 须带有 `STORAGE` 用途
+    ///
+    /// `workgroup_size` 为后一级着色器的 `@workgroup_size`（只用 x 维）。
+    pub fn create(
+        &self,
+        device: &Device,
+        counter: &Buffer,
+        counter_index: u32,
+        workgroup_size: u32,
+    ) -> IndirectDispatch {
+        let params = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Dispatch Args Params Buffer"),
+            contents: bytemuck::cast_slice(&[DispatchArgsParams {
+                counter_index,
+                workgroup_size: workgroup_size.max(1),
+                max_workgroups: self.max_workgroups,
+                _padding: 0,
+            }]),
+            usage: wgpu::BufferUsages::UNIFORM,
+        });
+        // 初始为零，生成之前调度不会执行任何工作组
+        let args_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Indirect Dispatch Args Buffer"),
+            contents: dispatch_args(0, 1).as_bytes(),
+            usage: wgpu::BufferUsages::STORAGE
+                | wgpu::BufferUsages::INDIRECT
+                | wgpu::BufferUsages::COPY_SRC,
+        });
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: &self.bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: params.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: counter.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: args_buffer.as_entire_binding(),
+                },
+            ],
+            label: Some("dispatch_args_bind_group"),
+        });
+
+        IndirectDispatch {
+            args_buffer,
+            bind_group,
+        }
+    }
+
+    /// 在一个计算通道中更新 `dispatches` 的参数，须在写入计数的通道之后、调度之前记录
+    pub fn generate(&self, encoder: &mut wgpu::CommandEncoder, dispatches: &[&IndirectDispatch]) {
+        if dispatches.is_empty() {
+            return;
+        }
+        let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+            label: Some("Dispatch Args Pass"),
+            timestamp_writes: None,
+        });
+        pass.set_pipeline(&self.pipeline);
+        for dispatch in dispatches {
+            pass.set_bind_group(0, &dispatch.bind_group, &[]);
+            pass.dispatch_workgroups(1, 1, 1);
+        }
+    }
+}
+
+/// 由 [`DispatchArgsGenerator::create`] 创建的一组间接调度参数
+pub struct IndirectDispatch {
+    /// `DispatchIndirectArgs` 布局，可以复制出来读回调试
+    pub args_buffer: Buffer,
+    bind_group: BindGroup,
+}
+
+impl IndirectDispatch {
+    /// 按最近一次生成的参数调度当前管线
+    pub fn dispatch(&self, pass: &mut wgpu::ComputePass) {
+        pass.dispatch_workgroups_indirect(&self.args_buffer, 0);
+    }
+}
@@ -0,0 +1,149 @@
+use std::collections::HashSet;
+
+use winit::{
+    dpi::PhysicalPosition,
+    event::{ElementState, MouseButton, MouseScrollDelta},
+    keyboard::{KeyCode, PhysicalKey},
+};
+
+use crate::replay::InputEvent;
+
+/// 由 [`WindowAppHandler`](crate::app::WindowAppHandler) 维护、可在
+/// [`WindowApp::update_with_input`](crate::app::WindowApp::update_with_input) 中查询的输入状态
+///
+/// “本帧”指自上一次 update 以来；固定步长下一帧执行多次 update 时，只有第一次能看到本帧的变化与位移。
+#[derive(Debug, Clone, Default)]
+pub struct InputState {
+    keys: HashSet<KeyCode>,
+    keys_pressed: HashSet<KeyCode>,
+    keys_released: HashSet<KeyCode>,
+    buttons: HashSet<MouseButton>,
+    buttons_pressed: HashSet<MouseButton>,
+    buttons_released: HashSet<MouseButton>,
+    cursor: Option<PhysicalPosition<f64>>,
+    cursor_delta: glam::Vec2,
+    mouse_motion: glam::Vec2,
+    wheel: f32,
+}
+
+impl InputState {
+    pub fn is_key_down(&self, key: KeyCode) -> bool {
+        self.keys.contains(&key)
+    }
+
+    /// 本帧按下，按键重复不算
+    pub fn key_just_pressed(&self, key: KeyCode) -> bool {
+        self.keys_pressed.contains(&key)
+    }
+
+    pub fn key_just_released(&self, key: KeyCode) -> bool {
+        self.keys_released.contains(&key)
+    }
+
+    pub fn is_button_down(&self, button: MouseButton) -> bool {
+        self.buttons.contains(&button)
+    }
+
+    pub fn button_just_pressed(&self, button: MouseButton) -> bool {
+        self.buttons_pressed.contains(&button)
+    }
+
+    pub fn button_just_released(&self, button: MouseButton) -> bool {
+        self.buttons_released.contains(&button)
+    }
+
+    /// 光标在窗口内的位置（物理像素），光标尚未进入窗口时为 `None`
+    pub fn cursor(&self) -> Option<PhysicalPosition<f64>> {
+        self.cursor
+    }
+
+    /// 本帧光标在窗口内移动的物理像素
+    pub fn cursor_delta(&self) -> glam::Vec2 {
+        self.cursor_delta
+    }
+
+    /// 本帧的原始鼠标相对移动，不受窗口边界与指针加速影响，适合视角旋转
+    pub fn mouse_motion(&self) -> glam::Vec2 {
+        self.mouse_motion
+    }
+
+    /// 本帧滚轮滚动的格数，向上为正；触控板的像素滚动按约 40 像素一格换算
+    pub fn wheel(&self) -> f32 {
+        self.wheel
+    }
+
+    /// 把按键、鼠标按键、光标与滚轮事件计入状态，失去焦点时松开所有按键
+    pub fn handle(&mut self, event: &InputEvent) {
+        match event {
+            InputEvent::Key(input) => {
+                let PhysicalKey::Code(code) = input.physical_key else {
+                    return;
+                };
+                match input.state {
+                    ElementState::Pressed => {
+                        if self.keys.insert(code) {
+                            self.keys_pressed.insert(code);
+                        }
+                    }
+                    ElementState::Released => {
+                        if self.keys.remove(&code) {
+                            self.keys_released.insert(code);
+                        }
+                    }
+                }
+            }
+            InputEvent::MouseButton(state, button) => match state {
+                ElementState::Pressed => {
+                    if self.buttons.insert(*button) {
+                        self.buttons_pressed.insert(*button);
+                    }
+                }
+                ElementState::Released => {
+                    if self.buttons.remove(button) {
+                        self.buttons_released.insert(*button);
+                    }
+                }
+            },
+            InputEvent::Wheel(delta) => {
+                self.wheel += match delta {
+                    MouseScrollDelta::LineDelta(_, y) => *y,
+                    MouseScrollDelta::PixelDelta(p) => p.y as f32 / 40.0,
+                };
+            }
+            InputEvent::Cursor(position) => {
+                if let Some(last) = self.cursor.replace(*position) {
+                    self.cursor_delta +=
+                        glam::vec2((position.x - last.x) as f32, (position.y - last.y) as f32);
+                }
+            }
+            InputEvent::MouseMotion(dx, dy) => {
+                self.mouse_motion += glam::vec2(*dx as f32, *dy as f32);
+            }
+            InputEvent::CursorLeft => self.cursor_left(),
+            InputEvent::Focused(false) => self.release_all(),
+            _ => {}
+        }
+    }
+
+    /// 光标离开窗口
+    pub fn cursor_left(&mut self) {
+        self.cursor = None;
+    }
+
+    /// 窗口失去焦点时松开所有按键，之后不会再收到它们的松开事件
+    pub fn release_all(&mut self) {
+        self.keys_released.extend(self.keys.drain());
+        self.buttons_released.extend(self.buttons.drain());
+    }
+
+    /// 清除本帧的变化与位移，由 handler 在每帧的第一次 update 之后调用
+    pub fn end_frame(&mut self) {
+        self.keys_pressed.clear();
+        self.keys_released.clear();
+        self.buttons_pressed.clear();
+        self.buttons_released.clear();
+        self.cursor_delta = glam::Vec2::ZERO;
+        self.mouse_motion = glam::Vec2::ZERO;
+        self.wheel = 0.0;
+    }
+}
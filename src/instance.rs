@@ -0,0 +1,131 @@
+use bytemuck::{Pod, Zeroable};
+use wgpu::{Buffer, Device, Queue};
+
+use crate::{jobs, model::RenderVertex};
+
+#[derive(Debug, Clone, Copy)]
+pub struct Instance {
+    pub position: glam::Vec3,
+    pub rotation: glam::Quat,
+    pub scale: glam::Vec3,
+}
+
+impl Default for Instance {
+    fn default() -> Self {
+        Self {
+            position: glam::Vec3::ZERO,
+            rotation: glam::Quat::IDENTITY,
+            scale: glam::Vec3::ONE,
+        }
+    }
+}
+
+impl Instance {
+    pub fn model_matrix(&self) -> glam::Mat4 {
+        glam::Mat4::from_scale_rotation_translation(self.scale, self.rotation, self.position)
+    }
+}
+
+/// 带有上一帧模型矩阵的实例数据，用于在顶点着色器中计算逐物体速度
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct MotionInstanceRaw {
+    model: [[f32; 4]; 4],
+    prev_model: [[f32; 4]; 4],
+}
+
+unsafe impl Zeroable for MotionInstanceRaw {}
+unsafe impl Pod for MotionInstanceRaw {}
+
+impl RenderVertex for MotionInstanceRaw {
+    /// 模型矩阵占用 0~3 号位置，上一帧模型矩阵占用 8~11 号位置，
+    /// 与使用 4~6 号位置的模型顶点不冲突
+    fn buffer_layout_desc<'a>() -> wgpu::VertexBufferLayout<'a> {
+        const ATTRIBUTES: [wgpu::VertexAttribute; 8] = wgpu::vertex_attr_array![
+            0 => Float32x4,
+            1 => Float32x4,
+            2 => Float32x4,
+            3 => Float32x4,
+            8 => Float32x4,
+            9 => Float32x4,
+            10 => Float32x4,
+            11 => Float32x4,
+        ];
+        wgpu::VertexBufferLayout {
+            array_stride: std::mem::size_of::<MotionInstanceRaw>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Instance,
+            attributes: &ATTRIBUTES,
+        }
+    }
+}
+
+/// 实例缓冲，每次 `update` 时把上一次上传的模型矩阵作为 `prev_model` 一并写入
+pub struct MotionInstanceBuffer {
+    pub instances: Vec<Instance>,
+    prev_models: Vec<glam::Mat4>,
+    buffer: Buffer,
+}
+
+impl MotionInstanceBuffer {
+    pub fn new(device: &Device, instances: Vec<Instance>) -> Self {
+        let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Motion Instance Buffer"),
+            size: (std::mem::size_of::<MotionInstanceRaw>() * instances.len().max(1))
+                as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        Self {
+            prev_models: instances.iter().map(Instance::model_matrix).collect(),
+            instances,
+            buffer,
+        }
+    }
+
+    /// 每帧调用一次；实例数量不能超过创建时的数量
+    pub fn update(&mut self, queue: &Queue) {
+        assert!(
+            self.instances.len() <= self.prev_models.len(),
+            "instance count grew from {} to {}",
+            self.prev_models.len(),
+            self.instances.len()
+        );
+        let models = jobs::global().map(
+            "instance transforms",
+            &self.instances,
+            Instance::model_matrix,
+        );
+        let data = models
+            .iter()
+            .zip(&mut self.prev_models)
+            .map(|(&model, prev)| {
+                let raw = MotionInstanceRaw {
+                    model: model.to_cols_array_2d(),
+                    prev_model: prev.to_cols_array_2d(),
+                };
+                *prev = model;
+                raw
+            })
+            .collect::<Vec<_>>();
+        queue.write_buffer(&self.buffer, 0, bytemuck::cast_slice(&data));
+    }
+
+    /// 实例被传送（而不是连续运动）后调用，避免产生错误的速度
+    pub fn reset_motion(&mut self) {
+        for (instance, prev) in self.instances.iter().zip(&mut self.prev_models) {
+            *prev = instance.model_matrix();
+        }
+    }
+
+    pub fn buffer(&self) -> &Buffer {
+        &self.buffer
+    }
+
+    pub fn len(&self) -> u32 {
+        self.instances.len() as u32
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.instances.is_empty()
+    }
+}
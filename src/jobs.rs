@@ -0,0 +1,234 @@
+use std::{
+    cmp::Ordering,
+    sync::{Arc, OnceLock},
+};
+
+use crate::profiler::CpuProfiler;
+
+/// CPU 端逐帧工作（实例矩阵转换、剔除、排序）的并行执行器
+///
+/// 开启 `parallel` 特性（默认开启）时在 rayon 线程池上执行，否则在调用线程上顺序执行，
+/// 两种情况下结果与顺序执行完全相同。元素少于 [`JobSystem::with_min_parallel_len`]
+/// 时直接顺序执行，避免小任务的调度开销。设置了 [`CpuProfiler`] 时每个任务记录一段耗时。
+///
+/// 框架内部使用 [`global`] 返回的实例，应用可以在启动时用 [`set_global`] 替换。
+pub struct JobSystem {
+    /// `None` 时使用 rayon 的全局线程池
+    #[cfg(feature = "parallel")]
+    pool: Option<rayon::ThreadPool>,
+    profiler: Option<Arc<CpuProfiler>>,
+    #[cfg_attr(not(feature = "parallel"), allow(dead_code))]
+    min_parallel_len: usize,
+}
+
+impl Default for JobSystem {
+    fn default() -> Self {
+        Self {
+            #[cfg(feature = "parallel")]
+            pool: None,
+            profiler: None,
+            min_parallel_len: Self::DEFAULT_MIN_PARALLEL_LEN,
+        }
+    }
+}
+
+impl JobSystem {
+    const DEFAULT_MIN_PARALLEL_LEN: usize = 1024;
+
+    /// 使用独立的线程池，`threads` 为 0 时按 CPU 核数决定
+    pub fn new(threads: usize) -> anyhow::Result<Self> {
+        #[cfg(feature = "parallel")]
+        {
+            let pool = rayon::ThreadPoolBuilder::new()
+                .num_threads(threads)
+                .thread_name(|i| format!("wgpu_dance-job-{i}"))
+                .build()?;
+            Ok(Self {
+                pool: Some(pool),
+                ..Self::default()
+            })
+        }
+        #[cfg(not(feature = "parallel"))]
+        {
+            let _ = threads;
+            Ok(Self::default())
+        }
+    }
+
+    pub fn with_profiler(self, profiler: Arc<CpuProfiler>) -> Self {
+        Self {
+            profiler: Some(profiler),
+            ..self
+        }
+    }
+
+    /// 元素数达到 `len` 才并行执行，同时也是每个线程至少处理的元素数
+    pub fn with_min_parallel_len(self, len: usize) -> Self {
+        Self {
+            min_parallel_len: len.max(1),
+            ..self
+        }
+    }
+
+    pub fn profiler(&self) -> Option<&Arc<CpuProfiler>> {
+        self.profiler.as_ref()
+    }
+
+    /// 可用的工作线程数
+    pub fn threads(&self) -> usize {
+        #[cfg(feature = "parallel")]
+        {
+            match &self.pool {
+                Some(pool) => pool.current_num_threads(),
+                None => rayon::current_num_threads(),
+            }
+        }
+        #[cfg(not(feature = "parallel"))]
+        {
+            1
+        }
+    }
+
+    #[cfg(feature = "parallel")]
+    fn is_parallel(&self, len: usize) -> bool {
+        len >= self.min_parallel_len && self.threads() > 1
+    }
+
+    /// 在线程池中执行 `job` 并记录耗时
+    fn run<R: Send>(&self, label: &str, len: usize, job: impl FnOnce() -> R + Send) -> R {
+        let mut scope = self.profiler.as_ref().map(|p| p.scope(label));
+        #[cfg(feature = "parallel")]
+        let (result, threads) = match &self.pool {
+            _ if !self.is_parallel(len) => (job(), 1),
+            Some(pool) => (pool.install(job), pool.current_num_threads()),
+            None => (job(), rayon::current_num_threads()),
+        };
+        #[cfg(not(feature = "parallel"))]
+        let (result, threads) = (job(), 1);
+        if let Some(scope) = &mut scope {
+            scope.set_items(len, threads);
+        }
+        result
+    }
+
+    /// 对每个元素调用 `f`，结果按原顺序排列
+    pub fn map<T, U, F>(&self, label: &str, items: &[T], f: F) -> Vec<U>
+    where
+        T: Sync,
+        U: Send,
+        F: Fn(&T) -> U + Sync + Send,
+    {
+        self.run(label, items.len(), || {
+            #[cfg(feature = "parallel")]
+            if self.is_parallel(items.len()) {
+                use rayon::prelude::*;
+                return items
+                    .par_iter()
+                    .with_min_len(self.min_parallel_len)
+                    .map(f)
+                    .collect();
+            }
+            items.iter().map(f).collect()
+        })
+    }
+
+    pub fn for_each_mut<T, F>(&self, label: &str, items: &mut [T], f: F)
+    where
+        T: Send,
+        F: Fn(&mut T) + Sync + Send,
+    {
+        let len = items.len();
+        self.run(label, len, || {
+            #[cfg(feature = "parallel")]
+            if self.is_parallel(len) {
+                use rayon::prelude::*;
+                items
+                    .par_iter_mut()
+                    .with_min_len(self.min_parallel_len)
+                    .for_each(f);
+                return;
+            }
+            items.iter_mut().for_each(f)
+        })
+    }
+
+    /// 满足 `predicate` 的元素下标，按升序排列，常用于剔除
+    pub fn filter_indices<T, F>(&self, label: &str, items: &[T], predicate: F) -> Vec<u32>
+    where
+        T: Sync,
+        F: Fn(&T) -> bool + Sync + Send,
+    {
+        self.run(label, items.len(), || {
+            #[cfg(feature = "parallel")]
+            if self.is_parallel(items.len()) {
+                use rayon::prelude::*;
+                return items
+                    .par_iter()
+                    .with_min_len(self.min_parallel_len)
+                    .enumerate()
+                    .filter(|(_, item)| predicate(item))
+                    .map(|(i, _)| i as u32)
+                    .collect();
+            }
+            items
+                .iter()
+                .enumerate()
+                .filter(|(_, item)| predicate(item))
+                .map(|(i, _)| i as u32)
+                .collect()
+        })
+    }
+
+    /// 稳定排序，相等的元素保持原有顺序
+    pub fn sort_by<T, F>(&self, label: &str, items: &mut [T], compare: F)
+    where
+        T: Send,
+        F: Fn(&T, &T) -> Ordering + Sync + Send,
+    {
+        let len = items.len();
+        self.run(label, len, || {
+            #[cfg(feature = "parallel")]
+            if self.is_parallel(len) {
+                use rayon::prelude::*;
+                items.par_sort_by(compare);
+                return;
+            }
+            items.sort_by(compare)
+        })
+    }
+
+    /// 同时执行两个互不相关的任务
+    pub fn join<A, B, RA, RB>(&self, a: A, b: B) -> (RA, RB)
+    where
+        A: FnOnce() -> RA + Send,
+        B: FnOnce() -> RB + Send,
+        RA: Send,
+        RB: Send,
+    {
+        #[cfg(feature = "parallel")]
+        {
+            match &self.pool {
+                Some(pool) => pool.join(a, b),
+                None => rayon::join(a, b),
+            }
+        }
+        #[cfg(not(feature = "parallel"))]
+        {
+            (a(), b())
+        }
+    }
+}
+
+static GLOBAL: OnceLock<JobSystem> = OnceLock::new();
+
+/// 框架内部使用的执行器，第一次调用时未设置过则使用默认配置
+pub fn global() -> &'static JobSystem {
+    GLOBAL.get_or_init(JobSystem::default)
+}
+
+/// 替换框架使用的执行器，必须在第一次调用 [`global`] 之前
+pub fn set_global(jobs: JobSystem) -> anyhow::Result<()> {
+    GLOBAL
+        .set(jobs)
+        .map_err(|_| anyhow::anyhow!("global job system is already initialized"))
+}
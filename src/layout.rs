@@ -0,0 +1,42 @@
+use std::{collections::HashMap, sync::Mutex};
+
+use wgpu::{BindGroupLayout, BindGroupLayoutEntry, Device};
+
+/// 按条目缓存 bind group layout，相同描述的 layout 只会创建一次
+#[derive(Debug, Default)]
+pub struct LayoutCache {
+    layouts: Mutex<HashMap<Vec<BindGroupLayoutEntry>, BindGroupLayout>>,
+}
+
+impl LayoutCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn get_or_create(
+        &self,
+        device: &Device,
+        label: &str,
+        entries: &[BindGroupLayoutEntry],
+    ) -> BindGroupLayout {
+        self.layouts
+            .lock()
+            .unwrap()
+            .entry(entries.to_vec())
+            .or_insert_with(|| {
+                device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                    label: Some(label),
+                    entries,
+                })
+            })
+            .clone()
+    }
+
+    pub fn len(&self) -> usize {
+        self.layouts.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
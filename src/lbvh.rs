@@ -0,0 +1,411 @@
+use anyhow::ensure;
+use bytemuck::{Pod, Zeroable};
+use wgpu::{util::DeviceExt, BindGroup, Buffer, Device};
+
+use crate::shader::ShaderLibrary;
+
+const WORKGROUP_SIZE: u32 = 256;
+/// 基数排序每轮处理的位数，所有轮次覆盖完整的 32 位键
+const RADIX_BITS: u32 = 4;
+const RADIX_PASSES: u32 = u32::BITS / RADIX_BITS;
+const RADIX: u32 = 1 << RADIX_BITS;
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+struct LbvhParams {
+    triangle_count: u32,
+    vertex_stride: u32,
+    position_offset: u32,
+    _padding: u32,
+}
+
+unsafe impl Zeroable for LbvhParams {}
+unsafe impl Pod for LbvhParams {}
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+struct RadixSortPass {
+    shift: u32,
+    count: u32,
+    group_count: u32,
+    _padding: u32,
+}
+
+unsafe impl Zeroable for RadixSortPass {}
+unsafe impl Pod for RadixSortPass {}
+
+/// 按顺序绑定到 0, 1, 2, ...
+fn entries<'a>(buffers: &[&'a Buffer]) -> Vec<wgpu::BindGroupEntry<'a>> {
+    buffers
+        .iter()
+        .enumerate()
+        .map(|(binding, buffer)| wgpu::BindGroupEntry {
+            binding: binding as u32,
+            resource: buffer.as_entire_binding(),
+        })
+        .collect()
+}
+
+/// [`GpuBvh::nodes`] 中的一个节点，与 `lbvh.wgsl` 中的 `LbvhNode` 布局相同
+///
+/// 0 号为根节点；`n` 个三角形时前 `n - 1` 个为内部节点，之后的 `n` 个为叶子。
+#[repr(C)]
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct GpuBvhNode {
+    pub min: [f32; 3],
+    /// 内部节点为左子节点下标，叶子为三角形下标
+    pub left: u32,
+    pub max: [f32; 3],
+    /// 内部节点为右子节点下标，叶子为 [`GpuBvhNode::LEAF`]
+    pub right: u32,
+}
+
+unsafe impl Zeroable for GpuBvhNode {}
+unsafe impl Pod for GpuBvhNode {}
+
+impl GpuBvhNode {
+    pub const LEAF: u32 = u32::MAX;
+
+    pub fn is_leaf(&self) -> bool {
+        self.right == Self::LEAF
+    }
+}
+
+/// 构建 [`GpuBvh`] 的三角形来源，两个缓冲都需要带有 `STORAGE` 用途
+#[derive(Debug, Copy, Clone)]
+pub struct GpuBvhGeometry<'a> {
+    /// 顶点位置为连续的三个 f32，可以是交错排列的顶点缓冲
+    pub vertices: &'a Buffer,
+    /// 顶点步长与位置在顶点中的偏移，以字节为单位，都须为 4 的倍数
+    pub vertex_stride: u64,
+    pub position_offset: u64,
+    /// 每三个 u32 为一个三角形
+    pub indices: &'a Buffer,
+    pub triangle_count: u32,
+}
+
+/// 在计算着色器中构建的线性 BVH（LBVH），每帧重建以支持顶点在 GPU 上变化的动态场景
+///
+/// 构建分为几个阶段：求所有三角形质心的包围盒，按质心计算 30 位 Morton 码，用每轮 4 位的
+/// 基数排序把三角形按 Morton 码排好，按 Karras 的方法由相邻键的公共前缀并行生成层次，
+/// 最后每个叶子向上原子地合并包围盒。结果在 [`GpuBvh::nodes`] 中，布局见 [`GpuBvhNode`]，
+/// 可以直接绑定给做光线遍历的着色器。树的质量不如 CPU 上的 [`Bvh`](crate::bvh::Bvh)，
+/// 换来的是不需要读回 CPU。
+pub struct GpuBvh {
+    triangle_count: u32,
+    nodes: Buffer,
+    bind_group: BindGroup,
+    sort_bind_groups: Vec<BindGroup>,
+    reset_pipeline: wgpu::ComputePipeline,
+    bounds_pipeline: wgpu::ComputePipeline,
+    morton_pipeline: wgpu::ComputePipeline,
+    histogram_pipeline: wgpu::ComputePipeline,
+    scan_pipeline: wgpu::ComputePipeline,
+    scatter_pipeline: wgpu::ComputePipeline,
+    hierarchy_pipeline: wgpu::ComputePipeline,
+    refit_pipeline: wgpu::ComputePipeline,
+    finalize_pipeline: wgpu::ComputePipeline,
+}
+
+impl GpuBvh {
+    pub fn new(device: &Device, geometry: GpuBvhGeometry) -> anyhow::Result<Self> {
+        let GpuBvhGeometry {
+            vertices,
+            vertex_stride,
+            position_offset,
+            indices,
+            triangle_count,
+        } = geometry;
+        ensure!(triangle_count > 0, "GPU BVH needs at least 1 triangle");
+        ensure!(
+            vertex_stride % 4 == 0 && position_offset % 4 == 0,
+            "vertex stride ({vertex_stride}) and position offset ({position_offset}) \
+             must be multiples of 4 bytes"
+        );
+        ensure!(
+            position_offset + 12 <= vertex_stride,
+            "position at offset {position_offset} does not fit in a {vertex_stride}-byte vertex"
+        );
+        ensure!(
+            indices.size() >= triangle_count as u64 * 12,
+            "index buffer holds fewer than {triangle_count} triangles"
+        );
+        let group_count = triangle_count.div_ceil(WORKGROUP_SIZE);
+        // 节点数约为三角形数的两倍
+        ensure!(
+            (2 * triangle_count as u64 - 1).div_ceil(WORKGROUP_SIZE as u64)
+                <= device.limits().max_compute_workgroups_per_dimension as u64,
+            "too many triangles for a GPU BVH: {triangle_count}"
+        );
+
+        let node_count = 2 * triangle_count as u64 - 1;
+        let storage_buffer = |label, size| {
+            device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some(label),
+                size,
+                usage: wgpu::BufferUsages::STORAGE,
+                mapped_at_creation: false,
+            })
+        };
+        let key_size = std::mem::size_of::<u32>() as u64 * triangle_count as u64;
+        let keys = [
+            storage_buffer("LBVH Keys Buffer", key_size),
+            storage_buffer("LBVH Keys Scratch Buffer", key_size),
+        ];
+        let values = [
+            storage_buffer("LBVH Values Buffer", key_size),
+            storage_buffer("LBVH Values Scratch Buffer", key_size),
+        ];
+        let histograms = storage_buffer(
+            "LBVH Histogram Buffer",
+            std::mem::size_of::<u32>() as u64 * (RADIX * group_count) as u64,
+        );
+        let scene_bounds = storage_buffer("LBVH Scene Bounds Buffer", 6 * 4);
+        let parents = storage_buffer("LBVH Parent Buffer", 4 * node_count);
+        let node_bounds = storage_buffer("LBVH Node Bounds Buffer", 6 * 4 * node_count);
+        let nodes = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("LBVH Node Buffer"),
+            size: std::mem::size_of::<GpuBvhNode>() as u64 * node_count,
+            usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_SRC,
+            mapped_at_creation: false,
+        });
+        let params = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("LBVH Params Buffer"),
+            contents: bytemuck::cast_slice(&[LbvhParams {
+                triangle_count,
+                vertex_stride: (vertex_stride / 4) as u32,
+                position_offset: (position_offset / 4) as u32,
+                _padding: 0,
+            }]),
+            usage: wgpu::BufferUsages::UNIFORM,
+        });
+
+        let uniform = |binding| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility: wgpu::ShaderStages::COMPUTE,
+            ty: wgpu::BindingType::Buffer {
+                ty: wgpu::BufferBindingType::Uniform,
+                has_dynamic_offset: false,
+                min_binding_size: None,
+            },
+            count: None,
+        };
+        let storage = |binding, read_only| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility: wgpu::ShaderStages::COMPUTE,
+            ty: wgpu::BindingType::Buffer {
+                ty: wgpu::BufferBindingType::Storage { read_only },
+                has_dynamic_offset: false,
+                min_binding_size: None,
+            },
+            count: None,
+        };
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &[
+                uniform(0),
+                storage(1, true),
+                storage(2, true),
+                storage(3, false),
+                storage(4, false),
+                storage(5, false),
+                storage(6, false),
+                storage(7, false),
+                storage(8, false),
+            ],
+            label: Some("lbvh_bind_group_layout"),
+        });
+        let sort_bind_group_layout =
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                entries: &[
+                    uniform(0),
+                    storage(1, true),
+                    storage(2, true),
+                    storage(3, false),
+                    storage(4, false),
+                    storage(5, false),
+                ],
+                label: Some("radix_sort_bind_group_layout"),
+            });
+
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: &bind_group_layout,
+            entries: &entries(&[
+                &params,
+                vertices,
+                indices,
+                &scene_bounds,
+                &keys[0],
+                &values[0],
+                &nodes,
+                &parents,
+                &node_bounds,
+            ]),
+            label: Some("lbvh_bind_group"),
+        });
+        // 轮次数为偶数，排序结果最终回到第一组缓冲
+        let sort_bind_groups = (0..RADIX_PASSES)
+            .map(|pass| {
+                let (src, dst) = ((pass % 2) as usize, ((pass + 1) % 2) as usize);
+                let pass_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                    label: Some("Radix Sort Pass Buffer"),
+                    contents: bytemuck::cast_slice(&[RadixSortPass {
+                        shift: pass * RADIX_BITS,
+                        count: triangle_count,
+                        group_count,
+                        _padding: 0,
+                    }]),
+                    usage: wgpu::BufferUsages::UNIFORM,
+                });
+                device.create_bind_group(&wgpu::BindGroupDescriptor {
+                    layout: &sort_bind_group_layout,
+                    entries: &entries(&[
+                        &pass_buffer,
+                        &keys[src],
+                        &values[src],
+                        &keys[dst],
+                        &values[dst],
+                        &histograms,
+                    ]),
+                    label: Some("radix_sort_bind_group"),
+                })
+            })
+            .collect();
+
+        let library = ShaderLibrary::new();
+        let shader = library
+            .create_shader_module(device, "LBVH Shader", include_str!("../shaders/lbvh.wgsl"))
+            .expect("built-in LBVH shader");
+        let sort_shader = library
+            .create_shader_module(
+                device,
+                "Radix Sort Shader",
+                include_str!("../shaders/radix_sort.wgsl"),
+            )
+            .expect("built-in radix sort shader");
+        let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("LBVH Pipeline Layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+        let sort_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Radix Sort Pipeline Layout"),
+            bind_group_layouts: &[&sort_bind_group_layout],
+            push_constant_ranges: &[],
+        });
+        let compute_pipeline = |label, layout, module, entry_point| {
+            device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+                label: Some(label),
+                layout: Some(layout),
+                module,
+                entry_point: Some(entry_point),
+                compilation_options: Default::default(),
+                cache: None,
+            })
+        };
+
+        Ok(Self {
+            triangle_count,
+            nodes,
+            bind_group,
+            sort_bind_groups,
+            reset_pipeline: compute_pipeline("LBVH Reset Pipeline", &layout, &shader, "cs_reset"),
+            bounds_pipeline: compute_pipeline(
+                "LBVH Bounds Pipeline",
+                &layout,
+                &shader,
+                "cs_bounds",
+            ),
+            morton_pipeline: compute_pipeline(
+                "LBVH Morton Pipeline",
+                &layout,
+                &shader,
+                "cs_morton",
+            ),
+            histogram_pipeline: compute_pipeline(
+                "Radix Sort Histogram Pipeline",
+                &sort_layout,
+                &sort_shader,
+                "cs_histogram",
+            ),
+            scan_pipeline: compute_pipeline(
+                "Radix Sort Scan Pipeline",
+                &sort_layout,
+                &sort_shader,
+                "cs_scan",
+            ),
+            scatter_pipeline: compute_pipeline(
+                "Radix Sort Scatter Pipeline",
+                &sort_layout,
+                &sort_shader,
+                "cs_scatter",
+            ),
+            hierarchy_pipeline: compute_pipeline(
+                "LBVH Hierarchy Pipeline",
+                &layout,
+                &shader,
+                "cs_hierarchy",
+            ),
+            refit_pipeline: compute_pipeline("LBVH Refit Pipeline", &layout, &shader, "cs_refit"),
+            finalize_pipeline: compute_pipeline(
+                "LBVH Finalize Pipeline",
+                &layout,
+                &shader,
+                "cs_finalize",
+            ),
+        })
+    }
+
+    /// 按顶点缓冲的当前内容重建，须在写入顶点的通道之后记录
+    pub fn build(&self, encoder: &mut wgpu::CommandEncoder) {
+        let groups = self.triangle_count.div_ceil(WORKGROUP_SIZE);
+        let node_groups = self.node_count().div_ceil(WORKGROUP_SIZE);
+        let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+            label: Some("LBVH Build Pass"),
+            timestamp_writes: None,
+        });
+        pass.set_bind_group(0, &self.bind_group, &[]);
+        for (pipeline, workgroups) in [
+            (&self.reset_pipeline, 1),
+            (&self.bounds_pipeline, groups),
+            (&self.morton_pipeline, groups),
+        ] {
+            pass.set_pipeline(pipeline);
+            pass.dispatch_workgroups(workgroups, 1, 1);
+        }
+
+        for bind_group in &self.sort_bind_groups {
+            pass.set_bind_group(0, bind_group, &[]);
+            for (pipeline, workgroups) in [
+                (&self.histogram_pipeline, groups),
+                (&self.scan_pipeline, 1),
+                (&self.scatter_pipeline, groups),
+            ] {
+                pass.set_pipeline(pipeline);
+                pass.dispatch_workgroups(workgroups, 1, 1);
+            }
+        }
+
+        pass.set_bind_group(0, &self.bind_group, &[]);
+        for (pipeline, workgroups) in [
+            (&self.hierarchy_pipeline, groups),
+            (&self.refit_pipeline, groups),
+            (&self.finalize_pipeline, node_groups),
+        ] {
+            pass.set_pipeline(pipeline);
+            pass.dispatch_workgroups(workgroups, 1, 1);
+        }
+    }
+
+    /// 按 [`GpuBvhNode`] 排列的节点，带有 `STORAGE` 与 `COPY_SRC` 用途
+    pub fn nodes(&self) -> &Buffer {
+        &self.nodes
+    }
+
+    pub fn node_count(&self) -> u32 {
+        2 * self.triangle_count - 1
+    }
+
+    pub fn triangle_count(&self) -> u32 {
+        self.triangle_count
+    }
+}
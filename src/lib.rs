@@ -1,5 +1,65 @@
+// 让 `#[derive(GpuUniform)]` 生成的 `::wgpu_dance` 路径在本 crate 内也能解析
+extern crate self as wgpu_dance;
+
+pub mod animation;
+pub mod ao;
 pub mod app;
+pub mod background;
+pub mod bvh;
 pub mod camera;
+pub mod camera2d;
+pub mod camera_path;
+pub mod capture;
+pub mod collider;
+pub mod compute;
+pub mod context;
+pub mod debug_draw;
+#[cfg(feature = "egui")]
+pub mod egui_layer;
+pub mod environment;
+pub mod executor;
+pub mod frustum;
+pub mod gizmo;
+pub mod indirect;
+pub mod input;
+pub mod instance;
+pub mod jobs;
+pub mod layout;
+pub mod lbvh;
+pub mod light;
+pub mod loading;
+pub mod lod;
+pub mod meshlet;
 pub mod model;
+pub mod overlay;
+pub mod pacing;
+pub mod particles;
+pub mod phase;
+pub mod pipeline;
+pub mod polyline;
+pub mod post;
+pub mod probe;
+pub mod profiler;
+pub mod ray;
+pub mod reflection;
+pub mod replay;
+pub mod resolution;
 pub mod resource;
+pub mod scatter;
+pub mod scene;
+pub mod settings;
+pub mod shader;
+pub mod shader_toy;
+pub mod shadow_atlas;
+pub mod sky;
+pub mod splat;
+pub mod spline;
+pub mod sprite;
+pub mod stats;
 pub mod texture;
+pub mod thumbnail;
+pub mod uniform;
+pub mod validation;
+pub mod viewport;
+pub mod water;
+pub mod window;
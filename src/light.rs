@@ -0,0 +1,385 @@
+use wgpu::{util::DeviceExt, BindGroup, BindGroupLayout, Buffer, Device, Queue};
+
+use crate::uniform::GpuUniform;
+
+/// 光源强度的单位
+///
+/// 平行光使用照度 [`LightUnit::Lux`]，点光源与聚光灯使用光通量 [`LightUnit::Lumens`]
+/// 或发光强度 [`LightUnit::Candela`]。物理单位的数值通常很大（正午阳光约 100000 lux），
+/// 写入 uniform 时乘以 [`ev100_exposure`] 得到的曝光换算到着色器使用的范围，
+/// 这样同一组灯光在不同场景中的相对亮度保持一致。
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum LightUnit {
+    /// 不换算，直接作为着色器中的强度，也不受曝光影响
+    #[default]
+    Unitless,
+    Lux,
+    Lumens,
+    Candela,
+}
+
+impl LightUnit {
+    /// 把以该单位表示的 `value` 换算为着色器中的强度
+    ///
+    /// 光通量按各向同性发光换算为发光强度（除以 4π），聚光灯的锥角不影响亮度。
+    pub fn to_shader_intensity(self, value: f32, exposure: f32) -> f32 {
+        match self {
+            Self::Unitless => value,
+            Self::Lux | Self::Candela => value * exposure,
+            Self::Lumens => value / (4.0 * std::f32::consts::PI) * exposure,
+        }
+    }
+}
+
+/// 相机 EV100 对应的曝光系数，按最大亮度不饱和的标定，例如晴天户外约为 EV 15
+pub fn ev100_exposure(ev100: f32) -> f32 {
+    1.0 / (1.2 * 2f32.powf(ev100))
+}
+
+/// 点光源与聚光灯随距离的衰减方式，两者都在 `range` 处截断
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum Attenuation {
+    /// 平方反比衰减乘以平滑窗口，在 `range` 处连续地衰减到 0
+    #[default]
+    SmoothWindow,
+    /// 纯粹的平方反比衰减，超过 `range` 直接为 0
+    InverseSquare,
+}
+
+impl Attenuation {
+    /// 与 `wgpu_dance/lighting.wgsl` 中 `light_attenuation` 的编号一致
+    fn shader_mode(self) -> f32 {
+        match self {
+            Self::SmoothWindow => 0.0,
+            Self::InverseSquare => 1.0,
+        }
+    }
+
+    /// 距光源 `distance` 处的衰减，与着色器中的计算一致
+    pub fn factor(self, distance: f32, range: f32) -> f32 {
+        let inverse_square = 1.0 / (distance * distance).max(1e-4);
+        match self {
+            Self::SmoothWindow => {
+                let ratio = (1.0 - (distance / range).powi(4)).clamp(0.0, 1.0);
+                ratio * ratio * inverse_square
+            }
+            Self::InverseSquare if distance < range => inverse_square,
+            Self::InverseSquare => 0.0,
+        }
+    }
+}
+
+/// 阴影的偏移与滤波参数，用于在阴影痤疮（acne）与漏光（peter-panning）之间取舍
+///
+/// 偏移越大越不容易出现痤疮，但物体与其阴影之间越容易脱开；着色器中的用法见
+/// `wgpu_dance/shadows.wgsl`。
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct ShadowSettings {
+    /// 比较时从接收面深度中减去的固定偏移，单位为阴影贴图的 NDC 深度
+    pub constant_bias: f32,
+    /// 随表面相对光线倾斜程度增大的偏移，乘以入射角的正切
+    pub slope_bias: f32,
+    /// 采样前沿法线把接收点外推的世界空间距离，掠射角时越大
+    pub normal_offset: f32,
+    /// PCF 滤波半径（纹素），0 为单次比较
+    pub pcf_radius: f32,
+    /// 相邻级联之间混合过渡区的宽度，占本级联范围的比例
+    pub cascade_blend: f32,
+}
+
+impl Default for ShadowSettings {
+    fn default() -> Self {
+        Self {
+            constant_bias: 0.0005,
+            slope_bias: 0.002,
+            normal_offset: 0.02,
+            pcf_radius: 1.0,
+            cascade_blend: 0.1,
+        }
+    }
+}
+
+impl ShadowSettings {
+    /// x: 固定偏移，y: 斜率偏移，z: 法线外推距离，w: PCF 半径
+    fn bias_params(&self) -> [f32; 4] {
+        [
+            self.constant_bias.max(0.0),
+            self.slope_bias.max(0.0),
+            self.normal_offset.max(0.0),
+            self.pcf_radius.max(0.0),
+        ]
+    }
+
+    /// x: 级联混合宽度
+    fn cascade_params(&self) -> [f32; 4] {
+        [self.cascade_blend.clamp(0.0, 1.0), 0.0, 0.0, 0.0]
+    }
+}
+
+/// 平行光，`direction` 指向光源
+#[derive(Debug, Copy, Clone)]
+pub struct DirectionalLight {
+    pub direction: glam::Vec3,
+    pub color: glam::Vec3,
+    pub intensity: f32,
+    pub unit: LightUnit,
+    pub shadow: ShadowSettings,
+}
+
+impl Default for DirectionalLight {
+    fn default() -> Self {
+        Self {
+            direction: glam::vec3(0.3, 1.0, 0.5).normalize(),
+            color: glam::Vec3::ONE,
+            intensity: 1.0,
+            unit: LightUnit::Unitless,
+            shadow: ShadowSettings::default(),
+        }
+    }
+}
+
+impl DirectionalLight {
+    pub fn shader_intensity(&self, exposure: f32) -> f32 {
+        self.unit.to_shader_intensity(self.intensity, exposure)
+    }
+
+    /// 覆盖以 `center` 为球心、`radius` 为半径的球体的正交阴影投影
+    pub fn shadow_view_proj(&self, center: glam::Vec3, radius: f32) -> glam::Mat4 {
+        let dir = self.direction.normalize_or(glam::Vec3::Y);
+        let up = if dir.y.abs() > 0.99 {
+            glam::Vec3::Z
+        } else {
+            glam::Vec3::Y
+        };
+        let view = glam::Mat4::look_at_rh(center + dir * radius * 2.0, center, up);
+        let proj =
+            glam::Mat4::orthographic_rh(-radius, radius, -radius, radius, radius, radius * 3.0);
+        proj * view
+    }
+}
+
+/// 点光源，光照在 `range` 处衰减到 0
+#[derive(Debug, Copy, Clone)]
+pub struct PointLight {
+    pub position: glam::Vec3,
+    pub color: glam::Vec3,
+    pub intensity: f32,
+    pub unit: LightUnit,
+    pub range: f32,
+    pub attenuation: Attenuation,
+}
+
+impl Default for PointLight {
+    fn default() -> Self {
+        Self {
+            position: glam::Vec3::ZERO,
+            color: glam::Vec3::ONE,
+            intensity: 1.0,
+            unit: LightUnit::Unitless,
+            range: 10.0,
+            attenuation: Attenuation::SmoothWindow,
+        }
+    }
+}
+
+impl PointLight {
+    pub fn shader_intensity(&self, exposure: f32) -> f32 {
+        self.unit.to_shader_intensity(self.intensity, exposure)
+    }
+
+    /// 立方体阴影六个面的投影，顺序为 +X、-X、+Y、-Y、+Z、-Z，与 `point_shadow_face` 一致
+    pub fn shadow_view_projs(&self, znear: f32) -> [glam::Mat4; 6] {
+        let proj = glam::Mat4::perspective_rh(
+            std::f32::consts::FRAC_PI_2,
+            1.0,
+            znear,
+            self.range.max(znear * 2.0),
+        );
+        [
+            (glam::Vec3::X, glam::Vec3::NEG_Y),
+            (glam::Vec3::NEG_X, glam::Vec3::NEG_Y),
+            (glam::Vec3::Y, glam::Vec3::Z),
+            (glam::Vec3::NEG_Y, glam::Vec3::NEG_Z),
+            (glam::Vec3::Z, glam::Vec3::NEG_Y),
+            (glam::Vec3::NEG_Z, glam::Vec3::NEG_Y),
+        ]
+        .map(|(dir, up)| proj * glam::Mat4::look_to_rh(self.position, dir, up))
+    }
+}
+
+/// 聚光灯，`direction` 为光照射的方向，角度为相对中轴的半角（弧度）
+///
+/// 在 `inner_angle` 内为全亮度，到 `outer_angle` 时衰减到 0。
+#[derive(Debug, Copy, Clone)]
+pub struct SpotLight {
+    pub position: glam::Vec3,
+    pub direction: glam::Vec3,
+    pub color: glam::Vec3,
+    pub intensity: f32,
+    pub unit: LightUnit,
+    pub range: f32,
+    pub attenuation: Attenuation,
+    pub inner_angle: f32,
+    pub outer_angle: f32,
+    pub shadow: ShadowSettings,
+}
+
+impl Default for SpotLight {
+    fn default() -> Self {
+        Self {
+            position: glam::Vec3::ZERO,
+            direction: glam::Vec3::NEG_Y,
+            color: glam::Vec3::ONE,
+            intensity: 1.0,
+            unit: LightUnit::Unitless,
+            range: 10.0,
+            attenuation: Attenuation::SmoothWindow,
+            inner_angle: 20f32.to_radians(),
+            outer_angle: 30f32.to_radians(),
+            shadow: ShadowSettings::default(),
+        }
+    }
+}
+
+impl SpotLight {
+    pub fn shader_intensity(&self, exposure: f32) -> f32 {
+        self.unit.to_shader_intensity(self.intensity, exposure)
+    }
+
+    /// 覆盖整个外锥的透视阴影投影
+    pub fn shadow_view_proj(&self, znear: f32) -> glam::Mat4 {
+        let dir = self.direction.normalize_or(glam::Vec3::NEG_Y);
+        let up = if dir.y.abs() > 0.99 {
+            glam::Vec3::Z
+        } else {
+            glam::Vec3::Y
+        };
+        let view = glam::Mat4::look_to_rh(self.position, dir, up);
+        let fovy = (self.outer_angle * 2.0).clamp(1e-3, std::f32::consts::PI - 1e-3);
+        let proj = glam::Mat4::perspective_rh(fovy, 1.0, znear, self.range.max(znear * 2.0));
+        proj * view
+    }
+}
+
+/// 与 `shaders/lighting.wgsl` 中的 `DirectionalLight` 保持一致
+#[repr(C)]
+#[derive(Debug, Copy, Clone, GpuUniform)]
+pub struct DirectionalLightUniform {
+    direction: [f32; 4],
+    color: [f32; 4],
+    shadow_bias: [f32; 4],
+    shadow_cascade: [f32; 4],
+}
+
+impl DirectionalLightUniform {
+    pub fn new(light: &DirectionalLight) -> Self {
+        let mut uniform = Self {
+            direction: [0.0; 4],
+            color: [0.0; 4],
+            shadow_bias: [0.0; 4],
+            shadow_cascade: [0.0; 4],
+        };
+        uniform.update(light);
+        uniform
+    }
+
+    pub fn update(&mut self, light: &DirectionalLight) {
+        self.update_with_exposure(light, 1.0);
+    }
+
+    /// 物理单位的强度乘以 `exposure` 后写入
+    pub fn update_with_exposure(&mut self, light: &DirectionalLight, exposure: f32) {
+        self.direction = light
+            .direction
+            .normalize()
+            .extend(light.shader_intensity(exposure))
+            .to_array();
+        self.color = light.color.extend(1.0).to_array();
+        self.shadow_bias = light.shadow.bias_params();
+        self.shadow_cascade = light.shadow.cascade_params();
+    }
+}
+
+/// 与 `shaders/lighting.wgsl` 中的 `PointLight` 保持一致
+#[repr(C)]
+#[derive(Debug, Copy, Clone, GpuUniform)]
+pub struct PointLightUniform {
+    position: [f32; 4],
+    color: [f32; 4],
+    attenuation: [f32; 4],
+}
+
+impl PointLightUniform {
+    pub fn new(light: &PointLight, exposure: f32) -> Self {
+        Self {
+            position: light.position.extend(light.range).to_array(),
+            color: light
+                .color
+                .extend(light.shader_intensity(exposure))
+                .to_array(),
+            attenuation: [light.attenuation.shader_mode(), 0.0, 0.0, 0.0],
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct DirectionalLightBundle {
+    pub light: DirectionalLight,
+    /// 物理单位的强度写入 uniform 时乘以的曝光，见 [`ev100_exposure`]
+    pub exposure: f32,
+    pub uniform: DirectionalLightUniform,
+    pub buffer: Buffer,
+    pub bind_group_layout: BindGroupLayout,
+    pub bind_group: BindGroup,
+}
+
+impl DirectionalLightBundle {
+    /// `bind_group_layout` 唯一的条目
+    pub fn layout_entry() -> wgpu::BindGroupLayoutEntry {
+        wgpu::BindGroupLayoutEntry {
+            binding: 0,
+            visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
+            ty: wgpu::BindingType::Buffer {
+                ty: wgpu::BufferBindingType::Uniform,
+                has_dynamic_offset: false,
+                min_binding_size: None,
+            },
+            count: None,
+        }
+    }
+
+    pub fn new(light: DirectionalLight, device: &Device) -> Self {
+        let uniform = DirectionalLightUniform::new(&light);
+        let buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Directional Light Buffer"),
+            contents: bytemuck::cast_slice(&[uniform]),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &[Self::layout_entry()],
+            label: Some("directional_light_bind_group_layout"),
+        });
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: &bind_group_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: buffer.as_entire_binding(),
+            }],
+            label: Some("directional_light_bind_group"),
+        });
+        Self {
+            light,
+            exposure: 1.0,
+            uniform,
+            buffer,
+            bind_group_layout,
+            bind_group,
+        }
+    }
+
+    pub fn update(&mut self, queue: &Queue) {
+        self.uniform
+            .update_with_exposure(&self.light, self.exposure);
+        self.uniform.write_to(queue, &self.buffer);
+    }
+}
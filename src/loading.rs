@@ -0,0 +1,281 @@
+use std::{
+    sync::{Arc, Mutex},
+    task::{Poll, Waker},
+};
+
+use wgpu::util::DeviceExt;
+use winit::dpi::PhysicalSize;
+
+use crate::{context::GpuContext, shader::ShaderLibrary, uniform::GpuUniform};
+
+/// 与 `loading.wgsl` 中的 `LoadingUniform` 保持一致
+#[repr(C)]
+#[derive(Debug, Copy, Clone, Default, GpuUniform)]
+pub struct LoadingUniform {
+    /// xy: 画面像素大小
+    resolution: [f32; 4],
+    /// x: 进度，小于 0 表示未知, y: 秒
+    params: [f32; 4],
+}
+
+/// 加载期间由 [`WindowAppHandler`](crate::app::WindowAppHandler) 绘制的进度条
+struct LoadingScreen {
+    gpu: GpuContext,
+    pipeline: wgpu::RenderPipeline,
+    uniform_buffer: wgpu::Buffer,
+    bind_group: wgpu::BindGroup,
+}
+
+impl LoadingScreen {
+    fn new(gpu: GpuContext) -> anyhow::Result<Self> {
+        let uniform_buffer = gpu
+            .device
+            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("Loading Uniform Buffer"),
+                contents: bytemuck::bytes_of(&LoadingUniform::default()),
+                usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            });
+        let bind_group_layout =
+            gpu.device
+                .create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                    label: Some("Loading Bind Group Layout"),
+                    entries: &[wgpu::BindGroupLayoutEntry {
+                        binding: 0,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Buffer {
+                            ty: wgpu::BufferBindingType::Uniform,
+                            has_dynamic_offset: false,
+                            min_binding_size: None,
+                        },
+                        count: None,
+                    }],
+                });
+        let bind_group = gpu.device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Loading Bind Group"),
+            layout: &bind_group_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: uniform_buffer.as_entire_binding(),
+            }],
+        });
+        let pipeline_layout = gpu
+            .device
+            .create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some("Loading Pipeline Layout"),
+                bind_group_layouts: &[&bind_group_layout],
+                push_constant_ranges: &[],
+            });
+        let shader = ShaderLibrary::new().create_shader_module(
+            &gpu.device,
+            "Loading Shader",
+            include_str!("../shaders/loading.wgsl"),
+        )?;
+        let pipeline = gpu
+            .device
+            .create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                label: Some("Loading Pipeline"),
+                layout: Some(&pipeline_layout),
+                vertex: wgpu::VertexState {
+                    module: &shader,
+                    compilation_options: Default::default(),
+                    entry_point: Some("vs_main"),
+                    buffers: &[],
+                },
+                fragment: Some(wgpu::FragmentState {
+                    module: &shader,
+                    compilation_options: Default::default(),
+                    entry_point: Some("fs_main"),
+                    targets: &[Some(wgpu::ColorTargetState {
+                        format: gpu.gamma_format(),
+                        blend: None,
+                        write_mask: wgpu::ColorWrites::ALL,
+                    })],
+                }),
+                primitive: wgpu::PrimitiveState::default(),
+                depth_stencil: None,
+                multisample: wgpu::MultisampleState::default(),
+                multiview: None,
+                cache: None,
+            });
+
+        Ok(Self {
+            gpu,
+            pipeline,
+            uniform_buffer,
+            bind_group,
+        })
+    }
+
+    fn render(&mut self, progress: Option<f32>, seconds: f32) -> Result<(), wgpu::SurfaceError> {
+        self.gpu.resize_if_needed();
+        let size = self.gpu.size();
+        LoadingUniform {
+            resolution: [size.width as f32, size.height as f32, 0.0, 0.0],
+            params: [progress.unwrap_or(-1.0), seconds, 0.0, 0.0],
+        }
+        .write_to(&self.gpu.queue, &self.uniform_buffer);
+
+        let mut frame = self.gpu.begin_frame()?;
+        let mut render_pass = frame.render_pass("Loading Pass").gamma().begin();
+        render_pass.set_pipeline(&self.pipeline);
+        render_pass.set_bind_group(0, &self.bind_group, &[]);
+        render_pass.draw(0..3, 0..1);
+        drop(render_pass);
+        self.gpu.end_frame(frame);
+        Ok(())
+    }
+}
+
+#[derive(Default)]
+struct LoadingState {
+    /// `None` 表示进度未知
+    progress: Option<f32>,
+    message: String,
+    /// 消息变化后还没有显示到窗口标题上
+    message_changed: bool,
+    /// 已经绘制的加载画面帧数
+    frames: u64,
+    /// 等待下一帧加载画面的 [`LoadingProgress::step`]
+    waker: Option<Waker>,
+    screen: Option<LoadingScreen>,
+}
+
+/// 报告加载进度的句柄，可以克隆后交给各个资源加载步骤
+#[derive(Clone, Default)]
+pub struct LoadingProgress {
+    state: Arc<Mutex<LoadingState>>,
+}
+
+impl LoadingProgress {
+    /// 已完成的比例，范围为 0 到 1
+    pub fn set(&self, fraction: f32) {
+        self.state.lock().unwrap().progress = Some(fraction.clamp(0.0, 1.0));
+    }
+
+    /// 显示在窗口标题上的当前步骤
+    pub fn set_message(&self, message: impl Into<String>) {
+        let mut state = self.state.lock().unwrap();
+        state.message = message.into();
+        state.message_changed = true;
+    }
+
+    /// 同时设置进度与消息，并等到加载画面绘制了新的一帧
+    ///
+    /// 资源加载通常是同步的文件读取，中间不让出就不会有机会重绘；在两个加载步骤之间调用它，
+    /// 进度条才会动起来。加载画面尚未绘制过时（例如使用阻塞的执行器）立即返回。
+    pub async fn step(&self, fraction: f32, message: impl Into<String>) {
+        self.set(fraction);
+        self.set_message(message);
+        let start = {
+            let state = self.state.lock().unwrap();
+            if state.frames == 0 || state.screen.is_none() {
+                return;
+            }
+            state.frames
+        };
+        futures::future::poll_fn(|cx| {
+            let mut state = self.state.lock().unwrap();
+            if state.frames > start || state.screen.is_none() {
+                Poll::Ready(())
+            } else {
+                state.waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        })
+        .await
+    }
+
+    pub(crate) fn attach(&self, gpu: GpuContext) -> anyhow::Result<()> {
+        self.state.lock().unwrap().screen = Some(LoadingScreen::new(gpu)?);
+        Ok(())
+    }
+
+    pub(crate) fn is_showing(&self) -> bool {
+        self.state.lock().unwrap().screen.is_some()
+    }
+
+    pub(crate) fn resize(&self, size: PhysicalSize<u32>) {
+        if let Some(screen) = self.state.lock().unwrap().screen.as_mut() {
+            screen.gpu.resize(size);
+        }
+    }
+
+    /// 绘制一帧加载画面并唤醒等待中的加载步骤，消息变化时返回新的消息
+    pub(crate) fn render(&self, seconds: f32) -> Option<String> {
+        let mut state = self.state.lock().unwrap();
+        let progress = state.progress;
+        let screen = state.screen.as_mut()?;
+        match screen.render(progress, seconds) {
+            Ok(()) => {}
+            // surface 过期时重新配置，下一帧再画
+            Err(wgpu::SurfaceError::Lost | wgpu::SurfaceError::Outdated) => {
+                screen.gpu.reconfigure();
+            }
+            Err(e) => log::warn!("loading screen: {e}"),
+        }
+        state.frames += 1;
+        if let Some(waker) = state.waker.take() {
+            waker.wake();
+        }
+        std::mem::take(&mut state.message_changed).then(|| state.message.clone())
+    }
+
+    fn take_gpu(&self) -> Option<GpuContext> {
+        let mut state = self.state.lock().unwrap();
+        let gpu = state.screen.take().map(|screen| screen.gpu);
+        if let Some(waker) = state.waker.take() {
+            waker.wake();
+        }
+        gpu
+    }
+}
+
+/// 交给 [`WindowApp::load`](crate::app::WindowApp::load) 的加载上下文
+///
+/// 加载期间窗口的 surface 由加载画面使用，应用可以用 `device` 与 `queue` 创建资源，
+/// 全部加载完成后以 [`Loading::finish`] 取得 [`GpuContext`]，加载画面随之结束。
+pub struct Loading {
+    pub device: wgpu::Device,
+    pub queue: wgpu::Queue,
+    surface_config: wgpu::SurfaceConfiguration,
+    progress: LoadingProgress,
+}
+
+impl Loading {
+    /// 加载画面已经显示时才会创建，因此其中一定有 GPU 上下文
+    pub(crate) fn new(progress: LoadingProgress) -> Option<Self> {
+        let state = progress.state.lock().unwrap();
+        let gpu = &state.screen.as_ref()?.gpu;
+        let (device, queue) = (gpu.device.clone(), gpu.queue.clone());
+        let surface_config = gpu.surface_config.clone();
+        drop(state);
+        Some(Self {
+            device,
+            queue,
+            surface_config,
+            progress,
+        })
+    }
+
+    /// 开始加载时的 surface 配置，用于创建与 surface 格式相关的管线
+    pub fn surface_config(&self) -> &wgpu::SurfaceConfiguration {
+        &self.surface_config
+    }
+
+    pub fn progress(&self) -> LoadingProgress {
+        self.progress.clone()
+    }
+
+    /// 见 [`LoadingProgress::step`]
+    pub async fn step(&self, fraction: f32, message: impl Into<String>) {
+        self.progress.step(fraction, message).await
+    }
+
+    /// 结束加载画面，取得其使用的 [`GpuContext`]；窗口大小在加载期间变化时，
+    /// 新的大小会在下一次 [`GpuContext::resize_if_needed`] 时生效
+    pub fn finish(self) -> GpuContext {
+        self.progress
+            .take_gpu()
+            .expect("loading screen finished twice")
+    }
+}
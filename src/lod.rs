@@ -0,0 +1,298 @@
+use anyhow::ensure;
+use bytemuck::{Pod, Zeroable};
+use wgpu::{util::DeviceExt, BindGroup, Buffer, Device, Queue};
+
+use crate::{
+    camera::Camera,
+    scatter::{frustum_planes, CulledInstanceRaw, ScatterInstance, SourceInstance},
+    shader::ShaderLibrary,
+};
+
+/// 同一批实例最多支持的 LOD 等级数
+pub const MAX_LOD_LEVELS: usize = 4;
+
+/// 一个 LOD 等级在合并后的索引缓冲中的范围，以及使用它的最大距离
+#[derive(Debug, Copy, Clone)]
+pub struct LodLevel {
+    pub first_index: u32,
+    pub index_count: u32,
+    pub base_vertex: i32,
+    pub max_distance: f32,
+}
+
+/// 把各等级的网格合并到同一对顶点/索引缓冲中，`levels` 为 `(顶点, 索引, 最大距离)`，由精细到粗糙排列
+pub fn pack_lod_levels<V: Copy>(
+    levels: &[(&[V], &[u32], f32)],
+) -> (Vec<V>, Vec<u32>, Vec<LodLevel>) {
+    let mut vertices = Vec::new();
+    let mut indices = Vec::new();
+    let mut lod_levels = Vec::new();
+    for (level_vertices, level_indices, max_distance) in levels {
+        lod_levels.push(LodLevel {
+            first_index: indices.len() as u32,
+            index_count: level_indices.len() as u32,
+            base_vertex: vertices.len() as i32,
+            max_distance: *max_distance,
+        });
+        vertices.extend_from_slice(level_vertices);
+        indices.extend_from_slice(level_indices);
+    }
+    (vertices, indices, lod_levels)
+}
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+struct LodUniform {
+    planes: [[f32; 4]; 6],
+    // xyz: 相机位置, w: 淡出范围
+    eye: [f32; 4],
+    distances: [f32; 4],
+    // x: 实例数量, y: 等级数量, z: 每个等级区域可容纳的实例数
+    params: [f32; 4],
+}
+
+unsafe impl Zeroable for LodUniform {}
+unsafe impl Pod for LodUniform {}
+
+/// 在 GPU 上做视锥剔除并按相机距离为每个实例选择 LOD，为每个等级写入一组间接绘制参数
+///
+/// 每个等级的实例在输出缓冲中占用独立的区域，绘制时用 [`LodCuller::instance_slice`]
+/// 绑定对应区域，因此不需要 `INDIRECT_FIRST_INSTANCE` 特性。实例布局见 [`CulledInstanceRaw`]，
+/// 其 `fade` 的 y 分量为所选等级。
+pub struct LodCuller {
+    /// 所有等级距离的缩放系数，大于 1 时更多实例使用精细的等级
+    pub lod_bias: f32,
+    /// 最后一级结束前的淡出范围
+    pub fade_range: f32,
+    levels: Vec<LodLevel>,
+    instance_count: u32,
+    uniform_buffer: Buffer,
+    output_buffer: Buffer,
+    indirect_buffer: Buffer,
+    bind_group: BindGroup,
+    pipeline: wgpu::ComputePipeline,
+}
+
+impl LodCuller {
+    const INDIRECT_ARGS_SIZE: wgpu::BufferAddress =
+        std::mem::size_of::<wgpu::util::DrawIndexedIndirectArgs>() as wgpu::BufferAddress;
+
+    /// `bounding_radius` 为模型在单位缩放下的包围球半径，`levels` 由近到远排列且距离递增
+    pub fn new(
+        device: &Device,
+        instances: &[ScatterInstance],
+        bounding_radius: f32,
+        levels: &[LodLevel],
+    ) -> anyhow::Result<Self> {
+        ensure!(
+            !levels.is_empty() && levels.len() <= MAX_LOD_LEVELS,
+            "LOD level count must be in 1..={MAX_LOD_LEVELS}, got {}",
+            levels.len()
+        );
+        ensure!(
+            levels
+                .windows(2)
+                .all(|pair| pair[0].max_distance < pair[1].max_distance),
+            "LOD level distances must be strictly increasing"
+        );
+
+        let sources = instances
+            .iter()
+            .map(|i| SourceInstance::new(i, bounding_radius))
+            .collect::<Vec<_>>();
+        let source_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("LOD Source Buffer"),
+            contents: bytemuck::cast_slice(&sources),
+            usage: wgpu::BufferUsages::STORAGE,
+        });
+        let output_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("LOD Culled Instance Buffer"),
+            size: (std::mem::size_of::<CulledInstanceRaw>() * instances.len().max(1) * levels.len())
+                as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::VERTEX,
+            mapped_at_creation: false,
+        });
+        let indirect_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("LOD Indirect Buffer"),
+            size: Self::INDIRECT_ARGS_SIZE * MAX_LOD_LEVELS as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::STORAGE
+                | wgpu::BufferUsages::INDIRECT
+                | wgpu::BufferUsages::COPY_DST
+                | wgpu::BufferUsages::COPY_SRC,
+            mapped_at_creation: false,
+        });
+        let uniform_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("LOD Uniform Buffer"),
+            size: std::mem::size_of::<LodUniform>() as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let storage = |binding, read_only| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility: wgpu::ShaderStages::COMPUTE,
+            ty: wgpu::BindingType::Buffer {
+                ty: wgpu::BufferBindingType::Storage { read_only },
+                has_dynamic_offset: false,
+                min_binding_size: None,
+            },
+            count: None,
+        };
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::COMPUTE,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                storage(1, true),
+                storage(2, false),
+                storage(3, false),
+            ],
+            label: Some("lod_cull_bind_group_layout"),
+        });
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: &bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: uniform_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: source_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: output_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 3,
+                    resource: indirect_buffer.as_entire_binding(),
+                },
+            ],
+            label: Some("lod_cull_bind_group"),
+        });
+
+        let shader = ShaderLibrary::new()
+            .create_shader_module(
+                device,
+                "LOD Cull Shader",
+                include_str!("../shaders/lod_cull.wgsl"),
+            )
+            .expect("built-in LOD cull shader");
+        let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("LOD Cull Pipeline Layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+        let pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+            label: Some("LOD Cull Pipeline"),
+            layout: Some(&layout),
+            module: &shader,
+            entry_point: Some("cs_main"),
+            compilation_options: Default::default(),
+            cache: None,
+        });
+
+        Ok(Self {
+            lod_bias: 1.0,
+            fade_range: 10.0,
+            levels: levels.to_vec(),
+            instance_count: instances.len() as u32,
+            uniform_buffer,
+            output_buffer,
+            indirect_buffer,
+            bind_group,
+            pipeline,
+        })
+    }
+
+    /// 写入本帧的剔除参数并重置每个等级的间接绘制参数
+    pub fn update(&self, queue: &Queue, camera: &Camera) {
+        let mut distances = [0.0; 4];
+        for (distance, level) in distances.iter_mut().zip(&self.levels) {
+            *distance = level.max_distance * self.lod_bias;
+        }
+        let uniform = LodUniform {
+            planes: frustum_planes(camera.build_view_projection_matrix()),
+            eye: camera.eye.extend(self.fade_range).to_array(),
+            distances,
+            params: [
+                self.instance_count as f32,
+                self.levels.len() as f32,
+                self.instance_count.max(1) as f32,
+                0.0,
+            ],
+        };
+        queue.write_buffer(&self.uniform_buffer, 0, bytemuck::cast_slice(&[uniform]));
+
+        let args = self
+            .levels
+            .iter()
+            .flat_map(|level| {
+                wgpu::util::DrawIndexedIndirectArgs {
+                    index_count: level.index_count,
+                    instance_count: 0,
+                    first_index: level.first_index,
+                    base_vertex: level.base_vertex,
+                    first_instance: 0,
+                }
+                .as_bytes()
+                .to_vec()
+            })
+            .collect::<Vec<_>>();
+        queue.write_buffer(&self.indirect_buffer, 0, &args);
+    }
+
+    pub fn cull(&self, encoder: &mut wgpu::CommandEncoder) {
+        let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+            label: Some("LOD Cull Pass"),
+            timestamp_writes: None,
+        });
+        pass.set_pipeline(&self.pipeline);
+        pass.set_bind_group(0, &self.bind_group, &[]);
+        pass.dispatch_workgroups(self.instance_count.div_ceil(64), 1, 1);
+    }
+
+    pub fn levels(&self) -> &[LodLevel] {
+        &self.levels
+    }
+
+    /// 第 `level` 级的实例区域
+    pub fn instance_slice(&self, level: usize) -> wgpu::BufferSlice<'_> {
+        let size = (std::mem::size_of::<CulledInstanceRaw>() * self.instance_count.max(1) as usize)
+            as wgpu::BufferAddress;
+        let start = size * level as wgpu::BufferAddress;
+        self.output_buffer.slice(start..start + size)
+    }
+
+    /// 带有 `COPY_SRC` 用途，可以从 [`INSTANCE_COUNT_OFFSET`](crate::indirect::INSTANCE_COUNT_OFFSET)
+    /// 处读回可见的实例数
+    pub fn indirect_buffer(&self) -> &Buffer {
+        &self.indirect_buffer
+    }
+
+    /// 第 `level` 级间接绘制参数在 [`LodCuller::indirect_buffer`] 中的偏移
+    pub fn indirect_offset(&self, level: usize) -> wgpu::BufferAddress {
+        Self::INDIRECT_ARGS_SIZE * level as wgpu::BufferAddress
+    }
+
+    /// 依次绘制每个等级，调用前需要设置好管线、bind group 以及合并后的顶点和索引缓冲，
+    /// 实例缓冲绑定到 `instance_slot`
+    pub fn draw(&self, render_pass: &mut wgpu::RenderPass, instance_slot: u32) {
+        for level in 0..self.levels.len() {
+            render_pass.set_vertex_buffer(instance_slot, self.instance_slice(level));
+            render_pass.draw_indexed_indirect(&self.indirect_buffer, self.indirect_offset(level));
+        }
+    }
+
+    pub fn instance_count(&self) -> u32 {
+        self.instance_count
+    }
+}
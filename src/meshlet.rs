@@ -0,0 +1,415 @@
+use std::collections::VecDeque;
+
+use anyhow::ensure;
+use bytemuck::{Pod, Zeroable};
+use wgpu::{util::DeviceExt, BindGroup, Buffer, Device, Queue};
+
+use crate::{camera::Camera, scatter::frustum_planes, shader::ShaderLibrary, uniform::GpuUniform};
+
+/// 每个 meshlet 最多包含的三角形数，与 `meshlet_cull.wgsl` 的工作组大小一致
+pub const MESHLET_MAX_TRIANGLES: usize = 64;
+/// 每个 meshlet 最多引用的不同顶点数
+pub const MESHLET_MAX_VERTICES: usize = 64;
+
+/// 单次调度在一个维度上最多的工作组数
+const MAX_DISPATCH: u32 = 65535;
+
+/// 一个 meshlet 在重排后的索引缓冲中的范围，以及用于剔除的包围球和法线锥
+#[derive(Debug, Copy, Clone)]
+pub struct Meshlet {
+    pub first_index: u32,
+    pub index_count: u32,
+    pub center: glam::Vec3,
+    pub radius: f32,
+    pub cone_axis: glam::Vec3,
+    /// 从包围球中心看过去，`dot(视线方向, cone_axis)` 超过该值时整个 meshlet 都是背面；
+    /// 大于 1 表示法线过于分散，不做背面剔除
+    pub cone_cutoff: f32,
+}
+
+/// 划分为 meshlet 的网格，`indices` 按 meshlet 重新排列，顶点缓冲保持不变
+#[derive(Debug, Clone)]
+pub struct MeshletMesh {
+    pub indices: Vec<u32>,
+    pub meshlets: Vec<Meshlet>,
+}
+
+impl MeshletMesh {
+    /// 从三角形邻接关系贪心生长出 meshlet，使同一个 meshlet 中的三角形在空间上相邻
+    pub fn build(positions: &[glam::Vec3], indices: &[u32]) -> Self {
+        assert!(indices.len().is_multiple_of(3));
+        let triangle_count = indices.len() / 3;
+
+        // 每个顶点相邻的三角形，按 CSR 方式存放
+        let mut offsets = vec![0usize; positions.len() + 1];
+        for &index in indices {
+            offsets[index as usize + 1] += 1;
+        }
+        for i in 0..positions.len() {
+            offsets[i + 1] += offsets[i];
+        }
+        let mut cursor = offsets.clone();
+        let mut vertex_triangles = vec![0u32; indices.len()];
+        for (i, &index) in indices.iter().enumerate() {
+            vertex_triangles[cursor[index as usize]] = (i / 3) as u32;
+            cursor[index as usize] += 1;
+        }
+
+        let mut assigned = vec![false; triangle_count];
+        // 记录顶点最近一次被哪个 meshlet 引用，避免每个 meshlet 都清空一次集合
+        let mut vertex_owner = vec![u32::MAX; positions.len()];
+        let mut out_indices = Vec::with_capacity(indices.len());
+        let mut meshlets = Vec::new();
+        let mut queue = VecDeque::new();
+
+        for seed in 0..triangle_count {
+            if assigned[seed] {
+                continue;
+            }
+            let id = meshlets.len() as u32;
+            let first_index = out_indices.len();
+            let mut vertex_count = 0;
+            queue.clear();
+            queue.push_back(seed);
+
+            while let Some(triangle) = queue.pop_front() {
+                if assigned[triangle] {
+                    continue;
+                }
+                let corners = &indices[triangle * 3..triangle * 3 + 3];
+                let new_vertices = corners
+                    .iter()
+                    .enumerate()
+                    .filter(|&(i, &v)| vertex_owner[v as usize] != id && !corners[..i].contains(&v))
+                    .count();
+                if vertex_count + new_vertices > MESHLET_MAX_VERTICES {
+                    continue;
+                }
+
+                assigned[triangle] = true;
+                for &v in corners {
+                    vertex_owner[v as usize] = id;
+                }
+                vertex_count += new_vertices;
+                out_indices.extend_from_slice(corners);
+                if out_indices.len() - first_index == MESHLET_MAX_TRIANGLES * 3 {
+                    break;
+                }
+
+                for &v in corners {
+                    let neighbours =
+                        &vertex_triangles[offsets[v as usize]..offsets[v as usize + 1]];
+                    queue.extend(
+                        neighbours
+                            .iter()
+                            .map(|&t| t as usize)
+                            .filter(|&t| !assigned[t]),
+                    );
+                }
+            }
+
+            meshlets.push(meshlet_bounds(
+                positions,
+                &out_indices[first_index..],
+                first_index as u32,
+            ));
+        }
+
+        Self {
+            indices: out_indices,
+            meshlets,
+        }
+    }
+
+    pub fn triangle_count(&self) -> usize {
+        self.indices.len() / 3
+    }
+}
+
+fn meshlet_bounds(positions: &[glam::Vec3], indices: &[u32], first_index: u32) -> Meshlet {
+    let (min, max) = indices.iter().fold(
+        (glam::Vec3::splat(f32::MAX), glam::Vec3::splat(f32::MIN)),
+        |(min, max), &i| {
+            (
+                min.min(positions[i as usize]),
+                max.max(positions[i as usize]),
+            )
+        },
+    );
+    let center = (min + max) * 0.5;
+    let radius = indices
+        .iter()
+        .map(|&i| positions[i as usize].distance(center))
+        .fold(0.0, f32::max);
+
+    let normals = indices
+        .chunks_exact(3)
+        .filter_map(|t| {
+            let [a, b, c] = [0, 1, 2].map(|k| positions[t[k] as usize]);
+            (b - a).cross(c - a).try_normalize()
+        })
+        .collect::<Vec<_>>();
+    let cone_axis = normals
+        .iter()
+        .sum::<glam::Vec3>()
+        .try_normalize()
+        .unwrap_or(glam::Vec3::Z);
+    let min_dot = normals.iter().map(|n| n.dot(cone_axis)).fold(1.0, f32::min);
+    // 法线锥半角超过 90 度时，任何方向都可能看到正面
+    let cone_cutoff = if normals.is_empty() || min_dot <= 0.0 {
+        2.0
+    } else {
+        (1.0 - min_dot * min_dot).sqrt()
+    };
+
+    Meshlet {
+        first_index,
+        index_count: indices.len() as u32,
+        center,
+        radius,
+        cone_axis,
+        cone_cutoff,
+    }
+}
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+struct MeshletRaw {
+    // xyz: 包围球中心, w: 半径
+    bounds: [f32; 4],
+    // xyz: 法线锥轴, w: 剔除阈值
+    cone: [f32; 4],
+    // x: 首个索引, y: 索引数量
+    range: [u32; 4],
+}
+
+unsafe impl Zeroable for MeshletRaw {}
+unsafe impl Pod for MeshletRaw {}
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone, GpuUniform)]
+struct MeshletUniform {
+    planes: [[f32; 4]; 6],
+    // xyz: 相机位置, w: 模型矩阵各轴的最大缩放
+    eye: [f32; 4],
+    model: [[f32; 4]; 4],
+    // x: meshlet 数量, y: 是否视锥剔除, z: 是否背面剔除
+    params: [u32; 4],
+}
+
+/// 在 GPU 上按视锥与法线锥剔除 meshlet，把可见 meshlet 的索引压缩到一个索引缓冲中，
+/// 并写入一组 `draw_indexed_indirect` 参数
+///
+/// 每个 meshlet 由一个工作组处理，不需要 `MULTI_DRAW_INDIRECT` 等特性。
+pub struct MeshletCuller {
+    pub frustum_culling: bool,
+    pub cone_culling: bool,
+    meshlet_count: u32,
+    triangle_count: u32,
+    uniform_buffer: Buffer,
+    index_buffer: Buffer,
+    indirect_buffer: Buffer,
+    bind_group: BindGroup,
+    pipeline: wgpu::ComputePipeline,
+}
+
+impl MeshletCuller {
+    pub fn new(device: &Device, mesh: &MeshletMesh) -> anyhow::Result<Self> {
+        ensure!(!mesh.meshlets.is_empty(), "meshlet mesh is empty");
+
+        let meshlets = mesh
+            .meshlets
+            .iter()
+            .map(|m| MeshletRaw {
+                bounds: m.center.extend(m.radius).to_array(),
+                cone: m.cone_axis.extend(m.cone_cutoff).to_array(),
+                range: [m.first_index, m.index_count, 0, 0],
+            })
+            .collect::<Vec<_>>();
+        let meshlet_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Meshlet Buffer"),
+            contents: bytemuck::cast_slice(&meshlets),
+            usage: wgpu::BufferUsages::STORAGE,
+        });
+        let source_index_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Meshlet Source Index Buffer"),
+            contents: bytemuck::cast_slice(&mesh.indices),
+            usage: wgpu::BufferUsages::STORAGE,
+        });
+        let index_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Meshlet Visible Index Buffer"),
+            size: (std::mem::size_of::<u32>() * mesh.indices.len()) as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::INDEX,
+            mapped_at_creation: false,
+        });
+        let indirect_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Meshlet Indirect Buffer"),
+            size: std::mem::size_of::<wgpu::util::DrawIndexedIndirectArgs>() as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::STORAGE
+                | wgpu::BufferUsages::INDIRECT
+                | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let uniform_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Meshlet Uniform Buffer"),
+            size: std::mem::size_of::<MeshletUniform>() as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let storage = |binding, read_only| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility: wgpu::ShaderStages::COMPUTE,
+            ty: wgpu::BindingType::Buffer {
+                ty: wgpu::BufferBindingType::Storage { read_only },
+                has_dynamic_offset: false,
+                min_binding_size: None,
+            },
+            count: None,
+        };
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::COMPUTE,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                storage(1, true),
+                storage(2, true),
+                storage(3, false),
+                storage(4, false),
+            ],
+            label: Some("meshlet_cull_bind_group_layout"),
+        });
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: &bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: uniform_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: meshlet_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: source_index_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 3,
+                    resource: index_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 4,
+                    resource: indirect_buffer.as_entire_binding(),
+                },
+            ],
+            label: Some("meshlet_cull_bind_group"),
+        });
+
+        let shader = ShaderLibrary::new()
+            .create_shader_module(
+                device,
+                "Meshlet Cull Shader",
+                include_str!("../shaders/meshlet_cull.wgsl"),
+            )
+            .expect("built-in meshlet cull shader");
+        let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Meshlet Cull Pipeline Layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+        let pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+            label: Some("Meshlet Cull Pipeline"),
+            layout: Some(&layout),
+            module: &shader,
+            entry_point: Some("cs_main"),
+            compilation_options: Default::default(),
+            cache: None,
+        });
+
+        Ok(Self {
+            frustum_culling: true,
+            cone_culling: true,
+            meshlet_count: mesh.meshlets.len() as u32,
+            triangle_count: mesh.triangle_count() as u32,
+            uniform_buffer,
+            index_buffer,
+            indirect_buffer,
+            bind_group,
+            pipeline,
+        })
+    }
+
+    /// 写入本帧的剔除参数并清零间接绘制的索引数量，`model` 为网格的模型矩阵
+    pub fn update(&self, queue: &Queue, camera: &Camera, model: glam::Mat4) {
+        let max_scale = [model.x_axis, model.y_axis, model.z_axis]
+            .map(|axis| axis.truncate().length())
+            .into_iter()
+            .fold(0.0, f32::max);
+        MeshletUniform {
+            planes: frustum_planes(camera.build_view_projection_matrix()),
+            eye: camera.eye.extend(max_scale).to_array(),
+            model: model.to_cols_array_2d(),
+            params: [
+                self.meshlet_count,
+                self.frustum_culling as u32,
+                self.cone_culling as u32,
+                0,
+            ],
+        }
+        .write_to(queue, &self.uniform_buffer);
+
+        let args = wgpu::util::DrawIndexedIndirectArgs {
+            index_count: 0,
+            instance_count: 1,
+            first_index: 0,
+            base_vertex: 0,
+            first_instance: 0,
+        };
+        queue.write_buffer(&self.indirect_buffer, 0, args.as_bytes());
+    }
+
+    pub fn cull(&self, encoder: &mut wgpu::CommandEncoder) {
+        let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+            label: Some("Meshlet Cull Pass"),
+            timestamp_writes: None,
+        });
+        pass.set_pipeline(&self.pipeline);
+        pass.set_bind_group(0, &self.bind_group, &[]);
+        pass.dispatch_workgroups(
+            self.meshlet_count.min(MAX_DISPATCH),
+            self.meshlet_count.div_ceil(MAX_DISPATCH),
+            1,
+        );
+    }
+
+    /// 只包含可见 meshlet 的索引缓冲，索引直接指向原顶点缓冲
+    pub fn index_buffer(&self) -> &Buffer {
+        &self.index_buffer
+    }
+
+    pub fn indirect_buffer(&self) -> &Buffer {
+        &self.indirect_buffer
+    }
+
+    /// 绘制可见的 meshlet，调用前需要设置好管线、bind group 以及顶点缓冲
+    pub fn draw(&self, render_pass: &mut wgpu::RenderPass) {
+        render_pass.set_index_buffer(self.index_buffer.slice(..), wgpu::IndexFormat::Uint32);
+        render_pass.draw_indexed_indirect(&self.indirect_buffer, 0);
+    }
+
+    pub fn meshlet_count(&self) -> u32 {
+        self.meshlet_count
+    }
+
+    pub fn triangle_count(&self) -> u32 {
+        self.triangle_count
+    }
+}
@@ -1,20 +1,30 @@
 use std::{
     io::{BufReader, Cursor},
     ops::Range,
+    path::Path,
 };
 
+use anyhow::anyhow;
+
 use bytemuck::{Pod, Zeroable};
 use wgpu::{util::DeviceExt, Buffer, Device};
 
 use crate::{
-    resource::{load_string, load_texture},
+    phase::{AlphaMode, RenderPhase},
+    resource::{load_binary_from, load_string_from, load_texture_from, res_dir},
     texture::Texture,
+    uniform::GpuUniform,
 };
 
 pub trait RenderVertex: Zeroable + Pod {
     fn buffer_layout_desc<'a>() -> wgpu::VertexBufferLayout<'a>;
 }
 
+/// 能取出模型空间位置的顶点，用于计算 [`Model::bounds`]
+pub trait VertexPosition {
+    fn position(&self) -> glam::Vec3;
+}
+
 #[derive(Debug, Clone)]
 pub struct Model<V: RenderVertex> {
     pub vertices: Vec<V>,
@@ -53,12 +63,155 @@ impl<V: RenderVertex> Model<V> {
         self.vertex_buffer.replace(vertex_buffer);
         self.index_buffer.replace(index_buffer);
     }
+
+    /// 模型空间中的轴对齐包围盒，`(最小点, 最大点)`，配合 [`crate::frustum::transform_aabb`]
+    /// 得到实例在世界空间中的包围盒；没有顶点时两者都为原点
+    pub fn bounds(&self) -> (glam::Vec3, glam::Vec3)
+    where
+        V: VertexPosition,
+    {
+        bounding_box(self.vertices.iter().map(V::position))
+    }
+}
+
+/// 材质贴图坐标的变换，与 glTF 的 KHR_texture_transform 相同：先缩放，再旋转，最后平移
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct TextureTransform {
+    pub offset: glam::Vec2,
+    pub scale: glam::Vec2,
+    /// 弧度，逆时针
+    pub rotation: f32,
+}
+
+impl Default for TextureTransform {
+    fn default() -> Self {
+        Self {
+            offset: glam::Vec2::ZERO,
+            scale: glam::Vec2::ONE,
+            rotation: 0.0,
+        }
+    }
+}
+
+impl TextureTransform {
+    /// 只取图集中 `min` 到 `max` 的子区域（uv 坐标）
+    pub fn sub_region(min: glam::Vec2, max: glam::Vec2) -> Self {
+        Self {
+            offset: min,
+            scale: max - min,
+            rotation: 0.0,
+        }
+    }
+
+    pub fn is_identity(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// 作用于齐次 uv `(u, v, 1)` 的矩阵
+    pub fn matrix(&self) -> glam::Mat3 {
+        let (sin, cos) = self.rotation.sin_cos();
+        // 与扩展规范一致的旋转矩阵，按列给出
+        let rotation = glam::Mat3::from_cols(
+            glam::vec3(cos, -sin, 0.0),
+            glam::vec3(sin, cos, 0.0),
+            glam::Vec3::Z,
+        );
+        glam::Mat3::from_translation(self.offset) * rotation * glam::Mat3::from_scale(self.scale)
+    }
+
+    /// 解析 MTL 贴图语句中的 `-o u v` 与 `-s u v` 选项，返回文件名与变换；其余选项被忽略
+    pub fn parse_mtl_map(statement: &str) -> (String, Self) {
+        let mut transform = Self::default();
+        let mut tokens = statement.split_whitespace().peekable();
+        let mut file_name = Vec::new();
+        while let Some(token) = tokens.next() {
+            let target = match token {
+                "-o" => &mut transform.offset,
+                "-s" => &mut transform.scale,
+                "-imfchan" | "-type" => {
+                    tokens.next();
+                    continue;
+                }
+                // 其余选项的参数是数字或 on/off，个数不定
+                t if t.starts_with('-') => {
+                    while tokens
+                        .peek()
+                        .is_some_and(|t| t.parse::<f32>().is_ok() || matches!(*t, "on" | "off"))
+                    {
+                        tokens.next();
+                    }
+                    continue;
+                }
+                t => {
+                    file_name.push(t);
+                    continue;
+                }
+            };
+            let mut values = Vec::new();
+            while values.len() < 3 {
+                match tokens.peek().and_then(|t| t.parse::<f32>().ok()) {
+                    Some(v) => {
+                        values.push(v);
+                        tokens.next();
+                    }
+                    None => break,
+                }
+            }
+            // 第二个分量缺省时与第一个相同，第三个分量（w）对二维贴图没有意义
+            if let Some(&u) = values.first() {
+                *target = glam::vec2(u, values.get(1).copied().unwrap_or(u));
+            }
+        }
+        (file_name.join(" "), transform)
+    }
+}
+
+/// 与 `shaders/texture_transform.wgsl` 中的 `TextureTransform` 保持一致
+#[repr(C)]
+#[derive(Debug, Copy, Clone, GpuUniform)]
+pub struct TextureTransformUniform {
+    /// 矩阵的前两行，`uv' = (dot(row_u.xyz, uv1), dot(row_v.xyz, uv1))`
+    row_u: [f32; 4],
+    row_v: [f32; 4],
+}
+
+impl From<TextureTransform> for TextureTransformUniform {
+    fn from(transform: TextureTransform) -> Self {
+        let m = transform.matrix().transpose();
+        Self {
+            row_u: m.x_axis.extend(0.0).to_array(),
+            row_v: m.y_axis.extend(0.0).to_array(),
+        }
+    }
 }
 
 pub struct Material {
     pub name: String,
     pub diffuse_texture: Texture,
     pub bind_group: wgpu::BindGroup,
+    /// 使用该材质的网格所属的渲染阶段
+    pub phase: RenderPhase,
+    /// 镂空阈值，只在 [`RenderPhase::AlphaTest`] 阶段有意义，其余阶段为 0
+    pub alpha_cutoff: f32,
+    /// 双面材质，对应 glTF 的 `doubleSided`：不剔除背面，着色器中背面的法线需要翻转
+    pub double_sided: bool,
+    /// 自发光辐射度（线性空间），可以大于 1，使 HDR 画面中的自发光超过泛光阈值
+    pub emissive: glam::Vec3,
+    /// 与 `emissive` 相乘的自发光贴图
+    pub emissive_texture: Option<Texture>,
+    /// 所有贴图共用的坐标变换，用于平铺或取图集中的子区域
+    pub texture_transform: TextureTransform,
+}
+
+impl Material {
+    /// 绘制该材质的管线应使用的剔除方式
+    pub fn cull_mode(&self) -> Option<wgpu::Face> {
+        if self.double_sided {
+            None
+        } else {
+            Some(wgpu::Face::Back)
+        }
+    }
 }
 
 pub struct Mesh {
@@ -72,20 +225,159 @@ pub struct Mesh {
 pub struct MeshModel {
     pub meshes: Vec<Mesh>,
     pub materials: Vec<Material>,
+    /// 模型空间中包围所有网格的球，`(球心, 半径)`
+    pub bounding_sphere: (glam::Vec3, f32),
+    /// 模型空间中包围所有网格的轴对齐包围盒，`(最小点, 最大点)`
+    pub bounding_box: (glam::Vec3, glam::Vec3),
 }
 
 pub trait VertexFromMeshIndex {
     fn from_mesh_index(mesh: &tobj::Mesh, index: usize) -> Self;
 }
 
+/// 常用的模型顶点：位置、纹理坐标与法线，分别使用 4、5、6 号位置，
+/// 与示例中的实例数据（0~3 号位置）配合使用
+#[repr(C)]
+#[derive(Copy, Clone, Debug)]
+pub struct ModelVertex {
+    pub position: [f32; 3],
+    pub tex_coords: [f32; 2],
+    pub normal: [f32; 3],
+}
+
+unsafe impl Zeroable for ModelVertex {}
+unsafe impl Pod for ModelVertex {}
+
+impl RenderVertex for ModelVertex {
+    fn buffer_layout_desc<'a>() -> wgpu::VertexBufferLayout<'a> {
+        const ATTRIBUTES: [wgpu::VertexAttribute; 3] = wgpu::vertex_attr_array![
+            4 => Float32x3,
+            5 => Float32x2,
+            6 => Float32x3,
+        ];
+        wgpu::VertexBufferLayout {
+            array_stride: std::mem::size_of::<ModelVertex>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Vertex,
+            attributes: &ATTRIBUTES,
+        }
+    }
+}
+
+impl VertexPosition for ModelVertex {
+    fn position(&self) -> glam::Vec3 {
+        self.position.into()
+    }
+}
+
+impl VertexFromMeshIndex for ModelVertex {
+    fn from_mesh_index(mesh: &tobj::Mesh, i: usize) -> Self {
+        // 缺少纹理坐标或法线的网格用零填充
+        let tex_coords = mesh
+            .texcoords
+            .get(i * 2..i * 2 + 2)
+            .map_or([0.0; 2], |t| [t[0], t[1]]);
+        let normal = mesh
+            .normals
+            .get(i * 3..i * 3 + 3)
+            .map_or([0.0; 3], |n| [n[0], n[1], n[2]]);
+        Self {
+            position: [
+                mesh.positions[i * 3],
+                mesh.positions[i * 3 + 1],
+                mesh.positions[i * 3 + 2],
+            ],
+            tex_coords,
+            normal,
+        }
+    }
+}
+
+/// 包含所有点的轴对齐包围盒，没有点时两者都为原点
+pub(crate) fn bounding_box(points: impl Iterator<Item = glam::Vec3>) -> (glam::Vec3, glam::Vec3) {
+    let (min, max) = points.fold(
+        (glam::Vec3::splat(f32::MAX), glam::Vec3::splat(f32::MIN)),
+        |(min, max), p| (min.min(p), max.max(p)),
+    );
+    if min.x > max.x {
+        return (glam::Vec3::ZERO, glam::Vec3::ZERO);
+    }
+    (min, max)
+}
+
+/// 以包围盒中心为球心、包含所有点的球，没有点时返回半径为 0 的球
+fn bounding_sphere(points: impl Iterator<Item = glam::Vec3> + Clone) -> (glam::Vec3, f32) {
+    let (min, max) = bounding_box(points.clone());
+    let center = (min + max) * 0.5;
+    let radius = points.map(|p| p.distance(center)).fold(0.0, f32::max);
+    (center, radius)
+}
+
+/// 用 MTL 的 `map_d` 贴图替换漫反射贴图的 alpha，尺寸不同时缩放到漫反射贴图的大小
+///
+/// `map_d` 带 alpha 通道时取其 alpha（导出工具常把漫反射贴图本身写作 `map_d`），否则取亮度。
+fn with_dissolve_map(
+    diffuse: &image::DynamicImage,
+    dissolve: &image::DynamicImage,
+) -> image::RgbaImage {
+    let mut rgba = diffuse.to_rgba8();
+    let coverage = if dissolve.color().has_alpha() {
+        let alpha = dissolve.to_rgba8();
+        image::GrayImage::from_fn(alpha.width(), alpha.height(), |x, y| {
+            image::Luma([alpha.get_pixel(x, y)[3]])
+        })
+    } else {
+        dissolve.to_luma8()
+    };
+    let coverage = if coverage.dimensions() == rgba.dimensions() {
+        coverage
+    } else {
+        image::imageops::resize(
+            &coverage,
+            rgba.width(),
+            rgba.height(),
+            image::imageops::FilterType::Triangle,
+        )
+    };
+    for (pixel, alpha) in rgba.pixels_mut().zip(coverage.pixels()) {
+        pixel[3] = alpha[0];
+    }
+    rgba
+}
+
 impl MeshModel {
+    /// 从内置资源目录加载 OBJ 模型
     pub async fn load_model<V: VertexFromMeshIndex + RenderVertex>(
         file_name: &str,
         device: &wgpu::Device,
         queue: &wgpu::Queue,
         layout: &wgpu::BindGroupLayout,
     ) -> anyhow::Result<Self> {
-        let obj_text = load_string(file_name).await?;
+        Self::load_model_in::<V>(&res_dir()?, file_name, device, queue, layout).await
+    }
+
+    /// 从任意路径加载 OBJ 模型，MTL 与贴图相对于模型所在的目录查找
+    pub async fn load_model_from_path<V: VertexFromMeshIndex + RenderVertex>(
+        path: &Path,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        layout: &wgpu::BindGroupLayout,
+    ) -> anyhow::Result<Self> {
+        let dir = path.parent().unwrap_or(Path::new("."));
+        let file_name = path
+            .file_name()
+            .and_then(|f| f.to_str())
+            .ok_or_else(|| anyhow!("invalid model path {}", path.display()))?;
+        Self::load_model_in::<V>(dir, file_name, device, queue, layout).await
+    }
+
+    async fn load_model_in<V: VertexFromMeshIndex + RenderVertex>(
+        dir: &Path,
+        file_name: &str,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        layout: &wgpu::BindGroupLayout,
+    ) -> anyhow::Result<Self> {
+        let obj_text = load_string_from(&dir.join(file_name)).await?;
         let obj_cursor = Cursor::new(obj_text);
         let mut obj_reader = BufReader::new(obj_cursor);
 
@@ -97,7 +389,9 @@ impl MeshModel {
                 ..Default::default()
             },
             |p| async move {
-                let mat_text = load_string(&p).await.unwrap();
+                let mat_text = load_string_from(&dir.join(p))
+                    .await
+                    .map_err(|_| tobj::LoadError::OpenFileFailed)?;
                 tobj::load_mtl_buf(&mut BufReader::new(Cursor::new(mat_text)))
             },
         )
@@ -105,7 +399,71 @@ impl MeshModel {
 
         let mut materials = Vec::new();
         for m in obj_materials? {
-            let diffuse_texture = load_texture(&m.diffuse_texture, device, queue).await?;
+            // MTL 中的 d 小于 1 视为半透明，带 map_d 透明度贴图则按 alpha 测试处理，
+            // map_d 在加载时写入漫反射贴图的 alpha，着色器只需测试漫反射的 alpha；
+            // MTL 没有镂空阈值，可用非标准的 `alpha_cutoff` 语句指定，缺省与 glTF 一样取 0.5
+            let alpha_mode = if m.dissolve < 1.0 {
+                AlphaMode::Blend
+            } else if !m.dissolve_texture.is_empty() {
+                let cutoff = m
+                    .unknown_param
+                    .get("alpha_cutoff")
+                    .and_then(|v| v.trim().parse().ok())
+                    .unwrap_or(0.5);
+                AlphaMode::Mask { cutoff }
+            } else {
+                AlphaMode::Opaque
+            };
+            // 同样是非标准语句，例如 `double_sided 1`
+            let double_sided = m
+                .unknown_param
+                .get("double_sided")
+                .is_some_and(|v| matches!(v.trim(), "1" | "on" | "true"));
+            // tobj 把 Ke 与 map_Ke 放在 unknown_param 中；非标准的 `emissive_strength`
+            // 与 glTF 的 KHR_materials_emissive_strength 一样放大自发光
+            let emissive_strength = m
+                .unknown_param
+                .get("emissive_strength")
+                .and_then(|v| v.trim().parse::<f32>().ok())
+                .unwrap_or(1.0);
+            let emissive = m
+                .unknown_param
+                .get("Ke")
+                .and_then(|v| {
+                    let c = v
+                        .split_whitespace()
+                        .map(str::parse::<f32>)
+                        .collect::<Result<Vec<_>, _>>()
+                        .ok()?;
+                    (c.len() == 3).then(|| glam::Vec3::from_slice(&c))
+                })
+                .unwrap_or(glam::Vec3::ZERO)
+                * emissive_strength;
+            let emissive_texture = match m.unknown_param.get("map_Ke") {
+                Some(statement) => {
+                    let (file_name, _) = TextureTransform::parse_mtl_map(statement);
+                    Some(load_texture_from(&dir.join(file_name), device, queue).await?)
+                }
+                None => None,
+            };
+            // map_Kd 可以带 `-o`、`-s` 选项，作为材质的贴图坐标变换
+            let (diffuse_file, texture_transform) =
+                TextureTransform::parse_mtl_map(&m.diffuse_texture);
+            let diffuse_texture = if m.dissolve_texture.is_empty() {
+                load_texture_from(&dir.join(diffuse_file), device, queue).await?
+            } else {
+                let diffuse_path = dir.join(diffuse_file);
+                let (dissolve_file, _) = TextureTransform::parse_mtl_map(&m.dissolve_texture);
+                let diffuse = image::load_from_memory(&load_binary_from(&diffuse_path).await?)?;
+                let dissolve =
+                    image::load_from_memory(&load_binary_from(&dir.join(dissolve_file)).await?)?;
+                Texture::from_image(
+                    device,
+                    queue,
+                    &with_dissolve_map(&diffuse, &dissolve).into(),
+                    Some(&diffuse_path.to_string_lossy()),
+                )?
+            };
             let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
                 layout,
                 entries: &[
@@ -125,9 +483,22 @@ impl MeshModel {
                 name: m.name,
                 diffuse_texture,
                 bind_group,
+                phase: alpha_mode.phase(),
+                alpha_cutoff: alpha_mode.cutoff(),
+                double_sided,
+                emissive,
+                emissive_texture,
+                texture_transform,
             })
         }
 
+        let positions = models
+            .iter()
+            .flat_map(|m| m.mesh.positions.chunks_exact(3))
+            .map(glam::Vec3::from_slice);
+        let bounding_sphere = bounding_sphere(positions.clone());
+        let bounding_box = bounding_box(positions);
+
         let meshes = models
             .into_iter()
             .map(|m| {
@@ -156,7 +527,12 @@ impl MeshModel {
             })
             .collect::<Vec<_>>();
 
-        Ok(MeshModel { meshes, materials })
+        Ok(MeshModel {
+            meshes,
+            materials,
+            bounding_sphere,
+            bounding_box,
+        })
     }
 }
 
@@ -174,6 +550,14 @@ pub trait DrawModel<'a> {
         instances: Range<u32>,
         camera_bind_group: &'a wgpu::BindGroup,
     );
+    /// 实例数量由 GPU 写入 `indirect_buffer`（`DrawIndexedIndirectArgs` 布局）
+    fn draw_mesh_indirect(
+        &mut self,
+        mesh: &'a Mesh,
+        material: &'a Material,
+        indirect_buffer: &'a wgpu::Buffer,
+        camera_bind_group: &'a wgpu::BindGroup,
+    );
     fn draw_model(&mut self, model: &'a MeshModel, camera_bind_group: &'a wgpu::BindGroup);
     fn draw_model_instanced(
         &mut self,
@@ -183,10 +567,7 @@ pub trait DrawModel<'a> {
     );
 }
 
-impl<'a, 'b> DrawModel<'b> for wgpu::RenderPass<'a>
-where
-    'b: 'a,
-{
+impl<'b> DrawModel<'b> for wgpu::RenderPass<'_> {
     fn draw_mesh(
         &mut self,
         mesh: &'b Mesh,
@@ -209,6 +590,21 @@ where
         self.set_bind_group(1, &material.bind_group, &[]);
         self.draw_indexed(0..mesh.num_elements, 0, instances);
     }
+
+    fn draw_mesh_indirect(
+        &mut self,
+        mesh: &'b Mesh,
+        material: &'b Material,
+        indirect_buffer: &'b wgpu::Buffer,
+        camera_bind_group: &'b wgpu::BindGroup,
+    ) {
+        self.set_vertex_buffer(1, mesh.vertex_buffer.slice(..));
+        self.set_index_buffer(mesh.index_buffer.slice(..), wgpu::IndexFormat::Uint32);
+        self.set_bind_group(0, camera_bind_group, &[]);
+        self.set_bind_group(1, &material.bind_group, &[]);
+        self.draw_indexed_indirect(indirect_buffer, 0);
+    }
+
     fn draw_model(&mut self, model: &'b MeshModel, camera_bind_group: &'b wgpu::BindGroup) {
         self.draw_model_instanced(model, 0..1, camera_bind_group);
     }
@@ -0,0 +1,124 @@
+use wgpu::{CommandEncoder, Device, RenderPipeline, SurfaceConfiguration, TextureView};
+
+use crate::{post::begin_fullscreen_pass, shader::ShaderLibrary, texture::Texture};
+
+/// 在色调映射之后叠加 UI 的合成通道
+///
+/// 精灵、文字或 egui 先画进一张非 sRGB 的 [`OverlayPass::FORMAT`] 纹理：颜色按 sRGB 编码的数值
+/// 原样写入，半透明混合也在 sRGB 空间进行，与设计稿和 egui 的约定一致。[`OverlayPass::composite`]
+/// 再以预乘 alpha 把它盖到已经色调映射的画面上，因此 UI 的颜色不受曝光、色调映射与 HDR 格式影响。
+///
+/// 叠加层以 `ALPHA_BLENDING` 绘制到清空为透明的纹理上时，得到的正是预乘 alpha 的颜色。
+/// 叠加层中采样的纹理应以非 sRGB 格式加载，否则采样时会被解码为线性值而变暗。
+pub struct OverlayPass {
+    target: Texture,
+    layout: wgpu::BindGroupLayout,
+    pipeline: RenderPipeline,
+}
+
+impl OverlayPass {
+    pub const FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba8Unorm;
+
+    /// `output_format` 为合成目标的格式，通常是 [`Frame::gamma_view`](crate::context::Frame::gamma_view)
+    /// 的格式；目标是 sRGB 格式时叠加层会先解码，只有半透明边缘的混合与非 sRGB 目标略有差异
+    pub fn new(
+        device: &Device,
+        config: &SurfaceConfiguration,
+        output_format: wgpu::TextureFormat,
+    ) -> Self {
+        let layout = Texture::texture_bind_group_layout(device);
+        let shader = ShaderLibrary::new()
+            .create_shader_module(
+                device,
+                "Overlay Shader",
+                include_str!("../shaders/overlay.wgsl"),
+            )
+            .expect("built-in overlay shader");
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Overlay Pipeline Layout"),
+            bind_group_layouts: &[&layout],
+            push_constant_ranges: &[],
+        });
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Overlay Pipeline"),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                compilation_options: Default::default(),
+                entry_point: Some("vs_main"),
+                buffers: &[],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                compilation_options: Default::default(),
+                entry_point: Some(if output_format.is_srgb() {
+                    "fs_linear"
+                } else {
+                    "fs_main"
+                }),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: output_format,
+                    blend: Some(wgpu::BlendState::PREMULTIPLIED_ALPHA_BLENDING),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+            cache: None,
+        });
+
+        Self {
+            target: Self::create_target(device, config),
+            layout,
+            pipeline,
+        }
+    }
+
+    fn create_target(device: &Device, config: &SurfaceConfiguration) -> Texture {
+        Texture::create_color_target(device, config, Self::FORMAT, "overlay_target")
+    }
+
+    pub fn resize(&mut self, device: &Device, config: &SurfaceConfiguration) {
+        self.target = Self::create_target(device, config);
+    }
+
+    /// 清空叠加层并开始绘制 UI，管线的颜色目标格式须为 [`OverlayPass::FORMAT`]
+    pub fn begin<'a>(&'a self, encoder: &'a mut CommandEncoder) -> wgpu::RenderPass<'a> {
+        encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Overlay Pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: &self.target.view,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT),
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            ..Default::default()
+        })
+    }
+
+    /// 把叠加层混合到 `output` 上，`output` 应已包含色调映射后的画面
+    pub fn composite(&self, device: &Device, encoder: &mut CommandEncoder, output: &TextureView) {
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: &self.layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(&self.target.view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(&self.target.sampler),
+                },
+            ],
+            label: Some("overlay_bind_group"),
+        });
+        let mut pass = begin_fullscreen_pass(encoder, "Overlay Composite Pass", output);
+        pass.set_pipeline(&self.pipeline);
+        pass.set_bind_group(0, &bind_group, &[]);
+        pass.draw(0..3, 0..1);
+    }
+}
@@ -0,0 +1,171 @@
+use std::time::Duration;
+
+// wasm32 上 std::time::Instant 不可用
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::Instant;
+#[cfg(target_arch = "wasm32")]
+use web_time::Instant;
+
+/// 帧率限制器的节拍统计：每帧实际开始的时刻比计划晚了多少
+#[derive(Debug, Clone, Default)]
+pub struct PacingStats {
+    /// 到了计划时刻后开始、计入统计的帧数，包括晚了一整个间隔以上而重新计时的帧；
+    /// 不含限制帧率后的第一帧与系统提前触发的重绘
+    pub frames: u64,
+    /// 晚于计划超过 [`PacingStats::LATE_THRESHOLD`] 的帧数
+    pub late_frames: u64,
+    pub max_lateness: Duration,
+    total_lateness: Duration,
+}
+
+impl PacingStats {
+    pub const LATE_THRESHOLD: Duration = Duration::from_millis(1);
+
+    fn record(&mut self, lateness: Duration) {
+        self.frames += 1;
+        if lateness > Self::LATE_THRESHOLD {
+            self.late_frames += 1;
+        }
+        self.max_lateness = self.max_lateness.max(lateness);
+        self.total_lateness += lateness;
+    }
+
+    pub fn average_lateness(&self) -> Duration {
+        if self.frames == 0 {
+            return Duration::ZERO;
+        }
+        self.total_lateness / self.frames as u32
+    }
+}
+
+impl std::fmt::Display for PacingStats {
+    /// 例如 `late 2/120 | lateness avg 0.05 ms, max 1.32 ms`
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let ms = |d: Duration| d.as_secs_f64() * 1000.0;
+        write!(
+            f,
+            "late {}/{} | lateness avg {:.2} ms, max {:.2} ms",
+            self.late_frames,
+            self.frames,
+            ms(self.average_lateness()),
+            ms(self.max_lateness)
+        )
+    }
+}
+
+/// 把渲染限制在目标帧率以下，与呈现模式无关
+///
+/// 在高刷新率显示器或 `Immediate`/`Mailbox` 呈现模式下，简单的示例不加限制会占满 GPU 与一个 CPU 核。
+/// [`WindowAppHandler`](crate::app::WindowAppHandler) 在每帧开始时调用 [`FramePacer::begin_frame`]，
+/// 帧结束后若还没到下一帧的时刻，先用 `ControlFlow::WaitUntil` 休眠到 [`FramePacer::wake_time`]，
+/// 剩下不到 `spin` 的时间用 [`FramePacer::spin_until_due`] 忙等。系统定时器的精度通常只有
+/// 毫秒级，忙等最后一小段可以让帧的间隔更均匀，代价是这段时间占用 CPU。
+#[derive(Debug, Clone)]
+pub struct FramePacer {
+    /// 为 `None` 时不限制帧率
+    interval: Option<Duration>,
+    next_frame: Option<Instant>,
+    spin: Duration,
+    stats: PacingStats,
+}
+
+impl Default for FramePacer {
+    fn default() -> Self {
+        Self {
+            interval: None,
+            next_frame: None,
+            spin: Self::DEFAULT_SPIN,
+            stats: PacingStats::default(),
+        }
+    }
+}
+
+impl FramePacer {
+    pub const DEFAULT_SPIN: Duration = Duration::from_millis(1);
+
+    pub fn uncapped() -> Self {
+        Self::default()
+    }
+
+    /// 休眠结束后忙等的最长时间，为零时只休眠；浏览器中不能阻塞，总是只休眠
+    pub fn with_spin(mut self, spin: Duration) -> Self {
+        self.spin = spin;
+        self
+    }
+
+    pub fn with_target_fps(fps: f64) -> Self {
+        let mut pacer = Self::default();
+        pacer.set_target_fps(Some(fps));
+        pacer
+    }
+
+    /// `None` 或非正数表示不限制帧率
+    pub fn set_target_fps(&mut self, fps: Option<f64>) {
+        self.interval = fps
+            .filter(|fps| *fps > 0.0)
+            .map(|fps| Duration::from_secs_f64(1.0 / fps));
+        self.next_frame = None;
+        self.reset_stats();
+    }
+
+    pub fn target_fps(&self) -> Option<f64> {
+        self.interval.map(|interval| 1.0 / interval.as_secs_f64())
+    }
+
+    /// 一帧开始时调用，安排下一帧的开始时刻
+    ///
+    /// 按时开始的帧沿用原来的节拍，避免等待误差逐帧累积；落后超过一帧时从现在重新开始计时，
+    /// 而不是连续渲染多帧追赶。系统提前触发的重绘（例如调整窗口大小时）不改变节拍。
+    pub fn begin_frame(&mut self, now: Instant) {
+        let Some(interval) = self.interval else {
+            return;
+        };
+        self.next_frame = Some(match self.next_frame {
+            Some(next) if now < next => next,
+            Some(next) => {
+                self.stats.record(now - next);
+                if now < next + interval {
+                    next + interval
+                } else {
+                    now + interval
+                }
+            }
+            None => now + interval,
+        });
+    }
+
+    /// 下一帧还不能开始时返回应等待到的时刻
+    pub fn wait_until(&self, now: Instant) -> Option<Instant> {
+        self.next_frame.filter(|next| *next > now)
+    }
+
+    /// 休眠应结束的时刻，即下一帧的时刻提前 `spin`；已经到了时返回 `None`
+    pub fn wake_time(&self, now: Instant) -> Option<Instant> {
+        #[cfg(not(target_arch = "wasm32"))]
+        let spin = self.spin;
+        #[cfg(target_arch = "wasm32")]
+        let spin = Duration::ZERO;
+        self.next_frame
+            .map(|next| next.checked_sub(spin).unwrap_or(next))
+            .filter(|wake| *wake > now)
+    }
+
+    /// 忙等到下一帧的时刻，浏览器中立即返回
+    pub fn spin_until_due(&self) {
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(next) = self.next_frame {
+            while Instant::now() < next {
+                std::thread::yield_now();
+            }
+        }
+    }
+
+    /// 上次 [`FramePacer::reset_stats`] 以来的节拍统计
+    pub fn stats(&self) -> &PacingStats {
+        &self.stats
+    }
+
+    pub fn reset_stats(&mut self) {
+        self.stats = PacingStats::default();
+    }
+}
@@ -0,0 +1,390 @@
+use bytemuck::{Pod, Zeroable};
+use wgpu::{BindGroup, BindGroupLayout, Buffer, Device, Queue, RenderPipeline};
+
+use crate::{
+    camera::Camera, environment::EnvironmentBundle, jobs, model::RenderVertex, scatter::Rng,
+    shader::ShaderLibrary, texture::Texture,
+};
+
+pub mod gpu;
+
+/// 发射器参数，颜色与尺寸在粒子生命周期内线性过渡
+#[derive(Debug, Copy, Clone)]
+pub struct EmitterSettings {
+    pub position: glam::Vec3,
+    /// 发射位置在各轴上的随机偏移范围
+    pub position_jitter: glam::Vec3,
+    /// 每秒发射的粒子数
+    pub spawn_rate: f32,
+    /// 单位秒
+    pub lifetime: f32,
+    pub velocity: glam::Vec3,
+    /// 初速度在各方向上的随机扰动幅度
+    pub velocity_jitter: f32,
+    pub acceleration: glam::Vec3,
+    /// 每秒速度衰减的比例
+    pub drag: f32,
+    pub start_size: f32,
+    pub end_size: f32,
+    pub start_color: glam::Vec4,
+    pub end_color: glam::Vec4,
+    pub max_particles: usize,
+}
+
+impl Default for EmitterSettings {
+    fn default() -> Self {
+        Self {
+            position: glam::Vec3::ZERO,
+            position_jitter: glam::Vec3::splat(0.3),
+            spawn_rate: 60.0,
+            lifetime: 4.0,
+            velocity: glam::vec3(0.0, 1.5, 0.0),
+            velocity_jitter: 0.4,
+            acceleration: glam::vec3(0.0, 0.2, 0.0),
+            drag: 0.3,
+            start_size: 0.6,
+            end_size: 2.5,
+            start_color: glam::vec4(0.8, 0.8, 0.8, 0.6),
+            end_color: glam::vec4(0.5, 0.5, 0.55, 0.0),
+            max_particles: 1024,
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone)]
+struct Particle {
+    position: glam::Vec3,
+    velocity: glam::Vec3,
+    age: f32,
+}
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct ParticleRaw {
+    position_size: [f32; 4],
+    color: [f32; 4],
+}
+
+unsafe impl Zeroable for ParticleRaw {}
+unsafe impl Pod for ParticleRaw {}
+
+impl RenderVertex for ParticleRaw {
+    fn buffer_layout_desc<'a>() -> wgpu::VertexBufferLayout<'a> {
+        const ATTRIBUTES: [wgpu::VertexAttribute; 2] = wgpu::vertex_attr_array![
+            0 => Float32x4,
+            1 => Float32x4,
+        ];
+        wgpu::VertexBufferLayout {
+            array_stride: std::mem::size_of::<ParticleRaw>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Instance,
+            attributes: &ATTRIBUTES,
+        }
+    }
+}
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+struct ParticleUniform {
+    camera_right: [f32; 4],
+    camera_up: [f32; 4],
+    eye: [f32; 4],
+    params: [f32; 4],
+}
+
+unsafe impl Zeroable for ParticleUniform {}
+unsafe impl Pod for ParticleUniform {}
+
+impl ParticleUniform {
+    fn new(camera: &Camera, softness: f32, fog: bool) -> Self {
+        let forward = (camera.target - camera.eye).normalize();
+        let right = forward.cross(camera.up).normalize();
+        let up = right.cross(forward);
+        Self {
+            camera_right: right.extend(0.0).to_array(),
+            camera_up: up.extend(0.0).to_array(),
+            eye: camera.eye.extend(softness.max(0.0)).to_array(),
+            params: [camera.znear, camera.zfar, if fog { 1.0 } else { 0.0 }, 0.0],
+        }
+    }
+}
+
+fn create_uniform_buffer(device: &Device) -> Buffer {
+    device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("Particle Uniform Buffer"),
+        size: std::mem::size_of::<ParticleUniform>() as wgpu::BufferAddress,
+        usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        mapped_at_creation: false,
+    })
+}
+
+/// 绘制粒子时的 group 1：粒子参数与场景深度
+fn create_scene_bind_group_layout(device: &Device) -> BindGroupLayout {
+    device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+        entries: &[
+            wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            },
+            wgpu::BindGroupLayoutEntry {
+                binding: 1,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Texture {
+                    sample_type: wgpu::TextureSampleType::Depth,
+                    view_dimension: wgpu::TextureViewDimension::D2,
+                    multisampled: false,
+                },
+                count: None,
+            },
+        ],
+        label: Some("particle_scene_bind_group_layout"),
+    })
+}
+
+fn create_scene_bind_group(
+    device: &Device,
+    layout: &BindGroupLayout,
+    uniform_buffer: &Buffer,
+    depth: &Texture,
+) -> BindGroup {
+    device.create_bind_group(&wgpu::BindGroupDescriptor {
+        layout,
+        entries: &[
+            wgpu::BindGroupEntry {
+                binding: 0,
+                resource: uniform_buffer.as_entire_binding(),
+            },
+            wgpu::BindGroupEntry {
+                binding: 1,
+                resource: wgpu::BindingResource::TextureView(&depth.view),
+            },
+        ],
+        label: Some("particle_scene_bind_group"),
+    })
+}
+
+fn create_render_pipeline(
+    device: &Device,
+    color_format: wgpu::TextureFormat,
+    camera_layout: &BindGroupLayout,
+    scene_bind_group_layout: &BindGroupLayout,
+    environment: &EnvironmentBundle,
+) -> RenderPipeline {
+    let shader = ShaderLibrary::new()
+        .create_shader_module(
+            device,
+            "Particle Shader",
+            include_str!("../shaders/particles.wgsl"),
+        )
+        .expect("built-in particle shader");
+    let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+        label: Some("Particle Pipeline Layout"),
+        bind_group_layouts: &[
+            camera_layout,
+            scene_bind_group_layout,
+            &environment.bind_group_layout,
+        ],
+        push_constant_ranges: &[],
+    });
+    device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+        label: Some("Particle Pipeline"),
+        layout: Some(&layout),
+        vertex: wgpu::VertexState {
+            module: &shader,
+            compilation_options: Default::default(),
+            entry_point: Some("vs_main"),
+            buffers: &[ParticleRaw::buffer_layout_desc()],
+        },
+        fragment: Some(wgpu::FragmentState {
+            module: &shader,
+            compilation_options: Default::default(),
+            entry_point: Some("fs_main"),
+            targets: &[Some(wgpu::ColorTargetState {
+                format: color_format,
+                blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                write_mask: wgpu::ColorWrites::ALL,
+            })],
+        }),
+        primitive: wgpu::PrimitiveState::default(),
+        depth_stencil: Some(wgpu::DepthStencilState {
+            format: Texture::DEPTH_FORMAT,
+            depth_write_enabled: false,
+            depth_compare: wgpu::CompareFunction::Less,
+            stencil: wgpu::StencilState::default(),
+            bias: wgpu::DepthBiasState::default(),
+        }),
+        multisample: wgpu::MultisampleState::default(),
+        multiview: None,
+        cache: None,
+    })
+}
+
+/// 在 CPU 上模拟、以面向相机的半透明面片绘制的粒子系统
+///
+/// 片元着色器读取场景深度，在粒子接近几何体时淡出（软粒子），并应用与
+/// [`EnvironmentBundle`] 一致的高度雾。由于同时采样深度纹理，绘制所在的
+/// 渲染通道必须以只读方式（`depth_ops: None`）绑定该深度附件。
+pub struct ParticleSystem {
+    pub settings: EmitterSettings,
+    /// 粒子与几何体的深度差小于该距离时开始淡出，为 0 时关闭软粒子
+    pub softness: f32,
+    /// 是否对粒子应用高度雾
+    pub fog: bool,
+    particles: Vec<Particle>,
+    spawn_accumulator: f32,
+    rng: Rng,
+    instance_buffer: Buffer,
+    /// 实例缓冲能容纳的粒子数，创建后不再随 `max_particles` 变化
+    capacity: usize,
+    instance_count: u32,
+    uniform_buffer: Buffer,
+    scene_bind_group_layout: BindGroupLayout,
+    scene_bind_group: Option<BindGroup>,
+    environment_bind_group: BindGroup,
+    pipeline: RenderPipeline,
+}
+
+impl ParticleSystem {
+    pub fn new(
+        device: &Device,
+        settings: EmitterSettings,
+        color_format: wgpu::TextureFormat,
+        camera_layout: &BindGroupLayout,
+        environment: &EnvironmentBundle,
+    ) -> Self {
+        let capacity = settings.max_particles.max(1);
+        let instance_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Particle Instance Buffer"),
+            size: (std::mem::size_of::<ParticleRaw>() * capacity) as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let uniform_buffer = create_uniform_buffer(device);
+
+        let scene_bind_group_layout = create_scene_bind_group_layout(device);
+        let pipeline = create_render_pipeline(
+            device,
+            color_format,
+            camera_layout,
+            &scene_bind_group_layout,
+            environment,
+        );
+
+        Self {
+            settings,
+            softness: 0.5,
+            fog: true,
+            particles: Vec::with_capacity(capacity),
+            spawn_accumulator: 0.0,
+            rng: Rng::new(0x5eed),
+            instance_buffer,
+            capacity,
+            instance_count: 0,
+            uniform_buffer,
+            scene_bind_group_layout,
+            scene_bind_group: None,
+            environment_bind_group: environment.bind_group.clone(),
+            pipeline,
+        }
+    }
+
+    /// 绑定用于软粒子的场景深度，surface 大小变化后需要重新调用
+    pub fn set_depth_texture(&mut self, device: &Device, depth: &Texture) {
+        self.scene_bind_group = Some(create_scene_bind_group(
+            device,
+            &self.scene_bind_group_layout,
+            &self.uniform_buffer,
+            depth,
+        ));
+    }
+
+    pub fn len(&self) -> usize {
+        self.particles.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.particles.is_empty()
+    }
+
+    fn spawn(&mut self) {
+        let s = &self.settings;
+        let mut jitter = |scale: glam::Vec3| {
+            glam::vec3(
+                self.rng.range(-1.0, 1.0),
+                self.rng.range(-1.0, 1.0),
+                self.rng.range(-1.0, 1.0),
+            ) * scale
+        };
+        let position = s.position + jitter(s.position_jitter);
+        let velocity = s.velocity + jitter(glam::Vec3::splat(s.velocity_jitter));
+        self.particles.push(Particle {
+            position,
+            velocity,
+            age: 0.0,
+        });
+    }
+
+    /// 推进模拟 `dt` 秒，按到相机的距离从远到近排序后上传
+    pub fn update(&mut self, queue: &Queue, camera: &Camera, dt: f32) {
+        let settings = self.settings;
+        let damping = (1.0 - settings.drag * dt).max(0.0);
+        jobs::global().for_each_mut("particle simulation", &mut self.particles, |particle| {
+            particle.velocity = (particle.velocity + settings.acceleration * dt) * damping;
+            particle.position += particle.velocity * dt;
+            particle.age += dt;
+        });
+        self.particles.retain(|p| p.age < settings.lifetime);
+
+        let max_particles = settings.max_particles.min(self.capacity);
+        self.spawn_accumulator += settings.spawn_rate * dt;
+        while self.spawn_accumulator >= 1.0 {
+            self.spawn_accumulator -= 1.0;
+            if self.particles.len() < max_particles {
+                self.spawn();
+            }
+        }
+
+        // 半透明混合需要从远到近绘制
+        let eye = camera.eye;
+        let jobs = jobs::global();
+        jobs.sort_by("particle sort", &mut self.particles, |a, b| {
+            b.position
+                .distance_squared(eye)
+                .total_cmp(&a.position.distance_squared(eye))
+        });
+        let instances = jobs.map("particle instances", &self.particles, |p| {
+            let t = (p.age / settings.lifetime).clamp(0.0, 1.0);
+            let size = settings.start_size + (settings.end_size - settings.start_size) * t;
+            ParticleRaw {
+                position_size: p.position.extend(size).to_array(),
+                color: settings.start_color.lerp(settings.end_color, t).to_array(),
+            }
+        });
+        queue.write_buffer(&self.instance_buffer, 0, bytemuck::cast_slice(&instances));
+        self.instance_count = instances.len() as u32;
+
+        let uniform = ParticleUniform::new(camera, self.softness, self.fog);
+        queue.write_buffer(&self.uniform_buffer, 0, bytemuck::cast_slice(&[uniform]));
+    }
+
+    pub fn draw(&self, render_pass: &mut wgpu::RenderPass, camera_bind_group: &BindGroup) {
+        let scene_bind_group = self
+            .scene_bind_group
+            .as_ref()
+            .expect("ParticleSystem::set_depth_texture must be called before drawing");
+        if self.instance_count == 0 {
+            return;
+        }
+        render_pass.set_pipeline(&self.pipeline);
+        render_pass.set_bind_group(0, camera_bind_group, &[]);
+        render_pass.set_bind_group(1, scene_bind_group, &[]);
+        render_pass.set_bind_group(2, &self.environment_bind_group, &[]);
+        render_pass.set_vertex_buffer(0, self.instance_buffer.slice(..));
+        render_pass.draw(0..6, 0..self.instance_count);
+    }
+}
@@ -0,0 +1,323 @@
+use bytemuck::{Pod, Zeroable};
+use wgpu::{BindGroup, BindGroupLayout, Buffer, Device, Queue, RenderPipeline};
+
+use super::{
+    create_render_pipeline, create_scene_bind_group, create_scene_bind_group_layout,
+    create_uniform_buffer, EmitterSettings, ParticleRaw, ParticleUniform,
+};
+use crate::{
+    camera::Camera, environment::EnvironmentBundle, shader::ShaderLibrary, texture::Texture,
+};
+
+/// 粒子与场景深度的碰撞参数
+#[derive(Debug, Copy, Clone)]
+pub struct DepthCollision {
+    pub enabled: bool,
+    /// 法线方向上保留的速度比例，1 为完全弹性
+    pub restitution: f32,
+    /// 碰撞时切线方向上损失的速度比例
+    pub friction: f32,
+    /// 表面之后多深的范围内判定为碰撞，单位为世界空间距离
+    pub thickness: f32,
+}
+
+impl Default for DepthCollision {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            restitution: 0.4,
+            friction: 0.2,
+            thickness: 0.5,
+        }
+    }
+}
+
+/// 着色器中 `Particle` 的大小：位置与年龄、速度与存活标记各一个 vec4
+const PARTICLE_STATE_SIZE: u32 = 32;
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+struct SimulationUniform {
+    view_proj: [[f32; 4]; 4],
+    inv_view_proj: [[f32; 4]; 4],
+    emitter: [f32; 4],
+    jitter_lifetime: [f32; 4],
+    velocity: [f32; 4],
+    acceleration: [f32; 4],
+    start_color: [f32; 4],
+    end_color: [f32; 4],
+    spawn: [f32; 4],
+    collision: [f32; 4],
+    params: [f32; 4],
+}
+
+unsafe impl Zeroable for SimulationUniform {}
+unsafe impl Pod for SimulationUniform {}
+
+/// 在计算着色器中模拟、可以与场景深度碰撞的粒子系统
+///
+/// 粒子状态只存在于 GPU 上，死亡的粒子在下一帧按发射速率重新发射。[`GpuParticleSystem::simulate`]
+/// 读取不透明通道写入的深度缓冲，粒子落到可见表面之后时沿重建的法线反弹，因此粒子只会与
+/// 屏幕上可见的几何体碰撞。绘制与 [`ParticleSystem`](super::ParticleSystem) 共用同一条管线，
+/// 支持软粒子与高度雾，但不做排序，适合火花这类小而密、绘制顺序不明显的粒子。
+///
+/// 每帧的顺序：[`update`](Self::update)，不透明通道之后 [`simulate`](Self::simulate)，
+/// 最后在只读绑定深度的通道中 [`draw`](Self::draw)。
+pub struct GpuParticleSystem {
+    pub settings: EmitterSettings,
+    pub collision: DepthCollision,
+    /// 含义与 [`ParticleSystem::softness`](super::ParticleSystem::softness) 相同
+    pub softness: f32,
+    pub fog: bool,
+    /// 粒子缓冲能容纳的粒子数，创建后不再随 `max_particles` 变化
+    capacity: u32,
+    spawn_accumulator: f32,
+    frame: u32,
+    particle_buffer: Buffer,
+    instance_buffer: Buffer,
+    counter_buffer: Buffer,
+    simulation_buffer: Buffer,
+    compute_bind_group_layout: BindGroupLayout,
+    compute_bind_group: Option<BindGroup>,
+    compute_pipeline: wgpu::ComputePipeline,
+    uniform_buffer: Buffer,
+    scene_bind_group_layout: BindGroupLayout,
+    scene_bind_group: Option<BindGroup>,
+    environment_bind_group: BindGroup,
+    pipeline: RenderPipeline,
+}
+
+impl GpuParticleSystem {
+    pub fn new(
+        device: &Device,
+        settings: EmitterSettings,
+        color_format: wgpu::TextureFormat,
+        camera_layout: &BindGroupLayout,
+        environment: &EnvironmentBundle,
+    ) -> Self {
+        let capacity = settings.max_particles.max(1) as u32;
+        let particle_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("GPU Particle State Buffer"),
+            size: (PARTICLE_STATE_SIZE * capacity) as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::STORAGE,
+            mapped_at_creation: false,
+        });
+        let instance_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("GPU Particle Instance Buffer"),
+            size: (std::mem::size_of::<ParticleRaw>() as u32 * capacity) as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::STORAGE,
+            mapped_at_creation: false,
+        });
+        let counter_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("GPU Particle Spawn Counter"),
+            size: std::mem::size_of::<u32>() as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let simulation_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("GPU Particle Simulation Buffer"),
+            size: std::mem::size_of::<SimulationUniform>() as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let storage = |binding| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility: wgpu::ShaderStages::COMPUTE,
+            ty: wgpu::BindingType::Buffer {
+                ty: wgpu::BufferBindingType::Storage { read_only: false },
+                has_dynamic_offset: false,
+                min_binding_size: None,
+            },
+            count: None,
+        };
+        let compute_bind_group_layout =
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                entries: &[
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 0,
+                        visibility: wgpu::ShaderStages::COMPUTE,
+                        ty: wgpu::BindingType::Buffer {
+                            ty: wgpu::BufferBindingType::Uniform,
+                            has_dynamic_offset: false,
+                            min_binding_size: None,
+                        },
+                        count: None,
+                    },
+                    storage(1),
+                    storage(2),
+                    storage(3),
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 4,
+                        visibility: wgpu::ShaderStages::COMPUTE,
+                        ty: wgpu::BindingType::Texture {
+                            sample_type: wgpu::TextureSampleType::Depth,
+                            view_dimension: wgpu::TextureViewDimension::D2,
+                            multisampled: false,
+                        },
+                        count: None,
+                    },
+                ],
+                label: Some("particle_simulate_bind_group_layout"),
+            });
+        let compute_shader = ShaderLibrary::new()
+            .create_shader_module(
+                device,
+                "Particle Simulate Shader",
+                include_str!("../../shaders/particles_simulate.wgsl"),
+            )
+            .expect("built-in particle simulate shader");
+        let compute_pipeline_layout =
+            device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some("Particle Simulate Pipeline Layout"),
+                bind_group_layouts: &[&compute_bind_group_layout],
+                push_constant_ranges: &[],
+            });
+        let compute_pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+            label: Some("Particle Simulate Pipeline"),
+            layout: Some(&compute_pipeline_layout),
+            module: &compute_shader,
+            entry_point: Some("cs_main"),
+            compilation_options: Default::default(),
+            cache: None,
+        });
+
+        let uniform_buffer = create_uniform_buffer(device);
+        let scene_bind_group_layout = create_scene_bind_group_layout(device);
+        let pipeline = create_render_pipeline(
+            device,
+            color_format,
+            camera_layout,
+            &scene_bind_group_layout,
+            environment,
+        );
+
+        Self {
+            settings,
+            collision: DepthCollision::default(),
+            softness: 0.5,
+            fog: true,
+            capacity,
+            spawn_accumulator: 0.0,
+            frame: 0,
+            particle_buffer,
+            instance_buffer,
+            counter_buffer,
+            simulation_buffer,
+            compute_bind_group_layout,
+            compute_bind_group: None,
+            compute_pipeline,
+            uniform_buffer,
+            scene_bind_group_layout,
+            scene_bind_group: None,
+            environment_bind_group: environment.bind_group.clone(),
+            pipeline,
+        }
+    }
+
+    /// 绑定用于碰撞与软粒子的场景深度，surface 大小变化后需要重新调用
+    pub fn set_depth_texture(&mut self, device: &Device, depth: &Texture) {
+        self.compute_bind_group = Some(device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: &self.compute_bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: self.simulation_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: self.particle_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: self.instance_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 3,
+                    resource: self.counter_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 4,
+                    resource: wgpu::BindingResource::TextureView(&depth.view),
+                },
+            ],
+            label: Some("particle_simulate_bind_group"),
+        }));
+        self.scene_bind_group = Some(create_scene_bind_group(
+            device,
+            &self.scene_bind_group_layout,
+            &self.uniform_buffer,
+            depth,
+        ));
+    }
+
+    /// 准备推进 `dt` 秒的模拟参数，实际的模拟在 [`GpuParticleSystem::simulate`] 中执行
+    pub fn update(&mut self, queue: &Queue, camera: &Camera, dt: f32) {
+        let s = &self.settings;
+        self.spawn_accumulator += s.spawn_rate * dt;
+        let spawn_count = self.spawn_accumulator.floor();
+        self.spawn_accumulator -= spawn_count;
+        // 种子以 f32 传入着色器，保持在能精确表示的范围内
+        self.frame = (self.frame + 1) % (1 << 24);
+
+        let view_proj = camera.build_view_projection_matrix();
+        let collision = &self.collision;
+        let uniform = SimulationUniform {
+            view_proj: view_proj.to_cols_array_2d(),
+            inv_view_proj: view_proj.inverse().to_cols_array_2d(),
+            emitter: s.position.extend(dt).to_array(),
+            jitter_lifetime: s.position_jitter.extend(s.lifetime).to_array(),
+            velocity: s.velocity.extend(s.velocity_jitter).to_array(),
+            acceleration: s.acceleration.extend(s.drag).to_array(),
+            start_color: s.start_color.to_array(),
+            end_color: s.end_color.to_array(),
+            spawn: [s.start_size, s.end_size, spawn_count, self.frame as f32],
+            collision: [
+                if collision.enabled { 1.0 } else { 0.0 },
+                collision.restitution,
+                collision.friction,
+                collision.thickness.max(0.0),
+            ],
+            params: [
+                camera.znear,
+                camera.zfar,
+                s.max_particles.min(self.capacity as usize) as f32,
+                0.0,
+            ],
+        };
+        queue.write_buffer(&self.simulation_buffer, 0, bytemuck::cast_slice(&[uniform]));
+        queue.write_buffer(&self.counter_buffer, 0, bytemuck::cast_slice(&[0u32]));
+
+        let uniform = ParticleUniform::new(camera, self.softness, self.fog);
+        queue.write_buffer(&self.uniform_buffer, 0, bytemuck::cast_slice(&[uniform]));
+    }
+
+    /// 在 GPU 上推进模拟，须在写入深度的不透明通道之后、[`GpuParticleSystem::draw`] 之前调用
+    pub fn simulate(&self, encoder: &mut wgpu::CommandEncoder) {
+        let compute_bind_group = self
+            .compute_bind_group
+            .as_ref()
+            .expect("GpuParticleSystem::set_depth_texture must be called before simulating");
+        let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+            label: Some("Particle Simulate Pass"),
+            timestamp_writes: None,
+        });
+        pass.set_pipeline(&self.compute_pipeline);
+        pass.set_bind_group(0, compute_bind_group, &[]);
+        pass.dispatch_workgroups(self.capacity.div_ceil(64), 1, 1);
+    }
+
+    /// 绘制全部粒子槽位，死亡的粒子尺寸与透明度为零
+    pub fn draw(&self, render_pass: &mut wgpu::RenderPass, camera_bind_group: &BindGroup) {
+        let scene_bind_group = self
+            .scene_bind_group
+            .as_ref()
+            .expect("GpuParticleSystem::set_depth_texture must be called before drawing");
+        render_pass.set_pipeline(&self.pipeline);
+        render_pass.set_bind_group(0, camera_bind_group, &[]);
+        render_pass.set_bind_group(1, scene_bind_group, &[]);
+        render_pass.set_bind_group(2, &self.environment_bind_group, &[]);
+        render_pass.set_vertex_buffer(0, self.instance_buffer.slice(..));
+        render_pass.draw(0..6, 0..self.capacity);
+    }
+}
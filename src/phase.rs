@@ -0,0 +1,195 @@
+use std::cmp::Ordering;
+
+use wgpu::{BindGroup, RenderPass};
+
+use crate::{
+    camera::Camera,
+    jobs,
+    model::{DrawModel, MeshModel},
+};
+
+/// 渲染阶段，按声明顺序依次绘制
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum RenderPhase {
+    #[default]
+    Opaque,
+    AlphaTest,
+    Transparent,
+    Overlay,
+    Ui,
+}
+
+impl RenderPhase {
+    pub const ALL: [RenderPhase; 5] = [
+        RenderPhase::Opaque,
+        RenderPhase::AlphaTest,
+        RenderPhase::Transparent,
+        RenderPhase::Overlay,
+        RenderPhase::Ui,
+    ];
+
+    /// 阶段内按深度排序的方式：不透明物体由近到远以减少重复着色，半透明物体由远到近以正确混合
+    pub fn sort_order(self) -> SortOrder {
+        match self {
+            RenderPhase::Opaque | RenderPhase::AlphaTest => SortOrder::FrontToBack,
+            RenderPhase::Transparent => SortOrder::BackToFront,
+            RenderPhase::Overlay | RenderPhase::Ui => SortOrder::Submission,
+        }
+    }
+}
+
+/// 材质的透明方式，与 glTF 的 `alphaMode` 一一对应
+#[derive(Debug, Copy, Clone, Default, PartialEq)]
+pub enum AlphaMode {
+    #[default]
+    Opaque,
+    /// 镂空：alpha 低于 `cutoff` 的片元被丢弃，glTF 默认的 `alphaCutoff` 为 0.5
+    Mask {
+        cutoff: f32,
+    },
+    Blend,
+}
+
+impl AlphaMode {
+    pub fn phase(self) -> RenderPhase {
+        match self {
+            AlphaMode::Opaque => RenderPhase::Opaque,
+            AlphaMode::Mask { .. } => RenderPhase::AlphaTest,
+            AlphaMode::Blend => RenderPhase::Transparent,
+        }
+    }
+
+    /// 传给 [`alpha_test_constants`](crate::shader::alpha_test_constants) 的阈值，非镂空时为 0
+    pub fn cutoff(self) -> f32 {
+        match self {
+            AlphaMode::Mask { cutoff } => cutoff,
+            AlphaMode::Opaque | AlphaMode::Blend => 0.0,
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum SortOrder {
+    FrontToBack,
+    BackToFront,
+    /// 忽略深度，只按 `layer` 与提交顺序
+    Submission,
+}
+
+/// 阶段内的排序键：先按 `layer` 升序，再按阶段的 [`SortOrder`] 比较 `depth`，键相同时保持提交顺序
+#[derive(Debug, Copy, Clone, Default, PartialEq)]
+pub struct SortKey {
+    pub layer: i32,
+    pub depth: f32,
+}
+
+impl SortKey {
+    pub fn layer(layer: i32) -> Self {
+        Self { layer, depth: 0.0 }
+    }
+
+    /// 以 `position` 沿相机朝向到相机的距离作为深度
+    pub fn view_depth(camera: &Camera, position: glam::Vec3) -> Self {
+        let forward = (camera.target - camera.eye).normalize_or_zero();
+        Self {
+            layer: 0,
+            depth: (position - camera.eye).dot(forward),
+        }
+    }
+
+    pub fn with_layer(self, layer: i32) -> Self {
+        Self { layer, ..self }
+    }
+
+    fn compare(&self, other: &Self, order: SortOrder) -> Ordering {
+        self.layer.cmp(&other.layer).then_with(|| match order {
+            SortOrder::FrontToBack => self.depth.total_cmp(&other.depth),
+            SortOrder::BackToFront => other.depth.total_cmp(&self.depth),
+            SortOrder::Submission => Ordering::Equal,
+        })
+    }
+}
+
+type DrawFn<'a> = Box<dyn FnOnce(&mut RenderPass<'_>) + 'a>;
+
+struct PhaseItem<'a> {
+    key: SortKey,
+    draw: DrawFn<'a>,
+}
+
+/// 按阶段收集一帧的绘制命令，绘制顺序由阶段与排序键决定，而不是 `render()` 中的调用顺序
+///
+/// 每个命令负责设置自己的管线和 bind group。
+#[derive(Default)]
+pub struct RenderPhases<'a> {
+    items: [Vec<PhaseItem<'a>>; RenderPhase::ALL.len()],
+}
+
+impl<'a> RenderPhases<'a> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(
+        &mut self,
+        phase: RenderPhase,
+        key: SortKey,
+        draw: impl FnOnce(&mut RenderPass<'_>) + 'a,
+    ) {
+        self.items[phase as usize].push(PhaseItem {
+            key,
+            draw: Box::new(draw),
+        });
+    }
+
+    /// 把模型的每个网格放入其材质所属的阶段，调用前需要设置好管线
+    pub fn push_model(
+        &mut self,
+        model: &'a MeshModel,
+        camera_bind_group: &'a BindGroup,
+        key: SortKey,
+    ) {
+        for mesh in &model.meshes {
+            let material = &model.materials[mesh.material];
+            self.push(material.phase, key, move |pass| {
+                pass.draw_mesh(mesh, material, camera_bind_group)
+            });
+        }
+    }
+
+    /// 排序并绘制 `phase` 中的命令，绘制后清空该阶段，便于把不同阶段放在不同的 render pass 中
+    pub fn draw_phase(&mut self, phase: RenderPhase, pass: &mut RenderPass<'_>) {
+        let items = std::mem::take(&mut self.items[phase as usize]);
+        // 绘制闭包不能跨线程，只对排序键与下标排序；sort_by 是稳定排序，键相同的命令保持提交顺序
+        let mut order = items
+            .iter()
+            .enumerate()
+            .map(|(i, item)| (item.key, i))
+            .collect::<Vec<_>>();
+        let sort_order = phase.sort_order();
+        jobs::global().sort_by("phase sort", &mut order, |a, b| {
+            a.0.compare(&b.0, sort_order)
+        });
+        let mut items = items.into_iter().map(Some).collect::<Vec<_>>();
+        for (_, i) in order {
+            if let Some(item) = items[i].take() {
+                (item.draw)(pass);
+            }
+        }
+    }
+
+    /// 按阶段顺序绘制所有命令
+    pub fn draw(&mut self, pass: &mut RenderPass<'_>) {
+        for phase in RenderPhase::ALL {
+            self.draw_phase(phase, pass);
+        }
+    }
+
+    pub fn len(&self, phase: RenderPhase) -> usize {
+        self.items[phase as usize].len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.iter().all(Vec::is_empty)
+    }
+}
@@ -0,0 +1,506 @@
+use std::collections::{BTreeMap, HashMap};
+
+use anyhow::{anyhow, bail, ensure, Context};
+use wgpu::{BindGroupLayout, BindGroupLayoutEntry, Device, ShaderStages};
+
+use crate::{shader::ShaderLibrary, validation};
+
+/// 带有 naga 反射信息的着色器，用于生成与其一致的 bind group layout 和管线
+pub struct ReflectedShader {
+    pub module: wgpu::ShaderModule,
+    ir: naga::Module,
+    info: naga::valid::ModuleInfo,
+}
+
+impl ReflectedShader {
+    pub fn new(
+        device: &Device,
+        library: &ShaderLibrary,
+        label: &str,
+        source: &str,
+    ) -> anyhow::Result<Self> {
+        let source = library.preprocess(source)?;
+        let (ir, info) =
+            validation::parse_preprocessed(&source).with_context(|| format!("shader `{label}`"))?;
+        let module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some(label),
+            source: wgpu::ShaderSource::Wgsl(source.into()),
+        });
+        Ok(Self { module, ir, info })
+    }
+
+    /// naga 解析得到的中间表示
+    pub fn ir(&self) -> &naga::Module {
+        &self.ir
+    }
+
+    /// 着色器用到的 bind group 数量，即最大的 group 编号加一
+    pub fn group_count(&self) -> u32 {
+        self.ir
+            .global_variables
+            .iter()
+            .filter_map(|(_, global)| global.binding.as_ref())
+            .map(|binding| binding.group + 1)
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// 由着色器中 `group` 的资源声明生成 layout 条目
+    ///
+    /// 可见性取自实际使用该资源的入口函数；浮点纹理按可过滤处理，采样器按 `Filtering` 处理，
+    /// 需要其他设置时请用 [`PipelineBuilder::bind_group_layout`] 传入已有的 layout。
+    pub fn bind_group_layout_entries(
+        &self,
+        group: u32,
+    ) -> anyhow::Result<Vec<BindGroupLayoutEntry>> {
+        let mut entries = Vec::new();
+        for (handle, global) in self.ir.global_variables.iter() {
+            let Some(binding) = &global.binding else {
+                continue;
+            };
+            if binding.group != group {
+                continue;
+            }
+            let name = global.name.as_deref().unwrap_or("?");
+            let ty = self.binding_type(global).with_context(|| {
+                format!("`{name}` at @group({group}) @binding({})", binding.binding)
+            })?;
+            entries.push(BindGroupLayoutEntry {
+                binding: binding.binding,
+                visibility: self.visibility(handle),
+                ty,
+                count: None,
+            });
+        }
+        entries.sort_by_key(|entry| entry.binding);
+        Ok(entries)
+    }
+
+    fn visibility(&self, global: naga::Handle<naga::GlobalVariable>) -> ShaderStages {
+        let mut used = ShaderStages::NONE;
+        let mut all = ShaderStages::NONE;
+        for (index, entry_point) in self.ir.entry_points.iter().enumerate() {
+            let stage = match entry_point.stage {
+                naga::ShaderStage::Vertex => ShaderStages::VERTEX,
+                naga::ShaderStage::Fragment => ShaderStages::FRAGMENT,
+                naga::ShaderStage::Compute => ShaderStages::COMPUTE,
+            };
+            all |= stage;
+            if !self.info.get_entry_point(index)[global].is_empty() {
+                used |= stage;
+            }
+        }
+        // 没有入口函数使用的资源仍然保留条目，使 binding 编号与着色器一致
+        if used.is_empty() {
+            all
+        } else {
+            used
+        }
+    }
+
+    fn binding_type(&self, global: &naga::GlobalVariable) -> anyhow::Result<wgpu::BindingType> {
+        let buffer = |ty| wgpu::BindingType::Buffer {
+            ty,
+            has_dynamic_offset: false,
+            min_binding_size: None,
+        };
+        let inner = &self.ir.types[global.ty].inner;
+        Ok(match global.space {
+            naga::AddressSpace::Uniform => buffer(wgpu::BufferBindingType::Uniform),
+            naga::AddressSpace::Storage { access } => buffer(wgpu::BufferBindingType::Storage {
+                read_only: !access.contains(naga::StorageAccess::STORE),
+            }),
+            naga::AddressSpace::Handle => match inner {
+                naga::TypeInner::Sampler { comparison } => {
+                    wgpu::BindingType::Sampler(if *comparison {
+                        wgpu::SamplerBindingType::Comparison
+                    } else {
+                        wgpu::SamplerBindingType::Filtering
+                    })
+                }
+                naga::TypeInner::Image {
+                    dim,
+                    arrayed,
+                    class,
+                } => {
+                    let view_dimension = match (dim, arrayed) {
+                        (naga::ImageDimension::D1, false) => wgpu::TextureViewDimension::D1,
+                        (naga::ImageDimension::D2, false) => wgpu::TextureViewDimension::D2,
+                        (naga::ImageDimension::D2, true) => wgpu::TextureViewDimension::D2Array,
+                        (naga::ImageDimension::D3, false) => wgpu::TextureViewDimension::D3,
+                        (naga::ImageDimension::Cube, false) => wgpu::TextureViewDimension::Cube,
+                        (naga::ImageDimension::Cube, true) => wgpu::TextureViewDimension::CubeArray,
+                        _ => bail!("unsupported texture dimension {dim:?} (arrayed: {arrayed})"),
+                    };
+                    match class {
+                        naga::ImageClass::Sampled { kind, multi } => wgpu::BindingType::Texture {
+                            sample_type: match kind {
+                                naga::ScalarKind::Float => {
+                                    wgpu::TextureSampleType::Float { filterable: true }
+                                }
+                                naga::ScalarKind::Sint => wgpu::TextureSampleType::Sint,
+                                naga::ScalarKind::Uint => wgpu::TextureSampleType::Uint,
+                                _ => bail!("unsupported texture sample type {kind:?}"),
+                            },
+                            view_dimension,
+                            multisampled: *multi,
+                        },
+                        naga::ImageClass::Depth { multi } => wgpu::BindingType::Texture {
+                            sample_type: wgpu::TextureSampleType::Depth,
+                            view_dimension,
+                            multisampled: *multi,
+                        },
+                        naga::ImageClass::Storage { format, access } => {
+                            let read = access.contains(naga::StorageAccess::LOAD);
+                            let write = access.contains(naga::StorageAccess::STORE);
+                            wgpu::BindingType::StorageTexture {
+                                access: match (read, write) {
+                                    (true, true) => wgpu::StorageTextureAccess::ReadWrite,
+                                    (true, false) => wgpu::StorageTextureAccess::ReadOnly,
+                                    _ => wgpu::StorageTextureAccess::WriteOnly,
+                                },
+                                format: storage_format(*format)?,
+                                view_dimension,
+                            }
+                        }
+                    }
+                }
+                _ => bail!("unsupported resource type {inner:?}"),
+            },
+            space => bail!("unsupported address space {space:?}"),
+        })
+    }
+
+    fn entry_point(
+        &self,
+        name: &str,
+        stage: naga::ShaderStage,
+    ) -> anyhow::Result<&naga::EntryPoint> {
+        self.ir
+            .entry_points
+            .iter()
+            .find(|entry_point| entry_point.name == name && entry_point.stage == stage)
+            .ok_or_else(|| anyhow!("{stage:?} entry point `{name}` not found"))
+    }
+
+    fn has_entry_point(&self, name: &str, stage: naga::ShaderStage) -> bool {
+        self.entry_point(name, stage).is_ok()
+    }
+
+    /// 着色器中 `override` 常量的键与是否有默认值；键为 `@id(n)` 中的数字，没有 id 时为常量名，
+    /// 与 [`wgpu::PipelineCompilationOptions::constants`] 的键一致
+    pub fn overrides(&self) -> Vec<(String, bool)> {
+        self.ir
+            .overrides
+            .iter()
+            .filter_map(|(_, o)| {
+                let key = o.id.map(|id| id.to_string()).or_else(|| o.name.clone())?;
+                Some((key, o.init.is_some()))
+            })
+            .collect()
+    }
+
+    /// 顶点入口函数的输入：`(location, 分量类型)`
+    pub fn vertex_inputs(&self, entry_point: &str) -> anyhow::Result<Vec<(u32, naga::ScalarKind)>> {
+        let function = &self
+            .entry_point(entry_point, naga::ShaderStage::Vertex)?
+            .function;
+        let mut inputs = Vec::new();
+        for argument in &function.arguments {
+            self.collect_locations(argument.ty, argument.binding.as_ref(), &mut inputs);
+        }
+        Ok(inputs)
+    }
+
+    /// 片元入口函数输出的 location
+    pub fn fragment_outputs(&self, entry_point: &str) -> anyhow::Result<Vec<u32>> {
+        let function = &self
+            .entry_point(entry_point, naga::ShaderStage::Fragment)?
+            .function;
+        let mut outputs = Vec::new();
+        if let Some(result) = &function.result {
+            self.collect_locations(result.ty, result.binding.as_ref(), &mut outputs);
+        }
+        Ok(outputs.into_iter().map(|(location, _)| location).collect())
+    }
+
+    fn collect_locations(
+        &self,
+        ty: naga::Handle<naga::Type>,
+        binding: Option<&naga::Binding>,
+        locations: &mut Vec<(u32, naga::ScalarKind)>,
+    ) {
+        let inner = &self.ir.types[ty].inner;
+        match (binding, inner) {
+            (Some(naga::Binding::Location { location, .. }), _) => {
+                if let Some(scalar) = inner.scalar() {
+                    locations.push((*location, scalar.kind));
+                }
+            }
+            (None, naga::TypeInner::Struct { members, .. }) => {
+                for member in members {
+                    self.collect_locations(member.ty, member.binding.as_ref(), locations);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn storage_format(format: naga::StorageFormat) -> anyhow::Result<wgpu::TextureFormat> {
+    use naga::StorageFormat as Sf;
+    use wgpu::TextureFormat as Tf;
+    Ok(match format {
+        Sf::R32Uint => Tf::R32Uint,
+        Sf::R32Sint => Tf::R32Sint,
+        Sf::R32Float => Tf::R32Float,
+        Sf::Rg32Uint => Tf::Rg32Uint,
+        Sf::Rg32Sint => Tf::Rg32Sint,
+        Sf::Rg32Float => Tf::Rg32Float,
+        Sf::Rgba8Unorm => Tf::Rgba8Unorm,
+        Sf::Rgba8Snorm => Tf::Rgba8Snorm,
+        Sf::Rgba8Uint => Tf::Rgba8Uint,
+        Sf::Rgba8Sint => Tf::Rgba8Sint,
+        Sf::Bgra8Unorm => Tf::Bgra8Unorm,
+        Sf::Rgba16Uint => Tf::Rgba16Uint,
+        Sf::Rgba16Sint => Tf::Rgba16Sint,
+        Sf::Rgba16Float => Tf::Rgba16Float,
+        Sf::Rgba32Uint => Tf::Rgba32Uint,
+        Sf::Rgba32Sint => Tf::Rgba32Sint,
+        Sf::Rgba32Float => Tf::Rgba32Float,
+        _ => bail!("unsupported storage texture format {format:?}"),
+    })
+}
+
+/// 顶点格式在着色器中对应的分量类型
+fn vertex_format_kind(format: wgpu::VertexFormat) -> naga::ScalarKind {
+    use wgpu::VertexFormat as Vf;
+    match format {
+        Vf::Uint8 | Vf::Uint8x2 | Vf::Uint8x4 | Vf::Uint16 | Vf::Uint16x2 | Vf::Uint16x4 => {
+            naga::ScalarKind::Uint
+        }
+        Vf::Uint32 | Vf::Uint32x2 | Vf::Uint32x3 | Vf::Uint32x4 => naga::ScalarKind::Uint,
+        Vf::Sint8 | Vf::Sint8x2 | Vf::Sint8x4 | Vf::Sint16 | Vf::Sint16x2 | Vf::Sint16x4 => {
+            naga::ScalarKind::Sint
+        }
+        Vf::Sint32 | Vf::Sint32x2 | Vf::Sint32x3 | Vf::Sint32x4 => naga::ScalarKind::Sint,
+        _ => naga::ScalarKind::Float,
+    }
+}
+
+/// 以着色器反射为准创建渲染管线
+///
+/// 未通过 [`PipelineBuilder::bind_group_layout`] 指定的 group 按着色器声明自动生成 layout；
+/// 指定的 layout 条目、顶点缓冲与颜色目标都会和着色器对照，不一致时 `build` 返回错误。
+pub struct PipelineBuilder<'a> {
+    shader: &'a ReflectedShader,
+    label: Option<&'a str>,
+    vertex_entry: &'a str,
+    fragment_entry: Option<&'a str>,
+    vertex_buffers: Vec<wgpu::VertexBufferLayout<'a>>,
+    targets: Vec<Option<wgpu::ColorTargetState>>,
+    primitive: wgpu::PrimitiveState,
+    depth_stencil: Option<wgpu::DepthStencilState>,
+    multisample: wgpu::MultisampleState,
+    layouts: BTreeMap<u32, (&'a BindGroupLayout, Vec<BindGroupLayoutEntry>)>,
+    constants: HashMap<String, f64>,
+}
+
+/// [`PipelineBuilder::build`] 的结果，`bind_group_layouts` 按 group 编号排列
+pub struct ReflectedPipeline {
+    pub pipeline: wgpu::RenderPipeline,
+    pub bind_group_layouts: Vec<BindGroupLayout>,
+}
+
+impl<'a> PipelineBuilder<'a> {
+    /// 默认使用入口函数 `vs_main` 与 `fs_main`（着色器中没有 `fs_main` 时不设置片元阶段）
+    pub fn from_reflection(shader: &'a ReflectedShader) -> Self {
+        Self {
+            shader,
+            label: None,
+            vertex_entry: "vs_main",
+            fragment_entry: shader
+                .has_entry_point("fs_main", naga::ShaderStage::Fragment)
+                .then_some("fs_main"),
+            vertex_buffers: Vec::new(),
+            targets: Vec::new(),
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            layouts: BTreeMap::new(),
+            constants: HashMap::new(),
+        }
+    }
+
+    pub fn label(mut self, label: &'a str) -> Self {
+        self.label = Some(label);
+        self
+    }
+
+    pub fn vertex_entry(mut self, entry_point: &'a str) -> Self {
+        self.vertex_entry = entry_point;
+        self
+    }
+
+    pub fn fragment_entry(mut self, entry_point: Option<&'a str>) -> Self {
+        self.fragment_entry = entry_point;
+        self
+    }
+
+    pub fn vertex_buffer(mut self, layout: wgpu::VertexBufferLayout<'a>) -> Self {
+        self.vertex_buffers.push(layout);
+        self
+    }
+
+    pub fn color_target(mut self, target: impl Into<Option<wgpu::ColorTargetState>>) -> Self {
+        self.targets.push(target.into());
+        self
+    }
+
+    pub fn primitive(mut self, primitive: wgpu::PrimitiveState) -> Self {
+        self.primitive = primitive;
+        self
+    }
+
+    pub fn depth_stencil(mut self, depth_stencil: wgpu::DepthStencilState) -> Self {
+        self.depth_stencil = Some(depth_stencil);
+        self
+    }
+
+    pub fn multisample(mut self, multisample: wgpu::MultisampleState) -> Self {
+        self.multisample = multisample;
+        self
+    }
+
+    /// 在 `group` 使用已有的 layout（例如相机的），`entries` 为创建它时的条目，用于和着色器对照
+    pub fn bind_group_layout(
+        mut self,
+        group: u32,
+        layout: &'a BindGroupLayout,
+        entries: &[BindGroupLayoutEntry],
+    ) -> Self {
+        self.layouts.insert(group, (layout, entries.to_vec()));
+        self
+    }
+
+    /// 设置管线可覆盖常量（WGSL 中的 `override`）的值，布尔常量用 0 与 1 表示
+    ///
+    /// 同一个着色器可以按不同的值创建多条管线，例如阴影采样数或 SSAO 核大小等画质选项。
+    pub fn constant(mut self, key: &str, value: impl Into<f64>) -> Self {
+        self.constants.insert(key.to_string(), value.into());
+        self
+    }
+
+    /// 批量设置可覆盖常量，例如 [`alpha_test_constants`](crate::shader::alpha_test_constants) 的结果
+    pub fn constants(mut self, constants: &HashMap<String, f64>) -> Self {
+        self.constants
+            .extend(constants.iter().map(|(key, value)| (key.clone(), *value)));
+        self
+    }
+
+    fn validate(&self) -> anyhow::Result<()> {
+        let shader = self.shader;
+        for (group, (_, entries)) in &self.layouts {
+            validation::check_bind_group(&shader.ir, *group, entries)?;
+        }
+
+        // wgpu 会忽略不存在的键，这里提前报出拼写错误
+        let overrides = shader.overrides();
+        for (key, value) in &self.constants {
+            ensure!(
+                overrides.iter().any(|(name, _)| name == key),
+                "shader has no override constant `{key}`"
+            );
+            ensure!(value.is_finite(), "override constant `{key}` is not finite");
+        }
+        for (key, has_default) in &overrides {
+            ensure!(
+                *has_default || self.constants.contains_key(key),
+                "override constant `{key}` has no default and must be set"
+            );
+        }
+
+        for (location, kind) in shader.vertex_inputs(self.vertex_entry)? {
+            let attribute = self
+                .vertex_buffers
+                .iter()
+                .flat_map(|buffer| buffer.attributes)
+                .find(|attribute| attribute.shader_location == location)
+                .ok_or_else(|| anyhow!("no vertex attribute provides @location({location})"))?;
+            ensure!(
+                vertex_format_kind(attribute.format) == kind,
+                "vertex attribute @location({location}) is {:?} but the shader expects {kind:?}",
+                attribute.format
+            );
+        }
+
+        if let Some(fragment_entry) = self.fragment_entry {
+            for location in shader.fragment_outputs(fragment_entry)? {
+                ensure!(
+                    self.targets
+                        .get(location as usize)
+                        .is_some_and(|target| target.is_some()),
+                    "fragment output @location({location}) has no color target"
+                );
+            }
+        }
+        Ok(())
+    }
+
+    pub fn build(self, device: &Device) -> anyhow::Result<ReflectedPipeline> {
+        self.validate()?;
+        let label = self.label.unwrap_or("Reflected Pipeline");
+
+        let mut bind_group_layouts = Vec::new();
+        for group in 0..self
+            .shader
+            .group_count()
+            .max(self.layouts.keys().next_back().map_or(0, |group| group + 1))
+        {
+            let layout = match self.layouts.get(&group) {
+                Some((layout, _)) => (*layout).clone(),
+                None => device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                    label: Some(label),
+                    entries: &self.shader.bind_group_layout_entries(group)?,
+                }),
+            };
+            bind_group_layouts.push(layout);
+        }
+
+        let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some(label),
+            bind_group_layouts: &bind_group_layouts.iter().collect::<Vec<_>>(),
+            push_constant_ranges: &[],
+        });
+        let compilation_options = wgpu::PipelineCompilationOptions {
+            constants: &self.constants,
+            ..Default::default()
+        };
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some(label),
+            layout: Some(&layout),
+            vertex: wgpu::VertexState {
+                module: &self.shader.module,
+                compilation_options: compilation_options.clone(),
+                entry_point: Some(self.vertex_entry),
+                buffers: &self.vertex_buffers,
+            },
+            fragment: self.fragment_entry.map(|entry_point| wgpu::FragmentState {
+                module: &self.shader.module,
+                compilation_options: compilation_options.clone(),
+                entry_point: Some(entry_point),
+                targets: &self.targets,
+            }),
+            primitive: self.primitive,
+            depth_stencil: self.depth_stencil,
+            multisample: self.multisample,
+            multiview: None,
+            cache: None,
+        });
+
+        Ok(ReflectedPipeline {
+            pipeline,
+            bind_group_layouts,
+        })
+    }
+}
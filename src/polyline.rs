@@ -0,0 +1,315 @@
+use bytemuck::{Pod, Zeroable};
+use wgpu::{BindGroup, BindGroupLayout, Buffer, Device, Queue, RenderPipeline};
+use winit::dpi::PhysicalSize;
+
+use crate::{camera::Camera, model::RenderVertex, shader::ShaderLibrary};
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum LineWidth {
+    /// 屏幕上的像素宽度，与距离无关
+    Pixels(f32),
+    /// 世界空间中的宽度，随透视缩放
+    World(f32),
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum LineJoin {
+    /// 相邻线段外边缘延长相交，端点为平头
+    Miter,
+    /// 转角与端点均为圆形
+    Round,
+}
+
+#[derive(Debug, Copy, Clone)]
+pub struct LineStyle {
+    pub color: glam::Vec4,
+    pub width: LineWidth,
+    pub join: LineJoin,
+    /// 斜接长度与半宽之比的上限，超过时该转角不再斜接
+    pub miter_limit: f32,
+}
+
+impl Default for LineStyle {
+    fn default() -> Self {
+        Self {
+            color: glam::Vec4::ONE,
+            width: LineWidth::Pixels(2.0),
+            join: LineJoin::Miter,
+            miter_limit: 4.0,
+        }
+    }
+}
+
+/// 一条线段及其前后相邻的点，每个实例在顶点着色器中展开为一个四边形
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct LineSegmentRaw {
+    /// w 为 1 时表示存在前一个点
+    prev: [f32; 4],
+    start: [f32; 4],
+    end: [f32; 4],
+    /// w 为 1 时表示存在后一个点
+    next: [f32; 4],
+    color: [f32; 4],
+    /// x: 宽度, y: 是否为世界单位, z: 连接方式, w: 斜接上限
+    params: [f32; 4],
+}
+
+unsafe impl Zeroable for LineSegmentRaw {}
+unsafe impl Pod for LineSegmentRaw {}
+
+impl RenderVertex for LineSegmentRaw {
+    fn buffer_layout_desc<'a>() -> wgpu::VertexBufferLayout<'a> {
+        const ATTRIBUTES: [wgpu::VertexAttribute; 6] = wgpu::vertex_attr_array![
+            0 => Float32x4,
+            1 => Float32x4,
+            2 => Float32x4,
+            3 => Float32x4,
+            4 => Float32x4,
+            5 => Float32x4,
+        ];
+        wgpu::VertexBufferLayout {
+            array_stride: std::mem::size_of::<LineSegmentRaw>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Instance,
+            attributes: &ATTRIBUTES,
+        }
+    }
+}
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+struct PolylineUniform {
+    viewport: [f32; 4],
+}
+
+unsafe impl Zeroable for PolylineUniform {}
+unsafe impl Pod for PolylineUniform {}
+
+/// 在顶点着色器中把折线展开为屏幕空间四边形的渲染器，
+/// 支持任意宽度、斜接或圆角连接以及边缘抗锯齿
+///
+/// 折线以半透明混合绘制且不写入深度，应在不透明几何体之后绘制。
+pub struct PolylineRenderer {
+    segments: Vec<LineSegmentRaw>,
+    dirty: bool,
+    /// 已上传到 GPU 的线段数量
+    uploaded: u32,
+    segment_buffer: Buffer,
+    segment_capacity: usize,
+    uniform_buffer: Buffer,
+    bind_group: BindGroup,
+    pipeline: RenderPipeline,
+}
+
+impl PolylineRenderer {
+    const INITIAL_CAPACITY: usize = 256;
+
+    pub fn new(
+        device: &Device,
+        color_format: wgpu::TextureFormat,
+        depth_format: Option<wgpu::TextureFormat>,
+        camera_layout: &BindGroupLayout,
+    ) -> Self {
+        let uniform_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Polyline Uniform Buffer"),
+            size: std::mem::size_of::<PolylineUniform>() as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::VERTEX,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            }],
+            label: Some("polyline_bind_group_layout"),
+        });
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: &bind_group_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: uniform_buffer.as_entire_binding(),
+            }],
+            label: Some("polyline_bind_group"),
+        });
+
+        let shader = ShaderLibrary::new()
+            .create_shader_module(
+                device,
+                "Polyline Shader",
+                include_str!("../shaders/polyline.wgsl"),
+            )
+            .expect("built-in polyline shader");
+        let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Polyline Pipeline Layout"),
+            bind_group_layouts: &[camera_layout, &bind_group_layout],
+            push_constant_ranges: &[],
+        });
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Polyline Pipeline"),
+            layout: Some(&layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                compilation_options: Default::default(),
+                entry_point: Some("vs_main"),
+                buffers: &[LineSegmentRaw::buffer_layout_desc()],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                compilation_options: Default::default(),
+                entry_point: Some("fs_main"),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: color_format,
+                    blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            // 线段朝向取决于投影后的方向，不做背面剔除
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: depth_format.map(|format| wgpu::DepthStencilState {
+                format,
+                depth_write_enabled: false,
+                depth_compare: wgpu::CompareFunction::LessEqual,
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState::default(),
+            }),
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+            cache: None,
+        });
+
+        Self {
+            segments: Vec::new(),
+            dirty: false,
+            uploaded: 0,
+            segment_buffer: Self::create_segment_buffer(device, Self::INITIAL_CAPACITY),
+            segment_capacity: Self::INITIAL_CAPACITY,
+            uniform_buffer,
+            bind_group,
+            pipeline,
+        }
+    }
+
+    fn create_segment_buffer(device: &Device, capacity: usize) -> Buffer {
+        device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Polyline Segment Buffer"),
+            size: (std::mem::size_of::<LineSegmentRaw>() * capacity) as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        })
+    }
+
+    /// 移除所有折线
+    pub fn clear(&mut self) {
+        self.segments.clear();
+        self.dirty = true;
+    }
+
+    /// 添加一条依次连接 `points` 的开放折线，少于两个点时忽略
+    pub fn add_strip(&mut self, points: &[glam::Vec3], style: &LineStyle) {
+        self.add_segments(points, false, style);
+    }
+
+    /// 添加一条首尾相连的闭合折线，少于三个点时忽略
+    pub fn add_loop(&mut self, points: &[glam::Vec3], style: &LineStyle) {
+        if points.len() >= 3 {
+            self.add_segments(points, true, style);
+        }
+    }
+
+    fn add_segments(&mut self, points: &[glam::Vec3], closed: bool, style: &LineStyle) {
+        let n = points.len();
+        if n < 2 {
+            return;
+        }
+        let (width, world) = match style.width {
+            LineWidth::Pixels(width) => (width, 0.0),
+            LineWidth::World(width) => (width, 1.0),
+        };
+        let join = match style.join {
+            LineJoin::Miter => 0.0,
+            LineJoin::Round => 1.0,
+        };
+        let params = [width, world, join, style.miter_limit.max(1.0)];
+        // 闭合折线的首尾同样需要相邻点，取下标时按点数回绕
+        let neighbor = |i: isize| -> [f32; 4] {
+            if closed {
+                points[i.rem_euclid(n as isize) as usize]
+                    .extend(1.0)
+                    .to_array()
+            } else if (0..n as isize).contains(&i) {
+                points[i as usize].extend(1.0).to_array()
+            } else {
+                [0.0; 4]
+            }
+        };
+
+        let count = if closed { n } else { n - 1 };
+        self.segments.extend((0..count).map(|i| {
+            let i = i as isize;
+            LineSegmentRaw {
+                prev: neighbor(i - 1),
+                start: neighbor(i),
+                end: neighbor(i + 1),
+                next: neighbor(i + 2),
+                color: style.color.to_array(),
+                params,
+            }
+        }));
+        self.dirty = true;
+    }
+
+    pub fn segment_count(&self) -> usize {
+        self.segments.len()
+    }
+
+    /// 上传视口参数与自上次更新以来修改过的线段；缓冲不足时重新创建
+    pub fn update(
+        &mut self,
+        device: &Device,
+        queue: &Queue,
+        camera: &Camera,
+        size: PhysicalSize<u32>,
+    ) {
+        let uniform = PolylineUniform {
+            viewport: [
+                size.width as f32,
+                size.height as f32,
+                camera.projection_matrix().y_axis.y,
+                0.0,
+            ],
+        };
+        queue.write_buffer(&self.uniform_buffer, 0, bytemuck::cast_slice(&[uniform]));
+
+        if !self.dirty {
+            return;
+        }
+        if self.segments.len() > self.segment_capacity {
+            self.segment_capacity = self.segments.len().next_power_of_two();
+            self.segment_buffer = Self::create_segment_buffer(device, self.segment_capacity);
+        }
+        queue.write_buffer(
+            &self.segment_buffer,
+            0,
+            bytemuck::cast_slice(&self.segments),
+        );
+        self.uploaded = self.segments.len() as u32;
+        self.dirty = false;
+    }
+
+    pub fn draw(&self, render_pass: &mut wgpu::RenderPass, camera_bind_group: &BindGroup) {
+        if self.uploaded == 0 {
+            return;
+        }
+        render_pass.set_pipeline(&self.pipeline);
+        render_pass.set_bind_group(0, camera_bind_group, &[]);
+        render_pass.set_bind_group(1, &self.bind_group, &[]);
+        render_pass.set_vertex_buffer(0, self.segment_buffer.slice(..));
+        render_pass.draw(0..6, 0..self.uploaded);
+    }
+}
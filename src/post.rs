@@ -0,0 +1,271 @@
+use std::any::Any;
+
+use wgpu::{CommandEncoder, Device, Queue, RenderPipeline, SurfaceConfiguration, TextureView};
+
+use crate::{camera::Camera, shader::ShaderLibrary, texture::Texture};
+
+pub mod aerial;
+pub mod bloom;
+pub mod compare;
+pub mod debug_view;
+pub mod exposure;
+pub mod lens;
+pub mod motion_blur;
+pub mod ssr;
+pub mod taa;
+pub mod tonemap;
+
+/// G-buffer 中法线与粗糙度纹理的格式：xyz 为世界空间法线，w 为粗糙度
+pub const NORMAL_ROUGHNESS_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba16Float;
+
+/// 速度纹理的格式：屏幕空间 uv 的位移，满足 `上一帧 uv = 当前 uv - 速度`
+pub const VELOCITY_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rg16Float;
+
+/// 场景渲染阶段产出、供后处理读取的纹理
+pub struct SceneTextures<'a> {
+    pub color: &'a Texture,
+    pub depth: &'a Texture,
+    /// 法线为零的像素视为没有几何体
+    pub normal_roughness: Option<&'a Texture>,
+    /// 逐物体速度，格式为 [`VELOCITY_FORMAT`]；没有时只能由深度重投影得到相机运动
+    pub velocity: Option<&'a Texture>,
+}
+
+/// 后处理栈中的一个节点
+pub trait PostEffect: Any {
+    fn label(&self) -> &str;
+
+    fn resize(&mut self, _device: &Device, _config: &SurfaceConfiguration) {}
+
+    fn update(&mut self, _queue: &Queue, _camera: &Camera) {}
+
+    /// 读取 `input` 并把结果写入 `output`，`output` 的格式为 [`PostStack::format`]
+    fn apply(
+        &self,
+        device: &Device,
+        encoder: &mut CommandEncoder,
+        input: &Texture,
+        scene: &SceneTextures,
+        output: &TextureView,
+    );
+}
+
+struct PostNode {
+    enabled: bool,
+    effect: Box<dyn PostEffect>,
+}
+
+/// 按顺序执行的后处理链，效果之间在两张中间纹理上来回切换，
+/// 最后拷贝到输出（通常是 surface）上
+pub struct PostStack {
+    format: wgpu::TextureFormat,
+    nodes: Vec<PostNode>,
+    targets: [Texture; 2],
+    blit_pipeline: RenderPipeline,
+    blit_layout: wgpu::BindGroupLayout,
+}
+
+impl PostStack {
+    /// `format` 为场景颜色与中间纹理的格式，`config.format` 为最终输出的格式
+    pub fn new(
+        device: &Device,
+        config: &SurfaceConfiguration,
+        format: wgpu::TextureFormat,
+    ) -> Self {
+        let blit_layout = Texture::texture_bind_group_layout(device);
+        let shader = ShaderLibrary::new()
+            .create_shader_module(device, "Blit Shader", include_str!("../shaders/blit.wgsl"))
+            .expect("built-in blit shader");
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Blit Pipeline Layout"),
+            bind_group_layouts: &[&blit_layout],
+            push_constant_ranges: &[],
+        });
+        let blit_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Blit Pipeline"),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                compilation_options: Default::default(),
+                entry_point: Some("vs_main"),
+                buffers: &[],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                compilation_options: Default::default(),
+                entry_point: Some("fs_main"),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: config.format,
+                    blend: None,
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+            cache: None,
+        });
+
+        Self {
+            format,
+            nodes: Vec::new(),
+            targets: Self::create_targets(device, config, format),
+            blit_pipeline,
+            blit_layout,
+        }
+    }
+
+    fn create_targets(
+        device: &Device,
+        config: &SurfaceConfiguration,
+        format: wgpu::TextureFormat,
+    ) -> [Texture; 2] {
+        [
+            Texture::create_color_target(device, config, format, "post_target_0"),
+            Texture::create_color_target(device, config, format, "post_target_1"),
+        ]
+    }
+
+    pub fn format(&self) -> wgpu::TextureFormat {
+        self.format
+    }
+
+    pub fn push(&mut self, effect: impl PostEffect) {
+        self.nodes.push(PostNode {
+            enabled: true,
+            effect: Box::new(effect),
+        });
+    }
+
+    /// 按标签启用或禁用效果，找不到时返回 false
+    pub fn set_enabled(&mut self, label: &str, enabled: bool) -> bool {
+        match self.nodes.iter_mut().find(|n| n.effect.label() == label) {
+            Some(node) => {
+                node.enabled = enabled;
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn is_enabled(&self, label: &str) -> bool {
+        self.nodes
+            .iter()
+            .any(|n| n.enabled && n.effect.label() == label)
+    }
+
+    /// 取出栈中第一个类型为 `T` 的效果以便调整参数
+    pub fn get_mut<T: PostEffect>(&mut self) -> Option<&mut T> {
+        self.nodes.iter_mut().find_map(|n| {
+            let effect: &mut dyn Any = n.effect.as_mut();
+            effect.downcast_mut::<T>()
+        })
+    }
+
+    pub fn resize(&mut self, device: &Device, config: &SurfaceConfiguration) {
+        self.targets = Self::create_targets(device, config, self.format);
+        for node in &mut self.nodes {
+            node.effect.resize(device, config);
+        }
+    }
+
+    pub fn update(&mut self, queue: &Queue, camera: &Camera) {
+        for node in self.nodes.iter_mut().filter(|n| n.enabled) {
+            node.effect.update(queue, camera);
+        }
+    }
+
+    pub fn run(
+        &self,
+        device: &Device,
+        encoder: &mut CommandEncoder,
+        scene: &SceneTextures,
+        output: &TextureView,
+    ) {
+        let mut input = scene.color;
+        for (i, node) in self.nodes.iter().filter(|n| n.enabled).enumerate() {
+            let target = &self.targets[i % 2];
+            node.effect
+                .apply(device, encoder, input, scene, &target.view);
+            input = target;
+        }
+        self.blit(device, encoder, input, output);
+    }
+
+    fn blit(
+        &self,
+        device: &Device,
+        encoder: &mut CommandEncoder,
+        source: &Texture,
+        output: &TextureView,
+    ) {
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: &self.blit_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(&source.view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(&source.sampler),
+                },
+            ],
+            label: Some("blit_bind_group"),
+        });
+        let mut pass = begin_fullscreen_pass(encoder, "Blit Pass", output);
+        pass.set_pipeline(&self.blit_pipeline);
+        pass.set_bind_group(0, &bind_group, &[]);
+        pass.draw(0..3, 0..1);
+    }
+}
+
+/// 创建 1x1 的全零纹理，在场景没有提供可选输入时占位
+pub(crate) fn placeholder_texture(
+    device: &Device,
+    format: wgpu::TextureFormat,
+    label: &str,
+) -> Texture {
+    let texture = device.create_texture(&wgpu::TextureDescriptor {
+        label: Some(label),
+        size: wgpu::Extent3d {
+            width: 1,
+            height: 1,
+            depth_or_array_layers: 1,
+        },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format,
+        usage: wgpu::TextureUsages::TEXTURE_BINDING,
+        view_formats: &[],
+    });
+    let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+    let sampler = device.create_sampler(&wgpu::SamplerDescriptor::default());
+    Texture {
+        texture,
+        view,
+        sampler,
+    }
+}
+
+/// 开始一个只有单个颜色附件、不清除内容的全屏绘制通道
+pub fn begin_fullscreen_pass<'a>(
+    encoder: &'a mut CommandEncoder,
+    label: &str,
+    output: &TextureView,
+) -> wgpu::RenderPass<'a> {
+    encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+        label: Some(label),
+        color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+            view: output,
+            resolve_target: None,
+            ops: wgpu::Operations {
+                load: wgpu::LoadOp::Load,
+                store: wgpu::StoreOp::Store,
+            },
+        })],
+        ..Default::default()
+    })
+}
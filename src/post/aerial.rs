@@ -0,0 +1,181 @@
+use bytemuck::{Pod, Zeroable};
+use wgpu::{CommandEncoder, Device, Queue, TextureView};
+
+use super::{begin_fullscreen_pass, PostEffect, SceneTextures};
+use crate::{
+    camera::Camera, environment::EnvironmentBundle, shader::ShaderLibrary, texture::Texture,
+};
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+struct AerialUniform {
+    inv_view_proj: [[f32; 4]; 4],
+    eye: [f32; 4],
+}
+
+unsafe impl Zeroable for AerialUniform {}
+unsafe impl Pod for AerialUniform {}
+
+/// 空气透视：按距离与高度衰减远处几何体的颜色，并混入对应方向的天空颜色
+///
+/// 散射参数与太阳方向来自 [`EnvironmentBundle`]，修改后调用其 `update` 即可生效。
+pub struct AerialPerspective {
+    /// 天空像素按该距离计算散射，设为 0 时不影响天空；为 `None` 时使用相机的 zfar
+    pub sky_distance: Option<f32>,
+    buffer: wgpu::Buffer,
+    bind_group_layout: wgpu::BindGroupLayout,
+    environment_bind_group: wgpu::BindGroup,
+    pipeline: wgpu::RenderPipeline,
+}
+
+impl AerialPerspective {
+    pub const LABEL: &'static str = "aerial_perspective";
+
+    pub fn new(
+        device: &Device,
+        format: wgpu::TextureFormat,
+        environment: &EnvironmentBundle,
+    ) -> Self {
+        let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Aerial Perspective Uniform Buffer"),
+            size: std::mem::size_of::<AerialUniform>() as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Depth,
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+            ],
+            label: Some("aerial_perspective_bind_group_layout"),
+        });
+
+        let shader = ShaderLibrary::new()
+            .create_shader_module(
+                device,
+                "Aerial Perspective Shader",
+                include_str!("../../shaders/aerial_perspective.wgsl"),
+            )
+            .expect("built-in aerial perspective shader");
+        let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Aerial Perspective Pipeline Layout"),
+            bind_group_layouts: &[&bind_group_layout, &environment.bind_group_layout],
+            push_constant_ranges: &[],
+        });
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Aerial Perspective Pipeline"),
+            layout: Some(&layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                compilation_options: Default::default(),
+                entry_point: Some("vs_main"),
+                buffers: &[],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                compilation_options: Default::default(),
+                entry_point: Some("fs_main"),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format,
+                    blend: None,
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+            cache: None,
+        });
+
+        Self {
+            sky_distance: None,
+            buffer,
+            bind_group_layout,
+            environment_bind_group: environment.bind_group.clone(),
+            pipeline,
+        }
+    }
+}
+
+impl PostEffect for AerialPerspective {
+    fn label(&self) -> &str {
+        Self::LABEL
+    }
+
+    fn update(&mut self, queue: &Queue, camera: &Camera) {
+        let uniform = AerialUniform {
+            inv_view_proj: camera
+                .build_view_projection_matrix()
+                .inverse()
+                .to_cols_array_2d(),
+            eye: camera
+                .eye
+                .extend(self.sky_distance.unwrap_or(camera.zfar))
+                .to_array(),
+        };
+        queue.write_buffer(&self.buffer, 0, bytemuck::cast_slice(&[uniform]));
+    }
+
+    fn apply(
+        &self,
+        device: &Device,
+        encoder: &mut CommandEncoder,
+        input: &Texture,
+        scene: &SceneTextures,
+        output: &TextureView,
+    ) {
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: &self.bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: self.buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::TextureView(&input.view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: wgpu::BindingResource::TextureView(&scene.depth.view),
+                },
+            ],
+            label: Some("aerial_perspective_bind_group"),
+        });
+
+        let mut pass = begin_fullscreen_pass(encoder, "Aerial Perspective Pass", output);
+        pass.set_pipeline(&self.pipeline);
+        pass.set_bind_group(0, &bind_group, &[]);
+        pass.set_bind_group(1, &self.environment_bind_group, &[]);
+        pass.draw(0..3, 0..1);
+    }
+}
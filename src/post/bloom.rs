@@ -0,0 +1,315 @@
+use wgpu::{CommandEncoder, Device, Queue, SurfaceConfiguration, TextureView};
+
+use super::{begin_fullscreen_pass, PostEffect, SceneTextures};
+use crate::{camera::Camera, shader::ShaderLibrary, texture::Texture, uniform::GpuUniform};
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone, GpuUniform)]
+struct BloomUniform {
+    params: [f32; 4],
+}
+
+/// 泛光：HDR 画面中超过阈值的部分逐级降采样模糊后叠加回画面，应放在色调映射之前
+///
+/// 默认阈值为 1，只有亮度超过显示范围的像素（例如自发光材质）才会产生光晕。
+pub struct Bloom {
+    pub threshold: f32,
+    /// 阈值附近的软过渡宽度，为 0 时是硬阈值
+    pub knee: f32,
+    pub intensity: f32,
+    /// 上采样滤波的半径，以纹素计，越大光晕越柔和
+    pub radius: f32,
+    /// 半分辨率起始的降采样链，每级一个 mip 视图
+    chain: Vec<TextureView>,
+    sampler: wgpu::Sampler,
+    buffer: wgpu::Buffer,
+    bind_group: wgpu::BindGroup,
+    texture_layout: wgpu::BindGroupLayout,
+    prefilter_pipeline: wgpu::RenderPipeline,
+    downsample_pipeline: wgpu::RenderPipeline,
+    upsample_pipeline: wgpu::RenderPipeline,
+    composite_pipeline: wgpu::RenderPipeline,
+}
+
+impl Bloom {
+    pub const LABEL: &'static str = "bloom";
+    /// 降采样链的最大级数
+    pub const MAX_LEVELS: u32 = 6;
+    const CHAIN_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba16Float;
+
+    pub fn new(
+        device: &Device,
+        config: &SurfaceConfiguration,
+        format: wgpu::TextureFormat,
+    ) -> Self {
+        let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Bloom Uniform Buffer"),
+            size: std::mem::size_of::<BloomUniform>() as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            }],
+            label: Some("bloom_bind_group_layout"),
+        });
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: &bind_group_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: buffer.as_entire_binding(),
+            }],
+            label: Some("bloom_bind_group"),
+        });
+        let texture_layout = Texture::texture_bind_group_layout(device);
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("Bloom Sampler"),
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+
+        let shader = ShaderLibrary::new()
+            .create_shader_module(
+                device,
+                "Bloom Shader",
+                include_str!("../../shaders/bloom.wgsl"),
+            )
+            .expect("built-in bloom shader");
+        let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Bloom Pipeline Layout"),
+            bind_group_layouts: &[&texture_layout, &bind_group_layout],
+            push_constant_ranges: &[],
+        });
+        let composite_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Bloom Composite Pipeline Layout"),
+            bind_group_layouts: &[&texture_layout, &bind_group_layout, &texture_layout],
+            push_constant_ranges: &[],
+        });
+        let pipeline = |label, layout, entry_point, format, blend| {
+            device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                label: Some(label),
+                layout: Some(layout),
+                vertex: wgpu::VertexState {
+                    module: &shader,
+                    compilation_options: Default::default(),
+                    entry_point: Some("vs_main"),
+                    buffers: &[],
+                },
+                fragment: Some(wgpu::FragmentState {
+                    module: &shader,
+                    compilation_options: Default::default(),
+                    entry_point: Some(entry_point),
+                    targets: &[Some(wgpu::ColorTargetState {
+                        format,
+                        blend,
+                        write_mask: wgpu::ColorWrites::ALL,
+                    })],
+                }),
+                primitive: wgpu::PrimitiveState::default(),
+                depth_stencil: None,
+                multisample: wgpu::MultisampleState::default(),
+                multiview: None,
+                cache: None,
+            })
+        };
+        let additive = wgpu::BlendState {
+            color: wgpu::BlendComponent {
+                src_factor: wgpu::BlendFactor::One,
+                dst_factor: wgpu::BlendFactor::One,
+                operation: wgpu::BlendOperation::Add,
+            },
+            alpha: wgpu::BlendComponent::REPLACE,
+        };
+        let prefilter_pipeline = pipeline(
+            "Bloom Prefilter Pipeline",
+            &layout,
+            "fs_prefilter",
+            Self::CHAIN_FORMAT,
+            None,
+        );
+        let downsample_pipeline = pipeline(
+            "Bloom Downsample Pipeline",
+            &layout,
+            "fs_downsample",
+            Self::CHAIN_FORMAT,
+            None,
+        );
+        let upsample_pipeline = pipeline(
+            "Bloom Upsample Pipeline",
+            &layout,
+            "fs_upsample",
+            Self::CHAIN_FORMAT,
+            Some(additive),
+        );
+        let composite_pipeline = pipeline(
+            "Bloom Composite Pipeline",
+            &composite_layout,
+            "fs_composite",
+            format,
+            None,
+        );
+
+        let chain = Self::create_chain(device, config);
+
+        Self {
+            threshold: 1.0,
+            knee: 0.5,
+            intensity: 0.3,
+            radius: 1.0,
+            chain,
+            sampler,
+            buffer,
+            bind_group,
+            texture_layout,
+            prefilter_pipeline,
+            downsample_pipeline,
+            upsample_pipeline,
+            composite_pipeline,
+        }
+    }
+
+    fn create_chain(device: &Device, config: &SurfaceConfiguration) -> Vec<TextureView> {
+        let width = (config.width / 2).max(1);
+        let height = (config.height / 2).max(1);
+        // 最小一级不小于 2 个像素
+        let levels = (width.min(height).max(2).ilog2()).clamp(1, Self::MAX_LEVELS);
+        let chain = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Bloom Chain"),
+            size: wgpu::Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: levels,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: Self::CHAIN_FORMAT,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+            view_formats: &[],
+        });
+        (0..levels)
+            .map(|level| {
+                chain.create_view(&wgpu::TextureViewDescriptor {
+                    label: Some("Bloom Chain Level"),
+                    base_mip_level: level,
+                    mip_level_count: Some(1),
+                    ..Default::default()
+                })
+            })
+            .collect()
+    }
+
+    fn texture_bind_group(&self, device: &Device, view: &TextureView) -> wgpu::BindGroup {
+        device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: &self.texture_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(&self.sampler),
+                },
+            ],
+            label: Some("bloom_texture_bind_group"),
+        })
+    }
+
+    fn draw(
+        &self,
+        device: &Device,
+        encoder: &mut CommandEncoder,
+        label: &str,
+        pipeline: &wgpu::RenderPipeline,
+        source: &TextureView,
+        target: &TextureView,
+    ) {
+        let source = self.texture_bind_group(device, source);
+        let mut pass = begin_fullscreen_pass(encoder, label, target);
+        pass.set_pipeline(pipeline);
+        pass.set_bind_group(0, &source, &[]);
+        pass.set_bind_group(1, &self.bind_group, &[]);
+        pass.draw(0..3, 0..1);
+    }
+}
+
+impl PostEffect for Bloom {
+    fn label(&self) -> &str {
+        Self::LABEL
+    }
+
+    fn resize(&mut self, device: &Device, config: &SurfaceConfiguration) {
+        self.chain = Self::create_chain(device, config);
+    }
+
+    fn update(&mut self, queue: &Queue, _camera: &Camera) {
+        BloomUniform {
+            params: [
+                self.threshold.max(0.0),
+                self.knee.max(0.0),
+                self.intensity.max(0.0),
+                self.radius.max(0.0),
+            ],
+        }
+        .write_to(queue, &self.buffer);
+    }
+
+    fn apply(
+        &self,
+        device: &Device,
+        encoder: &mut CommandEncoder,
+        input: &Texture,
+        _scene: &SceneTextures,
+        output: &TextureView,
+    ) {
+        let views = &self.chain;
+        self.draw(
+            device,
+            encoder,
+            "Bloom Prefilter Pass",
+            &self.prefilter_pipeline,
+            &input.view,
+            &views[0],
+        );
+        for level in 1..views.len() {
+            self.draw(
+                device,
+                encoder,
+                "Bloom Downsample Pass",
+                &self.downsample_pipeline,
+                &views[level - 1],
+                &views[level],
+            );
+        }
+        // 由小到大逐级上采样，叠加到上一级已有的降采样结果上
+        for level in (1..views.len()).rev() {
+            self.draw(
+                device,
+                encoder,
+                "Bloom Upsample Pass",
+                &self.upsample_pipeline,
+                &views[level],
+                &views[level - 1],
+            );
+        }
+
+        let scene = self.texture_bind_group(device, &input.view);
+        let glow = self.texture_bind_group(device, &views[0]);
+        let mut pass = begin_fullscreen_pass(encoder, "Bloom Composite Pass", output);
+        pass.set_pipeline(&self.composite_pipeline);
+        pass.set_bind_group(0, &scene, &[]);
+        pass.set_bind_group(1, &self.bind_group, &[]);
+        pass.set_bind_group(2, &glow, &[]);
+        pass.draw(0..3, 0..1);
+    }
+}
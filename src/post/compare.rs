@@ -0,0 +1,246 @@
+use bytemuck::{Pod, Zeroable};
+use wgpu::{CommandEncoder, Device, Queue, SurfaceConfiguration, TextureView};
+
+use super::begin_fullscreen_pass;
+use crate::{shader::ShaderLibrary, texture::Texture};
+
+/// [`ComparePass`] 把两张画面显示在一起的方式
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum CompareMode {
+    /// 分割线左侧显示左边的画面，右侧显示右边的画面，分割线可以拖动
+    Split,
+    /// 两侧各显示画面中间的一半
+    SideBySide,
+    /// 放大后的逐像素差异，完全一致时为黑色
+    Difference,
+}
+
+impl CompareMode {
+    /// 按 Split、SideBySide、Difference 的顺序切换
+    pub fn next(self) -> Self {
+        match self {
+            CompareMode::Split => CompareMode::SideBySide,
+            CompareMode::SideBySide => CompareMode::Difference,
+            CompareMode::Difference => CompareMode::Split,
+        }
+    }
+}
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+struct CompareUniform {
+    params: [f32; 4],
+}
+
+unsafe impl Zeroable for CompareUniform {}
+unsafe impl Pod for CompareUniform {}
+
+/// 对照两条渲染路径的调试视图
+///
+/// 同一个场景以两种配置（例如前向与延迟、MSAA 与 TAA）分别绘制到 [`ComparePass::left`] 与
+/// [`ComparePass::right`]，再由 [`ComparePass::composite`] 按 [`CompareMode`] 显示到输出上，
+/// 用于确认新的渲染路径与原有结果一致。两张纹理与 surface 大小相同，格式为创建时指定的格式。
+pub struct ComparePass {
+    pub mode: CompareMode,
+    /// 分割线的位置，为画面宽度的比例
+    pub split: f32,
+    /// [`CompareMode::Difference`] 下差异的放大倍数
+    pub difference_scale: f32,
+    format: wgpu::TextureFormat,
+    targets: [Texture; 2],
+    dragging: bool,
+    buffer: wgpu::Buffer,
+    bind_group_layout: wgpu::BindGroupLayout,
+    bind_group: wgpu::BindGroup,
+    pipeline: wgpu::RenderPipeline,
+}
+
+impl ComparePass {
+    /// `format` 为两张对照纹理的格式，`config.format` 为输出的格式
+    pub fn new(
+        device: &Device,
+        config: &SurfaceConfiguration,
+        format: wgpu::TextureFormat,
+    ) -> Self {
+        let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Compare Uniform Buffer"),
+            size: std::mem::size_of::<CompareUniform>() as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let texture_entry = |binding| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility: wgpu::ShaderStages::FRAGMENT,
+            ty: wgpu::BindingType::Texture {
+                sample_type: wgpu::TextureSampleType::Float { filterable: false },
+                view_dimension: wgpu::TextureViewDimension::D2,
+                multisampled: false,
+            },
+            count: None,
+        };
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &[
+                texture_entry(0),
+                texture_entry(1),
+                wgpu::BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+            ],
+            label: Some("compare_bind_group_layout"),
+        });
+
+        let shader = ShaderLibrary::new()
+            .create_shader_module(
+                device,
+                "Compare Shader",
+                include_str!("../../shaders/compare.wgsl"),
+            )
+            .expect("built-in compare shader");
+        let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Compare Pipeline Layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Compare Pipeline"),
+            layout: Some(&layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                compilation_options: Default::default(),
+                entry_point: Some("vs_main"),
+                buffers: &[],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                compilation_options: Default::default(),
+                entry_point: Some("fs_main"),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: config.format,
+                    blend: None,
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+            cache: None,
+        });
+
+        let targets = Self::create_targets(device, config, format);
+        let bind_group = Self::create_bind_group(device, &bind_group_layout, &targets, &buffer);
+        Self {
+            mode: CompareMode::Split,
+            split: 0.5,
+            difference_scale: 8.0,
+            format,
+            targets,
+            dragging: false,
+            buffer,
+            bind_group_layout,
+            bind_group,
+            pipeline,
+        }
+    }
+
+    fn create_targets(
+        device: &Device,
+        config: &SurfaceConfiguration,
+        format: wgpu::TextureFormat,
+    ) -> [Texture; 2] {
+        [
+            Texture::create_color_target(device, config, format, "compare_left"),
+            Texture::create_color_target(device, config, format, "compare_right"),
+        ]
+    }
+
+    fn create_bind_group(
+        device: &Device,
+        layout: &wgpu::BindGroupLayout,
+        targets: &[Texture; 2],
+        buffer: &wgpu::Buffer,
+    ) -> wgpu::BindGroup {
+        device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(&targets[0].view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::TextureView(&targets[1].view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: buffer.as_entire_binding(),
+                },
+            ],
+            label: Some("compare_bind_group"),
+        })
+    }
+
+    pub fn resize(&mut self, device: &Device, config: &SurfaceConfiguration) {
+        self.targets = Self::create_targets(device, config, self.format);
+        self.bind_group =
+            Self::create_bind_group(device, &self.bind_group_layout, &self.targets, &self.buffer);
+    }
+
+    pub fn format(&self) -> wgpu::TextureFormat {
+        self.format
+    }
+
+    /// 第一种配置的绘制目标
+    pub fn left(&self) -> &TextureView {
+        &self.targets[0].view
+    }
+
+    /// 第二种配置的绘制目标
+    pub fn right(&self) -> &TextureView {
+        &self.targets[1].view
+    }
+
+    /// 鼠标左键按下或松开，`x` 为光标横坐标占画面宽度的比例；
+    /// 分割模式下按下时把分割线移到光标处并开始拖动，返回是否处理了该事件
+    pub fn mouse_button(&mut self, pressed: bool, x: f32) -> bool {
+        if !pressed {
+            return std::mem::take(&mut self.dragging);
+        }
+        self.dragging = self.mode == CompareMode::Split;
+        self.drag(x)
+    }
+
+    /// 光标移动，拖动中时把分割线移到 `x`，返回是否处理了该事件
+    pub fn drag(&mut self, x: f32) -> bool {
+        if self.dragging {
+            self.split = x.clamp(0.0, 1.0);
+        }
+        self.dragging
+    }
+
+    /// 把两张纹理按当前模式绘制到 `output`，`output` 应与两张纹理大小相同
+    pub fn composite(&self, queue: &Queue, encoder: &mut CommandEncoder, output: &TextureView) {
+        let mode = match self.mode {
+            CompareMode::Split => 0.0,
+            CompareMode::SideBySide => 1.0,
+            CompareMode::Difference => 2.0,
+        };
+        let width = self.targets[0].texture.width() as f32;
+        let uniform = CompareUniform {
+            params: [mode, self.split * width, self.difference_scale, 0.0],
+        };
+        queue.write_buffer(&self.buffer, 0, bytemuck::bytes_of(&uniform));
+
+        let mut pass = begin_fullscreen_pass(encoder, "Compare Pass", output);
+        pass.set_pipeline(&self.pipeline);
+        pass.set_bind_group(0, &self.bind_group, &[]);
+        pass.draw(0..3, 0..1);
+    }
+}
@@ -0,0 +1,287 @@
+use bytemuck::{Pod, Zeroable};
+use wgpu::{CommandEncoder, Device, Queue, TextureView};
+
+use super::{begin_fullscreen_pass, SceneTextures};
+use crate::{camera::Camera, shader::ShaderLibrary, texture::Texture};
+
+/// 过度绘制计数纹理的格式
+pub const OVERDRAW_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::R16Float;
+
+/// 统计过度绘制时使用的混合：片元着色器输出 1，关闭深度测试，每个片元把计数加一
+pub const OVERDRAW_BLEND: wgpu::BlendState = wgpu::BlendState {
+    color: wgpu::BlendComponent {
+        src_factor: wgpu::BlendFactor::One,
+        dst_factor: wgpu::BlendFactor::One,
+        operation: wgpu::BlendOperation::Add,
+    },
+    alpha: wgpu::BlendComponent {
+        src_factor: wgpu::BlendFactor::One,
+        dst_factor: wgpu::BlendFactor::One,
+        operation: wgpu::BlendOperation::Add,
+    },
+};
+
+/// [`DebugViewPass`] 显示的内容
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum DebugView {
+    /// 正常的画面，调试视图不绘制
+    #[default]
+    Final,
+    Albedo,
+    /// 世界空间法线，映射到 [0, 1]
+    Normals,
+    /// 按相机近远平面线性化后的深度
+    Depth,
+    ShadowMap,
+    /// 每个像素被绘制的次数，以热力图显示
+    Overdraw,
+}
+
+impl DebugView {
+    /// 按声明的顺序切换，最后一个之后回到 [`DebugView::Final`]
+    pub fn next(self) -> Self {
+        match self {
+            DebugView::Final => DebugView::Albedo,
+            DebugView::Albedo => DebugView::Normals,
+            DebugView::Normals => DebugView::Depth,
+            DebugView::Depth => DebugView::ShadowMap,
+            DebugView::ShadowMap => DebugView::Overdraw,
+            DebugView::Overdraw => DebugView::Final,
+        }
+    }
+}
+
+/// 调试视图额外读取的纹理，[`SceneTextures`] 中没有的中间结果
+#[derive(Default)]
+pub struct DebugTextures<'a> {
+    pub albedo: Option<&'a Texture>,
+    /// 深度格式的阴影贴图或阴影图集，拉伸到整个画面显示
+    pub shadow_map: Option<&'a Texture>,
+    /// 格式为 [`OVERDRAW_FORMAT`]，以 [`OVERDRAW_BLEND`] 绘制场景得到
+    pub overdraw: Option<&'a Texture>,
+}
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+struct DebugViewUniform {
+    params: [f32; 4],
+}
+
+unsafe impl Zeroable for DebugViewUniform {}
+unsafe impl Pod for DebugViewUniform {}
+
+/// 把渲染管线的中间结果画到输出上的调试视图
+///
+/// [`DebugView::Final`] 时 [`DebugViewPass::is_active`] 为 false，应用照常运行后处理栈；
+/// 其余视图用 [`DebugViewPass::draw`] 代替后处理。所需的纹理缺失时显示品红色的棋盘格。
+pub struct DebugViewPass {
+    pub view: DebugView,
+    /// 过度绘制热力图中显示为红色的层数
+    pub overdraw_max: f32,
+    near: f32,
+    far: f32,
+    buffer: wgpu::Buffer,
+    bind_group_layout: wgpu::BindGroupLayout,
+    pipeline: wgpu::RenderPipeline,
+    /// 缺少对应纹理时绑定的 1x1 占位纹理
+    fallback_color: TextureView,
+    fallback_depth: TextureView,
+}
+
+impl DebugViewPass {
+    /// `format` 为输出的格式
+    pub fn new(device: &Device, format: wgpu::TextureFormat) -> Self {
+        let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Debug View Uniform Buffer"),
+            size: std::mem::size_of::<DebugViewUniform>() as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let texture_entry = |binding, sample_type| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility: wgpu::ShaderStages::FRAGMENT,
+            ty: wgpu::BindingType::Texture {
+                sample_type,
+                view_dimension: wgpu::TextureViewDimension::D2,
+                multisampled: false,
+            },
+            count: None,
+        };
+        let float = wgpu::TextureSampleType::Float { filterable: false };
+        let depth = wgpu::TextureSampleType::Depth;
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                texture_entry(1, float),
+                texture_entry(2, float),
+                texture_entry(3, depth),
+                texture_entry(4, depth),
+                texture_entry(5, float),
+            ],
+            label: Some("debug_view_bind_group_layout"),
+        });
+
+        let shader = ShaderLibrary::new()
+            .create_shader_module(
+                device,
+                "Debug View Shader",
+                include_str!("../../shaders/debug_view.wgsl"),
+            )
+            .expect("built-in debug view shader");
+        let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Debug View Pipeline Layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Debug View Pipeline"),
+            layout: Some(&layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                compilation_options: Default::default(),
+                entry_point: Some("vs_main"),
+                buffers: &[],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                compilation_options: Default::default(),
+                entry_point: Some("fs_main"),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format,
+                    blend: None,
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+            cache: None,
+        });
+
+        let fallback = |format, label| {
+            device
+                .create_texture(&wgpu::TextureDescriptor {
+                    label: Some(label),
+                    size: wgpu::Extent3d {
+                        width: 1,
+                        height: 1,
+                        depth_or_array_layers: 1,
+                    },
+                    mip_level_count: 1,
+                    sample_count: 1,
+                    dimension: wgpu::TextureDimension::D2,
+                    format,
+                    usage: wgpu::TextureUsages::TEXTURE_BINDING,
+                    view_formats: &[],
+                })
+                .create_view(&wgpu::TextureViewDescriptor::default())
+        };
+
+        Self {
+            view: DebugView::Final,
+            overdraw_max: 8.0,
+            near: 0.1,
+            far: 100.0,
+            buffer,
+            bind_group_layout,
+            pipeline,
+            fallback_color: fallback(wgpu::TextureFormat::Rgba8Unorm, "debug_view_fallback"),
+            fallback_depth: fallback(Texture::DEPTH_FORMAT, "debug_view_fallback_depth"),
+        }
+    }
+
+    /// 是否需要用调试视图代替正常的画面
+    pub fn is_active(&self) -> bool {
+        self.view != DebugView::Final
+    }
+
+    /// 记录线性化深度所需的近远平面
+    pub fn update(&mut self, camera: &Camera) {
+        self.near = camera.znear;
+        self.far = camera.zfar;
+    }
+
+    /// 按当前视图把中间结果绘制到 `output`，`output` 应与场景纹理大小相同；
+    /// [`DebugView::Final`] 时什么也不做
+    pub fn draw(
+        &self,
+        device: &Device,
+        queue: &Queue,
+        encoder: &mut CommandEncoder,
+        scene: &SceneTextures,
+        textures: &DebugTextures,
+        output: &TextureView,
+    ) {
+        let mode = match self.view {
+            DebugView::Final => return,
+            DebugView::Albedo => textures.albedo.map(|_| 1.0),
+            DebugView::Normals => scene.normal_roughness.map(|_| 2.0),
+            DebugView::Depth => Some(3.0),
+            DebugView::ShadowMap => textures.shadow_map.map(|_| 4.0),
+            DebugView::Overdraw => textures.overdraw.map(|_| 5.0),
+        }
+        .unwrap_or(6.0);
+        let uniform = DebugViewUniform {
+            params: [mode, self.near, self.far, self.overdraw_max.max(1.0)],
+        };
+        queue.write_buffer(&self.buffer, 0, bytemuck::bytes_of(&uniform));
+
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: &self.bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: self.buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::TextureView(
+                        textures.albedo.map_or(&self.fallback_color, |t| &t.view),
+                    ),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: wgpu::BindingResource::TextureView(
+                        scene
+                            .normal_roughness
+                            .map_or(&self.fallback_color, |t| &t.view),
+                    ),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 3,
+                    resource: wgpu::BindingResource::TextureView(&scene.depth.view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 4,
+                    resource: wgpu::BindingResource::TextureView(
+                        textures
+                            .shadow_map
+                            .map_or(&self.fallback_depth, |t| &t.view),
+                    ),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 5,
+                    resource: wgpu::BindingResource::TextureView(
+                        textures.overdraw.map_or(&self.fallback_color, |t| &t.view),
+                    ),
+                },
+            ],
+            label: Some("debug_view_bind_group"),
+        });
+
+        let mut pass = begin_fullscreen_pass(encoder, "Debug View Pass", output);
+        pass.set_pipeline(&self.pipeline);
+        pass.set_bind_group(0, &bind_group, &[]);
+        pass.draw(0..3, 0..1);
+    }
+}
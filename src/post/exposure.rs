@@ -0,0 +1,236 @@
+use std::time::Instant;
+
+use bytemuck::{Pod, Zeroable};
+use wgpu::{Buffer, CommandEncoder, Device, Queue};
+
+use crate::{shader::ShaderLibrary, texture::Texture};
+
+/// 自动曝光参数
+#[derive(Debug, Copy, Clone)]
+pub struct AutoExposureSettings {
+    /// 直方图覆盖的亮度范围下限，单位 EV（log2 亮度）
+    pub min_ev: f32,
+    /// 直方图覆盖的亮度范围上限，同时限制了适应的范围
+    pub max_ev: f32,
+    /// 画面变亮时的适应速度，越大越快
+    pub speed_up: f32,
+    /// 画面变暗时的适应速度，人眼对暗处的适应通常更慢
+    pub speed_down: f32,
+    /// 参与平均的像素所在的亮度百分位区间，用于排除少量极暗与极亮的像素
+    pub low_percentile: f32,
+    pub high_percentile: f32,
+}
+
+impl Default for AutoExposureSettings {
+    fn default() -> Self {
+        Self {
+            min_ev: -8.0,
+            max_ev: 6.0,
+            speed_up: 3.0,
+            speed_down: 1.0,
+            low_percentile: 0.1,
+            high_percentile: 0.95,
+        }
+    }
+}
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+struct ExposureUniform {
+    params: [f32; 4],
+    adaptation: [f32; 4],
+}
+
+unsafe impl Zeroable for ExposureUniform {}
+unsafe impl Pod for ExposureUniform {}
+
+/// 在计算着色器中统计 HDR 画面的亮度直方图，并把平均亮度随时间平滑地适应到目标值
+///
+/// 结果保存在 [`AutoExposure::state_buffer`] 中，不需要回读到 CPU。
+pub struct AutoExposure {
+    pub settings: AutoExposureSettings,
+    uniform_buffer: Buffer,
+    histogram_buffer: Buffer,
+    state_buffer: Buffer,
+    bind_group_layout: wgpu::BindGroupLayout,
+    histogram_pipeline: wgpu::ComputePipeline,
+    adapt_pipeline: wgpu::ComputePipeline,
+    last_update: Option<Instant>,
+    reset: bool,
+}
+
+impl AutoExposure {
+    const BIN_COUNT: u64 = 256;
+
+    pub fn new(device: &Device, settings: AutoExposureSettings) -> Self {
+        let uniform_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Exposure Uniform Buffer"),
+            size: std::mem::size_of::<ExposureUniform>() as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let histogram_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Exposure Histogram Buffer"),
+            size: Self::BIN_COUNT * 4,
+            usage: wgpu::BufferUsages::STORAGE,
+            mapped_at_creation: false,
+        });
+        // 初始为 0，第一次适应时直接取目标亮度
+        let state_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Exposure State Buffer"),
+            size: 16,
+            usage: wgpu::BufferUsages::STORAGE,
+            mapped_at_creation: false,
+        });
+
+        let storage = |binding| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility: wgpu::ShaderStages::COMPUTE,
+            ty: wgpu::BindingType::Buffer {
+                ty: wgpu::BufferBindingType::Storage { read_only: false },
+                has_dynamic_offset: false,
+                min_binding_size: None,
+            },
+            count: None,
+        };
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::COMPUTE,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::COMPUTE,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                storage(2),
+                storage(3),
+            ],
+            label: Some("exposure_bind_group_layout"),
+        });
+
+        let shader = ShaderLibrary::new()
+            .create_shader_module(
+                device,
+                "Exposure Shader",
+                include_str!("../../shaders/exposure.wgsl"),
+            )
+            .expect("built-in exposure shader");
+        let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Exposure Pipeline Layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+        let pipeline = |label, entry_point| {
+            device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+                label: Some(label),
+                layout: Some(&layout),
+                module: &shader,
+                entry_point: Some(entry_point),
+                compilation_options: Default::default(),
+                cache: None,
+            })
+        };
+
+        Self {
+            settings,
+            histogram_pipeline: pipeline("Exposure Histogram Pipeline", "cs_histogram"),
+            adapt_pipeline: pipeline("Exposure Adapt Pipeline", "cs_adapt"),
+            uniform_buffer,
+            histogram_buffer,
+            state_buffer,
+            bind_group_layout,
+            last_update: None,
+            reset: true,
+        }
+    }
+
+    /// 下一帧直接使用目标亮度而不做过渡，用于切换场景或相机跳转
+    pub fn reset(&mut self) {
+        self.reset = true;
+    }
+
+    /// 保存适应后平均亮度的存储缓冲，第一个 f32 有效
+    pub fn state_buffer(&self) -> &Buffer {
+        &self.state_buffer
+    }
+
+    /// 按距上一次调用的时间写入本帧参数
+    pub fn update(&mut self, queue: &Queue) {
+        let now = Instant::now();
+        let dt = self
+            .last_update
+            .map_or(0.0, |last| (now - last).as_secs_f32());
+        self.last_update = Some(now);
+
+        let s = &self.settings;
+        let uniform = ExposureUniform {
+            params: [
+                s.min_ev,
+                (s.max_ev - s.min_ev).max(1e-3),
+                dt,
+                if self.reset { 1.0 } else { 0.0 },
+            ],
+            adaptation: [
+                s.speed_up,
+                s.speed_down,
+                s.low_percentile.clamp(0.0, 1.0),
+                s.high_percentile.clamp(s.low_percentile, 1.0),
+            ],
+        };
+        queue.write_buffer(&self.uniform_buffer, 0, bytemuck::cast_slice(&[uniform]));
+        self.reset = false;
+    }
+
+    /// 统计 `input` 的直方图并更新适应后的亮度
+    pub fn dispatch(&self, device: &Device, encoder: &mut CommandEncoder, input: &Texture) {
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: &self.bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: self.uniform_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::TextureView(&input.view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: self.histogram_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 3,
+                    resource: self.state_buffer.as_entire_binding(),
+                },
+            ],
+            label: Some("exposure_bind_group"),
+        });
+
+        let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+            label: Some("Exposure Pass"),
+            timestamp_writes: None,
+        });
+        pass.set_bind_group(0, &bind_group, &[]);
+        pass.set_pipeline(&self.histogram_pipeline);
+        pass.dispatch_workgroups(
+            input.texture.width().div_ceil(16),
+            input.texture.height().div_ceil(16),
+            1,
+        );
+        pass.set_pipeline(&self.adapt_pipeline);
+        pass.dispatch_workgroups(1, 1, 1);
+    }
+}
@@ -0,0 +1,165 @@
+use bytemuck::{Pod, Zeroable};
+use wgpu::{CommandEncoder, Device, Queue, TextureView};
+
+use super::{begin_fullscreen_pass, PostEffect, SceneTextures};
+use crate::{camera::Camera, shader::ShaderLibrary, texture::Texture};
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+struct LensUniform {
+    params: [f32; 4],
+    frame: [f32; 4],
+}
+
+unsafe impl Zeroable for LensUniform {}
+unsafe impl Pod for LensUniform {}
+
+/// 模拟相机镜头的瑕疵：暗角、色差与胶片颗粒，各项强度为 0 时关闭
+pub struct LensEffects {
+    pub vignette: f32,
+    /// 暗角从画面边缘向内过渡的宽度，范围 (0, 1]
+    pub vignette_smoothness: f32,
+    pub chromatic_aberration: f32,
+    pub grain: f32,
+    frame: u32,
+    buffer: wgpu::Buffer,
+    bind_group: wgpu::BindGroup,
+    texture_layout: wgpu::BindGroupLayout,
+    pipeline: wgpu::RenderPipeline,
+}
+
+impl LensEffects {
+    pub const LABEL: &'static str = "lens";
+
+    pub fn new(device: &Device, format: wgpu::TextureFormat) -> Self {
+        let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Lens Uniform Buffer"),
+            size: std::mem::size_of::<LensUniform>() as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            }],
+            label: Some("lens_bind_group_layout"),
+        });
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: &bind_group_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: buffer.as_entire_binding(),
+            }],
+            label: Some("lens_bind_group"),
+        });
+        let texture_layout = Texture::texture_bind_group_layout(device);
+
+        let shader = ShaderLibrary::new()
+            .create_shader_module(
+                device,
+                "Lens Shader",
+                include_str!("../../shaders/lens.wgsl"),
+            )
+            .expect("built-in lens shader");
+        let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Lens Pipeline Layout"),
+            bind_group_layouts: &[&texture_layout, &bind_group_layout],
+            push_constant_ranges: &[],
+        });
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Lens Pipeline"),
+            layout: Some(&layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                compilation_options: Default::default(),
+                entry_point: Some("vs_main"),
+                buffers: &[],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                compilation_options: Default::default(),
+                entry_point: Some("fs_main"),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format,
+                    blend: None,
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+            cache: None,
+        });
+
+        Self {
+            vignette: 0.4,
+            vignette_smoothness: 0.6,
+            chromatic_aberration: 0.3,
+            grain: 0.04,
+            frame: 0,
+            buffer,
+            bind_group,
+            texture_layout,
+            pipeline,
+        }
+    }
+}
+
+impl PostEffect for LensEffects {
+    fn label(&self) -> &str {
+        Self::LABEL
+    }
+
+    fn update(&mut self, queue: &Queue, camera: &Camera) {
+        // 颗粒噪声随帧序号变化；取模避免转换为 f32 后丢失精度
+        self.frame = (self.frame + 1) % 4096;
+        let uniform = LensUniform {
+            params: [
+                self.vignette,
+                self.vignette_smoothness.clamp(1e-3, 1.0),
+                self.chromatic_aberration,
+                self.grain,
+            ],
+            frame: [self.frame as f32, camera.aspect, 0.0, 0.0],
+        };
+        queue.write_buffer(&self.buffer, 0, bytemuck::cast_slice(&[uniform]));
+    }
+
+    fn apply(
+        &self,
+        device: &Device,
+        encoder: &mut CommandEncoder,
+        input: &Texture,
+        _scene: &SceneTextures,
+        output: &TextureView,
+    ) {
+        let texture_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: &self.texture_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(&input.view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(&input.sampler),
+                },
+            ],
+            label: Some("lens_texture_bind_group"),
+        });
+
+        let mut pass = begin_fullscreen_pass(encoder, "Lens Pass", output);
+        pass.set_pipeline(&self.pipeline);
+        pass.set_bind_group(0, &texture_bind_group, &[]);
+        pass.set_bind_group(1, &self.bind_group, &[]);
+        pass.draw(0..3, 0..1);
+    }
+}
@@ -0,0 +1,208 @@
+use bytemuck::{Pod, Zeroable};
+use wgpu::{CommandEncoder, Device, Queue, TextureView};
+
+use super::{
+    begin_fullscreen_pass, placeholder_texture, PostEffect, SceneTextures, VELOCITY_FORMAT,
+};
+use crate::{camera::Camera, shader::ShaderLibrary, texture::Texture};
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+struct MotionBlurUniform {
+    inv_view_proj: [[f32; 4]; 4],
+    prev_view_proj: [[f32; 4]; 4],
+    params: [f32; 4],
+}
+
+unsafe impl Zeroable for MotionBlurUniform {}
+unsafe impl Pod for MotionBlurUniform {}
+
+/// 沿每个像素的速度方向做多次采样的运动模糊
+///
+/// 场景提供 [`SceneTextures::velocity`] 时使用逐物体速度，否则只模糊相机运动。
+pub struct MotionBlur {
+    /// 快门时间占帧间隔的比例，1 表示模糊整帧的位移
+    pub intensity: f32,
+    pub samples: u32,
+    /// 模糊长度上限，uv 单位
+    pub max_length: f32,
+    prev_view_proj: Option<glam::Mat4>,
+    buffer: wgpu::Buffer,
+    bind_group_layout: wgpu::BindGroupLayout,
+    pipeline: wgpu::RenderPipeline,
+    velocity_pipeline: wgpu::RenderPipeline,
+    empty_velocity: Texture,
+}
+
+impl MotionBlur {
+    pub const LABEL: &'static str = "motion_blur";
+
+    pub fn new(device: &Device, format: wgpu::TextureFormat) -> Self {
+        let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Motion Blur Uniform Buffer"),
+            size: std::mem::size_of::<MotionBlurUniform>() as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let texture = |binding, sample_type| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility: wgpu::ShaderStages::FRAGMENT,
+            ty: wgpu::BindingType::Texture {
+                sample_type,
+                view_dimension: wgpu::TextureViewDimension::D2,
+                multisampled: false,
+            },
+            count: None,
+        };
+        let float = wgpu::TextureSampleType::Float { filterable: true };
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                texture(1, float),
+                texture(2, wgpu::TextureSampleType::Depth),
+                texture(3, float),
+                wgpu::BindGroupLayoutEntry {
+                    binding: 4,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+            ],
+            label: Some("motion_blur_bind_group_layout"),
+        });
+
+        let shader = ShaderLibrary::new()
+            .create_shader_module(
+                device,
+                "Motion Blur Shader",
+                include_str!("../../shaders/motion_blur.wgsl"),
+            )
+            .expect("built-in motion blur shader");
+        let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Motion Blur Pipeline Layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+        // 是否读取速度纹理由可覆盖常量决定，两种情况各编译一条管线
+        let create_pipeline = |has_velocity: bool| {
+            let constants = [("HAS_VELOCITY".to_string(), has_velocity as u8 as f64)].into();
+            device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                label: Some("Motion Blur Pipeline"),
+                layout: Some(&layout),
+                vertex: wgpu::VertexState {
+                    module: &shader,
+                    compilation_options: Default::default(),
+                    entry_point: Some("vs_main"),
+                    buffers: &[],
+                },
+                fragment: Some(wgpu::FragmentState {
+                    module: &shader,
+                    compilation_options: wgpu::PipelineCompilationOptions {
+                        constants: &constants,
+                        ..Default::default()
+                    },
+                    entry_point: Some("fs_main"),
+                    targets: &[Some(wgpu::ColorTargetState {
+                        format,
+                        blend: None,
+                        write_mask: wgpu::ColorWrites::ALL,
+                    })],
+                }),
+                primitive: wgpu::PrimitiveState::default(),
+                depth_stencil: None,
+                multisample: wgpu::MultisampleState::default(),
+                multiview: None,
+                cache: None,
+            })
+        };
+
+        Self {
+            intensity: 0.5,
+            samples: 12,
+            max_length: 0.05,
+            prev_view_proj: None,
+            buffer,
+            bind_group_layout,
+            pipeline: create_pipeline(false),
+            velocity_pipeline: create_pipeline(true),
+            empty_velocity: placeholder_texture(
+                device,
+                VELOCITY_FORMAT,
+                "motion_blur_empty_velocity",
+            ),
+        }
+    }
+}
+
+impl PostEffect for MotionBlur {
+    fn label(&self) -> &str {
+        Self::LABEL
+    }
+
+    fn update(&mut self, queue: &Queue, camera: &Camera) {
+        let view_proj = camera.build_view_projection_matrix();
+        let uniform = MotionBlurUniform {
+            inv_view_proj: view_proj.inverse().to_cols_array_2d(),
+            prev_view_proj: self.prev_view_proj.unwrap_or(view_proj).to_cols_array_2d(),
+            params: [self.intensity, self.samples as f32, self.max_length, 0.0],
+        };
+        queue.write_buffer(&self.buffer, 0, bytemuck::cast_slice(&[uniform]));
+        self.prev_view_proj = Some(view_proj);
+    }
+
+    fn apply(
+        &self,
+        device: &Device,
+        encoder: &mut CommandEncoder,
+        input: &Texture,
+        scene: &SceneTextures,
+        output: &TextureView,
+    ) {
+        let velocity = scene.velocity.unwrap_or(&self.empty_velocity);
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: &self.bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: self.buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::TextureView(&input.view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: wgpu::BindingResource::TextureView(&scene.depth.view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 3,
+                    resource: wgpu::BindingResource::TextureView(&velocity.view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 4,
+                    resource: wgpu::BindingResource::Sampler(&input.sampler),
+                },
+            ],
+            label: Some("motion_blur_bind_group"),
+        });
+
+        let mut pass = begin_fullscreen_pass(encoder, "Motion Blur Pass", output);
+        pass.set_pipeline(if scene.velocity.is_some() {
+            &self.velocity_pipeline
+        } else {
+            &self.pipeline
+        });
+        pass.set_bind_group(0, &bind_group, &[]);
+        pass.draw(0..3, 0..1);
+    }
+}
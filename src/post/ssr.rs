@@ -0,0 +1,249 @@
+use bytemuck::{Pod, Zeroable};
+use wgpu::{CommandEncoder, Device, Queue, TextureView};
+
+use super::{
+    begin_fullscreen_pass, placeholder_texture, PostEffect, SceneTextures, NORMAL_ROUGHNESS_FORMAT,
+};
+use crate::{camera::Camera, probe::EnvironmentProbe, shader::ShaderLibrary, texture::Texture};
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+struct SsrUniform {
+    view_proj: [[f32; 4]; 4],
+    inv_view_proj: [[f32; 4]; 4],
+    eye: [f32; 4],
+    params: [f32; 4],
+    depth_params: [f32; 4],
+}
+
+unsafe impl Zeroable for SsrUniform {}
+unsafe impl Pod for SsrUniform {}
+
+/// 屏幕空间反射：沿反射方向在世界空间步进并投影回屏幕与深度比较，
+/// 未命中或表面过于粗糙时退回到环境探针
+///
+/// 需要 [`SceneTextures::normal_roughness`]；没有提供时该效果不改变画面。
+pub struct ScreenSpaceReflections {
+    pub max_steps: u32,
+    pub max_distance: f32,
+    /// 光线位于深度缓冲之后多远以内算作命中
+    pub thickness: f32,
+    /// 粗糙度超过该值时不再做屏幕空间追踪
+    pub roughness_cutoff: f32,
+    pub intensity: f32,
+    buffer: wgpu::Buffer,
+    bind_group_layout: wgpu::BindGroupLayout,
+    pipeline: wgpu::RenderPipeline,
+    sampler: wgpu::Sampler,
+    probe_view: TextureView,
+    /// 没有 G-buffer 时使用的 1x1 全零法线纹理
+    empty_normals: Texture,
+}
+
+impl ScreenSpaceReflections {
+    pub const LABEL: &'static str = "ssr";
+
+    pub fn new(device: &Device, format: wgpu::TextureFormat) -> Self {
+        let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("SSR Uniform Buffer"),
+            size: std::mem::size_of::<SsrUniform>() as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let texture = |binding, sample_type, view_dimension| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility: wgpu::ShaderStages::FRAGMENT,
+            ty: wgpu::BindingType::Texture {
+                sample_type,
+                view_dimension,
+                multisampled: false,
+            },
+            count: None,
+        };
+        let float = wgpu::TextureSampleType::Float { filterable: true };
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                texture(1, float, wgpu::TextureViewDimension::D2),
+                texture(
+                    2,
+                    wgpu::TextureSampleType::Depth,
+                    wgpu::TextureViewDimension::D2,
+                ),
+                texture(3, float, wgpu::TextureViewDimension::D2),
+                wgpu::BindGroupLayoutEntry {
+                    binding: 4,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+                texture(5, float, wgpu::TextureViewDimension::Cube),
+            ],
+            label: Some("ssr_bind_group_layout"),
+        });
+
+        let shader = ShaderLibrary::new()
+            .create_shader_module(device, "SSR Shader", include_str!("../../shaders/ssr.wgsl"))
+            .expect("built-in ssr shader");
+        let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("SSR Pipeline Layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("SSR Pipeline"),
+            layout: Some(&layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                compilation_options: Default::default(),
+                entry_point: Some("vs_main"),
+                buffers: &[],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                compilation_options: Default::default(),
+                entry_point: Some("fs_main"),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format,
+                    blend: None,
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+            cache: None,
+        });
+
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            address_mode_w: wgpu::AddressMode::ClampToEdge,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            mipmap_filter: wgpu::FilterMode::Nearest,
+            ..Default::default()
+        });
+
+        let probe_view = device
+            .create_texture(&wgpu::TextureDescriptor {
+                label: Some("ssr_empty_probe"),
+                size: wgpu::Extent3d {
+                    width: 1,
+                    height: 1,
+                    depth_or_array_layers: 6,
+                },
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format: EnvironmentProbe::FORMAT,
+                usage: wgpu::TextureUsages::TEXTURE_BINDING,
+                view_formats: &[],
+            })
+            .create_view(&wgpu::TextureViewDescriptor {
+                dimension: Some(wgpu::TextureViewDimension::Cube),
+                ..Default::default()
+            });
+        let empty_normals =
+            placeholder_texture(device, NORMAL_ROUGHNESS_FORMAT, "ssr_empty_normals");
+
+        Self {
+            max_steps: 64,
+            max_distance: 20.0,
+            thickness: 0.5,
+            roughness_cutoff: 0.6,
+            intensity: 1.0,
+            buffer,
+            bind_group_layout,
+            pipeline,
+            sampler,
+            probe_view,
+            empty_normals,
+        }
+    }
+
+    /// 设置未命中时使用的环境探针，不设置时退回为黑色
+    pub fn set_probe(&mut self, probe: &EnvironmentProbe) {
+        self.probe_view = probe.view.clone();
+    }
+}
+
+impl PostEffect for ScreenSpaceReflections {
+    fn label(&self) -> &str {
+        Self::LABEL
+    }
+
+    fn update(&mut self, queue: &Queue, camera: &Camera) {
+        let view_proj = camera.build_view_projection_matrix();
+        let uniform = SsrUniform {
+            view_proj: view_proj.to_cols_array_2d(),
+            inv_view_proj: view_proj.inverse().to_cols_array_2d(),
+            eye: camera.eye.extend(self.max_distance).to_array(),
+            params: [
+                self.max_steps.max(1) as f32,
+                self.thickness,
+                self.roughness_cutoff,
+                self.intensity,
+            ],
+            depth_params: [camera.znear, camera.zfar, 0.0, 0.0],
+        };
+        queue.write_buffer(&self.buffer, 0, bytemuck::cast_slice(&[uniform]));
+    }
+
+    fn apply(
+        &self,
+        device: &Device,
+        encoder: &mut CommandEncoder,
+        input: &Texture,
+        scene: &SceneTextures,
+        output: &TextureView,
+    ) {
+        let normals = scene.normal_roughness.unwrap_or(&self.empty_normals);
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: &self.bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: self.buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::TextureView(&input.view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: wgpu::BindingResource::TextureView(&scene.depth.view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 3,
+                    resource: wgpu::BindingResource::TextureView(&normals.view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 4,
+                    resource: wgpu::BindingResource::Sampler(&self.sampler),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 5,
+                    resource: wgpu::BindingResource::TextureView(&self.probe_view),
+                },
+            ],
+            label: Some("ssr_bind_group"),
+        });
+
+        let mut pass = begin_fullscreen_pass(encoder, "SSR Pass", output);
+        pass.set_pipeline(&self.pipeline);
+        pass.set_bind_group(0, &bind_group, &[]);
+        pass.draw(0..3, 0..1);
+    }
+}
@@ -0,0 +1,285 @@
+use bytemuck::{Pod, Zeroable};
+use wgpu::{CommandEncoder, Device, Queue, SurfaceConfiguration, TextureView};
+
+use super::{placeholder_texture, PostEffect, SceneTextures, VELOCITY_FORMAT};
+use crate::{camera::Camera, shader::ShaderLibrary, texture::Texture};
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+struct TaaUniform {
+    inv_view_proj: [[f32; 4]; 4],
+    prev_view_proj: [[f32; 4]; 4],
+    params: [f32; 4],
+}
+
+unsafe impl Zeroable for TaaUniform {}
+unsafe impl Pod for TaaUniform {}
+
+/// 基数为 `base` 的 Halton 序列第 `index` 项，范围 [0, 1)
+fn halton(mut index: u32, base: u32) -> f32 {
+    let mut result = 0.0;
+    let mut f = 1.0;
+    while index > 0 {
+        f /= base as f32;
+        result += f * (index % base) as f32;
+        index /= base;
+    }
+    result
+}
+
+/// 时间性抗锯齿：每帧以亚像素抖动投影，再把当前帧与重投影后的历史帧混合，
+/// 历史颜色被限制在当前帧 3x3 邻域的颜色范围内以减少拖影
+///
+/// 每帧在更新相机之前调用 [`TemporalAntiAliasing::next_jitter`]，
+/// 并把结果写入 [`crate::camera::CameraBundle::jitter`]。
+/// 场景提供 [`SceneTextures::velocity`] 时运动物体也能正确重投影，否则只考虑相机运动。
+pub struct TemporalAntiAliasing {
+    /// 历史帧的权重，越大越平滑但运动时越容易拖影
+    pub history_weight: f32,
+    jitter: glam::Vec2,
+    jitter_index: u32,
+    size: glam::Vec2,
+    prev_view_proj: Option<glam::Mat4>,
+    history: [Texture; 2],
+    /// 本帧写入的历史纹理下标，另一张为上一帧的结果
+    write_index: usize,
+    buffer: wgpu::Buffer,
+    bind_group_layout: wgpu::BindGroupLayout,
+    pipeline: wgpu::RenderPipeline,
+    velocity_pipeline: wgpu::RenderPipeline,
+    empty_velocity: Texture,
+}
+
+impl TemporalAntiAliasing {
+    pub const LABEL: &'static str = "taa";
+    /// 抖动序列的长度
+    pub const JITTER_SAMPLES: u32 = 8;
+
+    pub fn new(
+        device: &Device,
+        config: &SurfaceConfiguration,
+        format: wgpu::TextureFormat,
+    ) -> Self {
+        let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("TAA Uniform Buffer"),
+            size: std::mem::size_of::<TaaUniform>() as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let texture = |binding, sample_type| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility: wgpu::ShaderStages::FRAGMENT,
+            ty: wgpu::BindingType::Texture {
+                sample_type,
+                view_dimension: wgpu::TextureViewDimension::D2,
+                multisampled: false,
+            },
+            count: None,
+        };
+        let float = wgpu::TextureSampleType::Float { filterable: true };
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                texture(1, float),
+                texture(2, wgpu::TextureSampleType::Depth),
+                texture(3, float),
+                wgpu::BindGroupLayoutEntry {
+                    binding: 4,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+                texture(5, float),
+            ],
+            label: Some("taa_bind_group_layout"),
+        });
+
+        let shader = ShaderLibrary::new()
+            .create_shader_module(device, "TAA Shader", include_str!("../../shaders/taa.wgsl"))
+            .expect("built-in taa shader");
+        let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("TAA Pipeline Layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+        let target = Some(wgpu::ColorTargetState {
+            format,
+            blend: None,
+            write_mask: wgpu::ColorWrites::ALL,
+        });
+        // 是否读取速度纹理由可覆盖常量决定，两种情况各编译一条管线
+        let create_pipeline = |has_velocity: bool| {
+            let constants = [("HAS_VELOCITY".to_string(), has_velocity as u8 as f64)].into();
+            device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                label: Some("TAA Pipeline"),
+                layout: Some(&layout),
+                vertex: wgpu::VertexState {
+                    module: &shader,
+                    compilation_options: Default::default(),
+                    entry_point: Some("vs_main"),
+                    buffers: &[],
+                },
+                fragment: Some(wgpu::FragmentState {
+                    module: &shader,
+                    compilation_options: wgpu::PipelineCompilationOptions {
+                        constants: &constants,
+                        ..Default::default()
+                    },
+                    entry_point: Some("fs_main"),
+                    targets: &[target.clone(), target.clone()],
+                }),
+                primitive: wgpu::PrimitiveState::default(),
+                depth_stencil: None,
+                multisample: wgpu::MultisampleState::default(),
+                multiview: None,
+                cache: None,
+            })
+        };
+
+        Self {
+            history_weight: 0.9,
+            jitter: glam::Vec2::ZERO,
+            jitter_index: 0,
+            size: glam::vec2(config.width as f32, config.height as f32),
+            prev_view_proj: None,
+            history: Self::create_history(device, config, format),
+            write_index: 0,
+            buffer,
+            bind_group_layout,
+            pipeline: create_pipeline(false),
+            velocity_pipeline: create_pipeline(true),
+            empty_velocity: placeholder_texture(device, VELOCITY_FORMAT, "taa_empty_velocity"),
+        }
+    }
+
+    fn create_history(
+        device: &Device,
+        config: &SurfaceConfiguration,
+        format: wgpu::TextureFormat,
+    ) -> [Texture; 2] {
+        [
+            Texture::create_color_target(device, config, format, "taa_history_0"),
+            Texture::create_color_target(device, config, format, "taa_history_1"),
+        ]
+    }
+
+    /// 推进抖动序列并返回本帧在 NDC 中的偏移（半个像素以内）
+    pub fn next_jitter(&mut self) -> glam::Vec2 {
+        self.jitter_index = self.jitter_index % Self::JITTER_SAMPLES + 1;
+        let sample = glam::vec2(halton(self.jitter_index, 2), halton(self.jitter_index, 3));
+        self.jitter = (sample - 0.5) * 2.0 / self.size;
+        self.jitter
+    }
+
+    /// 丢弃历史帧，例如相机发生跳变时
+    pub fn reset_history(&mut self) {
+        self.prev_view_proj = None;
+    }
+}
+
+impl PostEffect for TemporalAntiAliasing {
+    fn label(&self) -> &str {
+        Self::LABEL
+    }
+
+    fn resize(&mut self, device: &Device, config: &SurfaceConfiguration) {
+        let format = self.history[0].texture.format();
+        self.history = Self::create_history(device, config, format);
+        self.size = glam::vec2(config.width as f32, config.height as f32);
+        self.reset_history();
+    }
+
+    fn update(&mut self, queue: &Queue, camera: &Camera) {
+        let view_proj = camera.build_view_projection_matrix();
+        let history_valid = self.prev_view_proj.is_some();
+        let uniform = TaaUniform {
+            inv_view_proj: view_proj.inverse().to_cols_array_2d(),
+            prev_view_proj: self.prev_view_proj.unwrap_or(view_proj).to_cols_array_2d(),
+            params: [
+                self.jitter.x,
+                self.jitter.y,
+                self.history_weight.clamp(0.0, 1.0),
+                if history_valid { 1.0 } else { 0.0 },
+            ],
+        };
+        queue.write_buffer(&self.buffer, 0, bytemuck::cast_slice(&[uniform]));
+        self.prev_view_proj = Some(view_proj);
+        self.write_index ^= 1;
+    }
+
+    fn apply(
+        &self,
+        device: &Device,
+        encoder: &mut CommandEncoder,
+        input: &Texture,
+        scene: &SceneTextures,
+        output: &TextureView,
+    ) {
+        let history_read = &self.history[self.write_index ^ 1];
+        let velocity = scene.velocity.unwrap_or(&self.empty_velocity);
+        let history_write = &self.history[self.write_index];
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: &self.bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: self.buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::TextureView(&input.view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: wgpu::BindingResource::TextureView(&scene.depth.view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 3,
+                    resource: wgpu::BindingResource::TextureView(&history_read.view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 4,
+                    resource: wgpu::BindingResource::Sampler(&history_read.sampler),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 5,
+                    resource: wgpu::BindingResource::TextureView(&velocity.view),
+                },
+            ],
+            label: Some("taa_bind_group"),
+        });
+
+        let attachment = |view| {
+            Some(wgpu::RenderPassColorAttachment {
+                view,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Load,
+                    store: wgpu::StoreOp::Store,
+                },
+            })
+        };
+        let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("TAA Pass"),
+            color_attachments: &[attachment(output), attachment(&history_write.view)],
+            ..Default::default()
+        });
+        pass.set_pipeline(if scene.velocity.is_some() {
+            &self.velocity_pipeline
+        } else {
+            &self.pipeline
+        });
+        pass.set_bind_group(0, &bind_group, &[]);
+        pass.draw(0..3, 0..1);
+    }
+}
@@ -0,0 +1,211 @@
+use bytemuck::{Pod, Zeroable};
+use wgpu::{CommandEncoder, Device, Queue, TextureView};
+
+use super::{
+    begin_fullscreen_pass,
+    exposure::{AutoExposure, AutoExposureSettings},
+    PostEffect, SceneTextures,
+};
+use crate::{camera::Camera, shader::ShaderLibrary, texture::Texture};
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum TonemapOperator {
+    Aces,
+    Reinhard,
+    /// 只应用曝光，不压缩高光
+    None,
+}
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+struct TonemapUniform {
+    params: [f32; 4],
+}
+
+unsafe impl Zeroable for TonemapUniform {}
+unsafe impl Pod for TonemapUniform {}
+
+/// 把 HDR 画面按曝光缩放后映射到显示范围，应放在后处理栈中所有 HDR 效果之后
+///
+/// 开启自动曝光时先统计输入画面的亮度直方图，再把适应后的平均亮度映射到中灰。
+pub struct Tonemapping {
+    pub operator: TonemapOperator,
+    /// 曝光，单位 EV；自动曝光时作为曝光补偿
+    pub exposure: f32,
+    pub auto_exposure: bool,
+    /// 自动曝光的统计与适应状态
+    pub meter: AutoExposure,
+    /// 输出 surface 的 alpha 模式，透明窗口设为 [`GpuContext::alpha_mode`](crate::context::GpuContext::alpha_mode)
+    ///
+    /// 清为透明后以 alpha 混合绘制的画面颜色已经乘过 alpha。`PreMultiplied` 与 `PostMultiplied`
+    /// 时先除以 alpha 再色调映射，前者结果再乘回 alpha；其余模式原样保留输入的 alpha。
+    pub alpha_mode: wgpu::CompositeAlphaMode,
+    buffer: wgpu::Buffer,
+    bind_group_layout: wgpu::BindGroupLayout,
+    pipeline: wgpu::RenderPipeline,
+}
+
+impl Tonemapping {
+    pub const LABEL: &'static str = "tonemapping";
+
+    pub fn new(device: &Device, format: wgpu::TextureFormat) -> Self {
+        let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Tonemap Uniform Buffer"),
+            size: std::mem::size_of::<TonemapUniform>() as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Storage { read_only: true },
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+            ],
+            label: Some("tonemap_bind_group_layout"),
+        });
+
+        let shader = ShaderLibrary::new()
+            .create_shader_module(
+                device,
+                "Tonemap Shader",
+                include_str!("../../shaders/tonemap_pass.wgsl"),
+            )
+            .expect("built-in tonemap shader");
+        let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Tonemap Pipeline Layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Tonemap Pipeline"),
+            layout: Some(&layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                compilation_options: Default::default(),
+                entry_point: Some("vs_main"),
+                buffers: &[],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                compilation_options: Default::default(),
+                entry_point: Some("fs_main"),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format,
+                    blend: None,
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+            cache: None,
+        });
+
+        Self {
+            operator: TonemapOperator::Aces,
+            exposure: 0.0,
+            auto_exposure: true,
+            meter: AutoExposure::new(device, AutoExposureSettings::default()),
+            alpha_mode: wgpu::CompositeAlphaMode::Opaque,
+            buffer,
+            bind_group_layout,
+            pipeline,
+        }
+    }
+}
+
+impl PostEffect for Tonemapping {
+    fn label(&self) -> &str {
+        Self::LABEL
+    }
+
+    fn update(&mut self, queue: &Queue, _camera: &Camera) {
+        let operator = match self.operator {
+            TonemapOperator::Aces => 0.0,
+            TonemapOperator::Reinhard => 1.0,
+            TonemapOperator::None => 2.0,
+        };
+        let alpha = match self.alpha_mode {
+            wgpu::CompositeAlphaMode::PreMultiplied => 1.0,
+            wgpu::CompositeAlphaMode::PostMultiplied => 2.0,
+            _ => 0.0,
+        };
+        let uniform = TonemapUniform {
+            params: [
+                self.exposure,
+                if self.auto_exposure { 1.0 } else { 0.0 },
+                operator,
+                alpha,
+            ],
+        };
+        queue.write_buffer(&self.buffer, 0, bytemuck::cast_slice(&[uniform]));
+        if self.auto_exposure {
+            self.meter.update(queue);
+        }
+    }
+
+    fn apply(
+        &self,
+        device: &Device,
+        encoder: &mut CommandEncoder,
+        input: &Texture,
+        _scene: &SceneTextures,
+        output: &TextureView,
+    ) {
+        if self.auto_exposure {
+            self.meter.dispatch(device, encoder, input);
+        }
+
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: &self.bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: self.buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::TextureView(&input.view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: self.meter.state_buffer().as_entire_binding(),
+                },
+            ],
+            label: Some("tonemap_bind_group"),
+        });
+
+        let mut pass = begin_fullscreen_pass(encoder, "Tonemap Pass", output);
+        pass.set_pipeline(&self.pipeline);
+        pass.set_bind_group(0, &bind_group, &[]);
+        pass.draw(0..3, 0..1);
+    }
+}
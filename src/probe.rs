@@ -0,0 +1,107 @@
+use wgpu::{Device, Queue};
+
+use crate::sky::{Sky, Sun};
+
+/// 立方体贴图形式的环境探针，目前由程序化天空渲染得到
+pub struct EnvironmentProbe {
+    pub texture: wgpu::Texture,
+    pub view: wgpu::TextureView,
+    pub sampler: wgpu::Sampler,
+    size: u32,
+    sky: Sky,
+}
+
+impl EnvironmentProbe {
+    pub const FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba16Float;
+
+    pub fn new(device: &Device, size: u32) -> Self {
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("environment_probe"),
+            size: wgpu::Extent3d {
+                width: size,
+                height: size,
+                depth_or_array_layers: 6,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: Self::FORMAT,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+            view_formats: &[],
+        });
+        let view = texture.create_view(&wgpu::TextureViewDescriptor {
+            dimension: Some(wgpu::TextureViewDimension::Cube),
+            ..Default::default()
+        });
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            address_mode_w: wgpu::AddressMode::ClampToEdge,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            mipmap_filter: wgpu::FilterMode::Nearest,
+            ..Default::default()
+        });
+
+        Self {
+            texture,
+            view,
+            sampler,
+            size,
+            sky: Sky::new(device, Self::FORMAT, None),
+        }
+    }
+
+    pub fn size(&self) -> u32 {
+        self.size
+    }
+
+    /// 按立方体贴图的面顺序（+X, -X, +Y, -Y, +Z, -Z）返回每个面的 view-projection 矩阵
+    pub fn face_view_projections() -> [glam::Mat4; 6] {
+        // 立方体贴图的面约定 v 轴向下，因此在投影后翻转 y
+        let proj = glam::Mat4::from_scale(glam::vec3(1.0, -1.0, 1.0))
+            * glam::Mat4::perspective_rh(std::f32::consts::FRAC_PI_2, 1.0, 0.1, 10.0);
+        [
+            (glam::Vec3::X, glam::Vec3::NEG_Y),
+            (glam::Vec3::NEG_X, glam::Vec3::NEG_Y),
+            (glam::Vec3::Y, glam::Vec3::Z),
+            (glam::Vec3::NEG_Y, glam::Vec3::NEG_Z),
+            (glam::Vec3::Z, glam::Vec3::NEG_Y),
+            (glam::Vec3::NEG_Z, glam::Vec3::NEG_Y),
+        ]
+        .map(|(forward, up)| proj * glam::Mat4::look_to_rh(glam::Vec3::ZERO, forward, up))
+    }
+
+    /// 用当前太阳位置重新渲染探针；每个面单独提交，以便复用同一个天空 uniform
+    pub fn render_sky(&mut self, device: &Device, queue: &Queue, sun: &Sun) {
+        for (face, view_proj) in Self::face_view_projections().into_iter().enumerate() {
+            self.sky.update_with_view_proj(queue, view_proj, sun);
+
+            let face_view = self.texture.create_view(&wgpu::TextureViewDescriptor {
+                dimension: Some(wgpu::TextureViewDimension::D2),
+                base_array_layer: face as u32,
+                array_layer_count: Some(1),
+                ..Default::default()
+            });
+            let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("Environment Probe Encoder"),
+            });
+            {
+                let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                    label: Some("Environment Probe Pass"),
+                    color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                        view: &face_view,
+                        resolve_target: None,
+                        ops: wgpu::Operations {
+                            load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                            store: wgpu::StoreOp::Store,
+                        },
+                    })],
+                    ..Default::default()
+                });
+                self.sky.draw(&mut pass);
+            }
+            queue.submit(Some(encoder.finish()));
+        }
+    }
+}
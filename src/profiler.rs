@@ -0,0 +1,502 @@
+use std::{
+    sync::{
+        mpsc::{channel, Receiver},
+        Mutex,
+    },
+    time::Duration,
+};
+
+// wasm32 上 std::time::Instant 不可用
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::Instant;
+#[cfg(target_arch = "wasm32")]
+use web_time::Instant;
+
+use wgpu::{Buffer, Device, QuerySet};
+
+/// 一个渲染 pass 的查询结果，设备不支持的统计为 `None`
+#[derive(Debug, Clone, Default)]
+pub struct PassStatistics {
+    pub name: String,
+    /// 通过深度与模板测试的采样数
+    pub samples_passed: u64,
+    pub vertex_invocations: Option<u64>,
+    /// 裁剪后输出的图元数
+    pub primitives: Option<u64>,
+    /// 执行片元着色器的次数
+    pub fragment_invocations: Option<u64>,
+}
+
+/// 读回缓冲的状态，映射期间不再写入新的结果
+enum Readback {
+    Idle,
+    /// 已记录复制命令，等待提交
+    Copied(Vec<String>),
+    Mapping(Vec<String>, Receiver<Result<(), wgpu::BufferAsyncError>>),
+}
+
+/// 按渲染 pass 统计遮挡查询与管线统计查询的结果
+///
+/// 遮挡查询总是可用；设备启用了 [`wgpu::Features::PIPELINE_STATISTICS_QUERY`] 时还统计
+/// 顶点、图元与片元数量。结果异步读回，通常晚几帧出现，不会阻塞渲染。每帧的用法：
+///
+/// 1. 创建 pass 时把 [`GpuProfiler::occlusion_query_set`] 填入 `occlusion_query_set`；
+/// 2. 在 pass 中的绘制前后调用 [`GpuProfiler::begin`] 与 [`GpuProfiler::end`]；
+/// 3. 所有 pass 结束后调用 [`GpuProfiler::resolve`]，提交之后调用 [`GpuProfiler::map`]；
+/// 4. 用 [`GpuProfiler::poll`] 取得最新的结果。
+pub struct GpuProfiler {
+    max_passes: u32,
+    occlusion: QuerySet,
+    pipeline_statistics: Option<QuerySet>,
+    resolve_buffer: Buffer,
+    readback_buffer: Buffer,
+    /// 管线统计结果在缓冲中的偏移
+    statistics_offset: u64,
+    /// 本帧已开始的 pass
+    names: Vec<String>,
+    /// 最近一个 pass 是否开始了查询
+    active: bool,
+    readback: Readback,
+    results: Vec<PassStatistics>,
+}
+
+impl GpuProfiler {
+    const STATISTICS: wgpu::PipelineStatisticsTypes =
+        wgpu::PipelineStatisticsTypes::VERTEX_SHADER_INVOCATIONS
+            .union(wgpu::PipelineStatisticsTypes::CLIPPER_PRIMITIVES_OUT)
+            .union(wgpu::PipelineStatisticsTypes::FRAGMENT_SHADER_INVOCATIONS);
+    /// 每个管线统计查询写入的 u64 个数，与 `STATISTICS` 中的位数一致
+    const STATISTICS_COUNT: u64 = 3;
+
+    /// 每帧最多统计 `max_passes` 个 pass
+    pub fn new(device: &Device, max_passes: u32) -> Self {
+        assert!(max_passes > 0, "profiler needs at least one pass");
+        let occlusion = device.create_query_set(&wgpu::QuerySetDescriptor {
+            label: Some("Profiler Occlusion Queries"),
+            ty: wgpu::QueryType::Occlusion,
+            count: max_passes,
+        });
+        let pipeline_statistics = device
+            .features()
+            .contains(wgpu::Features::PIPELINE_STATISTICS_QUERY)
+            .then(|| {
+                device.create_query_set(&wgpu::QuerySetDescriptor {
+                    label: Some("Profiler Pipeline Statistics Queries"),
+                    ty: wgpu::QueryType::PipelineStatistics(Self::STATISTICS),
+                    count: max_passes,
+                })
+            });
+
+        // 解析查询的目标偏移必须对齐到 256
+        let align = wgpu::QUERY_RESOLVE_BUFFER_ALIGNMENT;
+        let statistics_offset = (max_passes as u64 * 8).div_ceil(align) * align;
+        let size = statistics_offset + max_passes as u64 * Self::STATISTICS_COUNT * 8;
+        let resolve_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Profiler Resolve Buffer"),
+            size,
+            usage: wgpu::BufferUsages::QUERY_RESOLVE | wgpu::BufferUsages::COPY_SRC,
+            mapped_at_creation: false,
+        });
+        let readback_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Profiler Readback Buffer"),
+            size,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+
+        Self {
+            max_passes,
+            occlusion,
+            pipeline_statistics,
+            resolve_buffer,
+            readback_buffer,
+            statistics_offset,
+            names: Vec::new(),
+            active: false,
+            readback: Readback::Idle,
+            results: Vec::new(),
+        }
+    }
+
+    pub fn supports_pipeline_statistics(&self) -> bool {
+        self.pipeline_statistics.is_some()
+    }
+
+    /// 填入 `RenderPassDescriptor::occlusion_query_set`
+    pub fn occlusion_query_set(&self) -> Option<&QuerySet> {
+        Some(&self.occlusion)
+    }
+
+    /// 开始统计 `pass` 中之后的绘制；上一次的结果尚未读回或本帧 pass 数已满时不做统计
+    pub fn begin(&mut self, pass: &mut wgpu::RenderPass, name: &str) {
+        self.active =
+            matches!(self.readback, Readback::Idle) && self.names.len() < self.max_passes as usize;
+        if !self.active {
+            return;
+        }
+        let index = self.names.len() as u32;
+        self.names.push(name.to_string());
+        pass.begin_occlusion_query(index);
+        if let Some(set) = &self.pipeline_statistics {
+            pass.begin_pipeline_statistics_query(set, index);
+        }
+    }
+
+    pub fn end(&mut self, pass: &mut wgpu::RenderPass) {
+        if !std::mem::take(&mut self.active) {
+            return;
+        }
+        pass.end_occlusion_query();
+        if self.pipeline_statistics.is_some() {
+            pass.end_pipeline_statistics_query();
+        }
+    }
+
+    /// 把本帧的查询结果解析并复制到读回缓冲
+    pub fn resolve(&mut self, encoder: &mut wgpu::CommandEncoder) {
+        if self.names.is_empty() {
+            return;
+        }
+        let count = self.names.len() as u32;
+        encoder.resolve_query_set(&self.occlusion, 0..count, &self.resolve_buffer, 0);
+        if let Some(set) = &self.pipeline_statistics {
+            encoder.resolve_query_set(set, 0..count, &self.resolve_buffer, self.statistics_offset);
+        }
+        encoder.copy_buffer_to_buffer(
+            &self.resolve_buffer,
+            0,
+            &self.readback_buffer,
+            0,
+            self.readback_buffer.size(),
+        );
+        self.readback = Readback::Copied(std::mem::take(&mut self.names));
+    }
+
+    /// 提交包含 [`GpuProfiler::resolve`] 的命令之后调用，开始异步映射读回缓冲
+    pub fn map(&mut self) {
+        let Readback::Copied(names) = std::mem::replace(&mut self.readback, Readback::Idle) else {
+            return;
+        };
+        let (sender, receiver) = channel();
+        self.readback_buffer
+            .slice(..)
+            .map_async(wgpu::MapMode::Read, move |result| {
+                let _ = sender.send(result);
+            });
+        self.readback = Readback::Mapping(names, receiver);
+    }
+
+    /// 检查读回是否完成，完成时更新并返回最新的结果
+    pub fn poll(&mut self, device: &Device) -> Option<&[PassStatistics]> {
+        let Readback::Mapping(_, receiver) = &self.readback else {
+            return None;
+        };
+        device.poll(wgpu::Maintain::Poll);
+        let result = receiver.try_recv().ok()?;
+        let Readback::Mapping(names, _) = std::mem::replace(&mut self.readback, Readback::Idle)
+        else {
+            unreachable!()
+        };
+        if let Err(e) = result {
+            eprintln!("failed to read back profiler queries: {e}");
+            return None;
+        }
+
+        let data = self.readback_buffer.slice(..).get_mapped_range();
+        let values: &[u64] = bytemuck::cast_slice(&data);
+        let statistics = (self.statistics_offset / 8) as usize;
+        let statistics_count = Self::STATISTICS_COUNT as usize;
+        self.results = names
+            .into_iter()
+            .enumerate()
+            .map(|(i, name)| {
+                // 管线统计按 PipelineStatisticsTypes 的位顺序写入
+                let stat = |k: usize| {
+                    self.pipeline_statistics
+                        .as_ref()
+                        .map(|_| values[statistics + i * statistics_count + k])
+                };
+                PassStatistics {
+                    name,
+                    samples_passed: values[i],
+                    vertex_invocations: stat(0),
+                    primitives: stat(1),
+                    fragment_invocations: stat(2),
+                }
+            })
+            .collect();
+        drop(data);
+        self.readback_buffer.unmap();
+        Some(&self.results)
+    }
+
+    /// 最近一次读回的结果
+    pub fn results(&self) -> &[PassStatistics] {
+        &self.results
+    }
+}
+
+impl std::fmt::Display for PassStatistics {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {} samples", self.name, self.samples_passed)?;
+        if let (Some(vertices), Some(primitives), Some(fragments)) = (
+            self.vertex_invocations,
+            self.primitives,
+            self.fragment_invocations,
+        ) {
+            write!(
+                f,
+                ", {vertices} vertices, {primitives} primitives, {fragments} fragments"
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// [`GpuCounters`] 环形读回缓冲中一个槽位的状态，`frame` 为复制计数时的帧序号
+enum CounterSlot {
+    Idle,
+    Copied(u64, Vec<String>),
+    Mapping(
+        u64,
+        Vec<String>,
+        Receiver<Result<(), wgpu::BufferAsyncError>>,
+    ),
+}
+
+/// 不阻塞渲染地读回 GPU 上的计数，例如剔除后的实例数或存活的粒子数
+///
+/// 每帧把计数复制进环形排列的读回缓冲中的一个，提交后异步映射，完成后再取出，
+/// 因此结果通常晚几帧出现，延迟由 [`GpuCounters::latency`] 给出。所有缓冲都在等待映射时
+/// 跳过本帧的复制，不会等待 GPU。每帧的用法：
+///
+/// 1. 写入计数的通道之后调用 [`GpuCounters::copy`]，源缓冲须带有 `COPY_SRC` 用途；
+/// 2. 提交之后调用 [`GpuCounters::map`]；
+/// 3. 用 [`GpuCounters::poll`] 更新最新的结果，例如在下一帧的 `update` 中。
+pub struct GpuCounters {
+    capacity: u32,
+    buffers: Vec<Buffer>,
+    slots: Vec<CounterSlot>,
+    /// 本帧写入的槽位
+    current: usize,
+    frame: u64,
+    values: Vec<(String, u32)>,
+    /// 最近一次结果的帧序号
+    values_frame: Option<u64>,
+}
+
+impl GpuCounters {
+    /// 每帧最多读回 `capacity` 个 u32 计数，`frames` 为读回缓冲的个数
+    pub fn new(device: &Device, capacity: u32, frames: usize) -> Self {
+        assert!(capacity > 0, "counters need at least one slot");
+        assert!(frames > 0, "counters need at least one readback buffer");
+        let buffers = (0..frames)
+            .map(|_| {
+                device.create_buffer(&wgpu::BufferDescriptor {
+                    label: Some("Counter Readback Buffer"),
+                    size: capacity as u64 * 4,
+                    usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+                    mapped_at_creation: false,
+                })
+            })
+            .collect();
+        Self {
+            capacity,
+            buffers,
+            slots: (0..frames).map(|_| CounterSlot::Idle).collect(),
+            current: 0,
+            frame: 0,
+            values: Vec::new(),
+            values_frame: None,
+        }
+    }
+
+    /// 复制 `source` 中 `offset` 处的 u32，`offset` 须为 4 的倍数；
+    /// 本帧的缓冲仍在映射或计数已满时不复制并返回 `false`
+    pub fn copy(
+        &mut self,
+        encoder: &mut wgpu::CommandEncoder,
+        name: &str,
+        source: &Buffer,
+        offset: wgpu::BufferAddress,
+    ) -> bool {
+        if let CounterSlot::Idle = self.slots[self.current] {
+            self.slots[self.current] = CounterSlot::Copied(self.frame, Vec::new());
+        }
+        let CounterSlot::Copied(_, names) = &mut self.slots[self.current] else {
+            return false;
+        };
+        if names.len() >= self.capacity as usize {
+            return false;
+        }
+        let index = names.len() as u64;
+        names.push(name.to_string());
+        encoder.copy_buffer_to_buffer(source, offset, &self.buffers[self.current], index * 4, 4);
+        true
+    }
+
+    /// 提交包含 [`GpuCounters::copy`] 的命令之后每帧调用一次，开始异步映射并换到下一个缓冲
+    pub fn map(&mut self) {
+        let slot = &mut self.slots[self.current];
+        if let CounterSlot::Copied(frame, names) = std::mem::replace(slot, CounterSlot::Idle) {
+            let (sender, receiver) = channel();
+            self.buffers[self.current]
+                .slice(..names.len() as u64 * 4)
+                .map_async(wgpu::MapMode::Read, move |result| {
+                    let _ = sender.send(result);
+                });
+            *slot = CounterSlot::Mapping(frame, names, receiver);
+            self.current = (self.current + 1) % self.slots.len();
+        }
+        self.frame += 1;
+    }
+
+    /// 取出已经完成映射的结果，有新结果时返回 `true`
+    pub fn poll(&mut self, device: &Device) -> bool {
+        device.poll(wgpu::Maintain::Poll);
+        let mut updated = false;
+        for (slot, buffer) in self.slots.iter_mut().zip(&self.buffers) {
+            let CounterSlot::Mapping(_, _, receiver) = slot else {
+                continue;
+            };
+            let Ok(result) = receiver.try_recv() else {
+                continue;
+            };
+            let CounterSlot::Mapping(frame, names, _) = std::mem::replace(slot, CounterSlot::Idle)
+            else {
+                unreachable!()
+            };
+            if let Err(e) = result {
+                eprintln!("failed to read back GPU counters: {e}");
+                continue;
+            }
+            let data = buffer.slice(..names.len() as u64 * 4).get_mapped_range();
+            let values: &[u32] = bytemuck::cast_slice(&data);
+            // 多个缓冲同时完成时保留最新的一帧
+            if self.values_frame.is_none_or(|latest| frame > latest) {
+                self.values = names.into_iter().zip(values.iter().copied()).collect();
+                self.values_frame = Some(frame);
+                updated = true;
+            }
+            drop(data);
+            buffer.unmap();
+        }
+        updated
+    }
+
+    /// 最近一次读回的计数，按复制的顺序排列
+    pub fn values(&self) -> &[(String, u32)] {
+        &self.values
+    }
+
+    pub fn get(&self, name: &str) -> Option<u32> {
+        self.values
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|&(_, value)| value)
+    }
+
+    /// 最近一次结果距今的帧数，还没有结果时为 `None`
+    pub fn latency(&self) -> Option<u64> {
+        self.values_frame.map(|frame| self.frame - frame)
+    }
+
+    /// 例如 `visible 1234 | alive 560 (2 frames late)`，还没有结果时为空
+    pub fn summary(&self) -> String {
+        let Some(latency) = self.latency() else {
+            return String::new();
+        };
+        let values = self
+            .values
+            .iter()
+            .map(|(name, value)| format!("{name} {value}"))
+            .collect::<Vec<_>>()
+            .join(" | ");
+        format!("{values} ({latency} frames late)")
+    }
+}
+
+/// CPU 上一段工作的耗时，例如 [`JobSystem`](crate::jobs::JobSystem) 中的一次并行任务
+#[derive(Debug, Clone)]
+pub struct CpuSpan {
+    pub name: String,
+    pub duration: Duration,
+    /// 处理的元素个数
+    pub items: usize,
+    /// 分配到的工作线程数，1 表示在调用线程上顺序执行
+    pub threads: usize,
+}
+
+impl std::fmt::Display for CpuSpan {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}: {:.3} ms, {} items on {} threads",
+            self.name,
+            self.duration.as_secs_f64() * 1000.0,
+            self.items,
+            self.threads
+        )
+    }
+}
+
+/// 收集 CPU 帧准备阶段各段工作的耗时，可以在多个线程间共享
+///
+/// 每帧结束时用 [`CpuProfiler::take`] 取走本帧的记录。
+#[derive(Debug, Default)]
+pub struct CpuProfiler {
+    spans: Mutex<Vec<CpuSpan>>,
+}
+
+impl CpuProfiler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 开始计时，返回的守卫被丢弃时记录一段耗时
+    pub fn scope(&self, name: impl Into<String>) -> CpuScope<'_> {
+        CpuScope {
+            profiler: self,
+            name: name.into(),
+            start: Instant::now(),
+            items: 0,
+            threads: 1,
+        }
+    }
+
+    pub fn record(&self, span: CpuSpan) {
+        self.spans.lock().unwrap().push(span);
+    }
+
+    /// 取走目前记录的所有耗时，按记录顺序排列
+    pub fn take(&self) -> Vec<CpuSpan> {
+        std::mem::take(&mut *self.spans.lock().unwrap())
+    }
+}
+
+/// [`CpuProfiler::scope`] 返回的计时守卫
+pub struct CpuScope<'a> {
+    profiler: &'a CpuProfiler,
+    name: String,
+    start: Instant,
+    items: usize,
+    threads: usize,
+}
+
+impl CpuScope<'_> {
+    pub fn set_items(&mut self, items: usize, threads: usize) {
+        self.items = items;
+        self.threads = threads;
+    }
+}
+
+impl Drop for CpuScope<'_> {
+    fn drop(&mut self) {
+        self.profiler.record(CpuSpan {
+            name: std::mem::take(&mut self.name),
+            duration: self.start.elapsed(),
+            items: self.items,
+            threads: self.threads,
+        });
+    }
+}
@@ -0,0 +1,108 @@
+/// 世界空间中的射线，`direction` 为单位向量
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Ray {
+    pub origin: glam::Vec3,
+    pub direction: glam::Vec3,
+}
+
+impl Ray {
+    pub fn new(origin: glam::Vec3, direction: glam::Vec3) -> Self {
+        Self {
+            origin,
+            direction: direction.normalize(),
+        }
+    }
+
+    pub fn at(&self, t: f32) -> glam::Vec3 {
+        self.origin + self.direction * t
+    }
+
+    /// 经 `matrix` 变换后的射线，常用于变换到物体的模型空间求交；
+    /// 矩阵含缩放时方向会重新归一化，射线参数与原射线不再对应，应比较变换回来的交点
+    pub fn transform(&self, matrix: glam::Mat4) -> Self {
+        Self::new(
+            matrix.transform_point3(self.origin),
+            matrix.transform_vector3(self.direction),
+        )
+    }
+
+    /// 与过 `point`、法线为 `normal` 的平面的交点参数，射线与平面平行或交点在身后时返回 `None`
+    pub fn intersect_plane(&self, point: glam::Vec3, normal: glam::Vec3) -> Option<f32> {
+        let denom = self.direction.dot(normal);
+        if denom.abs() < 1e-6 {
+            return None;
+        }
+        let t = (point - self.origin).dot(normal) / denom;
+        (t >= 0.0).then_some(t)
+    }
+
+    /// 与球面最近的交点参数，起点在球内时返回 0
+    pub fn intersect_sphere(&self, center: glam::Vec3, radius: f32) -> Option<f32> {
+        let oc = self.origin - center;
+        let b = oc.dot(self.direction);
+        let c = oc.length_squared() - radius * radius;
+        if c <= 0.0 {
+            return Some(0.0);
+        }
+        let discriminant = b * b - c;
+        if discriminant < 0.0 {
+            return None;
+        }
+        let t = -b - discriminant.sqrt();
+        (t >= 0.0).then_some(t)
+    }
+
+    /// 射线与过 `point`、方向为 `direction` 的直线上最近的两点，返回 (射线参数, 直线参数)；
+    /// 两者平行时返回 `None`，射线参数可能为负
+    pub fn closest_to_line(&self, point: glam::Vec3, direction: glam::Vec3) -> Option<(f32, f32)> {
+        let w = self.origin - point;
+        let b = self.direction.dot(direction);
+        let c = direction.length_squared();
+        let d = self.direction.dot(w);
+        let e = direction.dot(w);
+        let denom = c - b * b;
+        if denom.abs() < 1e-6 * c {
+            return None;
+        }
+        Some(((b * e - c * d) / denom, (e - b * d) / denom))
+    }
+
+    /// 与轴对齐包围盒的进入参数，起点在盒内时返回 0
+    pub fn intersect_aabb(&self, min: glam::Vec3, max: glam::Vec3) -> Option<f32> {
+        let inv = self.direction.recip();
+        let t0 = (min - self.origin) * inv;
+        let t1 = (max - self.origin) * inv;
+        let near = t0.min(t1).max_element().max(0.0);
+        let far = t0.max(t1).min_element();
+        (near <= far).then_some(near)
+    }
+
+    /// 与三角形 `abc` 的交点（Möller–Trumbore），返回射线参数与 `b`、`c` 的重心坐标；
+    /// `cull_back` 为真时忽略从背面射入的交点，即沿射线看去 `abc` 为顺时针
+    pub fn intersect_triangle(
+        &self,
+        [a, b, c]: [glam::Vec3; 3],
+        cull_back: bool,
+    ) -> Option<(f32, glam::Vec2)> {
+        let ab = b - a;
+        let ac = c - a;
+        let p = self.direction.cross(ac);
+        let det = ab.dot(p);
+        if det.abs() < 1e-10 || (cull_back && det < 0.0) {
+            return None;
+        }
+        let inv_det = 1.0 / det;
+        let s = self.origin - a;
+        let u = s.dot(p) * inv_det;
+        if !(0.0..=1.0).contains(&u) {
+            return None;
+        }
+        let q = s.cross(ab);
+        let v = self.direction.dot(q) * inv_det;
+        if v < 0.0 || u + v > 1.0 {
+            return None;
+        }
+        let t = ac.dot(q) * inv_det;
+        (t >= 0.0).then_some((t, glam::vec2(u, v)))
+    }
+}
@@ -0,0 +1,224 @@
+use anyhow::ensure;
+use wgpu::{util::DeviceExt, BindGroup, BindGroupLayout, Buffer, Device, Queue};
+
+use crate::{
+    camera::{Camera, CameraBundle, CameraUniform},
+    texture::{DepthConvention, Texture},
+};
+
+/// 平面方程 `normal · p + d = 0`
+#[derive(Debug, Copy, Clone)]
+pub struct Plane {
+    pub normal: glam::Vec3,
+    pub d: f32,
+}
+
+impl Plane {
+    /// 法线与 `d` 一起缩放到单位长度的法线，法线为零向量时返回错误
+    pub fn new(normal: glam::Vec3, d: f32) -> anyhow::Result<Self> {
+        let len = normal.length();
+        ensure!(
+            len > 0.0 && len.is_finite(),
+            "invalid plane normal {normal}"
+        );
+        Ok(Self {
+            normal: normal / len,
+            d: d / len,
+        })
+    }
+
+    pub fn from_point_normal(point: glam::Vec3, normal: glam::Vec3) -> anyhow::Result<Self> {
+        Self::new(normal, -normal.dot(point))
+    }
+
+    pub fn signed_distance(&self, p: glam::Vec3) -> f32 {
+        self.normal.dot(p) + self.d
+    }
+
+    /// 关于该平面的镜像变换矩阵
+    pub fn reflection_matrix(&self) -> glam::Mat4 {
+        let glam::Vec3 { x, y, z } = self.normal;
+        let d = self.d;
+        glam::Mat4::from_cols(
+            glam::vec4(1.0 - 2.0 * x * x, -2.0 * x * y, -2.0 * x * z, 0.0),
+            glam::vec4(-2.0 * x * y, 1.0 - 2.0 * y * y, -2.0 * y * z, 0.0),
+            glam::vec4(-2.0 * x * z, -2.0 * y * z, 1.0 - 2.0 * z * z, 0.0),
+            glam::vec4(-2.0 * d * x, -2.0 * d * y, -2.0 * d * z, 1.0),
+        )
+    }
+
+    /// `(normal, d)`，与齐次坐标点的点积即有符号距离
+    pub fn to_vec4(&self) -> glam::Vec4 {
+        self.normal.extend(self.d)
+    }
+}
+
+/// 把投影的近平面换成观察空间中的平面 `clip`，只保留 `clip · p >= 0` 的一侧，
+/// 远平面随之倾斜（Lengyel 的斜近平面投影）；`clip.w` 须为负，即相机位于被裁掉的一侧
+fn oblique_projection(proj: glam::Mat4, clip: glam::Vec4, depth: DepthConvention) -> glam::Mat4 {
+    // 视锥中离 clip 最远的角，倾斜后的远平面经过这个角；无限远投影时为方向（w 为 0）
+    let corner =
+        proj.inverse() * glam::vec4(clip.x.signum(), clip.y.signum(), depth.far_depth(), 1.0);
+    let scaled = clip / clip.dot(corner);
+    let mut rows = proj.transpose();
+    rows.z_axis = match depth {
+        DepthConvention::Standard => scaled,
+        DepthConvention::Reversed => rows.w_axis - scaled,
+    };
+    rows.transpose()
+}
+
+/// 平面反射：把场景关于 `plane` 镜像后渲染到一张离屏纹理中，
+/// 反射面的材质再以屏幕空间坐标对其采样。
+///
+/// 反射相机使用斜近平面投影，近平面与反射平面重合，平面另一侧（原本在平面之下）的几何
+/// 由光栅化的近平面裁剪去掉，绘制反射场景的着色器不需要额外处理。相机位于平面之下时
+/// 无法这样裁剪，退回普通投影。
+///
+/// # NOTE:
+/// 镜像变换会翻转三角形的环绕方向，渲染反射场景的管线需要使用 `FrontFace::Cw`
+/// （或关闭背面剔除）。
+pub struct PlanarReflection {
+    pub plane: Plane,
+    pub color: Texture,
+    pub depth: Texture,
+    pub camera_buffer: Buffer,
+    pub camera_bind_group: BindGroup,
+    pub texture_bind_group: BindGroup,
+    uniform: CameraUniform,
+    /// 取自构建时的相机，决定深度纹理的比较方式与清除值
+    convention: DepthConvention,
+}
+
+impl PlanarReflection {
+    pub fn new(
+        plane: Plane,
+        device: &Device,
+        config: &wgpu::SurfaceConfiguration,
+        camera: &CameraBundle,
+        texture_layout: &BindGroupLayout,
+    ) -> Self {
+        let mut uniform = CameraUniform::new();
+        Self::set_reflected_camera(&mut uniform, &plane, &camera.state);
+        let camera_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Reflection Camera Buffer"),
+            contents: bytemuck::cast_slice(&[uniform]),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+        let camera_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: &camera.bind_group_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: camera_buffer.as_entire_binding(),
+            }],
+            label: Some("reflection_camera_bind_group"),
+        });
+
+        let color = Texture::create_render_target(device, config, "reflection_color");
+        let convention = camera.state.depth;
+        let depth =
+            Texture::create_depth_texture_with(device, config, "reflection_depth", convention);
+        let texture_bind_group = Self::create_texture_bind_group(device, &color, texture_layout);
+
+        Self {
+            plane,
+            color,
+            depth,
+            camera_buffer,
+            camera_bind_group,
+            texture_bind_group,
+            uniform,
+            convention,
+        }
+    }
+
+    /// 镜像后的相机写入 `uniform`，返回镜像后的 view-projection
+    fn set_reflected_camera(
+        uniform: &mut CameraUniform,
+        plane: &Plane,
+        camera: &Camera,
+    ) -> glam::Mat4 {
+        let reflection = plane.reflection_matrix();
+        let view = camera.view_matrix() * reflection;
+        let mut proj = camera.projection_matrix();
+        // 平面变换到镜像相机的观察空间，w 为镜像相机到平面的有符号距离
+        let clip = view.inverse().transpose() * plane.to_vec4();
+        if clip.w < 0.0 {
+            proj = oblique_projection(proj, clip, camera.depth);
+        }
+        // 镜像矩阵是自身的逆，镜像后的相机位于 eye 关于平面的对称点
+        uniform.set_view_and_projection(view, proj, reflection.transform_point3(camera.eye));
+        proj * view
+    }
+
+    fn create_texture_bind_group(
+        device: &Device,
+        color: &Texture,
+        layout: &BindGroupLayout,
+    ) -> BindGroup {
+        device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(&color.view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(&color.sampler),
+                },
+            ],
+            label: Some("reflection_texture_bind_group"),
+        })
+    }
+
+    /// surface 大小变化后需要重建离屏纹理
+    pub fn resize(
+        &mut self,
+        device: &Device,
+        config: &wgpu::SurfaceConfiguration,
+        texture_layout: &BindGroupLayout,
+    ) {
+        self.color = Texture::create_render_target(device, config, "reflection_color");
+        self.depth =
+            Texture::create_depth_texture_with(device, config, "reflection_depth", self.convention);
+        self.texture_bind_group =
+            Self::create_texture_bind_group(device, &self.color, texture_layout);
+    }
+
+    pub fn update(&mut self, queue: &Queue, camera: &Camera) {
+        let view_proj = Self::set_reflected_camera(&mut self.uniform, &self.plane, camera);
+        // 反射 pass 不输出速度，上一帧矩阵取本帧即可
+        self.uniform.set_motion(view_proj, glam::Vec2::ZERO);
+        queue.write_buffer(
+            &self.camera_buffer,
+            0,
+            bytemuck::cast_slice(&[self.uniform]),
+        );
+    }
+
+    /// 开始一个渲染到反射纹理的 pass，调用者在其中绘制需要被反射的物体
+    pub fn begin_pass<'a>(
+        &'a self,
+        encoder: &'a mut wgpu::CommandEncoder,
+        clear_color: wgpu::Color,
+    ) -> wgpu::RenderPass<'a> {
+        encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Reflection Pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: &self.color.view,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(clear_color),
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                view: &self.depth.view,
+                depth_ops: Some(self.convention.clear_ops()),
+                stencil_ops: None,
+            }),
+            ..Default::default()
+        })
+    }
+}
@@ -0,0 +1,347 @@
+//! 输入与窗口事件的录制与回放
+//!
+//! 录制文件为逐行的文本，`frame` 行记录一帧的时长，其后的事件在下一帧更新之前派发。
+//! 事件行可以以 `@秒数` 开头，记录事件距录制开始的时间：
+//!
+//! ```text
+//! frame 0.016667
+//! @0.0213 key KeyW down
+//! @0.0301 button Left up
+//! cursor 320.5 240
+//! wheel line 0 1
+//! motion 1.5 -2
+//! resize 1280 720
+//! scale 1.5
+//! focus false
+//! cursor_left
+//! hover /path/to/model.obj
+//! hover
+//! drop /path/to/model.obj
+//! ```
+//!
+//! 回放时帧时长也取自文件，配合固定步长或只依赖 [`FrameTime`](crate::app::FrameTime) 的应用，
+//! 每次回放得到相同的结果。时间戳只供阅读，回放始终按帧同步。
+
+use std::{
+    fs::File,
+    io::{BufWriter, Write},
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+// wasm32 上 std::time::Instant 不可用
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::Instant;
+#[cfg(target_arch = "wasm32")]
+use web_time::Instant;
+
+use anyhow::{anyhow, bail, Context};
+use winit::{
+    dpi::{PhysicalPosition, PhysicalSize},
+    event::{ElementState, MouseButton, MouseScrollDelta},
+    keyboard::{KeyCode, PhysicalKey},
+};
+
+use crate::app::KeyInput;
+
+/// 可以录制与回放的事件：输入，以及会影响应用状态的窗口事件
+#[derive(Debug, Clone, PartialEq)]
+pub enum InputEvent {
+    Key(KeyInput),
+    MouseButton(ElementState, MouseButton),
+    Wheel(MouseScrollDelta),
+    Cursor(PhysicalPosition<f64>),
+    /// 原始的鼠标相对移动，即 `DeviceEvent::MouseMotion`
+    MouseMotion(f64, f64),
+    /// 窗口内部大小，回放时请求把窗口调整为该大小
+    Resized(PhysicalSize<u32>),
+    ScaleFactor(f64),
+    Focused(bool),
+    CursorLeft,
+    /// 拖到窗口上方的文件，`None` 表示拖离窗口
+    HoveredFile(Option<PathBuf>),
+    DroppedFile(PathBuf),
+}
+
+macro_rules! key_codes {
+    ($($name:ident),* $(,)?) => {
+        fn key_name(code: KeyCode) -> Option<&'static str> {
+            match code {
+                $(KeyCode::$name => Some(stringify!($name)),)*
+                _ => None,
+            }
+        }
+
+        fn parse_key(name: &str) -> Option<KeyCode> {
+            match name {
+                $(stringify!($name) => Some(KeyCode::$name),)*
+                _ => None,
+            }
+        }
+    };
+}
+
+// 只录制常用按键，其余按键不写入文件
+#[rustfmt::skip]
+key_codes!(
+    KeyA, KeyB, KeyC, KeyD, KeyE, KeyF, KeyG, KeyH, KeyI, KeyJ, KeyK, KeyL, KeyM, KeyN, KeyO,
+    KeyP, KeyQ, KeyR, KeyS, KeyT, KeyU, KeyV, KeyW, KeyX, KeyY, KeyZ, Digit0, Digit1, Digit2,
+    Digit3, Digit4, Digit5, Digit6, Digit7, Digit8, Digit9, ArrowUp, ArrowDown, ArrowLeft,
+    ArrowRight, Space, Enter, Escape, Tab, Backspace, Delete, ShiftLeft, ShiftRight,
+    ControlLeft, ControlRight, AltLeft, AltRight, Equal, Minus, BracketLeft, BracketRight,
+    Comma, Period, Slash, Backslash, Semicolon, Quote, Backquote, PageUp, PageDown, Home, End,
+    F1, F2, F3, F4, F5, F6, F7, F8, F9, F10, F11, F12,
+);
+
+fn state_name(state: ElementState) -> &'static str {
+    match state {
+        ElementState::Pressed => "down",
+        ElementState::Released => "up",
+    }
+}
+
+fn parse_state(s: &str) -> anyhow::Result<ElementState> {
+    match s {
+        "down" => Ok(ElementState::Pressed),
+        "up" => Ok(ElementState::Released),
+        _ => bail!("expected `down` or `up`, found `{s}`"),
+    }
+}
+
+impl InputEvent {
+    /// 文件中的一行，无法录制的事件返回 `None`
+    fn to_line(&self) -> Option<String> {
+        Some(match self {
+            InputEvent::Key(key) => {
+                let PhysicalKey::Code(code) = key.physical_key else {
+                    return None;
+                };
+                let repeat = if key.repeat { " repeat" } else { "" };
+                format!("key {} {}{repeat}", key_name(code)?, state_name(key.state))
+            }
+            InputEvent::MouseButton(state, button) => {
+                let button = match button {
+                    MouseButton::Left => "Left".to_string(),
+                    MouseButton::Right => "Right".to_string(),
+                    MouseButton::Middle => "Middle".to_string(),
+                    MouseButton::Back => "Back".to_string(),
+                    MouseButton::Forward => "Forward".to_string(),
+                    MouseButton::Other(n) => n.to_string(),
+                };
+                format!("button {button} {}", state_name(*state))
+            }
+            InputEvent::Wheel(MouseScrollDelta::LineDelta(x, y)) => format!("wheel line {x} {y}"),
+            InputEvent::Wheel(MouseScrollDelta::PixelDelta(p)) => {
+                format!("wheel pixel {} {}", p.x, p.y)
+            }
+            InputEvent::Cursor(p) => format!("cursor {} {}", p.x, p.y),
+            InputEvent::MouseMotion(dx, dy) => format!("motion {dx} {dy}"),
+            InputEvent::Resized(size) => format!("resize {} {}", size.width, size.height),
+            InputEvent::ScaleFactor(scale) => format!("scale {scale}"),
+            InputEvent::Focused(focused) => format!("focus {focused}"),
+            InputEvent::CursorLeft => "cursor_left".to_string(),
+            InputEvent::HoveredFile(None) => "hover".to_string(),
+            // 路径占据行的剩余部分，不是 UTF-8 的路径无法录制
+            InputEvent::HoveredFile(Some(path)) => format!("hover {}", path.to_str()?),
+            InputEvent::DroppedFile(path) => format!("drop {}", path.to_str()?),
+        })
+    }
+
+    /// `rest` 为关键字之后的原始文本，供含空格的路径使用
+    fn parse(keyword: &str, args: &[&str], rest: &str) -> anyhow::Result<Self> {
+        let number = |i: usize| -> anyhow::Result<f64> {
+            let arg = args.get(i).ok_or_else(|| anyhow!("missing argument"))?;
+            arg.parse().map_err(|_| anyhow!("invalid number `{arg}`"))
+        };
+        let arg = |i: usize| -> anyhow::Result<&str> {
+            args.get(i)
+                .copied()
+                .ok_or_else(|| anyhow!("missing argument"))
+        };
+        Ok(match keyword {
+            "key" => {
+                let name = arg(0)?;
+                let code = parse_key(name).ok_or_else(|| anyhow!("unknown key `{name}`"))?;
+                InputEvent::Key(KeyInput {
+                    physical_key: PhysicalKey::Code(code),
+                    state: parse_state(arg(1)?)?,
+                    repeat: args.get(2) == Some(&"repeat"),
+                })
+            }
+            "button" => {
+                let button = match arg(0)? {
+                    "Left" => MouseButton::Left,
+                    "Right" => MouseButton::Right,
+                    "Middle" => MouseButton::Middle,
+                    "Back" => MouseButton::Back,
+                    "Forward" => MouseButton::Forward,
+                    other => MouseButton::Other(
+                        other
+                            .parse()
+                            .map_err(|_| anyhow!("unknown mouse button `{other}`"))?,
+                    ),
+                };
+                InputEvent::MouseButton(parse_state(arg(1)?)?, button)
+            }
+            "wheel" => match arg(0)? {
+                "line" => InputEvent::Wheel(MouseScrollDelta::LineDelta(
+                    number(1)? as f32,
+                    number(2)? as f32,
+                )),
+                "pixel" => InputEvent::Wheel(MouseScrollDelta::PixelDelta(PhysicalPosition::new(
+                    number(1)?,
+                    number(2)?,
+                ))),
+                other => bail!("expected `line` or `pixel`, found `{other}`"),
+            },
+            "cursor" => InputEvent::Cursor(PhysicalPosition::new(number(0)?, number(1)?)),
+            "motion" => InputEvent::MouseMotion(number(0)?, number(1)?),
+            "resize" => {
+                InputEvent::Resized(PhysicalSize::new(number(0)? as u32, number(1)? as u32))
+            }
+            "scale" => InputEvent::ScaleFactor(number(0)?),
+            "focus" => InputEvent::Focused(match arg(0)? {
+                "true" => true,
+                "false" => false,
+                other => bail!("expected `true` or `false`, found `{other}`"),
+            }),
+            "cursor_left" => InputEvent::CursorLeft,
+            "hover" if rest.is_empty() => InputEvent::HoveredFile(None),
+            "hover" => InputEvent::HoveredFile(Some(PathBuf::from(rest))),
+            "drop" if rest.is_empty() => bail!("missing argument"),
+            "drop" => InputEvent::DroppedFile(PathBuf::from(rest)),
+            _ => bail!("unknown event `{keyword}`"),
+        })
+    }
+}
+
+/// 把事件与每帧时长写入文件，交给 [`WindowAppHandler::with_input_recording`](crate::app::WindowAppHandler::with_input_recording)
+pub struct InputRecorder {
+    writer: BufWriter<File>,
+    started: Instant,
+}
+
+impl InputRecorder {
+    pub fn create(path: impl AsRef<Path>) -> anyhow::Result<Self> {
+        let path = path.as_ref();
+        let file = File::create(path)
+            .with_context(|| format!("failed to create input recording {}", path.display()))?;
+        Ok(Self {
+            writer: BufWriter::new(file),
+            started: Instant::now(),
+        })
+    }
+
+    /// 记录事件及其距录制开始的时间
+    pub fn record_event(&mut self, event: &InputEvent) {
+        if let Some(line) = event.to_line() {
+            let time = self.started.elapsed().as_secs_f64();
+            self.write_line(&format!("@{time:.4} {line}"));
+        }
+    }
+
+    /// 记录一帧的时长，此前记录的事件会在回放这一帧之前派发
+    pub fn record_frame(&mut self, delta: Duration) {
+        self.write_line(&format!("frame {}", delta.as_secs_f64()));
+    }
+
+    pub fn flush(&mut self) {
+        if let Err(e) = self.writer.flush() {
+            eprintln!("failed to write input recording: {e}");
+        }
+    }
+
+    fn write_line(&mut self, line: &str) {
+        if let Err(e) = writeln!(self.writer, "{line}") {
+            eprintln!("failed to write input recording: {e}");
+        }
+    }
+}
+
+/// 录制文件中的一帧：先派发的事件与这一帧的时长
+#[derive(Debug, Clone, Default)]
+pub struct ReplayFrame {
+    pub events: Vec<InputEvent>,
+    pub delta: Duration,
+}
+
+/// 从文件读取的录制，交给 [`WindowAppHandler::with_input_replay`](crate::app::WindowAppHandler::with_input_replay)
+#[derive(Debug, Clone, Default)]
+pub struct InputReplay {
+    frames: std::collections::VecDeque<ReplayFrame>,
+    /// 回放结束后退出程序，用于自动化测试
+    pub exit_when_finished: bool,
+    /// 回放结束时把最后一帧保存为截图，用于与基准图像比对；需要 [`WindowApp::gpu_context`](crate::app::WindowApp::gpu_context)
+    pub screenshot_when_finished: Option<PathBuf>,
+}
+
+impl InputReplay {
+    pub fn load(path: impl AsRef<Path>) -> anyhow::Result<Self> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path)
+            .with_context(|| format!("failed to read input recording {}", path.display()))?;
+        Self::parse(&text).with_context(|| format!("invalid input recording {}", path.display()))
+    }
+
+    pub fn parse(text: &str) -> anyhow::Result<Self> {
+        let mut frames = std::collections::VecDeque::new();
+        let mut events = Vec::new();
+        for (i, line) in text.lines().enumerate() {
+            let mut line = line.trim();
+            // 时间戳只供阅读
+            if line.starts_with('@') {
+                line = line
+                    .split_once(char::is_whitespace)
+                    .map_or("", |(_, rest)| rest);
+            }
+            let (keyword, rest) = line
+                .split_once(char::is_whitespace)
+                .map_or((line, ""), |(keyword, rest)| (keyword, rest.trim()));
+            if keyword.is_empty() || keyword.starts_with('#') {
+                continue;
+            }
+            let args = rest.split_whitespace().collect::<Vec<_>>();
+            if keyword == "frame" {
+                let delta = args
+                    .first()
+                    .and_then(|s| s.parse::<f64>().ok())
+                    .filter(|d| d.is_finite() && *d >= 0.0)
+                    .ok_or_else(|| anyhow!("line {}: invalid frame duration", i + 1))?;
+                frames.push_back(ReplayFrame {
+                    events: std::mem::take(&mut events),
+                    delta: Duration::from_secs_f64(delta),
+                });
+            } else {
+                events.push(
+                    InputEvent::parse(keyword, &args, rest)
+                        .with_context(|| format!("line {}", i + 1))?,
+                );
+            }
+        }
+        // 最后一帧之后的事件不会再有 update，丢弃
+        Ok(Self {
+            frames,
+            exit_when_finished: false,
+            screenshot_when_finished: None,
+        })
+    }
+
+    pub fn exit_when_finished(mut self, exit: bool) -> Self {
+        self.exit_when_finished = exit;
+        self
+    }
+
+    pub fn screenshot_when_finished(mut self, path: Option<PathBuf>) -> Self {
+        self.screenshot_when_finished = path;
+        self
+    }
+
+    /// 剩余的帧数
+    pub fn remaining(&self) -> usize {
+        self.frames.len()
+    }
+
+    pub fn next_frame(&mut self) -> Option<ReplayFrame> {
+        self.frames.pop_front()
+    }
+}
@@ -0,0 +1,327 @@
+use std::time::Duration;
+
+use wgpu::{BindGroup, Buffer, CommandEncoder, Device, Queue, RenderPipeline, TextureView};
+
+use crate::{
+    post::begin_fullscreen_pass, shader::ShaderLibrary, texture::Texture, uniform::GpuUniform,
+};
+
+/// 内部分辨率放大到输出时使用的滤波
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum UpscaleFilter {
+    Bilinear,
+    /// 双线性放大后做对比度自适应锐化，`sharpness` 取 0 到 1
+    Sharpen {
+        sharpness: f32,
+    },
+}
+
+/// 根据近期帧时间调整渲染比例的控制器
+#[derive(Debug, Clone)]
+pub struct ResolutionController {
+    /// 期望的帧时间，垂直同步下应略小于刷新间隔，否则帧时间总等于刷新间隔而无法提高比例
+    pub target_frame_time: Duration,
+    pub min_scale: f32,
+    pub max_scale: f32,
+    /// 每次调整的比例步长
+    pub step: f32,
+    /// 两次调整之间至少间隔的帧数，避免比例来回振荡
+    pub cooldown: u32,
+    scale: f32,
+    /// 帧时间的指数滑动平均，单位秒
+    average: Option<f32>,
+    frames_since_change: u32,
+}
+
+impl ResolutionController {
+    pub fn new(target_frame_time: Duration) -> Self {
+        Self {
+            target_frame_time,
+            min_scale: 0.5,
+            max_scale: 1.0,
+            step: 0.05,
+            cooldown: 30,
+            scale: 1.0,
+            average: None,
+            frames_since_change: 0,
+        }
+    }
+
+    pub fn scale(&self) -> f32 {
+        self.scale
+    }
+
+    pub fn set_scale(&mut self, scale: f32) {
+        self.scale = scale.clamp(self.min_scale, self.max_scale);
+        self.frames_since_change = 0;
+    }
+
+    /// 记录一帧的时长，比例改变时返回 `true`
+    pub fn record(&mut self, frame_time: Duration) -> bool {
+        let t = frame_time.as_secs_f32();
+        if t <= 0.0 {
+            return false;
+        }
+        let average = match self.average {
+            Some(average) => average + (t - average) * 0.1,
+            None => t,
+        };
+        self.average = Some(average);
+        self.frames_since_change += 1;
+        if self.frames_since_change < self.cooldown {
+            return false;
+        }
+
+        let target = self.target_frame_time.as_secs_f32();
+        // 在目标附近留出余量，超出时降低比例，明显有富余时才提高
+        let scale = if average > target * 1.05 {
+            self.scale - self.step
+        } else if average < target * 0.85 {
+            self.scale + self.step
+        } else {
+            return false;
+        };
+        let scale = scale.clamp(self.min_scale, self.max_scale);
+        if (scale - self.scale).abs() < f32::EPSILON {
+            return false;
+        }
+        self.set_scale(scale);
+        true
+    }
+}
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone, GpuUniform)]
+struct UpscaleUniform {
+    region: [f32; 4],
+    params: [f32; 4],
+}
+
+/// 动态分辨率：场景先以较低的内部分辨率渲染，再放大到输出
+///
+/// 内部颜色与深度纹理按输出大小分配，改变比例时只改变视口，不会重新创建纹理。
+/// 绘制场景时使用 [`DynamicResolution::color_view`] 与 [`DynamicResolution::depth_view`]
+/// 作为附件，并调用 [`DynamicResolution::set_viewport`]，之后用 [`DynamicResolution::upscale`]
+/// 输出到 surface。
+pub struct DynamicResolution {
+    pub controller: ResolutionController,
+    pub filter: UpscaleFilter,
+    /// 为 `false` 时固定按输出分辨率渲染
+    pub enabled: bool,
+    color: Texture,
+    depth: Texture,
+    output_size: (u32, u32),
+    sampler: wgpu::Sampler,
+    texture_layout: wgpu::BindGroupLayout,
+    texture_bind_group: BindGroup,
+    uniform_buffer: Buffer,
+    uniform_bind_group: BindGroup,
+    pipeline: RenderPipeline,
+}
+
+impl DynamicResolution {
+    /// `config` 为 surface 配置，内部颜色纹理与 surface 使用相同的格式
+    pub fn new(
+        device: &Device,
+        config: &wgpu::SurfaceConfiguration,
+        controller: ResolutionController,
+    ) -> Self {
+        let color = Texture::create_render_target(device, config, "dynamic_resolution_color");
+        let depth = Texture::create_depth_texture(device, config, "dynamic_resolution_depth");
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("Upscale Sampler"),
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+        let texture_layout = Texture::texture_bind_group_layout(device);
+        let texture_bind_group =
+            Self::create_texture_bind_group(device, &texture_layout, &color, &sampler);
+
+        let uniform_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Upscale Uniform Buffer"),
+            size: std::mem::size_of::<UpscaleUniform>() as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let uniform_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            }],
+            label: Some("upscale_uniform_bind_group_layout"),
+        });
+        let uniform_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: &uniform_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: uniform_buffer.as_entire_binding(),
+            }],
+            label: Some("upscale_uniform_bind_group"),
+        });
+
+        let shader = ShaderLibrary::new()
+            .create_shader_module(
+                device,
+                "Upscale Shader",
+                include_str!("../shaders/upscale.wgsl"),
+            )
+            .expect("built-in upscale shader");
+        let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Upscale Pipeline Layout"),
+            bind_group_layouts: &[&texture_layout, &uniform_layout],
+            push_constant_ranges: &[],
+        });
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Upscale Pipeline"),
+            layout: Some(&layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                compilation_options: Default::default(),
+                entry_point: Some("vs_main"),
+                buffers: &[],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                compilation_options: Default::default(),
+                entry_point: Some("fs_main"),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: config.format,
+                    blend: None,
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+            cache: None,
+        });
+
+        Self {
+            controller,
+            filter: UpscaleFilter::Sharpen { sharpness: 0.5 },
+            enabled: true,
+            color,
+            depth,
+            output_size: (config.width, config.height),
+            sampler,
+            texture_layout,
+            texture_bind_group,
+            uniform_buffer,
+            uniform_bind_group,
+            pipeline,
+        }
+    }
+
+    fn create_texture_bind_group(
+        device: &Device,
+        layout: &wgpu::BindGroupLayout,
+        color: &Texture,
+        sampler: &wgpu::Sampler,
+    ) -> BindGroup {
+        device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(&color.view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(sampler),
+                },
+            ],
+            label: Some("upscale_texture_bind_group"),
+        })
+    }
+
+    /// 输出大小改变后重新创建内部纹理
+    pub fn resize(&mut self, device: &Device, config: &wgpu::SurfaceConfiguration) {
+        self.color = Texture::create_render_target(device, config, "dynamic_resolution_color");
+        self.depth = Texture::create_depth_texture(device, config, "dynamic_resolution_depth");
+        self.texture_bind_group = Self::create_texture_bind_group(
+            device,
+            &self.texture_layout,
+            &self.color,
+            &self.sampler,
+        );
+        self.output_size = (config.width, config.height);
+    }
+
+    /// 当前的渲染比例，禁用时为 1
+    pub fn scale(&self) -> f32 {
+        if self.enabled {
+            self.controller.scale()
+        } else {
+            1.0
+        }
+    }
+
+    /// 内部渲染的像素大小
+    pub fn internal_size(&self) -> (u32, u32) {
+        let scale = self.scale();
+        let (width, height) = self.output_size;
+        (
+            ((width as f32 * scale).round() as u32).clamp(1, width),
+            ((height as f32 * scale).round() as u32).clamp(1, height),
+        )
+    }
+
+    /// 记录一帧的时长并调整比例，比例改变时返回 `true`
+    pub fn record_frame_time(&mut self, frame_time: Duration) -> bool {
+        self.enabled && self.controller.record(frame_time)
+    }
+
+    pub fn color_view(&self) -> &TextureView {
+        &self.color.view
+    }
+
+    pub fn depth_view(&self) -> &TextureView {
+        &self.depth.view
+    }
+
+    /// 把视口限制在内部分辨率对应的左上角区域
+    pub fn set_viewport(&self, pass: &mut wgpu::RenderPass) {
+        let (width, height) = self.internal_size();
+        pass.set_viewport(0.0, 0.0, width as f32, height as f32, 0.0, 1.0);
+    }
+
+    /// 把内部颜色纹理放大写入 `output`
+    pub fn upscale(&self, queue: &Queue, encoder: &mut CommandEncoder, output: &TextureView) {
+        let (width, height) = self.output_size;
+        let (internal_width, internal_height) = self.internal_size();
+        let texel = glam::vec2(1.0 / width as f32, 1.0 / height as f32);
+        let region = glam::vec2(
+            internal_width as f32 / width as f32,
+            internal_height as f32 / height as f32,
+        );
+        let sharpness = match self.filter {
+            UpscaleFilter::Bilinear => 0.0,
+            UpscaleFilter::Sharpen { sharpness } => sharpness.clamp(0.0, 1.0),
+        };
+        UpscaleUniform {
+            // 采样点不越过有效区域最后半个纹素，避免混入区域外的旧内容
+            region: [
+                region.x,
+                region.y,
+                region.x - texel.x * 0.5,
+                region.y - texel.y * 0.5,
+            ],
+            params: [texel.x, texel.y, sharpness, 0.0],
+        }
+        .write_to(queue, &self.uniform_buffer);
+
+        let mut pass = begin_fullscreen_pass(encoder, "Upscale Pass", output);
+        pass.set_pipeline(&self.pipeline);
+        pass.set_bind_group(0, &self.texture_bind_group, &[]);
+        pass.set_bind_group(1, &self.uniform_bind_group, &[]);
+        pass.draw(0..3, 0..1);
+    }
+}
@@ -1,32 +1,48 @@
+use std::path::{Path, PathBuf};
+
 use crate::texture::Texture;
 
+/// 内置资源所在的目录
+pub fn res_dir() -> anyhow::Result<PathBuf> {
+    Ok(std::env::current_dir()?.join("res").join("cube"))
+}
+
 pub async fn load_string(file_name: &str) -> anyhow::Result<String> {
-    let path = std::env::current_dir()?
-        .join("res")
-        .join("cube")
-        .join(file_name);
-    println!("load string path = {}", path.to_str().unwrap());
+    load_string_from(&res_dir()?.join(file_name)).await
+}
+
+pub async fn load_binary(file_name: &str) -> anyhow::Result<Vec<u8>> {
+    load_binary_from(&res_dir()?.join(file_name)).await
+}
+
+pub async fn load_texture(
+    file_name: &str,
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+) -> anyhow::Result<Texture> {
+    load_texture_from(&res_dir()?.join(file_name), device, queue).await
+}
+
+/// 与 [`load_string`] 相同，但使用任意路径，例如拖入窗口的文件
+pub async fn load_string_from(path: &Path) -> anyhow::Result<String> {
+    println!("load string path = {}", path.display());
     let txt = std::fs::read_to_string(path)?;
 
     Ok(txt)
 }
 
-pub async fn load_binary(file_name: &str) -> anyhow::Result<Vec<u8>> {
-    let path = std::env::current_dir()?
-        .join("res")
-        .join("cube")
-        .join(file_name);
-    println!("load binary path = {}", path.to_str().unwrap());
+pub async fn load_binary_from(path: &Path) -> anyhow::Result<Vec<u8>> {
+    println!("load binary path = {}", path.display());
     let data = std::fs::read(path)?;
 
     Ok(data)
 }
 
-pub async fn load_texture(
-    file_name: &str,
+pub async fn load_texture_from(
+    path: &Path,
     device: &wgpu::Device,
     queue: &wgpu::Queue,
 ) -> anyhow::Result<Texture> {
-    let data = load_binary(file_name).await?;
-    Texture::from_bytes(device, queue, &data, file_name)
+    let data = load_binary_from(path).await?;
+    Texture::from_bytes(device, queue, &data, &path.to_string_lossy())
 }
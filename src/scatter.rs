@@ -0,0 +1,592 @@
+use bytemuck::{Pod, Zeroable};
+use glam::Vec3Swizzles;
+use image::GenericImageView;
+use wgpu::{util::DeviceExt, BindGroup, Buffer, Device, Queue};
+
+use crate::{camera::Camera, frustum::Frustum, jobs, model::RenderVertex, shader::ShaderLibrary};
+
+/// 简单的确定性随机数生成器（SplitMix64），保证相同种子得到相同的分布
+#[derive(Debug, Clone)]
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Self(seed)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e3779b97f4a7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^ (z >> 31)
+    }
+
+    /// [0, 1) 之间的均匀分布
+    pub fn next_f32(&mut self) -> f32 {
+        (self.next_u64() >> 40) as f32 / (1u64 << 24) as f32
+    }
+
+    pub fn range(&mut self, min: f32, max: f32) -> f32 {
+        min + (max - min) * self.next_f32()
+    }
+}
+
+/// Bridson 泊松圆盘采样：在矩形区域内生成两两间距不小于 `min_distance` 的点
+pub fn poisson_disk(
+    min: glam::Vec2,
+    max: glam::Vec2,
+    min_distance: f32,
+    rng: &mut Rng,
+) -> Vec<glam::Vec2> {
+    const ATTEMPTS: usize = 30;
+
+    assert!(min_distance > 0.0);
+    let extent = max - min;
+    let cell = min_distance / std::f32::consts::SQRT_2;
+    let cols = (extent.x / cell).ceil().max(1.0) as usize;
+    let rows = (extent.y / cell).ceil().max(1.0) as usize;
+    let mut grid = vec![usize::MAX; cols * rows];
+    let cell_of = |p: glam::Vec2| {
+        let c = (((p.x - min.x) / cell) as usize).min(cols - 1);
+        let r = (((p.y - min.y) / cell) as usize).min(rows - 1);
+        (c, r)
+    };
+
+    let mut points = Vec::new();
+    let mut active = Vec::new();
+
+    let first = min + glam::vec2(rng.next_f32(), rng.next_f32()) * extent;
+    let (c, r) = cell_of(first);
+    grid[r * cols + c] = 0;
+    points.push(first);
+    active.push(0);
+
+    while !active.is_empty() {
+        let slot = (rng.next_u64() % active.len() as u64) as usize;
+        let center = points[active[slot]];
+        let mut found = false;
+
+        for _ in 0..ATTEMPTS {
+            let angle = rng.range(0.0, std::f32::consts::TAU);
+            let radius = rng.range(min_distance, 2.0 * min_distance);
+            let candidate = center + glam::Vec2::from_angle(angle) * radius;
+            if candidate.cmplt(min).any() || candidate.cmpge(max).any() {
+                continue;
+            }
+
+            let (c, r) = cell_of(candidate);
+            let too_close = (r.saturating_sub(2)..(r + 3).min(rows)).any(|nr| {
+                (c.saturating_sub(2)..(c + 3).min(cols)).any(|nc| {
+                    let index = grid[nr * cols + nc];
+                    index != usize::MAX
+                        && points[index].distance_squared(candidate) < min_distance * min_distance
+                })
+            });
+            if too_close {
+                continue;
+            }
+
+            grid[r * cols + c] = points.len();
+            active.push(points.len());
+            points.push(candidate);
+            found = true;
+            break;
+        }
+
+        if !found {
+            active.swap_remove(slot);
+        }
+    }
+
+    points
+}
+
+/// 灰度密度图，取值 [0, 1]，控制某处保留采样点的概率
+#[derive(Debug, Clone)]
+pub struct DensityMap {
+    pub width: u32,
+    pub height: u32,
+    pub values: Vec<f32>,
+}
+
+impl DensityMap {
+    pub fn from_image(img: &image::DynamicImage) -> Self {
+        let (width, height) = img.dimensions();
+        let values = img
+            .to_luma8()
+            .pixels()
+            .map(|p| p.0[0] as f32 / 255.0)
+            .collect();
+        Self {
+            width,
+            height,
+            values,
+        }
+    }
+
+    /// 双线性插值采样，`uv` 超出 [0, 1] 时取边缘值
+    pub fn sample(&self, uv: glam::Vec2) -> f32 {
+        let uv = uv.clamp(glam::Vec2::ZERO, glam::Vec2::ONE);
+        let x = uv.x * (self.width - 1) as f32;
+        let y = uv.y * (self.height - 1) as f32;
+        let (x0, y0) = (x.floor() as u32, y.floor() as u32);
+        let (x1, y1) = ((x0 + 1).min(self.width - 1), (y0 + 1).min(self.height - 1));
+        let (fx, fy) = (x.fract(), y.fract());
+        let at = |x: u32, y: u32| self.values[(y * self.width + x) as usize];
+        let top = at(x0, y0) * (1.0 - fx) + at(x1, y0) * fx;
+        let bottom = at(x0, y1) * (1.0 - fx) + at(x1, y1) * fx;
+        top * (1.0 - fy) + bottom * fy
+    }
+}
+
+/// 用于在地形网格上查询高度与法线，按 XZ 平面上的均匀网格加速
+#[derive(Debug, Clone)]
+pub struct MeshSurface {
+    triangles: Vec<[glam::Vec3; 3]>,
+    min: glam::Vec2,
+    max: glam::Vec2,
+    cells: usize,
+    buckets: Vec<Vec<u32>>,
+}
+
+impl MeshSurface {
+    pub fn new(positions: &[glam::Vec3], indices: &[u32]) -> Self {
+        let triangles = indices
+            .chunks_exact(3)
+            .map(|t| {
+                [
+                    positions[t[0] as usize],
+                    positions[t[1] as usize],
+                    positions[t[2] as usize],
+                ]
+            })
+            .collect::<Vec<_>>();
+
+        let (min, max) = positions.iter().fold(
+            (glam::Vec2::splat(f32::MAX), glam::Vec2::splat(f32::MIN)),
+            |(min, max), p| (min.min(p.xz()), max.max(p.xz())),
+        );
+        let cells = ((triangles.len() as f32).sqrt().ceil() as usize).max(1);
+        let mut surface = Self {
+            triangles,
+            min,
+            max,
+            cells,
+            buckets: vec![Vec::new(); cells * cells],
+        };
+
+        for (i, tri) in surface.triangles.iter().enumerate() {
+            let lo = tri[0].xz().min(tri[1].xz()).min(tri[2].xz());
+            let hi = tri[0].xz().max(tri[1].xz()).max(tri[2].xz());
+            let (c0, r0) = surface.cell_of(lo);
+            let (c1, r1) = surface.cell_of(hi);
+            for r in r0..=r1 {
+                for c in c0..=c1 {
+                    surface.buckets[r * cells + c].push(i as u32);
+                }
+            }
+        }
+
+        surface
+    }
+
+    fn cell_of(&self, p: glam::Vec2) -> (usize, usize) {
+        let extent = (self.max - self.min).max(glam::Vec2::splat(f32::EPSILON));
+        let t = ((p - self.min) / extent).clamp(glam::Vec2::ZERO, glam::Vec2::ONE);
+        let last = self.cells - 1;
+        (
+            ((t.x * self.cells as f32) as usize).min(last),
+            ((t.y * self.cells as f32) as usize).min(last),
+        )
+    }
+
+    /// XZ 平面上的包围范围
+    pub fn bounds(&self) -> (glam::Vec2, glam::Vec2) {
+        (self.min, self.max)
+    }
+
+    /// 查询 (x, z) 处最高的表面点，返回高度与朝上的法线
+    pub fn sample(&self, x: f32, z: f32) -> Option<(f32, glam::Vec3)> {
+        let p = glam::vec2(x, z);
+        if p.cmplt(self.min).any() || p.cmpgt(self.max).any() {
+            return None;
+        }
+        let (c, r) = self.cell_of(p);
+
+        self.buckets[r * self.cells + c]
+            .iter()
+            .filter_map(|&i| {
+                let [a, b, c] = self.triangles[i as usize];
+                let (v0, v1, v2) = (b.xz() - a.xz(), c.xz() - a.xz(), p - a.xz());
+                let denom = v0.x * v1.y - v1.x * v0.y;
+                if denom.abs() < f32::EPSILON {
+                    return None;
+                }
+                let u = (v2.x * v1.y - v1.x * v2.y) / denom;
+                let v = (v0.x * v2.y - v2.x * v0.y) / denom;
+                if u < 0.0 || v < 0.0 || u + v > 1.0 {
+                    return None;
+                }
+                let height = a.y + u * (b.y - a.y) + v * (c.y - a.y);
+                let mut normal = (b - a).cross(c - a).normalize();
+                if normal.y < 0.0 {
+                    normal = -normal;
+                }
+                Some((height, normal))
+            })
+            .max_by(|a, b| a.0.total_cmp(&b.0))
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ScatterSettings {
+    /// 实例之间的最小间距
+    pub min_distance: f32,
+    pub density: Option<DensityMap>,
+    pub seed: u64,
+    pub scale_range: (f32, f32),
+    /// 0 表示保持竖直，1 表示完全沿表面法线对齐
+    pub align_to_normal: f32,
+    /// 表面坡度超过该值（法线与 +Y 的夹角余弦小于它）时不放置实例
+    pub min_normal_y: f32,
+}
+
+impl Default for ScatterSettings {
+    fn default() -> Self {
+        Self {
+            min_distance: 1.0,
+            density: None,
+            seed: 0,
+            scale_range: (0.8, 1.2),
+            align_to_normal: 0.3,
+            min_normal_y: 0.5,
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone)]
+pub struct ScatterInstance {
+    pub position: glam::Vec3,
+    pub rotation: glam::Quat,
+    pub scale: f32,
+}
+
+impl ScatterInstance {
+    pub fn model_matrix(&self) -> glam::Mat4 {
+        glam::Mat4::from_scale_rotation_translation(
+            glam::Vec3::splat(self.scale),
+            self.rotation,
+            self.position,
+        )
+    }
+}
+
+/// 在网格表面上用泊松圆盘采样放置实例，再按密度图随机剔除
+pub fn scatter(surface: &MeshSurface, settings: &ScatterSettings) -> Vec<ScatterInstance> {
+    let mut rng = Rng::new(settings.seed);
+    let (min, max) = surface.bounds();
+    let extent = (max - min).max(glam::Vec2::splat(f32::EPSILON));
+
+    poisson_disk(min, max, settings.min_distance, &mut rng)
+        .into_iter()
+        .filter_map(|p| {
+            if let Some(density) = &settings.density {
+                if rng.next_f32() >= density.sample((p - min) / extent) {
+                    return None;
+                }
+            }
+            let (height, normal) = surface.sample(p.x, p.y)?;
+            if normal.y < settings.min_normal_y {
+                return None;
+            }
+
+            let up = glam::Vec3::Y
+                .lerp(normal, settings.align_to_normal)
+                .normalize();
+            let rotation = glam::Quat::from_rotation_arc(glam::Vec3::Y, up)
+                * glam::Quat::from_rotation_y(rng.range(0.0, std::f32::consts::TAU));
+            let scale = rng.range(settings.scale_range.0, settings.scale_range.1);
+
+            Some(ScatterInstance {
+                position: glam::vec3(p.x, height, p.y),
+                rotation,
+                scale,
+            })
+        })
+        .collect()
+}
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct SourceInstance {
+    model: [[f32; 4]; 4],
+    // xyz: 包围球球心, w: 半径
+    bounds: [f32; 4],
+}
+
+unsafe impl Zeroable for SourceInstance {}
+unsafe impl Pod for SourceInstance {}
+
+impl SourceInstance {
+    pub(crate) fn new(instance: &ScatterInstance, bounding_radius: f32) -> Self {
+        Self {
+            model: instance.model_matrix().to_cols_array_2d(),
+            bounds: instance
+                .position
+                .extend(bounding_radius * instance.scale)
+                .to_array(),
+        }
+    }
+}
+
+/// 经过 GPU 剔除后写入的实例数据，`fade` 为距离淡出系数
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct CulledInstanceRaw {
+    model: [[f32; 4]; 4],
+    fade: [f32; 4],
+}
+
+unsafe impl Zeroable for CulledInstanceRaw {}
+unsafe impl Pod for CulledInstanceRaw {}
+
+impl RenderVertex for CulledInstanceRaw {
+    /// 模型矩阵占用 0~3 号位置，淡出系数位于 7 号位置，
+    /// 与使用 4~6 号位置的模型顶点不冲突
+    fn buffer_layout_desc<'a>() -> wgpu::VertexBufferLayout<'a> {
+        const ATTRIBUTES: [wgpu::VertexAttribute; 5] = wgpu::vertex_attr_array![
+            0 => Float32x4,
+            1 => Float32x4,
+            2 => Float32x4,
+            3 => Float32x4,
+            7 => Float32x4,
+        ];
+        wgpu::VertexBufferLayout {
+            array_stride: std::mem::size_of::<CulledInstanceRaw>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Instance,
+            attributes: &ATTRIBUTES,
+        }
+    }
+}
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+struct CullUniform {
+    planes: [[f32; 4]; 6],
+    // xyz: 相机位置, w: 开始淡出的距离
+    eye: [f32; 4],
+    // x: 完全消失的距离, y: 实例数量
+    params: [f32; 4],
+}
+
+unsafe impl Zeroable for CullUniform {}
+unsafe impl Pod for CullUniform {}
+
+/// 从 view-projection 矩阵提取 6 个裁剪平面，见 [`Frustum`]
+pub(crate) fn frustum_planes(view_proj: glam::Mat4) -> [[f32; 4]; 6] {
+    Frustum::from_view_proj(view_proj)
+        .planes
+        .map(|plane| plane.to_array())
+}
+
+/// 在 CPU 上对包围球做视锥剔除，返回可见的下标；`spheres` 的 xyz 为球心，w 为半径
+pub fn cull_spheres(view_proj: glam::Mat4, spheres: &[glam::Vec4]) -> Vec<u32> {
+    let frustum = Frustum::from_view_proj(view_proj);
+    jobs::global().filter_indices("frustum culling", spheres, |sphere| {
+        frustum.intersects_sphere(sphere.truncate(), sphere.w)
+    })
+}
+
+/// 在 GPU 上对散布的实例做视锥剔除与距离淡出，
+/// 结果写入实例缓冲并通过 `draw_indexed_indirect` 绘制
+pub struct ScatterCuller {
+    pub fade_start: f32,
+    pub fade_end: f32,
+    instance_count: u32,
+    index_count: u32,
+    uniform_buffer: Buffer,
+    output_buffer: Buffer,
+    indirect_buffer: Buffer,
+    bind_group: BindGroup,
+    pipeline: wgpu::ComputePipeline,
+}
+
+impl ScatterCuller {
+    /// `bounding_radius` 为模型在单位缩放下的包围球半径，`index_count` 为要绘制的网格索引数
+    pub fn new(
+        device: &Device,
+        instances: &[ScatterInstance],
+        bounding_radius: f32,
+        index_count: u32,
+    ) -> Self {
+        let sources = instances
+            .iter()
+            .map(|i| SourceInstance::new(i, bounding_radius))
+            .collect::<Vec<_>>();
+        let source_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Scatter Source Buffer"),
+            contents: bytemuck::cast_slice(&sources),
+            usage: wgpu::BufferUsages::STORAGE,
+        });
+        let output_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Scatter Culled Instance Buffer"),
+            size: (std::mem::size_of::<CulledInstanceRaw>() * instances.len().max(1))
+                as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::VERTEX,
+            mapped_at_creation: false,
+        });
+        let indirect_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Scatter Indirect Buffer"),
+            contents: wgpu::util::DrawIndexedIndirectArgs {
+                index_count,
+                instance_count: 0,
+                first_index: 0,
+                base_vertex: 0,
+                first_instance: 0,
+            }
+            .as_bytes(),
+            usage: wgpu::BufferUsages::STORAGE
+                | wgpu::BufferUsages::INDIRECT
+                | wgpu::BufferUsages::COPY_DST
+                | wgpu::BufferUsages::COPY_SRC,
+        });
+        let uniform_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Scatter Cull Uniform Buffer"),
+            size: std::mem::size_of::<CullUniform>() as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let storage = |binding, read_only| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility: wgpu::ShaderStages::COMPUTE,
+            ty: wgpu::BindingType::Buffer {
+                ty: wgpu::BufferBindingType::Storage { read_only },
+                has_dynamic_offset: false,
+                min_binding_size: None,
+            },
+            count: None,
+        };
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::COMPUTE,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                storage(1, true),
+                storage(2, false),
+                storage(3, false),
+            ],
+            label: Some("scatter_cull_bind_group_layout"),
+        });
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: &bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: uniform_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: source_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: output_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 3,
+                    resource: indirect_buffer.as_entire_binding(),
+                },
+            ],
+            label: Some("scatter_cull_bind_group"),
+        });
+
+        let shader = ShaderLibrary::new()
+            .create_shader_module(
+                device,
+                "Scatter Cull Shader",
+                include_str!("../shaders/scatter_cull.wgsl"),
+            )
+            .expect("built-in scatter cull shader");
+        let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Scatter Cull Pipeline Layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+        let pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+            label: Some("Scatter Cull Pipeline"),
+            layout: Some(&layout),
+            module: &shader,
+            entry_point: Some("cs_main"),
+            compilation_options: Default::default(),
+            cache: None,
+        });
+
+        Self {
+            fade_start: 40.0,
+            fade_end: 60.0,
+            instance_count: instances.len() as u32,
+            index_count,
+            uniform_buffer,
+            output_buffer,
+            indirect_buffer,
+            bind_group,
+            pipeline,
+        }
+    }
+
+    /// 写入本帧的剔除参数并清零间接绘制的实例计数
+    pub fn update(&self, queue: &Queue, camera: &Camera) {
+        let uniform = CullUniform {
+            planes: frustum_planes(camera.build_view_projection_matrix()),
+            eye: camera.eye.extend(self.fade_start).to_array(),
+            params: [self.fade_end, self.instance_count as f32, 0.0, 0.0],
+        };
+        queue.write_buffer(&self.uniform_buffer, 0, bytemuck::cast_slice(&[uniform]));
+        queue.write_buffer(
+            &self.indirect_buffer,
+            0,
+            wgpu::util::DrawIndexedIndirectArgs {
+                index_count: self.index_count,
+                instance_count: 0,
+                first_index: 0,
+                base_vertex: 0,
+                first_instance: 0,
+            }
+            .as_bytes(),
+        );
+    }
+
+    pub fn cull(&self, encoder: &mut wgpu::CommandEncoder) {
+        let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+            label: Some("Scatter Cull Pass"),
+            timestamp_writes: None,
+        });
+        pass.set_pipeline(&self.pipeline);
+        pass.set_bind_group(0, &self.bind_group, &[]);
+        pass.dispatch_workgroups(self.instance_count.div_ceil(64), 1, 1);
+    }
+
+    /// 剔除后的实例缓冲，布局见 [`CulledInstanceRaw`]
+    pub fn instance_buffer(&self) -> &Buffer {
+        &self.output_buffer
+    }
+
+    /// 带有 `COPY_SRC` 用途，可以从 [`INSTANCE_COUNT_OFFSET`](crate::indirect::INSTANCE_COUNT_OFFSET)
+    /// 处读回可见的实例数
+    pub fn indirect_buffer(&self) -> &Buffer {
+        &self.indirect_buffer
+    }
+
+    pub fn instance_count(&self) -> u32 {
+        self.instance_count
+    }
+}
@@ -0,0 +1,421 @@
+use anyhow::ensure;
+use glam::{Mat4, Vec3};
+
+use crate::{
+    ao::AoBaker,
+    bvh::Bvh,
+    camera::Camera,
+    light::{DirectionalLight, PointLight},
+};
+
+pub mod pick;
+pub mod raster;
+pub mod raytrace;
+
+/// [`raster::SceneRenderer`] 最多支持的平行光数量，与 `shaders/scene.wgsl` 一致
+pub const MAX_DIRECTIONAL_LIGHTS: usize = 4;
+/// [`raster::SceneRenderer`] 最多支持的点光源数量，与 `shaders/scene.wgsl` 一致
+pub const MAX_POINT_LIGHTS: usize = 8;
+
+/// 两种渲染器共用的材质：Lambert 漫反射加 Blinn-Phong 高光
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct SceneMaterial {
+    /// 线性空间的漫反射颜色
+    pub base_color: Vec3,
+    /// 高光强度，0 表示纯漫反射
+    pub specular: f32,
+    pub shininess: f32,
+}
+
+impl Default for SceneMaterial {
+    fn default() -> Self {
+        Self {
+            base_color: Vec3::splat(0.8),
+            specular: 0.0,
+            shininess: 32.0,
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone)]
+pub enum SceneLight {
+    Directional(DirectionalLight),
+    Point(PointLight),
+}
+
+/// 三角形网格，顶点在模型空间，由 `transform` 变换到世界空间
+#[derive(Debug, Clone, PartialEq)]
+pub struct SceneMesh {
+    pub positions: Vec<Vec3>,
+    /// 与 `positions` 一一对应
+    pub normals: Vec<Vec3>,
+    /// 逆时针为正面
+    pub indices: Vec<u32>,
+    pub transform: Mat4,
+    /// [`Scene::materials`] 中的下标
+    pub material: usize,
+    /// 逐顶点的环境光遮蔽，与 `positions` 一一对应，乘以环境光；为空时不遮蔽，
+    /// 可由 [`Scene::bake_ao`] 生成
+    pub ao: Vec<f32>,
+}
+
+impl SceneMesh {
+    pub fn new(positions: Vec<Vec3>, normals: Vec<Vec3>, indices: Vec<u32>) -> Self {
+        Self {
+            positions,
+            normals,
+            indices,
+            transform: Mat4::IDENTITY,
+            material: 0,
+            ao: Vec::new(),
+        }
+    }
+
+    pub fn with_transform(mut self, transform: Mat4) -> Self {
+        self.transform = transform;
+        self
+    }
+
+    pub fn with_material(mut self, material: usize) -> Self {
+        self.material = material;
+        self
+    }
+
+    pub fn with_ao(mut self, ao: Vec<f32>) -> Self {
+        self.ao = ao;
+        self
+    }
+
+    /// 第 `vertex` 个顶点的环境光遮蔽
+    pub fn vertex_ao(&self, vertex: usize) -> f32 {
+        self.ao.get(vertex).copied().unwrap_or(1.0)
+    }
+
+    /// XZ 平面上边长为 `2 * half_size` 的正方形，法线朝 +Y
+    pub fn plane(half_size: f32) -> Self {
+        let h = half_size;
+        Self::new(
+            vec![
+                glam::vec3(-h, 0.0, -h),
+                glam::vec3(-h, 0.0, h),
+                glam::vec3(h, 0.0, h),
+                glam::vec3(h, 0.0, -h),
+            ],
+            vec![Vec3::Y; 4],
+            vec![0, 1, 2, 0, 2, 3],
+        )
+    }
+
+    /// 以原点为中心的长方体，每个面使用独立的顶点，保证棱边处法线不被平滑
+    pub fn cuboid(half_extents: Vec3) -> Self {
+        let mut mesh = Self::new(Vec::new(), Vec::new(), Vec::new());
+        for normal in [
+            Vec3::X,
+            Vec3::NEG_X,
+            Vec3::Y,
+            Vec3::NEG_Y,
+            Vec3::Z,
+            Vec3::NEG_Z,
+        ] {
+            // 面内的两个轴，`u × v` 与法线同向
+            let u = normal.any_orthonormal_vector();
+            let v = normal.cross(u);
+            let base = mesh.positions.len() as u32;
+            for (a, b) in [(-1.0, -1.0), (1.0, -1.0), (1.0, 1.0), (-1.0, 1.0)] {
+                mesh.positions.push((normal + u * a + v * b) * half_extents);
+                mesh.normals.push(normal);
+            }
+            mesh.indices
+                .extend([base, base + 1, base + 2, base, base + 2, base + 3]);
+        }
+        mesh
+    }
+
+    /// 以原点为中心的经纬球，`sectors` 为经线数，`stacks` 为纬线分段数
+    pub fn sphere(radius: f32, sectors: u32, stacks: u32) -> Self {
+        let sectors = sectors.max(3);
+        let stacks = stacks.max(2);
+        let mut mesh = Self::new(Vec::new(), Vec::new(), Vec::new());
+        for i in 0..=stacks {
+            let theta = i as f32 / stacks as f32 * std::f32::consts::PI;
+            for j in 0..=sectors {
+                let phi = j as f32 / sectors as f32 * std::f32::consts::TAU;
+                let normal = glam::vec3(
+                    theta.sin() * phi.cos(),
+                    theta.cos(),
+                    -theta.sin() * phi.sin(),
+                );
+                mesh.positions.push(normal * radius);
+                mesh.normals.push(normal);
+            }
+        }
+        let row = sectors + 1;
+        for i in 0..stacks {
+            for j in 0..sectors {
+                let a = i * row + j;
+                let b = a + row;
+                mesh.indices.extend([a, b, b + 1, a, b + 1, a + 1]);
+            }
+        }
+        mesh
+    }
+
+    /// 变换到世界空间的顶点位置与法线，两种渲染器都以此为准
+    pub fn world_vertices(&self) -> (Vec<Vec3>, Vec<Vec3>) {
+        let normal_matrix = glam::Mat3::from_mat4(self.transform).inverse().transpose();
+        let positions = self
+            .positions
+            .iter()
+            .map(|&p| self.transform.transform_point3(p))
+            .collect();
+        let normals = self
+            .normals
+            .iter()
+            .map(|&n| (normal_matrix * n).normalize_or_zero())
+            .collect();
+        (positions, normals)
+    }
+}
+
+/// 光栅化渲染器与 CPU 光线追踪共用的场景描述
+///
+/// 同一个场景既可以交给 [`raster::SceneRenderer`] 实时绘制，也可以交给
+/// [`raytrace::RayTracer`] 离线渲染；两者使用相同的世界空间几何
+/// （[`SceneMesh::world_vertices`]）与着色模型（[`Scene::shade`]），
+/// 因此光线追踪的结果可以作为光栅化路径的参考，用于对照或测试。
+#[derive(Debug, Clone)]
+pub struct Scene {
+    pub camera: Camera,
+    pub meshes: Vec<SceneMesh>,
+    pub materials: Vec<SceneMaterial>,
+    pub lights: Vec<SceneLight>,
+    /// 线性空间的环境光，乘以漫反射颜色
+    pub ambient: Vec3,
+    /// 线性空间的背景色
+    pub background: Vec3,
+    /// 物理单位的光源强度乘以的曝光，见 [`crate::light::ev100_exposure`]
+    pub exposure: f32,
+}
+
+impl Scene {
+    pub fn new(camera: Camera) -> Self {
+        Self {
+            camera,
+            meshes: Vec::new(),
+            materials: Vec::new(),
+            lights: Vec::new(),
+            ambient: Vec3::splat(0.03),
+            background: glam::vec3(0.05, 0.05, 0.08),
+            exposure: 1.0,
+        }
+    }
+
+    /// 添加材质，返回供 [`SceneMesh::material`] 使用的下标
+    pub fn add_material(&mut self, material: SceneMaterial) -> usize {
+        self.materials.push(material);
+        self.materials.len() - 1
+    }
+
+    /// 检查网格的下标、材质引用与光源数量
+    pub fn validate(&self) -> anyhow::Result<()> {
+        for (i, mesh) in self.meshes.iter().enumerate() {
+            ensure!(
+                mesh.positions.len() == mesh.normals.len(),
+                "mesh {i} has {} positions but {} normals",
+                mesh.positions.len(),
+                mesh.normals.len()
+            );
+            ensure!(
+                mesh.ao.is_empty() || mesh.ao.len() == mesh.positions.len(),
+                "mesh {i} has {} positions but {} ao values",
+                mesh.positions.len(),
+                mesh.ao.len()
+            );
+            ensure!(
+                mesh.indices.len() % 3 == 0,
+                "mesh {i} index count {} is not a multiple of 3",
+                mesh.indices.len()
+            );
+            ensure!(
+                mesh.indices
+                    .iter()
+                    .all(|&index| (index as usize) < mesh.positions.len()),
+                "mesh {i} has an index out of range"
+            );
+            ensure!(
+                mesh.material < self.materials.len(),
+                "mesh {i} uses material {} but the scene has {}",
+                mesh.material,
+                self.materials.len()
+            );
+        }
+        let directional = self
+            .lights
+            .iter()
+            .filter(|light| matches!(light, SceneLight::Directional(_)))
+            .count();
+        let point = self.lights.len() - directional;
+        ensure!(
+            directional <= MAX_DIRECTIONAL_LIGHTS,
+            "scene has {directional} directional lights, at most {MAX_DIRECTIONAL_LIGHTS} are supported"
+        );
+        ensure!(
+            point <= MAX_POINT_LIGHTS,
+            "scene has {point} point lights, at most {MAX_POINT_LIGHTS} are supported"
+        );
+        Ok(())
+    }
+
+    /// 烘焙所有网格的逐顶点环境光遮蔽，写入 [`SceneMesh::ao`]；遮挡物包括场景中所有网格
+    pub fn bake_ao(&mut self, baker: &AoBaker) -> anyhow::Result<()> {
+        self.validate()?;
+        let geometry = WorldGeometry::new(self);
+        for (mesh, (positions, normals)) in self.meshes.iter_mut().zip(&geometry.vertices) {
+            mesh.ao = baker.bake_vertices(&geometry.bvh, positions, normals);
+        }
+        Ok(())
+    }
+
+    /// 世界空间中 `position` 处的表面颜色（线性空间），与 `shaders/scene.wgsl` 的 `fs_main` 一致
+    ///
+    /// `normal` 与指向观察者的 `view_dir` 需已归一化；`ao` 为环境光遮蔽，只作用于环境光；
+    /// `visible` 接收指向光源的方向与距离（平行光为无穷远），返回 `false` 的光源不计入，
+    /// 用于光线追踪的阴影。
+    pub fn shade(
+        &self,
+        material: &SceneMaterial,
+        position: Vec3,
+        normal: Vec3,
+        view_dir: Vec3,
+        ao: f32,
+        mut visible: impl FnMut(Vec3, f32) -> bool,
+    ) -> Vec3 {
+        let mut color = material.base_color * self.ambient * ao;
+        for light in &self.lights {
+            let (light_dir, distance, radiance) = match light {
+                SceneLight::Directional(light) => (
+                    light.direction.normalize(),
+                    f32::INFINITY,
+                    light.color * light.shader_intensity(self.exposure),
+                ),
+                SceneLight::Point(light) => {
+                    let to_light = light.position - position;
+                    let distance = to_light.length();
+                    let attenuation = light.attenuation.factor(distance, light.range);
+                    (
+                        to_light / distance.max(1e-4),
+                        distance,
+                        light.color * light.shader_intensity(self.exposure) * attenuation,
+                    )
+                }
+            };
+            let n_dot_l = normal.dot(light_dir);
+            if n_dot_l <= 0.0 || !visible(light_dir, distance) {
+                continue;
+            }
+            let half_dir = (light_dir + view_dir).normalize();
+            let specular =
+                material.specular * normal.dot(half_dir).max(0.0).powf(material.shininess);
+            color += radiance * (material.base_color * n_dot_l + Vec3::splat(specular));
+        }
+        color
+    }
+}
+
+/// 所有网格变换到世界空间后的几何，三角形按网格顺序连续编号后放入同一个 [`Bvh`]
+#[derive(Debug, Clone)]
+pub(crate) struct WorldGeometry {
+    /// 每个网格世界空间的顶点位置与法线，见 [`SceneMesh::world_vertices`]
+    pub vertices: Vec<(Vec<Vec3>, Vec<Vec3>)>,
+    pub bvh: Bvh,
+    /// 每个网格第一个三角形的编号
+    first_triangle: Vec<usize>,
+}
+
+impl WorldGeometry {
+    pub fn new(scene: &Scene) -> Self {
+        let vertices = scene
+            .meshes
+            .iter()
+            .map(SceneMesh::world_vertices)
+            .collect::<Vec<_>>();
+        let mut first_triangle = Vec::with_capacity(scene.meshes.len());
+        let mut triangles = Vec::new();
+        for (mesh, (positions, _)) in scene.meshes.iter().zip(&vertices) {
+            first_triangle.push(triangles.len());
+            triangles.extend(
+                mesh.indices
+                    .chunks_exact(3)
+                    .map(|tri| [0, 1, 2].map(|i| positions[tri[i] as usize])),
+            );
+        }
+        Self {
+            vertices,
+            bvh: Bvh::from_triangles(triangles),
+            first_triangle,
+        }
+    }
+
+    /// 由 [`Bvh`] 中的三角形编号得到 `(网格下标, 网格内的三角形下标)`
+    pub fn locate(&self, triangle: usize) -> (usize, usize) {
+        let mesh = self
+            .first_triangle
+            .partition_point(|&first| first <= triangle)
+            - 1;
+        (mesh, triangle - self.first_triangle[mesh])
+    }
+}
+
+/// 线性空间的颜色编码为 8 位 sRGB，与写入 `*Srgb` 格式纹理时的转换一致
+pub fn linear_to_srgb8(color: Vec3) -> [u8; 3] {
+    color.to_array().map(|c| {
+        let c = c.clamp(0.0, 1.0);
+        let encoded = if c <= 0.0031308 {
+            c * 12.92
+        } else {
+            1.055 * c.powf(1.0 / 2.4) - 0.055
+        };
+        (encoded * 255.0).round() as u8
+    })
+}
+
+/// 两张图像之间的差异，只比较 RGB 通道
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct ImageDifference {
+    /// 单个通道的最大差值
+    pub max: u8,
+    /// 所有通道差值的均方根，0 到 255
+    pub rmse: f32,
+    /// 任一通道差值超过 `tolerance` 的像素所占的比例
+    pub mismatched: f32,
+}
+
+impl ImageDifference {
+    pub fn new(a: &image::RgbaImage, b: &image::RgbaImage, tolerance: u8) -> anyhow::Result<Self> {
+        ensure!(
+            a.dimensions() == b.dimensions(),
+            "image sizes differ: {:?} and {:?}",
+            a.dimensions(),
+            b.dimensions()
+        );
+        let mut max = 0;
+        let mut sum_squared = 0.0f64;
+        let mut mismatched = 0usize;
+        for (pa, pb) in a.pixels().zip(b.pixels()) {
+            let mut pixel_max = 0;
+            for channel in 0..3 {
+                let diff = pa[channel].abs_diff(pb[channel]);
+                pixel_max = pixel_max.max(diff);
+                sum_squared += (diff as f64).powi(2);
+            }
+            max = max.max(pixel_max);
+            mismatched += (pixel_max > tolerance) as usize;
+        }
+        let pixels = (a.width() as usize * a.height() as usize).max(1);
+        Ok(Self {
+            max,
+            rmse: (sum_squared / (pixels * 3) as f64).sqrt() as f32,
+            mismatched: mismatched as f32 / pixels as f32,
+        })
+    }
+}
@@ -0,0 +1,84 @@
+use glam::{Vec2, Vec3};
+use winit::dpi::PhysicalSize;
+
+use super::{Scene, WorldGeometry};
+use crate::{camera::Camera, ray::Ray};
+
+/// 拾取到的三角形
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Hit {
+    /// [`Scene::meshes`] 中的下标
+    pub mesh: usize,
+    /// 网格内的三角形下标，即 [`super::SceneMesh::indices`] 中每三个一组的序号
+    pub triangle: usize,
+    /// 沿射线的距离，以射线方向的长度为单位
+    pub t: f32,
+    /// 世界空间的命中点
+    pub position: Vec3,
+    /// 插值得到的世界空间法线，总是朝向射线的起点
+    pub normal: Vec3,
+    /// 三角形第二、三个顶点的重心坐标
+    pub barycentric: Vec2,
+}
+
+/// 在 CPU 上精确到三角形的拾取，用于没有 GPU 的场合（例如命令行工具与测试）
+///
+/// 与 [`super::raytrace::RayTracer`] 使用同一份世界空间几何与 [`Bvh`](crate::bvh::Bvh)，
+/// 只在构建时变换一次顶点，之后每次拾取只做一次 BVH 遍历。场景中的网格变化后需重新构建。
+/// 与光线追踪的主射线不同，拾取不剔除背面。
+#[derive(Debug, Clone)]
+pub struct ScenePicker {
+    geometry: WorldGeometry,
+    /// 每个网格的索引，用于插值法线
+    indices: Vec<Vec<u32>>,
+}
+
+impl ScenePicker {
+    pub fn new(scene: &Scene) -> anyhow::Result<Self> {
+        scene.validate()?;
+        Ok(Self {
+            geometry: WorldGeometry::new(scene),
+            indices: scene
+                .meshes
+                .iter()
+                .map(|mesh| mesh.indices.clone())
+                .collect(),
+        })
+    }
+
+    /// 射线最先穿过的三角形
+    pub fn pick(&self, ray: &Ray) -> Option<Hit> {
+        let hit = self.geometry.bvh.intersect(ray, false, f32::INFINITY)?;
+        let (mesh, triangle) = self.geometry.locate(hit.triangle);
+        let normals = &self.geometry.vertices[mesh].1;
+        let indices = &self.indices[mesh][triangle * 3..triangle * 3 + 3];
+        let [u, v] = hit.barycentric.to_array();
+        let weights = [1.0 - u - v, u, v];
+        let normal = (0..3)
+            .map(|i| normals[indices[i] as usize] * weights[i])
+            .sum::<Vec3>()
+            .normalize_or(-ray.direction.normalize_or(Vec3::Z));
+        Some(Hit {
+            mesh,
+            triangle,
+            t: hit.t,
+            position: ray.at(hit.t),
+            normal: if normal.dot(ray.direction) > 0.0 {
+                -normal
+            } else {
+                normal
+            },
+            barycentric: hit.barycentric,
+        })
+    }
+
+    /// 视口中 `cursor` 像素处的三角形，见 [`Camera::screen_ray`]
+    pub fn pick_screen(
+        &self,
+        camera: &Camera,
+        cursor: Vec2,
+        viewport: PhysicalSize<u32>,
+    ) -> Option<Hit> {
+        self.pick(&camera.screen_ray(cursor, viewport))
+    }
+}
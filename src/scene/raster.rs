@@ -0,0 +1,394 @@
+use bytemuck::{Pod, Zeroable};
+use wgpu::{util::DeviceExt, Device, Queue, RenderPass};
+
+use super::{Scene, SceneLight, SceneMaterial, MAX_DIRECTIONAL_LIGHTS, MAX_POINT_LIGHTS};
+use crate::{
+    camera::{CameraBundle, CameraUniform},
+    light::{DirectionalLightUniform, PointLightUniform},
+    shader::ShaderLibrary,
+    texture::{read_texture_rgba8, Texture},
+    uniform::GpuUniform,
+};
+
+/// 世界空间的顶点
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+struct SceneVertex {
+    position: [f32; 3],
+    normal: [f32; 3],
+    ao: f32,
+}
+
+unsafe impl Zeroable for SceneVertex {}
+unsafe impl Pod for SceneVertex {}
+
+impl SceneVertex {
+    const ATTRIBUTES: [wgpu::VertexAttribute; 3] =
+        wgpu::vertex_attr_array![0 => Float32x3, 1 => Float32x3, 2 => Float32];
+
+    fn buffer_layout_desc() -> wgpu::VertexBufferLayout<'static> {
+        wgpu::VertexBufferLayout {
+            array_stride: std::mem::size_of::<SceneVertex>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Vertex,
+            attributes: &Self::ATTRIBUTES,
+        }
+    }
+}
+
+/// 与 `shaders/scene.wgsl` 中的 `SceneUniform` 保持一致
+#[repr(C)]
+#[derive(Debug, Copy, Clone, GpuUniform)]
+pub struct SceneUniform {
+    view: [f32; 4],
+    ambient: [f32; 4],
+    counts: [u32; 4],
+    directional: [DirectionalLightUniform; MAX_DIRECTIONAL_LIGHTS],
+    point: [PointLightUniform; MAX_POINT_LIGHTS],
+}
+
+impl SceneUniform {
+    pub fn new(scene: &Scene) -> Self {
+        let mut uniform = Self::zeroed();
+        let camera = &scene.camera;
+        uniform.view = if camera.projection.is_orthographic() {
+            (camera.eye - camera.target).normalize().extend(0.0)
+        } else {
+            camera.eye.extend(1.0)
+        }
+        .to_array();
+        uniform.ambient = scene.ambient.extend(0.0).to_array();
+        let (mut directional, mut point) = (0, 0);
+        for light in scene.lights.iter() {
+            match light {
+                SceneLight::Directional(light) if directional < MAX_DIRECTIONAL_LIGHTS => {
+                    uniform.directional[directional] = DirectionalLightUniform::new(light);
+                    uniform.directional[directional].update_with_exposure(light, scene.exposure);
+                    directional += 1;
+                }
+                SceneLight::Point(light) if point < MAX_POINT_LIGHTS => {
+                    uniform.point[point] = PointLightUniform::new(light, scene.exposure);
+                    point += 1;
+                }
+                _ => {}
+            }
+        }
+        uniform.counts = [directional as u32, point as u32, 0, 0];
+        uniform
+    }
+}
+
+/// 与 `shaders/scene.wgsl` 中的 `SceneMaterial` 保持一致
+#[repr(C)]
+#[derive(Debug, Copy, Clone, GpuUniform)]
+pub struct SceneMaterialUniform {
+    base_color: [f32; 4],
+    params: [f32; 4],
+}
+
+impl From<&SceneMaterial> for SceneMaterialUniform {
+    fn from(material: &SceneMaterial) -> Self {
+        Self {
+            base_color: material.base_color.extend(material.specular).to_array(),
+            params: [material.shininess, 0.0, 0.0, 0.0],
+        }
+    }
+}
+
+struct GpuMesh {
+    vertex_buffer: wgpu::Buffer,
+    index_buffer: wgpu::Buffer,
+    num_elements: u32,
+    material: usize,
+}
+
+/// 用光栅化管线绘制 [`Scene`]
+///
+/// 网格在创建时变换到世界空间并上传，之后只有相机、光源与材质参数可以通过
+/// [`SceneRenderer::update`] 修改；增删网格、材质或重新烘焙 [`SceneMesh::ao`](super::SceneMesh::ao)
+/// 需要重新创建。
+/// 管线的深度格式为 [`Texture::DEPTH_FORMAT`]，剔除背面，与 [`super::raytrace::RayTracer`]
+/// 的主射线一致。
+pub struct SceneRenderer {
+    pub format: wgpu::TextureFormat,
+    camera_buffer: wgpu::Buffer,
+    camera_bind_group: wgpu::BindGroup,
+    scene_buffer: wgpu::Buffer,
+    scene_bind_group: wgpu::BindGroup,
+    material_buffers: Vec<wgpu::Buffer>,
+    material_bind_groups: Vec<wgpu::BindGroup>,
+    meshes: Vec<GpuMesh>,
+    pipeline: wgpu::RenderPipeline,
+}
+
+impl SceneRenderer {
+    pub fn new(
+        device: &Device,
+        format: wgpu::TextureFormat,
+        scene: &Scene,
+    ) -> anyhow::Result<Self> {
+        scene.validate()?;
+
+        let uniform_entry = |visibility| wgpu::BindGroupLayoutEntry {
+            binding: 0,
+            visibility,
+            ty: wgpu::BindingType::Buffer {
+                ty: wgpu::BufferBindingType::Uniform,
+                has_dynamic_offset: false,
+                min_binding_size: None,
+            },
+            count: None,
+        };
+        let camera_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &[CameraBundle::layout_entry(wgpu::ShaderStages::VERTEX)],
+            label: Some("scene_camera_bind_group_layout"),
+        });
+        let scene_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &[uniform_entry(wgpu::ShaderStages::FRAGMENT)],
+            label: Some("scene_uniform_bind_group_layout"),
+        });
+        let material_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &[uniform_entry(wgpu::ShaderStages::FRAGMENT)],
+            label: Some("scene_material_bind_group_layout"),
+        });
+
+        let uniform_bind_group = |layout, buffer: &wgpu::Buffer, label| {
+            device.create_bind_group(&wgpu::BindGroupDescriptor {
+                layout,
+                entries: &[wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: buffer.as_entire_binding(),
+                }],
+                label: Some(label),
+            })
+        };
+        let mut camera = CameraUniform::new();
+        camera.update_view_proj(&scene.camera);
+        let camera_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Scene Camera Buffer"),
+            contents: bytemuck::bytes_of(&camera),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+        let camera_bind_group =
+            uniform_bind_group(&camera_layout, &camera_buffer, "scene_camera_bind_group");
+        let scene_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Scene Uniform Buffer"),
+            contents: bytemuck::bytes_of(&SceneUniform::new(scene)),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+        let scene_bind_group =
+            uniform_bind_group(&scene_layout, &scene_buffer, "scene_uniform_bind_group");
+        let material_buffers = scene
+            .materials
+            .iter()
+            .map(|material| {
+                device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                    label: Some("Scene Material Buffer"),
+                    contents: bytemuck::bytes_of(&SceneMaterialUniform::from(material)),
+                    usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+                })
+            })
+            .collect::<Vec<_>>();
+        let material_bind_groups = material_buffers
+            .iter()
+            .map(|buffer| uniform_bind_group(&material_layout, buffer, "scene_material_bind_group"))
+            .collect();
+
+        let meshes = scene
+            .meshes
+            .iter()
+            .filter(|mesh| !mesh.indices.is_empty())
+            .map(|mesh| {
+                let (positions, normals) = mesh.world_vertices();
+                let vertices = positions
+                    .iter()
+                    .zip(&normals)
+                    .enumerate()
+                    .map(|(i, (p, n))| SceneVertex {
+                        position: p.to_array(),
+                        normal: n.to_array(),
+                        ao: mesh.vertex_ao(i),
+                    })
+                    .collect::<Vec<_>>();
+                GpuMesh {
+                    vertex_buffer: device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                        label: Some("Scene Vertex Buffer"),
+                        contents: bytemuck::cast_slice(&vertices),
+                        usage: wgpu::BufferUsages::VERTEX,
+                    }),
+                    index_buffer: device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                        label: Some("Scene Index Buffer"),
+                        contents: bytemuck::cast_slice(&mesh.indices),
+                        usage: wgpu::BufferUsages::INDEX,
+                    }),
+                    num_elements: mesh.indices.len() as u32,
+                    material: mesh.material,
+                }
+            })
+            .collect();
+
+        let shader = ShaderLibrary::new()
+            .create_shader_module(
+                device,
+                "Scene Shader",
+                include_str!("../../shaders/scene.wgsl"),
+            )
+            .expect("built-in scene shader");
+        let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Scene Pipeline Layout"),
+            bind_group_layouts: &[&camera_layout, &scene_layout, &material_layout],
+            push_constant_ranges: &[],
+        });
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Scene Pipeline"),
+            layout: Some(&layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                compilation_options: Default::default(),
+                entry_point: Some("vs_main"),
+                buffers: &[SceneVertex::buffer_layout_desc()],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                compilation_options: Default::default(),
+                entry_point: Some("fs_main"),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format,
+                    blend: None,
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            primitive: wgpu::PrimitiveState {
+                cull_mode: Some(wgpu::Face::Back),
+                ..Default::default()
+            },
+            depth_stencil: Some(wgpu::DepthStencilState {
+                format: Texture::DEPTH_FORMAT,
+                depth_write_enabled: true,
+                depth_compare: wgpu::CompareFunction::Less,
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState::default(),
+            }),
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+            cache: None,
+        });
+
+        Ok(Self {
+            format,
+            camera_buffer,
+            camera_bind_group,
+            scene_buffer,
+            scene_bind_group,
+            material_buffers,
+            material_bind_groups,
+            meshes,
+            pipeline,
+        })
+    }
+
+    /// 上传 `scene` 当前的相机、光源与材质参数
+    pub fn update(&self, queue: &Queue, scene: &Scene) {
+        let mut camera = CameraUniform::new();
+        camera.update_view_proj(&scene.camera);
+        camera.write_to(queue, &self.camera_buffer);
+        SceneUniform::new(scene).write_to(queue, &self.scene_buffer);
+        for (material, buffer) in scene.materials.iter().zip(&self.material_buffers) {
+            SceneMaterialUniform::from(material).write_to(queue, buffer);
+        }
+    }
+
+    /// 背景色不在这里绘制，由调用者清除颜色附件，见 [`Scene::background`]
+    pub fn draw(&self, pass: &mut RenderPass) {
+        pass.set_pipeline(&self.pipeline);
+        pass.set_bind_group(0, &self.camera_bind_group, &[]);
+        pass.set_bind_group(1, &self.scene_bind_group, &[]);
+        for mesh in &self.meshes {
+            pass.set_bind_group(2, &self.material_bind_groups[mesh.material], &[]);
+            pass.set_vertex_buffer(0, mesh.vertex_buffer.slice(..));
+            pass.set_index_buffer(mesh.index_buffer.slice(..), wgpu::IndexFormat::Uint32);
+            pass.draw_indexed(0..mesh.num_elements, 0, 0..1);
+        }
+    }
+}
+
+/// 离屏光栅化 `width`×`height` 像素的图像并读回 CPU，会阻塞到 GPU 完成
+///
+/// 相机的宽高比按图像大小设置，与 [`super::raytrace::RayTracer::render`] 一致，便于直接比较。
+pub fn render_image(
+    device: &Device,
+    queue: &Queue,
+    scene: &Scene,
+    width: u32,
+    height: u32,
+) -> anyhow::Result<image::RgbaImage> {
+    anyhow::ensure!(width > 0 && height > 0, "image size must be positive");
+    let mut scene = scene.clone();
+    scene
+        .camera
+        .set_viewport_size(winit::dpi::PhysicalSize::new(width, height));
+    let format = wgpu::TextureFormat::Rgba8UnormSrgb;
+    let renderer = SceneRenderer::new(device, format, &scene)?;
+
+    let extent = wgpu::Extent3d {
+        width,
+        height,
+        depth_or_array_layers: 1,
+    };
+    let color = device.create_texture(&wgpu::TextureDescriptor {
+        label: Some("Scene Image Color"),
+        size: extent,
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format,
+        usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+        view_formats: &[],
+    });
+    let depth = device.create_texture(&wgpu::TextureDescriptor {
+        label: Some("Scene Image Depth"),
+        size: extent,
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format: Texture::DEPTH_FORMAT,
+        usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+        view_formats: &[],
+    });
+    let color_view = color.create_view(&wgpu::TextureViewDescriptor::default());
+    let depth_view = depth.create_view(&wgpu::TextureViewDescriptor::default());
+
+    let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+        label: Some("Scene Image Encoder"),
+    });
+    let background = scene.background;
+    let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+        label: Some("Scene Image Pass"),
+        color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+            view: &color_view,
+            resolve_target: None,
+            ops: wgpu::Operations {
+                load: wgpu::LoadOp::Clear(wgpu::Color {
+                    r: background.x as f64,
+                    g: background.y as f64,
+                    b: background.z as f64,
+                    a: 1.0,
+                }),
+                store: wgpu::StoreOp::Store,
+            },
+        })],
+        depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+            view: &depth_view,
+            depth_ops: Some(wgpu::Operations {
+                load: wgpu::LoadOp::Clear(1.0),
+                store: wgpu::StoreOp::Discard,
+            }),
+            stencil_ops: None,
+        }),
+        ..Default::default()
+    });
+    renderer.draw(&mut pass);
+    drop(pass);
+    queue.submit(Some(encoder.finish()));
+
+    read_texture_rgba8(device, queue, &color)
+}
@@ -0,0 +1,127 @@
+use glam::Vec3;
+use winit::dpi::PhysicalSize;
+
+use super::{linear_to_srgb8, Scene, WorldGeometry};
+use crate::ray::Ray;
+
+/// 阴影射线起点沿法线的偏移，避免与自身相交
+const SHADOW_BIAS: f32 = 1e-3;
+
+/// 在 CPU 上对 [`Scene`] 做光线追踪，作为 [`super::raster::SceneRenderer`] 的参考结果
+///
+/// 着色与光栅化使用同一个 [`Scene::shade`]，区别只在可见性：每个像素按规则网格发出
+/// `samples * samples` 条主射线（抗锯齿），开启 `shadows` 时向每个光源发出阴影射线。
+/// 主射线与光栅化一样剔除背面。关闭阴影、每像素一条射线时，两者的差异只来自三角形边缘的
+/// 覆盖规则与浮点误差，可以在测试中作为基准。
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct RayTracer {
+    /// 每个像素在每个方向上的采样数
+    pub samples: u32,
+    pub shadows: bool,
+}
+
+impl Default for RayTracer {
+    fn default() -> Self {
+        Self {
+            samples: 1,
+            shadows: false,
+        }
+    }
+}
+
+impl RayTracer {
+    pub fn with_samples(mut self, samples: u32) -> Self {
+        self.samples = samples;
+        self
+    }
+
+    pub fn with_shadows(mut self, shadows: bool) -> Self {
+        self.shadows = shadows;
+        self
+    }
+
+    /// 渲染 `width`×`height` 像素的 sRGB 图像
+    ///
+    /// 相机的宽高比按图像大小设置，与 [`super::raster::render_image`] 一致。
+    pub fn render(
+        &self,
+        scene: &Scene,
+        width: u32,
+        height: u32,
+    ) -> anyhow::Result<image::RgbaImage> {
+        let pixels = self.render_linear(scene, width, height)?;
+        Ok(image::RgbaImage::from_fn(width, height, |x, y| {
+            let [r, g, b] = linear_to_srgb8(pixels[(y * width + x) as usize]);
+            image::Rgba([r, g, b, 255])
+        }))
+    }
+
+    /// 按行优先排列的线性空间颜色，行在 [`crate::jobs::global`] 上并行计算
+    pub fn render_linear(
+        &self,
+        scene: &Scene,
+        width: u32,
+        height: u32,
+    ) -> anyhow::Result<Vec<Vec3>> {
+        anyhow::ensure!(width > 0 && height > 0, "image size must be positive");
+        scene.validate()?;
+
+        let viewport = PhysicalSize::new(width, height);
+        let mut camera = scene.camera;
+        camera.set_viewport_size(viewport);
+        let geometry = WorldGeometry::new(scene);
+        let samples = self.samples.max(1);
+        let rows = (0..height).collect::<Vec<_>>();
+        let pixels = crate::jobs::global().map("ray trace", &rows, |&y| {
+            (0..width)
+                .map(|x| {
+                    let mut color = Vec3::ZERO;
+                    for sy in 0..samples {
+                        for sx in 0..samples {
+                            let offset = (glam::vec2(sx as f32, sy as f32) + 0.5) / samples as f32;
+                            let cursor = glam::vec2(x as f32, y as f32) + offset;
+                            let ray = camera.screen_ray(cursor, viewport);
+                            color += self.trace(scene, &geometry, &ray);
+                        }
+                    }
+                    color / (samples * samples) as f32
+                })
+                .collect::<Vec<_>>()
+        });
+        Ok(pixels.into_iter().flatten().collect())
+    }
+
+    fn trace(&self, scene: &Scene, geometry: &WorldGeometry, ray: &Ray) -> Vec3 {
+        let Some(hit) = geometry.bvh.intersect(ray, true, f32::INFINITY) else {
+            return scene.background;
+        };
+        let (index, triangle) = geometry.locate(hit.triangle);
+        let mesh = &scene.meshes[index];
+        let normals = &geometry.vertices[index].1;
+        let indices = &mesh.indices[triangle * 3..triangle * 3 + 3];
+        let [u, v] = hit.barycentric.to_array();
+        let weights = [1.0 - u - v, u, v];
+        let normal = (0..3)
+            .map(|i| normals[indices[i] as usize] * weights[i])
+            .sum::<Vec3>()
+            .normalize_or(Vec3::Y);
+        let ao = (0..3)
+            .map(|i| mesh.vertex_ao(indices[i] as usize) * weights[i])
+            .sum::<f32>();
+        let position = ray.at(hit.t);
+        scene.shade(
+            &scene.materials[mesh.material],
+            position,
+            normal,
+            -ray.direction,
+            ao,
+            |light_dir, distance| {
+                if !self.shadows {
+                    return true;
+                }
+                let shadow_ray = Ray::new(position + normal * SHADOW_BIAS, light_dir);
+                !geometry.bvh.occluded(&shadow_ray, distance - SHADOW_BIAS)
+            },
+        )
+    }
+}
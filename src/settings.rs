@@ -0,0 +1,213 @@
+use winit::dpi::PhysicalSize;
+
+use crate::context::PresentModeConfig;
+
+/// 阴影贴图的分辨率档位
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum ShadowQuality {
+    Off,
+    Low,
+    #[default]
+    Medium,
+    High,
+}
+
+impl ShadowQuality {
+    pub const ALL: [ShadowQuality; 4] = [
+        ShadowQuality::Off,
+        ShadowQuality::Low,
+        ShadowQuality::Medium,
+        ShadowQuality::High,
+    ];
+
+    /// 阴影贴图的边长，关闭阴影时为 `None`
+    pub fn map_size(self) -> Option<u32> {
+        match self {
+            ShadowQuality::Off => None,
+            ShadowQuality::Low => Some(1024),
+            ShadowQuality::Medium => Some(2048),
+            ShadowQuality::High => Some(4096),
+        }
+    }
+
+    /// PCF 的采样半径（纹素），半径为 r 时每个像素比较 (2⌈r⌉ + 1)² 次
+    pub fn pcf_radius(self) -> f32 {
+        match self {
+            ShadowQuality::Off => 0.0,
+            ShadowQuality::Low => 1.0,
+            ShadowQuality::Medium => 1.5,
+            ShadowQuality::High => 2.0,
+        }
+    }
+}
+
+/// 运行时可以修改的渲染设置
+///
+/// 界面只修改一份副本，再由 [`RenderSettings::apply`] 与当前生效的设置比较，
+/// 应用按返回的 [`SettingsChanges`] 只重建受影响的资源。
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct RenderSettings {
+    /// 场景渲染分辨率相对输出的比例，大于 1 时为超采样
+    pub render_scale: f32,
+    /// 场景颜色与深度附件的采样数，1 表示关闭 MSAA
+    pub msaa_samples: u32,
+    pub shadow_quality: ShadowQuality,
+    /// 纹理采样的最大各向异性，1 表示关闭
+    pub anisotropy: u16,
+    pub vsync: bool,
+}
+
+impl Default for RenderSettings {
+    fn default() -> Self {
+        Self {
+            render_scale: 1.0,
+            msaa_samples: 1,
+            shadow_quality: ShadowQuality::default(),
+            anisotropy: 1,
+            vsync: true,
+        }
+    }
+}
+
+/// 两份设置之间的差异，每一项对应一类需要重建的资源
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub struct SettingsChanges {
+    /// 渲染比例或采样数改变，需要重建场景的颜色与深度附件
+    pub render_targets: bool,
+    /// 采样数改变，需要重建绘制场景的管线
+    pub pipelines: bool,
+    /// 阴影质量改变，需要重建阴影贴图
+    pub shadows: bool,
+    /// 各向异性改变，需要重建采样器及引用它们的绑定组
+    pub samplers: bool,
+    /// 垂直同步改变，需要重新配置 surface
+    pub present_mode: bool,
+}
+
+impl SettingsChanges {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+impl RenderSettings {
+    pub const MIN_RENDER_SCALE: f32 = 0.25;
+    pub const MAX_RENDER_SCALE: f32 = 2.0;
+
+    /// 与 `previous` 相比需要重建的资源
+    pub fn changes(&self, previous: &RenderSettings) -> SettingsChanges {
+        let samples = self.msaa_samples != previous.msaa_samples;
+        SettingsChanges {
+            render_targets: samples || self.render_scale != previous.render_scale,
+            pipelines: samples,
+            shadows: self.shadow_quality != previous.shadow_quality,
+            samplers: self.anisotropy != previous.anisotropy,
+            present_mode: self.vsync != previous.vsync,
+        }
+    }
+
+    /// 把各项限制到设备支持的范围：采样数取 `format` 支持的不超过设定值的最大值，
+    /// 各向异性取 1 到 16 之间不超过设定值的 2 的幂
+    pub fn sanitize(&mut self, adapter: &wgpu::Adapter, format: wgpu::TextureFormat) {
+        self.render_scale = self
+            .render_scale
+            .clamp(Self::MIN_RENDER_SCALE, Self::MAX_RENDER_SCALE);
+        let flags = adapter.get_texture_format_features(format).flags;
+        self.msaa_samples = Self::supported_samples(&flags)
+            .into_iter()
+            .rev()
+            .find(|&n| n <= self.msaa_samples)
+            .unwrap_or(1);
+        let anisotropy = self.anisotropy.clamp(1, 16);
+        self.anisotropy = 1 << (15 - anisotropy.leading_zeros());
+    }
+
+    /// `flags` 支持的采样数，由小到大排列，总是包含 1
+    pub fn supported_samples(flags: &wgpu::TextureFormatFeatureFlags) -> Vec<u32> {
+        [1, 2, 4, 8, 16]
+            .into_iter()
+            .filter(|&n| n == 1 || flags.sample_count_supported(n))
+            .collect()
+    }
+
+    /// 把 `edited` 限制到设备支持的范围后作为新的设置，返回需要重建的资源；
+    /// 界面每帧调用即可，没有变化时返回空的 [`SettingsChanges`]
+    pub fn apply(
+        &mut self,
+        edited: &mut RenderSettings,
+        adapter: &wgpu::Adapter,
+        format: wgpu::TextureFormat,
+    ) -> SettingsChanges {
+        edited.sanitize(adapter, format);
+        let changes = edited.changes(self);
+        *self = *edited;
+        changes
+    }
+
+    /// 场景附件的像素大小
+    pub fn scaled_size(&self, output: PhysicalSize<u32>) -> PhysicalSize<u32> {
+        let scale = |v: u32| ((v as f32 * self.render_scale).round() as u32).max(1);
+        PhysicalSize::new(scale(output.width), scale(output.height))
+    }
+
+    /// 垂直同步对应的呈现模式，关闭时优先不撕裂的 Mailbox
+    pub fn present_mode(&self) -> PresentModeConfig {
+        if self.vsync {
+            PresentModeConfig::VSync
+        } else {
+            PresentModeConfig::LowLatency
+        }
+    }
+
+    /// 重复寻址、三线性过滤并带各向异性的采样器
+    pub fn sampler_descriptor<'a>(&self, label: Option<&'a str>) -> wgpu::SamplerDescriptor<'a> {
+        wgpu::SamplerDescriptor {
+            label,
+            address_mode_u: wgpu::AddressMode::Repeat,
+            address_mode_v: wgpu::AddressMode::Repeat,
+            address_mode_w: wgpu::AddressMode::Repeat,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            mipmap_filter: wgpu::FilterMode::Linear,
+            anisotropy_clamp: self.anisotropy,
+            ..Default::default()
+        }
+    }
+
+    /// 编辑设置的 egui 面板，返回是否有修改；`samples` 为可选的采样数，
+    /// 通常取自 [`RenderSettings::supported_samples`]
+    #[cfg(feature = "egui")]
+    pub fn ui(&mut self, ui: &mut egui::Ui, samples: &[u32]) -> bool {
+        let before = *self;
+        ui.add(
+            egui::Slider::new(
+                &mut self.render_scale,
+                Self::MIN_RENDER_SCALE..=Self::MAX_RENDER_SCALE,
+            )
+            .text("render scale"),
+        );
+        egui::ComboBox::from_label("MSAA")
+            .selected_text(format!("{}x", self.msaa_samples))
+            .show_ui(ui, |ui| {
+                for &n in samples {
+                    ui.selectable_value(&mut self.msaa_samples, n, format!("{n}x"));
+                }
+            });
+        egui::ComboBox::from_label("shadows")
+            .selected_text(format!("{:?}", self.shadow_quality))
+            .show_ui(ui, |ui| {
+                for quality in ShadowQuality::ALL {
+                    ui.selectable_value(&mut self.shadow_quality, quality, format!("{quality:?}"));
+                }
+            });
+        egui::ComboBox::from_label("anisotropy")
+            .selected_text(format!("{}x", self.anisotropy))
+            .show_ui(ui, |ui| {
+                for n in [1, 2, 4, 8, 16] {
+                    ui.selectable_value(&mut self.anisotropy, n, format!("{n}x"));
+                }
+            });
+        ui.checkbox(&mut self.vsync, "vsync");
+        *self != before
+    }
+}
@@ -0,0 +1,171 @@
+use std::{borrow::Cow, collections::HashMap};
+
+use anyhow::{anyhow, bail};
+
+/// crate 自带的 WGSL 片段，使用 `#include "wgpu_dance/<name>.wgsl"` 引入
+const BUILTIN_CHUNKS: &[(&str, &str)] = &[
+    (
+        "wgpu_dance/alpha_test.wgsl",
+        include_str!("../shaders/alpha_test.wgsl"),
+    ),
+    (
+        "wgpu_dance/baked_animation.wgsl",
+        include_str!("../shaders/baked_animation.wgsl"),
+    ),
+    (
+        "wgpu_dance/camera.wgsl",
+        include_str!("../shaders/camera.wgsl"),
+    ),
+    (
+        "wgpu_dance/environment.wgsl",
+        include_str!("../shaders/environment.wgsl"),
+    ),
+    (
+        "wgpu_dance/fullscreen.wgsl",
+        include_str!("../shaders/fullscreen.wgsl"),
+    ),
+    (
+        "wgpu_dance/lighting.wgsl",
+        include_str!("../shaders/lighting.wgsl"),
+    ),
+    (
+        "wgpu_dance/motion.wgsl",
+        include_str!("../shaders/motion.wgsl"),
+    ),
+    (
+        "wgpu_dance/shadows.wgsl",
+        include_str!("../shaders/shadows.wgsl"),
+    ),
+    ("wgpu_dance/sky.wgsl", include_str!("../shaders/sky.wgsl")),
+    (
+        "wgpu_dance/skinning.wgsl",
+        include_str!("../shaders/skinning.wgsl"),
+    ),
+    (
+        "wgpu_dance/tonemapping.wgsl",
+        include_str!("../shaders/tonemapping.wgsl"),
+    ),
+    (
+        "wgpu_dance/texture_transform.wgsl",
+        include_str!("../shaders/texture_transform.wgsl"),
+    ),
+    (
+        "wgpu_dance/two_sided.wgsl",
+        include_str!("../shaders/two_sided.wgsl"),
+    ),
+    (
+        "wgpu_dance/water.wgsl",
+        include_str!("../shaders/water.wgsl"),
+    ),
+];
+
+/// WGSL 片段库与 `#include` 预处理器
+///
+/// 每个片段在同一个着色器中只会被展开一次，重复的 `#include` 会被忽略。
+#[derive(Debug, Clone)]
+pub struct ShaderLibrary {
+    chunks: HashMap<String, Cow<'static, str>>,
+}
+
+impl ShaderLibrary {
+    pub fn new() -> Self {
+        let chunks = BUILTIN_CHUNKS
+            .iter()
+            .map(|(name, source)| (name.to_string(), Cow::Borrowed(*source)))
+            .collect();
+        Self { chunks }
+    }
+
+    pub fn add_chunk(&mut self, name: &str, source: impl Into<Cow<'static, str>>) {
+        self.chunks.insert(name.to_string(), source.into());
+    }
+
+    pub fn chunk(&self, name: &str) -> Option<&str> {
+        self.chunks.get(name).map(|c| c.as_ref())
+    }
+
+    /// 展开 `source` 中所有的 `#include "..."` 指令
+    pub fn preprocess(&self, source: &str) -> anyhow::Result<String> {
+        let mut output = String::with_capacity(source.len());
+        let mut included = Vec::new();
+        let mut stack = Vec::new();
+        self.expand(source, &mut output, &mut included, &mut stack)?;
+        Ok(output)
+    }
+
+    fn expand<'a>(
+        &'a self,
+        source: &str,
+        output: &mut String,
+        included: &mut Vec<&'a str>,
+        stack: &mut Vec<&'a str>,
+    ) -> anyhow::Result<()> {
+        for line in source.lines() {
+            let Some(rest) = line.trim_start().strip_prefix("#include") else {
+                output.push_str(line);
+                output.push('\n');
+                continue;
+            };
+
+            let name = rest
+                .trim()
+                .strip_prefix('"')
+                .and_then(|r| r.strip_suffix('"'))
+                .ok_or_else(|| anyhow!("malformed include directive: {}", line.trim()))?;
+            let (name, chunk) = self
+                .chunks
+                .get_key_value(name)
+                .ok_or_else(|| anyhow!("unknown shader chunk: {name}"))?;
+
+            if stack.contains(&name.as_str()) {
+                bail!("recursive include of shader chunk: {name}");
+            }
+            if included.contains(&name.as_str()) {
+                continue;
+            }
+            included.push(name);
+
+            stack.push(name);
+            self.expand(chunk, output, included, stack)?;
+            stack.pop();
+        }
+
+        Ok(())
+    }
+
+    pub fn create_shader_module(
+        &self,
+        device: &wgpu::Device,
+        label: &str,
+        source: &str,
+    ) -> anyhow::Result<wgpu::ShaderModule> {
+        let source = self.preprocess(source)?;
+        Ok(device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some(label),
+            source: wgpu::ShaderSource::Wgsl(source.into()),
+        }))
+    }
+}
+
+/// `wgpu_dance/alpha_test.wgsl` 的管线常量，填入 `PipelineCompilationOptions::constants`
+///
+/// 镂空材质按阈值创建各自的管线变体，`cutoff` 为 0 时等同于不透明管线。
+///
+/// # NOTE:
+/// wgpu 的 GL 后端按着色器模块与入口缓存链接好的程序，不区分管线常量，
+/// 同一个模块创建的变体会共用第一条管线的阈值；每个变体应使用单独创建的着色器模块。
+pub fn alpha_test_constants(cutoff: f32) -> HashMap<String, f64> {
+    HashMap::from([("alpha_cutoff".to_string(), cutoff as f64)])
+}
+
+/// `wgpu_dance/shadows.wgsl` 的管线常量，`radius` 为 `shadow_pcf` 的采样半径（纹素），
+/// 通常取 [`ShadowQuality::pcf_radius`](crate::settings::ShadowQuality::pcf_radius)
+pub fn shadow_pcf_constants(radius: f32) -> HashMap<String, f64> {
+    HashMap::from([("shadow_filter_radius".to_string(), radius as f64)])
+}
+
+impl Default for ShaderLibrary {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,305 @@
+use std::{marker::PhantomData, sync::Arc, time::SystemTime};
+
+use wgpu::util::DeviceExt;
+use winit::{
+    dpi::{PhysicalPosition, PhysicalSize},
+    event::{ElementState, KeyEvent, MouseButton},
+    keyboard::{KeyCode, PhysicalKey},
+    window::Window,
+};
+
+use crate::{
+    app::{FrameTime, KeyInput, WindowApp},
+    context::{GpuContext, GpuContextOptions},
+    shader::ShaderLibrary,
+    uniform::GpuUniform,
+    validation::parse_preprocessed,
+};
+
+/// 两次检查着色器文件修改时间的最小间隔，单位为秒
+const RELOAD_INTERVAL: f32 = 0.25;
+
+/// [`ShaderToyApp`] 绘制的片段着色器
+pub trait ShaderToy {
+    /// 热重载时监视的 WGSL 文件，通常以 `concat!(env!("CARGO_MANIFEST_DIR"), ...)` 给出
+    const PATH: &'static str;
+    /// 编译进程序的同一份源码，文件无法读取（例如 wasm）或启动时有错误时使用
+    const SOURCE: &'static str;
+}
+
+/// 与 `shader_toy.wgsl` 中的 `ShaderToyUniform` 保持一致
+#[repr(C)]
+#[derive(Debug, Copy, Clone, Default, GpuUniform)]
+pub struct ShaderToyUniform {
+    /// xy: 画面像素大小, zw: 其倒数
+    resolution: [f32; 4],
+    /// xy: 光标位置, zw: 最近一次左键按下的位置，未按住时为负
+    mouse: [f32; 4],
+    /// x: 秒, y: 本帧时长, z: 帧序号
+    time: [f32; 4],
+}
+
+/// 在 `main_image` 的源码之后追加全屏三角形、uniform 与入口函数，得到完整的着色器
+///
+/// 追加在后面而不是前面，编译错误中的行号与用户的文件一致。
+pub fn shader_toy_source(main_image: &str) -> String {
+    format!(
+        "{main_image}\n{}",
+        include_str!("../shaders/shader_toy.wgsl")
+    )
+}
+
+/// 只有一个全屏片段着色器的应用，类似 Shadertoy，写 WGSL 实验不需要任何样板代码
+///
+/// 着色器只需定义 `fn main_image(frag_coord: vec2f) -> vec4f`，`frag_coord` 为像素中心坐标，
+/// 左上角为原点；可以读取 uniform `toy` 中的分辨率、光标与时间。输出的颜色直接写入
+/// [`crate::context::Frame::gamma_view`]，不再经过 sRGB 编码。
+///
+/// [`ShaderToy::PATH`] 被修改后自动重新编译，编译失败时打印错误并保留上一个管线。
+/// 空格暂停时间，R 让时间从零开始。
+pub struct ShaderToyApp<T: ShaderToy> {
+    gpu: GpuContext,
+    pipeline_layout: wgpu::PipelineLayout,
+    pipeline: wgpu::RenderPipeline,
+    uniform_buffer: wgpu::Buffer,
+    bind_group: wgpu::BindGroup,
+    /// 着色器中的时间，暂停时不再增加
+    time: f32,
+    paused: bool,
+    cursor: PhysicalPosition<f64>,
+    click: Option<PhysicalPosition<f64>>,
+    modified: Option<SystemTime>,
+    since_check: f32,
+    toy: PhantomData<T>,
+}
+
+impl<T: ShaderToy> ShaderToyApp<T> {
+    fn compile(&self, source: &str) -> anyhow::Result<wgpu::RenderPipeline> {
+        create_pipeline(
+            &self.gpu.device,
+            &self.pipeline_layout,
+            self.gpu.gamma_format(),
+            source,
+        )
+    }
+
+    /// 文件的修改时间变化后重新编译
+    fn reload_if_modified(&mut self) {
+        let Ok(modified) = std::fs::metadata(T::PATH).and_then(|m| m.modified()) else {
+            return;
+        };
+        if self.modified.replace(modified) == Some(modified) {
+            return;
+        }
+        let result = std::fs::read_to_string(T::PATH)
+            .map_err(anyhow::Error::from)
+            .and_then(|source| self.compile(&source));
+        match result {
+            Ok(pipeline) => {
+                self.pipeline = pipeline;
+                println!("reloaded {}", T::PATH);
+            }
+            Err(e) => eprintln!("{}: {e:#}", T::PATH),
+        }
+    }
+}
+
+fn create_pipeline(
+    device: &wgpu::Device,
+    layout: &wgpu::PipelineLayout,
+    format: wgpu::TextureFormat,
+    source: &str,
+) -> anyhow::Result<wgpu::RenderPipeline> {
+    // 先用 naga 校验，错误的着色器不会走到 wgpu 的校验错误处理而导致退出
+    let source = ShaderLibrary::new().preprocess(&shader_toy_source(source))?;
+    parse_preprocessed(&source)?;
+    let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+        label: Some("Shader Toy"),
+        source: wgpu::ShaderSource::Wgsl(source.into()),
+    });
+
+    Ok(
+        device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Shader Toy Pipeline"),
+            layout: Some(layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                compilation_options: Default::default(),
+                entry_point: Some("vs_main"),
+                buffers: &[],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                compilation_options: Default::default(),
+                entry_point: Some("fs_main"),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format,
+                    blend: None,
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+            cache: None,
+        }),
+    )
+}
+
+impl<T: ShaderToy> WindowApp for ShaderToyApp<T> {
+    async fn new(window: Arc<Window>) -> Self {
+        let gpu = GpuContext::new(window, GpuContextOptions::default())
+            .await
+            .unwrap();
+
+        let uniform_buffer = gpu
+            .device
+            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("Shader Toy Uniform Buffer"),
+                contents: bytemuck::bytes_of(&ShaderToyUniform::default()),
+                usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            });
+        let bind_group_layout =
+            gpu.device
+                .create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                    label: Some("Shader Toy Bind Group Layout"),
+                    entries: &[wgpu::BindGroupLayoutEntry {
+                        binding: 0,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Buffer {
+                            ty: wgpu::BufferBindingType::Uniform,
+                            has_dynamic_offset: false,
+                            min_binding_size: None,
+                        },
+                        count: None,
+                    }],
+                });
+        let bind_group = gpu.device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Shader Toy Bind Group"),
+            layout: &bind_group_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: uniform_buffer.as_entire_binding(),
+            }],
+        });
+        let pipeline_layout = gpu
+            .device
+            .create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some("Shader Toy Pipeline Layout"),
+                bind_group_layouts: &[&bind_group_layout],
+                push_constant_ranges: &[],
+            });
+
+        // 优先使用磁盘上的最新版本，有错误时退回编译进程序的源码
+        let format = gpu.gamma_format();
+        let pipeline = std::fs::read_to_string(T::PATH)
+            .map_err(anyhow::Error::from)
+            .and_then(|source| create_pipeline(&gpu.device, &pipeline_layout, format, &source))
+            .or_else(|e| {
+                eprintln!("{}: {e:#}", T::PATH);
+                create_pipeline(&gpu.device, &pipeline_layout, format, T::SOURCE)
+            })
+            .unwrap();
+
+        Self {
+            gpu,
+            pipeline_layout,
+            pipeline,
+            uniform_buffer,
+            bind_group,
+            time: 0.0,
+            paused: false,
+            cursor: PhysicalPosition::default(),
+            click: None,
+            modified: std::fs::metadata(T::PATH).and_then(|m| m.modified()).ok(),
+            since_check: 0.0,
+            toy: PhantomData,
+        }
+    }
+
+    fn render(&mut self) -> Result<(), wgpu::SurfaceError> {
+        self.resize_surface_if_needed();
+
+        let mut frame = self.gpu.begin_frame()?;
+
+        let mut render_pass = frame.render_pass("Shader Toy Pass").gamma().begin();
+        render_pass.set_pipeline(&self.pipeline);
+        render_pass.set_bind_group(0, &self.bind_group, &[]);
+        render_pass.draw(0..3, 0..1);
+        drop(render_pass);
+
+        self.gpu.end_frame(frame);
+
+        Ok(())
+    }
+
+    fn set_window_resized(&mut self, new_size: PhysicalSize<u32>) {
+        self.gpu.resize(new_size);
+    }
+
+    fn resize_surface_if_needed(&mut self) {
+        self.gpu.resize_if_needed();
+    }
+
+    fn gpu_context(&mut self) -> Option<&mut GpuContext> {
+        Some(&mut self.gpu)
+    }
+
+    fn keyboard_input(&mut self, _event: &KeyEvent) -> bool {
+        false
+    }
+
+    fn key_input(&mut self, input: &KeyInput) -> bool {
+        if input.state != ElementState::Pressed || input.repeat {
+            return false;
+        }
+        match input.physical_key {
+            PhysicalKey::Code(KeyCode::Space) => self.paused = !self.paused,
+            PhysicalKey::Code(KeyCode::KeyR) => self.time = 0.0,
+            _ => return false,
+        }
+        true
+    }
+
+    fn mouse_click(&mut self, state: ElementState, button: MouseButton) -> bool {
+        if button != MouseButton::Left {
+            return false;
+        }
+        self.click = state.is_pressed().then_some(self.cursor);
+        true
+    }
+
+    fn cursor_move(&mut self, position: PhysicalPosition<f64>) -> bool {
+        self.cursor = position;
+        true
+    }
+
+    fn update(&mut self, time: FrameTime) {
+        self.since_check += time.delta_secs();
+        if self.since_check >= RELOAD_INTERVAL {
+            self.since_check = 0.0;
+            self.reload_if_modified();
+        }
+
+        let delta = if self.paused { 0.0 } else { time.delta_secs() };
+        self.time += delta;
+
+        let size = self.gpu.size();
+        let (width, height) = (size.width.max(1) as f32, size.height.max(1) as f32);
+        let click = self
+            .click
+            .map_or([-1.0, -1.0], |c| [c.x as f32, c.y as f32]);
+        let uniform = ShaderToyUniform {
+            resolution: [width, height, 1.0 / width, 1.0 / height],
+            mouse: [
+                self.cursor.x as f32,
+                self.cursor.y as f32,
+                click[0],
+                click[1],
+            ],
+            time: [self.time, delta, time.frame as f32, 0.0],
+        };
+        uniform.write_to(&self.gpu.queue, &self.uniform_buffer);
+    }
+}
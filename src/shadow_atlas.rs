@@ -0,0 +1,314 @@
+use anyhow::ensure;
+use wgpu::{BindGroup, BindGroupLayout, Device};
+
+use crate::texture::Texture;
+
+/// 一个需要阴影的光源，聚光灯占 1 个分块，点光源的立方体阴影占 6 个分块
+#[derive(Debug, Copy, Clone)]
+pub struct ShadowRequest {
+    /// 调用者自定义的光源编号，用于查询分配结果
+    pub light: u32,
+    /// 0 到 1 之间，例如光源在屏幕上的覆盖比例；决定期望的分辨率与空间不足时的取舍顺序
+    pub priority: f32,
+    pub faces: u32,
+}
+
+impl ShadowRequest {
+    pub fn spot(light: u32, priority: f32) -> Self {
+        Self {
+            light,
+            priority,
+            faces: 1,
+        }
+    }
+
+    pub fn point(light: u32, priority: f32) -> Self {
+        Self {
+            light,
+            priority,
+            faces: 6,
+        }
+    }
+}
+
+/// 图集中的一个方形分块
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct ShadowTile {
+    /// 以纹素为单位的左上角
+    pub x: u32,
+    pub y: u32,
+    pub size: u32,
+    /// xy: 分块在图集中的 uv 偏移，zw: uv 缩放，与 `shadow_atlas_uv` 一致
+    pub uv_rect: glam::Vec4,
+}
+
+impl ShadowTile {
+    /// 渲染该分块时使用的视口
+    pub fn set_viewport(&self, render_pass: &mut wgpu::RenderPass) {
+        render_pass.set_viewport(
+            self.x as f32,
+            self.y as f32,
+            self.size as f32,
+            self.size as f32,
+            0.0,
+            1.0,
+        );
+        render_pass.set_scissor_rect(self.x, self.y, self.size, self.size);
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ShadowAllocation {
+    pub light: u32,
+    pub tiles: Vec<ShadowTile>,
+}
+
+/// 把多个聚光灯与点光源的阴影贴图打包进同一张深度纹理
+///
+/// 每次 [`ShadowAtlas::allocate`] 按优先级为光源选择 2 的幂大小的分块：期望大小为
+/// `max_tile_size * priority`，总面积超出图集时反复把最大分块中优先级最低的一个减半，
+/// 都降到 `min_tile_size` 仍放不下时丢弃优先级最低的光源。分块由大到小沿 Z 序曲线摆放，
+/// 因此不会重叠也不会产生空隙。无论光源多少，着色器只需要绑定一张纹理。
+pub struct ShadowAtlas {
+    pub texture: Texture,
+    pub bind_group_layout: BindGroupLayout,
+    pub bind_group: BindGroup,
+    size: u32,
+    min_tile_size: u32,
+    max_tile_size: u32,
+    allocations: Vec<ShadowAllocation>,
+}
+
+impl ShadowAtlas {
+    /// `size`、`min_tile_size` 与 `max_tile_size` 都必须是 2 的幂
+    pub fn new(
+        device: &Device,
+        size: u32,
+        min_tile_size: u32,
+        max_tile_size: u32,
+    ) -> anyhow::Result<Self> {
+        ensure!(
+            [size, min_tile_size, max_tile_size]
+                .iter()
+                .all(|s| s.is_power_of_two()),
+            "shadow atlas sizes must be powers of two"
+        );
+        ensure!(
+            min_tile_size <= max_tile_size && max_tile_size <= size,
+            "shadow atlas tile sizes must satisfy min <= max <= atlas size"
+        );
+        let max_dimension = device.limits().max_texture_dimension_2d;
+        ensure!(
+            size <= max_dimension,
+            "shadow atlas size {size} exceeds max texture dimension {max_dimension}"
+        );
+
+        let texture = Self::create_texture(device, size);
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("shadow_atlas_bind_group_layout"),
+            entries: &Self::layout_entries(),
+        });
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("shadow_atlas_bind_group"),
+            layout: &bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(&texture.view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(&texture.sampler),
+                },
+            ],
+        });
+
+        Ok(Self {
+            texture,
+            bind_group_layout,
+            bind_group,
+            size,
+            min_tile_size,
+            max_tile_size,
+            allocations: Vec::new(),
+        })
+    }
+
+    fn create_texture(device: &Device, size: u32) -> Texture {
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("shadow_atlas"),
+            size: wgpu::Extent3d {
+                width: size,
+                height: size,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: Texture::DEPTH_FORMAT,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+            view_formats: &[],
+        });
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("shadow_atlas_sampler"),
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            compare: Some(wgpu::CompareFunction::LessEqual),
+            ..Default::default()
+        });
+        Texture {
+            texture,
+            view,
+            sampler,
+        }
+    }
+
+    /// 着色器中的 `texture_depth_2d` 与 `sampler_comparison`
+    pub fn layout_entries() -> [wgpu::BindGroupLayoutEntry; 2] {
+        [
+            wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Texture {
+                    sample_type: wgpu::TextureSampleType::Depth,
+                    view_dimension: wgpu::TextureViewDimension::D2,
+                    multisampled: false,
+                },
+                count: None,
+            },
+            wgpu::BindGroupLayoutEntry {
+                binding: 1,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Comparison),
+                count: None,
+            },
+        ]
+    }
+
+    pub fn size(&self) -> u32 {
+        self.size
+    }
+
+    /// 重新为所有光源分配分块，之前的分配全部失效；返回的分配按优先级由高到低排列，
+    /// 没有出现在其中的光源本帧没有阴影
+    pub fn allocate(&mut self, requests: &[ShadowRequest]) -> &[ShadowAllocation] {
+        let mut requests: Vec<_> = requests.iter().filter(|r| r.faces > 0).copied().collect();
+        requests.sort_by(|a, b| b.priority.total_cmp(&a.priority));
+
+        let mut sizes: Vec<u32> = requests
+            .iter()
+            .map(|r| {
+                let desired = (self.max_tile_size as f32 * r.priority.clamp(0.0, 1.0)) as u32;
+                prev_power_of_two(desired).clamp(self.min_tile_size, self.max_tile_size)
+            })
+            .collect();
+        let area = |requests: &[ShadowRequest], sizes: &[u32]| -> u64 {
+            requests
+                .iter()
+                .zip(sizes)
+                .map(|(r, &s)| r.faces as u64 * s as u64 * s as u64)
+                .sum()
+        };
+        let capacity = self.size as u64 * self.size as u64;
+        while area(&requests, &sizes) > capacity {
+            // 先缩小当前最大的分块中优先级最低的一个，保证优先级高的光源分辨率不低于优先级低的
+            let largest = sizes.iter().copied().max().unwrap_or(0);
+            match sizes
+                .iter()
+                .rposition(|&s| s == largest && s > self.min_tile_size)
+            {
+                Some(i) => sizes[i] /= 2,
+                None => {
+                    requests.pop();
+                    sizes.pop();
+                }
+            }
+        }
+
+        // 大的分块先摆放，按 Z 序依次占用，对齐到自身大小的 2 的幂方块不会互相重叠
+        let mut order: Vec<usize> = (0..requests.len()).collect();
+        order.sort_by_key(|&i| std::cmp::Reverse(sizes[i]));
+        let mut tiles = vec![Vec::new(); requests.len()];
+        let unit = self.min_tile_size as u64;
+        let mut cursor = 0u64;
+        for i in order {
+            let size = sizes[i];
+            for _ in 0..requests[i].faces {
+                let (x, y) = morton_decode(cursor);
+                let (x, y) = (x * self.min_tile_size, y * self.min_tile_size);
+                let inv = 1.0 / self.size as f32;
+                tiles[i].push(ShadowTile {
+                    x,
+                    y,
+                    size,
+                    uv_rect: glam::vec4(
+                        x as f32 * inv,
+                        y as f32 * inv,
+                        size as f32 * inv,
+                        size as f32 * inv,
+                    ),
+                });
+                cursor += (size as u64 / unit).pow(2);
+            }
+        }
+
+        self.allocations = requests
+            .iter()
+            .zip(tiles)
+            .map(|(r, tiles)| ShadowAllocation {
+                light: r.light,
+                tiles,
+            })
+            .collect();
+        &self.allocations
+    }
+
+    pub fn allocations(&self) -> &[ShadowAllocation] {
+        &self.allocations
+    }
+
+    /// 光源在最近一次分配中得到的分块，没有分配到时返回 `None`
+    pub fn allocation(&self, light: u32) -> Option<&ShadowAllocation> {
+        self.allocations.iter().find(|a| a.light == light)
+    }
+
+    /// 清空整张图集的深度，之后各分块用 [`ShadowTile::set_viewport`] 渲染
+    pub fn begin_pass<'a>(&'a self, encoder: &'a mut wgpu::CommandEncoder) -> wgpu::RenderPass<'a> {
+        encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Shadow Atlas Pass"),
+            color_attachments: &[],
+            depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                view: &self.texture.view,
+                depth_ops: Some(wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(1.0),
+                    store: wgpu::StoreOp::Store,
+                }),
+                stencil_ops: None,
+            }),
+            ..Default::default()
+        })
+    }
+}
+
+fn prev_power_of_two(value: u32) -> u32 {
+    if value == 0 {
+        0
+    } else {
+        1 << (31 - value.leading_zeros())
+    }
+}
+
+/// Z 序编号拆分为以最小分块为单位的二维坐标
+fn morton_decode(code: u64) -> (u32, u32) {
+    let compact = |mut v: u64| {
+        v &= 0x5555_5555_5555_5555;
+        v = (v | (v >> 1)) & 0x3333_3333_3333_3333;
+        v = (v | (v >> 2)) & 0x0f0f_0f0f_0f0f_0f0f;
+        v = (v | (v >> 4)) & 0x00ff_00ff_00ff_00ff;
+        v = (v | (v >> 8)) & 0x0000_ffff_0000_ffff;
+        v = (v | (v >> 16)) & 0x0000_0000_ffff_ffff;
+        v as u32
+    };
+    (compact(code), compact(code >> 1))
+}
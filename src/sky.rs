@@ -0,0 +1,216 @@
+use std::f32::consts::TAU;
+
+use bytemuck::{Pod, Zeroable};
+use wgpu::{util::DeviceExt, BindGroup, Buffer, Device, Queue, RenderPipeline};
+
+use crate::{
+    background::{Background, BackgroundDraw},
+    camera::Camera,
+    light::DirectionalLight,
+    shader::ShaderLibrary,
+};
+
+/// 按一天中的时刻计算太阳方向
+#[derive(Debug, Copy, Clone)]
+pub struct Sun {
+    /// 一天中的时刻，单位小时，范围 [0, 24)
+    pub time_of_day: f32,
+    /// 一年中的第几天，用于计算太阳赤纬
+    pub day_of_year: f32,
+    /// 观察者所在纬度，单位度
+    pub latitude: f32,
+    /// 大气浑浊度，2 为晴朗天空，10 左右为雾霾
+    pub turbidity: f32,
+}
+
+impl Default for Sun {
+    fn default() -> Self {
+        Self {
+            time_of_day: 10.0,
+            day_of_year: 172.0,
+            latitude: 35.0,
+            turbidity: 2.5,
+        }
+    }
+}
+
+impl Sun {
+    pub fn advance(&mut self, hours: f32) {
+        self.time_of_day = (self.time_of_day + hours).rem_euclid(24.0);
+    }
+
+    /// 指向太阳的单位向量，+Y 朝上，-Z 朝北
+    pub fn direction(&self) -> glam::Vec3 {
+        let declination = -23.44_f32.to_radians() * (TAU / 365.0 * (self.day_of_year + 10.0)).cos();
+        let latitude = self.latitude.to_radians();
+        let hour_angle = (self.time_of_day - 12.0) / 24.0 * TAU;
+
+        let up = latitude.sin() * declination.sin()
+            + latitude.cos() * declination.cos() * hour_angle.cos();
+        let east = -declination.cos() * hour_angle.sin();
+        let north = latitude.cos() * declination.sin()
+            - latitude.sin() * declination.cos() * hour_angle.cos();
+
+        glam::vec3(east, up, -north).normalize()
+    }
+
+    /// 太阳高度角，单位弧度，负值表示位于地平线以下
+    pub fn elevation(&self) -> f32 {
+        self.direction().y.clamp(-1.0, 1.0).asin()
+    }
+
+    /// 用太阳方向与颜色驱动平行光；阴影投射方向同样取自该光源
+    pub fn apply_to(&self, light: &mut DirectionalLight) {
+        let direction = self.direction();
+        let height = direction.y;
+
+        // 接近地平线时光线穿过更厚的大气，偏暖且更暗
+        let warm = glam::vec3(1.0, 0.45, 0.2);
+        let t = (height / 0.35).clamp(0.0, 1.0);
+        light.direction = direction;
+        light.color = warm.lerp(glam::Vec3::ONE, t);
+        light.intensity = ((height + 0.05) / 0.15).clamp(0.0, 1.0);
+    }
+}
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+struct SkyUniform {
+    inv_view_proj: [[f32; 4]; 4],
+    sun_direction: [f32; 4],
+    params: [f32; 4],
+}
+
+unsafe impl Zeroable for SkyUniform {}
+unsafe impl Pod for SkyUniform {}
+
+/// 全屏绘制的程序化天空，应在场景几何之前绘制
+pub struct Sky {
+    pub exposure: f32,
+    pub sun_disk_intensity: f32,
+    uniform: SkyUniform,
+    buffer: Buffer,
+    bind_group: BindGroup,
+    pipeline: RenderPipeline,
+}
+
+impl Sky {
+    pub fn new(
+        device: &Device,
+        color_format: wgpu::TextureFormat,
+        depth_format: Option<wgpu::TextureFormat>,
+    ) -> Self {
+        let uniform = SkyUniform {
+            inv_view_proj: glam::Mat4::IDENTITY.to_cols_array_2d(),
+            sun_direction: [0.0, 1.0, 0.0, 2.5],
+            params: [0.05, 20.0, 0.0, 0.0],
+        };
+        let buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Sky Buffer"),
+            contents: bytemuck::cast_slice(&[uniform]),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            }],
+            label: Some("sky_bind_group_layout"),
+        });
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: &bind_group_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: buffer.as_entire_binding(),
+            }],
+            label: Some("sky_bind_group"),
+        });
+
+        let shader = ShaderLibrary::new()
+            .create_shader_module(
+                device,
+                "Sky Shader",
+                include_str!("../shaders/sky_pass.wgsl"),
+            )
+            .expect("built-in sky shader");
+        let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Sky Pipeline Layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Sky Pipeline"),
+            layout: Some(&layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                compilation_options: Default::default(),
+                entry_point: Some("vs_main"),
+                buffers: &[],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                compilation_options: Default::default(),
+                entry_point: Some("fs_main"),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: color_format,
+                    blend: Some(wgpu::BlendState::REPLACE),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            primitive: wgpu::PrimitiveState::default(),
+            // 天空不写深度，后续绘制的几何体会覆盖它
+            depth_stencil: depth_format.map(|format| wgpu::DepthStencilState {
+                format,
+                depth_write_enabled: false,
+                depth_compare: wgpu::CompareFunction::Always,
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState::default(),
+            }),
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+            cache: None,
+        });
+
+        Self {
+            exposure: uniform.params[0],
+            sun_disk_intensity: uniform.params[1],
+            uniform,
+            buffer,
+            bind_group,
+            pipeline,
+        }
+    }
+
+    pub fn update(&mut self, queue: &Queue, camera: &Camera, sun: &Sun) {
+        self.update_with_view_proj(queue, camera.build_view_projection_matrix(), sun);
+    }
+
+    /// 直接指定 view-projection 矩阵，用于渲染环境探针的各个立方体面
+    pub fn update_with_view_proj(&mut self, queue: &Queue, view_proj: glam::Mat4, sun: &Sun) {
+        self.uniform.inv_view_proj = view_proj.inverse().to_cols_array_2d();
+        self.uniform.sun_direction = sun.direction().extend(sun.turbidity).to_array();
+        self.uniform.params = [self.exposure, self.sun_disk_intensity, 0.0, 0.0];
+        queue.write_buffer(&self.buffer, 0, bytemuck::cast_slice(&[self.uniform]));
+    }
+
+    /// 作为 [`GpuContext::background`](crate::context::GpuContext::background) 的天空盒，
+    /// 天空须以 surface 的格式且不带深度格式创建
+    pub fn background(&self) -> Background {
+        Background::Skybox(BackgroundDraw {
+            pipeline: self.pipeline.clone(),
+            bind_group: self.bind_group.clone(),
+        })
+    }
+
+    pub fn draw(&self, render_pass: &mut wgpu::RenderPass) {
+        render_pass.set_pipeline(&self.pipeline);
+        render_pass.set_bind_group(0, &self.bind_group, &[]);
+        render_pass.draw(0..3, 0..1);
+    }
+}
@@ -0,0 +1,666 @@
+use anyhow::{bail, ensure, Context};
+use bytemuck::{Pod, Zeroable};
+use wgpu::{BindGroup, BindGroupLayout, Buffer, CommandEncoder, Device, Queue, TextureView};
+
+use crate::{
+    camera::Camera, post::begin_fullscreen_pass, shader::ShaderLibrary, texture::Texture,
+    uniform::GpuUniform,
+};
+
+/// 一个三维高斯：协方差由缩放与旋转给出，`scale` 为三个主轴上的标准差
+#[derive(Debug, Copy, Clone)]
+pub struct Splat {
+    pub position: glam::Vec3,
+    pub scale: glam::Vec3,
+    pub rotation: glam::Quat,
+    pub color: glam::Vec3,
+    pub opacity: f32,
+}
+
+impl Splat {
+    /// 世界空间中的协方差 `R S Sᵀ Rᵀ`
+    pub fn covariance(&self) -> glam::Mat3 {
+        let m = glam::Mat3::from_quat(self.rotation) * glam::Mat3::from_diagonal(self.scale);
+        m * m.transpose()
+    }
+
+    fn to_raw(self) -> SplatRaw {
+        let c = self.covariance();
+        SplatRaw {
+            position_opacity: self.position.extend(self.opacity).to_array(),
+            color: self.color.extend(1.0).to_array(),
+            covariance_a: [c.x_axis.x, c.x_axis.y, c.x_axis.z, c.y_axis.y],
+            covariance_b: [c.y_axis.z, c.z_axis.z, 0.0, 0.0],
+        }
+    }
+}
+
+/// 解析 3D Gaussian Splatting 训练输出的二进制 PLY 文件
+///
+/// 需要 `x y z`、`f_dc_0..2`、`opacity`、`scale_0..2` 与 `rot_0..3` 属性；颜色只取球谐的 0 阶项，
+/// 不透明度与缩放按训练时的约定分别经过 sigmoid 与 exp。
+pub fn parse_ply(data: &[u8]) -> anyhow::Result<Vec<Splat>> {
+    const END_HEADER: &[u8] = b"end_header\n";
+    let header_len = data
+        .windows(END_HEADER.len())
+        .position(|w| w == END_HEADER)
+        .context("PLY header is not terminated by `end_header`")?;
+    let header = std::str::from_utf8(&data[..header_len]).context("PLY header is not UTF-8")?;
+    let body = &data[header_len + END_HEADER.len()..];
+
+    let mut lines = header.lines();
+    ensure!(lines.next() == Some("ply"), "not a PLY file");
+    let mut vertex_count = None;
+    let mut properties = Vec::new();
+    let mut in_vertex = false;
+    for line in lines {
+        let words = line.split_whitespace().collect::<Vec<_>>();
+        match words.as_slice() {
+            ["format", "binary_little_endian", _] => {}
+            ["format", format, _] => bail!("unsupported PLY format `{format}`"),
+            ["element", "vertex", count] => {
+                ensure!(
+                    vertex_count.is_none(),
+                    "PLY file has more than one vertex element"
+                );
+                vertex_count = Some(count.parse::<usize>().context("invalid vertex count")?);
+                in_vertex = true;
+            }
+            ["element", ..] => in_vertex = false,
+            ["property", "list", ..] if in_vertex => bail!("list properties are not supported"),
+            ["property", ty, name] if in_vertex => {
+                properties.push((*name, ply_scalar_size(ty)?, *ty))
+            }
+            _ => {}
+        }
+    }
+    let vertex_count = vertex_count.context("PLY file has no vertex element")?;
+    let stride = properties.iter().map(|(_, size, _)| size).sum::<usize>();
+    ensure!(
+        body.len() >= stride * vertex_count,
+        "PLY body holds fewer than {vertex_count} vertices"
+    );
+
+    let offset_of = |name: &str| {
+        let mut offset = 0;
+        for (property, size, ty) in &properties {
+            if *property == name {
+                return Ok((offset, *ty));
+            }
+            offset += size;
+        }
+        bail!("PLY vertex has no `{name}` property")
+    };
+    let fields = [
+        "x", "y", "z", "f_dc_0", "f_dc_1", "f_dc_2", "opacity", "scale_0", "scale_1", "scale_2",
+        "rot_0", "rot_1", "rot_2", "rot_3",
+    ]
+    .map(offset_of)
+    .into_iter()
+    .collect::<anyhow::Result<Vec<_>>>()?;
+
+    // 0 阶球谐系数
+    const SH_C0: f32 = 0.282_094_8;
+    Ok(body
+        .chunks_exact(stride)
+        .take(vertex_count)
+        .map(|vertex| {
+            let v = fields
+                .iter()
+                .map(|&(offset, ty)| read_ply_scalar(&vertex[offset..], ty))
+                .collect::<Vec<_>>();
+            Splat {
+                position: glam::vec3(v[0], v[1], v[2]),
+                color: (glam::vec3(v[3], v[4], v[5]) * SH_C0 + 0.5)
+                    .clamp(glam::Vec3::ZERO, glam::Vec3::ONE),
+                opacity: 1.0 / (1.0 + (-v[6]).exp()),
+                scale: glam::vec3(v[7], v[8], v[9]).exp(),
+                rotation: glam::Quat::from_xyzw(v[11], v[12], v[13], v[10]).normalize(),
+            }
+        })
+        .collect())
+}
+
+fn ply_scalar_size(ty: &str) -> anyhow::Result<usize> {
+    Ok(match ty {
+        "char" | "uchar" | "int8" | "uint8" => 1,
+        "short" | "ushort" | "int16" | "uint16" => 2,
+        "int" | "uint" | "float" | "int32" | "uint32" | "float32" => 4,
+        "double" | "float64" => 8,
+        _ => bail!("unknown PLY property type `{ty}`"),
+    })
+}
+
+fn read_ply_scalar(bytes: &[u8], ty: &str) -> f32 {
+    fn le<const N: usize>(bytes: &[u8]) -> [u8; N] {
+        bytes[..N].try_into().unwrap()
+    }
+    match ty {
+        "char" | "int8" => bytes[0] as i8 as f32,
+        "uchar" | "uint8" => bytes[0] as f32,
+        "short" | "int16" => i16::from_le_bytes(le(bytes)) as f32,
+        "ushort" | "uint16" => u16::from_le_bytes(le(bytes)) as f32,
+        "int" | "int32" => i32::from_le_bytes(le(bytes)) as f32,
+        "uint" | "uint32" => u32::from_le_bytes(le(bytes)) as f32,
+        "double" | "float64" => f64::from_le_bytes(le(bytes)) as f32,
+        _ => f32::from_le_bytes(le(bytes)),
+    }
+}
+
+/// 与 `splat.wgsl` 中的 `Splat` 保持一致，协方差只存上三角
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+struct SplatRaw {
+    position_opacity: [f32; 4],
+    color: [f32; 4],
+    /// xx, xy, xz, yy
+    covariance_a: [f32; 4],
+    /// yz, zz
+    covariance_b: [f32; 4],
+}
+
+unsafe impl Zeroable for SplatRaw {}
+unsafe impl Pod for SplatRaw {}
+
+/// 与 `splat.wgsl` 中的 `SplatUniform` 保持一致
+#[repr(C)]
+#[derive(Debug, Copy, Clone, GpuUniform)]
+pub struct SplatUniform {
+    view: [[f32; 4]; 4],
+    proj: [[f32; 4]; 4],
+    /// xy: 视口像素大小, zw: 以像素为单位的焦距
+    viewport: [f32; 4],
+    /// x: znear, y: 高斯个数, z: 缩放系数
+    params: [f32; 4],
+}
+
+/// 双调排序一轮比较交换的参数，按动态偏移逐轮绑定
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+struct SortStage {
+    block: u32,
+    distance: u32,
+    count: u32,
+    _padding: u32,
+}
+
+unsafe impl Zeroable for SortStage {}
+unsafe impl Pod for SortStage {}
+
+const WORKGROUP_SIZE: u32 = 256;
+
+/// 已上传的高斯数据及与之对应的绑定
+struct SplatBuffers {
+    count: u32,
+    /// 补齐到 2 的幂的排序长度
+    sort_count: u32,
+    stage_count: u32,
+    compute_bind_group: BindGroup,
+    stage_bind_group: BindGroup,
+    render_bind_group: BindGroup,
+}
+
+/// 实验性的高斯点云（Gaussian splatting）渲染器
+///
+/// 每帧先在计算着色器中按视深为全部高斯生成键，再以双调排序从近到远排好序；绘制时每个高斯
+/// 按投影后的二维协方差展开成一个面片，以“下方混合”（under）从前往后累积到一张预乘 alpha
+/// 的 [`SplatRenderer::FORMAT`] 纹理上，最后由 [`SplatRenderer::composite`] 盖到画面上。
+/// 累积时以只读方式绑定场景深度，因此高斯会被先绘制的不透明网格正确遮挡，但高斯本身不写深度。
+///
+/// 每帧的顺序：[`update`](Self::update)，不透明通道之后 [`render`](Self::render)，
+/// 最后 [`composite`](Self::composite)。
+pub struct SplatRenderer {
+    /// 高斯尺寸的整体缩放，便于检查重建质量
+    pub scale_modifier: f32,
+    target: Texture,
+    uniform_buffer: Buffer,
+    stage_stride: u32,
+    compute_bind_group_layout: BindGroupLayout,
+    stage_bind_group_layout: BindGroupLayout,
+    key_pipeline: wgpu::ComputePipeline,
+    sort_pipeline: wgpu::ComputePipeline,
+    render_bind_group_layout: BindGroupLayout,
+    render_pipeline: wgpu::RenderPipeline,
+    composite_layout: BindGroupLayout,
+    composite_bind_group: BindGroup,
+    composite_pipeline: wgpu::RenderPipeline,
+    splats: Option<SplatBuffers>,
+}
+
+impl SplatRenderer {
+    /// 累积高斯颜色的纹理格式
+    pub const FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba16Float;
+
+    /// `output_format` 为 [`SplatRenderer::composite`] 合成目标的格式
+    pub fn new(
+        device: &Device,
+        config: &wgpu::SurfaceConfiguration,
+        output_format: wgpu::TextureFormat,
+    ) -> Self {
+        let uniform_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Splat Uniform Buffer"),
+            size: std::mem::size_of::<SplatUniform>() as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let buffer_entry = |binding, visibility, ty| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility,
+            ty: wgpu::BindingType::Buffer {
+                ty,
+                has_dynamic_offset: false,
+                min_binding_size: None,
+            },
+            count: None,
+        };
+        let storage = |read_only| wgpu::BufferBindingType::Storage { read_only };
+        let compute = wgpu::ShaderStages::COMPUTE;
+        let compute_bind_group_layout =
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                entries: &[
+                    buffer_entry(0, compute, wgpu::BufferBindingType::Uniform),
+                    buffer_entry(1, compute, storage(true)),
+                    buffer_entry(2, compute, storage(false)),
+                    buffer_entry(3, compute, storage(false)),
+                ],
+                label: Some("splat_sort_bind_group_layout"),
+            });
+        let stage_bind_group_layout =
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                entries: &[wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: compute,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: true,
+                        min_binding_size: wgpu::BufferSize::new(
+                            std::mem::size_of::<SortStage>() as u64
+                        ),
+                    },
+                    count: None,
+                }],
+                label: Some("splat_sort_stage_bind_group_layout"),
+            });
+        let library = ShaderLibrary::new();
+        let sort_shader = library
+            .create_shader_module(
+                device,
+                "Splat Sort Shader",
+                include_str!("../shaders/splat_sort.wgsl"),
+            )
+            .expect("built-in splat sort shader");
+        let sort_pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Splat Sort Pipeline Layout"),
+            bind_group_layouts: &[&compute_bind_group_layout, &stage_bind_group_layout],
+            push_constant_ranges: &[],
+        });
+        let compute_pipeline = |label, entry_point| {
+            device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+                label: Some(label),
+                layout: Some(&sort_pipeline_layout),
+                module: &sort_shader,
+                entry_point: Some(entry_point),
+                compilation_options: Default::default(),
+                cache: None,
+            })
+        };
+        let key_pipeline = compute_pipeline("Splat Key Pipeline", "cs_keys");
+        let sort_pipeline = compute_pipeline("Splat Sort Pipeline", "cs_sort");
+
+        let vertex = wgpu::ShaderStages::VERTEX;
+        let render_bind_group_layout =
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                entries: &[
+                    buffer_entry(0, vertex, wgpu::BufferBindingType::Uniform),
+                    buffer_entry(1, vertex, storage(true)),
+                    buffer_entry(2, vertex, storage(true)),
+                ],
+                label: Some("splat_bind_group_layout"),
+            });
+        let shader = library
+            .create_shader_module(
+                device,
+                "Splat Shader",
+                include_str!("../shaders/splat.wgsl"),
+            )
+            .expect("built-in splat shader");
+        let render_pipeline_layout =
+            device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some("Splat Pipeline Layout"),
+                bind_group_layouts: &[&render_bind_group_layout],
+                push_constant_ranges: &[],
+            });
+        // 从前往后的 under 混合：dst += (1 - dst.a) * src，src 为预乘 alpha
+        let under = wgpu::BlendComponent {
+            src_factor: wgpu::BlendFactor::OneMinusDstAlpha,
+            dst_factor: wgpu::BlendFactor::One,
+            operation: wgpu::BlendOperation::Add,
+        };
+        let render_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Splat Pipeline"),
+            layout: Some(&render_pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                compilation_options: Default::default(),
+                entry_point: Some("vs_main"),
+                buffers: &[],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                compilation_options: Default::default(),
+                entry_point: Some("fs_main"),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: Self::FORMAT,
+                    blend: Some(wgpu::BlendState {
+                        color: under,
+                        alpha: under,
+                    }),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: Some(wgpu::DepthStencilState {
+                format: Texture::DEPTH_FORMAT,
+                depth_write_enabled: false,
+                depth_compare: wgpu::CompareFunction::Less,
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState::default(),
+            }),
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+            cache: None,
+        });
+
+        let composite_layout = Texture::texture_bind_group_layout(device);
+        let composite_shader = library
+            .create_shader_module(
+                device,
+                "Splat Composite Shader",
+                include_str!("../shaders/blit.wgsl"),
+            )
+            .expect("built-in blit shader");
+        let composite_pipeline_layout =
+            device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some("Splat Composite Pipeline Layout"),
+                bind_group_layouts: &[&composite_layout],
+                push_constant_ranges: &[],
+            });
+        let composite_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Splat Composite Pipeline"),
+            layout: Some(&composite_pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &composite_shader,
+                compilation_options: Default::default(),
+                entry_point: Some("vs_main"),
+                buffers: &[],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &composite_shader,
+                compilation_options: Default::default(),
+                entry_point: Some("fs_main"),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: output_format,
+                    blend: Some(wgpu::BlendState::PREMULTIPLIED_ALPHA_BLENDING),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+            cache: None,
+        });
+
+        let target = Self::create_target(device, config);
+        let composite_bind_group =
+            Self::create_composite_bind_group(device, &composite_layout, &target);
+        let alignment = device.limits().min_uniform_buffer_offset_alignment;
+
+        Self {
+            scale_modifier: 1.0,
+            target,
+            uniform_buffer,
+            stage_stride: (std::mem::size_of::<SortStage>() as u32).next_multiple_of(alignment),
+            compute_bind_group_layout,
+            stage_bind_group_layout,
+            key_pipeline,
+            sort_pipeline,
+            render_bind_group_layout,
+            render_pipeline,
+            composite_layout,
+            composite_bind_group,
+            composite_pipeline,
+            splats: None,
+        }
+    }
+
+    fn create_target(device: &Device, config: &wgpu::SurfaceConfiguration) -> Texture {
+        Texture::create_color_target(device, config, Self::FORMAT, "splat_target")
+    }
+
+    fn create_composite_bind_group(
+        device: &Device,
+        layout: &BindGroupLayout,
+        target: &Texture,
+    ) -> BindGroup {
+        device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(&target.view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(&target.sampler),
+                },
+            ],
+            label: Some("splat_composite_bind_group"),
+        })
+    }
+
+    pub fn resize(&mut self, device: &Device, config: &wgpu::SurfaceConfiguration) {
+        self.target = Self::create_target(device, config);
+        self.composite_bind_group =
+            Self::create_composite_bind_group(device, &self.composite_layout, &self.target);
+    }
+
+    pub fn len(&self) -> usize {
+        self.splats.as_ref().map_or(0, |s| s.count as usize)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// 上传一组高斯，替换之前的全部数据
+    pub fn set_splats(&mut self, device: &Device, queue: &Queue, splats: &[Splat]) {
+        if splats.is_empty() {
+            self.splats = None;
+            return;
+        }
+        let count = splats.len() as u32;
+        let sort_count = count.next_power_of_two();
+
+        let raw = splats.iter().map(|s| s.to_raw()).collect::<Vec<_>>();
+        let splat_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Splat Buffer"),
+            size: std::mem::size_of_val(raw.as_slice()) as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        queue.write_buffer(&splat_buffer, 0, bytemuck::cast_slice(&raw));
+        let sort_buffer = |label| {
+            device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some(label),
+                size: (std::mem::size_of::<u32>() as u32 * sort_count) as wgpu::BufferAddress,
+                usage: wgpu::BufferUsages::STORAGE,
+                mapped_at_creation: false,
+            })
+        };
+        let key_buffer = sort_buffer("Splat Sort Keys");
+        let index_buffer = sort_buffer("Splat Sort Indices");
+
+        // 双调排序的每一轮：块大小从 2 翻倍到 sort_count，块内比较距离逐次减半
+        let mut stages = Vec::new();
+        let mut block = 2;
+        while block <= sort_count {
+            let mut distance = block / 2;
+            while distance > 0 {
+                stages.push(SortStage {
+                    block,
+                    distance,
+                    count: sort_count,
+                    _padding: 0,
+                });
+                distance /= 2;
+            }
+            block *= 2;
+        }
+        // 只有一个高斯时没有排序轮次，但绑定仍需要一个有效的槽位
+        let stride = self.stage_stride as usize;
+        let mut stage_data = vec![0u8; stride * stages.len().max(1)];
+        for (i, stage) in stages.iter().enumerate() {
+            stage_data[i * stride..][..std::mem::size_of::<SortStage>()]
+                .copy_from_slice(bytemuck::bytes_of(stage));
+        }
+        let stage_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Splat Sort Stage Buffer"),
+            size: stage_data.len() as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        queue.write_buffer(&stage_buffer, 0, &stage_data);
+
+        let compute_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: &self.compute_bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: self.uniform_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: splat_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: key_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 3,
+                    resource: index_buffer.as_entire_binding(),
+                },
+            ],
+            label: Some("splat_sort_bind_group"),
+        });
+        let stage_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: &self.stage_bind_group_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: wgpu::BindingResource::Buffer(wgpu::BufferBinding {
+                    buffer: &stage_buffer,
+                    offset: 0,
+                    size: wgpu::BufferSize::new(std::mem::size_of::<SortStage>() as u64),
+                }),
+            }],
+            label: Some("splat_sort_stage_bind_group"),
+        });
+        let render_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: &self.render_bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: self.uniform_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: splat_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: index_buffer.as_entire_binding(),
+                },
+            ],
+            label: Some("splat_bind_group"),
+        });
+
+        self.splats = Some(SplatBuffers {
+            count,
+            sort_count,
+            stage_count: stages.len() as u32,
+            compute_bind_group,
+            stage_bind_group,
+            render_bind_group,
+        });
+    }
+
+    /// 写入本帧的相机参数，`viewport` 为累积纹理的像素大小
+    pub fn update(&self, queue: &Queue, camera: &Camera, viewport: glam::Vec2) {
+        let view = camera.view_matrix();
+        let proj = camera.projection_matrix();
+        let focal = glam::vec2(proj.x_axis.x, proj.y_axis.y) * viewport * 0.5;
+        let uniform = SplatUniform {
+            view: view.to_cols_array_2d(),
+            proj: proj.to_cols_array_2d(),
+            viewport: [viewport.x, viewport.y, focal.x, focal.y],
+            params: [camera.znear, self.len() as f32, self.scale_modifier, 0.0],
+        };
+        uniform.write_to(queue, &self.uniform_buffer);
+    }
+
+    /// 在 GPU 上按视深排序后把高斯累积到内部纹理，`depth` 为不透明通道写入的场景深度
+    pub fn render(&self, encoder: &mut CommandEncoder, depth: &Texture) {
+        if let Some(splats) = &self.splats {
+            let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+                label: Some("Splat Sort Pass"),
+                timestamp_writes: None,
+            });
+            let workgroups = splats.sort_count.div_ceil(WORKGROUP_SIZE);
+            pass.set_bind_group(0, &splats.compute_bind_group, &[]);
+            pass.set_bind_group(1, &splats.stage_bind_group, &[0]);
+            pass.set_pipeline(&self.key_pipeline);
+            pass.dispatch_workgroups(workgroups, 1, 1);
+            pass.set_pipeline(&self.sort_pipeline);
+            for stage in 0..splats.stage_count {
+                pass.set_bind_group(1, &splats.stage_bind_group, &[stage * self.stage_stride]);
+                pass.dispatch_workgroups(workgroups, 1, 1);
+            }
+        }
+
+        let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Splat Pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: &self.target.view,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT),
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                view: &depth.view,
+                depth_ops: None,
+                stencil_ops: None,
+            }),
+            ..Default::default()
+        });
+        if let Some(splats) = &self.splats {
+            pass.set_pipeline(&self.render_pipeline);
+            pass.set_bind_group(0, &splats.render_bind_group, &[]);
+            pass.draw(0..6, 0..splats.count);
+        }
+    }
+
+    /// 以预乘 alpha 把累积的高斯混合到 `output` 上
+    pub fn composite(&self, encoder: &mut CommandEncoder, output: &TextureView) {
+        if self.splats.is_none() {
+            return;
+        }
+        let mut pass = begin_fullscreen_pass(encoder, "Splat Composite Pass", output);
+        pass.set_pipeline(&self.composite_pipeline);
+        pass.set_bind_group(0, &self.composite_bind_group, &[]);
+        pass.draw(0..3, 0..1);
+    }
+}
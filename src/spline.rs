@@ -0,0 +1,479 @@
+use anyhow::ensure;
+
+use crate::model::{Model, RenderVertex};
+
+/// 参数 `t` 取值 [0, 1] 的三维曲线
+pub trait Curve {
+    fn point(&self, t: f32) -> glam::Vec3;
+
+    /// 对 `t` 的导数，未归一化
+    fn derivative(&self, t: f32) -> glam::Vec3;
+
+    /// 为 true 时 `point(1.0)` 与 `point(0.0)` 重合
+    fn is_closed(&self) -> bool {
+        false
+    }
+
+    fn tangent(&self, t: f32) -> glam::Vec3 {
+        self.derivative(t).normalize_or(glam::Vec3::Z)
+    }
+
+    /// 按参数等间隔采样 `count + 1` 个点（包含两端）
+    fn sample(&self, count: usize) -> Vec<glam::Vec3> {
+        let count = count.max(1);
+        (0..=count)
+            .map(|i| self.point(i as f32 / count as f32))
+            .collect()
+    }
+
+    /// 用折线近似的曲线长度
+    fn length(&self) -> f32 {
+        ArcLengthTable::new(self, 256).total()
+    }
+}
+
+/// 参数与弧长的对应表，用于按距离等间隔采样曲线
+#[derive(Debug, Clone)]
+pub struct ArcLengthTable {
+    /// 第 i 项为 `t = i / resolution` 处到起点的累计长度
+    lengths: Vec<f32>,
+}
+
+impl ArcLengthTable {
+    pub fn new<C: Curve + ?Sized>(curve: &C, resolution: usize) -> Self {
+        let points = curve.sample(resolution);
+        let mut lengths = Vec::with_capacity(points.len());
+        let mut total = 0.0;
+        lengths.push(0.0);
+        for pair in points.windows(2) {
+            total += pair[0].distance(pair[1]);
+            lengths.push(total);
+        }
+        Self { lengths }
+    }
+
+    pub fn total(&self) -> f32 {
+        *self.lengths.last().unwrap()
+    }
+
+    /// 累计长度为 `distance` 处的参数 `t`
+    pub fn t_at(&self, distance: f32) -> f32 {
+        let segments = self.lengths.len() - 1;
+        let distance = distance.clamp(0.0, self.total());
+        let i = self
+            .lengths
+            .partition_point(|&l| l < distance)
+            .clamp(1, segments);
+        let (a, b) = (self.lengths[i - 1], self.lengths[i]);
+        let local = if b > a { (distance - a) / (b - a) } else { 0.0 };
+        (i as f32 - 1.0 + local) / segments as f32
+    }
+
+    /// 按弧长等间隔给出 `count + 1` 个参数（包含两端）
+    pub fn uniform_ts(&self, count: usize) -> Vec<f32> {
+        let count = count.max(1);
+        (0..=count)
+            .map(|i| self.t_at(self.total() * i as f32 / count as f32))
+            .collect()
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct CubicBezier {
+    pub p0: glam::Vec3,
+    pub p1: glam::Vec3,
+    pub p2: glam::Vec3,
+    pub p3: glam::Vec3,
+}
+
+impl CubicBezier {
+    pub fn new(p0: glam::Vec3, p1: glam::Vec3, p2: glam::Vec3, p3: glam::Vec3) -> Self {
+        Self { p0, p1, p2, p3 }
+    }
+
+    /// 在 `t` 处分成两段（de Casteljau）
+    pub fn split(&self, t: f32) -> (Self, Self) {
+        let a = self.p0.lerp(self.p1, t);
+        let b = self.p1.lerp(self.p2, t);
+        let c = self.p2.lerp(self.p3, t);
+        let d = a.lerp(b, t);
+        let e = b.lerp(c, t);
+        let f = d.lerp(e, t);
+        (Self::new(self.p0, a, d, f), Self::new(f, e, c, self.p3))
+    }
+}
+
+impl Curve for CubicBezier {
+    fn point(&self, t: f32) -> glam::Vec3 {
+        let s = 1.0 - t;
+        self.p0 * (s * s * s)
+            + self.p1 * (3.0 * s * s * t)
+            + self.p2 * (3.0 * s * t * t)
+            + self.p3 * (t * t * t)
+    }
+
+    fn derivative(&self, t: f32) -> glam::Vec3 {
+        let s = 1.0 - t;
+        (self.p1 - self.p0) * (3.0 * s * s)
+            + (self.p2 - self.p1) * (6.0 * s * t)
+            + (self.p3 - self.p2) * (3.0 * t * t)
+    }
+}
+
+/// 首尾相接的多段三次 Bezier 曲线，每段占用相同的参数区间
+#[derive(Debug, Clone)]
+pub struct BezierSpline {
+    pub segments: Vec<CubicBezier>,
+}
+
+impl BezierSpline {
+    /// 控制点按 `端点, 控制点, 控制点, 端点, 控制点, ...` 排列，数量必须为 3n + 1
+    pub fn from_control_points(points: &[glam::Vec3]) -> anyhow::Result<Self> {
+        ensure!(
+            points.len() >= 4 && (points.len() - 1).is_multiple_of(3),
+            "bezier spline needs 3n + 1 control points, got {}",
+            points.len()
+        );
+        let segments = points
+            .windows(4)
+            .step_by(3)
+            .map(|p| CubicBezier::new(p[0], p[1], p[2], p[3]))
+            .collect();
+        Ok(Self { segments })
+    }
+
+    /// 把全局参数映射到某一段及段内参数，没有任何段时返回 `None`
+    fn locate(&self, t: f32) -> Option<(&CubicBezier, f32)> {
+        let n = self.segments.len();
+        if n == 0 {
+            return None;
+        }
+        let scaled = t.clamp(0.0, 1.0) * n as f32;
+        let i = (scaled as usize).min(n - 1);
+        Some((&self.segments[i], scaled - i as f32))
+    }
+}
+
+impl Curve for BezierSpline {
+    fn point(&self, t: f32) -> glam::Vec3 {
+        self.locate(t)
+            .map(|(segment, local)| segment.point(local))
+            .unwrap_or_default()
+    }
+
+    fn derivative(&self, t: f32) -> glam::Vec3 {
+        self.locate(t)
+            .map(|(segment, local)| segment.derivative(local) * self.segments.len() as f32)
+            .unwrap_or_default()
+    }
+
+    fn is_closed(&self) -> bool {
+        match (self.segments.first(), self.segments.last()) {
+            (Some(first), Some(last)) => first.p0.distance_squared(last.p3) < 1e-10,
+            _ => false,
+        }
+    }
+}
+
+/// 经过所有控制点的 Catmull-Rom 样条
+///
+/// `alpha` 为 0 时是均匀参数化，0.5 为向心参数化（不会产生尖点与自交），1 为弦长参数化。
+#[derive(Debug, Clone)]
+pub struct CatmullRom {
+    pub points: Vec<glam::Vec3>,
+    pub closed: bool,
+    pub alpha: f32,
+}
+
+impl CatmullRom {
+    /// 使用向心参数化的开放曲线
+    pub fn new(points: Vec<glam::Vec3>) -> Self {
+        Self {
+            points,
+            closed: false,
+            alpha: 0.5,
+        }
+    }
+
+    pub fn closed(mut self, closed: bool) -> Self {
+        self.closed = closed;
+        self
+    }
+
+    pub fn segment_count(&self) -> usize {
+        match self.points.len() {
+            0 | 1 => 0,
+            n if self.closed => n,
+            n => n - 1,
+        }
+    }
+
+    /// 第 `i` 段对应的 Bezier 曲线；开放曲线的两端按镜像补出虚拟控制点
+    pub fn segment(&self, i: usize) -> CubicBezier {
+        let n = self.points.len() as isize;
+        let at = |j: isize| -> glam::Vec3 {
+            if self.closed {
+                self.points[j.rem_euclid(n) as usize]
+            } else if j < 0 {
+                2.0 * self.points[0] - self.points[1]
+            } else if j >= n {
+                2.0 * self.points[n as usize - 1] - self.points[n as usize - 2]
+            } else {
+                self.points[j as usize]
+            }
+        };
+        let i = i as isize;
+        let (p0, p1, p2, p3) = (at(i - 1), at(i), at(i + 1), at(i + 2));
+
+        // 非均匀参数化下的端点切线，见 Barry-Goldman 递推的导数形式
+        let knot = |a: glam::Vec3, b: glam::Vec3| a.distance(b).powf(self.alpha).max(1e-4);
+        let (d0, d1, d2) = (knot(p0, p1), knot(p1, p2), knot(p2, p3));
+        let m1 = ((p1 - p0) / d0 - (p2 - p0) / (d0 + d1) + (p2 - p1) / d1) * d1;
+        let m2 = ((p2 - p1) / d1 - (p3 - p1) / (d1 + d2) + (p3 - p2) / d2) * d1;
+        CubicBezier::new(p1, p1 + m1 / 3.0, p2 - m2 / 3.0, p2)
+    }
+
+    pub fn to_bezier(&self) -> BezierSpline {
+        BezierSpline {
+            segments: (0..self.segment_count()).map(|i| self.segment(i)).collect(),
+        }
+    }
+
+    fn locate(&self, t: f32) -> (CubicBezier, f32) {
+        let n = self.segment_count();
+        let scaled = t.clamp(0.0, 1.0) * n as f32;
+        let i = (scaled as usize).min(n - 1);
+        (self.segment(i), scaled - i as f32)
+    }
+}
+
+impl Curve for CatmullRom {
+    fn point(&self, t: f32) -> glam::Vec3 {
+        match self.segment_count() {
+            0 => self.points.first().copied().unwrap_or_default(),
+            _ => {
+                let (segment, local) = self.locate(t);
+                segment.point(local)
+            }
+        }
+    }
+
+    fn derivative(&self, t: f32) -> glam::Vec3 {
+        match self.segment_count() {
+            0 => glam::Vec3::ZERO,
+            n => {
+                let (segment, local) = self.locate(t);
+                segment.derivative(local) * n as f32
+            }
+        }
+    }
+
+    fn is_closed(&self) -> bool {
+        self.closed && self.points.len() >= 3
+    }
+}
+
+/// 沿曲线的正交标架，`normal` 与 `binormal` 张成截面所在的平面
+#[derive(Debug, Copy, Clone)]
+pub struct Frame {
+    pub position: glam::Vec3,
+    pub tangent: glam::Vec3,
+    pub normal: glam::Vec3,
+    pub binormal: glam::Vec3,
+}
+
+/// 在给定参数处计算平行移动标架，截面沿曲线不会产生多余的扭转；
+/// 闭合曲线首尾的角度差会平均分摊到各个标架上
+pub fn parallel_transport_frames<C: Curve + ?Sized>(curve: &C, ts: &[f32]) -> Vec<Frame> {
+    let Some(&first) = ts.first() else {
+        return Vec::new();
+    };
+    let tangent = curve.tangent(first);
+    // 选择与切线最不平行的坐标轴来构造初始法线
+    let axis = if tangent.x.abs() < 0.9 {
+        glam::Vec3::X
+    } else {
+        glam::Vec3::Y
+    };
+    let mut normal = tangent.cross(axis).normalize();
+    let mut frames = Vec::with_capacity(ts.len());
+    frames.push(Frame {
+        position: curve.point(first),
+        tangent,
+        normal,
+        binormal: tangent.cross(normal),
+    });
+    for &t in &ts[1..] {
+        let prev = frames.last().unwrap().tangent;
+        let tangent = curve.tangent(t);
+        normal = glam::Quat::from_rotation_arc(prev, tangent) * normal;
+        // 消除累积误差，保持与切线正交
+        normal = (normal - tangent * normal.dot(tangent)).normalize_or(normal);
+        frames.push(Frame {
+            position: curve.point(t),
+            tangent,
+            normal,
+            binormal: tangent.cross(normal),
+        });
+    }
+
+    if curve.is_closed() && frames.len() > 1 {
+        let last = frames.last().unwrap();
+        let first = frames[0];
+        let target = glam::Quat::from_rotation_arc(first.tangent, last.tangent) * first.normal;
+        let angle = last
+            .normal
+            .cross(target)
+            .dot(last.tangent)
+            .atan2(last.normal.dot(target));
+        let count = (frames.len() - 1) as f32;
+        for (i, frame) in frames.iter_mut().enumerate() {
+            let rotation = glam::Quat::from_axis_angle(frame.tangent, angle * i as f32 / count);
+            frame.normal = rotation * frame.normal;
+            frame.binormal = frame.tangent.cross(frame.normal);
+        }
+    }
+    frames
+}
+
+/// 挤出得到的网格，索引为逆时针朝外的三角形列表
+#[derive(Debug, Clone, Default)]
+pub struct ExtrudedMesh {
+    pub positions: Vec<glam::Vec3>,
+    pub normals: Vec<glam::Vec3>,
+    /// u 沿截面轮廓取 [0, 1]，v 为沿曲线的弧长
+    pub uvs: Vec<glam::Vec2>,
+    pub indices: Vec<u32>,
+}
+
+impl ExtrudedMesh {
+    /// 用 `vertex` 把位置、法线与 uv 转换为具体的顶点类型
+    pub fn to_model<V: RenderVertex>(
+        &self,
+        label: &str,
+        vertex: impl Fn(glam::Vec3, glam::Vec3, glam::Vec2) -> V,
+    ) -> Model<V> {
+        let vertices = self
+            .positions
+            .iter()
+            .zip(&self.normals)
+            .zip(&self.uvs)
+            .map(|((&p, &n), &uv)| vertex(p, n, uv))
+            .collect::<Vec<_>>();
+        Model::new(&vertices, &self.indices, label)
+    }
+}
+
+#[derive(Debug, Copy, Clone)]
+pub struct ExtrudeSettings {
+    /// 沿曲线的分段数，按弧长均匀分布
+    pub segments: usize,
+    /// 开放曲线的两端是否封口，只适用于凸轮廓
+    pub caps: bool,
+}
+
+impl Default for ExtrudeSettings {
+    fn default() -> Self {
+        Self {
+            segments: 64,
+            caps: true,
+        }
+    }
+}
+
+/// 把闭合的二维轮廓沿曲线挤出，轮廓的 x、y 分别沿标架的 normal 与 binormal，
+/// 应按逆时针排列
+pub fn extrude<C: Curve + ?Sized>(
+    curve: &C,
+    profile: &[glam::Vec2],
+    settings: &ExtrudeSettings,
+) -> ExtrudedMesh {
+    let mut mesh = ExtrudedMesh::default();
+    let sides = profile.len();
+    if sides < 2 {
+        return mesh;
+    }
+
+    let table = ArcLengthTable::new(curve, settings.segments.max(1) * 8);
+    let ts = table.uniform_ts(settings.segments);
+    let frames = parallel_transport_frames(curve, &ts);
+    let step = table.total() / (ts.len() - 1) as f32;
+
+    // 轮廓的平滑法线取相邻两条边外法线的平均
+    let edge_normal = |i: usize| {
+        let d = profile[(i + 1) % sides] - profile[i];
+        glam::vec2(d.y, -d.x).normalize_or_zero()
+    };
+    let profile_normals = (0..sides)
+        .map(|i| (edge_normal((i + sides - 1) % sides) + edge_normal(i)).normalize_or_zero())
+        .collect::<Vec<_>>();
+
+    // 每圈多出一个与首个顶点重合的顶点，以便 u 坐标连续
+    let ring = sides as u32 + 1;
+    for (i, frame) in frames.iter().enumerate() {
+        for j in 0..=sides {
+            let p = profile[j % sides];
+            let n = profile_normals[j % sides];
+            mesh.positions
+                .push(frame.position + frame.normal * p.x + frame.binormal * p.y);
+            mesh.normals
+                .push((frame.normal * n.x + frame.binormal * n.y).normalize_or_zero());
+            mesh.uvs
+                .push(glam::vec2(j as f32 / sides as f32, i as f32 * step));
+        }
+    }
+    for i in 0..frames.len() as u32 - 1 {
+        for j in 0..sides as u32 {
+            let a = i * ring + j;
+            let b = a + 1;
+            let c = a + ring;
+            let d = c + 1;
+            mesh.indices.extend_from_slice(&[a, b, c, b, d, c]);
+        }
+    }
+
+    if settings.caps && !curve.is_closed() {
+        let ends = [(frames[0], -1.0_f32), (*frames.last().unwrap(), 1.0_f32)];
+        for (frame, side) in ends {
+            let normal = frame.tangent * side;
+            let center = mesh.positions.len() as u32;
+            mesh.positions.push(frame.position);
+            mesh.normals.push(normal);
+            mesh.uvs.push(glam::Vec2::splat(0.5));
+            for p in profile {
+                mesh.positions
+                    .push(frame.position + frame.normal * p.x + frame.binormal * p.y);
+                mesh.normals.push(normal);
+                mesh.uvs.push(*p * 0.5 + 0.5);
+            }
+            for j in 0..sides as u32 {
+                let a = center + 1 + j;
+                let b = center + 1 + (j + 1) % sides as u32;
+                // 末端朝向切线方向，起始端朝向其反方向，两者的绕序相反
+                if side > 0.0 {
+                    mesh.indices.extend_from_slice(&[center, a, b]);
+                } else {
+                    mesh.indices.extend_from_slice(&[center, b, a]);
+                }
+            }
+        }
+    }
+    mesh
+}
+
+/// 沿曲线生成圆管，`radial_segments` 为截面圆的分段数
+pub fn tube<C: Curve + ?Sized>(
+    curve: &C,
+    radius: f32,
+    radial_segments: usize,
+    settings: &ExtrudeSettings,
+) -> ExtrudedMesh {
+    let radial_segments = radial_segments.max(3);
+    let profile = (0..radial_segments)
+        .map(|i| {
+            let angle = i as f32 / radial_segments as f32 * std::f32::consts::TAU;
+            glam::vec2(angle.cos(), angle.sin()) * radius
+        })
+        .collect::<Vec<_>>();
+    extrude(curve, &profile, settings)
+}
@@ -0,0 +1,440 @@
+use bytemuck::{Pod, Zeroable};
+use wgpu::{BindGroup, Buffer, Device, Queue, RenderPipeline};
+use winit::dpi::PhysicalSize;
+
+use crate::{model::RenderVertex, shader::ShaderLibrary, texture::Texture, uniform::GpuUniform};
+
+pub mod animation;
+
+/// 纹理中的矩形区域，`min` 为左上角
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct UvRect {
+    pub min: glam::Vec2,
+    pub max: glam::Vec2,
+}
+
+impl UvRect {
+    pub const FULL: UvRect = UvRect {
+        min: glam::Vec2::ZERO,
+        max: glam::Vec2::ONE,
+    };
+
+    /// 左右翻转，用于朝向相反的精灵
+    pub fn flip_x(self) -> Self {
+        Self {
+            min: glam::vec2(self.max.x, self.min.y),
+            max: glam::vec2(self.min.x, self.max.y),
+        }
+    }
+}
+
+/// 精灵的形状；圆角矩形与圆由片元着色器中的 SDF 计算，边缘抗锯齿且不采样纹理
+#[derive(Debug, Copy, Clone, Default, PartialEq)]
+pub enum SpriteShape {
+    #[default]
+    Textured,
+    /// `outline` 为 0 时填充，否则只绘制该宽度的描边
+    RoundedRect { radius: f32, outline: f32 },
+    /// 直径取精灵宽高中较小的一个
+    Circle { outline: f32 },
+}
+
+/// 一个以 `position` 为中心的矩形精灵，单位与投影矩阵一致
+#[derive(Debug, Copy, Clone)]
+pub struct Sprite {
+    pub position: glam::Vec2,
+    pub size: glam::Vec2,
+    /// 绕中心的旋转，单位弧度
+    pub rotation: f32,
+    pub uv: UvRect,
+    /// 与纹理颜色相乘
+    pub color: glam::Vec4,
+    pub shape: SpriteShape,
+}
+
+impl Sprite {
+    pub fn new(position: glam::Vec2, size: glam::Vec2) -> Self {
+        Self {
+            position,
+            size,
+            rotation: 0.0,
+            uv: UvRect::FULL,
+            color: glam::Vec4::ONE,
+            shape: SpriteShape::Textured,
+        }
+    }
+
+    pub fn rounded_rect(
+        position: glam::Vec2,
+        size: glam::Vec2,
+        radius: f32,
+        color: glam::Vec4,
+    ) -> Self {
+        Self {
+            color,
+            shape: SpriteShape::RoundedRect {
+                radius,
+                outline: 0.0,
+            },
+            ..Self::new(position, size)
+        }
+    }
+
+    pub fn circle(center: glam::Vec2, radius: f32, color: glam::Vec4) -> Self {
+        Self {
+            color,
+            shape: SpriteShape::Circle { outline: 0.0 },
+            ..Self::new(center, glam::Vec2::splat(radius * 2.0))
+        }
+    }
+
+    pub fn with_uv(self, uv: UvRect) -> Self {
+        Self { uv, ..self }
+    }
+
+    pub fn with_color(self, color: glam::Vec4) -> Self {
+        Self { color, ..self }
+    }
+
+    pub fn with_rotation(self, rotation: f32) -> Self {
+        Self { rotation, ..self }
+    }
+
+    /// 把圆角矩形或圆改为只绘制描边，对纹理精灵无效
+    pub fn with_outline(self, width: f32) -> Self {
+        let shape = match self.shape {
+            SpriteShape::Textured => SpriteShape::Textured,
+            SpriteShape::RoundedRect { radius, .. } => SpriteShape::RoundedRect {
+                radius,
+                outline: width,
+            },
+            SpriteShape::Circle { .. } => SpriteShape::Circle { outline: width },
+        };
+        Self { shape, ..self }
+    }
+}
+
+/// 九宫格面板：四角保持原始大小，四边只沿一个方向拉伸，中间向两个方向拉伸
+#[derive(Debug, Copy, Clone)]
+pub struct NineSlice {
+    /// 面板左上角
+    pub min: glam::Vec2,
+    pub size: glam::Vec2,
+    /// 整张面板在纹理中的范围
+    pub uv: UvRect,
+    /// 左、上、右、下四条边在纹理中的宽度，单位为 uv
+    pub uv_border: glam::Vec4,
+    /// 四条边绘制到屏幕上的宽度，单位与投影矩阵一致，面板太小时按比例缩小
+    pub border: glam::Vec4,
+    pub color: glam::Vec4,
+}
+
+impl NineSlice {
+    /// 拆分为最多九个精灵，宽或高为零的部分被跳过
+    pub fn sprites(&self) -> impl Iterator<Item = Sprite> + '_ {
+        // 相对的两条边之和超过面板大小时按比例缩小，避免角落重叠
+        let fit = |a: f32, b: f32, size: f32| {
+            let scale = (size / (a + b)).min(1.0);
+            if scale.is_finite() {
+                (a * scale, b * scale)
+            } else {
+                (0.0, 0.0)
+            }
+        };
+        let (left, right) = fit(self.border.x, self.border.z, self.size.x);
+        let (top, bottom) = fit(self.border.y, self.border.w, self.size.y);
+        let xs = [0.0, left, self.size.x - right, self.size.x];
+        let ys = [0.0, top, self.size.y - bottom, self.size.y];
+        let (uv_min, uv_max) = (self.uv.min, self.uv.max);
+        let us = [
+            uv_min.x,
+            uv_min.x + self.uv_border.x,
+            uv_max.x - self.uv_border.z,
+            uv_max.x,
+        ];
+        let vs = [
+            uv_min.y,
+            uv_min.y + self.uv_border.y,
+            uv_max.y - self.uv_border.w,
+            uv_max.y,
+        ];
+
+        (0..9).filter_map(move |i| {
+            let (col, row) = (i % 3, i / 3);
+            let min = glam::vec2(xs[col], ys[row]);
+            let max = glam::vec2(xs[col + 1], ys[row + 1]);
+            let size = max - min;
+            (size.x > 0.0 && size.y > 0.0).then(|| {
+                Sprite::new(self.min + (min + max) * 0.5, size)
+                    .with_uv(UvRect {
+                        min: glam::vec2(us[col], vs[row]),
+                        max: glam::vec2(us[col + 1], vs[row + 1]),
+                    })
+                    .with_color(self.color)
+            })
+        })
+    }
+}
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+struct SpriteRaw {
+    /// xy: 中心, zw: 大小
+    rect: [f32; 4],
+    /// xy: uv 左上角, zw: uv 右下角
+    uv: [f32; 4],
+    color: [f32; 4],
+    /// 旋转角的余弦与正弦
+    rotation: [f32; 2],
+    /// x: 形状（0 纹理，1 圆角矩形）, y: 圆角半径, z: 描边宽度
+    shape: [f32; 4],
+}
+
+unsafe impl Zeroable for SpriteRaw {}
+unsafe impl Pod for SpriteRaw {}
+
+impl RenderVertex for SpriteRaw {
+    fn buffer_layout_desc<'a>() -> wgpu::VertexBufferLayout<'a> {
+        const ATTRIBUTES: [wgpu::VertexAttribute; 5] = wgpu::vertex_attr_array![
+            0 => Float32x4,
+            1 => Float32x4,
+            2 => Float32x4,
+            3 => Float32x2,
+            4 => Float32x4,
+        ];
+        wgpu::VertexBufferLayout {
+            array_stride: std::mem::size_of::<SpriteRaw>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Instance,
+            attributes: &ATTRIBUTES,
+        }
+    }
+}
+
+impl From<&Sprite> for SpriteRaw {
+    fn from(sprite: &Sprite) -> Self {
+        let (sin, cos) = sprite.rotation.sin_cos();
+        // 圆就是圆角半径等于半边长的圆角矩形
+        let shape = match sprite.shape {
+            SpriteShape::Textured => [0.0; 4],
+            SpriteShape::RoundedRect { radius, outline } => [1.0, radius, outline, 0.0],
+            SpriteShape::Circle { outline } => [1.0, sprite.size.min_element() * 0.5, outline, 0.0],
+        };
+        Self {
+            rect: [
+                sprite.position.x,
+                sprite.position.y,
+                sprite.size.x,
+                sprite.size.y,
+            ],
+            uv: [
+                sprite.uv.min.x,
+                sprite.uv.min.y,
+                sprite.uv.max.x,
+                sprite.uv.max.y,
+            ],
+            color: sprite.color.to_array(),
+            rotation: [cos, sin],
+            shape,
+        }
+    }
+}
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone, GpuUniform)]
+struct SpriteUniform {
+    view_proj: [[f32; 4]; 4],
+}
+
+/// 以像素为单位、原点在左上角、y 轴向下的正交投影；需要平移缩放时使用
+/// [`Camera2D::view_proj`](crate::camera2d::Camera2D::view_proj)
+pub fn pixel_projection(size: PhysicalSize<u32>) -> glam::Mat4 {
+    glam::Mat4::orthographic_rh(0.0, size.width as f32, size.height as f32, 0.0, -1.0, 1.0)
+}
+
+/// 从同一张纹理（通常是精灵图集）中取图的实例化精灵渲染器
+///
+/// 精灵按添加顺序以半透明混合绘制，不做深度测试；采样使用最近邻过滤，像素画不会变模糊。
+/// 也可以绘制 [`NineSlice`] 面板与 [`SpriteShape`] 中的圆角矩形和圆，用于简单的 HUD。
+pub struct SpriteRenderer {
+    sprites: Vec<SpriteRaw>,
+    dirty: bool,
+    /// 已上传到 GPU 的精灵数量
+    uploaded: u32,
+    sprite_buffer: Buffer,
+    sprite_capacity: usize,
+    uniform_buffer: Buffer,
+    uniform_bind_group: BindGroup,
+    texture_bind_group: BindGroup,
+    pipeline: RenderPipeline,
+}
+
+impl SpriteRenderer {
+    const INITIAL_CAPACITY: usize = 64;
+
+    pub fn new(
+        device: &Device,
+        color_format: wgpu::TextureFormat,
+        texture: &wgpu::TextureView,
+    ) -> Self {
+        let uniform_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Sprite Uniform Buffer"),
+            size: std::mem::size_of::<SpriteUniform>() as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let uniform_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::VERTEX,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            }],
+            label: Some("sprite_uniform_bind_group_layout"),
+        });
+        let uniform_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: &uniform_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: uniform_buffer.as_entire_binding(),
+            }],
+            label: Some("sprite_uniform_bind_group"),
+        });
+
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("Sprite Sampler"),
+            mag_filter: wgpu::FilterMode::Nearest,
+            min_filter: wgpu::FilterMode::Nearest,
+            ..Default::default()
+        });
+        let texture_layout = Texture::texture_bind_group_layout(device);
+        let texture_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: &texture_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(texture),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(&sampler),
+                },
+            ],
+            label: Some("sprite_texture_bind_group"),
+        });
+
+        let shader = ShaderLibrary::new()
+            .create_shader_module(
+                device,
+                "Sprite Shader",
+                include_str!("../shaders/sprite.wgsl"),
+            )
+            .expect("built-in sprite shader");
+        let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Sprite Pipeline Layout"),
+            bind_group_layouts: &[&uniform_layout, &texture_layout],
+            push_constant_ranges: &[],
+        });
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Sprite Pipeline"),
+            layout: Some(&layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                compilation_options: Default::default(),
+                entry_point: Some("vs_main"),
+                buffers: &[SpriteRaw::buffer_layout_desc()],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                compilation_options: Default::default(),
+                entry_point: Some("fs_main"),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: color_format,
+                    blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            // 翻转的 uv 与 y 向下的投影都会改变三角形朝向，不做背面剔除
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+            cache: None,
+        });
+
+        Self {
+            sprites: Vec::new(),
+            dirty: false,
+            uploaded: 0,
+            sprite_buffer: Self::create_sprite_buffer(device, Self::INITIAL_CAPACITY),
+            sprite_capacity: Self::INITIAL_CAPACITY,
+            uniform_buffer,
+            uniform_bind_group,
+            texture_bind_group,
+            pipeline,
+        }
+    }
+
+    fn create_sprite_buffer(device: &Device, capacity: usize) -> Buffer {
+        device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Sprite Instance Buffer"),
+            size: (std::mem::size_of::<SpriteRaw>() * capacity) as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        })
+    }
+
+    /// 移除所有精灵
+    pub fn clear(&mut self) {
+        self.sprites.clear();
+        self.dirty = true;
+    }
+
+    pub fn push(&mut self, sprite: &Sprite) {
+        self.sprites.push(sprite.into());
+        self.dirty = true;
+    }
+
+    pub fn push_nine_slice(&mut self, panel: &NineSlice) {
+        self.sprites
+            .extend(panel.sprites().map(|sprite| SpriteRaw::from(&sprite)));
+        self.dirty = true;
+    }
+
+    pub fn sprite_count(&self) -> usize {
+        self.sprites.len()
+    }
+
+    /// 上传投影矩阵与自上次更新以来修改过的精灵；缓冲不足时重新创建
+    pub fn update(&mut self, device: &Device, queue: &Queue, view_proj: glam::Mat4) {
+        SpriteUniform {
+            view_proj: view_proj.to_cols_array_2d(),
+        }
+        .write_to(queue, &self.uniform_buffer);
+
+        if !self.dirty {
+            return;
+        }
+        if self.sprites.len() > self.sprite_capacity {
+            self.sprite_capacity = self.sprites.len().next_power_of_two();
+            self.sprite_buffer = Self::create_sprite_buffer(device, self.sprite_capacity);
+        }
+        queue.write_buffer(&self.sprite_buffer, 0, bytemuck::cast_slice(&self.sprites));
+        self.uploaded = self.sprites.len() as u32;
+        self.dirty = false;
+    }
+
+    pub fn draw(&self, render_pass: &mut wgpu::RenderPass) {
+        if self.uploaded == 0 {
+            return;
+        }
+        render_pass.set_pipeline(&self.pipeline);
+        render_pass.set_bind_group(0, &self.uniform_bind_group, &[]);
+        render_pass.set_bind_group(1, &self.texture_bind_group, &[]);
+        render_pass.set_vertex_buffer(0, self.sprite_buffer.slice(..));
+        render_pass.draw(0..6, 0..self.uploaded);
+    }
+}
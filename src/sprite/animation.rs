@@ -0,0 +1,294 @@
+use std::collections::HashMap;
+
+use anyhow::{bail, ensure, Context};
+use serde::Deserialize;
+
+use super::UvRect;
+use crate::app::FrameTime;
+
+/// 按网格排列的精灵图集，帧从左上角开始按行编号
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct SpriteSheet {
+    pub columns: u32,
+    pub rows: u32,
+}
+
+impl SpriteSheet {
+    pub fn frame_count(&self) -> u32 {
+        self.columns * self.rows
+    }
+
+    /// 第 `frame` 帧在纹理中的 uv 范围
+    pub fn frame_uv(&self, frame: u32) -> UvRect {
+        let cell = glam::vec2(1.0 / self.columns as f32, 1.0 / self.rows as f32);
+        let min = glam::vec2((frame % self.columns) as f32, (frame / self.columns) as f32) * cell;
+        UvRect {
+            min,
+            max: min + cell,
+        }
+    }
+}
+
+/// 播放到最后一帧之后的行为
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Deserialize)]
+pub enum LoopMode {
+    /// 停在最后一帧
+    Once,
+    #[default]
+    Loop,
+    /// 到达两端后反向播放
+    PingPong,
+}
+
+/// 一段帧动画，`durations[i]` 为 `frames[i]` 的持续时间，单位秒
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpriteAnimation {
+    pub frames: Vec<u32>,
+    pub durations: Vec<f32>,
+    pub mode: LoopMode,
+}
+
+impl SpriteAnimation {
+    /// 每帧持续时间相同的动画
+    pub fn uniform(frames: Vec<u32>, frame_duration: f32, mode: LoopMode) -> Self {
+        let durations = vec![frame_duration; frames.len()];
+        Self {
+            frames,
+            durations,
+            mode,
+        }
+    }
+
+    /// 播放一遍的时长，乒乓模式中不计入往回的部分
+    pub fn duration(&self) -> f32 {
+        self.durations.iter().sum()
+    }
+
+    fn validate(&self) -> anyhow::Result<()> {
+        ensure!(!self.frames.is_empty(), "animation has no frames");
+        ensure!(
+            self.durations.len() == self.frames.len(),
+            "animation has {} frames but {} durations",
+            self.frames.len(),
+            self.durations.len()
+        );
+        ensure!(
+            self.durations.iter().all(|d| *d > 0.0),
+            "frame durations must be positive"
+        );
+        Ok(())
+    }
+}
+
+/// 精灵图集的描述文件，格式为 RON：
+///
+/// ```ron
+/// (
+///     columns: 8,
+///     rows: 4,
+///     animations: {
+///         // range 为首尾帧（包含），frame_duration 默认 0.1 秒，mode 默认 Loop
+///         "walk": (range: (8, 15), frame_duration: 0.08),
+///         "idle": (frames: [0, 1, 2, 1], mode: PingPong),
+///         // durations 逐帧指定持续时间，会覆盖 frame_duration
+///         "attack": (frames: [16, 17, 18], durations: [0.05, 0.05, 0.3], mode: Once),
+///     },
+/// )
+/// ```
+#[derive(Debug, Clone)]
+pub struct SpriteSheetDescriptor {
+    pub sheet: SpriteSheet,
+    pub animations: HashMap<String, SpriteAnimation>,
+}
+
+/// 描述文件中的一段动画，字段含义见 [`SpriteSheetDescriptor`]
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename = "SpriteAnimation")]
+struct AnimationDescriptor {
+    frames: Option<Vec<u32>>,
+    range: Option<(u32, u32)>,
+    frame_duration: Option<f32>,
+    durations: Option<Vec<f32>>,
+    #[serde(default)]
+    mode: LoopMode,
+}
+
+impl AnimationDescriptor {
+    const DEFAULT_FRAME_DURATION: f32 = 0.1;
+
+    fn build(self) -> anyhow::Result<SpriteAnimation> {
+        let frames = match (self.frames, self.range) {
+            (Some(frames), None) => frames,
+            (None, Some((first, last))) => {
+                ensure!(first <= last, "range ({first}, {last}) is empty");
+                (first..=last).collect()
+            }
+            (Some(_), Some(_)) => bail!("`frames` and `range` are mutually exclusive"),
+            (None, None) => bail!("missing `frames` or `range`"),
+        };
+        let frame_duration = self.frame_duration.unwrap_or(Self::DEFAULT_FRAME_DURATION);
+        let mut animation = SpriteAnimation::uniform(frames, frame_duration, self.mode);
+        if let Some(durations) = self.durations {
+            animation.durations = durations;
+        }
+        animation.validate()?;
+        Ok(animation)
+    }
+}
+
+/// 描述文件的顶层结构
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename = "SpriteSheet")]
+struct SheetFile {
+    columns: u32,
+    rows: u32,
+    #[serde(default)]
+    animations: HashMap<String, AnimationDescriptor>,
+}
+
+impl SpriteSheetDescriptor {
+    /// 可选字段直接写值即可，不需要 `Some(...)`
+    pub fn from_ron(source: &str) -> anyhow::Result<Self> {
+        let file: SheetFile = ron::Options::default()
+            .with_default_extension(ron::extensions::Extensions::IMPLICIT_SOME)
+            .from_str(source)?;
+        let sheet = SpriteSheet {
+            columns: file.columns,
+            rows: file.rows,
+        };
+        ensure!(
+            sheet.frame_count() > 0,
+            "sprite sheet must have at least one column and one row"
+        );
+
+        let mut animations = HashMap::new();
+        for (name, descriptor) in file.animations {
+            let animation = descriptor
+                .build()
+                .and_then(|animation| {
+                    if let Some(frame) =
+                        animation.frames.iter().find(|f| **f >= sheet.frame_count())
+                    {
+                        bail!(
+                            "frame {frame} is outside the {}x{} sheet",
+                            sheet.columns,
+                            sheet.rows
+                        );
+                    }
+                    Ok(animation)
+                })
+                .with_context(|| format!("invalid animation `{name}`"))?;
+            animations.insert(name, animation);
+        }
+        Ok(Self { sheet, animations })
+    }
+
+    pub fn animation(&self, name: &str) -> Option<&SpriteAnimation> {
+        self.animations.get(name)
+    }
+}
+
+/// 播放一段 [`SpriteAnimation`]，由 [`WindowApp::update`](crate::app::WindowApp::update)
+/// 传入的 [`FrameTime`] 推进，配合固定步长时各个精灵的动画保持同步
+#[derive(Debug, Clone)]
+pub struct AnimationPlayer {
+    animation: SpriteAnimation,
+    /// 播放速度倍率
+    pub speed: f32,
+    /// 当前帧在 `animation.frames` 中的下标
+    index: usize,
+    /// 当前帧已经播放的时间
+    time: f32,
+    /// 乒乓模式中是否正在反向播放
+    reverse: bool,
+    finished: bool,
+}
+
+impl AnimationPlayer {
+    pub fn new(animation: SpriteAnimation) -> Self {
+        Self {
+            animation,
+            speed: 1.0,
+            index: 0,
+            time: 0.0,
+            reverse: false,
+            finished: false,
+        }
+    }
+
+    /// 从第一帧开始播放另一段动画
+    pub fn play(&mut self, animation: SpriteAnimation) {
+        *self = Self {
+            speed: self.speed,
+            ..Self::new(animation)
+        };
+    }
+
+    pub fn restart(&mut self) {
+        self.index = 0;
+        self.time = 0.0;
+        self.reverse = false;
+        self.finished = false;
+    }
+
+    pub fn animation(&self) -> &SpriteAnimation {
+        &self.animation
+    }
+
+    /// 当前显示的图集帧
+    pub fn frame(&self) -> u32 {
+        self.animation.frames[self.index]
+    }
+
+    /// 单次播放的动画停在最后一帧后返回 `true`
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    pub fn update(&mut self, time: FrameTime) {
+        self.advance(time.delta_secs());
+    }
+
+    pub fn advance(&mut self, dt: f32) {
+        if self.finished {
+            return;
+        }
+        self.time += dt * self.speed.max(0.0);
+        while self.time >= self.animation.durations[self.index] {
+            self.time -= self.animation.durations[self.index];
+            self.step();
+            if self.finished {
+                self.time = 0.0;
+                return;
+            }
+        }
+    }
+
+    fn step(&mut self) {
+        let last = self.animation.frames.len() - 1;
+        match self.animation.mode {
+            LoopMode::Once if self.index == last => self.finished = true,
+            LoopMode::Once => self.index += 1,
+            LoopMode::Loop => {
+                self.index = if self.index == last {
+                    0
+                } else {
+                    self.index + 1
+                }
+            }
+            LoopMode::PingPong if last == 0 => {}
+            LoopMode::PingPong => {
+                if self.index == 0 {
+                    self.reverse = false;
+                } else if self.index == last {
+                    self.reverse = true;
+                }
+                if self.reverse {
+                    self.index -= 1;
+                } else {
+                    self.index += 1;
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,141 @@
+use std::{collections::VecDeque, time::Duration};
+
+/// [`FrameStats`] 定期输出统计结果的方式
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum StatsReporter {
+    /// 只收集，不输出
+    #[default]
+    None,
+    /// 追加在窗口标题之后
+    WindowTitle,
+    /// 打印到标准输出
+    Log,
+}
+
+/// 最近若干帧的帧时间统计：FPS、平均值与 95/99 百分位
+#[derive(Debug, Clone)]
+pub struct FrameStats {
+    samples: VecDeque<Duration>,
+    capacity: usize,
+    pub reporter: StatsReporter,
+    /// 两次输出之间的间隔
+    pub report_interval: Duration,
+    since_report: Duration,
+}
+
+impl Default for FrameStats {
+    fn default() -> Self {
+        Self::new(240)
+    }
+}
+
+impl FrameStats {
+    /// 保留最近 `capacity` 帧
+    pub fn new(capacity: usize) -> Self {
+        assert!(capacity > 0, "frame stats capacity must be positive");
+        Self {
+            samples: VecDeque::with_capacity(capacity),
+            capacity,
+            reporter: StatsReporter::None,
+            report_interval: Duration::from_millis(500),
+            since_report: Duration::ZERO,
+        }
+    }
+
+    pub fn with_reporter(mut self, reporter: StatsReporter) -> Self {
+        self.reporter = reporter;
+        self
+    }
+
+    /// 记录一帧的时长，零时长（例如第一帧）被忽略；到达输出间隔时返回 `true`
+    pub fn record(&mut self, frame_time: Duration) -> bool {
+        if frame_time.is_zero() {
+            return false;
+        }
+        if self.samples.len() == self.capacity {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(frame_time);
+
+        self.since_report += frame_time;
+        if self.since_report < self.report_interval {
+            return false;
+        }
+        self.since_report = Duration::ZERO;
+        true
+    }
+
+    pub fn clear(&mut self) {
+        self.samples.clear();
+        self.since_report = Duration::ZERO;
+    }
+
+    pub fn sample_count(&self) -> usize {
+        self.samples.len()
+    }
+
+    pub fn average(&self) -> Duration {
+        if self.samples.is_empty() {
+            return Duration::ZERO;
+        }
+        self.samples.iter().sum::<Duration>() / self.samples.len() as u32
+    }
+
+    /// 按平均帧时间计算的 FPS
+    pub fn fps(&self) -> f64 {
+        let average = self.average().as_secs_f64();
+        if average > 0.0 {
+            1.0 / average
+        } else {
+            0.0
+        }
+    }
+
+    /// `p` 百分位的帧时间，`p` 取 0 到 100
+    pub fn percentile(&self, p: f64) -> Duration {
+        if self.samples.is_empty() {
+            return Duration::ZERO;
+        }
+        let mut sorted = self.samples.iter().copied().collect::<Vec<_>>();
+        sorted.sort_unstable();
+        // 最近秩法：不小于 p% 样本的最小值
+        let rank = (p.clamp(0.0, 100.0) / 100.0 * sorted.len() as f64).ceil() as usize;
+        sorted[rank.saturating_sub(1)]
+    }
+
+    pub fn p95(&self) -> Duration {
+        self.percentile(95.0)
+    }
+
+    pub fn p99(&self) -> Duration {
+        self.percentile(99.0)
+    }
+
+    /// 帧时间的标准差，越小帧间隔越均匀
+    pub fn jitter(&self) -> Duration {
+        if self.samples.is_empty() {
+            return Duration::ZERO;
+        }
+        let average = self.average().as_secs_f64();
+        let variance = self
+            .samples
+            .iter()
+            .map(|d| (d.as_secs_f64() - average).powi(2))
+            .sum::<f64>()
+            / self.samples.len() as f64;
+        Duration::from_secs_f64(variance.sqrt())
+    }
+
+    /// 例如 `60.0 fps | avg 16.67 ms | p95 17.10 ms | p99 18.02 ms | jitter 0.21 ms`
+    pub fn summary(&self) -> String {
+        let ms = |d: Duration| d.as_secs_f64() * 1000.0;
+        format!(
+            "{:.1} fps | avg {:.2} ms | p95 {:.2} ms | p99 {:.2} ms | jitter {:.2} ms",
+            self.fps(),
+            ms(self.average()),
+            ms(self.p95()),
+            ms(self.p99()),
+            ms(self.jitter())
+        )
+    }
+}
@@ -77,33 +77,102 @@ impl Texture {
         })
     }
 
+    /// [`Texture::texture_bind_group_layout`] 的条目：binding 0 为纹理，binding 1 为采样器
+    pub fn texture_layout_entries() -> [wgpu::BindGroupLayoutEntry; 2] {
+        [
+            wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Texture {
+                    multisampled: false,
+                    view_dimension: wgpu::TextureViewDimension::D2,
+                    sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                },
+                count: None,
+            },
+            wgpu::BindGroupLayoutEntry {
+                binding: 1,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                // This should match the filterable field of the
+                // corresponding Texture entry above.
+                ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                count: None,
+            },
+        ]
+    }
+
     pub fn texture_bind_group_layout(device: &Device) -> BindGroupLayout {
         device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
-            entries: &[
-                wgpu::BindGroupLayoutEntry {
-                    binding: 0,
-                    visibility: wgpu::ShaderStages::FRAGMENT,
-                    ty: wgpu::BindingType::Texture {
-                        multisampled: false,
-                        view_dimension: wgpu::TextureViewDimension::D2,
-                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
-                    },
-                    count: None,
-                },
-                wgpu::BindGroupLayoutEntry {
-                    binding: 1,
-                    visibility: wgpu::ShaderStages::FRAGMENT,
-                    // This should match the filterable field of the
-                    // corresponding Texture entry above.
-                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
-                    count: None,
-                },
-            ],
+            entries: &Self::texture_layout_entries(),
             label: Some("texture_bind_group_layout"),
         })
     }
 }
 
+/// 深度缓冲中近处与远处的取值方式，由相机的投影、深度纹理与管线的深度比较共同遵守
+///
+/// 反向深度把近平面映射到 1、远平面映射到 0，浮点数在 0 附近的精度正好抵消透视除法造成的
+/// 精度损失，远处的深度冲突大幅减少，并且允许无限远的远平面。内置的后处理在线性化深度时
+/// 假定使用 `Standard`。
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum DepthConvention {
+    /// 近平面为 0、远平面为 1，深度越小越近
+    #[default]
+    Standard,
+    /// 近平面为 1、远平面为 0，深度越大越近
+    Reversed,
+}
+
+impl DepthConvention {
+    /// 近平面处的深度
+    pub fn near_depth(self) -> f32 {
+        match self {
+            DepthConvention::Standard => 0.0,
+            DepthConvention::Reversed => 1.0,
+        }
+    }
+
+    /// 远平面处的深度，也是深度附件的清除值
+    pub fn far_depth(self) -> f32 {
+        1.0 - self.near_depth()
+    }
+
+    /// 较近的片元通过测试的比较函数
+    pub fn compare(self) -> wgpu::CompareFunction {
+        match self {
+            DepthConvention::Standard => wgpu::CompareFunction::Less,
+            DepthConvention::Reversed => wgpu::CompareFunction::Greater,
+        }
+    }
+
+    /// 与 [`DepthConvention::compare`] 相同，但深度相等时也通过，例如绘制在远平面上的天空
+    pub fn compare_equal(self) -> wgpu::CompareFunction {
+        match self {
+            DepthConvention::Standard => wgpu::CompareFunction::LessEqual,
+            DepthConvention::Reversed => wgpu::CompareFunction::GreaterEqual,
+        }
+    }
+
+    /// 使用 [`Texture::DEPTH_FORMAT`] 的深度状态
+    pub fn depth_stencil_state(self, depth_write_enabled: bool) -> wgpu::DepthStencilState {
+        wgpu::DepthStencilState {
+            format: Texture::DEPTH_FORMAT,
+            depth_write_enabled,
+            depth_compare: self.compare(),
+            stencil: wgpu::StencilState::default(),
+            bias: wgpu::DepthBiasState::default(),
+        }
+    }
+
+    /// 渲染通道开始时把深度附件清为远平面并写回
+    pub fn clear_ops(self) -> wgpu::Operations<f32> {
+        wgpu::Operations {
+            load: wgpu::LoadOp::Clear(self.far_depth()),
+            store: wgpu::StoreOp::Store,
+        }
+    }
+}
+
 impl Texture {
     pub const DEPTH_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Depth32Float; // 1.
 
@@ -111,6 +180,16 @@ impl Texture {
         device: &wgpu::Device,
         config: &wgpu::SurfaceConfiguration,
         label: &str,
+    ) -> Self {
+        Self::create_depth_texture_with(device, config, label, DepthConvention::Standard)
+    }
+
+    /// 与 [`Texture::create_depth_texture`] 相同，比较采样器按 `convention` 判断远近
+    pub fn create_depth_texture_with(
+        device: &wgpu::Device,
+        config: &wgpu::SurfaceConfiguration,
+        label: &str,
+        convention: DepthConvention,
     ) -> Self {
         let size = wgpu::Extent3d {
             // 2.
@@ -140,7 +219,7 @@ impl Texture {
             mag_filter: wgpu::FilterMode::Linear,
             min_filter: wgpu::FilterMode::Linear,
             mipmap_filter: wgpu::FilterMode::Nearest,
-            compare: Some(wgpu::CompareFunction::LessEqual), // 5.
+            compare: Some(convention.compare_equal()), // 5.
             lod_min_clamp: 0.0,
             lod_max_clamp: 200.0,
             ..Default::default()
@@ -152,4 +231,147 @@ impl Texture {
             sampler,
         }
     }
+
+    /// 创建一个既可作为渲染附件、又可在着色器中采样的颜色纹理，大小与格式跟随 surface
+    pub fn create_render_target(
+        device: &wgpu::Device,
+        config: &wgpu::SurfaceConfiguration,
+        label: &str,
+    ) -> Self {
+        Self::create_color_target(device, config, config.format, label)
+    }
+
+    /// 与 [`Texture::create_render_target`] 相同，但使用指定的格式，例如 G-buffer 中的法线纹理
+    pub fn create_color_target(
+        device: &wgpu::Device,
+        config: &wgpu::SurfaceConfiguration,
+        format: wgpu::TextureFormat,
+        label: &str,
+    ) -> Self {
+        let size = wgpu::Extent3d {
+            width: config.width,
+            height: config.height,
+            depth_or_array_layers: 1,
+        };
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some(label),
+            size,
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+            view_formats: &[],
+        });
+
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            address_mode_w: wgpu::AddressMode::ClampToEdge,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            mipmap_filter: wgpu::FilterMode::Nearest,
+            ..Default::default()
+        });
+
+        Self {
+            texture,
+            view,
+            sampler,
+        }
+    }
+}
+
+/// 把二维 RGBA8 或 BGRA8 纹理（需带 `COPY_SRC` 用途）的第 0 级读回 CPU，结果统一为 RGBA 顺序
+///
+/// 会阻塞等待 GPU 完成之前提交的所有工作，只适合截图、缩略图这类偶发的读取。
+pub fn read_texture_rgba8(
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    texture: &wgpu::Texture,
+) -> anyhow::Result<image::RgbaImage> {
+    let swizzle = rgba8_swizzle(texture.format())?;
+    let (width, height) = (texture.width(), texture.height());
+    let padded = padded_bytes_per_row(width);
+
+    let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("Readback Buffer"),
+        size: (padded * height) as wgpu::BufferAddress,
+        usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+        mapped_at_creation: false,
+    });
+    let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+        label: Some("Readback Encoder"),
+    });
+    copy_texture_to_readback(&mut encoder, texture, &buffer);
+    queue.submit(Some(encoder.finish()));
+
+    let (sender, receiver) = std::sync::mpsc::channel();
+    let slice = buffer.slice(..);
+    slice.map_async(wgpu::MapMode::Read, move |result| {
+        let _ = sender.send(result);
+    });
+    device.poll(wgpu::Maintain::Wait);
+    receiver.recv()??;
+
+    let pixels = unpad_rgba8(&slice.get_mapped_range(), width, swizzle);
+    buffer.unmap();
+    image::RgbaImage::from_raw(width, height, pixels)
+        .ok_or_else(|| anyhow::anyhow!("readback buffer size mismatch"))
+}
+
+/// 读回后是否需要交换 R 与 B 通道，不支持的格式报错
+pub(crate) fn rgba8_swizzle(format: wgpu::TextureFormat) -> anyhow::Result<bool> {
+    use wgpu::TextureFormat as F;
+    match format {
+        F::Rgba8Unorm | F::Rgba8UnormSrgb => Ok(false),
+        F::Bgra8Unorm | F::Bgra8UnormSrgb => Ok(true),
+        other => anyhow::bail!("cannot read back texture format {other:?}"),
+    }
+}
+
+/// 缓冲中每行的字节数必须对齐到 256
+pub(crate) fn padded_bytes_per_row(width: u32) -> u32 {
+    (width * 4).div_ceil(wgpu::COPY_BYTES_PER_ROW_ALIGNMENT) * wgpu::COPY_BYTES_PER_ROW_ALIGNMENT
+}
+
+/// 把纹理第 0 级按 [`padded_bytes_per_row`] 的行距复制到缓冲
+pub(crate) fn copy_texture_to_readback(
+    encoder: &mut wgpu::CommandEncoder,
+    texture: &wgpu::Texture,
+    buffer: &wgpu::Buffer,
+) {
+    encoder.copy_texture_to_buffer(
+        texture.as_image_copy(),
+        wgpu::TexelCopyBufferInfo {
+            buffer,
+            layout: wgpu::TexelCopyBufferLayout {
+                offset: 0,
+                bytes_per_row: Some(padded_bytes_per_row(texture.width())),
+                rows_per_image: Some(texture.height()),
+            },
+        },
+        wgpu::Extent3d {
+            width: texture.width(),
+            height: texture.height(),
+            depth_or_array_layers: 1,
+        },
+    );
+}
+
+/// 去掉每行的对齐填充，需要时交换为 RGBA 顺序
+pub(crate) fn unpad_rgba8(data: &[u8], width: u32, swizzle: bool) -> Vec<u8> {
+    let unpadded = (width * 4) as usize;
+    let padded = padded_bytes_per_row(width) as usize;
+    let mut pixels = Vec::with_capacity(data.len() / padded * unpadded);
+    for row in data.chunks_exact(padded) {
+        pixels.extend_from_slice(&row[..unpadded]);
+    }
+    if swizzle {
+        for pixel in pixels.chunks_exact_mut(4) {
+            pixel.swap(0, 2);
+        }
+    }
+    pixels
 }
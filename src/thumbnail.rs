@@ -0,0 +1,332 @@
+use std::collections::HashMap;
+
+use wgpu::{util::DeviceExt, Device, Queue, RenderPipeline};
+
+use crate::{
+    model::{Material, MeshModel, ModelVertex, RenderVertex, TextureTransformUniform},
+    phase::RenderPhase,
+    shader::{alpha_test_constants, ShaderLibrary},
+    texture::{read_texture_rgba8, Texture},
+    uniform::GpuUniform,
+};
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone, GpuUniform)]
+struct ThumbnailUniform {
+    view_proj: [[f32; 4]; 4],
+    light_dir: [f32; 4],
+}
+
+/// 离屏渲染模型缩略图，不需要窗口或 surface
+///
+/// 模型须以 [`ModelVertex`] 加载；相机按 [`MeshModel::bounding_sphere`] 取景，
+/// 使整个模型落在画面中央，并以固定的方向光做简单的漫反射着色。镂空材质按
+/// [`Material::alpha_cutoff`] 丢弃片元，只有双面材质绘制背面。
+pub struct ThumbnailRenderer {
+    /// 背景色（线性空间）
+    pub background: wgpu::Color,
+    /// 相机看向模型的方向，不需要归一化
+    pub view_dir: glam::Vec3,
+    /// 指向光源的方向，不需要归一化
+    pub light_dir: glam::Vec3,
+    uniform_buffer: wgpu::Buffer,
+    uniform_bind_group: wgpu::BindGroup,
+    transform_layout: wgpu::BindGroupLayout,
+    layout: wgpu::PipelineLayout,
+    /// 单面不透明管线，镂空与双面材质的变体在渲染时创建
+    pipeline: RenderPipeline,
+}
+
+impl ThumbnailRenderer {
+    const FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba8UnormSrgb;
+    const FOVY: f32 = std::f32::consts::FRAC_PI_6;
+
+    pub fn new(device: &Device) -> Self {
+        let uniform_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Thumbnail Uniform Buffer"),
+            size: std::mem::size_of::<ThumbnailUniform>() as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let uniform_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            }],
+            label: Some("thumbnail_uniform_bind_group_layout"),
+        });
+        let uniform_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: &uniform_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: uniform_buffer.as_entire_binding(),
+            }],
+            label: Some("thumbnail_uniform_bind_group"),
+        });
+        // 与加载模型时传入的材质布局条目相同，两者兼容
+        let texture_layout = Texture::texture_bind_group_layout(device);
+        let transform_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            }],
+            label: Some("thumbnail_transform_bind_group_layout"),
+        });
+
+        let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Thumbnail Pipeline Layout"),
+            bind_group_layouts: &[&uniform_layout, &texture_layout, &transform_layout],
+            push_constant_ranges: &[],
+        });
+        let pipeline = Self::create_pipeline(device, &layout, 0.0, Some(wgpu::Face::Back));
+
+        Self {
+            background: wgpu::Color {
+                r: 0.2,
+                g: 0.2,
+                b: 0.2,
+                a: 1.0,
+            },
+            view_dir: glam::vec3(-1.0, -0.7, -1.2),
+            light_dir: glam::vec3(0.4, 1.0, 0.6),
+            uniform_buffer,
+            uniform_bind_group,
+            transform_layout,
+            layout,
+            pipeline,
+        }
+    }
+
+    /// `cutoff` 为 0 时是不透明管线，否则是丢弃低于该阈值片元的镂空变体
+    ///
+    /// 每个变体使用单独的着色器模块，见 [`alpha_test_constants`]。
+    fn create_pipeline(
+        device: &Device,
+        layout: &wgpu::PipelineLayout,
+        cutoff: f32,
+        cull_mode: Option<wgpu::Face>,
+    ) -> RenderPipeline {
+        let shader = &ShaderLibrary::new()
+            .create_shader_module(
+                device,
+                "Thumbnail Shader",
+                include_str!("../shaders/thumbnail.wgsl"),
+            )
+            .expect("built-in thumbnail shader");
+        let constants = alpha_test_constants(cutoff);
+        let compilation_options = wgpu::PipelineCompilationOptions {
+            constants: &constants,
+            ..Default::default()
+        };
+        device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Thumbnail Pipeline"),
+            layout: Some(layout),
+            vertex: wgpu::VertexState {
+                module: shader,
+                compilation_options: compilation_options.clone(),
+                entry_point: Some("vs_main"),
+                buffers: &[ModelVertex::buffer_layout_desc()],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: shader,
+                compilation_options,
+                entry_point: Some("fs_main"),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: Self::FORMAT,
+                    blend: None,
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            primitive: wgpu::PrimitiveState {
+                cull_mode,
+                ..Default::default()
+            },
+            depth_stencil: Some(wgpu::DepthStencilState {
+                format: Texture::DEPTH_FORMAT,
+                depth_write_enabled: true,
+                depth_compare: wgpu::CompareFunction::Less,
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState::default(),
+            }),
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+            cache: None,
+        })
+    }
+
+    /// 使包围球恰好落在视锥内的 view-projection
+    fn view_proj(&self, model: &MeshModel) -> glam::Mat4 {
+        let (center, radius) = model.bounding_sphere;
+        // 空模型或单点模型也给出合法的投影
+        let radius = radius.max(1e-3);
+        let distance = radius / (Self::FOVY * 0.5).sin() * 1.05;
+        let dir = self.view_dir.try_normalize().unwrap_or(glam::Vec3::NEG_Z);
+        let up = if dir.cross(glam::Vec3::Y).length_squared() < 1e-6 {
+            glam::Vec3::Z
+        } else {
+            glam::Vec3::Y
+        };
+        let eye = center - dir * distance;
+        let view = glam::Mat4::look_at_rh(eye, center, up);
+        let near = (distance - radius * 1.1).max(radius * 0.01);
+        let far = distance + radius * 1.1;
+        glam::Mat4::perspective_rh(Self::FOVY, 1.0, near, far) * view
+    }
+
+    /// 渲染 `size`×`size` 像素的缩略图并读回 CPU，会阻塞到 GPU 完成
+    pub fn render(
+        &self,
+        device: &Device,
+        queue: &Queue,
+        model: &MeshModel,
+        size: u32,
+    ) -> anyhow::Result<image::RgbaImage> {
+        anyhow::ensure!(size > 0, "thumbnail size must be positive");
+
+        ThumbnailUniform {
+            view_proj: self.view_proj(model).to_cols_array_2d(),
+            light_dir: self
+                .light_dir
+                .try_normalize()
+                .unwrap_or(glam::Vec3::Y)
+                .extend(0.0)
+                .to_array(),
+        }
+        .write_to(queue, &self.uniform_buffer);
+
+        let extent = wgpu::Extent3d {
+            width: size,
+            height: size,
+            depth_or_array_layers: 1,
+        };
+        let color = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Thumbnail Color"),
+            size: extent,
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: Self::FORMAT,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+            view_formats: &[],
+        });
+        let depth = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Thumbnail Depth"),
+            size: extent,
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: Texture::DEPTH_FORMAT,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+            view_formats: &[],
+        });
+        let color_view = color.create_view(&wgpu::TextureViewDescriptor::default());
+        let depth_view = depth.create_view(&wgpu::TextureViewDescriptor::default());
+
+        // 每种镂空阈值与剔除方式的组合对应一个管线变体
+        let variant_key = |material: &Material| {
+            let cutoff = match material.phase {
+                RenderPhase::AlphaTest => material.alpha_cutoff,
+                _ => 0.0,
+            };
+            (cutoff.to_bits(), material.cull_mode())
+        };
+        let default_key = (0.0f32.to_bits(), Some(wgpu::Face::Back));
+        let mut variants = HashMap::new();
+        for material in &model.materials {
+            let key = variant_key(material);
+            if key != default_key {
+                variants.entry(key).or_insert_with(|| {
+                    Self::create_pipeline(device, &self.layout, f32::from_bits(key.0), key.1)
+                });
+            }
+        }
+
+        // 每个材质的贴图坐标变换
+        let transforms = model
+            .materials
+            .iter()
+            .map(|material| {
+                let uniform = TextureTransformUniform::from(material.texture_transform);
+                let buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                    label: Some("Thumbnail Transform Buffer"),
+                    contents: bytemuck::bytes_of(&uniform),
+                    usage: wgpu::BufferUsages::UNIFORM,
+                });
+                device.create_bind_group(&wgpu::BindGroupDescriptor {
+                    layout: &self.transform_layout,
+                    entries: &[wgpu::BindGroupEntry {
+                        binding: 0,
+                        resource: buffer.as_entire_binding(),
+                    }],
+                    label: Some("thumbnail_transform_bind_group"),
+                })
+            })
+            .collect::<Vec<_>>();
+
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("Thumbnail Encoder"),
+        });
+        let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Thumbnail Pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: &color_view,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(self.background),
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                view: &depth_view,
+                depth_ops: Some(wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(1.0),
+                    store: wgpu::StoreOp::Discard,
+                }),
+                stencil_ops: None,
+            }),
+            ..Default::default()
+        });
+        pass.set_bind_group(0, &self.uniform_bind_group, &[]);
+        for mesh in &model.meshes {
+            // 没有材质的网格无法采样纹理，跳过
+            let Some(material) = model.materials.get(mesh.material) else {
+                continue;
+            };
+            let pipeline = variants
+                .get(&variant_key(material))
+                .unwrap_or(&self.pipeline);
+            pass.set_pipeline(pipeline);
+            pass.set_bind_group(1, &material.bind_group, &[]);
+            pass.set_bind_group(2, &transforms[mesh.material], &[]);
+            pass.set_vertex_buffer(0, mesh.vertex_buffer.slice(..));
+            pass.set_index_buffer(mesh.index_buffer.slice(..), wgpu::IndexFormat::Uint32);
+            pass.draw_indexed(0..mesh.num_elements, 0, 0..1);
+        }
+        drop(pass);
+        queue.submit(Some(encoder.finish()));
+
+        read_texture_rgba8(device, queue, &color)
+    }
+}
+
+/// 用默认设置渲染一张缩略图；需要批量生成时复用同一个 [`ThumbnailRenderer`]
+pub fn render_thumbnail(
+    device: &Device,
+    queue: &Queue,
+    model: &MeshModel,
+    size: u32,
+) -> anyhow::Result<image::RgbaImage> {
+    ThumbnailRenderer::new(device).render(device, queue, model, size)
+}
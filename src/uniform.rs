@@ -0,0 +1,55 @@
+use wgpu::{Buffer, Queue};
+
+#[doc(hidden)]
+pub use bytemuck;
+pub use wgpu_dance_derive::GpuUniform;
+
+/// 可以作为 std140 uniform 结构体字段的类型，`ALIGN` 为其在 std140 中的对齐
+///
+/// `[f32; 2]`、`[f32; 3]`、`[f32; 4]` 等按 WGSL 的向量处理；元素对齐为 16 的数组按数组处理。
+///
+/// # Safety
+/// 实现者的内存表示必须与 WGSL 中对应类型一致。
+pub unsafe trait Std140: bytemuck::Pod {
+    const ALIGN: usize;
+}
+
+/// std140 中数组元素的跨度必须是 16 的倍数，只有这些类型可以组成数组字段
+///
+/// # Safety
+/// 实现者的大小必须是 16 的倍数。
+pub unsafe trait Std140Array: Std140 {}
+
+macro_rules! impl_std140 {
+    ($align:literal: $($ty:ty),*) => {
+        $(unsafe impl Std140 for $ty {
+            const ALIGN: usize = $align;
+        })*
+    };
+}
+
+impl_std140!(4: f32, u32, i32);
+impl_std140!(8: [f32; 2], [u32; 2], [i32; 2]);
+impl_std140!(16: [f32; 3], [u32; 3], [i32; 3]);
+impl_std140!(16: [f32; 4], [u32; 4], [i32; 4]);
+
+unsafe impl Std140Array for [f32; 4] {}
+unsafe impl Std140Array for [u32; 4] {}
+unsafe impl Std140Array for [i32; 4] {}
+
+/// 元素跨度为 16 的数组，也包括以 `[[f32; 4]; 4]` 表示的 mat4x4
+unsafe impl<T: Std140Array, const N: usize> Std140 for [T; N] {
+    const ALIGN: usize = 16;
+}
+
+unsafe impl<T: Std140Array, const N: usize> Std140Array for [T; N] {}
+
+/// 由 `#[derive(GpuUniform)]` 实现，整体写入 uniform 缓冲
+pub trait GpuUniform: bytemuck::Pod {
+    /// 按声明顺序排列的字段名与字节偏移，用于和着色器中的结构体对照
+    const FIELDS: &'static [(&'static str, usize)];
+
+    fn write_to(&self, queue: &Queue, buffer: &Buffer) {
+        queue.write_buffer(buffer, 0, bytemuck::bytes_of(self));
+    }
+}
@@ -0,0 +1,164 @@
+use anyhow::{anyhow, bail, ensure, Context};
+use wgpu::{BindGroupLayoutEntry, BindingType, BufferBindingType};
+
+use crate::{shader::ShaderLibrary, uniform::GpuUniform};
+
+/// 展开 `#include` 后用 naga 解析并校验 WGSL，返回的模块可用于检查结构体布局与资源绑定
+pub fn parse_wgsl(library: &ShaderLibrary, source: &str) -> anyhow::Result<naga::Module> {
+    let source = library.preprocess(source)?;
+    Ok(parse_preprocessed(&source)?.0)
+}
+
+/// 解析已经展开 `#include` 的 WGSL，同时返回校验得到的模块信息
+pub(crate) fn parse_preprocessed(
+    source: &str,
+) -> anyhow::Result<(naga::Module, naga::valid::ModuleInfo)> {
+    let module = naga::front::wgsl::parse_str(source)
+        .map_err(|e| anyhow!("{}", e.emit_to_string(source)))?;
+    let info = naga::valid::Validator::new(
+        naga::valid::ValidationFlags::all(),
+        naga::valid::Capabilities::all(),
+    )
+    .validate(&module)
+    .map_err(|e| anyhow!("{}", e.emit_to_string(source)))?;
+    Ok((module, info))
+}
+
+/// 检查 Rust 端的 uniform 结构体与着色器中名为 `wgsl_struct` 的结构体布局一致
+///
+/// 比较总大小以及按顺序排列的各字段偏移；字段名只用于错误信息。
+pub fn check_uniform<T: GpuUniform>(
+    module: &naga::Module,
+    wgsl_struct: &str,
+) -> anyhow::Result<()> {
+    let rust_name = std::any::type_name::<T>();
+    let (members, span) = module
+        .types
+        .iter()
+        .find_map(|(_, ty)| match &ty.inner {
+            naga::TypeInner::Struct { members, span }
+                if ty.name.as_deref() == Some(wgsl_struct) =>
+            {
+                Some((members, *span))
+            }
+            _ => None,
+        })
+        .ok_or_else(|| anyhow!("struct `{wgsl_struct}` not found in shader"))?;
+
+    ensure!(
+        members.len() == T::FIELDS.len(),
+        "`{rust_name}` has {} fields but `{wgsl_struct}` has {}",
+        T::FIELDS.len(),
+        members.len()
+    );
+    for ((field, offset), member) in T::FIELDS.iter().zip(members) {
+        let member_name = member.name.as_deref().unwrap_or("?");
+        ensure!(
+            *offset == member.offset as usize,
+            "`{rust_name}::{field}` is at offset {offset} but `{wgsl_struct}.{member_name}` is at {}",
+            member.offset
+        );
+    }
+    ensure!(
+        std::mem::size_of::<T>() == span as usize,
+        "`{rust_name}` is {} bytes but `{wgsl_struct}` is {span}",
+        std::mem::size_of::<T>()
+    );
+    Ok(())
+}
+
+/// 着色器中类型为结构体 `wgsl_struct` 的资源所在的 group 与 binding
+pub fn find_binding(module: &naga::Module, wgsl_struct: &str) -> Option<naga::ResourceBinding> {
+    module.global_variables.iter().find_map(|(_, global)| {
+        let name = module.types[global.ty].name.as_deref();
+        (name == Some(wgsl_struct))
+            .then(|| global.binding.clone())
+            .flatten()
+    })
+}
+
+/// 检查着色器在 `group` 中声明的每个资源都能在 `entries` 中找到类型相符的条目
+///
+/// `entries` 可以比着色器用到的更多，这与 wgpu 创建管线时的规则一致。
+pub fn check_bind_group(
+    module: &naga::Module,
+    group: u32,
+    entries: &[BindGroupLayoutEntry],
+) -> anyhow::Result<()> {
+    for (_, global) in module.global_variables.iter() {
+        let Some(binding) = &global.binding else {
+            continue;
+        };
+        if binding.group != group {
+            continue;
+        }
+        let name = global.name.as_deref().unwrap_or("?");
+        let entry = entries
+            .iter()
+            .find(|entry| entry.binding == binding.binding)
+            .ok_or_else(|| {
+                anyhow!(
+                    "`{name}` is bound at @group({group}) @binding({}) but the layout has no such entry",
+                    binding.binding
+                )
+            })?;
+        check_binding_type(module, global, &entry.ty).with_context(|| {
+            format!("`{name}` at @group({group}) @binding({})", binding.binding)
+        })?;
+    }
+    Ok(())
+}
+
+fn check_binding_type(
+    module: &naga::Module,
+    global: &naga::GlobalVariable,
+    ty: &BindingType,
+) -> anyhow::Result<()> {
+    let inner = &module.types[global.ty].inner;
+    match (global.space, ty) {
+        (
+            naga::AddressSpace::Uniform,
+            BindingType::Buffer {
+                ty: BufferBindingType::Uniform,
+                ..
+            },
+        ) => Ok(()),
+        (
+            naga::AddressSpace::Storage { access },
+            BindingType::Buffer {
+                ty: BufferBindingType::Storage { read_only },
+                ..
+            },
+        ) => {
+            let writable = access.contains(naga::StorageAccess::STORE);
+            ensure!(
+                !(writable && *read_only),
+                "shader writes to a storage buffer that the layout declares read-only"
+            );
+            Ok(())
+        }
+        (naga::AddressSpace::Handle, _) => match (inner, ty) {
+            (naga::TypeInner::Sampler { comparison }, BindingType::Sampler(kind)) => {
+                ensure!(
+                    *comparison == (*kind == wgpu::SamplerBindingType::Comparison),
+                    "comparison sampler mismatch, layout is {kind:?}"
+                );
+                Ok(())
+            }
+            (
+                naga::TypeInner::Image { class, .. },
+                BindingType::Texture { .. } | BindingType::StorageTexture { .. },
+            ) => {
+                let storage = matches!(class, naga::ImageClass::Storage { .. });
+                let layout_storage = matches!(ty, BindingType::StorageTexture { .. });
+                ensure!(
+                    storage == layout_storage,
+                    "texture binding kind mismatch, layout is {ty:?}"
+                );
+                Ok(())
+            }
+            _ => bail!("shader declares {inner:?} but layout is {ty:?}"),
+        },
+        (space, _) => bail!("shader declares a {space:?} resource but layout is {ty:?}"),
+    }
+}
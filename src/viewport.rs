@@ -0,0 +1,304 @@
+use std::collections::HashSet;
+
+use anyhow::ensure;
+use wgpu::Queue;
+use winit::{
+    dpi::{PhysicalPosition, PhysicalSize},
+    event::{ElementState, MouseButton},
+    keyboard::PhysicalKey,
+};
+
+use crate::{app::KeyInput, camera::CameraBundle, replay::InputEvent};
+
+/// 渲染目标中的矩形区域，以像素为单位，原点在左上角
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub struct ViewportRect {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl ViewportRect {
+    pub fn new(x: u32, y: u32, width: u32, height: u32) -> Self {
+        Self {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    /// 覆盖整个 `size` 的区域
+    pub fn full(size: PhysicalSize<u32>) -> Self {
+        Self::new(0, 0, size.width, size.height)
+    }
+
+    pub fn size(&self) -> PhysicalSize<u32> {
+        PhysicalSize::new(self.width, self.height)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.width == 0 || self.height == 0
+    }
+
+    pub fn contains(&self, position: PhysicalPosition<f64>) -> bool {
+        let (x, y) = (position.x - self.x as f64, position.y - self.y as f64);
+        x >= 0.0 && y >= 0.0 && x < self.width as f64 && y < self.height as f64
+    }
+
+    /// 窗口坐标转换为区域内的坐标，配合 [`Camera::screen_ray`](crate::camera::Camera::screen_ray)
+    /// 时以 [`ViewportRect::size`] 作为视口大小
+    pub fn to_local(&self, position: PhysicalPosition<f64>) -> PhysicalPosition<f64> {
+        PhysicalPosition::new(position.x - self.x as f64, position.y - self.y as f64)
+    }
+
+    /// 把视口与裁剪矩形都限制在该区域，深度范围为 [0, 1]
+    pub fn set_viewport(&self, render_pass: &mut wgpu::RenderPass) {
+        render_pass.set_viewport(
+            self.x as f32,
+            self.y as f32,
+            self.width as f32,
+            self.height as f32,
+            0.0,
+            1.0,
+        );
+        render_pass.set_scissor_rect(self.x, self.y, self.width, self.height);
+    }
+}
+
+/// 一个相机与它在渲染目标中占据的区域
+///
+/// 每个视口的 [`CameraBundle`] 都有自己的 uniform 缓冲与绑定组，因此多个视口可以在同一个
+/// 渲染通道中依次绘制；用同一个 [`LayoutCache`](crate::layout::LayoutCache) 构建的相机共享绑定组布局，
+/// 可以使用同一条管线。
+#[derive(Debug)]
+pub struct Viewport {
+    rect: ViewportRect,
+    pub camera: CameraBundle,
+}
+
+impl Viewport {
+    /// 相机的宽高比随区域更新
+    pub fn new(rect: ViewportRect, mut camera: CameraBundle) -> Self {
+        if !rect.is_empty() {
+            camera.resize(rect.size());
+        }
+        Self { rect, camera }
+    }
+
+    pub fn rect(&self) -> ViewportRect {
+        self.rect
+    }
+
+    /// 修改区域并更新相机的宽高比，区域为空（例如窗口最小化）时保留原来的宽高比
+    pub fn set_rect(&mut self, rect: ViewportRect) {
+        self.rect = rect;
+        if !rect.is_empty() {
+            self.camera.resize(rect.size());
+        }
+    }
+
+    /// 设置视口与裁剪矩形，并把相机绑定到 `camera_group`；区域为空时返回 `false`，此时不应绘制
+    pub fn bind(&self, render_pass: &mut wgpu::RenderPass, camera_group: u32) -> bool {
+        if self.rect.is_empty() {
+            return false;
+        }
+        self.rect.set_viewport(render_pass);
+        render_pass.set_bind_group(camera_group, &self.camera.bind_group, &[]);
+        true
+    }
+}
+
+/// 分屏时视口的排列方向
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum SplitLayout {
+    /// 从左到右并排
+    #[default]
+    Horizontal,
+    /// 从上到下排列
+    Vertical,
+}
+
+impl SplitLayout {
+    /// 把 `size` 等分为 `count` 个区域，除不尽的像素分给靠后的区域
+    pub fn split(self, size: PhysicalSize<u32>, count: usize) -> Vec<ViewportRect> {
+        let count = count as u64;
+        let edge = |length: u32, i: u64| (length as u64 * i / count.max(1)) as u32;
+        (0..count)
+            .map(|i| match self {
+                SplitLayout::Horizontal => {
+                    let (start, end) = (edge(size.width, i), edge(size.width, i + 1));
+                    ViewportRect::new(start, 0, end - start, size.height)
+                }
+                SplitLayout::Vertical => {
+                    let (start, end) = (edge(size.height, i), edge(size.height, i + 1));
+                    ViewportRect::new(0, start, size.width, end - start)
+                }
+            })
+            .collect()
+    }
+}
+
+/// 在同一帧中用多个相机分屏渲染
+///
+/// 窗口大小变化时调用 [`SplitScreen::resize`] 重新划分区域，它会按各自区域设置相机的宽高比，
+/// 因此这些相机不应再从 [`WindowApp::cameras_mut`](crate::app::WindowApp::cameras_mut) 返回。
+/// 输入只交给光标所在或最近一次选中的视口。按住按键或鼠标按键时光标移入其他视口不会切换，
+/// 以免松开事件交给另一个相机，使原来的相机一直保持按下的状态。
+#[derive(Debug)]
+pub struct SplitScreen {
+    pub viewports: Vec<Viewport>,
+    layout: SplitLayout,
+    size: PhysicalSize<u32>,
+    active: usize,
+    /// 经 [`SplitScreen::process_event`] 交给当前视口、尚未松开的按键与鼠标按键
+    held_keys: HashSet<PhysicalKey>,
+    held_buttons: HashSet<MouseButton>,
+}
+
+impl SplitScreen {
+    pub fn new(
+        cameras: Vec<CameraBundle>,
+        layout: SplitLayout,
+        size: PhysicalSize<u32>,
+    ) -> anyhow::Result<Self> {
+        ensure!(!cameras.is_empty(), "split screen needs at least 1 camera");
+        let viewports = layout
+            .split(size, cameras.len())
+            .into_iter()
+            .zip(cameras)
+            .map(|(rect, camera)| Viewport::new(rect, camera))
+            .collect();
+        Ok(Self {
+            viewports,
+            layout,
+            size,
+            active: 0,
+            held_keys: HashSet::new(),
+            held_buttons: HashSet::new(),
+        })
+    }
+
+    pub fn layout(&self) -> SplitLayout {
+        self.layout
+    }
+
+    pub fn set_layout(&mut self, layout: SplitLayout) {
+        self.layout = layout;
+        self.resize(self.size);
+    }
+
+    pub fn resize(&mut self, size: PhysicalSize<u32>) {
+        self.size = size;
+        let rects = self.layout.split(size, self.viewports.len());
+        for (viewport, rect) in self.viewports.iter_mut().zip(rects) {
+            viewport.set_rect(rect);
+        }
+    }
+
+    /// 接收输入的视口下标
+    pub fn active(&self) -> usize {
+        self.active
+    }
+
+    /// 超出范围的下标被忽略；切换前向原来的视口发送仍按住的按键的松开事件
+    pub fn set_active(&mut self, index: usize) {
+        if index >= self.viewports.len() || index == self.active {
+            return;
+        }
+        let camera = &mut self.viewports[self.active].camera;
+        for physical_key in self.held_keys.drain() {
+            camera.process_event(&InputEvent::Key(KeyInput {
+                physical_key,
+                state: ElementState::Released,
+                repeat: false,
+            }));
+        }
+        for button in self.held_buttons.drain() {
+            camera.process_event(&InputEvent::MouseButton(ElementState::Released, button));
+        }
+        self.active = index;
+    }
+
+    /// 是否有交给当前视口的按键或鼠标按键仍未松开
+    pub fn is_input_held(&self) -> bool {
+        !self.held_keys.is_empty() || !self.held_buttons.is_empty()
+    }
+
+    pub fn active_viewport(&mut self) -> &mut Viewport {
+        &mut self.viewports[self.active]
+    }
+
+    /// 光标所在的视口下标
+    pub fn viewport_at(&self, position: PhysicalPosition<f64>) -> Option<usize> {
+        self.viewports
+            .iter()
+            .position(|viewport| viewport.rect.contains(position))
+    }
+
+    /// 把光标所在的视口设为接收输入的视口，用于 [`WindowApp::cursor_move`](crate::app::WindowApp::cursor_move)；
+    /// 仍有按键按住时保持当前视口
+    pub fn cursor_moved(&mut self, position: PhysicalPosition<f64>) {
+        if self.is_input_held() {
+            return;
+        }
+        if let Some(index) = self.viewport_at(position) {
+            self.active = index;
+        }
+    }
+
+    /// 把输入交给当前视口的相机，返回是否处理了该事件
+    pub fn process_event(&mut self, event: &InputEvent) -> bool {
+        match event {
+            InputEvent::Key(input) => match input.state {
+                ElementState::Pressed => {
+                    self.held_keys.insert(input.physical_key);
+                }
+                ElementState::Released => {
+                    self.held_keys.remove(&input.physical_key);
+                }
+            },
+            InputEvent::MouseButton(state, button) => match state {
+                ElementState::Pressed => {
+                    self.held_buttons.insert(*button);
+                }
+                ElementState::Released => {
+                    self.held_buttons.remove(button);
+                }
+            },
+            // 失去焦点后不会再收到松开事件
+            InputEvent::Focused(false) => {
+                self.held_keys.clear();
+                self.held_buttons.clear();
+            }
+            _ => {}
+        }
+        self.active_viewport().camera.process_event(event)
+    }
+
+    /// 更新所有相机，见 [`CameraBundle::update`]
+    pub fn update(&mut self, queue: &Queue, dt: f32) {
+        for viewport in &mut self.viewports {
+            viewport.camera.update(queue, dt);
+        }
+    }
+
+    /// 依次绑定每个非空视口并调用 `draw`，结束后把视口恢复为整个渲染目标
+    pub fn draw(
+        &self,
+        render_pass: &mut wgpu::RenderPass,
+        camera_group: u32,
+        mut draw: impl FnMut(&mut wgpu::RenderPass, &Viewport),
+    ) {
+        for viewport in &self.viewports {
+            if viewport.bind(render_pass, camera_group) {
+                draw(render_pass, viewport);
+            }
+        }
+        let full = ViewportRect::full(self.size);
+        if !full.is_empty() {
+            full.set_viewport(render_pass);
+        }
+    }
+}
@@ -0,0 +1,439 @@
+use bytemuck::{Pod, Zeroable};
+use wgpu::{util::DeviceExt, BindGroup, BindGroupLayout, Buffer, Device, Queue};
+
+use crate::{camera::Camera, shader::ShaderLibrary, texture::Texture};
+
+/// 单个 Gerstner 波
+#[derive(Debug, Copy, Clone)]
+pub struct GerstnerWave {
+    pub direction: glam::Vec2,
+    pub amplitude: f32,
+    pub wavelength: f32,
+    /// 0 为正弦波，1 为最尖锐的波峰
+    pub steepness: f32,
+    pub speed: f32,
+}
+
+#[derive(Debug, Clone)]
+pub struct WaterSettings {
+    /// 水面边长
+    pub size: f32,
+    /// 静止水面的高度
+    pub height: f32,
+    /// 每条边的顶点数
+    pub resolution: u32,
+    pub shallow_color: glam::Vec3,
+    pub deep_color: glam::Vec3,
+    /// 水深小于该值时逐渐透明，避免岸边出现生硬的交线
+    pub shoreline_fade: f32,
+    /// 最多 [`Water::MAX_WAVES`] 个
+    pub waves: Vec<GerstnerWave>,
+}
+
+impl Default for WaterSettings {
+    fn default() -> Self {
+        Self {
+            size: 40.0,
+            height: 0.0,
+            resolution: 128,
+            shallow_color: glam::vec3(0.1, 0.45, 0.5),
+            deep_color: glam::vec3(0.01, 0.08, 0.15),
+            shoreline_fade: 0.6,
+            waves: vec![
+                GerstnerWave {
+                    direction: glam::vec2(1.0, 0.3),
+                    amplitude: 0.12,
+                    wavelength: 6.0,
+                    steepness: 0.5,
+                    speed: 1.5,
+                },
+                GerstnerWave {
+                    direction: glam::vec2(-0.4, 1.0),
+                    amplitude: 0.06,
+                    wavelength: 3.1,
+                    steepness: 0.4,
+                    speed: 1.1,
+                },
+                GerstnerWave {
+                    direction: glam::vec2(0.7, -0.8),
+                    amplitude: 0.03,
+                    wavelength: 1.7,
+                    steepness: 0.3,
+                    speed: 0.8,
+                },
+            ],
+        }
+    }
+}
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+struct WaveUniform {
+    direction_steepness: [f32; 4],
+    params: [f32; 4],
+}
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+struct WaterUniform {
+    eye: [f32; 4],
+    grid: [f32; 4],
+    depth_params: [f32; 4],
+    shallow_color: [f32; 4],
+    deep_color: [f32; 4],
+    waves: [WaveUniform; Water::MAX_WAVES],
+}
+
+unsafe impl Zeroable for WaterUniform {}
+unsafe impl Pod for WaterUniform {}
+
+/// 每个顶点 32 字节：位置与法线，各为 vec4
+const VERTEX_STRIDE: wgpu::BufferAddress = 32;
+
+/// 由计算着色器驱动的 Gerstner 波水面
+///
+/// 每帧先调用 [`Water::compute`] 在 GPU 上生成顶点，再在不透明物体之后调用
+/// [`Water::draw`]。水面需要采样场景深度，因此绘制水面的 render pass
+/// 必须以只读方式绑定深度附件（`depth_ops: None`）。
+pub struct Water {
+    pub settings: WaterSettings,
+    uniform: WaterUniform,
+    uniform_buffer: Buffer,
+    vertex_buffer: Buffer,
+    index_buffer: Buffer,
+    index_count: u32,
+    compute_pipeline: wgpu::ComputePipeline,
+    compute_bind_group: BindGroup,
+    render_pipeline: wgpu::RenderPipeline,
+    uniform_bind_group: BindGroup,
+    scene_bind_group_layout: BindGroupLayout,
+    scene_bind_group: Option<BindGroup>,
+}
+
+impl Water {
+    pub const MAX_WAVES: usize = 4;
+
+    pub fn new(
+        device: &Device,
+        settings: WaterSettings,
+        color_format: wgpu::TextureFormat,
+        camera_layout: &BindGroupLayout,
+    ) -> Self {
+        assert!(settings.resolution >= 2);
+        assert!(settings.waves.len() <= Self::MAX_WAVES);
+
+        let uniform = Self::build_uniform(&settings, glam::Vec3::ZERO, 0.0, 0.1, 100.0);
+        let uniform_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Water Uniform Buffer"),
+            contents: bytemuck::cast_slice(&[uniform]),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+
+        let n = settings.resolution;
+        let vertex_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Water Vertex Buffer"),
+            size: (n * n) as wgpu::BufferAddress * VERTEX_STRIDE,
+            usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::STORAGE,
+            mapped_at_creation: false,
+        });
+        let indices = (0..n - 1)
+            .flat_map(|y| {
+                (0..n - 1).flat_map(move |x| {
+                    let i = y * n + x;
+                    [i, i + n, i + 1, i + 1, i + n, i + n + 1]
+                })
+            })
+            .collect::<Vec<u32>>();
+        let index_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Water Index Buffer"),
+            contents: bytemuck::cast_slice(&indices),
+            usage: wgpu::BufferUsages::INDEX,
+        });
+
+        let library = ShaderLibrary::new();
+
+        let compute_bind_group_layout =
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                entries: &[
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 0,
+                        visibility: wgpu::ShaderStages::COMPUTE,
+                        ty: wgpu::BindingType::Buffer {
+                            ty: wgpu::BufferBindingType::Uniform,
+                            has_dynamic_offset: false,
+                            min_binding_size: None,
+                        },
+                        count: None,
+                    },
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 1,
+                        visibility: wgpu::ShaderStages::COMPUTE,
+                        ty: wgpu::BindingType::Buffer {
+                            ty: wgpu::BufferBindingType::Storage { read_only: false },
+                            has_dynamic_offset: false,
+                            min_binding_size: None,
+                        },
+                        count: None,
+                    },
+                ],
+                label: Some("water_compute_bind_group_layout"),
+            });
+        let compute_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: &compute_bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: uniform_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: vertex_buffer.as_entire_binding(),
+                },
+            ],
+            label: Some("water_compute_bind_group"),
+        });
+        let compute_shader = library
+            .create_shader_module(
+                device,
+                "Water Compute Shader",
+                include_str!("../shaders/water_compute.wgsl"),
+            )
+            .expect("built-in water compute shader");
+        let compute_pipeline_layout =
+            device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some("Water Compute Pipeline Layout"),
+                bind_group_layouts: &[&compute_bind_group_layout],
+                push_constant_ranges: &[],
+            });
+        let compute_pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+            label: Some("Water Compute Pipeline"),
+            layout: Some(&compute_pipeline_layout),
+            module: &compute_shader,
+            entry_point: Some("cs_main"),
+            compilation_options: Default::default(),
+            cache: None,
+        });
+
+        let uniform_bind_group_layout =
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                entries: &[wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                }],
+                label: Some("water_uniform_bind_group_layout"),
+            });
+        let uniform_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: &uniform_bind_group_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: uniform_buffer.as_entire_binding(),
+            }],
+            label: Some("water_uniform_bind_group"),
+        });
+        let scene_bind_group_layout =
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                entries: &[
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 0,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Texture {
+                            multisampled: false,
+                            view_dimension: wgpu::TextureViewDimension::D2,
+                            sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                        },
+                        count: None,
+                    },
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 1,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                        count: None,
+                    },
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 2,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Texture {
+                            multisampled: false,
+                            view_dimension: wgpu::TextureViewDimension::D2,
+                            sample_type: wgpu::TextureSampleType::Depth,
+                        },
+                        count: None,
+                    },
+                ],
+                label: Some("water_scene_bind_group_layout"),
+            });
+
+        let render_shader = library
+            .create_shader_module(
+                device,
+                "Water Render Shader",
+                include_str!("../shaders/water_render.wgsl"),
+            )
+            .expect("built-in water render shader");
+        let render_pipeline_layout =
+            device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some("Water Render Pipeline Layout"),
+                bind_group_layouts: &[
+                    camera_layout,
+                    &uniform_bind_group_layout,
+                    &scene_bind_group_layout,
+                ],
+                push_constant_ranges: &[],
+            });
+        let render_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Water Render Pipeline"),
+            layout: Some(&render_pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &render_shader,
+                compilation_options: Default::default(),
+                entry_point: Some("vs_main"),
+                buffers: &[wgpu::VertexBufferLayout {
+                    array_stride: VERTEX_STRIDE,
+                    step_mode: wgpu::VertexStepMode::Vertex,
+                    attributes: &wgpu::vertex_attr_array![0 => Float32x4, 1 => Float32x4],
+                }],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &render_shader,
+                compilation_options: Default::default(),
+                entry_point: Some("fs_main"),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: color_format,
+                    blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleList,
+                // 水面从上下两侧都应可见
+                cull_mode: None,
+                ..Default::default()
+            },
+            depth_stencil: Some(wgpu::DepthStencilState {
+                format: Texture::DEPTH_FORMAT,
+                depth_write_enabled: false,
+                depth_compare: wgpu::CompareFunction::Less,
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState::default(),
+            }),
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+            cache: None,
+        });
+
+        Self {
+            settings,
+            uniform,
+            uniform_buffer,
+            vertex_buffer,
+            index_buffer,
+            index_count: indices.len() as u32,
+            compute_pipeline,
+            compute_bind_group,
+            render_pipeline,
+            uniform_bind_group,
+            scene_bind_group_layout,
+            scene_bind_group: None,
+        }
+    }
+
+    fn build_uniform(
+        settings: &WaterSettings,
+        eye: glam::Vec3,
+        time: f32,
+        znear: f32,
+        zfar: f32,
+    ) -> WaterUniform {
+        let mut waves = [WaveUniform {
+            direction_steepness: [1.0, 0.0, 0.0, 0.0],
+            params: [1.0, 0.0, 0.0, 0.0],
+        }; Self::MAX_WAVES];
+        for (dst, wave) in waves.iter_mut().zip(&settings.waves) {
+            dst.direction_steepness = [
+                wave.direction.x,
+                wave.direction.y,
+                wave.steepness,
+                wave.amplitude,
+            ];
+            dst.params = [wave.wavelength, wave.speed, 0.0, 0.0];
+        }
+
+        WaterUniform {
+            eye: eye.extend(time).to_array(),
+            grid: [
+                settings.size,
+                settings.height,
+                settings.resolution as f32,
+                settings.shoreline_fade,
+            ],
+            depth_params: [znear, zfar, settings.waves.len() as f32, 0.0],
+            shallow_color: settings.shallow_color.extend(1.0).to_array(),
+            deep_color: settings.deep_color.extend(1.0).to_array(),
+            waves,
+        }
+    }
+
+    /// 绑定反射纹理与场景深度，surface 大小变化后需要重新调用
+    pub fn set_scene_textures(&mut self, device: &Device, reflection: &Texture, depth: &Texture) {
+        self.scene_bind_group = Some(device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: &self.scene_bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(&reflection.view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(&reflection.sampler),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: wgpu::BindingResource::TextureView(&depth.view),
+                },
+            ],
+            label: Some("water_scene_bind_group"),
+        }));
+    }
+
+    pub fn update(&mut self, queue: &Queue, camera: &Camera, time: f32) {
+        self.uniform =
+            Self::build_uniform(&self.settings, camera.eye, time, camera.znear, camera.zfar);
+        queue.write_buffer(
+            &self.uniform_buffer,
+            0,
+            bytemuck::cast_slice(&[self.uniform]),
+        );
+    }
+
+    /// 在 GPU 上重新计算水面顶点
+    pub fn compute(&self, encoder: &mut wgpu::CommandEncoder) {
+        let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+            label: Some("Water Compute Pass"),
+            timestamp_writes: None,
+        });
+        pass.set_pipeline(&self.compute_pipeline);
+        pass.set_bind_group(0, &self.compute_bind_group, &[]);
+        let groups = self.settings.resolution.div_ceil(8);
+        pass.dispatch_workgroups(groups, groups, 1);
+    }
+
+    pub fn draw(&self, render_pass: &mut wgpu::RenderPass, camera_bind_group: &BindGroup) {
+        let scene_bind_group = self
+            .scene_bind_group
+            .as_ref()
+            .expect("Water::set_scene_textures must be called before drawing");
+        render_pass.set_pipeline(&self.render_pipeline);
+        render_pass.set_bind_group(0, camera_bind_group, &[]);
+        render_pass.set_bind_group(1, &self.uniform_bind_group, &[]);
+        render_pass.set_bind_group(2, scene_bind_group, &[]);
+        render_pass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
+        render_pass.set_index_buffer(self.index_buffer.slice(..), wgpu::IndexFormat::Uint32);
+        render_pass.draw_indexed(0..self.index_count, 0, 0..1);
+    }
+}
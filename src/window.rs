@@ -0,0 +1,88 @@
+use std::{cell::Cell, sync::Arc};
+
+use anyhow::Context;
+use winit::window::{CursorGrabMode, CursorIcon, Fullscreen, Window};
+
+/// 常用的窗口控制：无边框全屏、捕获与隐藏光标、光标图标
+///
+/// [`GpuContext`](crate::context::GpuContext) 持有一个，通过
+/// [`GpuContext::window`](crate::context::GpuContext::window) 访问，应用不需要自己保存 `Arc<Window>`。
+pub struct WindowControl {
+    window: Arc<Window>,
+    cursor_grabbed: Cell<bool>,
+}
+
+impl WindowControl {
+    pub fn new(window: Arc<Window>) -> Self {
+        Self {
+            window,
+            cursor_grabbed: Cell::new(false),
+        }
+    }
+
+    /// 底层的 winit 窗口，用于这里没有覆盖的操作
+    pub fn window(&self) -> &Window {
+        &self.window
+    }
+
+    pub(crate) fn shared(&self) -> Arc<Window> {
+        self.window.clone()
+    }
+
+    pub fn is_fullscreen(&self) -> bool {
+        self.window.fullscreen().is_some()
+    }
+
+    /// 在当前显示器上进入或退出无边框全屏
+    pub fn set_fullscreen(&self, fullscreen: bool) {
+        self.window
+            .set_fullscreen(fullscreen.then_some(Fullscreen::Borderless(None)));
+    }
+
+    /// 切换无边框全屏，返回切换后是否全屏
+    pub fn toggle_fullscreen(&self) -> bool {
+        let fullscreen = !self.is_fullscreen();
+        self.set_fullscreen(fullscreen);
+        fullscreen
+    }
+
+    pub fn is_cursor_grabbed(&self) -> bool {
+        self.cursor_grabbed.get()
+    }
+
+    /// 捕获并隐藏光标，或释放并显示光标
+    ///
+    /// 捕获时优先把光标锁定在原地，平台不支持时（Windows、X11）退而把光标限制在窗口内。
+    /// 捕获后用 [`WindowApp::device_input`](crate::app::WindowApp::device_input) 中的
+    /// 鼠标相对移动控制视角。
+    pub fn set_cursor_grab(&self, grab: bool) -> anyhow::Result<()> {
+        if grab {
+            self.window
+                .set_cursor_grab(CursorGrabMode::Locked)
+                .or_else(|_| self.window.set_cursor_grab(CursorGrabMode::Confined))
+                .context("failed to grab the cursor")?;
+        } else {
+            self.window
+                .set_cursor_grab(CursorGrabMode::None)
+                .context("failed to release the cursor")?;
+        }
+        self.window.set_cursor_visible(!grab);
+        self.cursor_grabbed.set(grab);
+        Ok(())
+    }
+
+    /// 切换光标捕获，返回切换后是否捕获
+    pub fn toggle_cursor_grab(&self) -> anyhow::Result<bool> {
+        let grab = !self.is_cursor_grabbed();
+        self.set_cursor_grab(grab)?;
+        Ok(grab)
+    }
+
+    pub fn set_cursor_visible(&self, visible: bool) {
+        self.window.set_cursor_visible(visible);
+    }
+
+    pub fn set_cursor_icon(&self, icon: CursorIcon) {
+        self.window.set_cursor(icon);
+    }
+}
@@ -0,0 +1,117 @@
+//! 凸包与简化碰撞网格的几何性质
+
+use glam::{vec3, Vec3};
+use wgpu_dance::{collider::CollisionMesh, scene::SceneMesh};
+
+fn triangles(mesh: &CollisionMesh) -> impl Iterator<Item = [Vec3; 3]> + '_ {
+    mesh.indices
+        .chunks_exact(3)
+        .map(|t| [0, 1, 2].map(|i| mesh.vertices[t[i] as usize]))
+}
+
+#[test]
+fn cube_hull_has_twelve_outward_triangles() {
+    let corners = (0..8)
+        .map(|i| vec3((i & 1) as f32, (i >> 1 & 1) as f32, (i >> 2 & 1) as f32) * 2.0 - 1.0)
+        .collect::<Vec<_>>();
+    // 内部的点、面上的点与重复的角点都不应出现在凸包中
+    let mut points = corners.clone();
+    for i in 0..64 {
+        let t = i as f32 / 64.0;
+        points.push(vec3(
+            t * 1.6 - 0.8,
+            (t * 7.0).sin() * 0.9,
+            (t * 5.0).cos() * 0.9,
+        ));
+    }
+    points.extend([
+        vec3(0.0, 0.0, 1.0),
+        vec3(1.0, 0.5, -0.5),
+        vec3(0.0, -1.0, 0.0),
+    ]);
+    points.extend_from_slice(&corners);
+
+    let hull = CollisionMesh::convex_hull(&points).unwrap();
+    assert!(hull.convex);
+    assert_eq!(hull.triangle_count(), 12);
+    assert_eq!(hull.vertices.len(), 8);
+    assert!(hull.vertices.iter().all(|v| corners.contains(v)));
+    assert_eq!(hull.bounds(), (Vec3::NEG_ONE, Vec3::ONE));
+
+    for [a, b, c] in triangles(&hull) {
+        let normal = (b - a).cross(c - a);
+        assert!(
+            normal.dot((a + b + c) / 3.0) > 0.0,
+            "triangle {a} {b} {c} faces inwards"
+        );
+        // 所有输入点都在每个面的内侧或面上
+        let normal = normal.normalize();
+        for p in &points {
+            assert!(normal.dot(*p - a) <= 1e-5, "{p} is outside the hull");
+        }
+    }
+}
+
+#[test]
+fn degenerate_hull_input_is_an_error() {
+    let p = vec3(1.0, 2.0, 3.0);
+    // 点太少
+    assert!(CollisionMesh::convex_hull(&[Vec3::ZERO, Vec3::X, Vec3::Y]).is_err());
+    // 全部重复
+    assert!(CollisionMesh::convex_hull(&[p; 6]).is_err());
+    // 去重后只剩三个点
+    assert!(CollisionMesh::convex_hull(&[Vec3::ZERO, Vec3::X, Vec3::Y, Vec3::X, Vec3::Y]).is_err());
+    // 共线
+    let line = (0..8).map(|i| p + Vec3::ONE * i as f32).collect::<Vec<_>>();
+    assert!(CollisionMesh::convex_hull(&line).is_err());
+    // 共面
+    let plane = (0..25)
+        .map(|i| vec3((i % 5) as f32, (i / 5) as f32, 0.0))
+        .collect::<Vec<_>>();
+    assert!(CollisionMesh::convex_hull(&plane).is_err());
+    // 非有限值
+    assert!(CollisionMesh::convex_hull(&[Vec3::ZERO, Vec3::X, Vec3::Y, Vec3::NAN]).is_err());
+}
+
+#[test]
+fn simplified_mesh_stays_within_budget() {
+    let sphere = SceneMesh::sphere(1.0, 48, 24);
+    let original = sphere.indices.len() / 3;
+    let (min, max) = (Vec3::splat(-1.0 - 1e-4), Vec3::splat(1.0 + 1e-4));
+
+    for budget in [original / 2, 200, 40] {
+        let mesh = CollisionMesh::simplified(&sphere.positions, &sphere.indices, budget).unwrap();
+        assert!(!mesh.convex);
+        assert!(
+            mesh.triangle_count() > 0 && mesh.triangle_count() <= budget,
+            "{} triangles for a budget of {budget}",
+            mesh.triangle_count()
+        );
+        assert!(mesh
+            .indices
+            .iter()
+            .all(|&i| (i as usize) < mesh.vertices.len()));
+        assert!(mesh
+            .vertices
+            .iter()
+            .all(|v| v.cmpge(min).all() && v.cmple(max).all()));
+        // 没有退化的三角形
+        assert!(triangles(&mesh).all(|[a, b, c]| a != b && b != c && c != a));
+    }
+
+    // 预算过小时报错，而不是返回超出预算的网格
+    for budget in 0..8 {
+        if let Ok(mesh) = CollisionMesh::simplified(&sphere.positions, &sphere.indices, budget) {
+            assert!(mesh.triangle_count() <= budget);
+        }
+    }
+
+    // 本来就满足预算时原样返回
+    let unchanged =
+        CollisionMesh::simplified(&sphere.positions, &sphere.indices, original).unwrap();
+    assert_eq!(unchanged.vertices, sphere.positions);
+    assert_eq!(unchanged.indices, sphere.indices);
+
+    assert!(CollisionMesh::simplified(&sphere.positions, &sphere.indices[..4], 1).is_err());
+    assert!(CollisionMesh::simplified(&sphere.positions[..3], &sphere.indices, 1).is_err());
+}
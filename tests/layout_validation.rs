@@ -0,0 +1,282 @@
+//! 用 naga 解析 crate 与示例中的 WGSL，对照 Rust 端的 uniform 结构体与 bind group layout
+
+use wgpu::{BindGroupLayoutEntry, ShaderStages};
+use wgpu_dance::{
+    animation::BakedClipsUniform,
+    background::GradientUniform,
+    camera::{CameraBundle, CameraUniform},
+    environment::{EnvironmentBundle, EnvironmentUniform},
+    light::{DirectionalLightBundle, DirectionalLightUniform, PointLightUniform},
+    loading::LoadingUniform,
+    model::TextureTransformUniform,
+    scene::raster::{SceneMaterialUniform, SceneUniform},
+    shader::{alpha_test_constants, shadow_pcf_constants, ShaderLibrary},
+    shader_toy::{shader_toy_source, ShaderToyUniform},
+    splat::SplatUniform,
+    texture::Texture,
+    validation::{check_bind_group, check_uniform, find_binding, parse_wgsl},
+};
+
+fn parse(source: &str) -> naga::Module {
+    parse_wgsl(&ShaderLibrary::new(), source).unwrap_or_else(|e| panic!("{e:#}"))
+}
+
+/// crate 自带的完整着色器（不含只用于 `#include` 的片段）
+const CRATE_SHADERS: &[(&str, &str)] = &[
+    (
+        "aerial_perspective",
+        include_str!("../shaders/aerial_perspective.wgsl"),
+    ),
+    ("background", include_str!("../shaders/background.wgsl")),
+    ("blit", include_str!("../shaders/blit.wgsl")),
+    ("bloom", include_str!("../shaders/bloom.wgsl")),
+    ("compare", include_str!("../shaders/compare.wgsl")),
+    ("debug_view", include_str!("../shaders/debug_view.wgsl")),
+    (
+        "dispatch_args",
+        include_str!("../shaders/dispatch_args.wgsl"),
+    ),
+    ("exposure", include_str!("../shaders/exposure.wgsl")),
+    ("lbvh", include_str!("../shaders/lbvh.wgsl")),
+    ("lens", include_str!("../shaders/lens.wgsl")),
+    ("loading", include_str!("../shaders/loading.wgsl")),
+    ("lod_cull", include_str!("../shaders/lod_cull.wgsl")),
+    ("meshlet_cull", include_str!("../shaders/meshlet_cull.wgsl")),
+    ("motion_blur", include_str!("../shaders/motion_blur.wgsl")),
+    ("overlay", include_str!("../shaders/overlay.wgsl")),
+    ("particles", include_str!("../shaders/particles.wgsl")),
+    (
+        "particles_simulate",
+        include_str!("../shaders/particles_simulate.wgsl"),
+    ),
+    ("polyline", include_str!("../shaders/polyline.wgsl")),
+    ("radix_sort", include_str!("../shaders/radix_sort.wgsl")),
+    ("scatter_cull", include_str!("../shaders/scatter_cull.wgsl")),
+    ("scene", include_str!("../shaders/scene.wgsl")),
+    ("sky_pass", include_str!("../shaders/sky_pass.wgsl")),
+    ("splat", include_str!("../shaders/splat.wgsl")),
+    ("splat_sort", include_str!("../shaders/splat_sort.wgsl")),
+    ("sprite", include_str!("../shaders/sprite.wgsl")),
+    ("ssr", include_str!("../shaders/ssr.wgsl")),
+    ("taa", include_str!("../shaders/taa.wgsl")),
+    ("thumbnail", include_str!("../shaders/thumbnail.wgsl")),
+    ("upscale", include_str!("../shaders/upscale.wgsl")),
+    ("tonemap_pass", include_str!("../shaders/tonemap_pass.wgsl")),
+    (
+        "water_compute",
+        include_str!("../shaders/water_compute.wgsl"),
+    ),
+    ("water_render", include_str!("../shaders/water_render.wgsl")),
+];
+
+#[test]
+fn crate_shaders_are_valid() {
+    for (name, source) in CRATE_SHADERS {
+        if let Err(e) = parse_wgsl(&ShaderLibrary::new(), source) {
+            panic!("shader `{name}` failed validation: {e:#}");
+        }
+    }
+}
+
+#[test]
+fn uniform_structs_match_shader_chunks() {
+    let camera = parse(r#"#include "wgpu_dance/camera.wgsl""#);
+    check_uniform::<CameraUniform>(&camera, "CameraUniform").unwrap();
+
+    let lighting = parse(r#"#include "wgpu_dance/lighting.wgsl""#);
+    check_uniform::<DirectionalLightUniform>(&lighting, "DirectionalLight").unwrap();
+    check_uniform::<PointLightUniform>(&lighting, "PointLight").unwrap();
+
+    let environment = parse(r#"#include "wgpu_dance/environment.wgsl""#);
+    check_uniform::<EnvironmentUniform>(&environment, "EnvironmentUniform").unwrap();
+
+    let texture_transform = parse(r#"#include "wgpu_dance/texture_transform.wgsl""#);
+    check_uniform::<TextureTransformUniform>(&texture_transform, "TextureTransform").unwrap();
+
+    let baked_animation = parse(r#"#include "wgpu_dance/baked_animation.wgsl""#);
+    check_uniform::<BakedClipsUniform>(&baked_animation, "BakedClips").unwrap();
+
+    let splat = parse(include_str!("../shaders/splat.wgsl"));
+    check_uniform::<SplatUniform>(&splat, "SplatUniform").unwrap();
+    let splat_sort = parse(include_str!("../shaders/splat_sort.wgsl"));
+    check_uniform::<SplatUniform>(&splat_sort, "SplatUniform").unwrap();
+
+    let loading = parse(include_str!("../shaders/loading.wgsl"));
+    check_uniform::<LoadingUniform>(&loading, "LoadingUniform").unwrap();
+
+    let shader_toy = parse(&shader_toy_source(include_str!(
+        "../examples/shader_toy/plasma.wgsl"
+    )));
+    check_uniform::<ShaderToyUniform>(&shader_toy, "ShaderToyUniform").unwrap();
+
+    let background = parse(include_str!("../shaders/background.wgsl"));
+    check_uniform::<GradientUniform>(&background, "GradientUniform").unwrap();
+
+    let scene = parse(include_str!("../shaders/scene.wgsl"));
+    check_uniform::<SceneUniform>(&scene, "SceneUniform").unwrap();
+    check_uniform::<SceneMaterialUniform>(&scene, "SceneMaterial").unwrap();
+}
+
+#[test]
+fn pipeline_constants_match_shader_overrides() {
+    let overrides = |module: &naga::Module| {
+        module
+            .overrides
+            .iter()
+            .filter_map(|(_, o)| o.name.clone())
+            .collect::<Vec<_>>()
+    };
+    let alpha_test = parse(r#"#include "wgpu_dance/alpha_test.wgsl""#);
+    for key in alpha_test_constants(0.5).keys() {
+        assert!(overrides(&alpha_test).contains(key), "alpha test `{key}`");
+    }
+    let shadows = parse(r#"#include "wgpu_dance/shadows.wgsl""#);
+    for key in shadow_pcf_constants(1.0).keys() {
+        assert!(overrides(&shadows).contains(key), "shadows `{key}`");
+    }
+}
+
+#[test]
+fn crate_renderers_bind_camera_and_environment_where_expected() {
+    let camera = [CameraBundle::layout_entry(ShaderStages::VERTEX)];
+    for (name, source) in CRATE_SHADERS {
+        let module = parse(source);
+        if let Some(binding) = find_binding(&module, "CameraUniform") {
+            // 所有接收 `camera_layout` 的渲染器都把相机放在 group 0
+            assert_eq!(binding.group, 0, "camera group in `{name}`");
+            check_bind_group(&module, 0, &camera).unwrap();
+        }
+        if let Some(binding) = find_binding(&module, "EnvironmentUniform") {
+            check_bind_group(&module, binding.group, &EnvironmentBundle::layout_entries())
+                .unwrap_or_else(|e| panic!("`{name}`: {e:#}"));
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone)]
+enum Layout {
+    Camera,
+    Texture,
+    Light,
+}
+
+impl Layout {
+    fn entries(self) -> Vec<BindGroupLayoutEntry> {
+        match self {
+            Layout::Camera => vec![CameraBundle::layout_entry(ShaderStages::VERTEX)],
+            Layout::Texture => Texture::texture_layout_entries().to_vec(),
+            Layout::Light => vec![DirectionalLightBundle::layout_entry()],
+        }
+    }
+}
+
+/// 示例着色器及其管线中 `bind_group_layouts` 的顺序，需要和 `examples/*/main.rs` 保持一致
+const EXAMPLE_PIPELINES: &[(&str, &str, &[Layout])] = &[
+    (
+        "auto_exposure",
+        include_str!("../examples/auto_exposure/shader.wgsl"),
+        &[Layout::Camera, Layout::Texture, Layout::Light],
+    ),
+    (
+        "camera",
+        include_str!("../examples/camera/shader.wgsl"),
+        &[Layout::Texture, Layout::Camera],
+    ),
+    (
+        "instance",
+        include_str!("../examples/instance/shader.wgsl"),
+        &[Layout::Texture, Layout::Camera],
+    ),
+    (
+        "load_model",
+        include_str!("../examples/load_model/shader.wgsl"),
+        &[Layout::Camera, Layout::Texture],
+    ),
+    (
+        "motion_blur",
+        include_str!("../examples/motion_blur/shader.wgsl"),
+        &[Layout::Camera, Layout::Texture, Layout::Light],
+    ),
+    (
+        "motion_blur floor",
+        include_str!("../examples/motion_blur/floor.wgsl"),
+        &[Layout::Camera, Layout::Light],
+    ),
+    (
+        "particles",
+        include_str!("../examples/particles/shader.wgsl"),
+        &[Layout::Camera, Layout::Texture, Layout::Light],
+    ),
+    (
+        "reflection",
+        include_str!("../examples/reflection/shader.wgsl"),
+        &[Layout::Camera, Layout::Texture],
+    ),
+    (
+        "render_settings",
+        include_str!("../examples/render_settings/shader.wgsl"),
+        &[Layout::Camera, Layout::Texture],
+    ),
+    (
+        "scatter",
+        include_str!("../examples/scatter/shader.wgsl"),
+        &[Layout::Camera, Layout::Texture, Layout::Light],
+    ),
+    (
+        "scatter terrain",
+        include_str!("../examples/scatter/terrain.wgsl"),
+        &[Layout::Camera, Layout::Light],
+    ),
+    (
+        "sky",
+        include_str!("../examples/sky/shader.wgsl"),
+        &[Layout::Camera, Layout::Texture, Layout::Light],
+    ),
+    (
+        "spline",
+        include_str!("../examples/spline/shader.wgsl"),
+        &[Layout::Camera, Layout::Light],
+    ),
+    (
+        "splat",
+        include_str!("../examples/splat/shader.wgsl"),
+        &[Layout::Camera, Layout::Texture, Layout::Light],
+    ),
+    (
+        "ssr",
+        include_str!("../examples/ssr/shader.wgsl"),
+        &[Layout::Camera, Layout::Texture, Layout::Light],
+    ),
+    (
+        "ssr floor",
+        include_str!("../examples/ssr/floor.wgsl"),
+        &[Layout::Camera, Layout::Light],
+    ),
+    (
+        "water",
+        include_str!("../examples/water/shader.wgsl"),
+        &[Layout::Camera, Layout::Texture],
+    ),
+];
+
+#[test]
+fn example_shaders_match_pipeline_layouts() {
+    for (name, source, layouts) in EXAMPLE_PIPELINES {
+        let module = parse(source);
+        for (group, layout) in layouts.iter().enumerate() {
+            check_bind_group(&module, group as u32, &layout.entries()).unwrap_or_else(|e| {
+                panic!("example `{name}` group {group} should be {layout:?}: {e:#}")
+            });
+        }
+        let used_groups = module
+            .global_variables
+            .iter()
+            .filter_map(|(_, global)| global.binding.as_ref())
+            .map(|binding| binding.group as usize)
+            .max();
+        assert!(
+            used_groups.is_none_or(|group| group < layouts.len()),
+            "example `{name}` uses a bind group that its pipeline layout does not provide"
+        );
+    }
+}
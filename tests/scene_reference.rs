@@ -0,0 +1,81 @@
+//! 以 CPU 光线追踪为基准检查光栅化渲染器，没有可用的 adapter 时跳过
+
+use glam::{vec3, Mat4, Quat, Vec3};
+use wgpu_dance::{
+    ao::AoBaker,
+    camera::{Camera, Projection},
+    compute::{ComputeContext, ComputeContextOptions},
+    light::{DirectionalLight, PointLight},
+    scene::{
+        raster::render_image, raytrace::RayTracer, ImageDifference, Scene, SceneLight,
+        SceneMaterial, SceneMesh,
+    },
+    texture::DepthConvention,
+};
+
+fn reference_scene() -> Scene {
+    let mut scene = Scene::new(Camera {
+        eye: vec3(0.0, 3.0, 7.0),
+        target: vec3(0.0, 0.8, 0.0),
+        up: Vec3::Y,
+        aspect: 1.0,
+        projection: Projection::Perspective { fovy: 45.0 },
+        znear: 0.1,
+        zfar: 50.0,
+        depth: DepthConvention::Standard,
+    });
+    let floor = scene.add_material(SceneMaterial::default());
+    let glossy = scene.add_material(SceneMaterial {
+        base_color: vec3(0.8, 0.2, 0.1),
+        specular: 0.6,
+        shininess: 64.0,
+    });
+    scene.meshes = vec![
+        SceneMesh::plane(5.0).with_material(floor),
+        SceneMesh::sphere(1.0, 32, 16)
+            .with_transform(Mat4::from_translation(vec3(-1.2, 1.0, 0.0)))
+            .with_material(glossy),
+        SceneMesh::cuboid(Vec3::splat(0.6))
+            .with_transform(Mat4::from_rotation_translation(
+                Quat::from_rotation_y(0.5),
+                vec3(1.4, 0.6, 0.0),
+            ))
+            .with_material(glossy),
+    ];
+    scene.lights = vec![
+        SceneLight::Directional(DirectionalLight::default()),
+        SceneLight::Point(PointLight {
+            position: vec3(2.0, 2.5, 2.0),
+            intensity: 5.0,
+            ..Default::default()
+        }),
+    ];
+    scene
+}
+
+#[test]
+fn raster_matches_ray_traced_reference() {
+    let Ok(gpu) =
+        futures::executor::block_on(ComputeContext::new(ComputeContextOptions::default()))
+    else {
+        eprintln!("no adapter available, skipping");
+        return;
+    };
+    let mut scene = reference_scene();
+    // 烘焙的环境光遮蔽在两条路径中都按顶点插值，结果仍应一致
+    scene.bake_ao(&AoBaker::default().with_samples(16)).unwrap();
+    for projection in [
+        Projection::Perspective { fovy: 45.0 },
+        Projection::Orthographic { half_height: 3.0 },
+    ] {
+        scene.camera.projection = projection;
+        let rasterized = render_image(&gpu.device, &gpu.queue, &scene, 96, 64).unwrap();
+        let reference = RayTracer::default().render(&scene, 96, 64).unwrap();
+        let diff = ImageDifference::new(&rasterized, &reference, 8).unwrap();
+        // 着色相同，差异只应来自三角形边缘的覆盖规则
+        assert!(
+            diff.rmse < 2.0 && diff.mismatched < 0.01,
+            "{projection:?}: {diff:?}"
+        );
+    }
+}
@@ -0,0 +1,113 @@
+use proc_macro::TokenStream;
+use quote::quote;
+use syn::{parse_macro_input, Data, DeriveInput, Fields};
+
+/// 为 uniform 缓冲结构体生成 std140 布局检查、`bytemuck::Pod` 与 `GpuUniform` 实现
+///
+/// 要求结构体为 `#[repr(C)]`、字段均实现 `wgpu_dance::uniform::Std140`。编译期检查：
+/// 每个字段的偏移满足 std140 对齐；结构体中没有隐式填充；总大小是 16 的倍数。
+#[proc_macro_derive(GpuUniform)]
+pub fn derive_gpu_uniform(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+fn expand(input: DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let name = &input.ident;
+    if !input.generics.params.is_empty() {
+        return Err(syn::Error::new_spanned(
+            &input.generics,
+            "GpuUniform does not support generic structs",
+        ));
+    }
+    if !has_repr_c(&input) {
+        return Err(syn::Error::new_spanned(
+            name,
+            "GpuUniform requires #[repr(C)]",
+        ));
+    }
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => {
+                return Err(syn::Error::new_spanned(
+                    name,
+                    "GpuUniform requires a struct with named fields",
+                ))
+            }
+        },
+        _ => {
+            return Err(syn::Error::new_spanned(
+                name,
+                "GpuUniform can only be derived for structs",
+            ))
+        }
+    };
+
+    let krate = quote!(::wgpu_dance::uniform);
+    let align_checks = fields.iter().map(|field| {
+        let ident = field.ident.as_ref().unwrap();
+        let ty = &field.ty;
+        let message = format!("field `{ident}` of `{name}` is not aligned for std140");
+        quote! {
+            assert!(
+                ::core::mem::offset_of!(#name, #ident).is_multiple_of(<#ty as #krate::Std140>::ALIGN),
+                #message
+            );
+        }
+    });
+    let field_offsets = fields.iter().map(|field| {
+        let ident = field.ident.as_ref().unwrap();
+        let field_name = ident.to_string();
+        quote!((#field_name, ::core::mem::offset_of!(#name, #ident)))
+    });
+    let field_types = fields.iter().map(|field| &field.ty);
+    let padding_message =
+        format!("`{name}` has implicit padding, add explicit padding fields instead");
+    let size_message = format!("size of `{name}` must be a multiple of 16 for std140");
+
+    Ok(quote! {
+        const _: () = {
+            #(#align_checks)*
+            assert!(
+                ::core::mem::size_of::<#name>() == 0 #(+ ::core::mem::size_of::<#field_types>())*,
+                #padding_message
+            );
+            assert!(
+                ::core::mem::size_of::<#name>().is_multiple_of(16),
+                #size_message
+            );
+        };
+
+        // 上面的检查保证了没有填充字节，且所有字段都是 Pod
+        unsafe impl #krate::bytemuck::Zeroable for #name {}
+        unsafe impl #krate::bytemuck::Pod for #name {}
+
+        unsafe impl #krate::Std140 for #name {
+            const ALIGN: usize = 16;
+        }
+        unsafe impl #krate::Std140Array for #name {}
+
+        impl #krate::GpuUniform for #name {
+            const FIELDS: &'static [(&'static str, usize)] = &[#(#field_offsets),*];
+        }
+    })
+}
+
+fn has_repr_c(input: &DeriveInput) -> bool {
+    input.attrs.iter().any(|attr| {
+        if !attr.path().is_ident("repr") {
+            return false;
+        }
+        let mut repr_c = false;
+        let _ = attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("C") {
+                repr_c = true;
+            }
+            Ok(())
+        });
+        repr_c
+    })
+}